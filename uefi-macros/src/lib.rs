@@ -219,3 +219,107 @@ pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
     };
     result.into()
 }
+
+/// Custom attribute for a UEFI driver unload handler.
+///
+/// This attribute modifies a function so that it can be registered as the
+/// unload handler of a driver with [`boot::set_unload_handler`]. The
+/// function:
+/// * Must return [`Status`].
+/// * Must have zero parameters.
+///
+/// The handler is called when the driver is unloaded with
+/// [`boot::unload_image`], and must free all resources of the driver. If it
+/// returns an error, the driver is not unloaded.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use uefi::prelude::*;
+/// use uefi::unload;
+///
+/// #[unload]
+/// fn unload() -> Status {
+///     Status::SUCCESS
+/// }
+///
+/// #[entry]
+/// fn main() -> Status {
+///     boot::set_unload_handler(unload).status()
+/// }
+/// ```
+///
+/// [`Status`]: https://docs.rs/uefi/latest/uefi/struct.Status.html
+/// [`boot::set_unload_handler`]: https://docs.rs/uefi/latest/uefi/boot/fn.set_unload_handler.html
+/// [`boot::unload_image`]: https://docs.rs/uefi/latest/uefi/boot/fn.unload_image.html
+#[proc_macro_attribute]
+pub fn unload(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut errors = TokenStream2::new();
+
+    if !args.is_empty() {
+        errors.append_all(err!(
+            TokenStream2::from(args),
+            "Unload attribute accepts no arguments"
+        ));
+    }
+
+    let mut f = parse_macro_input!(input as ItemFn);
+
+    if let Some(ref abi) = f.sig.abi {
+        errors.append_all(err!(abi, "Unload function must have no ABI modifier"));
+    }
+    if let Some(asyncness) = f.sig.asyncness {
+        errors.append_all(err!(asyncness, "Unload function should not be async"));
+    }
+    if let Some(constness) = f.sig.constness {
+        errors.append_all(err!(constness, "Unload function should not be const"));
+    }
+    if let Some(unsafety) = f.sig.unsafety {
+        errors.append_all(err!(unsafety, "Unload function should not be unsafe"));
+    }
+    if !f.sig.generics.params.is_empty() {
+        errors.append_all(err!(
+            f.sig.generics.params,
+            "Unload function should not be generic"
+        ));
+    }
+    if !f.sig.inputs.is_empty() {
+        errors.append_all(err!(f.sig.inputs, "Unload function must have no arguments"));
+    }
+
+    // Show most errors all at once instead of one by one.
+    if !errors.is_empty() {
+        return errors.into();
+    }
+
+    let signature_span = f.sig.span();
+
+    // The image handle is always that of the current image, which is already
+    // available through `boot::image_handle`.
+    f.sig.inputs = parse_quote_spanned!(
+        signature_span=>
+            _internal_image_handle: ::uefi::Handle,
+    );
+
+    // Set the required ABI.
+    f.sig.abi = Some(parse_quote_spanned!(signature_span=> extern "efiapi"));
+
+    let fn_ident = &f.sig.ident;
+    let fn_output = &f.sig.output;
+
+    // See the type check in `entry` for how this works.
+    let fn_type_check = quote_spanned! {signature_span=>
+        const _:
+            extern "efiapi" fn(::uefi::Handle) -> ::uefi::Status =
+            #fn_ident as extern "efiapi" fn(::uefi::Handle) #fn_output;
+    };
+
+    let result = quote! {
+        #fn_type_check
+
+        #f
+    };
+    result.into()
+}
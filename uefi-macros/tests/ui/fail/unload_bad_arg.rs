@@ -0,0 +1,8 @@
+#![no_main]
+
+use uefi::unload;
+
+#[unload]
+fn unload(_x: usize) -> uefi::Status {
+    uefi::Status::SUCCESS
+}
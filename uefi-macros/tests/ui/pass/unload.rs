@@ -0,0 +1,17 @@
+use uefi::{boot, entry, unload, Status};
+
+#[unload]
+fn unload() -> Status {
+    Status::SUCCESS
+}
+
+#[entry]
+fn efi_main() -> Status {
+    match boot::set_unload_handler(unload) {
+        Ok(()) => Status::SUCCESS,
+        Err(err) => err.status(),
+    }
+}
+
+// trybuild requires a `main` function.
+fn main() {}
@@ -33,6 +33,7 @@ mod status;
 
 use core::ffi::c_void;
 use core::fmt::{self, Debug, Formatter};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 pub use status::Status;
 pub use uguid::{guid, Guid};
 
@@ -70,11 +71,35 @@ pub type VirtualAddress = u64;
 #[repr(transparent)]
 pub struct Ipv4Address(pub [u8; 4]);
 
+impl From<Ipv4Addr> for Ipv4Address {
+    fn from(ip: Ipv4Addr) -> Self {
+        Self(ip.octets())
+    }
+}
+
+impl From<Ipv4Address> for Ipv4Addr {
+    fn from(ip: Ipv4Address) -> Self {
+        Self::from(ip.0)
+    }
+}
+
 /// An IPv6 internet protocol address.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
 pub struct Ipv6Address(pub [u8; 16]);
 
+impl From<Ipv6Addr> for Ipv6Address {
+    fn from(ip: Ipv6Addr) -> Self {
+        Self(ip.octets())
+    }
+}
+
+impl From<Ipv6Address> for Ipv6Addr {
+    fn from(ip: Ipv6Address) -> Self {
+        Self::from(ip.0)
+    }
+}
+
 /// An IPv4 or IPv6 internet protocol address.
 ///
 /// Corresponds to the `EFI_IP_ADDRESS` type in the UEFI specification. This
@@ -112,6 +137,44 @@ impl IpAddress {
             v6: Ipv6Address(ip_addr),
         }
     }
+
+    /// Convert to a [`core::net::IpAddr`], interpreting the contents as an
+    /// IPv6 address if `is_ipv6` is true and as an IPv4 address otherwise.
+    ///
+    /// # Safety
+    ///
+    /// If `is_ipv6` is true, all 16 bytes of the address must be
+    /// initialized. This is not the case for addresses created with
+    /// [`IpAddress::new_v4`].
+    #[must_use]
+    pub unsafe fn to_ip_addr(&self, is_ipv6: bool) -> IpAddr {
+        if is_ipv6 {
+            IpAddr::V6(unsafe { self.v6 }.into())
+        } else {
+            IpAddr::V4(unsafe { self.v4 }.into())
+        }
+    }
+}
+
+impl From<Ipv4Addr> for IpAddress {
+    fn from(ip: Ipv4Addr) -> Self {
+        Self::new_v4(ip.octets())
+    }
+}
+
+impl From<Ipv6Addr> for IpAddress {
+    fn from(ip: Ipv6Addr) -> Self {
+        Self::new_v6(ip.octets())
+    }
+}
+
+impl From<IpAddr> for IpAddress {
+    fn from(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(ip) => ip.into(),
+            IpAddr::V6(ip) => ip.into(),
+        }
+    }
 }
 
 impl Debug for IpAddress {
@@ -133,3 +196,23 @@ impl Default for IpAddress {
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
 pub struct MacAddress(pub [u8; 32]);
+
+impl From<[u8; 6]> for MacAddress {
+    /// Create a MAC address from an Ethernet address, zero-filling the
+    /// remaining bytes.
+    fn from(addr: [u8; 6]) -> Self {
+        let mut mac = [0; 32];
+        mac[..6].copy_from_slice(&addr);
+        Self(mac)
+    }
+}
+
+impl From<MacAddress> for [u8; 6] {
+    /// Get the Ethernet address stored in the first six bytes of a MAC
+    /// address.
+    fn from(mac: MacAddress) -> Self {
+        let mut addr = [0; 6];
+        addr.copy_from_slice(&mac.0[..6]);
+        addr
+    }
+}
@@ -0,0 +1,143 @@
+use super::device_path::DevicePathProtocol;
+use crate::{guid, Event, Guid, Status};
+use bitflags::bitflags;
+use core::ffi::c_void;
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct AtaPassThruAttributes: u32 {
+        const PHYSICAL = 0x0001;
+        const LOGICAL = 0x0002;
+        const NONBLOCKIO = 0x0004;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct AtaPassThruMode {
+    pub attributes: AtaPassThruAttributes,
+    pub io_align: u32,
+}
+
+newtype_enum! {
+    pub enum AtaPassThruCommandProtocol: u8 => {
+        ATA_HARDWARE_RESET = 0x00,
+        ATA_SOFTWARE_RESET = 0x01,
+        ATA_NON_DATA = 0x02,
+        PIO_DATA_IN = 0x04,
+        PIO_DATA_OUT = 0x05,
+        DMA = 0x06,
+        DMA_QUEUED = 0x07,
+        DEVICE_DIAGNOSTIC = 0x08,
+        DEVICE_RESET = 0x09,
+        UDMA_DATA_IN = 0x0a,
+        UDMA_DATA_OUT = 0x0b,
+        FPDMA = 0x0c,
+        RETURN_RESPONSE = 0xff,
+    }
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct AtaPassThruLength: u8 {
+        const BYTES = 0x80;
+        const MASK = 0x70;
+        const NO_DATA_TRANSFER = 0x00;
+        const FEATURES = 0x10;
+        const SECTOR_COUNT = 0x20;
+        const TPSIU = 0x30;
+        const COUNT = 0x0f;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct AtaCommandBlock {
+    pub reserved1: [u8; 2],
+    pub ata_command: u8,
+    pub ata_features: u8,
+    pub ata_sector_number: u8,
+    pub ata_cylinder_low: u8,
+    pub ata_cylinder_high: u8,
+    pub ata_device_head: u8,
+    pub ata_sector_number_exp: u8,
+    pub ata_cylinder_low_exp: u8,
+    pub ata_cylinder_high_exp: u8,
+    pub ata_features_exp: u8,
+    pub ata_sector_count: u8,
+    pub ata_sector_count_exp: u8,
+    pub reserved2: [u8; 6],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct AtaStatusBlock {
+    pub reserved1: [u8; 2],
+    pub ata_status: u8,
+    pub ata_error: u8,
+    pub ata_sector_number: u8,
+    pub ata_cylinder_low: u8,
+    pub ata_cylinder_high: u8,
+    pub ata_device_head: u8,
+    pub ata_sector_number_exp: u8,
+    pub ata_cylinder_low_exp: u8,
+    pub ata_cylinder_high_exp: u8,
+    pub reserved2: u8,
+    pub ata_sector_count: u8,
+    pub ata_sector_count_exp: u8,
+    pub reserved3: [u8; 6],
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct AtaPassThruCommandPacket {
+    pub asb: *mut AtaStatusBlock,
+    pub acb: *const AtaCommandBlock,
+    pub timeout: u64,
+    pub in_data_buffer: *mut c_void,
+    pub out_data_buffer: *const c_void,
+    pub in_transfer_length: u32,
+    pub out_transfer_length: u32,
+    pub protocol: AtaPassThruCommandProtocol,
+    pub length: AtaPassThruLength,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct AtaPassThruProtocol {
+    pub mode: *const AtaPassThruMode,
+    pub pass_thru: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        port: u16,
+        port_multiplier_port: u16,
+        packet: *mut AtaPassThruCommandPacket,
+        event: Event,
+    ) -> Status,
+    pub get_next_port: unsafe extern "efiapi" fn(this: *const Self, port: *mut u16) -> Status,
+    pub get_next_device: unsafe extern "efiapi" fn(
+        this: *const Self,
+        port: u16,
+        port_multiplier_port: *mut u16,
+    ) -> Status,
+    pub build_device_path: unsafe extern "efiapi" fn(
+        this: *const Self,
+        port: u16,
+        port_multiplier_port: u16,
+        device_path: *mut *mut DevicePathProtocol,
+    ) -> Status,
+    pub get_device: unsafe extern "efiapi" fn(
+        this: *const Self,
+        device_path: *const DevicePathProtocol,
+        port: *mut u16,
+        port_multiplier_port: *mut u16,
+    ) -> Status,
+    pub reset_port: unsafe extern "efiapi" fn(this: *mut Self, port: u16) -> Status,
+    pub reset_device:
+        unsafe extern "efiapi" fn(this: *mut Self, port: u16, port_multiplier_port: u16) -> Status,
+}
+
+impl AtaPassThruProtocol {
+    pub const GUID: Guid = guid!("1d3de7f0-0807-424f-aa69-11a54e19a46f");
+}
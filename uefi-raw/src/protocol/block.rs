@@ -1,4 +1,4 @@
-use crate::{guid, Guid, Status};
+use crate::{guid, Event, Guid, Status};
 use core::ffi::c_void;
 
 /// Logical block address.
@@ -52,3 +52,65 @@ pub struct BlockIoProtocol {
 impl BlockIoProtocol {
     pub const GUID: Guid = guid!("964e5b21-6459-11d2-8e39-00a0c969723b");
 }
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct EraseBlockToken {
+    pub event: Event,
+    pub transaction_status: Status,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct EraseBlockProtocol {
+    pub revision: u64,
+    pub erase_length_granularity: u32,
+    pub erase_blocks: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        media_id: u32,
+        lba: Lba,
+        token: *mut EraseBlockToken,
+        size: usize,
+    ) -> Status,
+}
+
+impl EraseBlockProtocol {
+    pub const GUID: Guid = guid!("95a9a93e-a86e-4926-aaef-9918e772d987");
+    pub const REVISION: u64 = (2 << 16) | 60;
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct BlockIo2Token {
+    pub event: Event,
+    pub transaction_status: Status,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct BlockIo2Protocol {
+    pub media: *const BlockIoMedia,
+    pub reset: unsafe extern "efiapi" fn(this: *mut Self, extended_verification: bool) -> Status,
+    pub read_blocks_ex: unsafe extern "efiapi" fn(
+        this: *const Self,
+        media_id: u32,
+        lba: Lba,
+        token: *mut BlockIo2Token,
+        buffer_size: usize,
+        buffer: *mut c_void,
+    ) -> Status,
+    pub write_blocks_ex: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        media_id: u32,
+        lba: Lba,
+        token: *mut BlockIo2Token,
+        buffer_size: usize,
+        buffer: *const c_void,
+    ) -> Status,
+    pub flush_blocks_ex:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut BlockIo2Token) -> Status,
+}
+
+impl BlockIo2Protocol {
+    pub const GUID: Guid = guid!("a77b2472-e282-4e9f-a245-c2c0e27bbcc1");
+}
@@ -1,7 +1,8 @@
 pub mod serial;
 
-use crate::{guid, Char16, Event, Guid, PhysicalAddress, Status};
+use crate::{guid, Char16, Event, Guid, Handle, PhysicalAddress, Status};
 use bitflags::bitflags;
+use core::ffi::c_void;
 use core::ptr;
 
 bitflags! {
@@ -72,6 +73,82 @@ impl SimpleTextInputProtocol {
     pub const GUID: Guid = guid!("387477c1-69c7-11d2-8e39-00a0c969723b");
 }
 
+bitflags! {
+    /// State of the shift, control, alt and logo modifier keys.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct KeyShiftState: u32 {
+        /// The other bits are valid.
+        const VALID = 0x8000_0000;
+        const RIGHT_SHIFT_PRESSED = 0x0000_0001;
+        const LEFT_SHIFT_PRESSED = 0x0000_0002;
+        const RIGHT_CONTROL_PRESSED = 0x0000_0004;
+        const LEFT_CONTROL_PRESSED = 0x0000_0008;
+        const RIGHT_ALT_PRESSED = 0x0000_0010;
+        const LEFT_ALT_PRESSED = 0x0000_0020;
+        const RIGHT_LOGO_PRESSED = 0x0000_0040;
+        const LEFT_LOGO_PRESSED = 0x0000_0080;
+        const MENU_KEY_PRESSED = 0x0000_0100;
+        const SYS_REQ_PRESSED = 0x0000_0200;
+    }
+}
+
+bitflags! {
+    /// State of the lock keys.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct KeyToggleState: u8 {
+        /// The other bits are valid.
+        const VALID = 0x80;
+        /// Partial keystrokes, where only a modifier key changed, are reported.
+        const KEY_STATE_EXPOSED = 0x40;
+        const SCROLL_LOCK_ACTIVE = 0x01;
+        const NUM_LOCK_ACTIVE = 0x02;
+        const CAPS_LOCK_ACTIVE = 0x04;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct KeyState {
+    pub key_shift_state: KeyShiftState,
+    pub key_toggle_state: KeyToggleState,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct KeyData {
+    pub key: InputKey,
+    pub key_state: KeyState,
+}
+
+pub type KeyNotifyFunction = unsafe extern "efiapi" fn(key_data: *mut KeyData) -> Status;
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct SimpleTextInputExProtocol {
+    pub reset: unsafe extern "efiapi" fn(this: *mut Self, extended_verification: bool) -> Status,
+    pub read_key_stroke_ex:
+        unsafe extern "efiapi" fn(this: *mut Self, key_data: *mut KeyData) -> Status,
+    pub wait_for_key_ex: Event,
+    pub set_state: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        key_toggle_state: *const KeyToggleState,
+    ) -> Status,
+    pub register_key_notify: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        key_data: *const KeyData,
+        key_notification_function: KeyNotifyFunction,
+        notify_handle: *mut *mut c_void,
+    ) -> Status,
+    pub unregister_key_notify:
+        unsafe extern "efiapi" fn(this: *mut Self, notification_handle: *mut c_void) -> Status,
+}
+
+impl SimpleTextInputExProtocol {
+    pub const GUID: Guid = guid!("dd9e7534-7762-4698-8c14-f58517a625aa");
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 #[repr(C)]
 pub struct SimpleTextOutputMode {
@@ -258,3 +335,60 @@ newtype_enum! {
         GRAPHICS_OUTPUT_BLT_OPERATION_MAX = 4,
     }
 }
+
+/// EDID of the display, as read from the hardware. Installed on the handles
+/// of video outputs.
+#[derive(Debug)]
+#[repr(C)]
+pub struct EdidDiscoveredProtocol {
+    pub size_of_edid: u32,
+    pub edid: *const u8,
+}
+
+impl EdidDiscoveredProtocol {
+    pub const GUID: Guid = guid!("1c0c34f6-d380-41fa-a049-8ad06c1a66aa");
+}
+
+/// EDID of the display that is in use, which is the discovered EDID unless
+/// the platform overrides it. Installed on the handles of video outputs.
+#[derive(Debug)]
+#[repr(C)]
+pub struct EdidActiveProtocol {
+    pub size_of_edid: u32,
+    pub edid: *const u8,
+}
+
+impl EdidActiveProtocol {
+    pub const GUID: Guid = guid!("bd8c1056-9f36-44ec-92a8-a6337f817986");
+}
+
+bitflags! {
+    /// Attributes returned by [`EdidOverrideProtocol::get_edid`].
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct EdidOverrideAttributes: u32 {
+        /// The discovered EDID is used, and the returned EDID is ignored.
+        const DONT_OVERRIDE = 0x01;
+
+        /// The display may be connected and disconnected while the system
+        /// is running.
+        const ENABLE_HOT_PLUG = 0x02;
+    }
+}
+
+/// Installed by the platform to override the EDID of displays.
+#[derive(Debug)]
+#[repr(C)]
+pub struct EdidOverrideProtocol {
+    pub get_edid: unsafe extern "efiapi" fn(
+        this: *const Self,
+        child_handle: *const Handle,
+        attributes: *mut EdidOverrideAttributes,
+        edid_size: *mut usize,
+        edid: *mut *const u8,
+    ) -> Status,
+}
+
+impl EdidOverrideProtocol {
+    pub const GUID: Guid = guid!("48ecb431-fb72-45c0-a922-f458fe040bd5");
+}
@@ -0,0 +1,31 @@
+//! Decompress protocol.
+
+use crate::{guid, Guid, Status};
+use core::ffi::c_void;
+
+/// The Decompress protocol, which decompresses data compressed with the
+/// UEFI compression algorithm.
+#[derive(Debug)]
+#[repr(C)]
+pub struct DecompressProtocol {
+    pub get_info: unsafe extern "efiapi" fn(
+        this: *const Self,
+        source: *const c_void,
+        source_size: u32,
+        destination_size: *mut u32,
+        scratch_size: *mut u32,
+    ) -> Status,
+    pub decompress: unsafe extern "efiapi" fn(
+        this: *const Self,
+        source: *const c_void,
+        source_size: u32,
+        destination: *mut c_void,
+        destination_size: u32,
+        scratch: *mut c_void,
+        scratch_size: u32,
+    ) -> Status,
+}
+
+impl DecompressProtocol {
+    pub const GUID: Guid = guid!("d8117cfe-94a6-11d4-9a3a-0090273fc14d");
+}
@@ -0,0 +1,163 @@
+//! Firmware Volume 2 protocol, defined in the Platform Initialization (PI)
+//! specification.
+
+use crate::{guid, Guid, Handle, Status};
+use bitflags::bitflags;
+use core::ffi::c_void;
+
+newtype_enum! {
+    /// Type of a file in a firmware volume.
+    pub enum FvFileType: u8 => {
+        /// Matches files of all types when searching.
+        ALL = 0x00,
+        RAW = 0x01,
+        FREEFORM = 0x02,
+        SECURITY_CORE = 0x03,
+        PEI_CORE = 0x04,
+        DXE_CORE = 0x05,
+        PEIM = 0x06,
+        DRIVER = 0x07,
+        COMBINED_PEIM_DRIVER = 0x08,
+        APPLICATION = 0x09,
+        MM = 0x0a,
+        FIRMWARE_VOLUME_IMAGE = 0x0b,
+        COMBINED_MM_DXE = 0x0c,
+        MM_CORE = 0x0d,
+        MM_STANDALONE = 0x0e,
+        MM_CORE_STANDALONE = 0x0f,
+        FFS_PAD = 0xf0,
+    }
+}
+
+newtype_enum! {
+    /// Type of a section of a file in a firmware volume.
+    pub enum SectionType: u8 => {
+        /// Matches sections of all types when searching.
+        ALL = 0x00,
+        COMPRESSION = 0x01,
+        GUID_DEFINED = 0x02,
+        DISPOSABLE = 0x03,
+        PE32 = 0x10,
+        PIC = 0x11,
+        TE = 0x12,
+        DXE_DEPEX = 0x13,
+        VERSION = 0x14,
+        USER_INTERFACE = 0x15,
+        COMPATIBILITY16 = 0x16,
+        FIRMWARE_VOLUME_IMAGE = 0x17,
+        FREEFORM_SUBTYPE_GUID = 0x18,
+        RAW = 0x19,
+        PEI_DEPEX = 0x1b,
+        MM_DEPEX = 0x1c,
+    }
+}
+
+bitflags! {
+    /// Attributes of a firmware volume.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct FvAttributes: u64 {
+        const READ_DISABLE_CAP = 0x0000_0001;
+        const READ_ENABLE_CAP = 0x0000_0002;
+        const READ_STATUS = 0x0000_0004;
+        const WRITE_DISABLE_CAP = 0x0000_0008;
+        const WRITE_ENABLE_CAP = 0x0000_0010;
+        const WRITE_STATUS = 0x0000_0020;
+        const LOCK_CAP = 0x0000_0040;
+        const LOCK_STATUS = 0x0000_0080;
+        const WRITE_POLICY_RELIABLE = 0x0000_0100;
+        const READ_LOCK_CAP = 0x0000_1000;
+        const READ_LOCK_STATUS = 0x0000_2000;
+        const WRITE_LOCK_CAP = 0x0000_4000;
+        const WRITE_LOCK_STATUS = 0x0000_8000;
+        const ALIGNMENT = 0x001f_0000;
+    }
+}
+
+bitflags! {
+    /// Attributes of a file in a firmware volume.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct FvFileAttributes: u32 {
+        const ALIGNMENT = 0x0000_001f;
+        const FIXED = 0x0000_0100;
+        const MEMORY_MAPPED = 0x0000_0200;
+    }
+}
+
+newtype_enum! {
+    /// Policy for writing files to a firmware volume.
+    pub enum FvWritePolicy: u32 => {
+        UNRELIABLE_WRITE = 0x0000_0000,
+        RELIABLE_WRITE = 0x0000_0001,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct FvWriteFileData {
+    pub name_guid: *const Guid,
+    pub file_type: FvFileType,
+    pub file_attributes: FvFileAttributes,
+    pub buffer: *const c_void,
+    pub buffer_size: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct FirmwareVolume2Protocol {
+    pub get_volume_attributes:
+        unsafe extern "efiapi" fn(this: *const Self, fv_attributes: *mut FvAttributes) -> Status,
+    pub set_volume_attributes:
+        unsafe extern "efiapi" fn(this: *const Self, fv_attributes: *mut FvAttributes) -> Status,
+    pub read_file: unsafe extern "efiapi" fn(
+        this: *const Self,
+        name_guid: *const Guid,
+        buffer: *mut *mut c_void,
+        buffer_size: *mut usize,
+        found_type: *mut FvFileType,
+        file_attributes: *mut FvFileAttributes,
+        authentication_status: *mut u32,
+    ) -> Status,
+    pub read_section: unsafe extern "efiapi" fn(
+        this: *const Self,
+        name_guid: *const Guid,
+        section_type: SectionType,
+        section_instance: usize,
+        buffer: *mut *mut c_void,
+        buffer_size: *mut usize,
+        authentication_status: *mut u32,
+    ) -> Status,
+    pub write_file: unsafe extern "efiapi" fn(
+        this: *const Self,
+        number_of_files: u32,
+        write_policy: FvWritePolicy,
+        file_data: *const FvWriteFileData,
+    ) -> Status,
+    pub get_next_file: unsafe extern "efiapi" fn(
+        this: *const Self,
+        key: *mut c_void,
+        file_type: *mut FvFileType,
+        name_guid: *mut Guid,
+        attributes: *mut FvFileAttributes,
+        size: *mut usize,
+    ) -> Status,
+    pub key_size: u32,
+    pub parent_handle: Handle,
+    pub get_info: unsafe extern "efiapi" fn(
+        this: *const Self,
+        information_type: *const Guid,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status,
+    pub set_info: unsafe extern "efiapi" fn(
+        this: *const Self,
+        information_type: *const Guid,
+        buffer_size: usize,
+        buffer: *const c_void,
+    ) -> Status,
+}
+
+impl FirmwareVolume2Protocol {
+    pub const GUID: Guid = guid!("220e73b6-6bdb-4413-8405-b974b108619a");
+}
@@ -33,3 +33,48 @@ pub struct LoadFile2Protocol {
 impl LoadFile2Protocol {
     pub const GUID: Guid = guid!("4006c0c1-fcb3-403e-996d-4a6c8724e06d");
 }
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct RamDiskProtocol {
+    pub register: unsafe extern "efiapi" fn(
+        ram_disk_base: u64,
+        ram_disk_size: u64,
+        ram_disk_type: *const Guid,
+        parent_device_path: *const DevicePathProtocol,
+        device_path: *mut *mut DevicePathProtocol,
+    ) -> Status,
+    pub unregister: unsafe extern "efiapi" fn(device_path: *const DevicePathProtocol) -> Status,
+}
+
+impl RamDiskProtocol {
+    pub const GUID: Guid = guid!("ab38a0df-6873-44a9-87e6-d4eb56148449");
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct StorageSecurityCommandProtocol {
+    pub receive_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        media_id: u32,
+        timeout: u64,
+        security_protocol_id: u8,
+        security_protocol_specific_data: u16,
+        payload_buffer_size: usize,
+        payload_buffer: *mut c_void,
+        payload_transfer_size: *mut usize,
+    ) -> Status,
+    pub send_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        media_id: u32,
+        timeout: u64,
+        security_protocol_id: u8,
+        security_protocol_specific_data: u16,
+        payload_buffer_size: usize,
+        payload_buffer: *const c_void,
+    ) -> Status,
+}
+
+impl StorageSecurityCommandProtocol {
+    pub const GUID: Guid = guid!("c88b0b6d-0dfc-49a7-9cb4-49074b4c3a78");
+}
@@ -0,0 +1,75 @@
+use crate::{guid, Event, Guid, Status};
+use core::ffi::c_void;
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ArpConfigData {
+    pub sw_address_type: u16,
+    pub sw_address_length: u8,
+    pub station_address: *mut c_void,
+    pub entry_time_out: u32,
+    pub retry_count: u32,
+    pub retry_time_out: u32,
+}
+
+/// Header of an ARP cache entry returned by [`ArpProtocol::find`].
+///
+/// The header is followed by the protocol address and then the hardware
+/// address of the entry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct ArpFindData {
+    pub size: u32,
+    pub deny_flag: bool,
+    pub static_flag: bool,
+    pub hw_address_type: u16,
+    pub sw_address_type: u16,
+    pub hw_address_length: u8,
+    pub sw_address_length: u8,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ArpProtocol {
+    pub configure:
+        unsafe extern "efiapi" fn(this: *mut Self, config_data: *const ArpConfigData) -> Status,
+    pub add: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        deny_flag: bool,
+        target_sw_address: *const c_void,
+        target_hw_address: *const c_void,
+        timeout_value: u32,
+        overwrite: bool,
+    ) -> Status,
+    pub find: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        by_sw_address: bool,
+        address_buffer: *const c_void,
+        entry_length: *mut u32,
+        entry_count: *mut u32,
+        entries: *mut *mut ArpFindData,
+        refresh: bool,
+    ) -> Status,
+    pub delete: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        by_sw_address: bool,
+        address_buffer: *const c_void,
+    ) -> Status,
+    pub flush: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+    pub request: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        target_sw_address: *const c_void,
+        resolved_event: Event,
+        target_hw_address: *mut c_void,
+    ) -> Status,
+    pub cancel: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        target_sw_address: *const c_void,
+        resolved_event: Event,
+    ) -> Status,
+}
+
+impl ArpProtocol {
+    pub const GUID: Guid = guid!("f4b427bb-ba21-4f16-bc4e-43e416ab619c");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("f44c00ee-1f2c-4a00-aa09-1c9f3e0800a3");
+}
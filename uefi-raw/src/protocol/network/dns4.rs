@@ -0,0 +1,132 @@
+use crate::{guid, Char16, Char8, Event, Guid, Ipv4Address, Status};
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4ConfigData {
+    pub dns_server_list_count: usize,
+    pub dns_server_list: *mut Ipv4Address,
+    pub use_default_setting: bool,
+    pub enable_dns_cache: bool,
+    pub protocol: u8,
+    pub station_ip: Ipv4Address,
+    pub subnet_mask: Ipv4Address,
+    pub local_port: u16,
+    pub retry_count: u32,
+    pub retry_interval: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4ModeData {
+    pub dns_config_data: Dns4ConfigData,
+    pub dns_server_count: u32,
+    pub dns_server_list: *mut Ipv4Address,
+    pub dns_cache_count: u32,
+    pub dns_cache_list: *mut Dns4CacheEntry,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4CacheEntry {
+    pub host_name: *mut Char16,
+    pub ip_address: *mut Ipv4Address,
+    pub timeout: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4HostToAddrData {
+    pub ip_count: u32,
+    pub ip_list: *mut Ipv4Address,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4AddrToHostData {
+    pub host_name: *mut Char16,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4ResourceRecord {
+    pub q_name: *mut Char8,
+    pub q_type: u16,
+    pub q_class: u16,
+    pub ttl: u32,
+    pub data_length: u16,
+    pub r_data: *mut Char8,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4GeneralLookupData {
+    pub rr_count: usize,
+    pub rr_list: *mut Dns4ResourceRecord,
+}
+
+#[repr(C)]
+pub union Dns4ResponseData {
+    pub h2a_data: *mut Dns4HostToAddrData,
+    pub a2h_data: *mut Dns4AddrToHostData,
+    pub g_lookup_data: *mut Dns4GeneralLookupData,
+}
+
+impl Debug for Dns4ResponseData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("Dns4ResponseData").finish()
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4CompletionToken {
+    pub event: Event,
+    pub status: Status,
+    pub retry_count: u32,
+    pub retry_interval: u32,
+    pub rsp_data: Dns4ResponseData,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns4Protocol {
+    pub get_mode_data:
+        unsafe extern "efiapi" fn(this: *const Self, dns_mode_data: *mut Dns4ModeData) -> Status,
+    pub configure: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        dns_config_data: *const Dns4ConfigData,
+    ) -> Status,
+    pub host_name_to_ip: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        host_name: *const Char16,
+        token: *mut Dns4CompletionToken,
+    ) -> Status,
+    pub ip_to_host_name: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        ip_address: Ipv4Address,
+        token: *mut Dns4CompletionToken,
+    ) -> Status,
+    pub general_lookup: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        q_name: *const Char8,
+        q_type: u16,
+        q_class: u16,
+        token: *mut Dns4CompletionToken,
+    ) -> Status,
+    pub update_dns_cache: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        delete_flag: bool,
+        override_flag: bool,
+        dns_cache_entry: Dns4CacheEntry,
+    ) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+    pub cancel:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Dns4CompletionToken) -> Status,
+}
+
+impl Dns4Protocol {
+    pub const GUID: Guid = guid!("ae3d28cc-e05b-4fa1-a011-7eb55a3f1401");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("b625b186-e063-44f7-8905-6a74dc6f52b4");
+}
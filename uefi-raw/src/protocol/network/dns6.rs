@@ -0,0 +1,130 @@
+use crate::{guid, Char16, Char8, Event, Guid, Ipv6Address, Status};
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6ConfigData {
+    pub enable_dns_cache: bool,
+    pub protocol: u8,
+    pub station_ip: Ipv6Address,
+    pub local_port: u16,
+    pub dns_server_count: u32,
+    pub dns_server_list: *mut Ipv6Address,
+    pub retry_count: u32,
+    pub retry_interval: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6ModeData {
+    pub dns_config_data: Dns6ConfigData,
+    pub dns_server_count: u32,
+    pub dns_server_list: *mut Ipv6Address,
+    pub dns_cache_count: u32,
+    pub dns_cache_list: *mut Dns6CacheEntry,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6CacheEntry {
+    pub host_name: *mut Char16,
+    pub ip_address: *mut Ipv6Address,
+    pub timeout: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6HostToAddrData {
+    pub ip_count: u32,
+    pub ip_list: *mut Ipv6Address,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6AddrToHostData {
+    pub host_name: *mut Char16,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6ResourceRecord {
+    pub q_name: *mut Char8,
+    pub q_type: u16,
+    pub q_class: u16,
+    pub ttl: u32,
+    pub data_length: u16,
+    pub r_data: *mut Char8,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6GeneralLookupData {
+    pub rr_count: usize,
+    pub rr_list: *mut Dns6ResourceRecord,
+}
+
+#[repr(C)]
+pub union Dns6ResponseData {
+    pub h2a_data: *mut Dns6HostToAddrData,
+    pub a2h_data: *mut Dns6AddrToHostData,
+    pub g_lookup_data: *mut Dns6GeneralLookupData,
+}
+
+impl Debug for Dns6ResponseData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("Dns6ResponseData").finish()
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6CompletionToken {
+    pub event: Event,
+    pub status: Status,
+    pub retry_count: u32,
+    pub retry_interval: u32,
+    pub rsp_data: Dns6ResponseData,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Dns6Protocol {
+    pub get_mode_data:
+        unsafe extern "efiapi" fn(this: *const Self, dns_mode_data: *mut Dns6ModeData) -> Status,
+    pub configure: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        dns_config_data: *const Dns6ConfigData,
+    ) -> Status,
+    pub host_name_to_ip: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        host_name: *const Char16,
+        token: *mut Dns6CompletionToken,
+    ) -> Status,
+    pub ip_to_host_name: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        ip_address: Ipv6Address,
+        token: *mut Dns6CompletionToken,
+    ) -> Status,
+    pub general_lookup: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        q_name: *const Char8,
+        q_type: u16,
+        q_class: u16,
+        token: *mut Dns6CompletionToken,
+    ) -> Status,
+    pub update_dns_cache: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        delete_flag: bool,
+        override_flag: bool,
+        dns_cache_entry: Dns6CacheEntry,
+    ) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+    pub cancel:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Dns6CompletionToken) -> Status,
+}
+
+impl Dns6Protocol {
+    pub const GUID: Guid = guid!("ca37bc1f-a327-4ae9-828a-8c40d8506a17");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("7f1647c8-b76e-44b2-a565-f70ff19cd19e");
+}
@@ -0,0 +1,60 @@
+use crate::{guid, Guid, Status};
+use core::ffi::c_void;
+
+newtype_enum! {
+    pub enum EapType: u8 => {
+        ATTRIBUTE      = 0,
+        IDENTITY       = 1,
+        NOTIFICATION   = 2,
+        NAK            = 3,
+        MD5_CHALLENGE  = 4,
+        OTP            = 5,
+        GTC            = 6,
+        EAP_TLS        = 13,
+        EAP_SIM        = 18,
+        TTLS           = 21,
+        PEAP           = 25,
+        MS_CHAP_V2     = 26,
+        EAP_EXTENSION  = 33,
+    }
+}
+
+newtype_enum! {
+    pub enum EapConfigDataType: i32 => {
+        EAP_AUTH_METHOD                          = 0,
+        EAP_SUPPORTED_AUTH_METHOD                = 1,
+        IDENTITY_STRING                          = 2,
+        EAP_TLS_CA_CERT                          = 3,
+        EAP_TLS_CLIENT_CERT                      = 4,
+        EAP_TLS_CLIENT_PRIVATE_KEY_FILE          = 5,
+        EAP_TLS_CLIENT_PRIVATE_KEY_FILE_PASSWORD = 6,
+        EAP_TLS_CIPHER_SUITE                     = 7,
+        EAP_TLS_SUPPORTED_CIPHER_SUITE           = 8,
+        EAP_MS_CHAP_V2_PASSWORD                  = 9,
+        EAP_2ND_AUTH_METHOD                      = 10,
+        MAXIMUM                                  = 11,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct EapConfigurationProtocol {
+    pub set_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        data: *const c_void,
+        data_size: usize,
+    ) -> Status,
+    pub get_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        data: *mut c_void,
+        data_size: *mut usize,
+    ) -> Status,
+}
+
+impl EapConfigurationProtocol {
+    pub const GUID: Guid = guid!("e5b58dbb-7688-44b4-97bf-5f1d4b7cc8db");
+}
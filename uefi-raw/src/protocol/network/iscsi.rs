@@ -0,0 +1,21 @@
+use crate::{guid, Guid, Status};
+use core::ffi::c_void;
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct IScsiInitiatorNameProtocol {
+    pub get: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        buffer_size: *mut usize,
+        buffer: *mut c_void,
+    ) -> Status,
+    pub set: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        buffer_size: *mut usize,
+        buffer: *const c_void,
+    ) -> Status,
+}
+
+impl IScsiInitiatorNameProtocol {
+    pub const GUID: Guid = guid!("59324945-ec44-4c0d-b1cd-9db139df070c");
+}
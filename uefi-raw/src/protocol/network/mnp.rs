@@ -0,0 +1,127 @@
+use crate::time::Time;
+use crate::{guid, Event, Guid, IpAddress, MacAddress, Status};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct ManagedNetworkConfigData {
+    pub received_queue_timeout_value: u32,
+    pub transmit_queue_timeout_value: u32,
+    pub protocol_type_filter: u16,
+    pub enable_unicast_receive: bool,
+    pub enable_multicast_receive: bool,
+    pub enable_broadcast_receive: bool,
+    pub enable_promiscuous_receive: bool,
+    pub flush_queues_on_reset: bool,
+    pub enable_receive_timestamps: bool,
+    pub disable_background_polling: bool,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ManagedNetworkReceiveData {
+    pub timestamp: Time,
+    pub recycle_event: Event,
+    pub packet_length: u32,
+    pub header_length: u32,
+    pub address_length: u32,
+    pub data_length: u32,
+    pub broadcast_flag: bool,
+    pub multicast_flag: bool,
+    pub promiscuous_flag: bool,
+    pub protocol_type: u16,
+    pub destination_address: *mut c_void,
+    pub source_address: *mut c_void,
+    pub media_header: *mut c_void,
+    pub packet_data: *mut c_void,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ManagedNetworkFragmentData {
+    pub fragment_length: u32,
+    pub fragment_buffer: *mut c_void,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ManagedNetworkTransmitData {
+    pub destination_address: *mut MacAddress,
+    pub source_address: *mut MacAddress,
+    pub protocol_type: u16,
+    pub data_length: u32,
+    pub header_length: u16,
+    pub fragment_count: u16,
+
+    /// Start of the fragment table.
+    ///
+    /// Note that this field is actually a variable-length array with
+    /// `fragment_count` elements.
+    pub fragment_table: [ManagedNetworkFragmentData; 1],
+}
+
+#[repr(C)]
+pub union ManagedNetworkPacket {
+    pub rx_data: *mut ManagedNetworkReceiveData,
+    pub tx_data: *mut ManagedNetworkTransmitData,
+}
+
+impl Debug for ManagedNetworkPacket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("ManagedNetworkPacket").finish()
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ManagedNetworkCompletionToken {
+    pub event: Event,
+    pub status: Status,
+    pub packet: ManagedNetworkPacket,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ManagedNetworkProtocol {
+    /// The last parameter is a pointer to an `EFI_SIMPLE_NETWORK_MODE`.
+    pub get_mode_data: unsafe extern "efiapi" fn(
+        this: *const Self,
+        mnp_config_data: *mut ManagedNetworkConfigData,
+        snp_mode_data: *mut c_void,
+    ) -> Status,
+    pub configure: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        mnp_config_data: *const ManagedNetworkConfigData,
+    ) -> Status,
+    pub mcast_ip_to_mac: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        ipv6_flag: bool,
+        ip_address: *const IpAddress,
+        mac_address: *mut MacAddress,
+    ) -> Status,
+    pub groups: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        join_flag: bool,
+        mac_address: *const MacAddress,
+    ) -> Status,
+    pub transmit: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        token: *mut ManagedNetworkCompletionToken,
+    ) -> Status,
+    pub receive: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        token: *mut ManagedNetworkCompletionToken,
+    ) -> Status,
+    pub cancel: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        token: *mut ManagedNetworkCompletionToken,
+    ) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+}
+
+impl ManagedNetworkProtocol {
+    pub const GUID: Guid = guid!("7ab33a91-ace5-4326-b572-e7ee33d39f16");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("f36ff770-a7e1-42cf-9ed2-56f0f271f44c");
+}
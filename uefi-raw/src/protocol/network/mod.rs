@@ -1,5 +1,19 @@
+pub mod arp;
 pub mod dhcp4;
+pub mod dns4;
+pub mod dns6;
+pub mod eap_configuration;
 pub mod http;
 pub mod ip4;
 pub mod ip4_config2;
+pub mod iscsi;
+pub mod mnp;
+pub mod mtftp4;
+pub mod rest;
+pub mod supplicant;
+pub mod tcp4;
+pub mod tcp6;
 pub mod tls;
+pub mod udp4;
+pub mod udp6;
+pub mod vlan_config;
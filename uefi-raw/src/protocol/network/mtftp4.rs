@@ -0,0 +1,244 @@
+use crate::{guid, Event, Guid, Ipv4Address, Status};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+newtype_enum! {
+    pub enum Mtftp4OpCode: u16 => {
+        RRQ   = 1,
+        WRQ   = 2,
+        DATA  = 3,
+        ACK   = 4,
+        ERROR = 5,
+        OACK  = 6,
+        DIR   = 7,
+        DATA8 = 8,
+        ACK8  = 9,
+    }
+}
+
+newtype_enum! {
+    pub enum Mtftp4ErrorCode: u16 => {
+        NOT_DEFINED         = 0,
+        FILE_NOT_FOUND      = 1,
+        ACCESS_VIOLATION    = 2,
+        DISK_FULL           = 3,
+        ILLEGAL_OPERATION   = 4,
+        UNKNOWN_TRANSFER_ID = 5,
+        FILE_ALREADY_EXISTS = 6,
+        NO_SUCH_USER        = 7,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct Mtftp4ConfigData {
+    pub use_default_setting: bool,
+    pub station_ip: Ipv4Address,
+    pub subnet_mask: Ipv4Address,
+    pub local_port: u16,
+    pub gateway_ip: Ipv4Address,
+    pub server_ip: Ipv4Address,
+    pub initial_server_port: u16,
+    pub try_count: u16,
+    pub timeout_value: u16,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Mtftp4ModeData {
+    pub config_data: Mtftp4ConfigData,
+    pub supported_option_count: u8,
+    pub supported_options: *mut *mut u8,
+    pub unsupported_option_count: u8,
+    pub unsupported_options: *mut *mut u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct Mtftp4OverrideData {
+    pub gateway_ip: Ipv4Address,
+    pub server_ip: Ipv4Address,
+    pub server_port: u16,
+    pub try_count: u16,
+    pub timeout_value: u16,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Mtftp4Option {
+    pub option_str: *mut u8,
+    pub value_str: *mut u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4ReqHeader {
+    pub op_code: u16,
+
+    /// Start of the file name, mode and option strings.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub filename: [u8; 1],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4OackHeader {
+    pub op_code: u16,
+
+    /// Start of the option strings.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub data: [u8; 1],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4DataHeader {
+    pub op_code: u16,
+    pub block: u16,
+
+    /// Start of the block data.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub data: [u8; 1],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4AckHeader {
+    pub op_code: u16,
+
+    /// Start of the acknowledged block numbers.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub block: [u16; 1],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4Data8Header {
+    pub op_code: u16,
+    pub block: u64,
+
+    /// Start of the block data.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub data: [u8; 1],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4Ack8Header {
+    pub op_code: u16,
+
+    /// Start of the acknowledged block numbers.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub block: [u64; 1],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Mtftp4ErrorHeader {
+    pub op_code: u16,
+    pub error_code: u16,
+
+    /// Start of the nul-terminated error message.
+    ///
+    /// Note that this field is actually a variable-length array.
+    pub error_message: [u8; 1],
+}
+
+/// A TFTP packet. All fields are in network byte order.
+#[derive(Clone, Copy)]
+#[repr(C, packed)]
+pub union Mtftp4Packet {
+    pub op_code: u16,
+    pub rrq: Mtftp4ReqHeader,
+    pub wrq: Mtftp4ReqHeader,
+    pub oack: Mtftp4OackHeader,
+    pub data: Mtftp4DataHeader,
+    pub ack: Mtftp4AckHeader,
+    pub data8: Mtftp4Data8Header,
+    pub ack8: Mtftp4Ack8Header,
+    pub error: Mtftp4ErrorHeader,
+}
+
+impl Debug for Mtftp4Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("Mtftp4Packet").finish()
+    }
+}
+
+pub type Mtftp4CheckPacket = unsafe extern "efiapi" fn(
+    this: *mut Mtftp4Protocol,
+    token: *mut Mtftp4Token,
+    packet_len: u16,
+    packet: *const Mtftp4Packet,
+) -> Status;
+
+pub type Mtftp4TimeoutCallback =
+    unsafe extern "efiapi" fn(this: *mut Mtftp4Protocol, token: *mut Mtftp4Token) -> Status;
+
+pub type Mtftp4PacketNeeded = unsafe extern "efiapi" fn(
+    this: *mut Mtftp4Protocol,
+    token: *mut Mtftp4Token,
+    length: *mut u16,
+    buffer: *mut *mut c_void,
+) -> Status;
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Mtftp4Token {
+    pub status: Status,
+    pub event: Event,
+    pub override_data: *mut Mtftp4OverrideData,
+    pub filename: *mut u8,
+    pub mode_str: *mut u8,
+    pub option_count: u32,
+    pub option_list: *mut Mtftp4Option,
+    pub buffer_size: u64,
+    pub buffer: *mut c_void,
+    pub context: *mut c_void,
+    pub check_packet: Option<Mtftp4CheckPacket>,
+    pub timeout_callback: Option<Mtftp4TimeoutCallback>,
+    pub packet_needed: Option<Mtftp4PacketNeeded>,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Mtftp4Protocol {
+    pub get_mode_data:
+        unsafe extern "efiapi" fn(this: *mut Self, mode_data: *mut Mtftp4ModeData) -> Status,
+    pub configure:
+        unsafe extern "efiapi" fn(this: *mut Self, config_data: *const Mtftp4ConfigData) -> Status,
+    pub get_info: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        override_data: *const Mtftp4OverrideData,
+        filename: *const u8,
+        mode_str: *const u8,
+        option_count: u8,
+        option_list: *const Mtftp4Option,
+        packet_length: *mut u32,
+        packet: *mut *mut Mtftp4Packet,
+    ) -> Status,
+    pub parse_options: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        packet_len: u32,
+        packet: *const Mtftp4Packet,
+        option_count: *mut u32,
+        option_list: *mut *mut Mtftp4Option,
+    ) -> Status,
+    pub read_file: unsafe extern "efiapi" fn(this: *mut Self, token: *mut Mtftp4Token) -> Status,
+    pub write_file: unsafe extern "efiapi" fn(this: *mut Self, token: *mut Mtftp4Token) -> Status,
+    pub read_directory:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Mtftp4Token) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+}
+
+impl Mtftp4Protocol {
+    pub const GUID: Guid = guid!("78247c57-63db-4708-99c2-a8b4a9a61f6b");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("2fe800be-8f01-4aa6-946b-d71388e1833f");
+}
@@ -0,0 +1,134 @@
+use super::http::{HttpConfigData, HttpMessage};
+use crate::time::Time;
+use crate::{guid, Event, Guid, Status};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct RestProtocol {
+    pub send_receive: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        request_message: *mut HttpMessage,
+        response_message: *mut HttpMessage,
+    ) -> Status,
+    pub get_service_time: unsafe extern "efiapi" fn(this: *mut Self, time: *mut Time) -> Status,
+}
+
+impl RestProtocol {
+    pub const GUID: Guid = guid!("0db48a36-4e54-ea9c-9b09-1ea5be3a660b");
+}
+
+newtype_enum! {
+    pub enum RestExServiceType: u8 => {
+        UNSPECIFIC = 0x01,
+        REDFISH = 0x02,
+        ODATA = 0x03,
+        VENDOR_SPECIFIC = 0xff,
+    }
+}
+
+newtype_enum! {
+    pub enum RestExServiceAccessMode: u8 => {
+        IN_BAND = 0x01,
+        OUT_OF_BAND = 0x02,
+    }
+}
+
+newtype_enum! {
+    pub enum RestExConfigType: u8 => {
+        HTTP = 0x00,
+        UNSPECIFIC = 0x01,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct RestExServiceInfoVer {
+    pub major: u8,
+    pub minor: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct RestExServiceInfoHeader {
+    pub length: u32,
+    pub rest_service_info_ver: RestExServiceInfoVer,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct RestExServiceInfoV1 {
+    pub header: RestExServiceInfoHeader,
+    pub rest_ex_service_type: RestExServiceType,
+    pub rest_service_access_mode: RestExServiceAccessMode,
+    pub vendor_rest_service_name: Guid,
+    pub vendor_specific_data_length: u32,
+    pub vendor_specific_data: *mut u8,
+    pub rest_ex_config_type: RestExConfigType,
+    pub rest_ex_config_data_length: u8,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union RestExServiceInfo {
+    pub header: RestExServiceInfoHeader,
+    pub v1: RestExServiceInfoV1,
+}
+
+impl Debug for RestExServiceInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("RestExServiceInfo").finish()
+    }
+}
+
+/// Configuration data for instances with [`RestExConfigType::HTTP`].
+#[derive(Debug)]
+#[repr(C)]
+pub struct RestExHttpConfigData {
+    pub http_config_data: HttpConfigData,
+    pub send_receive_timeout: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct RestExToken {
+    pub event: Event,
+    pub status: Status,
+    pub response_message: *mut HttpMessage,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct RestExProtocol {
+    pub send_receive: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        request_message: *mut HttpMessage,
+        response_message: *mut HttpMessage,
+    ) -> Status,
+    pub get_service: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        rest_ex_service_info: *mut *mut RestExServiceInfo,
+    ) -> Status,
+    pub get_mode_data:
+        unsafe extern "efiapi" fn(this: *mut Self, rest_ex_config_data: *mut *mut c_void) -> Status,
+    pub configure:
+        unsafe extern "efiapi" fn(this: *mut Self, rest_ex_config_data: *const c_void) -> Status,
+    pub async_send_receive: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        request_message: *mut HttpMessage,
+        rest_ex_token: *mut RestExToken,
+        time_out_in_milli_seconds: *mut usize,
+    ) -> Status,
+    pub event_service: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        request_message: *mut HttpMessage,
+        rest_ex_token: *mut RestExToken,
+    ) -> Status,
+}
+
+impl RestExProtocol {
+    pub const GUID: Guid = guid!("55648b91-0e7d-40a3-a9b3-a815d14df791");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("456bbe01-99d0-45ea-bb5f-16d84bedc559");
+}
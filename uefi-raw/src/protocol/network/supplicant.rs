@@ -0,0 +1,120 @@
+use crate::{guid, Guid, Status};
+use core::ffi::c_void;
+
+newtype_enum! {
+    pub enum SupplicantDataType: i32 => {
+        AKM_SUITE                        = 0,
+        GROUP_DATA_CIPHER_SUITE          = 1,
+        PAIRWISE_CIPHER_SUITE            = 2,
+        PSK_PASSWORD                     = 3,
+        TARGET_SSID_NAME                 = 4,
+        STATION_MAC                      = 5,
+        TARGET_SSID_MAC                  = 6,
+        PTK                              = 7,
+        GTK                              = 8,
+        STATE                            = 9,
+        LINK_STATE                       = 10,
+        KEY_REFRESH                      = 11,
+        SUPPORTED_AKM_SUITES             = 12,
+        SUPPORTED_SOFTWARE_CIPHER_SUITES = 13,
+        SUPPORTED_HARDWARE_CIPHER_SUITES = 14,
+        IGTK                             = 15,
+        PMK                              = 16,
+        MAXIMUM                          = 17,
+    }
+}
+
+newtype_enum! {
+    pub enum SupplicantCryptMode: i32 => {
+        ENCRYPT = 0,
+        DECRYPT = 1,
+    }
+}
+
+newtype_enum! {
+    pub enum EapolSupplicantPaeState: i32 => {
+        LOGOFF         = 1,
+        DISCONNECTED   = 2,
+        CONNECTING     = 3,
+        ACQUIRED       = 4,
+        AUTHENTICATING = 5,
+        HELD           = 6,
+        AUTHENTICATED  = 7,
+        MAXIMUM        = 8,
+    }
+}
+
+newtype_enum! {
+    pub enum Ieee80211LinkState: i32 => {
+        UNAUTHENTICATED_UNASSOCIATED = 0,
+        AUTHENTICATED_UNASSOCIATED   = 1,
+        PENDING_RSN_AUTHENTICATION   = 2,
+        AUTHENTICATED_ASSOCIATED     = 3,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct Ieee80211SuiteSelector {
+    pub oui: [u8; 3],
+    pub suite_type: u8,
+}
+
+/// Header of an `EFI_80211_AKM_SUITE_SELECTOR` or
+/// `EFI_80211_CIPHER_SUITE_SELECTOR`, which is followed by `suite_count`
+/// suite selectors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct Ieee80211SuiteSelectorList {
+    pub suite_count: u16,
+    pub suite_list: [Ieee80211SuiteSelector; 1],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct Ieee80211Ssid {
+    pub ssid_len: u8,
+    pub ssid: [u8; 32],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct SupplicantFragmentData {
+    pub fragment_length: u32,
+    pub fragment_buffer: *mut c_void,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct SupplicantProtocol {
+    pub build_response_packet: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        request_buffer: *const u8,
+        request_buffer_size: usize,
+        buffer: *mut u8,
+        buffer_size: *mut usize,
+    ) -> Status,
+    pub process_packet: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        fragment_table: *mut *mut SupplicantFragmentData,
+        fragment_count: *mut u32,
+        crypt_mode: SupplicantCryptMode,
+    ) -> Status,
+    pub set_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        data_type: SupplicantDataType,
+        data: *const c_void,
+        data_size: usize,
+    ) -> Status,
+    pub get_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        data_type: SupplicantDataType,
+        data: *mut u8,
+        data_size: *mut usize,
+    ) -> Status,
+}
+
+impl SupplicantProtocol {
+    pub const GUID: Guid = guid!("54fcc43e-aa89-4333-9a85-cdea24051e9e");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("45bcd98e-59ad-4174-9546-344a07485898");
+}
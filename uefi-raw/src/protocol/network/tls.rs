@@ -1,4 +1,5 @@
-use crate::{guid, Guid, Status};
+use crate::{guid, Char8, Guid, Status};
+use bitflags::bitflags;
 use core::ffi::c_void;
 
 newtype_enum! {
@@ -33,3 +34,145 @@ impl TlsConfigurationProtocol {
     pub const GUID: Guid = guid!("1682fe44-bd7a-4407-b7c7-dca37ca3922d");
     pub const SERVICE_BINDING_GUID: Guid = guid!("952cb795-ff36-48cf-a249-4df486d6ab8d");
 }
+
+newtype_enum! {
+    pub enum TlsSessionDataType: i32 => {
+        VERSION            = 0,
+        CONNECTION_END     = 1,
+        CIPHER_LIST        = 2,
+        COMPRESSION_METHOD = 3,
+        EXTENSION_DATA     = 4,
+        VERIFY_METHOD      = 5,
+        SESSION_ID         = 6,
+        SESSION_STATE      = 7,
+        CLIENT_RANDOM      = 8,
+        SERVER_RANDOM      = 9,
+        KEY_MATERIAL       = 10,
+        VERIFY_HOST        = 11,
+        MAXIMUM            = 12,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[repr(C)]
+pub struct TlsVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+newtype_enum! {
+    pub enum TlsConnectionEnd: i32 => {
+        CLIENT = 0,
+        SERVER = 1,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct TlsCipher {
+    pub data1: u8,
+    pub data2: u8,
+}
+
+bitflags! {
+    /// Peer verification mode, corresponding to `EFI_TLS_VERIFY`.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct TlsVerify: u32 {
+        // `VERIFY_NONE` is the empty set.
+
+        /// Verify the peer certificate.
+        const PEER = 0x1;
+
+        /// Fail if the peer does not present a certificate.
+        const FAIL_IF_NO_PEER_CERT = 0x2;
+
+        /// Only request the client certificate on the initial handshake.
+        const CLIENT_ONCE = 0x4;
+    }
+}
+
+bitflags! {
+    /// Host name verification flags, corresponding to
+    /// `EFI_TLS_VERIFY_HOST_FLAG`.
+    #[repr(transparent)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct TlsVerifyHostFlags: u32 {
+        const ALWAYS_CHECK_SUBJECT = 0x01;
+        const NO_WILDCARDS = 0x02;
+        const NO_PARTIAL_WILDCARDS = 0x04;
+        const MULTI_LABEL_WILDCARDS = 0x08;
+        const SINGLE_LABEL_SUBDOMAINS = 0x10;
+        const NEVER_CHECK_SUBJECT = 0x20;
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct TlsVerifyHost {
+    pub flags: TlsVerifyHostFlags,
+    pub host_name: *mut Char8,
+}
+
+newtype_enum! {
+    pub enum TlsSessionState: i32 => {
+        NOT_STARTED = 0,
+        HANDSHAKING = 1,
+        CONNECTED   = 2,
+        CLOSED      = 3,
+        ERROR       = 4,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct TlsFragmentData {
+    pub fragment_length: u32,
+    pub fragment_buffer: *mut c_void,
+}
+
+newtype_enum! {
+    pub enum TlsCryptMode: i32 => {
+        ENCRYPT = 0,
+        DECRYPT = 1,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct TlsProtocol {
+    pub set_session_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        data_type: TlsSessionDataType,
+        data: *const c_void,
+        data_size: usize,
+    ) -> Status,
+    pub get_session_data: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        data_type: TlsSessionDataType,
+        data: *mut c_void,
+        data_size: *mut usize,
+    ) -> Status,
+    pub build_response_packet: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        request_buffer: *const u8,
+        request_size: usize,
+        buffer: *mut u8,
+        buffer_size: *mut usize,
+    ) -> Status,
+    pub process_packet: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        fragment_table: *mut *mut TlsFragmentData,
+        fragment_count: *mut u32,
+        crypt_mode: TlsCryptMode,
+    ) -> Status,
+}
+
+impl TlsProtocol {
+    pub const GUID: Guid = guid!("00ca959f-6cfa-4db1-95bc-e46c47514390");
+    pub const SERVICE_BINDING_GUID: Guid = TlsConfigurationProtocol::SERVICE_BINDING_GUID;
+}
+
+/// Vendor GUID of the `TlsCaCertificate` variable, which holds the CA
+/// certificates used by the HTTP driver for HTTPS connections.
+pub const TLS_CA_CERTIFICATE_GUID: Guid = guid!("fd2340d0-3dab-4349-a6c7-3b4f12b48eae");
@@ -0,0 +1,132 @@
+use crate::time::Time;
+use crate::{guid, Event, Guid, Ipv4Address, Status};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct Udp4ConfigData {
+    pub accept_broadcast: bool,
+    pub accept_promiscuous: bool,
+    pub accept_any_port: bool,
+    pub allow_duplicate_port: bool,
+    pub type_of_service: u8,
+    pub time_to_live: u8,
+    pub do_not_fragment: bool,
+    pub receive_timeout: u32,
+    pub transmit_timeout: u32,
+    pub use_default_address: bool,
+    pub station_address: Ipv4Address,
+    pub subnet_mask: Ipv4Address,
+    pub station_port: u16,
+    pub remote_address: Ipv4Address,
+    pub remote_port: u16,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct Udp4SessionData {
+    pub source_address: Ipv4Address,
+    pub source_port: u16,
+    pub destination_address: Ipv4Address,
+    pub destination_port: u16,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Udp4FragmentData {
+    pub fragment_length: u32,
+    pub fragment_buffer: *mut c_void,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp4TransmitData {
+    pub udp_session_data: *mut Udp4SessionData,
+    pub gateway_address: *mut Ipv4Address,
+    pub data_length: u32,
+    pub fragment_count: u32,
+
+    /// Start of the fragment table.
+    ///
+    /// Note that this field is actually a variable-length array with
+    /// `fragment_count` elements.
+    pub fragment_table: [Udp4FragmentData; 1],
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp4ReceiveData {
+    pub time_stamp: Time,
+    pub recycle_signal: Event,
+    pub udp_session: Udp4SessionData,
+    pub data_length: u32,
+    pub fragment_count: u32,
+
+    /// Start of the fragment table.
+    ///
+    /// Note that this field is actually a variable-length array with
+    /// `fragment_count` elements.
+    pub fragment_table: [Udp4FragmentData; 1],
+}
+
+#[repr(C)]
+pub union Udp4Packet {
+    pub rx_data: *mut Udp4ReceiveData,
+    pub tx_data: *mut Udp4TransmitData,
+}
+
+impl Debug for Udp4Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // This is a union type, so we can't access the internal data.
+        f.debug_struct("Udp4Packet").finish()
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp4CompletionToken {
+    pub event: Event,
+    pub status: Status,
+    pub packet: Udp4Packet,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct Udp4Protocol {
+    pub get_mode_data: unsafe extern "efiapi" fn(
+        this: *const Self,
+        udp4_config_data: *mut Udp4ConfigData,
+        ip4_mode_data: *mut c_void,
+        mnp_config_data: *mut c_void,
+        snp_mode_data: *mut c_void,
+    ) -> Status,
+    pub configure: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        udp_config_data: *const Udp4ConfigData,
+    ) -> Status,
+    pub groups: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        join_flag: bool,
+        multicast_address: *const Ipv4Address,
+    ) -> Status,
+    pub routes: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        delete_route: bool,
+        subnet_address: *const Ipv4Address,
+        subnet_mask: *const Ipv4Address,
+        gateway_address: *const Ipv4Address,
+    ) -> Status,
+    pub transmit:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Udp4CompletionToken) -> Status,
+    pub receive:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Udp4CompletionToken) -> Status,
+    pub cancel:
+        unsafe extern "efiapi" fn(this: *mut Self, token: *mut Udp4CompletionToken) -> Status,
+    pub poll: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+}
+
+impl Udp4Protocol {
+    pub const GUID: Guid = guid!("3ad9df29-4501-478d-b1f8-7f7fe70e50f3");
+    pub const SERVICE_BINDING_GUID: Guid = guid!("83f01464-99bd-45e5-b383-af6305d8e9e6");
+}
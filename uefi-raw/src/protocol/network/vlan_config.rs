@@ -0,0 +1,25 @@
+use crate::{guid, Guid, Status};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct VlanFindData {
+    pub vlan_id: u16,
+    pub priority: u8,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct VlanConfigProtocol {
+    pub set: unsafe extern "efiapi" fn(this: *mut Self, vlan_id: u16, priority: u8) -> Status,
+    pub find: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        vlan_id: *const u16,
+        number_of_vlan: *mut u16,
+        entries: *mut *mut VlanFindData,
+    ) -> Status,
+    pub remove: unsafe extern "efiapi" fn(this: *mut Self, vlan_id: u16) -> Status,
+}
+
+impl VlanConfigProtocol {
+    pub const GUID: Guid = guid!("9e23d768-d2f3-4366-9fc3-3a7aba864374");
+}
@@ -0,0 +1,134 @@
+use super::device_path::DevicePathProtocol;
+use crate::{guid, Event, Guid, Status};
+use bitflags::bitflags;
+use core::ffi::c_void;
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct NvmExpressPassThruAttributes: u32 {
+        const PHYSICAL = 0x0001;
+        const LOGICAL = 0x0002;
+        const NONBLOCKIO = 0x0004;
+        const CMD_SET_NVM = 0x0008;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct NvmExpressPassThruMode {
+    pub attributes: NvmExpressPassThruAttributes,
+    pub io_align: u32,
+    pub nvme_version: u32,
+}
+
+newtype_enum! {
+    pub enum NvmExpressQueueType: u8 => {
+        ADMIN = 0,
+        IO = 1,
+    }
+}
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct NvmExpressCommandFlags: u8 {
+        const CDW2_VALID = 0x01;
+        const CDW3_VALID = 0x02;
+        const CDW10_VALID = 0x04;
+        const CDW11_VALID = 0x08;
+        const CDW12_VALID = 0x10;
+        const CDW13_VALID = 0x20;
+        const CDW14_VALID = 0x40;
+        const CDW15_VALID = 0x80;
+    }
+}
+
+/// Command dword 0: the opcode in bits 0-7 and the fused operation in bits
+/// 8-9.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct NvmExpressCdw0(pub u32);
+
+impl NvmExpressCdw0 {
+    #[must_use]
+    pub const fn new(opcode: u8, fused_operation: u8) -> Self {
+        Self(opcode as u32 | ((fused_operation as u32 & 0b11) << 8))
+    }
+
+    #[must_use]
+    pub const fn opcode(self) -> u8 {
+        self.0 as u8
+    }
+
+    #[must_use]
+    pub const fn fused_operation(self) -> u8 {
+        ((self.0 >> 8) & 0b11) as u8
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct NvmExpressCommand {
+    pub cdw0: NvmExpressCdw0,
+    pub flags: NvmExpressCommandFlags,
+    pub nsid: u32,
+    pub cdw2: u32,
+    pub cdw3: u32,
+    pub cdw10: u32,
+    pub cdw11: u32,
+    pub cdw12: u32,
+    pub cdw13: u32,
+    pub cdw14: u32,
+    pub cdw15: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct NvmExpressCompletion {
+    pub dw0: u32,
+    pub dw1: u32,
+    pub dw2: u32,
+    pub dw3: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct NvmExpressPassThruCommandPacket {
+    pub command_timeout: u64,
+    pub transfer_buffer: *mut c_void,
+    pub transfer_length: u32,
+    pub metadata_buffer: *mut c_void,
+    pub metadata_length: u32,
+    pub queue_type: NvmExpressQueueType,
+    pub nvme_cmd: *const NvmExpressCommand,
+    pub nvme_completion: *mut NvmExpressCompletion,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct NvmExpressPassThruProtocol {
+    pub mode: *const NvmExpressPassThruMode,
+    pub pass_thru: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        namespace_id: u32,
+        packet: *mut NvmExpressPassThruCommandPacket,
+        event: Event,
+    ) -> Status,
+    pub get_next_namespace:
+        unsafe extern "efiapi" fn(this: *const Self, namespace_id: *mut u32) -> Status,
+    pub build_device_path: unsafe extern "efiapi" fn(
+        this: *const Self,
+        namespace_id: u32,
+        device_path: *mut *mut DevicePathProtocol,
+    ) -> Status,
+    pub get_namespace: unsafe extern "efiapi" fn(
+        this: *const Self,
+        device_path: *const DevicePathProtocol,
+        namespace_id: *mut u32,
+    ) -> Status,
+}
+
+impl NvmExpressPassThruProtocol {
+    pub const GUID: Guid = guid!("52c78312-8edc-4233-98f2-1a1aa5e388a5");
+}
@@ -0,0 +1,118 @@
+use super::device_path::DevicePathProtocol;
+use crate::{guid, Event, Guid, Status};
+use bitflags::bitflags;
+use core::ffi::c_void;
+
+/// Maximum size of a SCSI target address in bytes.
+pub const TARGET_MAX_BYTES: usize = 0x10;
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct ExtScsiPassThruAttributes: u32 {
+        const PHYSICAL = 0x0001;
+        const LOGICAL = 0x0002;
+        const NONBLOCKIO = 0x0004;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct ExtScsiPassThruMode {
+    pub adapter_id: u32,
+    pub attributes: ExtScsiPassThruAttributes,
+    pub io_align: u32,
+}
+
+newtype_enum! {
+    pub enum ExtScsiDataDirection: u8 => {
+        READ = 0,
+        WRITE = 1,
+        BIDIRECTIONAL = 2,
+    }
+}
+
+newtype_enum! {
+    pub enum HostAdapterStatus: u8 => {
+        OK = 0x00,
+        TIMEOUT_COMMAND = 0x09,
+        TIMEOUT = 0x0b,
+        MESSAGE_REJECT = 0x0d,
+        BUS_RESET = 0x0e,
+        PARITY_ERROR = 0x0f,
+        REQUEST_SENSE_FAILED = 0x10,
+        SELECTION_TIMEOUT = 0x11,
+        DATA_OVERRUN_UNDERRUN = 0x12,
+        BUS_FREE = 0x13,
+        PHASE_ERROR = 0x14,
+        OTHER = 0x7f,
+    }
+}
+
+newtype_enum! {
+    pub enum TargetStatus: u8 => {
+        GOOD = 0x00,
+        CHECK_CONDITION = 0x02,
+        CONDITION_MET = 0x04,
+        BUSY = 0x08,
+        INTERMEDIATE = 0x10,
+        INTERMEDIATE_CONDITION_MET = 0x14,
+        RESERVATION_CONFLICT = 0x18,
+        TASK_SET_FULL = 0x28,
+        ACA_ACTIVE = 0x30,
+        TASK_ABORTED = 0x40,
+    }
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ExtScsiPassThruScsiRequestPacket {
+    pub timeout: u64,
+    pub in_data_buffer: *mut c_void,
+    pub out_data_buffer: *const c_void,
+    pub sense_data: *mut c_void,
+    pub cdb: *const c_void,
+    pub in_transfer_length: u32,
+    pub out_transfer_length: u32,
+    pub cdb_length: u8,
+    pub data_direction: ExtScsiDataDirection,
+    pub host_adapter_status: HostAdapterStatus,
+    pub target_status: TargetStatus,
+    pub sense_data_length: u8,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct ExtScsiPassThruProtocol {
+    pub mode: *const ExtScsiPassThruMode,
+    pub pass_thru: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        target: *const u8,
+        lun: u64,
+        packet: *mut ExtScsiPassThruScsiRequestPacket,
+        event: Event,
+    ) -> Status,
+    pub get_next_target_lun:
+        unsafe extern "efiapi" fn(this: *const Self, target: *mut *mut u8, lun: *mut u64) -> Status,
+    pub build_device_path: unsafe extern "efiapi" fn(
+        this: *const Self,
+        target: *const u8,
+        lun: u64,
+        device_path: *mut *mut DevicePathProtocol,
+    ) -> Status,
+    pub get_target_lun: unsafe extern "efiapi" fn(
+        this: *const Self,
+        device_path: *const DevicePathProtocol,
+        target: *mut *mut u8,
+        lun: *mut u64,
+    ) -> Status,
+    pub reset_channel: unsafe extern "efiapi" fn(this: *mut Self) -> Status,
+    pub reset_target_lun:
+        unsafe extern "efiapi" fn(this: *mut Self, target: *const u8, lun: u64) -> Status,
+    pub get_next_target:
+        unsafe extern "efiapi" fn(this: *const Self, target: *mut *mut u8) -> Status,
+}
+
+impl ExtScsiPassThruProtocol {
+    pub const GUID: Guid = guid!("143b7632-b81b-4cb7-abd3-b625a5b9bffe");
+}
@@ -0,0 +1,85 @@
+use super::device_path::DevicePathProtocol;
+use crate::{guid, Event, Guid, Status};
+use core::ffi::c_void;
+
+newtype_enum! {
+    pub enum SdMmcCommandType: u32 => {
+        BC = 0,
+        BCR = 1,
+        AC = 2,
+        ADTC = 3,
+    }
+}
+
+newtype_enum! {
+    pub enum SdMmcResponseType: u32 => {
+        R1 = 0,
+        R1B = 1,
+        R2 = 2,
+        R3 = 3,
+        R4 = 4,
+        R5 = 5,
+        R5B = 6,
+        R6 = 7,
+        R7 = 8,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct SdMmcCommandBlock {
+    pub command_index: u16,
+    pub command_argument: u32,
+    pub command_type: SdMmcCommandType,
+    pub response_type: SdMmcResponseType,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct SdMmcStatusBlock {
+    pub resp0: u32,
+    pub resp1: u32,
+    pub resp2: u32,
+    pub resp3: u32,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct SdMmcPassThruCommandPacket {
+    pub sd_mmc_cmd_blk: *mut SdMmcCommandBlock,
+    pub sd_mmc_status_blk: *mut SdMmcStatusBlock,
+    pub timeout: u64,
+    pub in_data_buffer: *mut c_void,
+    pub out_data_buffer: *mut c_void,
+    pub in_transfer_length: u32,
+    pub out_transfer_length: u32,
+    pub transaction_status: Status,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct SdMmcPassThruProtocol {
+    pub io_align: usize,
+    pub pass_thru: unsafe extern "efiapi" fn(
+        this: *mut Self,
+        slot: u8,
+        packet: *mut SdMmcPassThruCommandPacket,
+        event: Event,
+    ) -> Status,
+    pub get_next_slot: unsafe extern "efiapi" fn(this: *const Self, slot: *mut u8) -> Status,
+    pub build_device_path: unsafe extern "efiapi" fn(
+        this: *const Self,
+        slot: u8,
+        device_path: *mut *mut DevicePathProtocol,
+    ) -> Status,
+    pub get_slot_number: unsafe extern "efiapi" fn(
+        this: *const Self,
+        device_path: *const DevicePathProtocol,
+        slot: *mut u8,
+    ) -> Status,
+    pub reset_device: unsafe extern "efiapi" fn(this: *mut Self, slot: u8) -> Status,
+}
+
+impl SdMmcPassThruProtocol {
+    pub const GUID: Guid = guid!("716ef0d9-ff83-4f69-81e9-518bd39a8e70");
+}
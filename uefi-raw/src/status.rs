@@ -94,6 +94,13 @@ pub enum Status: usize => {
     IP_ADDRESS_CONFLICT     = Self::ERROR_BIT | 34,
     /// A HTTP error occurred during the network operation.
     HTTP_ERROR              = Self::ERROR_BIT | 35,
+    /// The receiving or transmission operation fails because this connection
+    /// is being closed by the remote peer.
+    CONNECTION_FIN          = Self::ERROR_BIT | 104,
+    /// The connection was reset by the remote peer.
+    CONNECTION_RESET        = Self::ERROR_BIT | 105,
+    /// The connection was refused by the remote peer.
+    CONNECTION_REFUSED      = Self::ERROR_BIT | 106,
 }}
 
 impl Status {
@@ -192,7 +192,8 @@ pub struct BootServices {
     // Multi-protocol handlers
     pub install_multiple_protocol_interfaces:
         unsafe extern "efiapi" fn(handle: *mut Handle, ...) -> Status,
-    pub uninstall_multiple_protocol_interfaces: unsafe extern "efiapi" fn(handle: Handle, ...) -> Status,
+    pub uninstall_multiple_protocol_interfaces:
+        unsafe extern "efiapi" fn(handle: Handle, ...) -> Status,
 
     // CRC services
     pub calculate_crc32:
@@ -436,6 +437,27 @@ pub struct OpenProtocolInformationEntry {
     pub open_count: u32,
 }
 
+bitflags! {
+    /// Attributes of `open_protocol`, describing how a protocol interface is
+    /// opened. These are the bits of `OpenProtocolInformationEntry::attributes`.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[repr(transparent)]
+    pub struct OpenProtocolFlags: u32 {
+        /// Opened by `handle_protocol`.
+        const BY_HANDLE_PROTOCOL = 0x01;
+        /// Opened to get the interface, without tracking.
+        const GET_PROTOCOL = 0x02;
+        /// Only tested for support; this does not open the protocol.
+        const TEST_PROTOCOL = 0x04;
+        /// Opened by a bus driver for one of its child controllers.
+        const BY_CHILD_CONTROLLER = 0x08;
+        /// Opened by a driver managing the controller.
+        const BY_DRIVER = 0x10;
+        /// Opened exclusively.
+        const EXCLUSIVE = 0x20;
+    }
+}
+
 newtype_enum! {
 /// Task priority level.
 ///
@@ -1,12 +1,20 @@
 use alloc::vec::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use uefi::allocator::{MemoryTypeAllocator, PageAllocator};
 use uefi::boot::{self, AllocateType};
-use uefi::mem::memory_map::{MemoryMap, MemoryMapMut, MemoryType};
+use uefi::mem::memory_map::{MemoryMap, MemoryMapMut, MemoryMapOwned, MemoryMapRef, MemoryType};
+use uefi::mem::tracking::{self, AllocationKind};
+use uefi::Status;
 
 pub fn test() {
     info!("Testing memory functions");
 
     test_allocate_pages();
+    test_allocate_constrained_pages();
     test_allocate_pool();
+    test_allocation_tracking();
+    test_page_allocator();
+    test_memory_type_allocator();
 
     vec_alloc();
     alloc_alignment();
@@ -31,6 +39,104 @@ fn test_allocate_pages() {
     unsafe { boot::free_pages(ptr, num_pages) }.unwrap();
 }
 
+fn test_allocate_constrained_pages() {
+    let ptr = boot::allocate_pages_below_4gib(MemoryType::LOADER_DATA, 2).unwrap();
+    assert!(ptr.as_ptr() as u64 + 2 * 4096 <= 0x1_0000_0000);
+    unsafe { boot::free_pages(ptr, 2) }.unwrap();
+
+    let align = 2 * 1024 * 1024;
+    let ptr =
+        boot::allocate_pages_aligned(AllocateType::AnyPages, MemoryType::LOADER_DATA, 3, align)
+            .unwrap();
+    assert_eq!(ptr.as_ptr() as usize % align, 0);
+    unsafe { ptr.as_ptr().add(3 * 4096 - 1).write_volatile(0xff) };
+    unsafe { boot::free_pages(ptr, 3) }.unwrap();
+
+    let range = 0x1_0000..0x10_0000;
+    let ptr =
+        boot::allocate_pages_in_range(range.clone(), MemoryType::LOADER_DATA, 1, 0x1000).unwrap();
+    let addr = ptr.as_ptr() as u64;
+    assert!(range.contains(&addr) && addr + 4096 <= range.end);
+    unsafe { boot::free_pages(ptr, 1) }.unwrap();
+}
+
+fn test_allocation_tracking() {
+    let storage = Vec::leak(vec![None; 64]);
+    tracking::enable(storage);
+
+    let ptr = {
+        let _tag = tracking::set_tag("test");
+        boot::allocate_pool(MemoryType::LOADER_DATA, 24).unwrap()
+    };
+    let mut found = None;
+    tracking::for_each_live(|allocation| {
+        if allocation.addr() == ptr.as_ptr() as u64 {
+            found = Some(*allocation);
+        }
+    });
+    let found = found.expect("allocation was not recorded");
+    assert_eq!(found.kind(), AllocationKind::Pool);
+    assert_eq!(found.size(), 24);
+    assert_eq!(found.tag(), Some("test"));
+    assert_eq!(found.location().file(), file!());
+
+    unsafe { boot::free_pool(ptr) }.unwrap();
+    tracking::for_each_live(|allocation| assert_ne!(allocation.addr(), ptr.as_ptr() as u64));
+
+    tracking::freeze();
+    let err = boot::allocate_pages(AllocateType::AnyPages, MemoryType::LOADER_DATA, 1).unwrap_err();
+    assert_eq!(err.status(), Status::ACCESS_DENIED);
+    tracking::unfreeze();
+    assert_eq!(tracking::denied_allocation().unwrap().file(), file!());
+
+    tracking::disable();
+}
+
+fn test_page_allocator() {
+    static ALLOCATOR: PageAllocator = PageAllocator::new();
+
+    let layouts = [
+        Layout::from_size_align(24, 8).unwrap(),
+        Layout::from_size_align(100, 64).unwrap(),
+        Layout::from_size_align(4096, 4096).unwrap(),
+        Layout::from_size_align(128 * 1024, 8).unwrap(),
+        Layout::from_size_align(64, 2 * 1024 * 1024).unwrap(),
+    ];
+    for _ in 0..2 {
+        let ptrs = layouts.map(|layout| {
+            let ptr = unsafe { ALLOCATOR.alloc(layout) };
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % layout.align(), 0);
+            unsafe { ptr.write_bytes(0xff, layout.size()) };
+            ptr
+        });
+        for (ptr, layout) in ptrs.into_iter().zip(layouts) {
+            unsafe { ALLOCATOR.dealloc(ptr, layout) };
+        }
+    }
+}
+
+fn test_memory_type_allocator() {
+    let allocator = MemoryTypeAllocator::new(MemoryType::ACPI_RECLAIM);
+    let layout = Layout::from_size_align(100, 64).unwrap();
+    let ptr = allocator.allocate(layout).unwrap();
+    assert_eq!(ptr.as_ptr() as usize % 64, 0);
+    unsafe { ptr.as_ptr().write_bytes(0xff, layout.size()) };
+
+    // The memory has the requested type.
+    let addr = ptr.as_ptr() as u64;
+    let memory_map = boot::memory_map(MemoryType::LOADER_DATA).unwrap();
+    let desc = memory_map
+        .entries()
+        .find(|desc| (desc.phys_start..desc.phys_start + desc.page_count * 4096).contains(&addr))
+        .unwrap();
+    assert_eq!(desc.ty, MemoryType::ACPI_RECLAIM);
+
+    unsafe { allocator.deallocate(ptr, layout) };
+    let empty = allocator.allocate(Layout::new::<()>()).unwrap();
+    unsafe { allocator.deallocate(empty, Layout::new::<()>()) };
+}
+
 fn test_allocate_pool() {
     let ptr = boot::allocate_pool(MemoryType::LOADER_DATA, 10).unwrap();
 
@@ -104,4 +210,47 @@ fn test_memory_map() {
     }
     let page_count = first_desc.page_count;
     assert!(page_count != 0, "Memory map entry has size zero");
+
+    // Merging keeps the total number of pages. The new map may differ from
+    // the old one in more than the merged descriptors, since getting it
+    // allocates memory.
+    let total_pages = |map: &MemoryMapOwned| map.entries().map(|d| d.page_count).sum::<u64>();
+    let mut merged =
+        boot::memory_map(MemoryType::LOADER_DATA).expect("Failed to retrieve UEFI memory map");
+    merged.merge();
+    assert!(merged.is_sorted());
+    assert_eq!(total_pages(&merged), total_pages(&memory_map));
+    let diff = memory_map.diff(&merged);
+    assert_eq!(
+        memory_map.len() + diff.added().count(),
+        merged.len() + diff.removed().count()
+    );
+    assert!(merged.diff(&merged).is_empty());
+
+    let stats = merged.stats();
+    info!(
+        "Installed RAM: {} MiB, free: {} MiB",
+        stats.ram_pages() / 256,
+        stats.conventional_pages() / 256
+    );
+    assert_eq!(stats.total_pages(), total_pages(&merged));
+    assert!(stats.usable_pages() >= stats.conventional_pages());
+    let (_, largest_free) = stats.largest_free_region().unwrap();
+    assert!(largest_free <= stats.conventional_pages());
+
+    // Serialize the map like a bootloader would for its kernel, and parse it
+    // again without copying.
+    let mut buffer = vec![0u64; merged.serialized_size().div_ceil(8)];
+    // SAFETY: the buffer is valid for `size_of_val` bytes.
+    let bytes = unsafe {
+        core::slice::from_raw_parts_mut(
+            buffer.as_mut_ptr().cast::<u8>(),
+            core::mem::size_of_val(buffer.as_slice()),
+        )
+    };
+    let size = merged.serialize(bytes).unwrap();
+    let parsed = MemoryMapRef::from_serialized(&bytes[..size]).unwrap();
+    assert_eq!(parsed.meta().desc_size, merged.meta().desc_size);
+    assert_eq!(parsed.meta().desc_version, merged.meta().desc_version);
+    assert!(parsed.entries().eq(merged.entries()));
 }
@@ -1,21 +1,39 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cell::RefCell;
 use core::ffi::c_void;
 use core::mem;
+use core::pin::Pin;
 use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
 
 use uefi::boot::{
-    EventType, OpenProtocolAttributes, OpenProtocolParams, SearchType, TimerTrigger, Tpl,
+    EventGroup, EventType, Instant, OpenProtocolAttributes, OpenProtocolParams, ProtocolWatcher,
+    SearchType, Timer, TimerTrigger, Tpl, Watchdog,
 };
+use uefi::executor::{self, Executor};
+use uefi::helpers;
 use uefi::mem::memory_map::MemoryType;
 use uefi::proto::unsafe_protocol;
-use uefi::{boot, guid, system, Event, Guid, Identify};
+use uefi::{boot, guid, system, Event, Guid, Identify, Status};
 
 pub fn test() {
     test_tpl();
     info!("Testing timer...");
     test_timer();
+    test_timer_callback();
+    test_executor();
+    test_poll_until();
+    test_instant();
     info!("Testing events...");
     test_check_event();
     test_callback_with_ctx();
+    test_event_group();
     info!("Testing watchdog...");
     test_watchdog();
     info!("Testing protocol handler services...");
@@ -23,13 +41,49 @@ pub fn test() {
     test_install_protocol_interface();
     test_reinstall_protocol_interface();
     test_uninstall_protocol_interface();
+    test_install_protocol();
+    test_protocol_watcher();
     test_install_configuration_table();
+    info!("Testing driver connection...");
+    test_connect_all_controllers();
 }
 
 fn test_tpl() {
-    info!("Testing watchdog...");
-    // There's no way to query the TPL, so we can't assert that this does anything.
-    let _guard = unsafe { boot::raise_tpl(Tpl::NOTIFY) };
+    info!("Testing TPL guards...");
+    assert_eq!(boot::current_tpl(), Tpl::APPLICATION);
+    {
+        let guard = boot::raise_tpl(Tpl::CALLBACK);
+        assert_eq!(guard.old_tpl(), Tpl::APPLICATION);
+        assert_eq!(boot::current_tpl(), Tpl::CALLBACK);
+        {
+            let _guard = boot::raise_tpl(Tpl::NOTIFY);
+            assert_eq!(boot::current_tpl(), Tpl::NOTIFY);
+            // Allocation is still allowed at this level.
+            drop(Box::new(1u8));
+
+            // Lowering the level with a guard has no effect.
+            let guard = boot::raise_tpl(Tpl::APPLICATION);
+            assert_eq!(guard.old_tpl(), Tpl::NOTIFY);
+            assert_eq!(boot::current_tpl(), Tpl::NOTIFY);
+            drop(guard);
+
+            // The global allocator fails above `Tpl::NOTIFY`.
+            let _guard = unsafe { boot::raise_tpl_unchecked(Tpl::HIGH_LEVEL) };
+            let ptr = unsafe { alloc::alloc::alloc(Layout::new::<u64>()) };
+            assert!(ptr.is_null());
+        }
+        assert_eq!(boot::current_tpl(), Tpl::CALLBACK);
+    }
+    assert_eq!(boot::current_tpl(), Tpl::APPLICATION);
+
+    // Dropping guards out of order does not raise the level again.
+    let outer = boot::raise_tpl(Tpl::CALLBACK);
+    let inner = boot::raise_tpl(Tpl::NOTIFY);
+    drop(outer);
+    assert_eq!(boot::current_tpl(), Tpl::APPLICATION);
+    drop(inner);
+    assert_eq!(boot::current_tpl(), Tpl::APPLICATION);
+    drop(Box::new(1u8));
 }
 
 fn test_check_event() {
@@ -59,6 +113,96 @@ fn test_timer() {
     boot::close_event(timer_event).unwrap();
 }
 
+fn test_timer_callback() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let timer = {
+        let count = count.clone();
+        Timer::new(Tpl::CALLBACK, move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap()
+    };
+
+    timer.set_relative(Duration::from_millis(1)).unwrap();
+    boot::stall(50_000);
+    assert_eq!(count.load(Ordering::Relaxed), 1);
+
+    timer.set_periodic(Duration::from_millis(10)).unwrap();
+    boot::stall(100_000);
+    assert!(count.load(Ordering::Relaxed) >= 3);
+
+    timer.cancel().unwrap();
+    let stopped = count.load(Ordering::Relaxed);
+    boot::stall(50_000);
+    assert_eq!(count.load(Ordering::Relaxed), stopped);
+}
+
+fn test_executor() {
+    info!("Testing executor...");
+    assert_eq!(
+        executor::block_on(async {
+            executor::sleep(Duration::from_millis(5)).await.unwrap();
+            1
+        }),
+        1
+    );
+
+    // Tasks waiting for events run concurrently.
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let mut tasks = Executor::new();
+    for (id, delay) in [(0, 30), (1, 10)] {
+        let order = order.clone();
+        tasks.spawn(async move {
+            executor::sleep(Duration::from_millis(delay)).await.unwrap();
+            order.borrow_mut().push(id);
+        });
+    }
+    let event =
+        unsafe { boot::create_event(EventType::empty(), Tpl::CALLBACK, None, None) }.unwrap();
+    {
+        let event = unsafe { event.unsafe_clone() };
+        let order = order.clone();
+        tasks.spawn(async move {
+            executor::signaled(&event).await.unwrap();
+            order.borrow_mut().push(2);
+        });
+    }
+    {
+        let event = unsafe { event.unsafe_clone() };
+        tasks.spawn(async move {
+            executor::sleep(Duration::from_millis(20)).await.unwrap();
+            boot::signal_event(&event).unwrap();
+        });
+    }
+    tasks.run();
+    assert_eq!(*order.borrow(), [1, 2, 0]);
+
+    // The event is not signaled anymore.
+    let result = executor::block_on(executor::timeout(
+        Duration::from_millis(10),
+        executor::signaled(&event),
+    ));
+    assert_eq!(result.unwrap_err().status(), Status::TIMEOUT);
+    let result = executor::block_on(executor::timeout(
+        Duration::from_millis(100),
+        executor::sleep(Duration::from_millis(1)),
+    ));
+    assert!(matches!(result, Ok(Ok(()))));
+
+    // The timer of a timed out future is closed when it is dropped, and the
+    // task can wait for other events afterwards.
+    executor::block_on(async {
+        let result = executor::timeout(
+            Duration::from_millis(1),
+            executor::sleep(Duration::from_secs(5)),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().status(), Status::TIMEOUT);
+        executor::sleep(Duration::from_millis(5)).await.unwrap();
+    });
+    boot::close_event(event).unwrap();
+}
+
 fn test_callback_with_ctx() {
     let mut data = 123u32;
 
@@ -91,11 +235,88 @@ fn test_callback_with_ctx() {
     assert_eq!(data, 456);
 }
 
+fn test_poll_until() {
+    boot::stall_for(Duration::from_micros(1500));
+
+    let mut calls = 0;
+    let value = boot::poll_until(Duration::from_micros(10), Duration::from_millis(1), || {
+        calls += 1;
+        (calls == 3).then_some(calls)
+    })
+    .unwrap();
+    assert_eq!(value, 3);
+
+    // The closure is called once, and then after each of the 10 stalls.
+    calls = 0;
+    let err = boot::poll_until(Duration::from_micros(100), Duration::from_millis(1), || {
+        calls += 1;
+        None::<()>
+    })
+    .unwrap_err();
+    assert_eq!(err.status(), Status::TIMEOUT);
+    assert_eq!(calls, 11);
+}
+
+fn test_instant() {
+    let first = boot::get_next_monotonic_count().unwrap();
+    assert!(boot::get_next_monotonic_count().unwrap() > first);
+
+    let start = Instant::now();
+    boot::stall(10_000);
+    let end = Instant::now();
+    if let Some(elapsed) = end.duration_since(start) {
+        info!("Stalling for 10ms took {elapsed:?}");
+        assert!(elapsed >= Duration::from_millis(5));
+    } else {
+        assert_eq!(start.frequency(), None);
+        assert_ne!(start, end);
+    }
+}
+
 fn test_watchdog() {
     // There's no way to check the watchdog timer value, so just test setting it.
 
     // Disable the UEFI watchdog timer.
     boot::set_watchdog_timer(0, 0x10000, None).expect("Could not set watchdog timer");
+
+    // Arm the watchdog, refresh it, and disable it again when dropped.
+    let mut watchdog =
+        Watchdog::arm(Duration::from_millis(600_500), 0x10000).expect("Could not arm watchdog");
+    assert_eq!(watchdog.timeout(), Duration::from_secs(601));
+    watchdog.refresh().expect("Could not refresh watchdog");
+    watchdog
+        .auto_refresh(Duration::from_millis(10))
+        .expect("Could not refresh watchdog automatically");
+    boot::stall(50_000);
+    watchdog.stop_auto_refresh();
+    drop(watchdog);
+}
+
+fn test_event_group() {
+    const GROUP: EventGroup = EventGroup(guid!("a1e3d7f6-54b5-4c8e-9f0d-1b6a2e7c3d49"));
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let event = boot::create_notify_event(Tpl::CALLBACK, Some(GROUP), || {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+    })
+    .unwrap();
+
+    boot::signal_event_group(&GROUP).unwrap();
+    assert_eq!(COUNT.load(Ordering::Relaxed), 1);
+    boot::signal_event(event.event()).unwrap();
+    assert_eq!(COUNT.load(Ordering::Relaxed), 2);
+
+    // The closure is not called after the event is dropped.
+    drop(event);
+    boot::signal_event_group(&GROUP).unwrap();
+    assert_eq!(COUNT.load(Ordering::Relaxed), 2);
+
+    assert_eq!(
+        boot::create_notify_event(Tpl::CALLBACK, Some(EventGroup::EXIT_BOOT_SERVICES), || {})
+            .unwrap_err()
+            .status(),
+        Status::INVALID_PARAMETER
+    );
 }
 
 /// Dummy protocol for tests
@@ -185,6 +406,128 @@ fn test_uninstall_protocol_interface() {
     }
 }
 
+/// Dummy protocol for the safe install tests
+#[derive(Debug)]
+#[unsafe_protocol("0c5ad2f4-6b43-4e2e-a0b9-25f3e7b6a3d1")]
+struct SafeTestProtocol {
+    data: u32,
+}
+
+fn test_install_protocol() {
+    info!("Installing SafeTestProtocol");
+    let first = Pin::static_mut(Box::leak(Box::new(SafeTestProtocol { data: 1 })));
+    let installed = boot::install_protocol(None, first).unwrap();
+    let handle = installed.handle();
+    assert_eq!(
+        boot::locate_handle_buffer(SearchType::from_proto::<SafeTestProtocol>()).unwrap()[0],
+        handle
+    );
+
+    // Installing the protocol on the same handle again is refused, and the
+    // interface is returned.
+    let other = Pin::static_mut(Box::leak(Box::new(SafeTestProtocol { data: 3 })));
+    let err = boot::install_protocol(Some(handle), other).unwrap_err();
+    assert_eq!(err.status(), Status::INVALID_PARAMETER);
+    assert_eq!(err.data().data, 3);
+
+    info!("Opening SafeTestProtocol by driver");
+    {
+        let driver = boot::image_handle();
+        let protocol = boot::open_protocol_by_driver::<SafeTestProtocol>(handle, driver).unwrap();
+        assert_eq!(protocol.data, 1);
+        assert_eq!(protocol.open_params().controller, Some(handle));
+        let err = boot::open_protocol_by_driver::<SafeTestProtocol>(handle, driver).unwrap_err();
+        assert_eq!(err.status(), Status::ALREADY_STARTED);
+
+        let child =
+            boot::open_protocol_by_child_controller::<SafeTestProtocol>(handle, driver, driver)
+                .unwrap();
+        assert_eq!(child.open_params().controller, Some(driver));
+
+        let info = boot::open_protocol_information(handle, &SafeTestProtocol::GUID).unwrap();
+        assert_eq!(info.len(), 2);
+        assert!(info.iter().all(|entry| entry.agent() == Some(driver)));
+        let entry = info
+            .iter()
+            .find(|entry| entry.controller() == Some(handle))
+            .unwrap();
+        assert_eq!(entry.attributes(), OpenProtocolAttributes::ByDriver.into());
+        assert_eq!(entry.open_count(), 1);
+
+        let mut dump = String::new();
+        helpers::dump_handle_database(&mut dump).unwrap();
+        assert!(dump.contains(&SafeTestProtocol::GUID.to_string()));
+        assert!(dump.contains("BY_CHILD_CONTROLLER"));
+    }
+    // The protocol was closed, so it can be opened again.
+    drop(
+        boot::open_protocol_by_driver_exclusive::<SafeTestProtocol>(handle, boot::image_handle())
+            .unwrap(),
+    );
+
+    info!("Reinstalling SafeTestProtocol");
+    let second = Pin::static_mut(Box::leak(Box::new(SafeTestProtocol { data: 2 })));
+    let (installed, old) = boot::reinstall_protocol(installed, second).unwrap();
+    assert_eq!(old.data, 1);
+    assert_eq!(installed.handle(), handle);
+    assert_eq!(unsafe { (*installed.as_ptr()).data }, 2);
+
+    info!("Uninstalling SafeTestProtocol");
+    let interface = boot::uninstall_protocol(installed).unwrap();
+    assert_eq!(interface.data, 2);
+    assert!(boot::locate_handle_buffer(SearchType::from_proto::<SafeTestProtocol>()).is_err());
+}
+
+fn test_protocol_watcher() {
+    static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe extern "efiapi" fn callback(_event: Event, _context: Option<NonNull<c_void>>) {
+        NOTIFIED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    info!("Watching for SafeTestProtocol");
+    let mut watcher = ProtocolWatcher::new::<SafeTestProtocol>().unwrap();
+    let notifier =
+        unsafe { ProtocolWatcher::with_callback::<SafeTestProtocol>(Tpl::NOTIFY, callback, None) }
+            .unwrap();
+    assert_eq!(watcher.next_handle().unwrap(), None);
+
+    let interface = Pin::static_mut(Box::leak(Box::new(SafeTestProtocol { data: 4 })));
+    let installed = boot::install_protocol(None, interface).unwrap();
+    assert_eq!(NOTIFIED.load(Ordering::Relaxed), 1);
+    assert!(boot::check_event(unsafe { watcher.event().unsafe_clone() }).unwrap());
+    assert_eq!(watcher.wait().unwrap(), installed.handle());
+    assert_eq!(watcher.next_handle().unwrap(), None);
+
+    // The handle is reported again when the interface is reinstalled.
+    let interface = Pin::static_mut(Box::leak(Box::new(SafeTestProtocol { data: 5 })));
+    let (installed, _) = boot::reinstall_protocol(installed, interface).unwrap();
+    assert_eq!(NOTIFIED.load(Ordering::Relaxed), 2);
+    assert_eq!(watcher.next_handle().unwrap(), Some(installed.handle()));
+
+    // No notifications after the watcher is dropped.
+    drop(notifier);
+    let interface = boot::uninstall_protocol(installed).unwrap();
+    let installed = boot::install_protocol(None, interface).unwrap();
+    assert_eq!(NOTIFIED.load(Ordering::Relaxed), 2);
+    assert_eq!(watcher.next_handle().unwrap(), Some(installed.handle()));
+    boot::uninstall_protocol(installed).unwrap();
+}
+
+fn test_connect_all_controllers() {
+    let count = boot::locate_handle_buffer(SearchType::AllHandles)
+        .unwrap()
+        .len();
+    boot::connect_all_controllers().expect("failed to connect controllers");
+    // Connecting drivers never removes handles.
+    assert!(
+        boot::locate_handle_buffer(SearchType::AllHandles)
+            .unwrap()
+            .len()
+            >= count
+    );
+}
+
 fn test_install_configuration_table() {
     // Get the current number of entries.
     let count = system::with_config_table(|t| t.len());
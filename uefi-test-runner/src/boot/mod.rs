@@ -1,11 +1,12 @@
 use alloc::string::ToString;
-use uefi::boot::{LoadImageSource, SearchType};
+use uefi::boot::{LaunchSource, LoadImageSource, SearchType};
 use uefi::fs::FileSystem;
 use uefi::proto::console::text::Output;
 use uefi::proto::device_path::media::FilePath;
 use uefi::proto::device_path::{DevicePath, LoadedImageDevicePath};
+use uefi::proto::loaded_image::LoadedImage;
 use uefi::proto::BootPolicy;
-use uefi::{boot, CString16, Identify};
+use uefi::{boot, cstr16, CString16, Identify, Status};
 
 mod memory;
 mod misc;
@@ -19,7 +20,7 @@ pub fn test() {
 }
 
 fn test_locate_handles() {
-    info!("Testing the `locate_handle_buffer`/`find_handles` functions");
+    info!("Testing the `locate_handle_buffer`/`find_handles`/`locate_handle_array` functions");
 
     {
         // search all handles
@@ -41,6 +42,15 @@ fn test_locate_handles() {
         // `boot::locate_handle` as well.
         let handles_vec = boot::find_handles::<Output>().unwrap();
         assert_eq!(*handles, handles_vec);
+
+        // Compare with `boot::locate_handle_array`.
+        let handles_array =
+            boot::locate_handle_array::<64>(SearchType::ByProtocol(&Output::GUID)).unwrap();
+        assert_eq!(*handles, *handles_array);
+        let err =
+            boot::locate_handle_array::<0>(SearchType::ByProtocol(&Output::GUID)).unwrap_err();
+        assert_eq!(err.status(), Status::BUFFER_TOO_SMALL);
+        assert_eq!(*err.data(), Some(handles.len()));
     }
 }
 
@@ -100,6 +110,19 @@ fn test_load_image() {
             boot::open_protocol_exclusive::<LoadedImageDevicePath>(loaded_image)
                 .expect("should open LoadedImageDevicePath protocol");
         assert!(loaded_image_device_path.get().is_none());
+
+        // Load the image again, with a device path and load options.
+        let load_source = LoadImageSource::FromBuffer {
+            buffer: image_data.as_slice(),
+            file_path: Some(image_device_path),
+        };
+        let options = cstr16!("test options");
+        let loaded_image =
+            boot::load_image_with_options(boot::image_handle(), load_source, options)
+                .expect("should load image with options");
+        let loaded_image_info = boot::open_protocol_exclusive::<LoadedImage>(loaded_image)
+            .expect("should open LoadedImage protocol");
+        assert_eq!(loaded_image_info.load_options_as_cstr16().unwrap(), options);
     }
     // Variant B: FromDevicePath
     {
@@ -111,4 +134,10 @@ fn test_load_image() {
 
         log::debug!("load_image with FromFilePath strategy works");
     }
+    // `launch` looks for the image on the boot device.
+    {
+        let path = cstr16!(r"\EFI\BOOT\MISSING.EFI");
+        let err = boot::launch(LaunchSource::Path(path), None).unwrap_err();
+        assert_eq!(err.status(), Status::NOT_FOUND);
+    }
 }
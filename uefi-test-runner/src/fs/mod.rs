@@ -2,16 +2,31 @@
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use uefi::boot::ScopedProtocol;
-use uefi::fs::{FileSystem, IoError, IoErrorContext, PathBuf};
+use core::fmt::Write;
+use uefi::boot::{self, ScopedProtocol};
+use uefi::fs::{
+    FileSystem, FileTimes, FileWriter, IoError, IoErrorContext, OpenOptions, PathBuf, PathError,
+    SeekFrom,
+};
+use uefi::proto::device_path::build::{self, DevicePathBuilder};
+use uefi::proto::device_path::DevicePath;
+use uefi::proto::media::file::{FileAttribute, FileMode};
 use uefi::proto::media::fs::SimpleFileSystem;
-use uefi::{cstr16, fs, Status};
+use uefi::runtime::{Daylight, Time, TimeParams};
+use uefi::{cstr16, fs, Handle, Status};
 
 /// Tests functionality from the `uefi::fs` module. This test relies on a
 /// working File System Protocol, which is tested at a dedicated place.
 pub fn test(sfs: ScopedProtocol<SimpleFileSystem>) -> Result<(), fs::Error> {
     let mut fs = FileSystem::new(sfs);
 
+    // test volume info
+    let volume_info = fs.volume_info()?;
+    assert!(volume_info.size > 0);
+    assert!(volume_info.free_space <= volume_info.size);
+    assert!(volume_info.block_size > 0);
+    assert!(!volume_info.read_only);
+
     // test create dir
     fs.create_dir(cstr16!("foo_dir"))?;
 
@@ -47,8 +62,16 @@ pub fn test(sfs: ScopedProtocol<SimpleFileSystem>) -> Result<(), fs::Error> {
         cstr16!("foo_dir\\1\\2\\3\\4\\5\\6\\7\\8\\foobar"),
         data_to_write,
     )?;
-    let boxinfo = fs.metadata(cstr16!("foo_dir\\1\\2\\3\\4\\5\\6\\7\\8\\foobar"))?;
-    assert_eq!(boxinfo.file_size(), data_to_write.len() as u64);
+    let metadata = fs.metadata(cstr16!("foo_dir\\1\\2\\3\\4\\5\\6\\7\\8\\foobar"))?;
+    assert_eq!(metadata.len(), data_to_write.len() as u64);
+    assert_eq!(metadata.file_name(), cstr16!("foobar"));
+    assert!(metadata.is_file());
+    assert!(!metadata.is_dir());
+    assert!(!metadata.is_read_only());
+    assert!(!metadata.is_hidden());
+    assert!(!metadata.is_system());
+    assert!(metadata.modified().is_some());
+    assert!(fs.metadata(cstr16!("foo_dir\\1\\2"))?.is_dir());
 
     // test remove dir all
     fs.remove_dir_all(cstr16!("foo_dir\\1"))?;
@@ -58,10 +81,398 @@ pub fn test(sfs: ScopedProtocol<SimpleFileSystem>) -> Result<(), fs::Error> {
     test_copy_error(&mut fs)?;
     test_copy_success(&mut fs)?;
     test_copy_success_chunks(&mut fs)?;
+    test_file_streaming(&mut fs)?;
+    test_walk(&mut fs)?;
+    test_rename(&mut fs)?;
+    test_open_options(&mut fs)?;
+    test_set_info(&mut fs)?;
+    test_no_alloc(&mut fs)?;
+    test_file_writer(&mut fs)?;
+    test_text(&mut fs)?;
+    test_dir_all(&mut fs)?;
+
+    Ok(())
+}
+
+/// Tests the API that doesn't allocate.
+fn test_no_alloc(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    fs.create_dir(cstr16!("foo_dir\\no_alloc"))?;
+    fs.write(cstr16!("foo_dir\\no_alloc\\data"), "no alloc")?;
+
+    let mut buffer = [0; 16];
+    let len = fs
+        .read_into(cstr16!("foo_dir\\no_alloc\\data"), &mut buffer)
+        .unwrap();
+    assert_eq!(&buffer[..len], b"no alloc");
+    let err = fs
+        .read_into(cstr16!("foo_dir\\no_alloc\\data"), &mut buffer[..4])
+        .unwrap_err();
+    assert_eq!(err.status(), Status::BUFFER_TOO_SMALL);
+    assert_eq!(*err.data(), Some(8));
+    let err = fs
+        .read_into(cstr16!("foo_dir\\no_alloc"), &mut buffer)
+        .unwrap_err();
+    assert_eq!(err.status(), Status::INVALID_PARAMETER);
+
+    // A buffer that is too small for an entry doesn't skip it.
+    let mut small = [0; 8];
+    let mut reader = fs
+        .read_dir_with_buffer(cstr16!("foo_dir\\no_alloc"), &mut small)
+        .unwrap();
+    let err = reader.next_entry().unwrap_err();
+    assert_eq!(err.status(), Status::BUFFER_TOO_SMALL);
+    let dir = reader.into_inner();
+
+    let mut buffer = [0; 1024];
+    let mut reader = fs::DirectoryReader::new(dir, &mut buffer);
+    let mut names = Vec::new();
+    while let Some(info) = reader.next_entry().unwrap() {
+        names.push(info.file_name().to_string());
+    }
+    assert_eq!(names, [".", "..", "data"]);
+
+    fs.remove_dir_all(cstr16!("foo_dir\\no_alloc"))
+}
+
+/// Tests writing formatted text with [`FileWriter`].
+fn test_file_writer(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let path = cstr16!("foo_dir\\log.txt");
+    fs.write(path, "")?;
+    let file = fs
+        .open_handle(path, FileMode::ReadWrite)
+        .unwrap()
+        .into_regular_file()
+        .unwrap();
+
+    // A small buffer, so that it is written in between.
+    let mut log = FileWriter::<8>::new(file);
+    for i in 0..3 {
+        writeln!(log, "line {i}").unwrap();
+    }
+    assert_eq!(log.buffered(), 5);
+    log.flush().unwrap();
+    assert_eq!(log.buffered(), 0);
+    write!(log, "end").unwrap();
+    drop(log.into_inner().unwrap());
+
+    assert_eq!(fs.read_to_string(path)?, "line 0\nline 1\nline 2\nend");
+    fs.remove_file(path)
+}
+
+/// Tests reading and writing text files with different encodings.
+fn test_text(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let path = cstr16!("foo_dir\\text.txt");
+    fs.write_str(path, "key=\u{e4}")?;
+    assert_eq!(fs.read(path)?, "key=\u{e4}".as_bytes());
+    assert_eq!(fs.read_to_string(path)?, "key=\u{e4}");
+
+    // Longer than the buffer used for encoding.
+    let text = "0123456789\u{1f600}".repeat(100);
+    fs.write_str_utf16(path, &text)?;
+    let content = fs.read(path)?;
+    assert_eq!(content[..2], [0xff, 0xfe]);
+    assert_eq!(content.len(), 2 + 2 * text.encode_utf16().count());
+    assert_eq!(fs.read_to_string(path)?, text);
+
+    fs.write(path, b"\xef\xbb\xbfkey")?;
+    assert_eq!(fs.read_to_string(path)?, "key");
+    fs.remove_file(path)
+}
+
+/// Tests the edge cases of `create_dir_all` and `remove_dir_all`.
+fn test_dir_all(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    // Existing components are kept.
+    fs.create_dir_all(cstr16!("foo_dir\\all\\a"))?;
+    fs.write(cstr16!("foo_dir\\all\\a\\file"), "file")?;
+    fs.create_dir_all(cstr16!("\\foo_dir\\all\\a\\b\\c"))?;
+    assert!(fs.try_exists(cstr16!("foo_dir\\all\\a\\file"))?);
+    assert!(fs.metadata(cstr16!("foo_dir\\all\\a\\b\\c"))?.is_dir());
+
+    // A file can't be a component.
+    let err = fs
+        .create_dir_all(cstr16!("foo_dir\\all\\a\\file\\d"))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::NotADirectory,
+            ..
+        })
+    ));
 
+    // Names that FAT file systems would change are rejected.
+    let err = fs.create_dir_all(cstr16!("foo_dir\\all\\e.")).unwrap_err();
+    assert!(matches!(err, fs::Error::Path(PathError::TrailingChar(_))));
+    assert!(!fs.try_exists(cstr16!("foo_dir\\all\\e"))?);
+
+    let err = fs
+        .remove_dir_all(cstr16!("foo_dir\\all\\a\\file"))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::NotADirectory,
+            ..
+        })
+    ));
+    fs.remove_dir_all(cstr16!("foo_dir\\all"))?;
+    assert!(!fs.try_exists(cstr16!("foo_dir\\all"))?);
     Ok(())
 }
 
+/// Tests finding the volume of the test disk, whose handle is `handle`, by
+/// its label and by a device path.
+pub fn test_volume_search(handle: Handle) -> Result<(), fs::Error> {
+    let label = cstr16!("mbrtestdisk");
+    let mut fs = FileSystem::from_label(label).expect("test disk should be found");
+    assert_eq!(fs.volume_info()?.label, cstr16!("MbrTestDisk"));
+    let path = cstr16!("by_device_path");
+    fs.write(path, "found")?;
+    drop(fs);
+
+    let err = FileSystem::from_label(cstr16!("NoSuchDisk")).unwrap_err();
+    assert_eq!(err.status(), Status::NOT_FOUND);
+
+    let mut contents = Vec::new();
+    FileSystem::open_by_label(label, path)?.read_to_end(&mut contents)?;
+    assert_eq!(contents, b"found");
+
+    // Open the file with the device path of the disk and a file path node.
+    let disk_path = boot::open_protocol_exclusive::<DevicePath>(handle)
+        .unwrap()
+        .to_boxed();
+    let mut storage = Vec::new();
+    let mut builder = DevicePathBuilder::with_vec(&mut storage);
+    for node in disk_path.node_iter() {
+        builder = builder.push(&node).unwrap();
+    }
+    let file_path = builder
+        .push(&build::media::FilePath { path_name: path })
+        .unwrap()
+        .finalize()
+        .unwrap();
+    let mut contents = Vec::new();
+    FileSystem::open_device_path(file_path)?.read_to_end(&mut contents)?;
+    assert_eq!(contents, b"found");
+
+    FileSystem::from_label(label).unwrap().remove_file(path)
+}
+
+/// Test reading and writing a file in parts.
+fn test_file_streaming(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let path = cstr16!("foo_dir\\stream");
+    let mut file = fs.create_file(path)?;
+    file.write(b"hello ")?;
+    file.write(b"world")?;
+    assert_eq!(file.stream_position()?, 11);
+    assert_eq!(file.seek(SeekFrom::Start(6))?, 6);
+    file.write(b"W")?;
+    file.flush()?;
+    drop(file);
+
+    let mut file = fs.open_file(path)?;
+    assert_eq!(file.seek(SeekFrom::End(-5))?, 6);
+    let mut buffer = [0; 3];
+    assert_eq!(file.read(&mut buffer)?, 3);
+    assert_eq!(&buffer, b"Wor");
+    assert_eq!(file.seek(SeekFrom::Current(-9))?, 0);
+    let mut contents = Vec::new();
+    assert_eq!(file.read_to_end(&mut contents)?, 11);
+    assert_eq!(contents, b"hello World");
+    assert_eq!(file.metadata()?.len(), 11);
+
+    // Seeking before the start of the file fails.
+    let err = file.seek(SeekFrom::Current(-20)).unwrap_err();
+    assert!(matches!(
+        err,
+        fs::Error::Io(IoError {
+            context: IoErrorContext::SeekFailure,
+            ..
+        })
+    ));
+
+    // A reopened file has its own position.
+    file.seek(SeekFrom::Start(6))?;
+    let mut header = file.reopen(fs)?;
+    let mut buffer = [0; 5];
+    assert_eq!(header.read(&mut buffer)?, 5);
+    assert_eq!(&buffer, b"hello");
+    assert_eq!(file.read(&mut buffer)?, 5);
+    assert_eq!(&buffer, b"World");
+    drop(header);
+
+    // Directories cannot be opened as files.
+    assert!(fs.open_file(cstr16!("foo_dir")).is_err());
+
+    fs.remove_file(path)
+}
+
+/// Test setting the attributes and times of a file.
+fn test_set_info(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let path = cstr16!("foo_dir\\info");
+    fs.write(path, "info")?;
+
+    fs.set_attributes(path, FileAttribute::HIDDEN | FileAttribute::READ_ONLY)?;
+    let metadata = fs.metadata(path)?;
+    assert!(metadata.is_hidden());
+    assert!(metadata.is_read_only());
+    assert!(metadata.is_file());
+
+    // The attributes of read-only files can be changed too.
+    fs.set_attributes(path, FileAttribute::ARCHIVE)?;
+    let metadata = fs.metadata(path)?;
+    assert_eq!(metadata.attributes(), FileAttribute::ARCHIVE);
+
+    // FAT stores the modification time in units of two seconds.
+    let time = Time::new(TimeParams {
+        year: 2020,
+        month: 2,
+        day: 29,
+        hour: 12,
+        minute: 34,
+        second: 56,
+        nanosecond: 0,
+        time_zone: None,
+        daylight: Daylight::empty(),
+    })
+    .unwrap();
+    fs.set_times(path, FileTimes::new().set_modified(time))?;
+    let modified = fs.metadata(path)?.modified().unwrap();
+    assert_eq!(
+        (modified.year(), modified.month(), modified.day()),
+        (2020, 2, 29)
+    );
+    assert_eq!(
+        (modified.hour(), modified.minute(), modified.second()),
+        (12, 34, 56)
+    );
+
+    // Apply the times to a copy.
+    let copy_path = cstr16!("foo_dir\\info_copy");
+    fs.copy(path, copy_path)?;
+    let times = FileTimes::from(&fs.metadata(path)?);
+    fs.set_times(copy_path, times)?;
+    assert_eq!(fs.metadata(copy_path)?.modified(), Some(modified));
+
+    fs.remove_file(copy_path)?;
+    fs.remove_file(path)
+}
+
+/// Test opening files with `OpenOptions`.
+fn test_open_options(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    let path = cstr16!("foo_dir\\options");
+    let read = |fs: &mut FileSystem| -> Result<Vec<u8>, fs::Error> { fs.read(path) };
+
+    // The file is only created with `create` or `create_new`.
+    assert!(OpenOptions::new().write(true).open(fs, path).is_err());
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(fs, path)?
+        .write(b"hello")?;
+    assert_eq!(read(fs)?, b"hello");
+    let err = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(fs, path)
+        .unwrap_err();
+    assert!(
+        matches!(err, fs::Error::Io(IoError { uefi_error, .. }) if uefi_error.status() == Status::ACCESS_DENIED)
+    );
+
+    // Without truncating, the existing contents are overwritten in place.
+    OpenOptions::new().write(true).open(fs, path)?.write(b"J")?;
+    assert_eq!(read(fs)?, b"Jello");
+
+    // In append mode, writes go to the end regardless of the position.
+    let mut file = OpenOptions::new().append(true).open(fs, path)?;
+    file.write(b" world")?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write(b"!")?;
+    drop(file);
+    assert_eq!(read(fs)?, b"Jello world!");
+
+    OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(fs, path)?
+        .write(b"bye")?;
+    assert_eq!(read(fs)?, b"bye");
+
+    // Invalid combinations of options.
+    assert!(OpenOptions::new().open(fs, path).is_err());
+    assert!(OpenOptions::new()
+        .read(true)
+        .truncate(true)
+        .open(fs, path)
+        .is_err());
+
+    fs.remove_file(path)
+}
+
+/// Test walking a directory tree recursively.
+fn test_walk(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    fs.create_dir_all(cstr16!("foo_dir\\walk\\a\\b"))?;
+    fs.write(cstr16!("foo_dir\\walk\\top"), "top")?;
+    fs.write(cstr16!("foo_dir\\walk\\a\\b\\deep"), "deep")?;
+
+    let walk = |fs: &mut FileSystem, max_depth| -> Result<Vec<_>, fs::Error> {
+        let mut entries = fs
+            .walk(cstr16!("foo_dir\\walk"))?
+            .max_depth(max_depth)
+            .map(|entry| entry.map(|entry| (entry.path().to_string(), entry.depth())))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+        Ok(entries)
+    };
+    let expected = [
+        ("foo_dir\\walk\\a", 1),
+        ("foo_dir\\walk\\a\\b", 2),
+        ("foo_dir\\walk\\a\\b\\deep", 3),
+        ("foo_dir\\walk\\top", 1),
+    ]
+    .map(|(path, depth)| (path.to_string(), depth));
+    assert_eq!(walk(fs, usize::MAX)?, expected);
+    assert_eq!(walk(fs, 1)?, [expected[0].clone(), expected[3].clone()]);
+
+    // Walking a file fails.
+    assert!(fs.walk(cstr16!("foo_dir\\walk\\top")).is_err());
+
+    fs.remove_dir_all(cstr16!("foo_dir\\walk"))
+}
+
+/// Test renaming and moving files and directories.
+fn test_rename(fs: &mut FileSystem) -> Result<(), fs::Error> {
+    fs.create_dir_all(cstr16!("foo_dir\\rename\\sub"))?;
+    let path = cstr16!("foo_dir\\rename\\config");
+    let tmp_path = cstr16!("foo_dir\\rename\\config.tmp");
+    fs.write(path, "old")?;
+
+    // Replace a file by renaming a temporary file over it.
+    fs.write(tmp_path, "new")?;
+    fs.rename(tmp_path, path)?;
+    assert!(!fs.try_exists(tmp_path)?);
+    assert_eq!(fs.read(path)?, b"new");
+
+    // Move a file to another directory.
+    let moved_path = cstr16!("foo_dir\\rename\\sub\\config");
+    fs.rename(path, moved_path)?;
+    assert!(!fs.try_exists(path)?);
+    assert_eq!(fs.read(moved_path)?, b"new");
+
+    // Rename a directory with its contents.
+    fs.rename(
+        cstr16!("foo_dir\\rename\\sub"),
+        cstr16!("foo_dir\\rename\\sub2"),
+    )?;
+    assert_eq!(fs.read(cstr16!("foo_dir\\rename\\sub2\\config"))?, b"new");
+
+    // Renaming a missing file fails.
+    assert!(fs.rename(path, tmp_path).is_err());
+
+    fs.remove_dir_all(cstr16!("foo_dir\\rename"))
+}
+
 fn test_copy_error(fs: &mut FileSystem) -> Result<(), fs::Error> {
     let file1_path = cstr16!("file1");
     let dir_path = cstr16!("dir");
@@ -162,6 +573,25 @@ fn test_copy_success_chunks(fs: &mut FileSystem) -> Result<(), fs::Error> {
     assert_eq!(fs.read(file1_path)?, big_buf);
     assert_eq!(fs.read(file2_path)?, big_buf);
 
+    // Test that the progress is reported after each chunk.
+    let mut progress = Vec::new();
+    fs.copy_with_progress(file1_path, file2_path, chunk_size, |copied, total| {
+        progress.push((copied, total));
+    })?;
+    let total = big_buf.len() as u64;
+    let chunk_size = chunk_size as u64;
+    assert_eq!(
+        progress,
+        [
+            (chunk_size, total),
+            (2 * chunk_size, total),
+            (3 * chunk_size, total),
+            (4 * chunk_size, total),
+            (total, total),
+        ]
+    );
+    assert_eq!(fs.read(file2_path)?, big_buf);
+
     // Clean up temporary files.
     fs.remove_file(file1_path)?;
     fs.remove_file(file2_path)?;
@@ -8,12 +8,14 @@ extern crate alloc;
 
 use alloc::string::ToString;
 use alloc::vec::Vec;
+use uefi::boot::ExitBootServicesOptions;
+use uefi::helpers::{set_print_policy, PrintPolicy};
 use uefi::mem::memory_map::{MemoryMap, MemoryType};
 use uefi::prelude::*;
 use uefi::proto::console::serial::Serial;
 use uefi::proto::device_path::build::{self, DevicePathBuilder};
 use uefi::proto::device_path::messaging::Vendor;
-use uefi::{print, println, system, Result};
+use uefi::{eprintln, print, println, system, Result};
 
 mod boot;
 mod fs;
@@ -32,6 +34,9 @@ fn efi_main() -> Status {
         "Testing {} macro with formatting: {:#010b} ",
         println, 155u8
     );
+    set_print_policy(PrintPolicy::Ignore);
+    eprintln!("Testing eprintln! macro");
+    set_print_policy(PrintPolicy::Panic);
 
     // Reset the console before running all the other tests.
     system::with_stdout(|stdout| stdout.reset(false).expect("Failed to reset stdout"));
@@ -207,7 +212,17 @@ fn shutdown() -> ! {
     info!("Testing complete, exiting boot services...");
 
     // Exit boot services as a proof that it works :)
-    let mmap = unsafe { uefi::boot::exit_boot_services(MemoryType::LOADER_DATA) };
+    let mut hook_called = false;
+    let mut before_exit = || {
+        info!("Calling the hook before exiting boot services");
+        hook_called = true;
+    };
+    let options = ExitBootServicesOptions::new()
+        .memory_type(MemoryType::LOADER_DATA)
+        .attempts(3)
+        .before_exit(&mut before_exit);
+    let mmap = unsafe { uefi::boot::exit_boot_services_with_options(options) };
+    assert!(hook_called);
 
     info!("Memory Map:");
     for desc in mmap.entries() {
@@ -0,0 +1,62 @@
+use alloc::vec::Vec;
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use uefi::proto::ata::pass_thru::AtaPassThru;
+
+pub fn test() {
+    info!("Testing the ATA pass thru protocol");
+
+    let handles = boot::find_handles::<AtaPassThru>().unwrap_or_default();
+    // The disks of the aarch64 machine are attached to virtio controllers.
+    if cfg!(not(any(target_arch = "x86", target_arch = "x86_64"))) && handles.is_empty() {
+        info!("No ATA controller found, skipping test");
+        return;
+    }
+
+    let mut found = false;
+    for handle in handles {
+        // This cannot be opened in `EXCLUSIVE` mode, as doing so would
+        // disconnect the drivers of the disks used by other tests.
+        let mut ata = unsafe {
+            boot::open_protocol::<AtaPassThru>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .unwrap();
+
+        let ports: Vec<u16> = ata.ports().collect();
+        let devices: Vec<_> = ata.devices().collect();
+        assert!(devices.iter().all(|device| ports.contains(&device.port)));
+
+        for device in devices {
+            let identify = ata.identify_device(device).unwrap();
+            info!(
+                "ATA device {}/{}: {} ({} sectors)",
+                device.port,
+                device.port_multiplier_port,
+                identify.model_number(),
+                identify.sector_count()
+            );
+            if identify.model_number().as_bytes() != b"QEMU HARDDISK" {
+                continue;
+            }
+            found = true;
+            assert!(identify.sector_count() > 0);
+            assert_eq!(identify.logical_sector_size(), 512);
+
+            let device_path = ata.device_path(device).unwrap();
+            assert_eq!(ata.device(&device_path).unwrap(), device);
+
+            if identify.supports_smart() && identify.smart_enabled() {
+                let smart = ata.smart_data(device).unwrap();
+                assert!(smart.is_checksum_valid());
+                assert!(!ata.smart_threshold_exceeded(device).unwrap());
+            }
+        }
+    }
+    assert!(found, "no QEMU ATA disk found");
+}
@@ -1,6 +1,17 @@
 use crate::{send_request_to_host, HostRequest};
+use alloc::vec;
+use alloc::vec::Vec;
 use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
-use uefi::proto::console::gop::{BltOp, BltPixel, FrameBuffer, GraphicsOutput, PixelFormat};
+use uefi::proto::console::bmp::Bitmap;
+use uefi::proto::console::edid::EdidActive;
+use uefi::proto::console::font::TextRenderer;
+use uefi::proto::console::gop::{
+    BltOp, BltPixel, BltRegion, FrameBuffer, GraphicsOutput, ModeCriteria, PixelConverter,
+    PixelFormat,
+};
+use uefi::proto::console::pointer::SoftwareCursor;
+use uefi::proto::console::surface::Surface;
+use uefi::{Handle, Status};
 
 pub unsafe fn test() {
     info!("Running graphics output protocol test");
@@ -20,6 +31,7 @@ pub unsafe fn test() {
     .expect("failed to open Graphics Output Protocol");
 
     set_graphics_mode(gop);
+    select_mode(gop, handle);
     fill_color(gop);
     draw_fb(gop);
 
@@ -27,6 +39,13 @@ pub unsafe fn test() {
     if cfg!(not(target_arch = "aarch64")) {
         send_request_to_host(HostRequest::Screenshot("gop_test"));
     }
+
+    // Runs after the screenshot, as it draws over the screen.
+    draw_surface(gop);
+    draw_bitmap(gop);
+    draw_text(gop);
+    convert_pixels(gop);
+    draw_cursor(gop);
 }
 
 // Set a larger graphics mode.
@@ -43,6 +62,37 @@ fn set_graphics_mode(gop: &mut GraphicsOutput) {
     gop.set_mode(&mode).expect("Failed to set graphics mode");
 }
 
+// Select modes by criteria, without leaving the 1024x768 mode.
+fn select_mode(gop: &mut GraphicsOutput, handle: Handle) {
+    if let Ok(edid) = boot::open_protocol_exclusive::<EdidActive>(handle) {
+        let edid = edid.parse();
+        info!(
+            "Display {:?} with preferred resolution {:?}",
+            edid.and_then(|edid| edid.display_name()),
+            edid.and_then(|edid| edid.preferred_timing())
+                .map(|timing| timing.resolution())
+        );
+    }
+
+    let criteria = ModeCriteria::new().set_max_resolution((1024, 768));
+    let best = gop.best_mode(&criteria).unwrap();
+    assert_eq!(best.info().resolution(), (1024, 768));
+
+    // The current mode is kept, even though larger modes exist.
+    let criteria = ModeCriteria::new()
+        .set_min_resolution((800, 600))
+        .set_keep_current(true);
+    let mode = gop.set_best_mode(&criteria).unwrap();
+    assert_eq!(mode.info().resolution(), (1024, 768));
+    assert_eq!(gop.current_mode_info().resolution(), (1024, 768));
+
+    let criteria = ModeCriteria::new().set_min_resolution((100_000, 100_000));
+    assert_eq!(
+        gop.set_best_mode(&criteria).unwrap_err().status(),
+        Status::NOT_FOUND
+    );
+}
+
 // Fill the screen with color.
 fn fill_color(gop: &mut GraphicsOutput) {
     let op = BltOp::VideoFill {
@@ -102,3 +152,145 @@ fn draw_fb(gop: &mut GraphicsOutput) {
     fill_rectangle((50, 30), (150, 600), [250, 128, 64]);
     fill_rectangle((400, 120), (750, 450), [16, 128, 255]);
 }
+
+// Draw through a back buffer, partly outside of the screen.
+fn draw_surface(gop: &mut GraphicsOutput) {
+    let red = BltPixel::new(255, 0, 0);
+    let mut surface = Surface::new(64, 32);
+    surface.clear(BltPixel::new(0, 0, 0));
+    surface.fill_rect((8, 8), (8, 8), red);
+    surface.flush_at(gop, (1000, 750)).unwrap();
+    assert_eq!(surface.dirty_regions().count(), 0);
+
+    let mut buffer = vec![BltPixel::new(0, 0, 0); 24 * 18];
+    gop.blt(BltOp::VideoToBltBuffer {
+        buffer: &mut buffer,
+        src: (1000, 750),
+        dest: BltRegion::Full,
+        dims: (24, 18),
+    })
+    .unwrap();
+    assert_eq!(buffer[0], BltPixel::new(0, 0, 0));
+    assert_eq!(buffer[8 * 24 + 8], red);
+
+    // Only changed pixels are copied.
+    surface.set_pixel(0, 0, red);
+    assert_eq!(surface.dirty_regions().next(), Some(((0, 0), (1, 1))));
+    surface.flush_at(gop, (1000, 750)).unwrap();
+}
+
+// Draw a bitmap that is clipped at the corner of the screen.
+fn draw_bitmap(gop: &mut GraphicsOutput) {
+    let green = BltPixel::new(0, 255, 0);
+    let pixels = (0..8 * 8)
+        .map(|i| {
+            if i % 9 == 0 {
+                green
+            } else {
+                BltPixel::new(0, 0, 0)
+            }
+        })
+        .collect();
+    let bitmap = Bitmap::new(8, 8, pixels).unwrap();
+    bitmap.draw(gop, (1020, 764)).unwrap();
+
+    let mut buffer = vec![BltPixel::new(0, 0, 0); 4 * 4];
+    gop.blt(BltOp::VideoToBltBuffer {
+        buffer: &mut buffer,
+        src: (1020, 764),
+        dest: BltRegion::Full,
+        dims: (4, 4),
+    })
+    .unwrap();
+    for (i, pixel) in buffer.iter().enumerate() {
+        assert_eq!(*pixel == green, i % 5 == 0);
+    }
+}
+
+// Draw wrapped text into a surface and check that it reaches the screen.
+fn draw_text(gop: &mut GraphicsOutput) {
+    let white = BltPixel::new(255, 255, 255);
+    let blue = BltPixel::new(0, 0, 255);
+    let renderer = TextRenderer::default()
+        .set_foreground(white)
+        .set_background(Some(blue));
+    let mut surface = Surface::new(80, 32);
+    let size = renderer.draw_wrapped(&mut surface, (0, 0), 80, "boot menu entry");
+    assert_eq!(size, (72, 32));
+    surface.flush_at(gop, (900, 700)).unwrap();
+
+    // The top of the 'b' starts in the leftmost column of the third row.
+    let mut buffer = vec![BltPixel::new(0, 0, 0); 8 * 16];
+    gop.blt(BltOp::VideoToBltBuffer {
+        buffer: &mut buffer,
+        src: (900, 700),
+        dest: BltRegion::Full,
+        dims: (8, 16),
+    })
+    .unwrap();
+    assert_eq!(buffer[0], blue);
+    assert_eq!(buffer[2 * 8], white);
+    assert_eq!(buffer[2 * 8 + 7], blue);
+}
+
+// Write a row of pixels to the frame buffer and read it back with blits.
+fn convert_pixels(gop: &mut GraphicsOutput) {
+    let info = gop.current_mode_info();
+    let Some(converter) = PixelConverter::new(&info) else {
+        return;
+    };
+    let row: Vec<_> = (0..16u8)
+        .map(|i| BltPixel::new(i * 16, 255 - i * 16, 0x80))
+        .collect();
+    let offset = (767 * info.stride() + 1000) * converter.bytes_per_pixel();
+    let mut frame_buffer = gop.frame_buffer();
+    assert_eq!(converter.write_row(&mut frame_buffer, offset, &row), 16);
+    let mut read = vec![BltPixel::new(0, 0, 0); 16];
+    assert_eq!(converter.read_row(&frame_buffer, offset, &mut read), 16);
+    assert_eq!(read, row);
+
+    let mut buffer = vec![BltPixel::new(0, 0, 0); 16];
+    gop.blt(BltOp::VideoToBltBuffer {
+        buffer: &mut buffer,
+        src: (1000, 767),
+        dest: BltRegion::Full,
+        dims: (16, 1),
+    })
+    .unwrap();
+    assert_eq!(buffer, row);
+}
+
+// Draw a software cursor clipped at the corner of the screen, then remove it.
+fn draw_cursor(gop: &mut GraphicsOutput) {
+    let read = |gop: &mut GraphicsOutput| {
+        let mut buffer = vec![BltPixel::new(0, 0, 0); 4 * 8];
+        gop.blt(BltOp::VideoToBltBuffer {
+            buffer: &mut buffer,
+            src: (1020, 760),
+            dest: BltRegion::Full,
+            dims: (4, 8),
+        })
+        .unwrap();
+        buffer
+    };
+    let gray = BltPixel::new(128, 128, 128);
+    gop.blt(BltOp::VideoFill {
+        color: gray,
+        dest: (1020, 760),
+        dims: (4, 8),
+    })
+    .unwrap();
+    let before = read(gop);
+
+    let mut cursor = SoftwareCursor::new();
+    cursor.show(gop, (1020, 760)).unwrap();
+    assert!(cursor.is_shown());
+    let shown = read(gop);
+    // Outline, fill, and transparent pixels of the arrow.
+    assert_eq!(shown[0], BltPixel::new(0, 0, 0));
+    assert_eq!(shown[2 * 4 + 1], BltPixel::new(255, 255, 255));
+    assert_eq!(shown[3], gray);
+
+    cursor.hide(gop).unwrap();
+    assert_eq!(read(gop), before);
+}
@@ -4,15 +4,18 @@ pub fn test() {
     info!("Testing console protocols");
 
     system::with_stdout(stdout::test);
+    system::with_stdin(stdin::test);
 
     unsafe {
         serial::test();
         gop::test();
     }
     pointer::test();
+    pointer::test_absolute();
 }
 
 mod gop;
 mod pointer;
 mod serial;
+mod stdin;
 mod stdout;
@@ -1,4 +1,5 @@
 use uefi::boot;
+use uefi::proto::console::absolute_pointer::AbsolutePointer;
 use uefi::proto::console::pointer::Pointer;
 
 pub fn test() {
@@ -21,3 +22,29 @@ pub fn test() {
         info!("Pointer state has not changed since the last query");
     }
 }
+
+pub fn test_absolute() {
+    info!("Running absolute pointer protocol test");
+    // Only present if the VM has a tablet or touchscreen device.
+    let Ok(handle) = boot::get_handle_for_protocol::<AbsolutePointer>() else {
+        info!("No absolute pointer device");
+        return;
+    };
+    let mut pointer = boot::open_protocol_exclusive::<AbsolutePointer>(handle)
+        .expect("failed to open absolute pointer protocol");
+
+    pointer
+        .reset(false)
+        .expect("Failed to reset absolute pointer device");
+    info!("Absolute pointer mode: {:#?}", pointer.mode());
+
+    let state = pointer
+        .read_state()
+        .expect("Failed to retrieve absolute pointer state");
+    if let Some(state) = state {
+        info!(
+            "New absolute pointer position: {:?}",
+            pointer.to_screen(&state, (1024, 768))
+        );
+    }
+}
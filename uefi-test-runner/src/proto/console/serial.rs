@@ -1,5 +1,5 @@
 use crate::reconnect_serial_to_console;
-use uefi::proto::console::serial::{ControlBits, Serial};
+use uefi::proto::console::serial::{ControlBits, FlowControl, Serial, SerialConfig};
 use uefi::{boot, Result, ResultExt, Status};
 
 // For the duration of this function, the serial device is opened in
@@ -20,6 +20,20 @@ fn serial_test_helper(serial: &mut Serial) -> Result {
 
     serial.set_control_bits(ctrl_bits)?;
 
+    // Reapply the default line settings, which keeps the control bits.
+    let config = SerialConfig::new()
+        .set_baud_rate(115_200)
+        .set_flow_control(FlowControl::Hardware);
+    serial.configure(&config)?;
+    serial.update_control_bits(ControlBits::REQUEST_TO_SEND, false)?;
+    if serial.io_mode().baud_rate != 115_200
+        || !serial
+            .get_control_bits()?
+            .contains(ControlBits::SOFTWARE_LOOPBACK_ENABLE)
+    {
+        return Err(Status::ABORTED.into());
+    }
+
     // Keep this message short, we need it to fit in the FIFO.
     const OUTPUT: &[u8] = b"Hello world!";
     const MSG_LEN: usize = OUTPUT.len();
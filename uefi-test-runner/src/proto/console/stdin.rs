@@ -0,0 +1,59 @@
+use core::cell::RefCell;
+use core::time::Duration;
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use uefi::proto::console::text::{Input, InputEx, Key, KeyShiftState, KeyStroke, ScanCode};
+use uefi::proto::console::tui::{Menu, MenuChoice};
+use uefi::{system, Status};
+
+pub fn test(stdin: &mut Input) {
+    info!("Running text input protocol test");
+
+    // No keys are pressed while the tests run, so the read times out.
+    let key = stdin
+        .read_key_timeout(Duration::from_millis(10))
+        .expect("failed to wait for a key");
+    assert_eq!(key, None);
+
+    test_menu(stdin);
+    test_input_ex();
+}
+
+// Show a menu whose timeout elapses right away.
+fn test_menu(stdin: &mut Input) {
+    let menu = Menu::new(&["First", "Second"])
+        .set_title("Test menu")
+        .set_default(1)
+        .set_timeout(Duration::ZERO);
+    let stdin = RefCell::new(stdin);
+    let choice = system::with_stdout(|stdout| menu.run(&mut stdin.borrow_mut(), stdout))
+        .expect("failed to run menu");
+    assert_eq!(choice, MenuChoice::TimedOut(1));
+}
+
+extern "efiapi" fn key_notify(_key: &KeyStroke) -> Status {
+    Status::SUCCESS
+}
+
+fn test_input_ex() {
+    info!("Running extended text input protocol test");
+    let handle = boot::get_handle_for_protocol::<InputEx>().expect("missing InputEx protocol");
+    let mut input = unsafe {
+        boot::open_protocol::<InputEx>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            // Don't disconnect the console from the keyboard.
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .expect("failed to open InputEx protocol");
+
+    assert_eq!(input.read_key_stroke().unwrap(), None);
+
+    let key = KeyStroke::new(Key::Special(ScanCode::FUNCTION_2))
+        .set_shift_state(KeyShiftState::LEFT_CONTROL_PRESSED);
+    let notify = input.register_key_notify(&key, key_notify).unwrap();
+    input.unregister_key_notify(notify).unwrap();
+}
@@ -1,13 +1,22 @@
+use alloc::string::String;
+use core::fmt::Write;
+use uefi::helpers::ConsoleMux;
 use uefi::prelude::*;
-use uefi::proto::console::text::{Color, Output};
+use uefi::proto::console::text::{AnsiWriter, Color, Output};
+use uefi::proto::console::tui::ProgressBar;
 
 pub fn test(stdout: &mut Output) {
     info!("Running text output protocol test");
 
     get_current_mode(stdout);
     change_text_mode(stdout);
+    best_text_mode(stdout);
+    save_cursor(stdout);
     change_color(stdout);
     center_text(stdout);
+    ansi_escapes(stdout);
+    mux_output(stdout);
+    progress_bar(stdout);
 
     // Print all modes.
     for (index, mode) in stdout.modes().enumerate() {
@@ -23,12 +32,59 @@ pub fn test(stdout: &mut Output) {
     stdout.reset(false).unwrap();
 }
 
+// Write to the console and a string through a multiplexer.
+fn mux_output(stdout: &mut Output) {
+    let mut copy = String::new();
+    {
+        let mut mux = ConsoleMux::<2>::new();
+        let console = mux.add(stdout).unwrap();
+        let string = mux.add(&mut copy).unwrap();
+        writeln!(mux, "Written through a console multiplexer").unwrap();
+        assert!(!mux.failed(console));
+        mux.set_enabled(string, false);
+        writeln!(mux, "Only written to the console").unwrap();
+    }
+    assert_eq!(copy, "Written through a console multiplexer\n");
+}
+
+// Draw a progress bar, which stays on the same line.
+fn progress_bar(stdout: &mut Output) {
+    let progress = ProgressBar::new(4).set_width(20);
+    let row = stdout.cursor_position().1;
+    for done in 0..=4 {
+        progress.draw(stdout, done).unwrap();
+        assert_eq!(stdout.cursor_position().1, row);
+    }
+    writeln!(stdout).unwrap();
+}
+
 // Retrieves and prints the current output mode.
 fn get_current_mode(stdout: &mut Output) {
     let current_mode = stdout.current_mode().unwrap();
     info!("UEFI standard output current mode: {:?}", current_mode);
 }
 
+// Select the largest text mode, and check the reported dimensions.
+fn best_text_mode(stdout: &mut Output) {
+    let mode = stdout
+        .set_best_mode(80, 25)
+        .expect("failed to set the best text mode");
+    assert_eq!(stdout.dimensions().unwrap(), (mode.columns(), mode.rows()));
+    assert_eq!(
+        stdout.set_best_mode(100_000, 25).unwrap_err().status(),
+        Status::NOT_FOUND
+    );
+}
+
+// Move the cursor and change colors, then restore them.
+fn save_cursor(stdout: &mut Output) {
+    let saved = stdout.save_cursor();
+    stdout.set_cursor_position(1, 1).unwrap();
+    stdout.set_color(Color::Yellow, Color::Blue).unwrap();
+    stdout.restore_cursor(&saved).unwrap();
+    assert_eq!(stdout.save_cursor(), saved);
+}
+
 // Switch to the maximum supported text mode.
 fn change_text_mode(stdout: &mut Output) {
     let best_mode = stdout.modes().last().unwrap();
@@ -66,3 +122,16 @@ fn center_text(stdout: &mut Output) {
             _ => panic!("Failed to hide cursor"),
         });
 }
+
+// Move the cursor and change colors with escape sequences.
+fn ansi_escapes(stdout: &mut Output) {
+    let mut writer = AnsiWriter::new(stdout);
+    write!(writer, "\x1b[5;10H").unwrap();
+    assert_eq!(writer.get_mut().cursor_position(), (9, 4));
+    write!(writer, "\x1b[1;33mansi\x1b[2C").unwrap();
+    assert_eq!(writer.get_mut().cursor_position(), (15, 4));
+    assert_eq!(writer.get_mut().colors(), (Color::Yellow, Color::Blue));
+    write!(writer, "\x1b[0m\x1b[1;1H\x1b[K").unwrap();
+    assert_eq!(writer.get_mut().colors(), (Color::White, Color::Blue));
+    assert_eq!(writer.get_mut().cursor_position(), (0, 0));
+}
@@ -0,0 +1,38 @@
+use uefi::boot;
+use uefi::proto::decompress::Decompress;
+use uefi::Status;
+
+pub fn test() {
+    info!("Running decompress protocol test");
+
+    let handle =
+        boot::get_handle_for_protocol::<Decompress>().expect("No decompress protocol handles");
+    let decompress = boot::open_protocol_exclusive::<Decompress>(handle)
+        .expect("Failed to open decompress protocol");
+
+    // The header contains the compressed and the original size.
+    let mut header = [0; 8];
+    header[4..].copy_from_slice(&16u32.to_le_bytes());
+    let info = decompress.get_info(&header).unwrap();
+    assert_eq!(info.destination_size, 16);
+    assert!(info.scratch_size > 0);
+
+    // Buffers that are too small are rejected before calling the firmware.
+    let mut destination = [0; 8];
+    let mut scratch = [0; 8];
+    assert_eq!(
+        decompress
+            .decompress_into(&header, &mut destination, &mut scratch)
+            .unwrap_err()
+            .status(),
+        Status::BUFFER_TOO_SMALL
+    );
+
+    // Data with an original size of zero decompresses to nothing.
+    assert_eq!(decompress.decompress(&[0; 8]).unwrap(), []);
+
+    assert_eq!(
+        decompress.get_info(&[0; 4]).unwrap_err().status(),
+        Status::INVALID_PARAMETER
+    );
+}
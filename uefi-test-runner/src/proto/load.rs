@@ -5,8 +5,11 @@ use core::ffi::c_void;
 use core::pin::Pin;
 use core::ptr;
 use core::ptr::addr_of;
+use uefi::proto::device_path::build;
 use uefi::proto::device_path::build::DevicePathBuilder;
-use uefi::proto::media::load_file::{LoadFile, LoadFile2};
+use uefi::proto::media::load_file::{
+    LinuxInitrd, LoadFile, LoadFile2, LINUX_EFI_INITRD_MEDIA_GUID,
+};
 use uefi::proto::BootPolicy;
 use uefi::{boot, Guid, Handle};
 use uefi_raw::protocol::device_path::DevicePathProtocol;
@@ -51,6 +54,7 @@ impl CustomLoadFile2Protocol {
             log::debug!("Writing file content to buffer");
             unsafe {
                 ptr::copy_nonoverlapping(self.file_data.as_ptr(), buf.cast(), self.file_data.len());
+                *buf_len = self.file_data.len();
             }
             Status::SUCCESS
         }
@@ -108,6 +112,29 @@ pub fn test() {
     assert_eq!(load_data_msg, &loadfile_file_string);
     assert_eq!(load_data_msg, &loadfile2_file_string);
 
+    // Load the file into a caller-provided buffer.
+    let size = load_file_protocol
+        .file_size(dummy_dvp, BootPolicy::BootSelection)
+        .unwrap();
+    assert_eq!(size, load_data_msg.len());
+    let mut buffer = [0; 64];
+    let err = load_file_protocol
+        .load_file_into(dummy_dvp, BootPolicy::BootSelection, &mut buffer[..4])
+        .unwrap_err();
+    assert_eq!(err.status(), Status::BUFFER_TOO_SMALL);
+    assert_eq!(*err.data(), Some(size));
+    let len = load_file_protocol
+        .load_file_into(dummy_dvp, BootPolicy::BootSelection, &mut buffer)
+        .unwrap();
+    assert_eq!(&buffer[..len], load_data_msg.as_bytes());
+
+    assert_eq!(load_file2_protocol.file_size(dummy_dvp).unwrap(), size);
+    let mut buffer = [0; 64];
+    let len = load_file2_protocol
+        .load_file_into(dummy_dvp, &mut buffer)
+        .unwrap();
+    assert_eq!(&buffer[..len], load_data_msg.as_bytes());
+
     // Cleanup: Uninstall protocols again.
     drop(load_file_protocol);
     drop(load_file2_protocol);
@@ -128,4 +155,37 @@ pub fn test() {
             .map_err(|e| e.status()),
         Err(Status::UNSUPPORTED)
     );
+
+    test_linux_initrd();
+}
+
+/// Test the initrd provider by loading the initrd the way Linux does.
+fn test_linux_initrd() {
+    let initrd_data = b"Example initrd content.";
+    let initrd = LinuxInitrd::install(initrd_data.to_vec().into_boxed_slice()).unwrap();
+    assert_eq!(
+        LinuxInitrd::install(Box::new([])).unwrap_err().status(),
+        Status::ALREADY_STARTED
+    );
+
+    let mut dvp_vec = Vec::new();
+    let initrd_dvp = DevicePathBuilder::with_vec(&mut dvp_vec)
+        .push(&build::media::Vendor {
+            vendor_guid: LINUX_EFI_INITRD_MEDIA_GUID,
+            vendor_defined_data: &[],
+        })
+        .unwrap()
+        .finalize()
+        .unwrap();
+    let mut remaining = initrd_dvp;
+    let handle = boot::locate_device_path::<LoadFile2>(&mut remaining).unwrap();
+    assert_eq!(handle, initrd.handle());
+
+    let mut load_file2 = boot::open_protocol_exclusive::<LoadFile2>(handle).unwrap();
+    assert_eq!(&*load_file2.load_file(remaining).unwrap(), initrd_data);
+    drop(load_file2);
+
+    assert_eq!(&*initrd.uninstall().unwrap(), initrd_data);
+    let mut remaining = initrd_dvp;
+    assert!(boot::locate_device_path::<LoadFile2>(&mut remaining).is_err());
 }
@@ -1,19 +1,28 @@
 use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::cell::RefCell;
-use core::ptr::NonNull;
+use core::future::Future;
+use core::pin::pin;
+use core::ptr::{self, NonNull};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 use uefi::boot::{
     self, EventType, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol, Tpl,
 };
 use uefi::data_types::Align;
 use uefi::prelude::*;
-use uefi::proto::media::block::BlockIO;
-use uefi::proto::media::disk::{DiskIo, DiskIo2, DiskIo2Token};
+use uefi::proto::device_path::media::RamDiskType;
+use uefi::proto::media::block::{BlockIO, BlockIO2};
+use uefi::proto::media::disk::{DiskIo, DiskIo2, DiskIo2Operation, DiskIo2Token};
 use uefi::proto::media::file::{
     Directory, File, FileAttribute, FileInfo, FileMode, FileSystemInfo, FileSystemVolumeLabel,
 };
 use uefi::proto::media::fs::SimpleFileSystem;
-use uefi::proto::media::partition::{MbrOsType, PartitionInfo};
+use uefi::proto::media::partition::{MbrOsType, PartitionInfo, PartitionRecord};
+use uefi::proto::media::ram_disk::RamDisk;
 use uefi::runtime::{Daylight, Time, TimeParams};
+use uefi::storage::gpt::{Gpt, GptError};
+use uefi::storage::reader::BlockIoReader;
+use uefi::storage::PartitionTable;
 
 /// Test directory entry iteration.
 fn test_existing_dir(directory: &mut Directory) {
@@ -340,6 +349,197 @@ fn test_raw_disk_io2(handle: Handle) {
     }
 }
 
+/// Tests the blocking and non-blocking disk I/O 2 wrappers.
+fn test_disk_io2(handle: Handle) {
+    info!("Testing disk I/O 2");
+
+    let Ok(disk_io2) = boot::open_protocol_exclusive::<DiskIo2>(handle) else {
+        return;
+    };
+    let media_id = get_block_media_id(handle);
+
+    let mut mbr = [0; 512];
+    disk_io2.read_disk(media_id, 0, &mut mbr).unwrap();
+    assert_eq!(mbr[510..], [0x55, 0xaa]);
+
+    // Start two reads, and collect them in completion order.
+    let mut operations = vec![
+        disk_io2.read_disk_async(media_id, 0, vec![0; 512]).unwrap(),
+        disk_io2
+            .read_disk_async(media_id, 512, vec![0; 512])
+            .unwrap(),
+    ];
+    let mut buffers = Vec::new();
+    while !operations.is_empty() {
+        let index = DiskIo2Operation::wait_any(&mut operations).unwrap();
+        let mut operation = operations.remove(index);
+        assert!(operation.is_complete().unwrap());
+        buffers.push(operation.wait().unwrap());
+    }
+    assert_eq!(buffers.len(), 2);
+    assert!(buffers.iter().any(|buffer| buffer[..] == mbr[..]));
+
+    info!("Disk I/O 2 succeeded");
+}
+
+/// Poll `future` until it completes. The block I/O 2 futures ask to be polled
+/// again right away, so the waker does nothing.
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Tests the blocking and non-blocking block I/O 2 wrappers.
+fn test_block_io2(handle: Handle) {
+    info!("Testing block I/O 2");
+
+    // Not all block devices support the protocol.
+    let Ok(block_io2) = (unsafe {
+        boot::open_protocol::<BlockIO2>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }) else {
+        info!("Block I/O 2 is not supported");
+        return;
+    };
+    let media = block_io2.media();
+    let media_id = media.media_id();
+    let block_size = media.block_size() as usize;
+
+    let mut first = vec![0; block_size];
+    block_io2.read_blocks(media_id, 0, &mut first).unwrap();
+
+    let operation = block_io2
+        .read_blocks_async(media_id, 0, vec![0; block_size])
+        .unwrap();
+    assert_eq!(operation.wait().unwrap(), first);
+
+    let operation = block_io2
+        .read_blocks_async(media_id, 0, vec![0; block_size])
+        .unwrap();
+    assert_eq!(block_on(operation).unwrap(), first);
+
+    info!("Block I/O 2 succeeded");
+}
+
+/// Tests that the GPT reader rejects the MBR-formatted test disk.
+fn test_gpt(handle: Handle) {
+    info!("Testing GPT reader");
+
+    let mut block_io = unsafe {
+        boot::open_protocol::<BlockIO>(
+            OpenProtocolParams {
+                handle,
+                agent: boot::image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .expect("Failed to get block I/O protocol");
+    assert!(matches!(
+        Gpt::read(&mut *block_io),
+        Err(GptError::InvalidSignature)
+    ));
+}
+
+/// Tests reading the partition table of the whole MBR test disk.
+fn test_partition_table() {
+    info!("Testing partition table reader");
+
+    let mut found = false;
+    for handle in boot::find_handles::<BlockIO>().unwrap() {
+        let mut block_io = unsafe {
+            boot::open_protocol::<BlockIO>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .unwrap();
+        let media = block_io.media();
+        if media.is_logical_partition() || !media.is_media_present() {
+            continue;
+        }
+
+        let Ok(PartitionTable::Mbr(mbr)) = PartitionTable::read(&mut *block_io) else {
+            continue;
+        };
+        found |= mbr.partitions().any(|record| {
+            record.os_type == MbrOsType(6) && { record.starting_lba } == 1 && { record.size_in_lba }
+                == 20479
+        });
+        assert!(mbr.read_logical_partitions(&mut *block_io).is_ok());
+
+        // Read the boot signature at the end of the MBR, which is not
+        // aligned to the block size.
+        let mut reader = BlockIoReader::new(&mut *block_io);
+        let mut signature = [0; 2];
+        reader.read_exact_at(510, &mut signature).unwrap();
+        assert_eq!(signature, [0x55, 0xaa]);
+    }
+    assert!(found, "MBR test disk not found");
+}
+
+/// Tests registering a buffer as a RAM disk.
+fn test_ram_disk() {
+    info!("Testing RAM disk");
+
+    let Ok(handle) = boot::get_handle_for_protocol::<RamDisk>() else {
+        info!("RAM disk protocol is not supported");
+        return;
+    };
+    let ram_disk = boot::open_protocol_exclusive::<RamDisk>(handle).unwrap();
+
+    let mut buffer = vec![0; 64 * 512].into_boxed_slice();
+    buffer[510..512].copy_from_slice(&[0x55, 0xaa]);
+    let registration = ram_disk
+        .register(buffer, RamDiskType::VIRTUAL_DISK, None)
+        .unwrap();
+
+    // The RAM disk is accessible through block I/O.
+    let mut device_path = registration.device_path();
+    let disk_handle = boot::locate_device_path::<BlockIO>(&mut device_path).unwrap();
+    {
+        let block_io = boot::open_protocol_exclusive::<BlockIO>(disk_handle).unwrap();
+        let media = block_io.media();
+        assert_eq!(media.last_block(), 63);
+        let mut block = vec![0; media.block_size() as usize];
+        block_io
+            .read_blocks(media.media_id(), 0, &mut block)
+            .unwrap();
+        assert_eq!(block[510..512], [0x55, 0xaa]);
+    }
+
+    let buffer = registration.unregister().unwrap();
+    assert_eq!(buffer.len(), 64 * 512);
+
+    info!("RAM disk succeeded");
+}
+
 /// Check that `disk_handle` points to the expected MBR partition.
 fn test_partition_info(disk_handle: Handle) {
     let pi = boot::open_protocol_exclusive::<PartitionInfo>(disk_handle)
@@ -355,6 +555,11 @@ fn test_partition_info(disk_handle: Handle) {
     assert_eq!({ mbr.starting_chs }, [0, 0, 0]);
     assert_eq!(mbr.ending_chs, [0, 0, 0]);
     assert_eq!(mbr.os_type, MbrOsType(6));
+
+    let Some(PartitionRecord::Mbr(record)) = pi.record() else {
+        panic!("Not an MBR record");
+    };
+    assert_eq!({ record.starting_lba }, 1);
 }
 
 /// Find the disk with the "MbrTestDisk" label. Return the handle and opened
@@ -438,7 +643,13 @@ pub fn test() {
     // This will also drop the `SimpleFileSystem` protocol so that the raw disk
     // tests work.
     crate::fs::test(sfs).unwrap();
+    crate::fs::test_volume_search(handle).unwrap();
 
     test_raw_disk_io(handle);
     test_raw_disk_io2(handle);
+    test_disk_io2(handle);
+    test_block_io2(handle);
+    test_gpt(handle);
+    test_partition_table();
+    test_ram_disk();
 }
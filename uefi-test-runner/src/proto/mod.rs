@@ -11,16 +11,22 @@ pub fn test() {
     test_protocols_per_handle();
     test_test_protocol();
 
+    ata::test();
     debug::test();
+    decompress::test();
     device_path::test();
     driver::test();
     load::test();
     loaded_image::test();
     media::test();
     network::test();
+    nvme::test();
     pi::test();
     rng::test();
+    scsi::test();
+    sd_mmc::test();
     shell_params::test();
+    storage_security::test();
     string::test();
     misc::test();
 
@@ -60,8 +66,10 @@ fn test_test_protocol() {
     .unwrap());
 }
 
+mod ata;
 mod console;
 mod debug;
+mod decompress;
 mod device_path;
 mod driver;
 mod load;
@@ -69,8 +77,11 @@ mod loaded_image;
 mod media;
 mod misc;
 mod network;
+mod nvme;
 mod pi;
 mod rng;
+mod scsi;
+mod sd_mmc;
 mod shell_params;
 #[cfg(any(
     target_arch = "x86",
@@ -79,5 +90,6 @@ mod shell_params;
     target_arch = "aarch64"
 ))]
 mod shim;
+mod storage_security;
 mod string;
 mod tcg;
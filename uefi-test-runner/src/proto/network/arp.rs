@@ -0,0 +1,57 @@
+use core::net::Ipv4Addr;
+use core::time::Duration;
+use uefi::proto::network::arp::{ArpConfig, ArpResolver, ArpServiceBinding};
+use uefi::proto::network::MacAddress;
+use uefi::{boot, Status};
+
+pub fn test() {
+    info!("Testing the ARP protocol");
+
+    // Addresses on the QEMU user network used by the PXE test.
+    let station = Ipv4Addr::new(192, 168, 17, 15);
+    let gateway = Ipv4Addr::new(192, 168, 17, 2);
+    let unused = Ipv4Addr::new(192, 168, 17, 200);
+
+    let handles = boot::find_handles::<ArpServiceBinding>().unwrap_or_default();
+    for handle in handles {
+        let mut resolver = ArpResolver::new(handle, &ArpConfig::new(station))
+            .expect("failed to create ARP instance");
+
+        let mac = resolver
+            .resolve(gateway, Some(Duration::from_secs(5)))
+            .expect("failed to resolve gateway");
+        info!("Gateway address: {:02x?}", &mac.0[..6]);
+        assert_ne!(mac, MacAddress([0; 32]));
+
+        let arp = resolver.protocol();
+        assert_eq!(arp.lookup(gateway).unwrap(), Some(mac));
+        assert!(arp
+            .entries()
+            .unwrap()
+            .iter()
+            .any(|entry| entry.ip == gateway));
+
+        // Static entries are returned without sending requests.
+        let fake = MacAddress::from([0x02, 0, 0, 0, 0, 1]);
+        arp.add(unused, fake, None, true).unwrap();
+        let entry = arp
+            .entries()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.ip == unused)
+            .unwrap();
+        assert_eq!(entry.mac, fake);
+        assert!(entry.is_static);
+        assert_eq!(resolver.resolve(unused, None).unwrap(), fake);
+
+        let arp = resolver.protocol();
+        arp.delete(Some(unused)).unwrap();
+        assert_eq!(arp.lookup(unused).unwrap(), None);
+
+        // Denied addresses are never resolved.
+        arp.deny(unused, false).unwrap();
+        let err = resolver.resolve(unused, None).unwrap_err();
+        assert_eq!(err.status(), Status::ACCESS_DENIED);
+        resolver.protocol().delete(Some(unused)).unwrap();
+    }
+}
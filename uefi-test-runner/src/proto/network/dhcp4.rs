@@ -0,0 +1,26 @@
+use uefi::proto::network::dhcp4::{Dhcp4, Dhcp4Config};
+use uefi::Status;
+
+pub fn test() {
+    super::test_children::<Dhcp4>("DHCPv4", |dhcp| {
+        // The DHCP state is shared by all instances on the interface, so
+        // the firmware may already have acquired a lease.
+        let lease = dhcp.lease().unwrap();
+        info!("DHCP state: {:?}", lease.state);
+        if let Some(ack) = dhcp.reply_packet().unwrap() {
+            assert_eq!(ack.your_address(), lease.client_address);
+        }
+
+        // Only one instance per interface may be configured at a time, and
+        // the firmware's IPv4 configuration may already own one.
+        let options = [1, 3, 6];
+        let config = Dhcp4Config {
+            request_options: &options,
+            ..Default::default()
+        };
+        match dhcp.configure(&config) {
+            Ok(()) => dhcp.reset().unwrap(),
+            Err(err) => assert_eq!(err.status(), Status::ACCESS_DENIED),
+        }
+    });
+}
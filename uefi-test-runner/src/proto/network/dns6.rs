@@ -0,0 +1,26 @@
+use uefi::proto::network::dns6::{Dns6, Dns6Config};
+use uefi::Status;
+
+pub fn test() {
+    super::test_children::<Dns6>("DNSv6", |dns| {
+        // Too many DNS servers are rejected before calling into the
+        // implementation.
+        let servers = [core::net::Ipv6Addr::LOCALHOST; 9];
+        let config = Dns6Config {
+            dns_servers: &servers,
+            ..Default::default()
+        };
+        assert_eq!(
+            dns.configure(&config).unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+
+        // Lookups fail on an unconfigured instance.
+        assert_eq!(
+            dns.host_name_to_ip(uefi::cstr16!("localhost"))
+                .unwrap_err()
+                .status(),
+            Status::NOT_STARTED
+        );
+    });
+}
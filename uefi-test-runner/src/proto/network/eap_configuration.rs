@@ -0,0 +1,18 @@
+use uefi::boot;
+use uefi::proto::network::eap_configuration::{EapConfiguration, EapType};
+
+pub fn test() {
+    // The EAP configuration protocol is only installed on wireless
+    // interfaces.
+    let Ok(handle) = boot::get_handle_for_protocol::<EapConfiguration>() else {
+        info!("EAP configuration protocol is not available");
+        return;
+    };
+
+    info!("Testing the EAP configuration protocol");
+
+    let mut eap = boot::open_protocol_exclusive::<EapConfiguration>(handle).unwrap();
+    eap.set_auth_method(EapType::EAP_TLS).unwrap();
+    assert_eq!(eap.auth_method().unwrap(), EapType::EAP_TLS);
+    eap.set_identity(b"uefi-rs").unwrap();
+}
@@ -0,0 +1,12 @@
+use uefi::proto::network::http::Http;
+use uefi::Status;
+
+pub fn test() {
+    super::test_children::<Http>("HTTP", |http| {
+        // Responses can't be received on an unconfigured instance.
+        assert_eq!(
+            http.receive_response().unwrap_err().status(),
+            Status::NOT_STARTED
+        );
+    });
+}
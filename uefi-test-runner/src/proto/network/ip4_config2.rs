@@ -0,0 +1,19 @@
+use uefi::boot;
+use uefi::proto::network::ip4_config2::{Ip4Config2, Ip4Config2Policy};
+
+pub fn test() {
+    info!("Testing the IPv4 configuration protocol");
+
+    let handles = boot::find_handles::<Ip4Config2>().unwrap_or_default();
+    for handle in handles {
+        let mut ip4_config = boot::open_protocol_exclusive::<Ip4Config2>(handle).unwrap();
+
+        let policy = ip4_config.policy().unwrap();
+        assert!(policy == Ip4Config2Policy::STATIC || policy == Ip4Config2Policy::DHCP);
+
+        let info = ip4_config.interface_info().unwrap();
+        info!("Interface {}: address {}", info.name, info.station_address);
+        // QEMU emulates an Ethernet device.
+        assert_eq!(info.hw_addr_size, 6);
+    }
+}
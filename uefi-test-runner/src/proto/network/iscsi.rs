@@ -0,0 +1,28 @@
+use uefi::proto::network::iscsi::{IScsiInitiatorName, MAX_ISCSI_NAME_LEN};
+use uefi::{boot, cstr8, Status};
+
+pub fn test() {
+    // The iSCSI driver is optional in OVMF.
+    let Ok(handle) = boot::get_handle_for_protocol::<IScsiInitiatorName>() else {
+        info!("iSCSI initiator name protocol is not available");
+        return;
+    };
+
+    info!("Testing the iSCSI initiator name protocol");
+
+    let mut iscsi = boot::open_protocol_exclusive::<IScsiInitiatorName>(handle).unwrap();
+
+    let name = cstr8!("iqn.2024-01.org.uefi-rs:test-runner");
+    iscsi.set_name(name).unwrap();
+    let mut buf = [0; MAX_ISCSI_NAME_LEN + 1];
+    assert_eq!(iscsi.name(&mut buf).unwrap(), name);
+
+    let mut small_buf = [0; 4];
+    let err = iscsi.name(&mut small_buf).unwrap_err();
+    assert_eq!(err.status(), Status::BUFFER_TOO_SMALL);
+    assert_eq!(*err.data(), Some(name.as_bytes().len()));
+
+    // Invalid names are rejected before reaching the firmware.
+    let err = iscsi.set_name(cstr8!("not an iscsi name")).unwrap_err();
+    assert_eq!(err.status(), Status::INVALID_PARAMETER);
+}
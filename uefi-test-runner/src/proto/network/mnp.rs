@@ -0,0 +1,35 @@
+use core::time::Duration;
+use uefi::proto::network::mnp::{ManagedNetworkServiceBinding, MnpConfig, MnpSocket};
+use uefi::proto::network::MacAddress;
+use uefi::{boot, Status};
+
+/// EtherType reserved for local experiments.
+const ETHER_TYPE: u16 = 0x88b5;
+
+pub fn test() {
+    info!("Testing the Managed Network protocol");
+
+    let handles = boot::find_handles::<ManagedNetworkServiceBinding>().unwrap_or_default();
+    for handle in handles {
+        let mut socket = MnpSocket::open(handle, ETHER_TYPE).expect("failed to open MNP socket");
+        assert_eq!(
+            socket.protocol().config().unwrap(),
+            MnpConfig::new(ETHER_TYPE)
+        );
+
+        let mac = socket.local_addr().unwrap();
+        info!("Interface address: {:02x?}", &mac.0[..6]);
+
+        let mut broadcast = MacAddress([0; 32]);
+        broadcast.0[..6].fill(0xff);
+        socket.send(broadcast, &[0; 46]).unwrap();
+
+        // Nothing on the network uses this EtherType, so receiving must time
+        // out.
+        let mut buf = [0; 64];
+        let err = socket
+            .recv(&mut buf, Some(Duration::from_millis(10)))
+            .unwrap_err();
+        assert_eq!(err.status(), Status::TIMEOUT);
+    }
+}
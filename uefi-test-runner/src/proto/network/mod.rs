@@ -1,9 +1,79 @@
+use uefi::boot;
+use uefi::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use uefi::proto::ProtocolPointer;
+
 pub fn test() {
     info!("Testing Network protocols");
 
     pxe::test();
+
+    // Most of the network stack is only set up by the firmware for the PXE
+    // test.
+    let pxe = cfg!(feature = "pxe");
+    if pxe {
+        arp::test();
+        dhcp4::test();
+        dns4::test();
+        dns6::test();
+        eap_configuration::test();
+        http::test();
+        ip4_config2::test();
+        iscsi::test();
+        mnp::test();
+        mtftp4::test();
+        rest::test();
+        supplicant::test();
+        tcp4::test();
+        tcp6::test();
+        udp4::test();
+        udp6::test();
+    }
+    tls::test();
     snp::test();
+    // Run last, since changing VLANs reconnects the network stack.
+    if pxe {
+        vlan_config::test();
+    }
+}
+
+/// Create a `P` child on each handle with a service binding for `P`, and
+/// run `test` with it. The child is destroyed afterwards.
+fn test_children<P: ServiceBindingChild + ProtocolPointer>(
+    name: &str,
+    mut test: impl FnMut(&mut ServiceChild<P>),
+) {
+    let handles = boot::find_handles::<ServiceBinding<P>>().unwrap_or_default();
+    if handles.is_empty() {
+        info!("{name} protocol is not available");
+        return;
+    }
+
+    info!("Testing the {name} protocol");
+
+    for handle in handles {
+        let mut child = ServiceChild::<P>::new(handle)
+            .unwrap_or_else(|err| panic!("failed to create {name} child: {err:?}"));
+        test(&mut child);
+    }
 }
 
+mod arp;
+mod dhcp4;
+mod dns4;
+mod dns6;
+mod eap_configuration;
+mod http;
+mod ip4_config2;
+mod iscsi;
+mod mnp;
+mod mtftp4;
 mod pxe;
+mod rest;
 mod snp;
+mod supplicant;
+mod tcp4;
+mod tcp6;
+mod tls;
+mod udp4;
+mod udp6;
+mod vlan_config;
@@ -0,0 +1,43 @@
+use core::net::Ipv4Addr;
+use uefi::proto::network::mtftp4::{Mtftp4, Mtftp4Client, Mtftp4ServiceBinding};
+use uefi::{boot, cstr8, Status};
+
+pub fn test() {
+    super::test_children::<Mtftp4>("MTFTPv4", |tftp| {
+        // Transfers require a configured instance.
+        let err = tftp
+            .read_file(None, cstr8!("file"), &mut |_| Ok(()), &mut |_| {})
+            .unwrap_err();
+        assert_eq!(err.status(), Status::NOT_STARTED);
+    });
+
+    let handles = boot::find_handles::<Mtftp4ServiceBinding>().unwrap_or_default();
+    for handle in handles {
+        // Configuring with the default address fails if no address has been
+        // assigned to the interface yet, which is fine for this test.
+        let mut client = match Mtftp4Client::new(handle) {
+            Ok(client) => client,
+            Err(err) => {
+                assert_eq!(err.status(), Status::NO_MAPPING);
+                continue;
+            }
+        };
+
+        // QEMU's TFTP server is at the second address of the user network.
+        let server = Ipv4Addr::new(192, 168, 17, 2);
+        let mut last_progress = None;
+        let data = client.read_file_to_vec(server, cstr8!("example-file.txt"));
+        assert_eq!(data.unwrap(), b"Hello world!");
+
+        let size = client
+            .read_file_with_progress(
+                server,
+                cstr8!("example-file.txt"),
+                &mut |_| Ok(()),
+                &mut |progress| last_progress = Some(progress),
+            )
+            .unwrap();
+        assert_eq!(size, 12);
+        assert_eq!(last_progress.unwrap().received, 12);
+    }
+}
@@ -1,4 +1,4 @@
-use uefi::proto::network::pxe::{BaseCode, DhcpV4Packet, IpFilter, IpFilters, UdpOpFlags};
+use uefi::proto::network::pxe::{BaseCode, DhcpV4Packet, IpFilter, IpFilters, PxeBoot, UdpOpFlags};
 use uefi::proto::network::IpAddress;
 use uefi::{boot, CStr8};
 
@@ -105,6 +105,15 @@ pub fn test() {
         received.reverse();
         assert_eq!(payload, received);
 
+        info!("Running the PXE boot sequence");
+        let file = PxeBoot::new()
+            .boot_file(example_file_name)
+            .run(&mut base_code)
+            .expect("failed to run the PXE boot sequence");
+        assert_eq!(file.server_ip, server_ip);
+        assert_eq!(file.file_name, b"example-file.txt");
+        assert_eq!(file.data, EXAMPLE_FILE_CONTENT);
+
         info!("Stopping PXE Base Code");
         base_code.stop().expect("failed to stop PXE Base Code");
     }
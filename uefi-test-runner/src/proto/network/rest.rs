@@ -0,0 +1,21 @@
+use uefi::proto::network::http::{HttpConfig, HttpMethod};
+use uefi::proto::network::rest::{RestEx, RestRequest};
+use uefi::{cstr16, Status};
+
+pub fn test() {
+    // OVMF does not include a REST EX driver by default.
+    super::test_children::<RestEx>("REST EX", |rest| {
+        let info = rest.service_info().unwrap();
+        info!("REST EX service: {info:?}");
+
+        // Requests fail before the instance is configured.
+        let request = RestRequest::new(HttpMethod::GET, cstr16!("/redfish/v1"));
+        assert_eq!(
+            rest.send_receive(&request).unwrap_err().status(),
+            Status::NOT_STARTED
+        );
+
+        rest.configure(&HttpConfig::default(), 5000).unwrap();
+        rest.reset().unwrap();
+    });
+}
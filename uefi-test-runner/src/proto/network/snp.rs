@@ -1,5 +1,6 @@
+use core::time::Duration;
 use uefi::proto::network::snp::{InterruptStatus, ReceiveFlags, SimpleNetwork};
-use uefi::proto::network::MacAddress;
+use uefi::proto::network::{IpAddress, MacAddress};
 use uefi::{boot, Status};
 
 pub fn test() {
@@ -49,6 +50,30 @@ pub fn test() {
             )
             .expect("Failed to set receive filters");
 
+        // Check the receive filter helpers
+        let filters = ReceiveFlags::UNICAST | ReceiveFlags::BROADCAST;
+        simple_network
+            .set_receive_filters(filters)
+            .expect("Failed to set receive filters");
+        assert_eq!(
+            simple_network.current_receive_filters()
+                & (filters | ReceiveFlags::MULTICAST | ReceiveFlags::PROMISCUOUS),
+            filters
+        );
+        if simple_network.mode().max_mcast_filter_count > 0 {
+            let group = simple_network
+                .mcast_ip_to_mac(false, IpAddress::new_v4([224, 0, 0, 251]))
+                .unwrap();
+            simple_network
+                .set_multicast_filter(&[group])
+                .expect("Failed to set multicast filter");
+            assert_eq!(simple_network.multicast_filter(), &[group]);
+            simple_network
+                .set_multicast_filter(&[])
+                .expect("Failed to clear multicast filter");
+            assert!(simple_network.multicast_filter().is_empty());
+        }
+
         // Check media
         if !simple_network.mode().media_present_supported || !simple_network.mode().media_present {
             continue;
@@ -119,5 +144,24 @@ pub fn test() {
         // One frame should have been transmitted and one received
         assert_eq!(stats.tx_total_frames().unwrap(), 1);
         assert_eq!(stats.rx_total_frames().unwrap(), 1);
+
+        // Send the frame again, and receive the reply with a `Receiver`
+        simple_network
+            .transmit(
+                simple_network.mode().media_header_size as usize,
+                payload,
+                None,
+                Some(dest_addr),
+                Some(0x0800),
+            )
+            .expect("Failed to transmit frame");
+        let mut receiver = simple_network
+            .receiver()
+            .expect("Failed to create receiver");
+        let frame = receiver
+            .recv(Some(Duration::from_secs(1)))
+            .expect("Failed to receive frame");
+        assert_eq!(frame.protocol, 0x0800);
+        assert_eq!(frame.payload()[28..33], [4, 4, 3, 2, 1]);
     }
 }
@@ -0,0 +1,38 @@
+use uefi::proto::network::supplicant::{Supplicant, AKM_SUITE_PSK, CIPHER_SUITE_CCMP};
+use uefi::{cstr8, Status};
+
+pub fn test() {
+    // The supplicant is only installed on wireless interfaces.
+    super::test_children::<Supplicant>("Supplicant", |supplicant| {
+        let akm_suites = supplicant.supported_akm_suites().unwrap();
+        info!("Supported AKM suites: {akm_suites:?}");
+
+        supplicant.set_target_ssid(b"uefi-rs").unwrap();
+        supplicant.set_akm_suite(AKM_SUITE_PSK).unwrap();
+        supplicant
+            .set_pairwise_cipher_suite(CIPHER_SUITE_CCMP)
+            .unwrap();
+        supplicant
+            .set_group_cipher_suite(CIPHER_SUITE_CCMP)
+            .unwrap();
+        supplicant
+            .set_psk_password(cstr8!("uefi-rs test runner"))
+            .unwrap();
+
+        // Invalid parameters are rejected before reaching the firmware.
+        assert_eq!(
+            supplicant
+                .set_psk_password(cstr8!("short"))
+                .unwrap_err()
+                .status(),
+            Status::INVALID_PARAMETER
+        );
+        assert_eq!(
+            supplicant
+                .set_target_ssid(&[b'a'; 33])
+                .unwrap_err()
+                .status(),
+            Status::INVALID_PARAMETER
+        );
+    });
+}
@@ -0,0 +1,13 @@
+use uefi::proto::network::tcp6::{Tcp6, Tcp6ConnectionState};
+use uefi::Status;
+
+pub fn test() {
+    super::test_children::<Tcp6>("TCPv6", |tcp| {
+        // A freshly created instance is not connected. Some implementations
+        // don't report any mode data before the instance is configured.
+        match tcp.connection_state() {
+            Ok(state) => assert_eq!(state, Tcp6ConnectionState::CLOSED),
+            Err(err) => assert_eq!(err.status(), Status::NOT_STARTED),
+        }
+    });
+}
@@ -0,0 +1,17 @@
+use uefi::boot;
+use uefi::proto::network::tls::{TlsServiceBinding, TlsSession, TlsSessionState};
+
+pub fn test() {
+    info!("Testing the TLS protocols");
+
+    // The TLS driver is not part of every firmware build.
+    let handles = boot::find_handles::<TlsServiceBinding>().unwrap_or_default();
+    for handle in handles {
+        let mut session = TlsSession::new(handle).expect("failed to create TLS child");
+
+        assert_eq!(
+            session.session_state().unwrap(),
+            TlsSessionState::NOT_STARTED
+        );
+    }
+}
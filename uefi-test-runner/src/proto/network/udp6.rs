@@ -0,0 +1,27 @@
+use core::net::{Ipv6Addr, SocketAddrV6};
+use core::time::Duration;
+use uefi::proto::network::udp6::{Udp6, Udp6Config};
+use uefi::Status;
+
+pub fn test() {
+    super::test_children::<Udp6>("UDPv6", |udp| {
+        // A freshly created instance is not configured.
+        assert_eq!(udp.config().unwrap_err().status(), Status::NOT_STARTED);
+
+        // Configuring with the default address fails if no address has been
+        // assigned to the interface yet, which is fine for this test.
+        let config = Udp6Config::new(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0));
+        match udp.configure(&config) {
+            Ok(()) => {
+                // Nothing is sent to this instance, so receiving must time out.
+                let mut buf = [0; 16];
+                let err = udp
+                    .receive(&mut buf, Some(Duration::from_millis(10)))
+                    .unwrap_err();
+                assert_eq!(err.status(), Status::TIMEOUT);
+                udp.reset().unwrap();
+            }
+            Err(err) => assert_eq!(err.status(), Status::NO_MAPPING),
+        }
+    });
+}
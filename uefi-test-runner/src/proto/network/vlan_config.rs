@@ -0,0 +1,40 @@
+use uefi::proto::network::vlan_config::{Vlan, VlanConfig};
+use uefi::{boot, Status};
+
+/// VLAN ID not used by the test network.
+const VLAN_ID: u16 = 100;
+
+pub fn test() {
+    info!("Testing the VLAN configuration protocol");
+
+    let handles = boot::find_handles::<VlanConfig>().unwrap_or_default();
+    for handle in handles {
+        let mut vlan_config = boot::open_protocol_exclusive::<VlanConfig>(handle).unwrap();
+        assert!(vlan_config.vlans().unwrap().is_empty());
+
+        vlan_config.set(VLAN_ID, 3).unwrap();
+        let vlans = vlan_config.vlans().unwrap();
+        assert_eq!(vlans.len(), 1);
+        assert_eq!(
+            vlans.iter().next(),
+            Some(Vlan {
+                id: VLAN_ID,
+                priority: 3
+            })
+        );
+
+        // Setting an existing VLAN updates its priority.
+        vlan_config.set(VLAN_ID, 5).unwrap();
+        assert_eq!(
+            vlan_config.vlan(VLAN_ID).unwrap().map(|vlan| vlan.priority),
+            Some(5)
+        );
+
+        vlan_config.remove(VLAN_ID).unwrap();
+        assert_eq!(vlan_config.vlan(VLAN_ID).unwrap(), None);
+        assert_eq!(
+            vlan_config.remove(VLAN_ID).unwrap_err().status(),
+            Status::NOT_FOUND
+        );
+    }
+}
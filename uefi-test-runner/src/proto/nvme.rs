@@ -0,0 +1,77 @@
+use alloc::vec::Vec;
+use uefi::boot;
+use uefi::proto::nvme::pass_thru::{NvmePassThru, NvmeRequest, NVME_BROADCAST_NAMESPACE_ID};
+use uefi::proto::nvme::{CriticalWarning, LogPageId, SecureErase};
+use uefi::Status;
+
+/// Serial number of the NVMe controller set up by xtask.
+const TEST_SERIAL_NUMBER: &[u8] = b"uefi-rs-nvme";
+
+pub fn test() {
+    info!("Testing the NVMe pass thru protocol");
+
+    let handles = boot::find_handles::<NvmePassThru>().expect("no NVMe controller found");
+    let mut found = false;
+    for handle in handles {
+        let mut nvme = boot::open_protocol_exclusive::<NvmePassThru>(handle).unwrap();
+        let controller = nvme.identify_controller().unwrap();
+        if controller.serial_number() != TEST_SERIAL_NUMBER {
+            continue;
+        }
+        found = true;
+        assert_eq!(controller.model_number(), b"QEMU NVMe Ctrl");
+
+        let namespaces: Vec<u32> = nvme.namespaces().collect();
+        assert_eq!(namespaces, [1]);
+        assert_eq!(nvme.active_namespace_ids().unwrap(), [1]);
+
+        let smart_log = nvme.smart_log().unwrap();
+        assert_eq!(smart_log.critical_warning(), CriticalWarning::empty());
+        assert!(smart_log.composite_temperature() > 0);
+        let mut buffer = nvme.alloc_io_buffer(6).unwrap();
+        assert_eq!(
+            nvme.get_log_page(
+                NVME_BROADCAST_NAMESPACE_ID,
+                LogPageId::SMART,
+                buffer.as_mut_slice()
+            )
+            .unwrap_err()
+            .status(),
+            Status::INVALID_PARAMETER
+        );
+
+        let namespace = nvme.identify_namespace(1).unwrap();
+        let block_size = namespace.current_lba_format().unwrap().block_size();
+        assert_eq!(namespace.size() * block_size, 1024 * 1024);
+
+        // The test disk is empty, so it can be formatted with its current
+        // block format.
+        nvme.format_nvm(1, namespace.current_lba_format_index(), SecureErase::NONE)
+            .unwrap();
+
+        let device_path = nvme.device_path(1).unwrap();
+        assert_eq!(nvme.namespace_id(&device_path).unwrap(), 1);
+
+        // Read the first block with the NVM Read command.
+        let mut buffer = nvme.alloc_io_buffer(block_size as usize).unwrap();
+        let request = NvmeRequest::io(0x02)
+            .with_cdw10(0)
+            .with_cdw11(0)
+            .with_cdw12(0)
+            .with_transfer_buffer(buffer.as_mut_slice());
+        let response = nvme.execute_command(1, request).unwrap();
+        assert_eq!(response.transfer_length, block_size as usize);
+        assert!(buffer.as_slice().iter().all(|&b| b == 0));
+
+        // Unaligned buffers are rejected before reaching the driver.
+        if nvme.io_align() > 1 {
+            let request =
+                NvmeRequest::io(0x02).with_transfer_buffer(&mut buffer.as_mut_slice()[1..]);
+            assert_eq!(
+                nvme.execute_command(1, request).unwrap_err().status(),
+                Status::INVALID_PARAMETER
+            );
+        }
+    }
+    assert!(found, "test NVMe controller not found");
+}
@@ -0,0 +1,38 @@
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use uefi::proto::pi::firmware_volume::{FirmwareVolume2, FvFileType, SectionType};
+
+pub fn test() {
+    info!("Testing the firmware volume 2 protocol");
+
+    let handles =
+        boot::find_handles::<FirmwareVolume2>().expect("failed to find firmware volume 2 handles");
+    let mut num_drivers = 0;
+    for handle in handles {
+        // The firmware volumes are used by the DXE core, so only get the
+        // protocol instead of opening it exclusively.
+        let fv = unsafe {
+            boot::open_protocol::<FirmwareVolume2>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .expect("failed to open firmware volume 2 protocol");
+        fv.attributes().unwrap();
+
+        for file in fv.files(FvFileType::DRIVER) {
+            let file = file.unwrap();
+            assert_eq!(file.file_type, FvFileType::DRIVER);
+            assert_eq!(fv.file_info(&file.name).unwrap(), file);
+
+            let image = fv.read_section(&file.name, SectionType::PE32, 0).unwrap();
+            assert_eq!(&image[..2], b"MZ");
+            num_drivers += 1;
+        }
+    }
+    // OVMF contains many drivers in its DXE firmware volume.
+    assert!(num_drivers > 0);
+}
@@ -1,7 +1,9 @@
 pub fn test() {
     info!("Testing Platform Initialization protocols");
 
+    firmware_volume::test();
     mp::test();
 }
 
+mod firmware_volume;
 mod mp;
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::ffi::c_void;
 use core::ptr;
 use core::sync::atomic::{AtomicUsize, Ordering};
@@ -26,6 +27,7 @@ pub fn test() {
     test_get_processor_info(mp_support);
     test_startup_all_aps(mp_support);
     test_startup_this_ap(mp_support);
+    test_run_on_aps(mp_support);
     test_enable_disable_ap(mp_support);
     test_switch_bsp_and_who_am_i(mp_support);
 }
@@ -66,6 +68,15 @@ fn test_get_processor_info(mps: &MpServices) {
 
     // Enable second CPU back
     mps.enable_disable_ap(1, true, None).unwrap();
+
+    // Enumerate all CPUs
+    let cpus = mps
+        .processors()
+        .unwrap()
+        .collect::<uefi::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(cpus.len(), NUM_CPUS);
+    assert_eq!(cpus.iter().filter(|cpu| cpu.is_bsp()).count(), 1);
 }
 
 extern "efiapi" fn proc_increment_atomic(arg: *mut c_void) {
@@ -96,6 +107,28 @@ fn test_startup_all_aps(mps: &MpServices) {
     assert_eq!(ret.map_err(|err| err.status()), Err(Status::TIMEOUT));
 }
 
+fn test_run_on_aps(mps: &MpServices) {
+    let counter = AtomicUsize::new(0);
+    unsafe {
+        mps.run_on_all_aps(true, None, &|| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+    }
+    .unwrap();
+    assert_eq!(counter.load(Ordering::Relaxed), NUM_CPUS - 1);
+
+    for i in 1..NUM_CPUS {
+        let counter = &counter;
+        unsafe {
+            mps.run_on_ap(i, Some(Duration::from_secs(1)), move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+            })
+        }
+        .unwrap();
+    }
+    assert_eq!(counter.load(Ordering::Relaxed), 2 * (NUM_CPUS - 1));
+}
+
 fn test_startup_this_ap(mps: &MpServices) {
     // Ensure that each AP starts up
     let counter = AtomicUsize::new(0);
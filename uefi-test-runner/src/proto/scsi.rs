@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+use uefi::boot;
+use uefi::proto::scsi::pass_thru::{ExtScsiPassThru, ScsiRequest};
+use uefi::proto::scsi::InquiryData;
+use uefi::Status;
+
+/// Product ID of the SCSI disk set up by xtask.
+const TEST_PRODUCT_ID: &[u8] = b"uefi-rs-scsi";
+
+pub fn test() {
+    info!("Testing the extended SCSI pass thru protocol");
+
+    let handles = boot::find_handles::<ExtScsiPassThru>().expect("no SCSI controller found");
+    let mut found = false;
+    for handle in handles {
+        let mut scsi = boot::open_protocol_exclusive::<ExtScsiPassThru>(handle).unwrap();
+        let targets: Vec<_> = scsi.targets().collect();
+        let devices: Vec<_> = scsi.devices().collect();
+        assert!(devices
+            .iter()
+            .all(|device| targets.contains(&device.target)));
+
+        for device in devices {
+            let inquiry = scsi.inquiry(device).unwrap();
+            if inquiry.product_id() != TEST_PRODUCT_ID {
+                continue;
+            }
+            found = true;
+            assert_eq!(inquiry.peripheral_qualifier(), 0);
+            assert_eq!(
+                inquiry.peripheral_device_type(),
+                InquiryData::DEVICE_TYPE_DISK
+            );
+
+            let capacity = scsi.read_capacity(device).unwrap();
+            assert_eq!(
+                capacity.block_count() * u64::from(capacity.block_size),
+                1024 * 1024
+            );
+
+            let device_path = scsi.device_path(device).unwrap();
+            assert_eq!(scsi.device(&device_path).unwrap(), device);
+
+            // Read the first block with READ (10).
+            let block_size = capacity.block_size as usize;
+            let mut buffer = scsi.alloc_io_buffer(block_size).unwrap();
+            let cdb = [0x28, 0, 0, 0, 0, 0, 0, 0, 1, 0];
+            let request = ScsiRequest::new(&cdb).with_read_buffer(buffer.as_mut_slice());
+            let response = scsi.execute_command(device, request).unwrap();
+            assert!(response.is_good());
+            assert_eq!(response.in_transfer_length, block_size);
+            assert!(buffer.as_slice().iter().all(|&b| b == 0));
+
+            // Unaligned buffers are rejected before reaching the driver.
+            if scsi.io_align() > 1 {
+                let request =
+                    ScsiRequest::new(&cdb).with_read_buffer(&mut buffer.as_mut_slice()[1..]);
+                let err = scsi.execute_command(device, request).unwrap_err();
+                assert_eq!(err.status(), Status::INVALID_PARAMETER);
+                assert_eq!(*err.data(), None);
+            }
+        }
+    }
+    assert!(found, "test SCSI disk not found");
+}
@@ -0,0 +1,22 @@
+use alloc::vec::Vec;
+use uefi::boot;
+use uefi::proto::sd_mmc::pass_thru::SdMmcPassThru;
+
+pub fn test() {
+    info!("Testing the SD MMC pass thru protocol");
+
+    // OVMF does not include an SD host controller driver, so this only runs
+    // on firmware that does.
+    let Ok(handles) = boot::find_handles::<SdMmcPassThru>() else {
+        info!("No SD MMC controller found, skipping test");
+        return;
+    };
+    for handle in handles {
+        let sd_mmc = boot::open_protocol_exclusive::<SdMmcPassThru>(handle).unwrap();
+        let slots: Vec<u8> = sd_mmc.slots().collect();
+        for slot in slots {
+            let device_path = sd_mmc.device_path(slot).unwrap();
+            assert_eq!(sd_mmc.slot(&device_path).unwrap(), slot);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use uefi::boot::{self, OpenProtocolAttributes, OpenProtocolParams};
+use uefi::proto::media::block::BlockIO;
+use uefi::proto::storage_security::opal::FeatureCode;
+use uefi::proto::storage_security::{StorageSecurityCommand, SECURITY_PROTOCOL_TCG_1};
+use uefi::Status;
+
+pub fn test() {
+    info!("Testing the storage security command protocol");
+
+    let Ok(handles) = boot::find_handles::<StorageSecurityCommand>() else {
+        info!("No storage security command protocol found, skipping test");
+        return;
+    };
+    for handle in handles {
+        let media_id = unsafe {
+            boot::open_protocol::<BlockIO>(
+                OpenProtocolParams {
+                    handle,
+                    agent: boot::image_handle(),
+                    controller: None,
+                },
+                OpenProtocolAttributes::GetProtocol,
+            )
+        }
+        .unwrap()
+        .media()
+        .media_id();
+        let mut ssc = boot::open_protocol_exclusive::<StorageSecurityCommand>(handle).unwrap();
+
+        // The emulated drives do not support security commands, but the
+        // protocol may still be installed.
+        let mut buffer = [0; 512];
+        let protocols = match ssc.supported_protocols(media_id, &mut buffer) {
+            Ok(protocols) => protocols,
+            Err(err) if err.status() == Status::UNSUPPORTED => continue,
+            Err(err) => panic!("failed to get supported protocols: {err:?}"),
+        };
+        info!("Supported security protocols: {:x?}", protocols.protocols());
+        let has_tcg = protocols.contains(SECURITY_PROTOCOL_TCG_1);
+
+        let mut buffer = [0; 2048];
+        if has_tcg {
+            let discovery = ssc.level0_discovery(media_id, &mut buffer).unwrap();
+            for feature in discovery.features() {
+                info!("TCG feature {:?}", feature.code);
+            }
+            assert!(discovery.feature(FeatureCode::TPER).is_some());
+        }
+    }
+}
@@ -1,17 +1,26 @@
 //! This module implements Rust's global allocator interface using UEFI's memory allocation functions.
 //!
 //! If the `global_allocator` feature is enabled, the [`Allocator`] will be used
-//! as the global Rust allocator.
+//! as the global Rust allocator. If the `global_page_allocator` feature is
+//! enabled, the [`PageAllocator`] will be used instead.
 //!
-//! This allocator can only be used while boot services are active. If boot
+//! These allocators can only be used while boot services are active. If boot
 //! services are not active, `alloc` will return a null pointer, and `dealloc`
-//! will panic.
+//! will panic for the [`Allocator`] and leak the memory for the
+//! [`PageAllocator`].
+//!
+//! Memory cannot be allocated above [`Tpl::NOTIFY`]. While the level is
+//! raised above that with [`boot::raise_tpl_unchecked`], `alloc` will return a null
+//! pointer, and `dealloc` will leak the memory.
+//!
+//! [`Tpl::NOTIFY`]: boot::Tpl::NOTIFY
 
 use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
 use core::ptr::{self, NonNull};
 use core::sync::atomic::{AtomicU32, Ordering};
 
-use crate::boot;
+use crate::boot::{self, AllocateType, Tpl, PAGE_SIZE};
 use crate::mem::memory_map::MemoryType;
 use crate::proto::loaded_image::LoadedImage;
 
@@ -52,66 +61,522 @@ unsafe impl GlobalAlloc for Allocator {
     /// of type [`MemoryType::LOADER_DATA`] for UEFI applications, [`MemoryType::BOOT_SERVICES_DATA`]
     /// for UEFI boot drivers and [`MemoryType::RUNTIME_SERVICES_DATA`] for UEFI runtime drivers.
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        if !boot::are_boot_services_active() {
+        if !boot::are_boot_services_active() || !boot::allocation_allowed() {
             return ptr::null_mut();
         }
 
-        let size = layout.size();
-        let align = layout.align();
-        let memory_type = get_memory_type();
+        alloc_pool(get_memory_type(), layout)
+            .map(|ptr| ptr.as_ptr())
+            .unwrap_or(ptr::null_mut())
+    }
 
-        if align > 8 {
-            // The requested alignment is greater than 8, but `allocate_pool` is
-            // only guaranteed to provide eight-byte alignment. Allocate extra
-            // space so that we can return an appropriately-aligned pointer
-            // within the allocation.
-            let full_alloc_ptr = if let Ok(ptr) = boot::allocate_pool(memory_type, size + align) {
-                ptr.as_ptr()
-            } else {
-                return ptr::null_mut();
-            };
-
-            // Calculate the offset needed to get an aligned pointer within the
-            // full allocation. If that offset is zero, increase it to `align`
-            // so that we still have space to store the extra pointer described
-            // below.
-            let mut offset = full_alloc_ptr.align_offset(align);
-            if offset == 0 {
-                offset = align;
-            }
+    /// Deallocate memory using [`boot::free_pool`].
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if !boot::allocation_allowed() {
+            // Freeing memory is not allowed at this level, and there is no
+            // way to report an error, so leak the memory.
+            return;
+        }
+
+        // OK to unwrap: `ptr` is required to be a valid allocation by the trait API.
+        let ptr = NonNull::new(ptr).unwrap();
+
+        // Warning: this will panic after exiting boot services.
+        unsafe { free_pool(ptr, layout) };
+    }
+}
+
+/// Allocate memory for `layout`, which must not be zero-sized, using
+/// [`boot::allocate_pool`].
+fn alloc_pool(memory_type: MemoryType, layout: Layout) -> crate::Result<NonNull<u8>> {
+    let size = layout.size();
+    let align = layout.align();
+
+    if align > 8 {
+        // The requested alignment is greater than 8, but `allocate_pool` is
+        // only guaranteed to provide eight-byte alignment. Allocate extra
+        // space so that we can return an appropriately-aligned pointer
+        // within the allocation.
+        let full_alloc_ptr = boot::allocate_pool(memory_type, size + align)?.as_ptr();
+
+        // Calculate the offset needed to get an aligned pointer within the
+        // full allocation. If that offset is zero, increase it to `align`
+        // so that we still have space to store the extra pointer described
+        // below.
+        let mut offset = full_alloc_ptr.align_offset(align);
+        if offset == 0 {
+            offset = align;
+        }
 
-            // Before returning the aligned allocation, store a pointer to the
-            // full unaligned allocation in the bytes just before the aligned
-            // allocation. We know we have at least eight bytes there due to
-            // adding `align` to the memory allocation size. We also know the
-            // write is appropriately aligned for a `*mut u8` pointer because
-            // `align_ptr` is aligned, and alignments are always powers of two
-            // (as enforced by the `Layout` type).
+        // Before returning the aligned allocation, store a pointer to the
+        // full unaligned allocation in the bytes just before the aligned
+        // allocation. We know we have at least eight bytes there due to
+        // adding `align` to the memory allocation size. We also know the
+        // write is appropriately aligned for a `*mut u8` pointer because
+        // `align_ptr` is aligned, and alignments are always powers of two
+        // (as enforced by the `Layout` type).
+        unsafe {
             let aligned_ptr = full_alloc_ptr.add(offset);
             (aligned_ptr.cast::<*mut u8>()).sub(1).write(full_alloc_ptr);
-            aligned_ptr
-        } else {
-            // The requested alignment is less than or equal to eight, and
-            // `allocate_pool` always provides eight-byte alignment, so we can
-            // use `allocate_pool` directly.
-            boot::allocate_pool(memory_type, size)
-                .map(|ptr| ptr.as_ptr())
-                .unwrap_or(ptr::null_mut())
+            Ok(NonNull::new_unchecked(aligned_ptr))
         }
+    } else {
+        // The requested alignment is less than or equal to eight, and
+        // `allocate_pool` always provides eight-byte alignment, so we can
+        // use `allocate_pool` directly.
+        boot::allocate_pool(memory_type, size)
     }
+}
 
-    /// Deallocate memory using [`boot::free_pool`].
-    unsafe fn dealloc(&self, mut ptr: *mut u8, layout: Layout) {
-        if layout.align() > 8 {
-            // Retrieve the pointer to the full allocation that was packed right
-            // before the aligned allocation in `alloc`.
-            ptr = (ptr as *const *mut u8).sub(1).read();
+/// Free memory allocated by [`alloc_pool`] with the same `layout`.
+///
+/// # Safety
+///
+/// `ptr` must come from [`alloc_pool`] with the same `layout`.
+unsafe fn free_pool(mut ptr: NonNull<u8>, layout: Layout) {
+    if layout.align() > 8 {
+        // Retrieve the pointer to the full allocation that was packed right
+        // before the aligned allocation in `alloc_pool`.
+        ptr = unsafe { ptr.cast::<NonNull<u8>>().as_ptr().sub(1).read() };
+    }
+
+    unsafe { boot::free_pool(ptr) }.unwrap();
+}
+
+/// Allocator for memory of a chosen [`MemoryType`], using
+/// [`boot::allocate_pool`].
+///
+/// Use it for data that must survive [`boot::exit_boot_services`], such as
+/// [`MemoryType::RUNTIME_SERVICES_DATA`] for a runtime driver,
+/// [`MemoryType::ACPI_RECLAIM`] for tables that the OS reads, or a custom
+/// memory type (`0x8000_0000` and above) that an OS loader reserves for its
+/// kernel.
+///
+/// If the `unstable` and `alloc` features are enabled, this type implements
+/// the [`Allocator`] trait of the `allocator_api`, so that it can be used
+/// with collections such as `Box::new_in` and `Vec::new_in`.
+///
+/// After boot services have been exited, deallocating leaks the memory,
+/// since memory of these types stays allocated anyway.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::allocator::MemoryTypeAllocator;
+/// use uefi::mem::memory_map::MemoryType;
+///
+/// #[repr(C)]
+/// struct HandoffData {
+///     framebuffer: u64,
+///     rsdp: u64,
+/// }
+///
+/// # fn example() -> uefi::Result {
+/// let allocator = MemoryTypeAllocator::new(MemoryType::custom(0x8000_0001));
+/// let handoff = allocator.leak(HandoffData {
+///     framebuffer: 0,
+///     rsdp: 0,
+/// })?;
+/// handoff.framebuffer = 0x8000_0000;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Allocator`]: https://doc.rust-lang.org/alloc/alloc/trait.Allocator.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryTypeAllocator {
+    memory_type: MemoryType,
+}
+
+impl MemoryTypeAllocator {
+    /// Create an allocator for memory of `memory_type`.
+    #[must_use]
+    pub const fn new(memory_type: MemoryType) -> Self {
+        Self { memory_type }
+    }
+
+    /// The memory type of the allocations.
+    #[must_use]
+    pub const fn memory_type(&self) -> MemoryType {
+        self.memory_type
+    }
+
+    /// Allocate memory for `layout`.
+    ///
+    /// A zero-sized `layout` returns a dangling, but aligned, pointer without
+    /// allocating.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: boot services are not active, or the
+    ///   level is raised above [`Tpl::NOTIFY`] with
+    ///   [`boot::raise_tpl_unchecked`].
+    /// * See [`boot::allocate_pool`] for the other errors.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    /// [`Tpl::NOTIFY`]: boot::Tpl::NOTIFY
+    pub fn allocate(&self, layout: Layout) -> crate::Result<NonNull<u8>> {
+        if layout.size() == 0 {
+            return Ok(unsafe { NonNull::new_unchecked(layout.align() as *mut u8) });
+        }
+        if !boot::are_boot_services_active() || !boot::allocation_allowed() {
+            return Err(crate::Status::UNSUPPORTED.into());
+        }
+        alloc_pool(self.memory_type, layout)
+    }
+
+    /// Deallocate memory allocated by [`allocate`] with the same `layout`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from [`allocate`] of an allocator with the same
+    /// memory type and `layout`, and must not be used anymore.
+    ///
+    /// [`allocate`]: Self::allocate
+    pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() == 0 || !boot::are_boot_services_active() || !boot::allocation_allowed() {
+            return;
+        }
+        unsafe { free_pool(ptr, layout) };
+    }
+
+    /// Move `value` into newly allocated memory, which is never freed, and
+    /// get a reference to it.
+    ///
+    /// # Errors
+    ///
+    /// See [`allocate`]. `value` is dropped if the allocation fails.
+    ///
+    /// [`allocate`]: Self::allocate
+    pub fn leak<T>(&self, value: T) -> crate::Result<&'static mut T> {
+        let ptr = self.allocate(Layout::new::<T>())?.cast::<T>();
+        unsafe {
+            ptr.as_ptr().write(value);
+            Ok(&mut *ptr.as_ptr())
+        }
+    }
+}
+
+#[cfg(all(feature = "unstable", feature = "alloc"))]
+unsafe impl core::alloc::Allocator for MemoryTypeAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, core::alloc::AllocError> {
+        let ptr = Self::allocate(self, layout).map_err(|_| core::alloc::AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { Self::deallocate(self, ptr, layout) }
+    }
+}
+
+/// Number of pages that [`PageAllocator`] allocates at once for small
+/// allocations.
+const CHUNK_PAGES: usize = 256;
+
+/// Allocations of at least this size are made with their own pages by
+/// [`PageAllocator`].
+const LARGE_ALLOCATION: usize = 64 * 1024;
+
+/// Alignment and granularity of the blocks in a [`FreeList`].
+const BLOCK_ALIGN: usize = 16;
+
+/// Allocator which allocates memory in large page ranges with
+/// [`boot::allocate_pages`], and hands out memory from them with a free list.
+///
+/// This is faster than the [`Allocator`] for workloads with many
+/// allocations, such as decompression, because the pool allocator of some
+/// firmware is slow. Allocations of 64 KiB or more get their own pages, and
+/// smaller allocations are made from ranges of 1 MiB. Those ranges are not
+/// given back to the firmware when their memory is freed, but reused for
+/// later allocations.
+///
+/// The memory type is the same as for [`Allocator`].
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::allocator::PageAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: PageAllocator = PageAllocator::new();
+/// ```
+#[derive(Debug)]
+pub struct PageAllocator {
+    free_list: UnsafeCell<FreeList>,
+}
+
+// SAFETY: the free list is only accessed at `Tpl::NOTIFY`, and allocations
+// can only be made on the bootstrap processor.
+unsafe impl Sync for PageAllocator {}
+
+impl PageAllocator {
+    /// Create an allocator that has not allocated any pages yet.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            free_list: UnsafeCell::new(FreeList::new()),
+        }
+    }
+}
+
+impl Default for PageAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `layout` gets its own pages in [`PageAllocator`].
+const fn is_large(layout: Layout) -> bool {
+    layout.size() >= LARGE_ALLOCATION || layout.align() > PAGE_SIZE
+}
+
+unsafe impl GlobalAlloc for PageAllocator {
+    /// Allocate memory from the free list, or using
+    /// [`boot::allocate_pages_aligned`] for large allocations.
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !boot::are_boot_services_active() || !boot::allocation_allowed() {
+            return ptr::null_mut();
+        }
+
+        let memory_type = get_memory_type();
+        if is_large(layout) {
+            return boot::allocate_pages_aligned(
+                AllocateType::AnyPages,
+                memory_type,
+                layout.size().div_ceil(PAGE_SIZE),
+                layout.align(),
+            )
+            .map(|ptr| ptr.as_ptr())
+            .unwrap_or(ptr::null_mut());
+        }
+
+        let size = layout.size().next_multiple_of(BLOCK_ALIGN);
+        let align = layout.align().max(BLOCK_ALIGN);
+        let _guard = boot::raise_tpl(Tpl::NOTIFY);
+        // SAFETY: the level is raised, so nothing else accesses the list.
+        let free_list = unsafe { &mut *self.free_list.get() };
+        if let Some(ptr) = free_list.alloc(size, align) {
+            return ptr.as_ptr();
+        }
+
+        let Ok(chunk) = boot::allocate_pages(AllocateType::AnyPages, memory_type, CHUNK_PAGES)
+        else {
+            return ptr::null_mut();
+        };
+        unsafe { free_list.add(chunk.as_ptr(), CHUNK_PAGES * PAGE_SIZE) };
+        free_list
+            .alloc(size, align)
+            .map(|ptr| ptr.as_ptr())
+            .unwrap_or(ptr::null_mut())
+    }
+
+    /// Give the memory back to the free list, or free it using
+    /// [`boot::free_pages`] for large allocations.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if !boot::are_boot_services_active() || !boot::allocation_allowed() {
+            // Freeing memory is not allowed, and there is no way to report
+            // an error, so leak the memory.
+            return;
         }
 
         // OK to unwrap: `ptr` is required to be a valid allocation by the trait API.
         let ptr = NonNull::new(ptr).unwrap();
+        if is_large(layout) {
+            unsafe { boot::free_pages(ptr, layout.size().div_ceil(PAGE_SIZE)) }.unwrap();
+            return;
+        }
 
-        // Warning: this will panic after exiting boot services.
-        boot::free_pool(ptr).unwrap();
+        let _guard = boot::raise_tpl(Tpl::NOTIFY);
+        // SAFETY: the level is raised, so nothing else accesses the list, and
+        // the block was allocated from the list with the same size.
+        unsafe {
+            (*self.free_list.get()).add(ptr.as_ptr(), layout.size().next_multiple_of(BLOCK_ALIGN))
+        };
+    }
+}
+
+/// Header of a free block in a [`FreeList`], stored in the block itself.
+#[repr(C, align(16))]
+struct FreeBlock {
+    size: usize,
+    next: *mut Self,
+}
+
+/// List of free blocks, sorted by address. Adjacent blocks are merged.
+///
+/// All blocks are aligned to and a multiple of [`BLOCK_ALIGN`].
+#[derive(Debug)]
+struct FreeList {
+    head: *mut FreeBlock,
+}
+
+impl FreeList {
+    const fn new() -> Self {
+        Self {
+            head: ptr::null_mut(),
+        }
+    }
+
+    /// Find the first block that fits `size` bytes at `align`, and take them
+    /// out of the list.
+    ///
+    /// `size` and `align` must be multiples of [`BLOCK_ALIGN`].
+    fn alloc(&mut self, size: usize, align: usize) -> Option<NonNull<u8>> {
+        let mut prev: *mut FreeBlock = ptr::null_mut();
+        let mut block = self.head;
+        while !block.is_null() {
+            // SAFETY: all blocks in the list are valid.
+            let FreeBlock {
+                size: block_size,
+                next,
+            } = unsafe { block.read() };
+            let start = block as usize;
+            let end = start + block_size;
+            if let Some(aligned) = start.checked_next_multiple_of(align) {
+                if aligned
+                    .checked_add(size)
+                    .is_some_and(|alloc_end| alloc_end <= end)
+                {
+                    let front = aligned - start;
+                    let back = end - aligned - size;
+
+                    // Keep the memory after the allocation in the list.
+                    let mut following = next;
+                    if back != 0 {
+                        following = (aligned + size) as *mut FreeBlock;
+                        unsafe { following.write(FreeBlock { size: back, next }) };
+                    }
+
+                    // Keep the memory before the allocation in the list.
+                    if front != 0 {
+                        unsafe {
+                            block.write(FreeBlock {
+                                size: front,
+                                next: following,
+                            })
+                        };
+                    } else if prev.is_null() {
+                        self.head = following;
+                    } else {
+                        unsafe { (*prev).next = following };
+                    }
+                    return NonNull::new(aligned as *mut u8);
+                }
+            }
+            prev = block;
+            block = next;
+        }
+        None
+    }
+
+    /// Add the `size` bytes at `ptr` to the list.
+    ///
+    /// # Safety
+    ///
+    /// The memory must be valid and unused, aligned to [`BLOCK_ALIGN`], and
+    /// `size` must be a non-zero multiple of [`BLOCK_ALIGN`].
+    unsafe fn add(&mut self, ptr: *mut u8, mut size: usize) {
+        let addr = ptr as usize;
+        let mut prev: *mut FreeBlock = ptr::null_mut();
+        let mut next = self.head;
+        while !next.is_null() && (next as usize) < addr {
+            prev = next;
+            next = unsafe { (*next).next };
+        }
+
+        // Merge with the following block.
+        if !next.is_null() && addr + size == next as usize {
+            unsafe {
+                size += (*next).size;
+                next = (*next).next;
+            }
+        }
+
+        // Merge with the previous block.
+        if !prev.is_null() && prev as usize + unsafe { (*prev).size } == addr {
+            unsafe {
+                (*prev).size += size;
+                (*prev).next = next;
+            }
+            return;
+        }
+
+        let block = ptr.cast::<FreeBlock>();
+        unsafe { block.write(FreeBlock { size, next }) };
+        if prev.is_null() {
+            self.head = block;
+        } else {
+            unsafe { (*prev).next = block };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Get the address and size of the blocks in `list`.
+    fn blocks(list: &FreeList) -> Vec<(usize, usize)> {
+        let mut blocks = Vec::new();
+        let mut block = list.head;
+        while !block.is_null() {
+            let FreeBlock { size, next } = unsafe { block.read() };
+            blocks.push((block as usize, size));
+            block = next;
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_free_list() {
+        #[repr(align(4096))]
+        struct Chunk([u8; 4096]);
+        let mut chunk = Chunk([0; 4096]);
+        let base = chunk.0.as_mut_ptr();
+        let start = base as usize;
+
+        let mut list = FreeList::new();
+        assert_eq!(list.alloc(16, 16), None);
+        unsafe { list.add(base, 4096) };
+        assert_eq!(blocks(&list), [(start, 4096)]);
+
+        let a = list.alloc(32, 16).unwrap();
+        let b = list.alloc(16, 16).unwrap();
+        assert_eq!(a.as_ptr() as usize, start);
+        assert_eq!(b.as_ptr() as usize, start + 32);
+        assert_eq!(blocks(&list), [(start + 48, 4048)]);
+
+        // Aligning the allocation keeps the memory before it.
+        let c = list.alloc(64, 1024).unwrap();
+        assert_eq!(c.as_ptr() as usize, start + 1024);
+        assert_eq!(blocks(&list), [(start + 48, 976), (start + 1088, 3008)]);
+        assert_eq!(list.alloc(4096, 16), None);
+
+        // Freeing merges the blocks again.
+        unsafe { list.add(a.as_ptr(), 32) };
+        assert_eq!(
+            blocks(&list),
+            [(start, 32), (start + 48, 976), (start + 1088, 3008)]
+        );
+        unsafe { list.add(c.as_ptr(), 64) };
+        assert_eq!(blocks(&list), [(start, 32), (start + 48, 4048)]);
+        unsafe { list.add(b.as_ptr(), 16) };
+        assert_eq!(blocks(&list), [(start, 4096)]);
+
+        // A block that fits exactly is removed.
+        let all = list.alloc(4096, 16).unwrap();
+        assert_eq!(all.as_ptr(), base);
+        assert!(blocks(&list).is_empty());
+    }
+
+    #[test]
+    fn test_is_large() {
+        assert!(!is_large(Layout::from_size_align(16, 8).unwrap()));
+        assert!(!is_large(Layout::from_size_align(4096, 4096).unwrap()));
+        assert!(is_large(Layout::from_size_align(16, 8192).unwrap()));
+        assert!(is_large(
+            Layout::from_size_align(LARGE_ALLOCATION, 8).unwrap()
+        ));
     }
 }
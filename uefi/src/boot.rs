@@ -19,12 +19,22 @@
 //!
 //! [`proto`]: crate::proto
 
+#[cfg(feature = "alloc")]
+pub use event::{create_notify_event, NotifyEvent, Timer};
+pub use time::{Instant, Watchdog};
 pub use uefi_raw::table::boot::{
-    EventType, MemoryAttribute, MemoryDescriptor, MemoryType, Tpl, PAGE_SIZE,
+    EventType, MemoryAttribute, MemoryDescriptor, MemoryType, OpenProtocolFlags, Tpl, PAGE_SIZE,
 };
 
+#[cfg(feature = "alloc")]
+mod event;
+mod time;
+
 use crate::data_types::PhysicalAddress;
-use crate::mem::memory_map::{MemoryMapBackingMemory, MemoryMapKey, MemoryMapMeta, MemoryMapOwned};
+use crate::mem::memory_map::{
+    MemoryMap, MemoryMapBackingMemory, MemoryMapKey, MemoryMapMeta, MemoryMapMut, MemoryMapOwned,
+};
+use crate::mem::tracking::{self, AllocationKind};
 use crate::polyfill::maybe_uninit_slice_assume_init_ref;
 #[cfg(doc)]
 use crate::proto::device_path::LoadedImageDevicePath;
@@ -35,16 +45,22 @@ use crate::proto::{BootPolicy, Protocol, ProtocolPointer};
 use crate::runtime::{self, ResetType};
 use crate::table::Revision;
 use crate::util::opt_nonnull_to_ptr;
-use crate::{table, Char16, Error, Event, Guid, Handle, Result, Status, StatusExt};
+use crate::{
+    duration, guid, table, CStr16, Char16, Error, Event, Guid, Handle, Result, Status, StatusExt,
+};
 use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
 use core::mem::MaybeUninit;
-use core::ops::{Deref, DerefMut};
+use core::ops::{Deref, DerefMut, Range};
+use core::panic::Location;
+use core::pin::Pin;
 use core::ptr::{self, NonNull};
-use core::sync::atomic::{AtomicPtr, Ordering};
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use core::time::Duration;
 use core::{mem, slice};
 use uefi_raw::table::boot::InterfaceType;
 #[cfg(feature = "alloc")]
-use {alloc::vec::Vec, uefi::ResultExt};
+use {crate::CString16, alloc::vec::Vec, uefi::ResultExt};
 
 /// Global image handle. This is only set by [`set_image_handle`], and it is
 /// only read by [`image_handle`].
@@ -94,30 +110,105 @@ fn boot_services_raw_panicking() -> NonNull<uefi_raw::table::boot::BootServices>
     NonNull::new(st.boot_services).expect("boot services are not active")
 }
 
+/// The task priority level most recently set with a [`TplGuard`], or
+/// [`Tpl::APPLICATION`] if none is active.
+static GUARD_TPL: AtomicUsize = AtomicUsize::new(Tpl::APPLICATION.0);
+
+/// Gets the current task priority level.
+///
+/// The firmware does not provide a way to query the level, so this briefly
+/// raises it to [`Tpl::HIGH_LEVEL`] to get the old level.
+#[must_use]
+pub fn current_tpl() -> Tpl {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    // Safety: the level is restored immediately.
+    unsafe {
+        let tpl = (bt.raise_tpl)(Tpl::HIGH_LEVEL);
+        (bt.restore_tpl)(tpl);
+        tpl
+    }
+}
+
 /// Raises a task's priority level and returns a [`TplGuard`].
 ///
-/// The effect of calling `raise_tpl` with a `Tpl` that is below the current
-/// one (which, sadly, cannot be queried) is undefined by the UEFI spec,
-/// which also warns against remaining at high `Tpl`s for a long time.
+/// The effect of raising to a `Tpl` that is below the current one is
+/// undefined by the UEFI spec, so in that case the level is left unchanged.
+/// The spec also warns against remaining at high `Tpl`s for a long time.
 ///
 /// This function returns an RAII guard that will automatically restore the
-/// original `Tpl` when dropped.
+/// original `Tpl` when dropped. This can be used for critical sections
+/// that must not be interrupted by event callbacks, for example with
+/// [`Tpl::NOTIFY`]. Guards dropped out of order never raise the level
+/// again; see [`TplGuard`].
+///
+/// Most boot services, including memory allocation, cannot be used above
+/// [`Tpl::NOTIFY`], so this function only raises the level up to that. Use
+/// [`raise_tpl_unchecked`] for higher levels.
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot::{self, Tpl};
+///
+/// fn update_shared_state() {
+///     let _guard = boot::raise_tpl(Tpl::NOTIFY);
+///     // Event callbacks at `Tpl::NOTIFY` or below cannot run here.
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if `tpl` is above [`Tpl::NOTIFY`].
+#[must_use]
+pub fn raise_tpl(tpl: Tpl) -> TplGuard {
+    assert!(
+        tpl <= Tpl::NOTIFY,
+        "use `raise_tpl_unchecked` to raise the level above `Tpl::NOTIFY`"
+    );
+    // Safety: boot services can be used at this level.
+    unsafe { raise_tpl_unchecked(tpl) }
+}
+
+/// Raises a task's priority level to any level, including above
+/// [`Tpl::NOTIFY`], and returns a [`TplGuard`]. See [`raise_tpl`].
+///
+/// While a guard for a level above [`Tpl::NOTIFY`] is active, the global
+/// [`Allocator`] fails allocations and leaks deallocated memory, rather than
+/// calling into the firmware.
+///
+/// [`Allocator`]: crate::allocator::Allocator
 ///
 /// # Safety
 ///
-/// Raising a task's priority level can affect other running tasks and
-/// critical processes run by UEFI. The highest priority level is the
-/// most dangerous, since it disables interrupts.
+/// While the guard is active at a level above [`Tpl::NOTIFY`], the caller
+/// must only use the boot services that the UEFI specification allows at
+/// that level, such as [`signal_event`] and [`stall`]. In particular, memory
+/// must not be allocated or freed other than through the global allocator,
+/// and protocols must not be opened or located. Some firmware lowers the
+/// level inside these services, which breaks the critical section.
 #[must_use]
-pub unsafe fn raise_tpl(tpl: Tpl) -> TplGuard {
+pub unsafe fn raise_tpl_unchecked(tpl: Tpl) -> TplGuard {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
+    let tpl = tpl.max(current_tpl());
+    // Safety: the level is not lowered, and the guard restores it.
+    let old_tpl = unsafe { (bt.raise_tpl)(tpl) };
+    let old_guard_tpl = Tpl(GUARD_TPL.swap(tpl.0, Ordering::AcqRel));
     TplGuard {
-        old_tpl: (bt.raise_tpl)(tpl),
+        old_tpl,
+        old_guard_tpl,
     }
 }
 
+/// Whether memory can be allocated and freed at the level set with
+/// [`raise_tpl_unchecked`].
+pub(crate) fn allocation_allowed() -> bool {
+    Tpl(GUARD_TPL.load(Ordering::Acquire)) <= Tpl::NOTIFY
+}
+
 /// Allocates memory pages from the system.
 ///
 /// UEFI OS loaders should allocate memory of the type `LoaderData`.
@@ -128,10 +219,13 @@ pub unsafe fn raise_tpl(tpl: Tpl) -> TplGuard {
 /// * [`Status::INVALID_PARAMETER`]: `mem_ty` is [`MemoryType::PERSISTENT_MEMORY`],
 ///   [`MemoryType::UNACCEPTED`], or in the range [`MemoryType::MAX`]`..=0x6fff_ffff`.
 /// * [`Status::NOT_FOUND`]: the requested pages could not be found.
+#[track_caller]
 pub fn allocate_pages(ty: AllocateType, mem_ty: MemoryType, count: usize) -> Result<NonNull<u8>> {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
+    let location = Location::caller();
+    tracking::check_allowed(location)?;
     let (ty, mut addr) = match ty {
         AllocateType::AnyPages => (0, 0),
         AllocateType::MaxAddress(addr) => (1, addr),
@@ -139,6 +233,13 @@ pub fn allocate_pages(ty: AllocateType, mem_ty: MemoryType, count: usize) -> Res
     };
     let addr =
         unsafe { (bt.allocate_pages)(ty, mem_ty, count, &mut addr) }.to_result_with_val(|| addr)?;
+    tracking::track(
+        AllocationKind::Pages,
+        addr,
+        count * PAGE_SIZE,
+        mem_ty,
+        location,
+    );
     let ptr = addr as *mut u8;
     Ok(NonNull::new(ptr).expect("allocate_pages must not return a null pointer if successful"))
 }
@@ -159,7 +260,152 @@ pub unsafe fn free_pages(ptr: NonNull<u8>, count: usize) -> Result {
     let bt = unsafe { bt.as_ref() };
 
     let addr = ptr.as_ptr() as PhysicalAddress;
-    unsafe { (bt.free_pages)(addr, count) }.to_result()
+    unsafe { (bt.free_pages)(addr, count) }.to_result()?;
+    tracking::untrack_pages(addr, count);
+    Ok(())
+}
+
+/// Allocates memory pages below 4 GiB, for example for a 32-bit kernel.
+///
+/// # Errors
+///
+/// See [`allocate_pages`].
+#[track_caller]
+pub fn allocate_pages_below_4gib(mem_ty: MemoryType, count: usize) -> Result<NonNull<u8>> {
+    allocate_pages(AllocateType::MaxAddress(u64::from(u32::MAX)), mem_ty, count)
+}
+
+/// Allocates memory pages whose start is a multiple of `align` bytes, for
+/// example 2 MiB to map them with large pages.
+///
+/// If `align` is larger than [`PAGE_SIZE`], more pages are allocated than
+/// needed, and the pages before and after the aligned range are freed again.
+/// The pages can be freed with [`free_pages`], like any other pages.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: `align` is not a power of two, or `ty`
+///   is [`AllocateType::Address`] with an unaligned address.
+/// * See [`allocate_pages`] for the other errors.
+#[track_caller]
+pub fn allocate_pages_aligned(
+    ty: AllocateType,
+    mem_ty: MemoryType,
+    count: usize,
+    align: usize,
+) -> Result<NonNull<u8>> {
+    if !align.is_power_of_two() {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let align = align as u64;
+    if let AllocateType::Address(addr) = ty {
+        if addr % align != 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+    }
+    if align <= PAGE_SIZE as u64 || matches!(ty, AllocateType::Address(_)) {
+        return allocate_pages(ty, mem_ty, count);
+    }
+
+    let extra_pages = (align / PAGE_SIZE as u64 - 1) as usize;
+    let total = count
+        .checked_add(extra_pages)
+        .ok_or(Status::OUT_OF_RESOURCES)?;
+    let ptr = allocate_pages(ty, mem_ty, total)?;
+    let addr = ptr.as_ptr() as PhysicalAddress;
+    let (head_pages, aligned) = trim_to_alignment(addr, align);
+
+    // Freeing the trimmed pages can only fail if the firmware is broken, in
+    // which case they are leaked, but the aligned pages are still usable.
+    // If tracking is enabled, the recorded allocation shrinks to the aligned
+    // pages.
+    if head_pages != 0 {
+        let _ = unsafe { free_pages(ptr, head_pages) };
+    }
+    let tail_pages = extra_pages - head_pages;
+    if tail_pages != 0 {
+        let tail = aligned + (count * PAGE_SIZE) as u64;
+        let _ = unsafe { free_pages(NonNull::new(tail as *mut u8).unwrap(), tail_pages) };
+    }
+    Ok(NonNull::new(aligned as *mut u8).unwrap())
+}
+
+/// Allocates memory pages within the physical address `range`, starting at a
+/// multiple of `align` bytes, for example for a real-mode trampoline that
+/// must be between 64 KiB and 1 MiB.
+///
+/// If `range` starts at zero, this is the same as [`allocate_pages_aligned`]
+/// with [`AllocateType::MaxAddress`]. Otherwise the free regions of the
+/// [`memory_map`] are tried from the lowest address.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: `align` is not a power of two.
+/// * [`Status::NOT_FOUND`]: there are not enough free pages within `range`.
+/// * See [`allocate_pages`] for the other errors.
+#[track_caller]
+pub fn allocate_pages_in_range(
+    range: Range<PhysicalAddress>,
+    mem_ty: MemoryType,
+    count: usize,
+    align: usize,
+) -> Result<NonNull<u8>> {
+    if !align.is_power_of_two() {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    if range.end <= range.start {
+        return Err(Status::NOT_FOUND.into());
+    }
+    if range.start == 0 {
+        return allocate_pages_aligned(
+            AllocateType::MaxAddress(range.end - 1),
+            mem_ty,
+            count,
+            align,
+        );
+    }
+
+    let align = (align as u64).max(PAGE_SIZE as u64);
+    let mut memory_map = memory_map(MemoryType::LOADER_DATA)?;
+    memory_map.sort();
+    for desc in memory_map.entries() {
+        if desc.ty != MemoryType::CONVENTIONAL {
+            continue;
+        }
+        let Some(start) = range_candidate(desc, &range, count, align) else {
+            continue;
+        };
+        // The memory map may be outdated, for example if allocating it took
+        // the pages, so try the next region if this one fails.
+        if let Ok(ptr) = allocate_pages(AllocateType::Address(start), mem_ty, count) {
+            return Ok(ptr);
+        }
+    }
+    Err(Status::NOT_FOUND.into())
+}
+
+/// Get the number of pages before the first multiple of `align` at or after
+/// `addr`, and that multiple.
+const fn trim_to_alignment(addr: PhysicalAddress, align: u64) -> (usize, PhysicalAddress) {
+    let aligned = (addr + align - 1) & !(align - 1);
+    (((aligned - addr) / PAGE_SIZE as u64) as usize, aligned)
+}
+
+/// Get the lowest multiple of `align` at which `count` pages fit both in
+/// `desc` and in `range`.
+fn range_candidate(
+    desc: &MemoryDescriptor,
+    range: &Range<PhysicalAddress>,
+    count: usize,
+    align: u64,
+) -> Option<PhysicalAddress> {
+    let size = (count as u64).checked_mul(PAGE_SIZE as u64)?;
+    let desc_end = desc
+        .phys_start
+        .checked_add(desc.page_count.checked_mul(PAGE_SIZE as u64)?)?;
+    let start = desc.phys_start.max(range.start).checked_add(align - 1)? & !(align - 1);
+    let end = desc_end.min(range.end);
+    (start.checked_add(size)? <= end).then_some(start)
 }
 
 /// Allocates from a memory pool. The pointer will be 8-byte aligned.
@@ -169,13 +415,23 @@ pub unsafe fn free_pages(ptr: NonNull<u8>, count: usize) -> Result {
 /// * [`Status::OUT_OF_RESOURCES`]: allocation failed.
 /// * [`Status::INVALID_PARAMETER`]: `mem_ty` is [`MemoryType::PERSISTENT_MEMORY`],
 ///   [`MemoryType::UNACCEPTED`], or in the range [`MemoryType::MAX`]`..=0x6fff_ffff`.
+#[track_caller]
 pub fn allocate_pool(mem_ty: MemoryType, size: usize) -> Result<NonNull<u8>> {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
+    let location = Location::caller();
+    tracking::check_allowed(location)?;
     let mut buffer = ptr::null_mut();
     let ptr =
         unsafe { (bt.allocate_pool)(mem_ty, size, &mut buffer) }.to_result_with_val(|| buffer)?;
+    tracking::track(
+        AllocationKind::Pool,
+        ptr as PhysicalAddress,
+        size,
+        mem_ty,
+        location,
+    );
 
     Ok(NonNull::new(ptr).expect("allocate_pool must not return a null pointer if successful"))
 }
@@ -194,7 +450,9 @@ pub unsafe fn free_pool(ptr: NonNull<u8>) -> Result {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
-    unsafe { (bt.free_pool)(ptr.as_ptr()) }.to_result()
+    unsafe { (bt.free_pool)(ptr.as_ptr()) }.to_result()?;
+    tracking::untrack_pool(ptr.as_ptr() as PhysicalAddress);
+    Ok(())
 }
 
 /// Queries the `get_memory_map` function of UEFI to retrieve the current
@@ -351,6 +609,7 @@ pub unsafe fn create_event(
 ///
 /// If no group is specified by `event_group`, this function behaves as if the
 /// same parameters had been passed to `create_event()`.
+/// See [`EventGroup`] for the groups defined by the UEFI specification.
 ///
 /// Event groups are collections of events identified by a shared GUID where,
 /// when one member event is signaled, all other events are signaled and their
@@ -418,6 +677,32 @@ pub unsafe fn create_event_ex(
     )
 }
 
+/// Signals all events in `event_group`.
+///
+/// This creates a temporary event in the group and signals it. For example,
+/// boot managers signal [`EventGroup::READY_TO_BOOT`] before starting a boot
+/// option.
+///
+/// # Errors
+///
+/// * [`Status::OUT_OF_RESOURCES`]: the event could not be allocated.
+/// * [`Status::UNSUPPORTED`]: the firmware is older than UEFI 2.0.
+pub fn signal_event_group(event_group: &EventGroup) -> Result {
+    // Safety: the event has no callback.
+    let event = unsafe {
+        create_event_ex(
+            EventType::empty(),
+            Tpl::CALLBACK,
+            None,
+            None,
+            Some(NonNull::from(&event_group.0)),
+        )
+    }?;
+    let result = signal_event(&event);
+    close_event(event)?;
+    result
+}
+
 /// Checks to see if an event is signaled, without blocking execution to wait for it.
 ///
 /// Returns `Ok(true)` if the event is in the signaled state or `Ok(false)`
@@ -443,6 +728,24 @@ pub fn check_event(event: Event) -> Result<bool> {
     }
 }
 
+/// Places `event` in the signaled state.
+///
+/// If `event` is of type [`NOTIFY_SIGNAL`], its notification function is
+/// scheduled. If `event` is part of an event group, all events in the group
+/// are signaled.
+///
+/// # Errors
+///
+/// The specification does not list any errors.
+///
+/// [`NOTIFY_SIGNAL`]: EventType::NOTIFY_SIGNAL
+pub fn signal_event(event: &Event) -> Result {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    unsafe { (bt.signal_event)(event.as_ptr()) }.to_result()
+}
+
 /// Removes `event` from any event group to which it belongs and closes it.
 ///
 /// If `event` was registered with [`register_protocol_notify`], then the
@@ -536,7 +839,16 @@ pub fn wait_for_event(events: &mut [Event]) -> Result<usize, Option<usize>> {
 ///
 /// Usually one disconnects and then reconnects certain drivers
 /// to make them rescan some state that changed, e.g. reconnecting
-/// a block handle after your app modified disk partitions.
+/// a block handle after your app modified disk partitions. It is also
+/// needed to start a driver that was loaded after the controllers were
+/// connected; see [`connect_all_controllers`] for connecting it to every
+/// controller.
+///
+/// If `driver_image` is set, that driver is tried before any other drivers.
+/// If `remaining_device_path` is set, it is passed to the drivers to select
+/// the child controller to create; an end node means that no child controller
+/// is created. If `recursive` is true, the drivers are also connected to the
+/// child controllers, and to their children.
 ///
 /// # Errors
 ///
@@ -553,10 +865,18 @@ pub fn connect_controller(
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
+    // The firmware takes a null-terminated list of driver images.
+    let mut driver_images = [Handle::opt_to_ptr(driver_image), ptr::null_mut()];
+    let driver_images = if driver_image.is_some() {
+        driver_images.as_mut_ptr().cast()
+    } else {
+        ptr::null_mut()
+    };
+
     unsafe {
         (bt.connect_controller)(
             controller.as_ptr(),
-            Handle::opt_to_ptr(driver_image),
+            driver_images,
             remaining_device_path
                 .map(|dp| dp.as_ffi_ptr())
                 .unwrap_or(ptr::null())
@@ -567,8 +887,30 @@ pub fn connect_controller(
     .to_result_with_err(|_| ())
 }
 
+/// Connect drivers to all controllers in the system, recursively.
+///
+/// This is the equivalent of the shell's `connect -r`, and is typically
+/// called after loading and starting drivers, so that they bind to the
+/// devices they support. Failures to connect individual controllers are
+/// ignored, since most handles are not controllers that any driver supports.
+///
+/// # Errors
+///
+/// * [`Status::OUT_OF_RESOURCES`]: the list of handles could not be allocated.
+pub fn connect_all_controllers() -> Result {
+    let handles = locate_handle_buffer(SearchType::AllHandles)?;
+    for &handle in handles.iter() {
+        let _ = connect_controller(handle, None, None, true);
+    }
+    Ok(())
+}
+
 /// Disconnect one or more drivers from a controller.
 ///
+/// If `driver_image` is `None`, all drivers managing `controller` are
+/// disconnected. If `child` is set, only that child controller is destroyed;
+/// otherwise all child controllers are destroyed.
+///
 /// See also [`connect_controller`].
 ///
 /// # Errors
@@ -686,6 +1028,152 @@ pub unsafe fn uninstall_protocol_interface(
     (bt.uninstall_protocol_interface)(handle.as_ptr(), protocol, interface).to_result()
 }
 
+/// Installs the protocol interface `interface` on `handle`, or on a new
+/// handle if `handle` is `None`.
+///
+/// This is a safe variant of [`install_protocol_interface`]. The interface
+/// is borrowed for as long as it is installed, which is tracked by the
+/// returned [`InstalledProtocol`]. Pass it to [`uninstall_protocol`] to get
+/// the interface back. If an interface is never uninstalled, dropping the
+/// `InstalledProtocol` leaves it installed.
+///
+/// The interface must be `'static` and pinned, since other code can use it
+/// at any time until it is uninstalled. A pinned static reference can be
+/// created with [`Pin::static_mut`], from a leaked `Box` for example.
+///
+/// # Errors
+///
+/// The interface is returned in the error data.
+///
+/// * [`Status::OUT_OF_RESOURCES`]: failed to allocate a new handle.
+/// * [`Status::INVALID_PARAMETER`]: this protocol is already installed on the handle.
+///
+/// # Example
+///
+/// ```
+/// use core::pin::Pin;
+/// use uefi::boot::{self, InstalledProtocol};
+/// use uefi::proto::unsafe_protocol;
+///
+/// #[derive(Debug)]
+/// #[repr(C)]
+/// #[unsafe_protocol("8ed6a3c1-3a6a-45b1-9c3f-2cd1e76ed3d0")]
+/// struct Counter {
+///     value: u32,
+/// }
+///
+/// fn publish_counter() -> uefi::Result<InstalledProtocol<Counter>> {
+///     let counter = Box::leak(Box::new(Counter { value: 0 }));
+///     boot::install_protocol(None, Pin::static_mut(counter))
+///         .map_err(|err| err.to_err_without_payload())
+/// }
+/// ```
+pub fn install_protocol<P: Protocol + Debug>(
+    handle: Option<Handle>,
+    interface: Pin<&'static mut P>,
+) -> Result<InstalledProtocol<P>, Pin<&'static mut P>> {
+    // Safety: the interface is never moved or accessed through `interface`
+    // while it is installed.
+    let ptr = NonNull::from(unsafe { Pin::into_inner_unchecked(interface) });
+    // Safety: the GUID matches the interface type, and the interface is
+    // valid for as long as it is installed.
+    match unsafe { install_protocol_interface(handle, &P::GUID, ptr.as_ptr().cast()) } {
+        Ok(handle) => Ok(InstalledProtocol {
+            handle,
+            interface: ptr,
+        }),
+        Err(err) => Err(Error::new(err.status(), unsafe { pin_static(ptr) })),
+    }
+}
+
+/// Replaces the interface of `installed` with `new_interface`, and returns
+/// the new [`InstalledProtocol`] and the old interface.
+///
+/// This is a safe variant of [`reinstall_protocol_interface`]. As with
+/// [`install_protocol`], any process that has registered to wait for the
+/// installation of the interface is notified.
+///
+/// # Errors
+///
+/// The unchanged `installed` and `new_interface` are returned in the error
+/// data.
+///
+/// * [`Status::NOT_FOUND`]: the old interface was not found on the handle.
+/// * [`Status::ACCESS_DENIED`]: the old interface is still in use and cannot be uninstalled.
+pub fn reinstall_protocol<P: Protocol + Debug>(
+    installed: InstalledProtocol<P>,
+    new_interface: Pin<&'static mut P>,
+) -> Result<ProtocolPair<P>, ProtocolPair<P>> {
+    // Safety: see `install_protocol`.
+    let new_ptr = NonNull::from(unsafe { Pin::into_inner_unchecked(new_interface) });
+    let result = unsafe {
+        reinstall_protocol_interface(
+            installed.handle,
+            &P::GUID,
+            installed.interface.as_ptr().cast(),
+            new_ptr.as_ptr().cast(),
+        )
+    };
+    match result {
+        Ok(()) => {
+            let old = unsafe { pin_static(installed.interface) };
+            let installed = InstalledProtocol {
+                handle: installed.handle,
+                interface: new_ptr,
+            };
+            Ok((installed, old))
+        }
+        Err(err) => Err(Error::new(
+            err.status(),
+            (installed, unsafe { pin_static(new_ptr) }),
+        )),
+    }
+}
+
+/// Removes the interface of `installed` from its handle, and returns the
+/// interface.
+///
+/// This is a safe variant of [`uninstall_protocol_interface`]. The firmware
+/// refuses to uninstall interfaces that are opened by drivers. Interfaces
+/// that are opened with [`OpenProtocolAttributes::GetProtocol`] may still be
+/// referenced afterwards, which is why opening a protocol in that mode is
+/// unsafe.
+///
+/// # Errors
+///
+/// The unchanged `installed` is returned in the error data.
+///
+/// * [`Status::NOT_FOUND`]: the interface was not found on the handle.
+/// * [`Status::ACCESS_DENIED`]: the interface is still in use and cannot be uninstalled.
+pub fn uninstall_protocol<P: Protocol>(
+    installed: InstalledProtocol<P>,
+) -> Result<Pin<&'static mut P>, InstalledProtocol<P>> {
+    let result = unsafe {
+        uninstall_protocol_interface(
+            installed.handle,
+            &P::GUID,
+            installed.interface.as_ptr().cast(),
+        )
+    };
+    match result {
+        Ok(()) => Ok(unsafe { pin_static(installed.interface) }),
+        Err(err) => Err(Error::new(err.status(), installed)),
+    }
+}
+
+/// An installed protocol along with an interface that is not installed.
+type ProtocolPair<P> = (InstalledProtocol<P>, Pin<&'static mut P>);
+
+/// Restore the pinned static reference of an interface that is no longer
+/// installed.
+///
+/// # Safety
+///
+/// `ptr` must come from a `Pin<&'static mut P>`, and not be installed.
+unsafe fn pin_static<P>(mut ptr: NonNull<P>) -> Pin<&'static mut P> {
+    Pin::new_unchecked(ptr.as_mut())
+}
+
 /// Registers `event` to be signaled whenever a protocol interface is registered for
 /// `protocol` by [`install_protocol_interface`] or [`reinstall_protocol_interface`].
 ///
@@ -736,6 +1224,33 @@ pub fn protocols_per_handle(handle: Handle) -> Result<ProtocolsPerHandle> {
         })
 }
 
+/// Get the list of agents that have the protocol with GUID `protocol` open on
+/// `handle`.
+///
+/// This is useful to find out why opening a protocol fails with
+/// [`Status::ACCESS_DENIED`] or [`Status::ALREADY_STARTED`].
+///
+/// # Errors
+///
+/// * [`Status::NOT_FOUND`]: `handle` does not support the protocol.
+/// * [`Status::OUT_OF_RESOURCES`]: out of memory.
+pub fn open_protocol_information(
+    handle: Handle,
+    protocol: &Guid,
+) -> Result<OpenProtocolInformation> {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    let mut entries = ptr::null();
+    let mut count = 0;
+
+    unsafe { (bt.open_protocol_information)(handle.as_ptr(), protocol, &mut entries, &mut count) }
+        .to_result_with_val(|| OpenProtocolInformation {
+            entries: entries.cast(),
+            count,
+        })
+}
+
 /// Locates the handle of a device on the device path that supports the specified protocol.
 ///
 /// The `device_path` is updated to point at the remaining part of the [`DevicePath`] after
@@ -772,6 +1287,10 @@ pub fn locate_device_path<P: ProtocolPointer + ?Sized>(
 
 /// Enumerates all handles installed on the system which match a certain query.
 ///
+/// The handles are written to the caller-supplied `buffer`, so this does not
+/// allocate. See also [`locate_handle_array`], which uses an array of fixed
+/// size, and [`locate_handle_buffer`], which uses pool memory.
+///
 /// # Errors
 ///
 /// * [`Status::NOT_FOUND`]: no matching handles found.
@@ -811,6 +1330,39 @@ pub fn locate_handle<'buf>(
     }
 }
 
+/// Enumerates the handles which match a certain query into an array of up to
+/// `N` handles.
+///
+/// This does not allocate, so it can be used before an allocator is
+/// available. The array is returned by value; use [`locate_handle`] to fill a
+/// buffer of your own instead.
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot::{self, SearchType};
+/// use uefi::proto::console::text::Output;
+///
+/// fn count_consoles() -> uefi::Result<usize> {
+///     let handles = boot::locate_handle_array::<16>(SearchType::from_proto::<Output>())
+///         .map_err(|err| err.to_err_without_payload())?;
+///     Ok(handles.len())
+/// }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::NOT_FOUND`]: no matching handles found.
+/// * [`Status::BUFFER_TOO_SMALL`]: more than `N` handles match. The number of
+///   matching handles will be returned in the error data.
+pub fn locate_handle_array<const N: usize>(
+    search_ty: SearchType,
+) -> Result<HandleArray<N>, Option<usize>> {
+    let mut handles = [MaybeUninit::uninit(); N];
+    let len = locate_handle(search_ty, &mut handles)?.len();
+    Ok(HandleArray { handles, len })
+}
+
 /// Returns an array of handles that support the requested protocol in a
 /// pool-allocated buffer.
 ///
@@ -923,7 +1475,9 @@ pub fn get_handle_for_protocol<P: ProtocolPointer + ?Sized>() -> Result<Handle>
 
 /// Opens a protocol interface for a handle.
 ///
-/// See also [`open_protocol_exclusive`], which provides a safe subset of this
+/// See also [`open_protocol_exclusive`], [`open_protocol_by_driver`],
+/// [`open_protocol_by_driver_exclusive`] and
+/// [`open_protocol_by_child_controller`], which provide safe subsets of this
 /// functionality.
 ///
 /// This function attempts to get the protocol implementation of a handle, based
@@ -1010,6 +1564,104 @@ pub fn open_protocol_exclusive<P: ProtocolPointer + ?Sized>(
     }
 }
 
+/// Opens a protocol interface for a controller handle on behalf of a driver.
+///
+/// This is what a driver's `Start` function uses to take a protocol of the
+/// controller it manages, such as the PCI I/O protocol. No other driver can
+/// open the protocol with [`OpenProtocolAttributes::ByDriver`] while it is
+/// open. If the protocol is uninstalled or reinstalled, the firmware first
+/// calls the `Stop` function of the driver, which must drop the returned
+/// [`ScopedProtocol`].
+///
+/// `driver` is the handle of the driver's `EFI_DRIVER_BINDING_PROTOCOL`. The
+/// protocol is opened with `handle` as the controller, and is closed with the
+/// same parameters when the [`ScopedProtocol`] is dropped.
+///
+/// # Errors
+///
+/// * [`Status::UNSUPPORTED`]: the handle does not support the protocol.
+/// * [`Status::ALREADY_STARTED`]: `driver` already has the protocol open.
+/// * [`Status::ACCESS_DENIED`]: another driver has the protocol open.
+pub fn open_protocol_by_driver<P: ProtocolPointer + ?Sized>(
+    handle: Handle,
+    driver: Handle,
+) -> Result<ScopedProtocol<P>> {
+    // Safety: the firmware does not remove a protocol that is open by a
+    // driver without calling the driver's `Stop` function, and refuses to
+    // remove it if the protocol is still open afterwards.
+    unsafe {
+        open_protocol::<P>(
+            OpenProtocolParams {
+                handle,
+                agent: driver,
+                controller: Some(handle),
+            },
+            OpenProtocolAttributes::ByDriver,
+        )
+    }
+}
+
+/// Opens a protocol interface for a controller handle on behalf of a driver,
+/// disconnecting any other drivers that have it open.
+///
+/// This is like [`open_protocol_by_driver`], but first stops the drivers that
+/// opened the protocol with [`OpenProtocolAttributes::ByDriver`], and keeps
+/// any driver from opening it again while it is open.
+///
+/// # Errors
+///
+/// * [`Status::UNSUPPORTED`]: the handle does not support the protocol.
+/// * [`Status::ALREADY_STARTED`]: `driver` already has the protocol open.
+/// * [`Status::ACCESS_DENIED`]: the protocol is open exclusively, or another
+///   driver could not be stopped.
+pub fn open_protocol_by_driver_exclusive<P: ProtocolPointer + ?Sized>(
+    handle: Handle,
+    driver: Handle,
+) -> Result<ScopedProtocol<P>> {
+    // Safety: see `open_protocol_by_driver`.
+    unsafe {
+        open_protocol::<P>(
+            OpenProtocolParams {
+                handle,
+                agent: driver,
+                controller: Some(handle),
+            },
+            OpenProtocolAttributes::ByDriverExclusive,
+        )
+    }
+}
+
+/// Opens a protocol interface of a bus controller for one of its child
+/// controllers.
+///
+/// Bus drivers use this to record that the child `child` uses the protocol
+/// of the bus controller `handle`, which `driver` has opened with
+/// [`open_protocol_by_driver`]. The firmware then stops the child before the
+/// protocol can be removed from the bus controller.
+///
+/// # Errors
+///
+/// * [`Status::UNSUPPORTED`]: the handle does not support the protocol.
+/// * [`Status::ALREADY_STARTED`]: the protocol is already open for `child`.
+pub fn open_protocol_by_child_controller<P: ProtocolPointer + ?Sized>(
+    handle: Handle,
+    driver: Handle,
+    child: Handle,
+) -> Result<ScopedProtocol<P>> {
+    // Safety: the firmware refuses to remove a protocol that is open for a
+    // child controller.
+    unsafe {
+        open_protocol::<P>(
+            OpenProtocolParams {
+                handle,
+                agent: driver,
+                controller: Some(child),
+            },
+            OpenProtocolAttributes::ByChildController,
+        )
+    }
+}
+
 /// Tests whether a handle supports a protocol.
 ///
 /// Returns `Ok(true)` if the handle supports the protocol, `Ok(false)` if not.
@@ -1091,38 +1743,391 @@ pub fn load_image(parent_image_handle: Handle, source: LoadImageSource) -> Resul
     }
 }
 
-/// Unloads a UEFI image.
+/// Loads a UEFI image into memory as with [`load_image`], and sets its load
+/// options, such as the command line of a kernel.
 ///
-/// # Errors
+/// The image can then be started with [`start_image`]. This is typically used
+/// with [`LoadImageSource::FromBuffer`] to chainload an image that was
+/// downloaded or decompressed, with a device path that tells the image where
+/// it was loaded from.
 ///
-/// * [`Status::UNSUPPORTED`]: the image has been started, and does not support unload.
-/// * [`Status::INVALID_PARAMETER`]: `image_handle` is not valid.
-pub fn unload_image(image_handle: Handle) -> Result {
-    let bt = boot_services_raw_panicking();
-    let bt = unsafe { bt.as_ref() };
-
-    unsafe { (bt.unload_image)(image_handle.as_ptr()) }.to_result()
-}
-
-/// Transfers control to a loaded image's entry point.
+/// `load_options` is copied into pool memory, which is not freed since the
+/// image can keep using it, even after it returns from [`start_image`].
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot::{self, LoadImageSource};
+/// use uefi::proto::device_path::DevicePath;
+/// use uefi::cstr16;
+///
+/// fn start_kernel(kernel: &[u8], path: &DevicePath) -> uefi::Result {
+///     let source = LoadImageSource::FromBuffer {
+///         buffer: kernel,
+///         file_path: Some(path),
+///     };
+///     let options = cstr16!("console=ttyS0 root=/dev/sda2");
+///     let image = boot::load_image_with_options(boot::image_handle(), source, options)?;
+///     boot::start_image(image)
+/// }
+/// ```
 ///
 /// # Errors
 ///
-/// * [`Status::INVALID_PARAMETER`]: `image_handle` is not valid, or the image
-///   has already been initialized with `start_image`.
-/// * [`Status::SECURITY_VIOLATION`]: a security policy specifies that the image
-///   should not be started.
+/// * [`Status::OUT_OF_RESOURCES`]: insufficient resources to copy `load_options`.
+/// * [`Status::INVALID_PARAMETER`]: `load_options` is larger than 4 GiB.
+///
+/// The image is unloaded if its load options cannot be set. See also
+/// [`load_image`].
+pub fn load_image_with_options(
+    parent_image_handle: Handle,
+    source: LoadImageSource,
+    load_options: &CStr16,
+) -> Result<Handle> {
+    let image = load_image(parent_image_handle, source)?;
+    match set_load_options(image, load_options) {
+        Ok(()) => Ok(image),
+        Err(err) => {
+            let _ = unload_image(image);
+            Err(err)
+        }
+    }
+}
+
+/// Copy `load_options` into pool memory, and set it as the load options of
+/// `image`.
+fn set_load_options(image: Handle, load_options: &CStr16) -> Result {
+    let options = load_options.as_bytes();
+    let size = u32::try_from(options.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+    let mut loaded_image = open_protocol_exclusive::<LoadedImage>(image)?;
+    let copy = allocate_pool(MemoryType::LOADER_DATA, options.len())?;
+    // Safety: the copy has the size of `options`, and is never freed.
+    unsafe {
+        ptr::copy_nonoverlapping(options.as_ptr(), copy.as_ptr(), options.len());
+        loaded_image.set_load_options(copy.as_ptr(), size);
+    }
+    Ok(())
+}
+
+/// Downloads a UEFI image from `url` over HTTP and loads it into memory.
+///
+/// The image is downloaded with an [`HttpClient`] on the network interface
+/// `nic_handle`, using its default IPv4 address. The address must already
+/// be configured, for example with [`Ip4Config2::ifup`]. Like the firmware's
+/// own HTTP boot, the loaded image gets the device path of the interface
+/// followed by a URI node for `url`, so that it can find out where it was
+/// loaded from.
+///
+/// The image is loaded as with [`load_image`], and can be started with
+/// [`start_image`].
+///
+/// # Errors
+///
+/// * [`Status::HTTP_ERROR`]: the server did not respond with `200 OK`.
+/// * [`Status::OUT_OF_RESOURCES`]: the image does not fit into memory.
+///
+/// See also [`HttpClient::get`] and [`load_image`].
+///
+/// [`HttpClient`]: crate::proto::network::http::HttpClient
+/// [`HttpClient::get`]: crate::proto::network::http::HttpClient::get
+/// [`Ip4Config2::ifup`]: crate::proto::network::ip4_config2::Ip4Config2::ifup
+#[cfg(feature = "alloc")]
+pub fn load_image_from_url(
+    parent_image_handle: Handle,
+    nic_handle: Handle,
+    url: &CStr16,
+) -> Result<Handle> {
+    use crate::proto::network::http::{HttpClient, HttpStatusCode};
+
+    let mut image = Vec::new();
+    {
+        let mut client = HttpClient::new(nic_handle)?;
+        let mut response = client.get(url)?;
+        if response.status() != HttpStatusCode::STATUS_200_OK {
+            return Err(Status::HTTP_ERROR.into());
+        }
+        if let Some(len) = response.content_length() {
+            image
+                .try_reserve_exact(len)
+                .map_err(|_| Status::OUT_OF_RESOURCES)?;
+        }
+        response.read_to_end(&mut image)?;
+    }
+
+    let mut path_buf = Vec::new();
+    let file_path = url_device_path(nic_handle, url, &mut path_buf);
+    load_image(
+        parent_image_handle,
+        LoadImageSource::FromBuffer {
+            buffer: &image,
+            file_path,
+        },
+    )
+}
+
+/// Build the device path of `nic_handle` followed by a URI node for `url`.
+/// Returns `None` if the interface has no device path or `url` is not ASCII.
+#[cfg(feature = "alloc")]
+fn url_device_path<'a>(
+    nic_handle: Handle,
+    url: &CStr16,
+    buf: &'a mut Vec<u8>,
+) -> Option<&'a DevicePath> {
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
+
+    let nic_path = unsafe {
+        open_protocol::<DevicePath>(
+            OpenProtocolParams {
+                handle: nic_handle,
+                agent: image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .ok()?;
+    let uri = url
+        .to_u16_slice()
+        .iter()
+        .map(|&c| u8::try_from(c).ok().filter(u8::is_ascii))
+        .collect::<Option<Vec<u8>>>()?;
+
+    let mut builder = DevicePathBuilder::with_vec(buf);
+    for node in nic_path.node_iter() {
+        builder = builder.push(&node).ok()?;
+    }
+    builder
+        .push(&build::messaging::Uri { value: &uri })
+        .ok()?
+        .finalize()
+        .ok()
+}
+
+/// Unloads a UEFI image.
+///
+/// # Errors
+///
+/// * [`Status::UNSUPPORTED`]: the image has been started, and does not support unload.
+/// * [`Status::INVALID_PARAMETER`]: `image_handle` is not valid.
+pub fn unload_image(image_handle: Handle) -> Result {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    unsafe { (bt.unload_image)(image_handle.as_ptr()) }.to_result()
+}
+
+/// Registers `handler` as the unload handler of the current image.
+///
+/// Drivers must register an unload handler to support being unloaded with
+/// [`unload_image`], for example with the shell's `unload` command. The
+/// handler is called with the image handle, and is responsible for freeing
+/// all resources of the driver, such as stopping the driver on its
+/// controllers and uninstalling its protocols. If it returns an error, the
+/// image is not unloaded.
+///
+/// The [`unload`] attribute turns a function into an unload handler.
+///
+/// # Example
+///
+/// ```no_run
+/// #![no_main]
+///
+/// use uefi::prelude::*;
+/// use uefi::unload;
+///
+/// #[unload]
+/// fn unload() -> Status {
+///     // Uninstall the driver's protocols here.
+///     Status::SUCCESS
+/// }
+///
+/// #[entry]
+/// fn main() -> Status {
+///     boot::set_unload_handler(unload).status()
+/// }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::ACCESS_DENIED`]: the [`LoadedImage`] protocol of the image is
+///   already open exclusively.
+///
+/// [`unload`]: crate::unload
+pub fn set_unload_handler(handler: extern "efiapi" fn(Handle) -> Status) -> Result {
+    let mut loaded_image = open_protocol_exclusive::<LoadedImage>(image_handle())?;
+    // Safety: the handler is registered for the current image.
+    unsafe { loaded_image.set_unload(handler) };
+    Ok(())
+}
+
+/// Transfers control to a loaded image's entry point.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: `image_handle` is not valid, or the image
+///   has already been initialized with `start_image`.
+/// * [`Status::SECURITY_VIOLATION`]: a security policy specifies that the image
+///   should not be started.
+///
+/// The exit data of the image is discarded; use [`start_image_with_exit_data`]
+/// to get it.
 pub fn start_image(image_handle: Handle) -> Result {
     let bt = boot_services_raw_panicking();
     let bt = unsafe { bt.as_ref() };
 
-    // TODO: implement returning exit data to the caller.
     let mut exit_data_size: usize = 0;
     let mut exit_data: *mut u16 = ptr::null_mut();
 
-    unsafe {
-        (bt.start_image)(image_handle.as_ptr(), &mut exit_data_size, &mut exit_data).to_result()
+    let status =
+        unsafe { (bt.start_image)(image_handle.as_ptr(), &mut exit_data_size, &mut exit_data) };
+    if let Some(exit_data) = NonNull::new(exit_data) {
+        // The caller is responsible for freeing the exit data.
+        let _ = unsafe { free_pool(exit_data.cast()) };
     }
+    status.to_result()
+}
+
+/// Transfers control to a loaded image's entry point, and returns its exit
+/// status and exit data once it returns.
+///
+/// Unlike [`start_image`], this does not distinguish errors while starting
+/// the image from errors returned by the image. Both are returned by
+/// [`ImageExit::status`].
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn start_image_with_exit_data(image_handle: Handle) -> ImageExit {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    let mut exit_data_size: usize = 0;
+    let mut exit_data: *mut u16 = ptr::null_mut();
+
+    let status =
+        unsafe { (bt.start_image)(image_handle.as_ptr(), &mut exit_data_size, &mut exit_data) };
+    let data = match NonNull::new(exit_data) {
+        Some(exit_data) => unsafe {
+            // Safety: the image allocated `exit_data_size` bytes with
+            // `allocate_pool`, which are owned by the caller.
+            let data =
+                slice::from_raw_parts(exit_data.as_ptr(), exit_data_size / mem::size_of::<u16>())
+                    .to_vec();
+            let _ = free_pool(exit_data.cast());
+            data
+        },
+        None => Vec::new(),
+    };
+    ImageExit { status, data }
+}
+
+/// Loads an image, sets its load options and starts it, and returns its exit
+/// status and exit data once it returns.
+///
+/// This combines [`load_image_with_options`] and
+/// [`start_image_with_exit_data`], with the current image as the parent
+/// image. An image given by [`LaunchSource::Path`] is loaded from the device
+/// that the current image was loaded from.
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot::{self, LaunchSource};
+/// use uefi::{cstr16, println, StatusExt};
+///
+/// fn run_shell() -> uefi::Result {
+///     let path = cstr16!("\\EFI\\tools\\shell.efi");
+///     let exit = boot::launch(LaunchSource::Path(path), Some(cstr16!("-nostartup")))?;
+///     if let Some(description) = exit.description() {
+///         println!("shell exited: {description}");
+///     }
+///     exit.status().to_result()
+/// }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::UNSUPPORTED`]: the source is a path, and the current image was
+///   not loaded from a device.
+/// * [`Status::INVALID_PARAMETER`]: the source is a path that does not fit
+///   into a device path node.
+///
+/// See also [`load_image_with_options`]. Errors from starting the image are
+/// returned by [`ImageExit::status`]. If the image could not be started, it
+/// is unloaded.
+#[cfg(feature = "alloc")]
+pub fn launch(source: LaunchSource, load_options: Option<&CStr16>) -> Result<ImageExit> {
+    let mut path_buf = Vec::new();
+    let device_path = match source {
+        LaunchSource::Path(path) => image_file_device_path(path, &mut path_buf)?,
+        LaunchSource::DevicePath(device_path) => device_path,
+    };
+    let source = LoadImageSource::FromDevicePath {
+        device_path,
+        boot_policy: BootPolicy::ExactMatch,
+    };
+    let image = match load_options {
+        Some(load_options) => load_image_with_options(image_handle(), source, load_options)?,
+        None => load_image(image_handle(), source)?,
+    };
+    let loaded_image = loaded_image_ptr(image);
+    let exit = start_image_with_exit_data(image);
+    // If the image was started and exited with an error, the firmware has
+    // unloaded it, and the handle may have been reused by an image that it
+    // loaded. The image could not be started if the handle still has the
+    // same loaded image protocol, and only then it must be unloaded here.
+    if exit.status().is_error() && loaded_image.is_some() && loaded_image_ptr(image) == loaded_image
+    {
+        let _ = unload_image(image);
+    }
+    Ok(exit)
+}
+
+/// Get the address of the [`LoadedImage`] protocol of `image`, which tells
+/// apart the images that a handle is reused for.
+#[cfg(feature = "alloc")]
+fn loaded_image_ptr(image: Handle) -> Option<*const LoadedImage> {
+    // Safety: only the address of the protocol is used.
+    let loaded_image = unsafe {
+        open_protocol::<LoadedImage>(
+            OpenProtocolParams {
+                handle: image,
+                agent: image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .ok()?;
+    Some(ptr::from_ref(&*loaded_image))
+}
+
+/// Build the device path of the file at `path` on the device the current
+/// image was loaded from.
+#[cfg(feature = "alloc")]
+fn image_file_device_path<'a>(path: &CStr16, buf: &'a mut Vec<u8>) -> Result<&'a DevicePath> {
+    use crate::proto::device_path::build::{self, DevicePathBuilder};
+
+    let device = open_protocol_exclusive::<LoadedImage>(image_handle())?
+        .device()
+        .ok_or(Status::UNSUPPORTED)?;
+    let device_path = unsafe {
+        open_protocol::<DevicePath>(
+            OpenProtocolParams {
+                handle: device,
+                agent: image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }?;
+
+    let mut builder = DevicePathBuilder::with_vec(buf);
+    for node in device_path.node_iter() {
+        builder = builder.push(&node).map_err(|_| Status::INVALID_PARAMETER)?;
+    }
+    builder
+        .push(&build::media::FilePath { path_name: path })
+        .and_then(|builder| builder.finalize())
+        .map_err(|_| Status::INVALID_PARAMETER.into())
 }
 
 /// Exits the UEFI application and returns control to the UEFI component
@@ -1154,6 +2159,34 @@ pub unsafe fn exit(
     )
 }
 
+/// Exits the current UEFI image with `exit_status`, and passes `message` to
+/// the component that started it as the exit data.
+///
+/// The message is copied into pool memory, as required by the specification.
+/// It is typically a description of an error, which the starting component
+/// gets with [`ImageExit::description`]. The firmware only passes exit data
+/// on if `exit_status` is not [`Status::SUCCESS`]. If the copy cannot be
+/// allocated, the image exits without exit data.
+///
+/// # Safety
+///
+/// The same requirements as for [`exit`] apply.
+pub unsafe fn exit_with_message(exit_status: Status, message: &CStr16) -> ! {
+    let message = message.as_bytes();
+    match allocate_pool(MemoryType::BOOT_SERVICES_DATA, message.len()) {
+        Ok(exit_data) => {
+            ptr::copy_nonoverlapping(message.as_ptr(), exit_data.as_ptr(), message.len());
+            exit(
+                image_handle(),
+                exit_status,
+                message.len(),
+                exit_data.as_ptr().cast(),
+            )
+        }
+        Err(_) => exit(image_handle(), exit_status, 0, ptr::null_mut()),
+    }
+}
+
 /// Get the current memory map and exit boot services.
 unsafe fn get_memory_map_and_exit_boot_services(buf: &mut [u8]) -> Result<MemoryMapMeta> {
     let bt = boot_services_raw_panicking();
@@ -1190,6 +2223,9 @@ unsafe fn get_memory_map_and_exit_boot_services(buf: &mut [u8]) -> Result<Memory
 /// `global_allocator` feature is enabled, attempting to use the allocator
 /// after exiting boot services will panic.
 ///
+/// See [`exit_boot_services_with_options`] for more control over exiting boot
+/// services, such as a hook to stop devices first.
+///
 /// # Safety
 ///
 /// The caller is responsible for ensuring that no references to
@@ -1221,16 +2257,46 @@ unsafe fn get_memory_map_and_exit_boot_services(buf: &mut [u8]) -> Result<Memory
 /// [`PoolString`]: crate::proto::device_path::text::PoolString
 #[must_use]
 pub unsafe fn exit_boot_services(memory_type: MemoryType) -> MemoryMapOwned {
+    unsafe {
+        exit_boot_services_with_options(ExitBootServicesOptions::new().memory_type(memory_type))
+    }
+}
+
+/// Exit UEFI boot services, with the given [`ExitBootServicesOptions`].
+///
+/// This works like [`exit_boot_services`], except that:
+/// * The hook set with [`ExitBootServicesOptions::before_exit`] is called
+///   first, while boot services and the [`helpers`] are still usable.
+/// * Exiting boot services is attempted as often as set with
+///   [`ExitBootServicesOptions::attempts`].
+///
+/// # Safety
+///
+/// See [`exit_boot_services`].
+///
+/// # Errors
+///
+/// See [`exit_boot_services`]. If exiting boot services fails, the system is
+/// reset.
+///
+/// [`helpers`]: crate::helpers
+#[must_use]
+pub unsafe fn exit_boot_services_with_options(
+    options: ExitBootServicesOptions<'_>,
+) -> MemoryMapOwned {
+    if let Some(before_exit) = options.before_exit {
+        before_exit();
+    }
+
     crate::helpers::exit();
 
-    let mut buf = MemoryMapBackingMemory::new(memory_type).expect("Failed to allocate memory");
+    let mut buf = tracking::allow_while_frozen(|| MemoryMapBackingMemory::new(options.memory_type))
+        .expect("Failed to allocate memory");
 
     // Calling `exit_boot_services` can fail if the memory map key is not
-    // current. Retry a second time if that occurs. This matches the
-    // behavior of the Linux kernel:
-    // https://github.com/torvalds/linux/blob/e544a0743/drivers/firmware/efi/libstub/efi-stub-helper.c#L375
+    // current, so retry if that occurs.
     let mut status = Status::ABORTED;
-    for _ in 0..2 {
+    for _ in 0..options.attempts.max(1) {
         match unsafe { get_memory_map_and_exit_boot_services(buf.as_mut_slice()) } {
             Ok(memory_map) => {
                 return MemoryMapOwned::from_initialized_mem(buf, memory_map);
@@ -1332,6 +2398,83 @@ pub fn stall(microseconds: usize) {
     }
 }
 
+/// Stalls execution for `duration`, rounded up to whole microseconds.
+///
+/// See also [`stall`].
+pub fn stall_for(duration: Duration) {
+    let mut microseconds = duration::as_micros_ceil(duration);
+    while microseconds > 0 {
+        let chunk = usize::try_from(microseconds).unwrap_or(usize::MAX);
+        stall(chunk);
+        microseconds -= chunk as u128;
+    }
+}
+
+/// Calls `f` every `interval` until it returns `Some`, giving up after
+/// `timeout`.
+///
+/// `f` is called once right away, and then again after stalling for each
+/// `interval`, which is at least one microsecond. Only the stalls count
+/// towards the timeout, not the time spent in `f`.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::time::Duration;
+/// use uefi::boot;
+///
+/// fn read_status() -> u32 {
+///     // Read a device register.
+///     # 1
+/// }
+///
+/// // Wait up to 100ms for the device to become ready.
+/// let status = boot::poll_until(
+///     Duration::from_micros(10),
+///     Duration::from_millis(100),
+///     || Some(read_status()).filter(|status| status & 1 != 0),
+/// )
+/// .expect("device not ready");
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::TIMEOUT`]: `f` did not return `Some` within `timeout`.
+pub fn poll_until<T>(
+    interval: Duration,
+    timeout: Duration,
+    mut f: impl FnMut() -> Option<T>,
+) -> Result<T> {
+    let interval = interval.max(Duration::from_micros(1));
+    let mut elapsed = Duration::ZERO;
+    loop {
+        if let Some(value) = f() {
+            return Ok(value);
+        }
+        if elapsed >= timeout {
+            return Err(Status::TIMEOUT.into());
+        }
+        stall_for(interval);
+        elapsed = elapsed.saturating_add(interval);
+    }
+}
+
+/// Returns the next value of the platform's monotonic counter.
+///
+/// The counter increases by one on each call, and its upper 32 bits increase
+/// on each reset, so it can be used to order events but not to measure time.
+///
+/// # Errors
+///
+/// * [`Status::DEVICE_ERROR`]: the counter has failed.
+pub fn get_next_monotonic_count() -> Result<u64> {
+    let bt = boot_services_raw_panicking();
+    let bt = unsafe { bt.as_ref() };
+
+    let mut count = 0;
+    unsafe { (bt.get_next_monotonic_count)(&mut count) }.to_result_with_val(|| count)
+}
+
 /// Retrieves a [`SimpleFileSystem`] protocol associated with the device the given
 /// image was loaded from.
 ///
@@ -1390,6 +2533,69 @@ impl Deref for ProtocolsPerHandle {
     }
 }
 
+/// The agents that have a protocol open, as returned by
+/// [`open_protocol_information`].
+#[derive(Debug)]
+pub struct OpenProtocolInformation {
+    entries: *const OpenProtocolInformationEntry,
+    count: usize,
+}
+
+impl Drop for OpenProtocolInformation {
+    fn drop(&mut self) {
+        if let Some(entries) = NonNull::new(self.entries.cast_mut()) {
+            let _ = unsafe { free_pool(entries.cast::<u8>()) };
+        }
+    }
+}
+
+impl Deref for OpenProtocolInformation {
+    type Target = [OpenProtocolInformationEntry];
+
+    fn deref(&self) -> &Self::Target {
+        if self.entries.is_null() {
+            return &[];
+        }
+        // SAFETY: the firmware is assumed to provide a correctly-aligned
+        // pointer and array length.
+        unsafe { slice::from_raw_parts(self.entries, self.count) }
+    }
+}
+
+/// An agent that has a protocol open, as returned by
+/// [`open_protocol_information`].
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct OpenProtocolInformationEntry(uefi_raw::table::boot::OpenProtocolInformationEntry);
+
+impl OpenProtocolInformationEntry {
+    /// The agent that opened the protocol: the image handle of an
+    /// application, or the driver binding handle of a driver.
+    #[must_use]
+    pub fn agent(&self) -> Option<Handle> {
+        unsafe { Handle::from_ptr(self.0.agent_handle) }
+    }
+
+    /// The controller the protocol was opened for, if any.
+    #[must_use]
+    pub fn controller(&self) -> Option<Handle> {
+        unsafe { Handle::from_ptr(self.0.controller_handle) }
+    }
+
+    /// How the protocol is open.
+    #[must_use]
+    pub const fn attributes(&self) -> OpenProtocolFlags {
+        OpenProtocolFlags::from_bits_retain(self.0.attributes)
+    }
+
+    /// The number of times the agent opened the protocol for the controller
+    /// with these attributes.
+    #[must_use]
+    pub const fn open_count(&self) -> u32 {
+        self.0.open_count
+    }
+}
+
 /// A buffer returned by [`locate_handle_buffer`] that contains an array of
 /// [`Handle`]s that support the requested protocol.
 #[derive(Debug, Eq, PartialEq)]
@@ -1412,6 +2618,36 @@ impl Deref for HandleBuffer {
     }
 }
 
+/// An array of up to `N` [`Handle`]s returned by [`locate_handle_array`].
+#[derive(Clone, Copy)]
+pub struct HandleArray<const N: usize> {
+    handles: [MaybeUninit<Handle>; N],
+    len: usize,
+}
+
+impl<const N: usize> Deref for HandleArray<N> {
+    type Target = [Handle];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the first `len` handles have been initialized.
+        unsafe { maybe_uninit_slice_assume_init_ref(&self.handles[..self.len]) }
+    }
+}
+
+impl<const N: usize> Debug for HandleArray<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize> PartialEq for HandleArray<N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<const N: usize> Eq for HandleArray<N> {}
+
 /// An open protocol interface. Automatically closes the protocol
 /// interface on drop.
 ///
@@ -1485,14 +2721,221 @@ impl<P: Protocol + ?Sized> ScopedProtocol<P> {
     pub fn get_mut(&mut self) -> Option<&mut P> {
         self.interface.map(|mut p| unsafe { p.as_mut() })
     }
+
+    /// Get the parameters the protocol was opened with, which are also used
+    /// to close it.
+    #[must_use]
+    pub const fn open_params(&self) -> &OpenProtocolParams {
+        &self.open_params
+    }
+}
+
+/// A protocol interface installed with [`install_protocol`].
+///
+/// Dropping this leaves the interface installed, for good; use
+/// [`uninstall_protocol`] to remove it and get it back.
+pub struct InstalledProtocol<P: Protocol> {
+    handle: Handle,
+    interface: NonNull<P>,
+}
+
+impl<P: Protocol> InstalledProtocol<P> {
+    /// Get the handle the interface is installed on.
+    #[must_use]
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Get a pointer to the installed interface.
+    #[must_use]
+    pub const fn as_ptr(&self) -> *const P {
+        self.interface.as_ptr()
+    }
+}
+
+impl<P: Protocol> Debug for InstalledProtocol<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstalledProtocol")
+            .field("handle", &self.handle)
+            .field("interface", &self.interface)
+            .finish()
+    }
 }
 
-/// RAII guard for task priority level changes.
+/// Watches for handles on which a protocol is (re)installed, such as USB
+/// install media that is connected late.
+///
+/// Only handles on which the protocol is installed after the watcher was
+/// created are reported. Use [`locate_handle_buffer`] with
+/// [`SearchType::ByProtocol`] to find the handles that already exist.
+///
+/// The watcher's event is closed when it is dropped, which stops the
+/// notifications.
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot::ProtocolWatcher;
+/// use uefi::proto::media::fs::SimpleFileSystem;
+/// use uefi::Handle;
+///
+/// fn wait_for_new_file_system() -> uefi::Result<Handle> {
+///     let mut watcher = ProtocolWatcher::new::<SimpleFileSystem>()?;
+///     watcher.wait()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProtocolWatcher {
+    event: Event,
+    key: ProtocolSearchKey,
+}
+
+impl ProtocolWatcher {
+    /// Create a watcher for the protocol `P` that is polled with
+    /// [`next_handle`] or [`wait`].
+    ///
+    /// [`next_handle`]: Self::next_handle
+    /// [`wait`]: Self::wait
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: the event could not be allocated.
+    pub fn new<P: ProtocolPointer + ?Sized>() -> Result<Self> {
+        // Safety: the event has no callback.
+        let event = unsafe { create_event(EventType::empty(), Tpl::APPLICATION, None, None) }?;
+        Self::register(&P::GUID, event)
+    }
+
+    /// Create a watcher for the protocol `P` that calls `notify_fn` at
+    /// `notify_tpl` when the protocol is installed on a handle.
+    ///
+    /// The callback can get the new handles with [`next_handle`], through a
+    /// watcher that is reachable from its context, or with [`locate_handle`]
+    /// and the watcher's [`search_type`].
+    ///
+    /// [`next_handle`]: Self::next_handle
+    /// [`search_type`]: Self::search_type
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`create_event`] apply: the callback must
+    /// handle exit from boot services correctly.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: an invalid `notify_tpl` was provided.
+    /// * [`Status::OUT_OF_RESOURCES`]: the event could not be allocated.
+    pub unsafe fn with_callback<P: ProtocolPointer + ?Sized>(
+        notify_tpl: Tpl,
+        notify_fn: EventNotifyFn,
+        notify_ctx: Option<NonNull<c_void>>,
+    ) -> Result<Self> {
+        let event = create_event(
+            EventType::NOTIFY_SIGNAL,
+            notify_tpl,
+            Some(notify_fn),
+            notify_ctx,
+        )?;
+        Self::register(&P::GUID, event)
+    }
+
+    fn register(protocol: &'static Guid, event: Event) -> Result<Self> {
+        match register_protocol_notify(protocol, &event) {
+            Ok(SearchType::ByRegisterNotify(key)) => Ok(Self { event, key }),
+            Ok(_) => unreachable!("register_protocol_notify returned a different search type"),
+            Err(err) => {
+                let _ = close_event(event);
+                Err(err)
+            }
+        }
+    }
+
+    /// Get the next handle on which the protocol was installed, or `None` if
+    /// there are no new handles.
+    ///
+    /// # Errors
+    ///
+    /// This only fails on invalid firmware behavior.
+    pub fn next_handle(&mut self) -> Result<Option<Handle>> {
+        let mut buffer = [MaybeUninit::uninit()];
+        match locate_handle(self.search_type(), &mut buffer) {
+            Ok(handles) => Ok(handles.first().copied()),
+            Err(err) if err.status() == Status::NOT_FOUND => Ok(None),
+            Err(err) => Err(err.to_err_without_payload()),
+        }
+    }
+
+    /// Wait until the protocol is installed on a new handle, and return it.
+    ///
+    /// This must be called at [`Tpl::APPLICATION`], and only on watchers
+    /// created with [`new`].
+    ///
+    /// [`new`]: Self::new
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the watcher has a callback.
+    /// * [`Status::UNSUPPORTED`]: the current TPL is not `Tpl::APPLICATION`.
+    pub fn wait(&mut self) -> Result<Handle> {
+        loop {
+            if let Some(handle) = self.next_handle()? {
+                return Ok(handle);
+            }
+            // Safety: the event stays open while the watcher exists.
+            let event = unsafe { self.event.unsafe_clone() };
+            wait_for_event(&mut [event]).map_err(|err| err.to_err_without_payload())?;
+        }
+    }
+
+    /// Get the event that is signaled when the protocol is installed on a
+    /// handle.
+    ///
+    /// For watchers created with [`new`], this can be used with
+    /// [`wait_for_event`] or [`check_event`] along with other events.
+    ///
+    /// [`new`]: Self::new
+    #[must_use]
+    pub const fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Get the search type for finding new handles with [`locate_handle`].
+    #[must_use]
+    pub const fn search_type(&self) -> SearchType<'static> {
+        SearchType::ByRegisterNotify(self.key)
+    }
+}
+
+impl Drop for ProtocolWatcher {
+    fn drop(&mut self) {
+        // Safety: the event is not used after this.
+        let event = unsafe { self.event.unsafe_clone() };
+        // Closing the event also stops the notifications.
+        let _ = close_event(event);
+    }
+}
+
+/// RAII guard for task priority level changes, returned by [`raise_tpl`] and
+/// [`raise_tpl_unchecked`].
 ///
 /// Will automatically restore the former task priority level when dropped.
+/// Guards should be dropped in the reverse order of their creation. If an
+/// outer guard is dropped first, it lowers the level to the one it saved,
+/// and dropping the inner guard afterwards leaves the level unchanged, since
+/// restoring it would raise the level, which the UEFI spec does not allow.
 #[derive(Debug)]
 pub struct TplGuard {
     old_tpl: Tpl,
+    old_guard_tpl: Tpl,
+}
+
+impl TplGuard {
+    /// Get the task priority level that is restored when the guard is
+    /// dropped.
+    #[must_use]
+    pub const fn old_tpl(&self) -> Tpl {
+        self.old_tpl
+    }
 }
 
 impl Drop for TplGuard {
@@ -1500,6 +2943,13 @@ impl Drop for TplGuard {
         let bt = boot_services_raw_panicking();
         let bt = unsafe { bt.as_ref() };
 
+        // An outer guard was dropped first, and already lowered the level
+        // below the one this guard would restore.
+        if self.old_tpl > current_tpl() {
+            return;
+        }
+
+        GUARD_TPL.store(self.old_guard_tpl.0, Ordering::Release);
         unsafe {
             (bt.restore_tpl)(self.old_tpl);
         }
@@ -1555,6 +3005,99 @@ pub enum OpenProtocolAttributes {
     Exclusive = 0x20,
 }
 
+impl From<OpenProtocolAttributes> for OpenProtocolFlags {
+    fn from(attributes: OpenProtocolAttributes) -> Self {
+        Self::from_bits_retain(attributes as u32)
+    }
+}
+
+/// Options for [`exit_boot_services_with_options`].
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot::{self, ExitBootServicesOptions};
+/// use uefi::mem::memory_map::MemoryType;
+///
+/// let mut quiesce = || {
+///     // Stop DMA of devices, flush logs, ...
+/// };
+/// let options = ExitBootServicesOptions::new()
+///     .memory_type(MemoryType::custom(0x8000_0001))
+///     .attempts(4)
+///     .before_exit(&mut quiesce);
+/// let memory_map = unsafe { boot::exit_boot_services_with_options(options) };
+/// ```
+pub struct ExitBootServicesOptions<'a> {
+    memory_type: MemoryType,
+    attempts: usize,
+    before_exit: Option<&'a mut dyn FnMut()>,
+}
+
+impl<'a> ExitBootServicesOptions<'a> {
+    /// The default options: the memory map is stored in
+    /// [`MemoryType::LOADER_DATA`] memory, exiting boot services is attempted
+    /// twice, and there is no hook.
+    ///
+    /// Retrying once matches the behavior of the [Linux kernel].
+    ///
+    /// [Linux kernel]: https://github.com/torvalds/linux/blob/e544a0743/drivers/firmware/efi/libstub/efi-stub-helper.c#L375
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            memory_type: MemoryType::LOADER_DATA,
+            attempts: 2,
+            before_exit: None,
+        }
+    }
+
+    /// Set the memory type of the pool allocation that backs the returned
+    /// memory map.
+    #[must_use]
+    pub const fn memory_type(mut self, memory_type: MemoryType) -> Self {
+        self.memory_type = memory_type;
+        self
+    }
+
+    /// Set how often exiting boot services is attempted before the system is
+    /// reset. Exiting fails if the memory map changes between getting it and
+    /// exiting, for example due to a timer event. At least one attempt is
+    /// made.
+    #[must_use]
+    pub const fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Set a hook that is called right before the memory map is retrieved and
+    /// boot services are exited, for example to stop devices or loggers.
+    ///
+    /// Boot services can still be used in the hook. Anything that changes the
+    /// memory map afterwards, such as timer events, may make attempts to exit
+    /// boot services fail.
+    #[must_use]
+    pub fn before_exit(mut self, before_exit: &'a mut dyn FnMut()) -> Self {
+        self.before_exit = Some(before_exit);
+        self
+    }
+}
+
+impl Default for ExitBootServicesOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for ExitBootServicesOptions<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExitBootServicesOptions")
+            .field("memory_type", &self.memory_type)
+            .field("attempts", &self.attempts)
+            .field("before_exit", &self.before_exit.is_some())
+            .finish()
+    }
+}
+
 /// Parameters passed to [`open_protocol`].
 #[derive(Debug)]
 pub struct OpenProtocolParams {
@@ -1613,6 +3156,66 @@ pub enum LoadImageSource<'a> {
     },
 }
 
+/// The image to start with [`launch`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug)]
+pub enum LaunchSource<'a> {
+    /// A path such as `\EFI\tools\shell.efi` on the device that the current
+    /// image was loaded from.
+    Path(&'a CStr16),
+
+    /// The full device path of the image, see
+    /// [`LoadImageSource::FromDevicePath`].
+    DevicePath(&'a DevicePath),
+}
+
+/// The exit status and exit data of an image, see
+/// [`start_image_with_exit_data`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImageExit {
+    status: Status,
+    data: Vec<u16>,
+}
+
+#[cfg(feature = "alloc")]
+impl ImageExit {
+    /// Get the exit status of the image.
+    #[must_use = "the image may have failed to start or returned an error"]
+    pub const fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Get the exit data of the image. This is empty if the image did not
+    /// return exit data.
+    ///
+    /// The exit data starts with a null-terminated string, which can be
+    /// followed by binary data.
+    #[must_use]
+    pub fn data(&self) -> &[u16] {
+        &self.data
+    }
+
+    /// Get the string at the start of the exit data, such as a description
+    /// of an error. Returns `None` if there is no valid string.
+    #[must_use]
+    pub fn description(&self) -> Option<&CStr16> {
+        CStr16::from_u16_until_nul(&self.data).ok()
+    }
+
+    /// Convert the exit status to a [`Result`], with the description as the
+    /// error data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status is an error or a warning. The error
+    /// data is the description from the exit data, if any.
+    pub fn to_result(&self) -> Result<(), Option<CString16>> {
+        self.status
+            .to_result_with_err(|_| self.description().map(CString16::from))
+    }
+}
+
 impl<'a> LoadImageSource<'a> {
     /// Returns the raw FFI parameters for `load_image`.
     #[must_use]
@@ -1688,6 +3291,63 @@ impl<'guid> SearchType<'guid> {
 /// Event notification callback type.
 pub type EventNotifyFn = unsafe extern "efiapi" fn(event: Event, context: Option<NonNull<c_void>>);
 
+/// A group of events that are all signaled when one of them is signaled,
+/// see [`create_event_ex`].
+///
+/// Events in the groups defined by the UEFI specification are signaled by
+/// the firmware.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(transparent)]
+pub struct EventGroup(pub Guid);
+
+impl EventGroup {
+    /// Signaled when [`exit_boot_services`] is called, after
+    /// [`BEFORE_EXIT_BOOT_SERVICES`].
+    ///
+    /// The notification functions must not use boot services or allocate
+    /// memory.
+    ///
+    /// [`BEFORE_EXIT_BOOT_SERVICES`]: Self::BEFORE_EXIT_BOOT_SERVICES
+    pub const EXIT_BOOT_SERVICES: Self = Self(guid!("27abf055-b1b8-4c26-8048-748f37baa2df"));
+
+    /// Signaled when [`exit_boot_services`] is called, before
+    /// [`EXIT_BOOT_SERVICES`]. Boot services can still be used.
+    ///
+    /// This group is only signaled by firmware for UEFI 2.8 and later.
+    ///
+    /// [`EXIT_BOOT_SERVICES`]: Self::EXIT_BOOT_SERVICES
+    pub const BEFORE_EXIT_BOOT_SERVICES: Self = Self(guid!("8be0e274-3970-4b44-80c5-1ab9502f3bfc"));
+
+    /// Signaled when the operating system calls `SetVirtualAddressMap`.
+    ///
+    /// The notification functions must not use boot services or allocate
+    /// memory.
+    pub const VIRTUAL_ADDRESS_CHANGE: Self = Self(guid!("13fa7698-c831-49c7-87ea-8f43fcc25196"));
+
+    /// Signaled when the memory map changes.
+    pub const MEMORY_MAP_CHANGE: Self = Self(guid!("78bee926-692f-48fd-9edb-01422ef0d7ab"));
+
+    /// Signaled by the boot manager before it starts a boot option.
+    pub const READY_TO_BOOT: Self = Self(guid!("7ce88fb3-4bd7-4679-87a8-a8d8dee50d2b"));
+
+    /// Signaled by the boot manager after [`READY_TO_BOOT`], once its
+    /// notification functions have run.
+    ///
+    /// This group is only signaled by firmware for UEFI 2.9 and later.
+    ///
+    /// [`READY_TO_BOOT`]: Self::READY_TO_BOOT
+    pub const AFTER_READY_TO_BOOT: Self = Self(guid!("3a2a00ad-98b9-4cdf-a478-702777f1c10b"));
+
+    /// Signaled when `ResetSystem` is called, before the platform is reset.
+    pub const RESET_SYSTEM: Self = Self(guid!("62da6a56-13fb-485a-a8da-a3dd7912cb6b"));
+}
+
+impl From<Guid> for EventGroup {
+    fn from(guid: Guid) -> Self {
+        Self(guid)
+    }
+}
+
 /// Timer events manipulation.
 #[derive(Debug)]
 pub enum TimerTrigger {
@@ -1708,3 +3368,67 @@ pub enum TimerTrigger {
 #[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
 pub struct ProtocolSearchKey(pub(crate) NonNull<c_void>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+    use alloc::vec;
+
+    #[test]
+    fn test_image_exit() {
+        let mut data = cstr16!("bad config").to_u16_slice_with_nul().to_vec();
+        data.extend([0x1234, 0x5678]);
+        let exit = ImageExit {
+            status: Status::LOAD_ERROR,
+            data,
+        };
+        assert_eq!(exit.description(), Some(cstr16!("bad config")));
+        let err = exit.to_result().unwrap_err();
+        assert_eq!(err.status(), Status::LOAD_ERROR);
+        assert_eq!(err.data().as_deref(), Some(cstr16!("bad config")));
+
+        let exit = ImageExit {
+            status: Status::SUCCESS,
+            data: vec![],
+        };
+        assert_eq!(exit.description(), None);
+        assert!(exit.to_result().is_ok());
+    }
+
+    #[test]
+    fn test_page_alignment() {
+        const MIB: u64 = 0x10_0000;
+        assert_eq!(trim_to_alignment(2 * MIB, 2 * MIB), (0, 2 * MIB));
+        assert_eq!(trim_to_alignment(2 * MIB + 0x1000, 2 * MIB), (511, 4 * MIB));
+        assert_eq!(trim_to_alignment(0x3000, 0x4000), (1, 0x4000));
+
+        let desc = MemoryDescriptor {
+            ty: MemoryType::CONVENTIONAL,
+            phys_start: 0x1000,
+            page_count: 0x200,
+            ..Default::default()
+        };
+        // The region is 0x1000..0x201000.
+        assert_eq!(
+            range_candidate(&desc, &(0x8000..MIB), 1, 0x1000),
+            Some(0x8000)
+        );
+        assert_eq!(range_candidate(&desc, &(0..MIB), 1, 0x10000), Some(0x10000));
+        assert_eq!(
+            range_candidate(&desc, &(0..MIB), 0xf0, 0x10000),
+            Some(0x10000)
+        );
+        assert_eq!(range_candidate(&desc, &(0..MIB), 0xf1, 0x10000), None);
+        assert_eq!(
+            range_candidate(&desc, &(MIB..4 * MIB), 0x101, 0x1000),
+            Some(MIB)
+        );
+        assert_eq!(range_candidate(&desc, &(MIB..4 * MIB), 0x102, 0x1000), None);
+        assert_eq!(range_candidate(&desc, &(4 * MIB..8 * MIB), 1, 0x1000), None);
+        assert_eq!(
+            range_candidate(&desc, &(0..u64::MAX), usize::MAX, 0x1000),
+            None
+        );
+    }
+}
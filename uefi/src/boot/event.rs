@@ -0,0 +1,255 @@
+//! Events with closures as their notification functions.
+
+#[cfg(doc)]
+use super::signal_event;
+use super::{
+    close_event, create_event, create_event_ex, set_timer, EventGroup, EventType, TimerTrigger, Tpl,
+};
+use crate::{duration, Event, Result, Status};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::ptr::NonNull;
+use core::time::Duration;
+
+/// Creates an event that calls `notify` when it is signaled, optionally as
+/// part of `event_group`.
+///
+/// This is a safe variant of [`create_event_ex`] with a closure for the
+/// notification function. The closure runs at `notify_tpl`, so it must not
+/// use services that are not allowed at that level, such as waiting for
+/// events. It must be [`Send`], since it can interrupt the code that created
+/// it, so share data with it through atomics rather than `Rc` or `Cell`. The
+/// returned [`NotifyEvent`] closes the event and frees the
+/// closure when it is dropped.
+///
+/// The closure cannot be used with [`EventGroup::EXIT_BOOT_SERVICES`] and
+/// [`EventGroup::VIRTUAL_ADDRESS_CHANGE`], since boot services and the
+/// allocator are not available while those groups are signaled. Use
+/// [`create_event_ex`] for them.
+///
+/// # Example
+///
+/// ```
+/// use uefi::boot::{self, EventGroup, NotifyEvent, Tpl};
+///
+/// fn on_ready_to_boot() -> uefi::Result<NotifyEvent> {
+///     boot::create_notify_event(Tpl::CALLBACK, Some(EventGroup::READY_TO_BOOT), || {
+///         // Save state before an OS is booted.
+///     })
+/// }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: the group cannot be used with a closure,
+///   or `notify_tpl` is invalid.
+/// * [`Status::OUT_OF_RESOURCES`]: the event could not be allocated.
+/// * [`Status::UNSUPPORTED`]: a group is specified, and the firmware is older
+///   than UEFI 2.0.
+pub fn create_notify_event(
+    notify_tpl: Tpl,
+    event_group: Option<EventGroup>,
+    notify: impl FnMut() + Send + 'static,
+) -> Result<NotifyEvent> {
+    if matches!(
+        event_group,
+        Some(EventGroup::EXIT_BOOT_SERVICES | EventGroup::VIRTUAL_ADDRESS_CHANGE)
+    ) {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    new_notify_event(
+        EventType::NOTIFY_SIGNAL,
+        notify_tpl,
+        event_group,
+        Box::new(notify),
+    )
+}
+
+/// Create an event of `event_type` that calls `notify` when it is signaled.
+///
+/// `event_group` must not be a group that is signaled while exiting boot
+/// services.
+fn new_notify_event(
+    event_type: EventType,
+    notify_tpl: Tpl,
+    event_group: Option<EventGroup>,
+    notify: NotifyFn,
+) -> Result<NotifyEvent> {
+    unsafe extern "efiapi" fn call_notify(_event: Event, context: Option<NonNull<c_void>>) {
+        // Safety: the context is the closure of a `NotifyEvent`, which is
+        // freed only after closing the event.
+        if let Some(context) = context {
+            let notify = unsafe { context.cast::<NotifyFn>().as_mut() };
+            notify();
+        }
+    }
+
+    let notify = NonNull::from(Box::leak(Box::new(notify)));
+    // Safety: the callback only runs the closure, which cannot be called
+    // after exiting boot services since those groups are not allowed.
+    let event = unsafe {
+        match &event_group {
+            Some(group) => create_event_ex(
+                event_type,
+                notify_tpl,
+                Some(call_notify),
+                Some(notify.cast()),
+                Some(NonNull::from(&group.0)),
+            ),
+            None => create_event(
+                event_type,
+                notify_tpl,
+                Some(call_notify),
+                Some(notify.cast()),
+            ),
+        }
+    };
+    match event {
+        Ok(event) => Ok(NotifyEvent { event, notify }),
+        Err(err) => {
+            // Safety: the closure was never registered.
+            drop(unsafe { Box::from_raw(notify.as_ptr()) });
+            Err(err)
+        }
+    }
+}
+
+type NotifyFn = Box<dyn FnMut() + Send>;
+
+/// An event with a closure as its notification function, created with
+/// [`create_notify_event`].
+///
+/// The event is closed when this is dropped.
+pub struct NotifyEvent {
+    event: Event,
+    notify: NonNull<NotifyFn>,
+}
+
+impl NotifyEvent {
+    /// Get the event, for example to signal it with [`signal_event`].
+    #[must_use]
+    pub const fn event(&self) -> &Event {
+        &self.event
+    }
+}
+
+impl Debug for NotifyEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotifyEvent")
+            .field("event", &self.event)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for NotifyEvent {
+    fn drop(&mut self) {
+        // Safety: the event is not used after this.
+        let event = unsafe { self.event.unsafe_clone() };
+        // Closing the event ensures the closure is not called anymore.
+        let _ = close_event(event);
+        // Safety: the closure was leaked in `create_notify_event`.
+        drop(unsafe { Box::from_raw(self.notify.as_ptr()) });
+    }
+}
+
+/// A timer that calls a closure when it expires, such as for animations or
+/// for resetting the watchdog timer.
+///
+/// The timer is created stopped. Start it with [`set_periodic`] or
+/// [`set_relative`]. It is cancelled when dropped.
+///
+/// # Example
+///
+/// ```
+/// use core::time::Duration;
+/// use uefi::boot::{self, Timer, Tpl};
+///
+/// fn pet_watchdog() -> uefi::Result<Timer> {
+///     let timer = Timer::new(Tpl::CALLBACK, || {
+///         let _ = boot::set_watchdog_timer(5 * 60, 0x10000, None);
+///     })?;
+///     timer.set_periodic(Duration::from_secs(60))?;
+///     Ok(timer)
+/// }
+/// ```
+///
+/// [`set_periodic`]: Self::set_periodic
+/// [`set_relative`]: Self::set_relative
+#[derive(Debug)]
+pub struct Timer(NotifyEvent);
+
+impl Timer {
+    /// Create a stopped timer that calls `notify` at `notify_tpl` each time it
+    /// expires.
+    ///
+    /// The closure must not use services that are not allowed at
+    /// `notify_tpl`, such as waiting for events. It must be [`Send`], since
+    /// it can interrupt the code that created the timer.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `notify_tpl` is invalid.
+    /// * [`Status::OUT_OF_RESOURCES`]: the event could not be allocated.
+    pub fn new(notify_tpl: Tpl, notify: impl FnMut() + Send + 'static) -> Result<Self> {
+        new_notify_event(
+            EventType::TIMER | EventType::NOTIFY_SIGNAL,
+            notify_tpl,
+            None,
+            Box::new(notify),
+        )
+        .map(Self)
+    }
+
+    /// Call the closure every `period`, starting one `period` from now.
+    ///
+    /// This replaces any previous setting. A `period` of zero calls the
+    /// closure on every timer tick. The period is rounded up to units of
+    /// 100ns.
+    ///
+    /// # Errors
+    ///
+    /// This only fails on invalid firmware behavior.
+    pub fn set_periodic(&self, period: Duration) -> Result {
+        set_timer(
+            self.event(),
+            TimerTrigger::Periodic(duration::as_timer_units(period)),
+        )
+    }
+
+    /// Call the closure once, `delay` from now.
+    ///
+    /// This replaces any previous setting. The delay is rounded up to units
+    /// of 100ns.
+    ///
+    /// # Errors
+    ///
+    /// This only fails on invalid firmware behavior.
+    pub fn set_relative(&self, delay: Duration) -> Result {
+        set_timer(
+            self.event(),
+            TimerTrigger::Relative(duration::as_timer_units(delay)),
+        )
+    }
+
+    /// Stop the timer. It can be started again.
+    ///
+    /// # Errors
+    ///
+    /// This only fails on invalid firmware behavior.
+    pub fn cancel(&self) -> Result {
+        set_timer(self.event(), TimerTrigger::Cancel)
+    }
+
+    /// Get the timer event, which is signaled each time the timer expires.
+    #[must_use]
+    pub const fn event(&self) -> &Event {
+        self.0.event()
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let _ = self.cancel();
+    }
+}
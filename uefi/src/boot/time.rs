@@ -0,0 +1,326 @@
+//! Measuring time and guarding against hangs with the watchdog timer.
+
+use super::{
+    are_boot_services_active, get_handle_for_protocol, get_next_monotonic_count, image_handle,
+    open_protocol, set_watchdog_timer, OpenProtocolAttributes, OpenProtocolParams, ScopedProtocol,
+};
+#[cfg(feature = "alloc")]
+use super::{Timer, Tpl};
+use crate::proto::misc::Timestamp;
+#[cfg(doc)]
+use crate::Status;
+use crate::{duration, Result};
+use core::mem;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// The boot-services watchdog timer, armed for as long as this value lives.
+///
+/// The firmware arms a 5-minute watchdog before starting an image, which
+/// resets the system if it expires. Operations that take longer, such as big
+/// downloads, must either disable it or keep refreshing it. A `Watchdog`
+/// replaces the watchdog with a timeout of its own, which is refreshed with
+/// [`refresh`], or periodically with [`auto_refresh`]. Dropping the
+/// `Watchdog` disables the watchdog.
+///
+/// Only one `Watchdog` should exist at a time, since they all control the
+/// same watchdog timer.
+///
+/// # Example
+///
+/// ```
+/// use core::time::Duration;
+/// use uefi::boot::Watchdog;
+///
+/// fn download() -> uefi::Result {
+///     let watchdog = Watchdog::arm(Duration::from_secs(30), 0x10000)?;
+///     for _chunk in 0..10 {
+///         // Download a chunk.
+///         watchdog.refresh()?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// [`refresh`]: Self::refresh
+/// [`auto_refresh`]: Self::auto_refresh
+#[derive(Debug)]
+pub struct Watchdog {
+    timeout_in_seconds: usize,
+    watchdog_code: u64,
+    #[cfg(feature = "alloc")]
+    refresh_timer: Option<Timer>,
+}
+
+impl Watchdog {
+    /// Arm the watchdog to reset the system if it is not refreshed within
+    /// `timeout`.
+    ///
+    /// The timeout is rounded up to whole seconds. The firmware logs
+    /// `watchdog_code` when the watchdog expires; see [`set_watchdog_timer`].
+    ///
+    /// # Errors
+    ///
+    /// See [`set_watchdog_timer`].
+    pub fn arm(timeout: Duration, watchdog_code: u64) -> Result<Self> {
+        let watchdog = Self {
+            timeout_in_seconds: watchdog_seconds(timeout),
+            watchdog_code,
+            #[cfg(feature = "alloc")]
+            refresh_timer: None,
+        };
+        match watchdog.refresh() {
+            Ok(()) => Ok(watchdog),
+            Err(err) => {
+                // Nothing was armed, so there is nothing to disarm.
+                mem::forget(watchdog);
+                Err(err)
+            }
+        }
+    }
+
+    /// Restart the countdown of the watchdog.
+    ///
+    /// # Errors
+    ///
+    /// See [`set_watchdog_timer`].
+    pub fn refresh(&self) -> Result {
+        set_watchdog_timer(self.timeout_in_seconds, self.watchdog_code, None)
+    }
+
+    /// Get the timeout of the watchdog, rounded up to whole seconds.
+    #[must_use]
+    pub const fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_in_seconds as u64)
+    }
+
+    /// Refresh the watchdog every `period` from a timer event, until
+    /// [`stop_auto_refresh`] is called or the `Watchdog` is dropped.
+    ///
+    /// The timer event is notified at [`Tpl::CALLBACK`], so the watchdog
+    /// still expires if the TPL is raised above that level for longer than
+    /// the timeout. This replaces any previous automatic refresh.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::OUT_OF_RESOURCES`]: the timer event could not be allocated.
+    ///
+    /// [`stop_auto_refresh`]: Self::stop_auto_refresh
+    #[cfg(feature = "alloc")]
+    pub fn auto_refresh(&mut self, period: Duration) -> Result {
+        self.stop_auto_refresh();
+        let (timeout_in_seconds, watchdog_code) = (self.timeout_in_seconds, self.watchdog_code);
+        let timer = Timer::new(Tpl::CALLBACK, move || {
+            let _ = set_watchdog_timer(timeout_in_seconds, watchdog_code, None);
+        })?;
+        timer.set_periodic(period)?;
+        self.refresh_timer = Some(timer);
+        Ok(())
+    }
+
+    /// Stop refreshing the watchdog automatically. The watchdog stays armed.
+    #[cfg(feature = "alloc")]
+    pub fn stop_auto_refresh(&mut self) {
+        self.refresh_timer = None;
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        // Stop the refresh timer first, so that it can't arm the watchdog
+        // again.
+        #[cfg(feature = "alloc")]
+        self.stop_auto_refresh();
+        let _ = set_watchdog_timer(0, self.watchdog_code, None);
+    }
+}
+
+/// Convert a watchdog timeout to whole seconds, rounding up. A timeout of
+/// zero seconds would disable the watchdog, so the result is at least one.
+fn watchdog_seconds(timeout: Duration) -> usize {
+    usize::try_from(duration::as_secs_ceil(timeout))
+        .unwrap_or(usize::MAX)
+        .max(1)
+}
+
+/// A point in time, for measuring how long something takes.
+///
+/// The time is read from the [`Timestamp`] protocol if the firmware provides
+/// it. Otherwise the platform's monotonic counter is used, which has no known
+/// frequency; the methods converting to [`Duration`] then return `None`.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot::Instant;
+///
+/// let start = Instant::now();
+/// // Load the kernel.
+/// if let Some(elapsed) = start.elapsed() {
+///     log::info!("Loading the kernel took {elapsed:?}");
+/// }
+/// ```
+///
+/// [`Timestamp`]: crate::proto::misc::Timestamp
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instant {
+    ticks: u64,
+    /// Frequency of `ticks`, in Hz, or zero for the monotonic counter.
+    frequency: u64,
+    /// The value of `ticks` before it rolls over to zero.
+    end_value: u64,
+}
+
+impl Instant {
+    /// Get the current time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if boot services are not active, or if the monotonic counter is
+    /// used and has failed.
+    #[must_use]
+    pub fn now() -> Self {
+        assert!(are_boot_services_active(), "boot services are not active");
+
+        let Some(timestamp) = timestamp_protocol() else {
+            let ticks = get_next_monotonic_count().expect("monotonic counter failed");
+            return Self {
+                ticks,
+                frequency: 0,
+                end_value: u64::MAX,
+            };
+        };
+        Self {
+            ticks: timestamp.get_timestamp(),
+            frequency: TIMESTAMP_FREQUENCY.load(Ordering::Acquire),
+            end_value: TIMESTAMP_END_VALUE.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Get the raw counter value.
+    #[must_use]
+    pub const fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Get the frequency of the counter in Hz, or `None` if the monotonic
+    /// counter is used.
+    #[must_use]
+    pub const fn frequency(&self) -> Option<u64> {
+        if self.frequency == 0 {
+            None
+        } else {
+            Some(self.frequency)
+        }
+    }
+
+    /// Get the time from `earlier` to `self`.
+    ///
+    /// A single rollover of the counter between the two instants is taken
+    /// into account. Returns `None` if the monotonic counter is used, or if
+    /// the instants were measured with different counters.
+    #[must_use]
+    pub fn duration_since(&self, earlier: Self) -> Option<Duration> {
+        if self.frequency == 0
+            || self.frequency != earlier.frequency
+            || self.end_value != earlier.end_value
+        {
+            return None;
+        }
+        let ticks = if self.ticks >= earlier.ticks {
+            u128::from(self.ticks - earlier.ticks)
+        } else {
+            u128::from(self.end_value - earlier.ticks) + u128::from(self.ticks) + 1
+        };
+        let nanos = ticks * 1_000_000_000 / u128::from(self.frequency);
+        let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+        Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+    }
+
+    /// Get the time since `self`.
+    ///
+    /// See [`duration_since`] and [`now`].
+    ///
+    /// [`duration_since`]: Self::duration_since
+    /// [`now`]: Self::now
+    #[must_use]
+    pub fn elapsed(&self) -> Option<Duration> {
+        Self::now().duration_since(*self)
+    }
+}
+
+static TIMESTAMP_FREQUENCY: AtomicU64 = AtomicU64::new(0);
+static TIMESTAMP_END_VALUE: AtomicU64 = AtomicU64::new(0);
+
+/// Open the [`Timestamp`] protocol, or return `None` if the firmware does not
+/// provide it or its frequency is unknown. The properties of the protocol are
+/// cached in [`TIMESTAMP_FREQUENCY`] and [`TIMESTAMP_END_VALUE`].
+///
+/// [`Timestamp`]: crate::proto::misc::Timestamp
+fn timestamp_protocol() -> Option<ScopedProtocol<Timestamp>> {
+    let handle = get_handle_for_protocol::<Timestamp>().ok()?;
+    // Safety: the protocol is only used while the returned `ScopedProtocol`
+    // is alive.
+    let protocol = unsafe {
+        open_protocol::<Timestamp>(
+            OpenProtocolParams {
+                handle,
+                agent: image_handle(),
+                controller: None,
+            },
+            OpenProtocolAttributes::GetProtocol,
+        )
+    }
+    .ok()?;
+
+    if TIMESTAMP_FREQUENCY.load(Ordering::Acquire) == 0 {
+        let properties = protocol.get_properties().ok()?;
+        if properties.frequency == 0 {
+            return None;
+        }
+        TIMESTAMP_END_VALUE.store(properties.end_value, Ordering::Relaxed);
+        TIMESTAMP_FREQUENCY.store(properties.frequency, Ordering::Release);
+    }
+    Some(protocol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_duration_since() {
+        let instant = |ticks| Instant {
+            ticks,
+            frequency: 1_000_000,
+            end_value: 0xff_ffff,
+        };
+        assert_eq!(
+            instant(1_500_000).duration_since(instant(500_000)),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            instant(5).duration_since(instant(0xff_fffe)),
+            Some(Duration::from_micros(7))
+        );
+        assert_eq!(instant(7).duration_since(instant(7)), Some(Duration::ZERO));
+
+        let monotonic = Instant {
+            ticks: 2,
+            frequency: 0,
+            end_value: u64::MAX,
+        };
+        assert_eq!(monotonic.frequency(), None);
+        assert_eq!(monotonic.duration_since(monotonic), None);
+        assert_eq!(instant(2).duration_since(monotonic), None);
+    }
+
+    #[test]
+    fn test_watchdog_seconds() {
+        assert_eq!(watchdog_seconds(Duration::ZERO), 1);
+        assert_eq!(watchdog_seconds(Duration::from_millis(1)), 1);
+        assert_eq!(watchdog_seconds(Duration::from_secs(300)), 300);
+        assert_eq!(watchdog_seconds(Duration::from_millis(1500)), 2);
+        assert_eq!(watchdog_seconds(Duration::MAX), usize::MAX);
+    }
+}
@@ -0,0 +1,66 @@
+//! Conversions of [`Duration`]s to the integer units used by UEFI interfaces.
+//!
+//! All conversions round up, so that a nonzero duration never becomes zero,
+//! which many interfaces treat as "no timeout" or "right away". Conversions
+//! to fixed-size integers saturate on overflow.
+
+use core::time::Duration;
+
+/// Convert `duration` to whole seconds, rounding up.
+pub(crate) const fn as_secs_ceil(duration: Duration) -> u64 {
+    duration
+        .as_secs()
+        .saturating_add((duration.subsec_nanos() != 0) as u64)
+}
+
+/// Convert `duration` to microseconds, rounding up.
+pub(crate) const fn as_micros_ceil(duration: Duration) -> u128 {
+    duration.as_nanos().div_ceil(1000)
+}
+
+/// Convert `duration` to microseconds, rounding up, saturating at
+/// `usize::MAX`.
+pub(crate) fn as_micros_usize(duration: Duration) -> usize {
+    usize::try_from(as_micros_ceil(duration)).unwrap_or(usize::MAX)
+}
+
+/// Convert `duration` to the 100ns units used by [`boot::set_timer`] and
+/// various protocol timeouts, rounding up.
+///
+/// [`boot::set_timer`]: crate::boot::set_timer
+pub(crate) fn as_timer_units(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos().div_ceil(100)).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_secs_ceil() {
+        assert_eq!(as_secs_ceil(Duration::ZERO), 0);
+        assert_eq!(as_secs_ceil(Duration::from_millis(1)), 1);
+        assert_eq!(as_secs_ceil(Duration::from_millis(1500)), 2);
+        assert_eq!(as_secs_ceil(Duration::from_secs(300)), 300);
+        assert_eq!(as_secs_ceil(Duration::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_as_micros_ceil() {
+        assert_eq!(as_micros_ceil(Duration::ZERO), 0);
+        assert_eq!(as_micros_ceil(Duration::from_nanos(1)), 1);
+        assert_eq!(as_micros_ceil(Duration::from_nanos(1001)), 2);
+        assert_eq!(as_micros_ceil(Duration::from_millis(3)), 3000);
+        assert_eq!(as_micros_usize(Duration::from_nanos(1500)), 2);
+        assert_eq!(as_micros_usize(Duration::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn test_as_timer_units() {
+        assert_eq!(as_timer_units(Duration::ZERO), 0);
+        assert_eq!(as_timer_units(Duration::from_nanos(99)), 1);
+        assert_eq!(as_timer_units(Duration::from_micros(1)), 10);
+        assert_eq!(as_timer_units(Duration::from_secs(2)), 20_000_000);
+        assert_eq!(as_timer_units(Duration::MAX), u64::MAX);
+    }
+}
@@ -0,0 +1,603 @@
+//! A minimal single-threaded executor for futures, driven by UEFI events.
+//!
+//! Futures that wait for an [`Event`], such as [`signaled`], [`sleep`],
+//! [`timeout`] and the non-blocking operations of [`DiskIo2`] and
+//! [`BlockIO2`], register their event with the running executor. When no task
+//! can make progress, the executor waits for one of the registered events
+//! with [`boot::wait_for_event`], instead of polling in a busy loop.
+//!
+//! Use [`block_on`] to run a single future, or an [`Executor`] to run several
+//! tasks concurrently. Both must be used at [`Tpl::APPLICATION`]. The futures
+//! in this crate also work with other executors, which poll them repeatedly.
+//!
+//! # Example
+//!
+//! ```
+//! use core::time::Duration;
+//! use uefi::executor::{self, Executor};
+//! use uefi::println;
+//!
+//! fn blink() {
+//!     let mut executor = Executor::new();
+//!     for id in 0..3 {
+//!         executor.spawn(async move {
+//!             for _ in 0..5 {
+//!                 let _ = executor::sleep(Duration::from_millis(100 * (id + 1))).await;
+//!                 println!("task {id}");
+//!             }
+//!         });
+//!     }
+//!     executor.run();
+//! }
+//! ```
+//!
+//! [`BlockIO2`]: crate::proto::media::block::BlockIO2
+//! [`DiskIo2`]: crate::proto::media::disk::DiskIo2
+//! [`Tpl::APPLICATION`]: boot::Tpl::APPLICATION
+
+use crate::boot;
+use crate::proto::completion::Timer;
+use crate::{Event, Result, Status};
+use alloc::boxed::Box;
+use alloc::rc::{Rc, Weak};
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::fmt::{self, Debug, Formatter};
+use core::future::Future;
+use core::pin::{pin, Pin};
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+
+/// How long the executor waits at most before polling again, for tasks that
+/// are woken by something other than a registered event, such as a notify
+/// function.
+const IDLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Events registered by the futures polled by the running executor, along
+/// with the wakers to wake when they are signaled.
+#[derive(Default)]
+struct WaitList {
+    entries: RefCell<Vec<(u64, Event, Waker)>>,
+    next_id: Cell<u64>,
+}
+
+impl WaitList {
+    /// Remove the registrations of `waker`, before its task is polled again.
+    fn remove(&self, waker: &Waker) {
+        self.entries
+            .borrow_mut()
+            .retain(|(_, _, w)| !w.will_wake(waker));
+    }
+
+    /// Wake all tasks with registrations, so that they check their events.
+    fn wake_all(&self) {
+        for (_, _, waker) in self.entries.borrow_mut().drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Wait for one of the registered events, and wake its task. Return
+    /// after [`IDLE_INTERVAL`] at the latest, so that tasks woken in other
+    /// ways are polled without spinning.
+    fn wait(&self) {
+        let Ok(idle) = Timer::new(IDLE_INTERVAL) else {
+            boot::stall_for(IDLE_INTERVAL);
+            self.wake_all();
+            return;
+        };
+        // Safety: a registration is removed when the future that made it is
+        // dropped, which happens before the future's event is closed, so the
+        // registered events are still open. The clones do not outlive this
+        // function.
+        let mut events: Vec<Event> = self
+            .entries
+            .borrow()
+            .iter()
+            .map(|(_, event, _)| unsafe { event.unsafe_clone() })
+            .collect();
+        let registered = events.len();
+        events.push(unsafe { idle.event().unsafe_clone() });
+
+        match boot::wait_for_event(&mut events) {
+            // The idle timer expired.
+            Ok(index) if index == registered => {}
+            Ok(index) => {
+                // Waiting clears the signal, so signal the event again for the
+                // future to see it.
+                let _ = boot::signal_event(&events[index]);
+                let (_, _, waker) = self.entries.borrow_mut().swap_remove(index);
+                waker.wake();
+            }
+            Err(_) => self.wake_all(),
+        }
+    }
+}
+
+/// The wait list of the running executor, or null if none is running.
+static CURRENT: AtomicPtr<Rc<WaitList>> = AtomicPtr::new(ptr::null_mut());
+
+/// Sets the wait list of the running executor, and restores the previous one
+/// when dropped.
+struct CurrentGuard(*mut Rc<WaitList>);
+
+impl CurrentGuard {
+    fn set(waits: &Rc<WaitList>) -> Self {
+        Self(CURRENT.swap(ptr::from_ref(waits).cast_mut(), Ordering::AcqRel))
+    }
+}
+
+impl Drop for CurrentGuard {
+    fn drop(&mut self) {
+        CURRENT.store(self.0, Ordering::Release);
+    }
+}
+
+/// Registration of an event with the running executor, see [`register`].
+///
+/// Dropping the registration unregisters the event. Futures keep it next to
+/// the event they registered, so that it is dropped before the event is
+/// closed.
+#[derive(Debug, Default)]
+pub(crate) struct Registration(Option<(Weak<WaitList>, u64)>);
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Some((waits, id)) = self.0.take() {
+            // The executor may have returned already.
+            if let Some(waits) = waits.upgrade() {
+                waits.entries.borrow_mut().retain(|(i, _, _)| *i != id);
+            }
+        }
+    }
+}
+
+/// Register `event` to wake `waker` when it is signaled, until the returned
+/// [`Registration`] is dropped.
+///
+/// If no executor from this module is running, `waker` is woken right away,
+/// so that other executors keep polling.
+#[must_use = "dropping the registration unregisters the event"]
+pub(crate) fn register(event: &Event, waker: &Waker) -> Registration {
+    let waits = CURRENT.load(Ordering::Acquire);
+    if waits.is_null() {
+        waker.wake_by_ref();
+        return Registration::default();
+    }
+    // Safety: the wait list outlives the `CurrentGuard` that set it.
+    let waits = unsafe { &*waits };
+    let id = waits.next_id.get();
+    waits.next_id.set(id + 1);
+    // Safety: the registration is dropped before the event is closed.
+    let event = unsafe { event.unsafe_clone() };
+    waits.entries.borrow_mut().push((id, event, waker.clone()));
+    Registration(Some((Rc::downgrade(waits), id)))
+}
+
+/// Waker of a task, which marks the task for polling.
+struct TaskWaker(AtomicBool);
+
+impl TaskWaker {
+    /// Create a waker for a task that must be polled.
+    fn new() -> Arc<Self> {
+        Arc::new(Self(AtomicBool::new(true)))
+    }
+
+    /// Check whether the task was woken, and reset the flag.
+    fn take_woken(&self) -> bool {
+        self.0.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+/// Run `future` to completion, and return its output.
+///
+/// While the future is pending, this waits for the events it registered. A
+/// future that is woken in another way, such as from a notify function, is
+/// polled again within 10 ms.
+///
+/// # Example
+///
+/// ```
+/// use uefi::executor;
+/// use uefi::proto::media::disk::DiskIo2;
+///
+/// fn read_header(disk: &DiskIo2, media_id: u32) -> uefi::Result<Vec<u8>> {
+///     executor::block_on(async {
+///         disk.read_disk_async(media_id, 0, vec![0; 512])?.await
+///     })
+/// }
+/// ```
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let task = TaskWaker::new();
+    let waker = Waker::from(task.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let waits = Rc::new(WaitList::default());
+    let _current = CurrentGuard::set(&waits);
+    loop {
+        if task.take_woken() {
+            waits.remove(&waker);
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        } else {
+            waits.wait();
+        }
+    }
+}
+
+/// A spawned task.
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    task: Arc<TaskWaker>,
+    waker: Waker,
+}
+
+/// Runs several tasks concurrently on the current CPU.
+///
+/// Tasks are added with [`spawn`], and run with [`run`] until they have all
+/// completed.
+///
+/// [`run`]: Self::run
+/// [`spawn`]: Self::spawn
+#[derive(Default)]
+pub struct Executor {
+    tasks: Vec<Task>,
+}
+
+impl Executor {
+    /// Create an executor without tasks.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Add a task that runs `future`.
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'static) {
+        let task = TaskWaker::new();
+        self.tasks.push(Task {
+            future: Box::pin(future),
+            waker: Waker::from(task.clone()),
+            task,
+        });
+    }
+
+    /// Get the number of tasks that have not completed.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Check whether all tasks have completed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Run the tasks until they have all completed.
+    pub fn run(&mut self) {
+        let waits = Rc::new(WaitList::default());
+        let _current = CurrentGuard::set(&waits);
+        while !self.tasks.is_empty() {
+            let mut polled = false;
+            self.tasks.retain_mut(|task| {
+                if !task.task.take_woken() {
+                    return true;
+                }
+                polled = true;
+                waits.remove(&task.waker);
+                let mut cx = Context::from_waker(&task.waker);
+                let pending = task.future.as_mut().poll(&mut cx).is_pending();
+                if !pending {
+                    waits.remove(&task.waker);
+                }
+                pending
+            });
+            if !polled {
+                waits.wait();
+            }
+        }
+    }
+}
+
+impl Debug for Executor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Executor")
+            .field("tasks", &self.tasks.len())
+            .finish()
+    }
+}
+
+/// Future that completes when `event` is signaled, see [`signaled`].
+#[derive(Debug)]
+pub struct Signaled<'a> {
+    event: &'a Event,
+    registration: Registration,
+}
+
+impl Future for Signaled<'_> {
+    type Output = Result;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        // Safety: the clone does not outlive `this.event`.
+        match boot::check_event(unsafe { this.event.unsafe_clone() }) {
+            Ok(true) => Poll::Ready(Ok(())),
+            Ok(false) => {
+                this.registration = register(this.event, cx.waker());
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+/// Wait for `event` to be signaled.
+///
+/// The event must not be of type [`NOTIFY_SIGNAL`]. Completing the future
+/// resets the event to the non-signaled state.
+///
+/// # Errors
+///
+/// * [`Status::INVALID_PARAMETER`]: `event` is of type [`NOTIFY_SIGNAL`].
+///
+/// [`NOTIFY_SIGNAL`]: boot::EventType::NOTIFY_SIGNAL
+#[must_use]
+pub const fn signaled(event: &Event) -> Signaled<'_> {
+    Signaled {
+        event,
+        registration: Registration(None),
+    }
+}
+
+/// Future that completes after a delay, see [`sleep`].
+#[derive(Debug)]
+pub struct Sleep {
+    timer: Option<Result<Timer>>,
+    registration: Registration,
+}
+
+impl Future for Sleep {
+    type Output = Result;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let timer = match &this.timer {
+            Some(Ok(timer)) => timer,
+            Some(Err(_)) => return Poll::Ready(this.timer.take().unwrap().map(|_| ())),
+            None => panic!("`Sleep` polled after completion"),
+        };
+        match timer.is_expired() {
+            Ok(true) => {
+                this.registration = Registration::default();
+                this.timer = None;
+                Poll::Ready(Ok(()))
+            }
+            Ok(false) => {
+                this.registration = register(timer.event(), cx.waker());
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+/// Wait until `duration` has elapsed.
+///
+/// The duration starts when this is called, rather than when the future is
+/// first polled.
+///
+/// # Errors
+///
+/// * [`Status::OUT_OF_RESOURCES`]: the timer event could not be allocated.
+#[must_use]
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        timer: Some(Timer::new(duration)),
+        registration: Registration::default(),
+    }
+}
+
+/// Future that completes with an error if the inner future does not complete
+/// in time, see [`timeout`].
+#[derive(Debug)]
+pub struct Timeout<F> {
+    future: F,
+    sleep: Sleep,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: `future` is never moved out of the pinned `Timeout`.
+        let this = unsafe { self.get_unchecked_mut() };
+        // Check the deadline first, so that `future` is not polled, and does
+        // not register its event, once it is going to be dropped.
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(Ok(())) => return Poll::Ready(Err(Status::TIMEOUT.into())),
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => {}
+        }
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        future.poll(cx).map(Ok)
+    }
+}
+
+/// Run `future`, but give up once `duration` has elapsed.
+///
+/// The duration starts when this is called. If `future` completes in time,
+/// its output is returned. Otherwise `future` is not polled again, even if it
+/// could complete, and the operation it was waiting for must be cancelled by
+/// dropping it. For example,
+/// dropping a [`DiskIo2Operation`] leaks its buffer.
+///
+/// # Example
+///
+/// ```
+/// use core::time::Duration;
+/// use uefi::executor;
+/// use uefi::proto::media::disk::DiskIo2;
+///
+/// async fn read_header(disk: &DiskIo2, media_id: u32) -> uefi::Result<Vec<u8>> {
+///     let read = disk.read_disk_async(media_id, 0, vec![0; 512])?;
+///     executor::timeout(Duration::from_secs(5), read).await?
+/// }
+/// ```
+///
+/// # Errors
+///
+/// * [`Status::TIMEOUT`]: `future` did not complete in time.
+/// * [`Status::OUT_OF_RESOURCES`]: the timer event could not be allocated.
+///
+/// [`DiskIo2Operation`]: crate::proto::media::disk::DiskIo2Operation
+#[must_use]
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        sleep: sleep(duration),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::ptr::NonNull;
+
+    /// Future that is pending once, waking itself.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_on() {
+        assert_eq!(
+            block_on(async {
+                YieldNow(false).await;
+                42
+            }),
+            42
+        );
+    }
+
+    /// Future that registers a dummy event and stays pending.
+    struct Registered(Event, Registration);
+
+    impl Future for Registered {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let this = self.get_mut();
+            this.1 = register(&this.0, cx.waker());
+            Poll::Pending
+        }
+    }
+
+    /// Get the number of registrations with the running executor.
+    fn registered() -> usize {
+        let waits = CURRENT.load(Ordering::Acquire);
+        unsafe { &*waits }.entries.borrow().len()
+    }
+
+    #[test]
+    fn test_registration_dropped() {
+        let new_future = || {
+            let event = unsafe { Event::from_ptr(NonNull::dangling().as_ptr()) }.unwrap();
+            Registered(event, Registration::default())
+        };
+        let waker = Waker::from(TaskWaker::new());
+        let mut cx = Context::from_waker(&waker);
+
+        block_on(async {
+            let mut future = new_future();
+            assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+            // Polling again replaces the registration.
+            assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+            assert_eq!(registered(), 1);
+
+            // Dropping a pending future unregisters its event, so the
+            // executor does not wait for it while the task awaits something
+            // else.
+            drop(future);
+            assert_eq!(registered(), 0);
+            YieldNow(false).await;
+        });
+
+        // Registrations that outlive the executor are ignored when dropped.
+        #[allow(clippy::async_yields_async)]
+        let future = block_on(async {
+            let mut future = new_future();
+            let _ = Pin::new(&mut future).poll(&mut cx);
+            future
+        });
+        drop(future);
+    }
+
+    #[test]
+    fn test_timeout_elapsed() {
+        block_on(async {
+            let event = unsafe { Event::from_ptr(NonNull::dangling().as_ptr()) }.unwrap();
+            // A timer that has already expired, without boot services.
+            let timeout = Timeout {
+                future: Registered(event, Registration::default()),
+                sleep: Sleep {
+                    timer: Some(Err(Status::TIMEOUT.into())),
+                    registration: Registration::default(),
+                },
+            };
+            let result = timeout.await;
+            assert_eq!(result.unwrap_err().status(), Status::TIMEOUT);
+
+            // The inner future was not polled, so there is no registration
+            // left for the executor to wait for when the task is pending
+            // again.
+            assert_eq!(registered(), 0);
+            YieldNow(false).await;
+        });
+    }
+
+    #[test]
+    fn test_executor() {
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let mut executor = Executor::new();
+        for id in 0..2 {
+            let order = order.clone();
+            executor.spawn(async move {
+                order.borrow_mut().push(id);
+                YieldNow(false).await;
+                order.borrow_mut().push(id + 10);
+            });
+        }
+        assert_eq!(executor.len(), 2);
+        executor.run();
+        assert!(executor.is_empty());
+        assert_eq!(*order.borrow(), vec![0, 1, 10, 11]);
+    }
+}
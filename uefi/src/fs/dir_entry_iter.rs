@@ -1,7 +1,10 @@
-//! Module for directory iteration. See [`UefiDirectoryIter`].
+//! Module for directory iteration. See [`UefiDirectoryIter`] and
+//! [`DirectoryReader`].
 
 use super::*;
+use crate::data_types::Align;
 use crate::{cstr16, CStr16, Result};
+#[cfg(feature = "alloc")]
 use alloc::boxed::Box;
 
 /// Common skip dirs in UEFI/FAT-style file systems.
@@ -12,9 +15,11 @@ pub const COMMON_SKIP_DIRS: &[&CStr16] = &[cstr16!("."), cstr16!("..")];
 ///
 /// Note that on UEFI/FAT-style file systems, the root dir usually doesn't
 /// return the entries `.` and `..`, whereas sub directories do.
+#[cfg(feature = "alloc")]
 #[derive(Debug)]
 pub struct UefiDirectoryIter(UefiDirectoryHandle);
 
+#[cfg(feature = "alloc")]
 impl UefiDirectoryIter {
     /// Constructor.
     #[must_use]
@@ -23,6 +28,7 @@ impl UefiDirectoryIter {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl Iterator for UefiDirectoryIter {
     type Item = Result<Box<UefiFileInfo>, ()>;
 
@@ -36,3 +42,55 @@ impl Iterator for UefiDirectoryIter {
         }
     }
 }
+
+/// Reads the entries of an UEFI directory into a caller-provided buffer,
+/// without allocating. Returned by [`FileSystem::read_dir_with_buffer`].
+///
+/// Each call to [`next_entry`] overwrites the previous entry in the buffer.
+/// An entry consists of a [`UefiFileInfo`] and the file name; a buffer of
+/// 1 KiB is large enough for any entry of a FAT file system. Like
+/// [`UefiDirectoryIter`], the reader returns the entries `.` and `..` of sub
+/// directories, see [`COMMON_SKIP_DIRS`].
+///
+/// [`next_entry`]: Self::next_entry
+#[derive(Debug)]
+pub struct DirectoryReader<'buf> {
+    dir: UefiDirectoryHandle,
+    buffer: &'buf mut [u8],
+}
+
+impl<'buf> DirectoryReader<'buf> {
+    /// Constructor. The buffer doesn't need to be aligned; aligning it for
+    /// [`UefiFileInfo`] takes up to seven bytes at its start.
+    #[must_use]
+    pub fn new(dir: UefiDirectoryHandle, buffer: &'buf mut [u8]) -> Self {
+        let buffer = UefiFileInfo::align_buf(buffer).unwrap_or(&mut []);
+        Self { dir, buffer }
+    }
+
+    /// Read the next entry of the directory into the buffer. Returns `None`
+    /// if there are no more entries.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: the buffer is too small for the
+    ///   entry. The required size is returned in the error data. The entry
+    ///   is not skipped, so the reader can continue with a larger buffer,
+    ///   see [`into_inner`].
+    ///
+    /// See [`UefiDirectoryHandle::read_entry`] for other errors.
+    ///
+    /// [`Status::BUFFER_TOO_SMALL`]: crate::Status::BUFFER_TOO_SMALL
+    /// [`into_inner`]: Self::into_inner
+    pub fn next_entry(&mut self) -> Result<Option<&UefiFileInfo>, Option<usize>> {
+        self.dir
+            .read_entry(self.buffer)
+            .map(|info| info.map(|info| &*info))
+    }
+
+    /// Get the directory handle back.
+    #[must_use]
+    pub fn into_inner(self) -> UefiDirectoryHandle {
+        self.dir
+    }
+}
@@ -0,0 +1,213 @@
+//! Module for [`File`].
+
+use crate::fs::*;
+use crate::Status;
+use alloc::vec::Vec;
+
+/// Position to move to with [`File::seek`], like `std::io::SeekFrom`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SeekFrom {
+    /// Move to the given number of bytes from the start of the file.
+    Start(u64),
+    /// Move to the end of the file plus the given number of bytes.
+    End(i64),
+    /// Move to the current position plus the given number of bytes.
+    Current(i64),
+}
+
+/// An open regular file of a [`FileSystem`], with an API close to
+/// `std::fs::File`. Use [`FileSystem::open_file`] or
+/// [`FileSystem::create_file`] to open a file.
+///
+/// Unlike [`FileSystem::read`] and [`FileSystem::write`], a `File` reads and
+/// writes the contents of a file in parts, which allows to stream large files
+/// without loading them into memory entirely. With the `embedded-io` feature,
+/// the `embedded_io` `Read`, `Write` and `Seek` traits are implemented for
+/// `File`, so that generic code can consume it.
+#[derive(Debug)]
+pub struct File {
+    file: UefiRegularFile,
+    path: PathBuf,
+    writable: bool,
+    append: bool,
+}
+
+impl File {
+    pub(super) const fn new(
+        file: UefiRegularFile,
+        path: PathBuf,
+        writable: bool,
+        append: bool,
+    ) -> Self {
+        Self {
+            file,
+            path,
+            writable,
+            append,
+        }
+    }
+
+    /// Get the path that the file was opened with.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the file again through its [`path`], with the same options. The
+    /// new `File` has its own position, starting at the start of the file,
+    /// so that different parts of the file can be read at the same time,
+    /// for example to parse a header while streaming the payload.
+    ///
+    /// `fs` must be the file system that the file was opened on, and the
+    /// file must still exist at its path. Unlike `std::fs::File::try_clone`,
+    /// the positions of the two files are independent of each other.
+    ///
+    /// [`path`]: Self::path
+    pub fn reopen(&self, fs: &mut FileSystem) -> FileSystemResult<Self> {
+        OpenOptions::new()
+            .read(true)
+            .write(self.writable)
+            .append(self.append)
+            .open(fs, &self.path)
+    }
+
+    /// Get the underlying UEFI file handle.
+    #[must_use]
+    pub fn into_regular_file(self) -> UefiRegularFile {
+        self.file
+    }
+
+    /// Read bytes at the current position into `buffer`, advance the
+    /// position, and get the number of bytes read. Returns zero at the end
+    /// of the file.
+    pub fn read(&mut self, buffer: &mut [u8]) -> FileSystemResult<usize> {
+        self.file
+            .read(buffer)
+            .map_err(|err| self.io_error(IoErrorContext::ReadFailure, err))
+    }
+
+    /// Read the remaining contents of the file and append them to `buffer`,
+    /// and get the number of bytes read.
+    pub fn read_to_end(&mut self, buffer: &mut Vec<u8>) -> FileSystemResult<usize> {
+        let start = buffer.len();
+        let mut chunk = [0; 4096];
+        loop {
+            let count = self.read(&mut chunk)?;
+            if count == 0 {
+                return Ok(buffer.len() - start);
+            }
+            buffer.extend_from_slice(&chunk[..count]);
+        }
+    }
+
+    /// Write `buffer` at the current position and advance the position. The
+    /// file grows if the end of the file is reached. If the file was opened
+    /// in [append] mode, `buffer` is written at the end of the file.
+    ///
+    /// [append]: OpenOptions::append
+    pub fn write(&mut self, buffer: &[u8]) -> FileSystemResult<()> {
+        self.seek_for_append()?;
+        self.file.write(buffer).map_err(|err| {
+            self.io_error(IoErrorContext::WriteFailure, err.to_err_without_payload())
+        })
+    }
+
+    /// Flush buffered data of the file to the device.
+    pub fn flush(&mut self) -> FileSystemResult<()> {
+        UefiFileTrait::flush(&mut self.file)
+            .map_err(|err| self.io_error(IoErrorContext::FlushFailure, err))
+    }
+
+    /// Move the current position to `pos`, and get the new position in bytes
+    /// from the start of the file. Seeking past the end of the file is
+    /// allowed, the file grows on the next write.
+    pub fn seek(&mut self, pos: SeekFrom) -> FileSystemResult<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(position) => (Ok(position), 0),
+            SeekFrom::Current(offset) => (self.file.get_position(), offset),
+            SeekFrom::End(offset) => (
+                self.file
+                    .set_position(UefiRegularFile::END_OF_FILE)
+                    .and_then(|()| self.file.get_position()),
+                offset,
+            ),
+        };
+        let position = base.and_then(|base| {
+            base.checked_add_signed(offset)
+                .ok_or_else(|| Status::INVALID_PARAMETER.into())
+        });
+        position
+            .and_then(|position| self.file.set_position(position).map(|()| position))
+            .map_err(|err| self.io_error(IoErrorContext::SeekFailure, err))
+    }
+
+    /// Get the current position in bytes from the start of the file.
+    pub fn stream_position(&mut self) -> FileSystemResult<u64> {
+        self.file
+            .get_position()
+            .map_err(|err| self.io_error(IoErrorContext::SeekFailure, err))
+    }
+
+    /// Get the metadata of the file.
+    pub fn metadata(&mut self) -> FileSystemResult<Metadata> {
+        self.file
+            .get_boxed_info()
+            .map(Metadata::new)
+            .map_err(|err| self.io_error(IoErrorContext::Metadata, err))
+    }
+
+    /// Move to the end of the file before writing, in append mode.
+    fn seek_for_append(&mut self) -> FileSystemResult<()> {
+        if !self.append {
+            return Ok(());
+        }
+        self.file
+            .set_position(UefiRegularFile::END_OF_FILE)
+            .map_err(|err| self.io_error(IoErrorContext::SeekFailure, err))
+    }
+
+    fn io_error(&self, context: IoErrorContext, uefi_error: crate::Error) -> Error {
+        Error::Io(IoError {
+            path: self.path.clone(),
+            context,
+            uefi_error,
+        })
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ErrorType for File {
+    type Error = Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> FileSystemResult<usize> {
+        Self::read(self, buf)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Write for File {
+    fn write(&mut self, buf: &[u8]) -> FileSystemResult<usize> {
+        self.seek_for_append()?;
+        embedded_io::Write::write(&mut self.file, buf)
+            .map_err(|err| self.io_error(IoErrorContext::WriteFailure, err))
+    }
+
+    fn flush(&mut self) -> FileSystemResult<()> {
+        Self::flush(self)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Seek for File {
+    fn seek(&mut self, pos: embedded_io::SeekFrom) -> FileSystemResult<u64> {
+        let pos = match pos {
+            embedded_io::SeekFrom::Start(position) => SeekFrom::Start(position),
+            embedded_io::SeekFrom::End(offset) => SeekFrom::End(offset),
+            embedded_io::SeekFrom::Current(offset) => SeekFrom::Current(offset),
+        };
+        Self::seek(self, pos)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::fs::{PathBuf, PathError};
 use alloc::string::FromUtf8Error;
+use core::char::DecodeUtf16Error;
 use core::fmt::{self, Debug, Display, Formatter};
 
 /// All errors that can happen when working with the [`FileSystem`].
@@ -13,6 +14,8 @@ pub enum Error {
     Path(PathError),
     /// Can't parse file content as UTF-8. See [`FromUtf8Error`].
     Utf8Encoding(FromUtf8Error),
+    /// Can't parse file content as UTF-16. See [`DecodeUtf16Error`].
+    Utf16Encoding(DecodeUtf16Error),
 }
 
 impl Display for Error {
@@ -21,6 +24,7 @@ impl Display for Error {
             Self::Io(_) => write!(f, "IO error"),
             Self::Path(_) => write!(f, "path error"),
             Self::Utf8Encoding(_) => write!(f, "UTF-8 encoding error"),
+            Self::Utf16Encoding(_) => write!(f, "UTF-16 encoding error"),
         }
     }
 }
@@ -62,6 +66,10 @@ pub enum IoErrorContext {
     ReadFailure,
     /// Error writing bytes.
     WriteFailure,
+    /// Error moving the position in a file.
+    SeekFailure,
+    /// Error renaming a file or directory.
+    RenameFailure,
     /// The path exists but does not correspond to a directory when a directory
     /// was expected.
     NotADirectory,
@@ -81,6 +89,8 @@ impl Display for IoErrorContext {
             Self::OpenError => "failed to open file",
             Self::ReadFailure => "failed to read file",
             Self::WriteFailure => "failed to write file",
+            Self::SeekFailure => "failed to seek in file",
+            Self::RenameFailure => "failed to rename file",
             Self::NotADirectory => "expected a directory",
             Self::NotAFile => "expected a file",
         };
@@ -101,6 +111,7 @@ impl core::error::Error for Error {
             Error::Io(err) => Some(err),
             Error::Path(err) => Some(err),
             Error::Utf8Encoding(err) => Some(err),
+            Error::Utf16Encoding(err) => Some(err),
         }
     }
 }
@@ -111,3 +122,14 @@ impl core::error::Error for IoError {
         Some(&self.uefi_error)
     }
 }
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Error for Error {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            Self::Io(err) => embedded_io::Error::kind(&err.uefi_error),
+            Self::Path(_) => embedded_io::ErrorKind::InvalidInput,
+            Self::Utf8Encoding(_) | Self::Utf16Encoding(_) => embedded_io::ErrorKind::InvalidData,
+        }
+    }
+}
@@ -1,16 +1,25 @@
 //! Module for [`FileSystem`].
 
 use crate::fs::*;
-use crate::Status;
-use alloc::boxed::Box;
-use alloc::string::String;
-use alloc::vec;
-use alloc::vec::Vec;
+use crate::{CStr16, Error as UefiError, Result as UefiResult, Status};
 use core::fmt;
 use core::fmt::{Debug, Formatter};
 use uefi::boot::ScopedProtocol;
 
+#[cfg(feature = "alloc")]
+use {
+    crate::data_types::Align,
+    crate::proto::device_path::{DevicePath, DevicePathNodeEnum},
+    crate::proto::media::file::FileInfoCreationError,
+    crate::runtime::Time,
+    crate::{boot, CString16, Char16},
+    alloc::string::String,
+    alloc::vec,
+    alloc::vec::Vec,
+};
+
 /// Return type for public [`FileSystem`] operations.
+#[cfg(feature = "alloc")]
 pub type FileSystemResult<T> = Result<T, Error>;
 
 /// High-level file-system abstraction for UEFI volumes with an API that is
@@ -22,6 +31,8 @@ pub type FileSystemResult<T> = Result<T, Error>;
 /// [module documentation]: uefi::fs
 pub struct FileSystem(ScopedProtocol<SimpleFileSystemProtocol>);
 
+/// Operations that do not need the `alloc` feature. Paths are passed to the
+/// file system unchanged, so they must use `\` as separator.
 impl FileSystem {
     /// Constructor.
     #[must_use]
@@ -29,6 +40,157 @@ impl FileSystem {
         proto.into()
     }
 
+    /// Opens the file or directory at `path` with `mode`, and returns the
+    /// low-level UEFI file handle.
+    pub fn open_handle(&mut self, path: &CStr16, mode: UefiFileMode) -> UefiResult<UefiFileHandle> {
+        self.0
+            .open_volume()?
+            .open(path, mode, UefiFileAttribute::empty())
+    }
+
+    /// Reads the entire contents of a file into `buffer`, and returns the
+    /// number of bytes read.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small for the file.
+    ///   The size of the file is returned in the error data.
+    /// * [`Status::INVALID_PARAMETER`]: `path` is a directory.
+    ///
+    /// See [`Self::open_handle`] for other errors.
+    pub fn read_into(
+        &mut self,
+        path: &CStr16,
+        buffer: &mut [u8],
+    ) -> UefiResult<usize, Option<usize>> {
+        let without_size = |err: UefiError| UefiError::new(err.status(), None);
+        let mut file = self
+            .open_handle(path, UefiFileMode::Read)
+            .map_err(without_size)?
+            .into_regular_file()
+            .ok_or(UefiError::new(Status::INVALID_PARAMETER, None))?;
+
+        // Get the size without a buffer for the file info.
+        file.set_position(UefiRegularFile::END_OF_FILE)
+            .and_then(|()| file.get_position())
+            .and_then(|size| file.set_position(0).map(|()| size))
+            .map_err(without_size)
+            .and_then(|size| {
+                let size = usize::try_from(size).unwrap_or(usize::MAX);
+                let buffer = buffer
+                    .get_mut(..size)
+                    .ok_or(UefiError::new(Status::BUFFER_TOO_SMALL, Some(size)))?;
+                file.read(buffer).map_err(without_size)
+            })
+    }
+
+    /// Returns a reader for the entries within a directory, which reads each
+    /// entry into `buffer`. See [`DirectoryReader`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `path` is a regular file.
+    ///
+    /// See [`Self::open_handle`] for other errors.
+    pub fn read_dir_with_buffer<'buf>(
+        &mut self,
+        path: &CStr16,
+        buffer: &'buf mut [u8],
+    ) -> UefiResult<DirectoryReader<'buf>> {
+        let dir = self
+            .open_handle(path, UefiFileMode::Read)?
+            .into_directory()
+            .ok_or(Status::INVALID_PARAMETER)?;
+        Ok(DirectoryReader::new(dir, buffer))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FileSystem {
+    /// Opens the file system of the volume labeled `label`. Like on FAT file
+    /// systems, the label is compared without regard to ASCII case. If
+    /// several volumes have the label, the first one found is opened.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no volume has the label.
+    ///
+    /// See [`boot::find_handles`] for other errors.
+    ///
+    /// [`boot::find_handles`]: crate::boot::find_handles
+    pub fn from_label(label: &CStr16) -> crate::Result<Self> {
+        for handle in boot::find_handles::<SimpleFileSystemProtocol>()? {
+            // Volumes that are already opened exclusively are skipped.
+            let Ok(protocol) = boot::open_protocol_exclusive(handle) else {
+                continue;
+            };
+            let mut fs = Self::new(protocol);
+            if fs
+                .volume_info()
+                .is_ok_and(|info| eq_ignore_ascii_case(&info.label, label))
+            {
+                return Ok(fs);
+            }
+        }
+        Err(Status::NOT_FOUND.into())
+    }
+
+    /// Opens the file system of the volume that `device_path` points to, and
+    /// returns it together with the path of the file within the volume, which
+    /// is made up of the file path nodes at the end of the device path. This
+    /// is for example the form of the paths of boot options.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no volume matches the device path.
+    /// * [`Status::INVALID_PARAMETER`]: the device path contains nodes other
+    ///   than file path nodes after the volume.
+    pub fn from_device_path(device_path: &DevicePath) -> crate::Result<(Self, PathBuf)> {
+        let mut remaining = device_path;
+        let handle = boot::locate_device_path::<SimpleFileSystemProtocol>(&mut remaining)?;
+
+        let mut path = PathBuf::new();
+        for node in remaining.node_iter() {
+            let Ok(DevicePathNodeEnum::MediaFilePath(node)) = node.as_enum() else {
+                return Err(Status::INVALID_PARAMETER.into());
+            };
+            let name =
+                CString16::try_from(&node.path_name()).map_err(|_| Status::INVALID_PARAMETER)?;
+            path.push(&*name);
+        }
+
+        let fs = Self::new(boot::open_protocol_exclusive(handle)?);
+        Ok((fs, path))
+    }
+
+    /// Opens the file `path` for reading on the volume labeled `label`. See
+    /// [`Self::from_label`] and [`Self::open_file`].
+    pub fn open_by_label(label: &CStr16, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        let path = path.as_ref();
+        Self::from_label(label)
+            .map_err(|err| {
+                Error::Io(IoError {
+                    path: path.to_path_buf(),
+                    context: IoErrorContext::CantOpenVolume,
+                    uefi_error: err,
+                })
+            })?
+            .open_file(path)
+    }
+
+    /// Opens the file that `device_path` points to for reading. See
+    /// [`Self::from_device_path`] and [`Self::open_file`].
+    pub fn open_device_path(device_path: &DevicePath) -> FileSystemResult<File> {
+        let (mut fs, path) = Self::from_device_path(device_path).map_err(|err| {
+            Error::Io(IoError {
+                path: PathBuf::new(),
+                context: IoErrorContext::CantOpenVolume,
+                uefi_error: err,
+            })
+        })?;
+        fs.open_file(path)
+    }
+
     /// Returns `Ok(true)` if the path points at an existing file.
     ///
     /// If the file does not exist, `Ok(false)` is returned. If it cannot be
@@ -53,6 +215,24 @@ impl FileSystem {
         &mut self,
         src_path: impl AsRef<Path>,
         dest_path: impl AsRef<Path>,
+    ) -> FileSystemResult<()> {
+        // 1 MiB copy buffer.
+        self.copy_with_progress(src_path, dest_path, 1024 * 1024, |_, _| {})
+    }
+
+    /// Copies the contents of one file to another, like [`Self::copy`], and
+    /// reports the progress.
+    ///
+    /// The file is copied in chunks of `chunk_size` bytes through a single
+    /// buffer, so that large files can be copied without much memory. After
+    /// each chunk, `progress` is called with the number of bytes copied so far
+    /// and the size of the file. A `chunk_size` of zero is treated as one.
+    pub fn copy_with_progress(
+        &mut self,
+        src_path: impl AsRef<Path>,
+        dest_path: impl AsRef<Path>,
+        chunk_size: usize,
+        mut progress: impl FnMut(u64, u64),
     ) -> FileSystemResult<()> {
         let src_path = src_path.as_ref();
         let dest_path = dest_path.as_ref();
@@ -95,8 +275,9 @@ impl FileSystem {
                 uefi_error: Status::INVALID_PARAMETER.into(),
             }))?;
 
-        // 1 MiB copy buffer.
-        let mut chunk = vec![0; 1024 * 1024];
+        // Small files do not need the whole buffer.
+        let chunk_size = usize::try_from(src_size).map_or(chunk_size, |size| chunk_size.min(size));
+        let mut chunk = vec![0; chunk_size.max(1)];
 
         // Read chunks from the source file and write to the destination file.
         let mut remaining_size = src_size;
@@ -130,6 +311,7 @@ impl FileSystem {
             })?;
 
             remaining_size -= u64::try_from(num_bytes_read).unwrap();
+            progress(src_size - remaining_size, src_size);
         }
 
         dest.flush().map_err(|err| {
@@ -143,44 +325,63 @@ impl FileSystem {
         Ok(())
     }
 
-    /// Creates a new, empty directory at the provided path
+    /// Creates a new, empty directory at the provided path. The names of
+    /// new directories must be valid on FAT file systems, see [`PathError`].
     pub fn create_dir(&mut self, path: impl AsRef<Path>) -> FileSystemResult<()> {
         let path = path.as_ref();
+        validate_new_path(path)?;
         self.open(path, UefiFileMode::CreateReadWrite, true)
             .map(|_| ())
     }
 
     /// Recursively create a directory and all of its parent components if they
-    /// are missing.
+    /// are missing. The names of new directories must be valid on FAT file
+    /// systems, see [`PathError`].
+    ///
+    /// Fails with the [`IoErrorContext::NotADirectory`] context if a
+    /// component exists as a regular file.
     pub fn create_dir_all(&mut self, path: impl AsRef<Path>) -> FileSystemResult<()> {
         let path = path.as_ref();
+        validate_new_path(path)?;
+        let io_error = |context, uefi_error| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context,
+                uefi_error,
+            })
+        };
 
-        // Collect all relevant sub paths in a vector.
-        let mut dirs_to_create = vec![path.to_path_buf()];
-        while let Some(parent) = dirs_to_create.last().unwrap().parent() {
-            dirs_to_create.push(parent)
-        }
-        // Now reverse, so that we have something like this:
-        // - a
-        // - a\\b
-        // - a\\b\\c
-        dirs_to_create.reverse();
-
-        for parent in dirs_to_create {
-            if !self.try_exists(&parent)? {
-                self.create_dir(parent)?;
-            }
+        // Open or create each component through its parent directory, instead
+        // of opening each of the sub paths from the root directory.
+        let mut dir = self.open_root()?;
+        for component in path.components() {
+            let entry = match dir.open(&component, UefiFileMode::Read, UefiFileAttribute::empty()) {
+                Err(err) if err.status() == Status::NOT_FOUND => dir.open(
+                    &component,
+                    UefiFileMode::CreateReadWrite,
+                    UefiFileAttribute::DIRECTORY,
+                ),
+                result => result,
+            };
+            dir = entry
+                .map_err(|err| io_error(IoErrorContext::OpenError, err))?
+                .into_directory()
+                .ok_or_else(|| {
+                    io_error(
+                        IoErrorContext::NotADirectory,
+                        Status::INVALID_PARAMETER.into(),
+                    )
+                })?;
         }
-
         Ok(())
     }
 
     /// Given a path, query the file system to get information about a file,
-    /// directory, etc. Returns [`UefiFileInfo`].
-    pub fn metadata(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Box<UefiFileInfo>> {
+    /// directory, etc., such as its size, times, and attributes.
+    pub fn metadata(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Metadata> {
         let path = path.as_ref();
         let mut file = self.open(path, UefiFileMode::Read, false)?;
-        file.get_boxed_info().map_err(|err| {
+        file.get_boxed_info().map(Metadata::new).map_err(|err| {
             Error::Io(IoError {
                 path: path.to_path_buf(),
                 context: IoErrorContext::Metadata,
@@ -229,6 +430,108 @@ impl FileSystem {
         Ok(vec)
     }
 
+    /// Open an existing file for reading, positioned at the start of the
+    /// file. See [`File`] for reading the file in parts.
+    pub fn open_file(&mut self, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        let path = path.as_ref();
+        let file = self
+            .open(path, UefiFileMode::Read, false)?
+            .into_regular_file()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotAFile,
+                // We do not have a real UEFI error here as we have a logical
+                // problem.
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+        Ok(File::new(file, path.to_path_buf(), false, false))
+    }
+
+    /// Open a file for reading and writing. This function will create a file
+    /// if it does not exist, and will entirely replace its contents if it
+    /// does. See [`File`] for writing the file in parts.
+    pub fn create_file(&mut self, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        let path = path.as_ref();
+
+        // since there is no .truncate() in UEFI, we delete the file first it it
+        // exists.
+        if self.try_exists(path)? {
+            self.remove_file(path)?;
+        }
+
+        let file = self
+            .open(path, UefiFileMode::CreateReadWrite, false)?
+            .into_regular_file()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::OpenError,
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+        Ok(File::new(file, path.to_path_buf(), true, false))
+    }
+
+    /// Open a file with the given options. See [`OpenOptions`].
+    pub fn open_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &OpenOptions,
+    ) -> FileSystemResult<File> {
+        let path = path.as_ref();
+        let io_error = |context, uefi_error| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context,
+                uefi_error,
+            })
+        };
+        let mode = options
+            .mode()
+            .map_err(|status| io_error(IoErrorContext::OpenError, status.into()))?;
+
+        if options.create_new && self.try_exists(path)? {
+            return Err(io_error(
+                IoErrorContext::OpenError,
+                Status::ACCESS_DENIED.into(),
+            ));
+        }
+
+        let mut handle = self.open(path, mode, false)?;
+        if !handle
+            .is_regular_file()
+            .map_err(|err| io_error(IoErrorContext::Metadata, err))?
+        {
+            return Err(io_error(
+                IoErrorContext::NotAFile,
+                Status::INVALID_PARAMETER.into(),
+            ));
+        }
+
+        // Truncate by setting the size, which keeps the other metadata of the
+        // file, unlike deleting and recreating it.
+        if options.truncate {
+            let info = handle
+                .get_boxed_info::<UefiFileInfo>()
+                .map_err(|err| io_error(IoErrorContext::Metadata, err))?;
+            if info.file_size() != 0 {
+                InfoUpdate {
+                    file_size: 0,
+                    ..InfoUpdate::new(&info)
+                }
+                .apply(&mut handle)
+                .map_err(|err| io_error(IoErrorContext::WriteFailure, err))?;
+            }
+        }
+
+        let file = handle.into_regular_file().unwrap();
+        let writable = mode != UefiFileMode::Read;
+        Ok(File::new(
+            file,
+            path.to_path_buf(),
+            writable,
+            options.append,
+        ))
+    }
+
     /// Returns an iterator over the entries within a directory.
     pub fn read_dir(&mut self, path: impl AsRef<Path>) -> FileSystemResult<UefiDirectoryIter> {
         let path = path.as_ref();
@@ -245,9 +548,31 @@ impl FileSystem {
         Ok(UefiDirectoryIter::new(dir))
     }
 
+    /// Returns an iterator over all entries within a directory and its sub
+    /// directories, recursively. See [`Walk`] for the order of the entries
+    /// and the options to skip entries.
+    pub fn walk(&mut self, path: impl AsRef<Path>) -> FileSystemResult<Walk> {
+        let path = path.as_ref();
+        let dir = self
+            .open(path, UefiFileMode::Read, false)?
+            .into_directory()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotADirectory,
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+        Ok(Walk::new(dir, path.to_path_buf()))
+    }
+
     /// Read the entire contents of a file into a Rust string.
+    ///
+    /// The contents must be UTF-8, unless they start with a UTF-16 byte order
+    /// mark, in which case they are decoded as UTF-16 with that byte order.
+    /// Byte order marks are not part of the returned string. UTF-8 contents
+    /// are validated in place, without copying them, except that removing a
+    /// UTF-8 byte order mark moves the remaining bytes.
     pub fn read_to_string(&mut self, path: impl AsRef<Path>) -> FileSystemResult<String> {
-        String::from_utf8(self.read(path)?).map_err(Error::Utf8Encoding)
+        decode_text(self.read(path)?)
     }
 
     /// Removes an empty directory.
@@ -281,31 +606,28 @@ impl FileSystem {
 
     /// Removes a directory at this path, after removing all its contents. Use
     /// carefully!
+    ///
+    /// The first error stops the removal, so some of the contents may be
+    /// removed already.
     pub fn remove_dir_all(&mut self, path: impl AsRef<Path>) -> FileSystemResult<()> {
         let path = path.as_ref();
-        for file_info in self
-            .read_dir(path)?
-            .filter_map(|file_info_result| file_info_result.ok())
-        {
-            if COMMON_SKIP_DIRS.contains(&file_info.file_name()) {
-                continue;
-            }
-
-            let mut abs_entry_path = PathBuf::new();
-            abs_entry_path.push(path);
-            abs_entry_path.push(file_info.file_name());
-            if file_info.is_directory() {
-                // delete all inner files
-                // This recursion is fine as there are no links in UEFI/FAT file
-                // systems. No cycles possible.
-                self.remove_dir_all(&abs_entry_path)?;
-            } else {
-                self.remove_file(abs_entry_path)?;
-            }
-        }
+        let mut dir = self
+            .open(path, UefiFileMode::ReadWrite, false)?
+            .into_directory()
+            .ok_or(Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::NotADirectory,
+                uefi_error: Status::INVALID_PARAMETER.into(),
+            }))?;
+        remove_dir_contents(&mut dir, path)?;
         // Now that the dir is empty, we delete it as final step.
-        self.remove_dir(path)?;
-        Ok(())
+        dir.delete().map_err(|err| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context: IoErrorContext::CantDeleteDirectory,
+                uefi_error: err,
+            })
+        })
     }
 
     /// Removes a file from the filesystem.
@@ -336,16 +658,128 @@ impl FileSystem {
     }
 
     /// Rename a file or directory to a new name, replacing the original file if
-    /// it already exists.
+    /// it already exists. The new name can be in another directory of the
+    /// volume.
+    ///
+    /// The file is renamed by updating its [`UefiFileInfo`], without copying
+    /// its contents. This makes it cheap to replace a file by writing a
+    /// temporary file first and renaming it over the original. If the file
+    /// system cannot rename the file, for example because it does not support
+    /// moving files to another directory, a regular file is copied to the new
+    /// name and deleted instead.
     pub fn rename(
         &mut self,
         src_path: impl AsRef<Path>,
         dest_path: impl AsRef<Path>,
     ) -> FileSystemResult<()> {
-        self.copy(&src_path, dest_path)?;
+        let src_path = src_path.as_ref();
+        let dest_path = dest_path.as_ref();
+        validate_path(dest_path)?;
+        if src_path == dest_path {
+            return Ok(());
+        }
+
+        let mut src = self.open(src_path, UefiFileMode::ReadWrite, false)?;
+        let info = src.get_boxed_info::<UefiFileInfo>().map_err(|err| {
+            Error::Io(IoError {
+                path: src_path.to_path_buf(),
+                context: IoErrorContext::Metadata,
+                uefi_error: err,
+            })
+        })?;
+        let rename_error = |err| {
+            Error::Io(IoError {
+                path: src_path.to_path_buf(),
+                context: IoErrorContext::RenameFailure,
+                uefi_error: err,
+            })
+        };
+
+        let err = match set_file_name(&mut src, &info, dest_path) {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        // FAT file systems are case-insensitive, so the destination is the
+        // source itself if only the case differs, and must not be deleted.
+        if !info.is_regular_file()
+            || eq_ignore_ascii_case(src_path.to_cstr16(), dest_path.to_cstr16())
+        {
+            return Err(rename_error(err));
+        }
+
+        // Renaming fails if the destination exists.
+        if self.try_exists(dest_path)? {
+            self.remove_file(dest_path)?;
+            if set_file_name(&mut src, &info, dest_path).is_ok() {
+                return Ok(());
+            }
+        }
+
+        drop(src);
+        self.copy(src_path, dest_path)?;
         self.remove_file(src_path)
     }
 
+    /// Get information about the volume, such as its label, its size and the
+    /// free space.
+    pub fn volume_info(&mut self) -> FileSystemResult<VolumeInfo> {
+        let mut root = self.open_root()?;
+        let info = root.get_boxed_info::<UefiFileSystemInfo>().map_err(|err| {
+            Error::Io(IoError {
+                path: PathBuf::from(SEPARATOR_STR),
+                context: IoErrorContext::Metadata,
+                uefi_error: err,
+            })
+        })?;
+
+        // Some drivers only return the label with the dedicated info type.
+        let mut label = CString16::from(info.volume_label());
+        if label.is_empty() {
+            if let Ok(volume_label) = root.get_boxed_info::<UefiFileSystemVolumeLabel>() {
+                label = CString16::from(volume_label.volume_label());
+            }
+        }
+
+        Ok(VolumeInfo {
+            label,
+            size: info.volume_size(),
+            free_space: info.free_space(),
+            block_size: info.block_size(),
+            read_only: info.read_only(),
+        })
+    }
+
+    /// Set the attributes of a file or directory, such as
+    /// [`UefiFileAttribute::HIDDEN`]. Whether the path is a directory cannot be
+    /// changed, so [`UefiFileAttribute::DIRECTORY`] is ignored.
+    pub fn set_attributes(
+        &mut self,
+        path: impl AsRef<Path>,
+        attributes: UefiFileAttribute,
+    ) -> FileSystemResult<()> {
+        let directory = UefiFileAttribute::DIRECTORY;
+        self.update_info(path.as_ref(), |update| {
+            update.attribute = (attributes & UefiFileAttribute::VALID_ATTR & !directory)
+                | (update.attribute & directory);
+        })
+    }
+
+    /// Set the creation, modification or access time of a file or directory.
+    /// See [`FileTimes`].
+    pub fn set_times(&mut self, path: impl AsRef<Path>, times: FileTimes) -> FileSystemResult<()> {
+        self.update_info(path.as_ref(), |update| {
+            if let Some(time) = times.created {
+                update.create_time = time;
+            }
+            if let Some(time) = times.modified {
+                update.modification_time = time;
+            }
+            if let Some(time) = times.accessed {
+                update.last_access_time = time;
+            }
+        })
+    }
+
     /// Write a slice as the entire contents of a file. This function will
     /// create a file if it does not exist, and will entirely replace its
     /// contents if it does.
@@ -384,6 +818,77 @@ impl FileSystem {
         Ok(())
     }
 
+    /// Write a string as the entire contents of a file, encoded as UTF-8
+    /// without a byte order mark. Like [`Self::write`], the file is created
+    /// if it does not exist, and its contents are replaced if it does.
+    pub fn write_str(&mut self, path: impl AsRef<Path>, content: &str) -> FileSystemResult<()> {
+        self.write(path, content)
+    }
+
+    /// Write a string as the entire contents of a file, encoded as UTF-16
+    /// with a little-endian byte order mark, as expected by some firmware
+    /// tools. Like [`Self::write`], the file is created if it does not
+    /// exist, and its contents are replaced if it does.
+    ///
+    /// The string is encoded in chunks through a small buffer, so no copy of
+    /// the whole encoded string is allocated.
+    pub fn write_str_utf16(
+        &mut self,
+        path: impl AsRef<Path>,
+        content: &str,
+    ) -> FileSystemResult<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(self, path)?;
+
+        let mut buffer = [0; 512];
+        buffer[..2].copy_from_slice(&UTF16_LE_BOM);
+        let mut len = 2;
+        for unit in content.encode_utf16() {
+            if len == buffer.len() {
+                file.write(&buffer)?;
+                len = 0;
+            }
+            buffer[len..len + 2].copy_from_slice(&unit.to_le_bytes());
+            len += 2;
+        }
+        file.write(&buffer[..len])?;
+        file.flush()
+    }
+
+    /// Update the [`UefiFileInfo`] of `path`.
+    fn update_info(
+        &mut self,
+        path: &Path,
+        update: impl FnOnce(&mut InfoUpdate),
+    ) -> FileSystemResult<()> {
+        let io_error = |context, uefi_error| {
+            Error::Io(IoError {
+                path: path.to_path_buf(),
+                context,
+                uefi_error,
+            })
+        };
+        // Read-only files cannot be opened for writing, but their attributes
+        // can be changed through a handle opened for reading.
+        let mut file = match self.open(path, UefiFileMode::ReadWrite, false) {
+            Err(Error::Io(err)) if err.uefi_error.status() == Status::ACCESS_DENIED => {
+                self.open(path, UefiFileMode::Read, false)?
+            }
+            file => file?,
+        };
+        let info = file
+            .get_boxed_info::<UefiFileInfo>()
+            .map_err(|err| io_error(IoErrorContext::Metadata, err))?;
+        let mut info_update = InfoUpdate::new(&info);
+        update(&mut info_update);
+        info_update
+            .apply(&mut file)
+            .map_err(|err| io_error(IoErrorContext::WriteFailure, err))
+    }
+
     /// Opens a fresh handle to the root directory of the volume.
     fn open_root(&mut self) -> FileSystemResult<UefiDirectoryHandle> {
         self.0.open_volume().map_err(|err| {
@@ -431,6 +936,174 @@ impl FileSystem {
     }
 }
 
+/// Remove all entries of the directory `dir`, whose path is `path`,
+/// recursively. The entries are opened through their parent directory.
+#[cfg(feature = "alloc")]
+fn remove_dir_contents(dir: &mut UefiDirectoryHandle, path: &Path) -> FileSystemResult<()> {
+    loop {
+        let info = match dir.read_entry_boxed() {
+            Ok(Some(info)) => info,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                return Err(Error::Io(IoError {
+                    path: path.to_path_buf(),
+                    context: IoErrorContext::ReadFailure,
+                    uefi_error: err,
+                }))
+            }
+        };
+        if COMMON_SKIP_DIRS.contains(&info.file_name()) {
+            continue;
+        }
+
+        let mut entry_path = path.to_path_buf();
+        entry_path.push(info.file_name());
+        let io_error = |context, uefi_error| {
+            Error::Io(IoError {
+                path: entry_path.clone(),
+                context,
+                uefi_error,
+            })
+        };
+        let entry = dir
+            .open(
+                info.file_name(),
+                UefiFileMode::ReadWrite,
+                UefiFileAttribute::empty(),
+            )
+            .and_then(|entry| entry.into_type())
+            .map_err(|err| io_error(IoErrorContext::OpenError, err))?;
+        match entry {
+            UefiFileType::Dir(mut sub_dir) => {
+                // This recursion is fine as there are no links in UEFI/FAT
+                // file systems. No cycles possible.
+                remove_dir_contents(&mut sub_dir, &entry_path)?;
+                sub_dir
+                    .delete()
+                    .map_err(|err| io_error(IoErrorContext::CantDeleteDirectory, err))?;
+            }
+            UefiFileType::Regular(file) => file
+                .delete()
+                .map_err(|err| io_error(IoErrorContext::CantDeleteFile, err))?,
+        }
+    }
+}
+
+/// Rename `file` to `path`, relative to the root directory, by updating its
+/// `info`.
+#[cfg(feature = "alloc")]
+fn set_file_name(file: &mut UefiFileHandle, info: &UefiFileInfo, path: &Path) -> crate::Result {
+    // Names without a leading separator are relative to the directory of the
+    // file.
+    let mut name = CString16::new();
+    if path.to_cstr16().as_slice().first() != Some(&SEPARATOR) {
+        name.push(SEPARATOR);
+    }
+    name.push_str(path.to_cstr16());
+    InfoUpdate {
+        name: &name,
+        ..InfoUpdate::new(info)
+    }
+    .apply(file)
+}
+
+/// The fields of a [`UefiFileInfo`] to set with [`UefiFileTrait::set_info`].
+#[cfg(feature = "alloc")]
+struct InfoUpdate<'a> {
+    file_size: u64,
+    physical_size: u64,
+    create_time: Time,
+    last_access_time: Time,
+    modification_time: Time,
+    attribute: UefiFileAttribute,
+    name: &'a CStr16,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> InfoUpdate<'a> {
+    /// Keep all fields of `info`.
+    fn new(info: &'a UefiFileInfo) -> Self {
+        Self {
+            file_size: info.file_size(),
+            physical_size: info.physical_size(),
+            create_time: *info.create_time(),
+            last_access_time: *info.last_access_time(),
+            modification_time: *info.modification_time(),
+            attribute: info.attribute(),
+            name: info.file_name(),
+        }
+    }
+
+    fn apply(&self, file: &mut UefiFileHandle) -> crate::Result {
+        // Query the size first, the buffer is aligned within the storage.
+        let size = match self.build(&mut []) {
+            Err(FileInfoCreationError::InsufficientStorage(size)) => size,
+            Ok(_) => 0,
+        };
+        let mut storage = vec![0; size + UefiFileInfo::alignment()];
+        let info = self
+            .build(&mut storage)
+            .expect("storage should be large enough");
+        file.set_info(info)
+    }
+
+    fn build<'buf>(
+        &self,
+        storage: &'buf mut [u8],
+    ) -> Result<&'buf mut UefiFileInfo, FileInfoCreationError> {
+        UefiFileInfo::new(
+            storage,
+            self.file_size,
+            self.physical_size,
+            self.create_time,
+            self.last_access_time,
+            self.modification_time,
+            self.attribute,
+            self.name,
+        )
+    }
+}
+
+#[cfg(feature = "alloc")]
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+#[cfg(feature = "alloc")]
+const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+#[cfg(feature = "alloc")]
+const UTF16_BE_BOM: [u8; 2] = [0xfe, 0xff];
+
+/// Decode the contents of a text file, see [`FileSystem::read_to_string`].
+#[cfg(feature = "alloc")]
+fn decode_text(mut content: Vec<u8>) -> FileSystemResult<String> {
+    let from_bytes: fn([u8; 2]) -> u16 = match content.get(..2) {
+        Some(bom) if bom == UTF16_LE_BOM => u16::from_le_bytes,
+        Some(bom) if bom == UTF16_BE_BOM => u16::from_be_bytes,
+        _ => {
+            if content.starts_with(&UTF8_BOM) {
+                content.drain(..UTF8_BOM.len());
+            }
+            return String::from_utf8(content).map_err(Error::Utf8Encoding);
+        }
+    };
+
+    let units = content[2..].chunks_exact(2);
+    // A trailing odd byte is reported like an unpaired surrogate.
+    let incomplete = (!units.remainder().is_empty()).then_some(0xdc00);
+    let units = units
+        .map(|unit| from_bytes([unit[0], unit[1]]))
+        .chain(incomplete);
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(Error::Utf16Encoding)
+}
+
+#[cfg(feature = "alloc")]
+fn eq_ignore_ascii_case(a: &CStr16, b: &CStr16) -> bool {
+    let lowercase = |c: &Char16| char::from(*c).to_ascii_lowercase();
+    let a = a.as_slice().iter().map(lowercase);
+    let b = b.as_slice().iter().map(lowercase);
+    a.eq(b)
+}
+
 impl Debug for FileSystem {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let ptr: *const _ = &self.0;
@@ -443,3 +1116,30 @@ impl From<uefi::boot::ScopedProtocol<SimpleFileSystemProtocol>> for FileSystem {
         Self(proto)
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_text() {
+        let decode = |bytes: &[u8]| decode_text(bytes.to_vec());
+        assert_eq!(decode(b"").unwrap(), "");
+        assert_eq!(decode(b"abc").unwrap(), "abc");
+        assert_eq!(decode(b"\xef\xbb\xbfabc").unwrap(), "abc");
+        assert_eq!(decode(b"\xff\xfea\0\xe4\0").unwrap(), "a\u{e4}");
+        assert_eq!(decode(b"\xfe\xff\0a\0\xe4").unwrap(), "a\u{e4}");
+        assert_eq!(decode(b"\xff\xfe\x3d\xd8\x00\xde").unwrap(), "\u{1f600}");
+
+        assert!(matches!(decode(b"\xff"), Err(Error::Utf8Encoding(_))));
+        // Unpaired surrogate and incomplete code unit.
+        assert!(matches!(
+            decode(b"\xff\xfe\x3d\xd8"),
+            Err(Error::Utf16Encoding(_))
+        ));
+        assert!(matches!(
+            decode(b"\xff\xfea\0b"),
+            Err(Error::Utf16Encoding(_))
+        ));
+    }
+}
@@ -1,5 +1,7 @@
+#[cfg(feature = "alloc")]
 mod error;
 mod fs;
 
+#[cfg(feature = "alloc")]
 pub use error::*;
 pub use fs::*;
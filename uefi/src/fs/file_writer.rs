@@ -0,0 +1,163 @@
+//! Module for [`FileWriter`].
+
+use super::*;
+use crate::Result;
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+/// Buffered [`fmt::Write`] adapter for a [`UefiRegularFile`], for example to
+/// persist boot logs on the ESP with [`write!`] or a logger.
+///
+/// Text is collected in an internal buffer of `N` bytes and only written to
+/// the file when the buffer is full, or on [`flush`]. Writing the text in
+/// larger parts is considerably faster than writing each formatted argument
+/// on its own. The buffer is also written when the writer is dropped, but
+/// errors are ignored then; call [`flush`] to handle them, and to make sure
+/// that the log survives a crash.
+///
+/// The text is written at the current position of the file, as UTF-8.
+/// [`fmt::Write`] can't report the cause of an error, so [`last_error`]
+/// holds the error of the last failed write.
+///
+/// This type does not need the `alloc` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::fmt::Write;
+/// use uefi::fs::FileWriter;
+/// use uefi::proto::media::file::{File, FileAttribute, FileMode, RegularFile};
+/// use uefi::{boot, cstr16};
+/// # fn write_log() -> uefi::Result {
+///
+/// let mut sfs = boot::get_image_file_system(boot::image_handle())?;
+/// let mut file = sfs
+///     .open_volume()?
+///     .open(cstr16!("\\boot.log"), FileMode::CreateReadWrite, FileAttribute::empty())?
+///     .into_regular_file()
+///     .unwrap();
+/// file.set_position(RegularFile::END_OF_FILE)?;
+///
+/// let mut log = FileWriter::<512>::new(file);
+/// writeln!(log, "booted in {} ms", 42).unwrap();
+/// log.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`flush`]: Self::flush
+/// [`last_error`]: Self::last_error
+#[derive(Debug)]
+pub struct FileWriter<const N: usize = 512> {
+    file: UefiRegularFile,
+    buffer: [u8; N],
+    len: usize,
+    last_error: Option<crate::Error>,
+}
+
+impl<const N: usize> FileWriter<N> {
+    /// Create a writer for `file`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size `N` of the buffer is zero.
+    #[must_use]
+    pub fn new(file: UefiRegularFile) -> Self {
+        assert!(N > 0, "the buffer of a FileWriter must not be empty");
+        Self {
+            file,
+            buffer: [0; N],
+            len: 0,
+            last_error: None,
+        }
+    }
+
+    /// Get the file.
+    #[must_use]
+    pub const fn get_ref(&self) -> &UefiRegularFile {
+        &self.file
+    }
+
+    /// Get the number of bytes in the buffer that are not yet written to the
+    /// file.
+    #[must_use]
+    pub const fn buffered(&self) -> usize {
+        self.len
+    }
+
+    /// Get the error of the last write that failed, if any.
+    #[must_use]
+    pub const fn last_error(&self) -> Option<&crate::Error> {
+        self.last_error.as_ref()
+    }
+
+    /// Write the buffered text to the file, and flush the file to the
+    /// device.
+    ///
+    /// # Errors
+    ///
+    /// See [`UefiRegularFile::write`] and [`UefiFileTrait::flush`]. Text that
+    /// could not be written stays in the buffer.
+    pub fn flush(&mut self) -> Result {
+        self.write_buffer()?;
+        UefiFileTrait::flush(&mut self.file).inspect_err(|err| self.last_error = Some(err.clone()))
+    }
+
+    /// Write the buffered text to the file and get the file back.
+    ///
+    /// # Errors
+    ///
+    /// See [`UefiRegularFile::write`]. The writer is dropped on errors.
+    pub fn into_inner(mut self) -> Result<UefiRegularFile> {
+        self.write_buffer()?;
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped again, so the file is not
+        // closed twice. The other fields do not need to be dropped.
+        Ok(unsafe { ptr::read(&this.file) })
+    }
+
+    /// Write the buffered text to the file, without flushing the file.
+    fn write_buffer(&mut self) -> Result {
+        if self.len == 0 {
+            return Ok(());
+        }
+        match self.file.write(&self.buffer[..self.len]) {
+            Ok(()) => {
+                self.len = 0;
+                Ok(())
+            }
+            Err(err) => {
+                // Keep the text that was not written for the next attempt.
+                let written = (*err.data()).min(self.len);
+                self.buffer.copy_within(written..self.len, 0);
+                self.len -= written;
+                let err = err.to_err_without_payload();
+                self.last_error = Some(err.clone());
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<const N: usize> fmt::Write for FileWriter<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            if self.len == N {
+                self.write_buffer().map_err(|_| fmt::Error)?;
+            }
+            let count = bytes.len().min(N - self.len);
+            self.buffer[self.len..self.len + count].copy_from_slice(&bytes[..count]);
+            self.len += count;
+            bytes = &bytes[count..];
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Drop for FileWriter<N> {
+    fn drop(&mut self) {
+        let _ = self.write_buffer();
+    }
+}
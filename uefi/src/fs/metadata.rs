@@ -0,0 +1,183 @@
+//! Module for [`Metadata`].
+
+use crate::fs::*;
+use crate::runtime::Time;
+use crate::CStr16;
+use alloc::boxed::Box;
+
+/// Metadata of a file or directory, returned by [`FileSystem::metadata`]
+/// and [`File::metadata`], with an API close to `std::fs::Metadata`.
+///
+/// The times are those of the file system, usually local time on FAT file
+/// systems. Times that the file system does not record are `None`; for
+/// example, FAT only records the date of the last access.
+#[derive(Debug)]
+pub struct Metadata(Box<UefiFileInfo>);
+
+impl Metadata {
+    pub(super) const fn new(info: Box<UefiFileInfo>) -> Self {
+        Self(info)
+    }
+
+    /// Get the name of the file, without the path.
+    #[must_use]
+    pub fn file_name(&self) -> &CStr16 {
+        self.0.file_name()
+    }
+
+    /// Get the size of the file in bytes.
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.0.file_size()
+    }
+
+    /// Returns `true` if the file is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of bytes that the file occupies on the volume,
+    /// including padding.
+    #[must_use]
+    pub const fn physical_len(&self) -> u64 {
+        self.0.physical_size()
+    }
+
+    /// Returns `true` if this is the metadata of a directory.
+    #[must_use]
+    pub const fn is_dir(&self) -> bool {
+        self.0.is_directory()
+    }
+
+    /// Returns `true` if this is the metadata of a regular file.
+    #[must_use]
+    pub const fn is_file(&self) -> bool {
+        self.0.is_regular_file()
+    }
+
+    /// Get the creation time, if known.
+    #[must_use]
+    pub fn created(&self) -> Option<Time> {
+        valid_time(self.0.create_time())
+    }
+
+    /// Get the time of the last modification, if known.
+    #[must_use]
+    pub fn modified(&self) -> Option<Time> {
+        valid_time(self.0.modification_time())
+    }
+
+    /// Get the time of the last access, if known.
+    #[must_use]
+    pub fn accessed(&self) -> Option<Time> {
+        valid_time(self.0.last_access_time())
+    }
+
+    /// Get the attributes of the file.
+    #[must_use]
+    pub const fn attributes(&self) -> UefiFileAttribute {
+        self.0.attribute()
+    }
+
+    /// Returns `true` if the file cannot be opened for modification.
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.attributes().contains(UefiFileAttribute::READ_ONLY)
+    }
+
+    /// Returns `true` if the file is hidden from normal directory views.
+    #[must_use]
+    pub const fn is_hidden(&self) -> bool {
+        self.attributes().contains(UefiFileAttribute::HIDDEN)
+    }
+
+    /// Returns `true` if the file belongs to the system.
+    #[must_use]
+    pub const fn is_system(&self) -> bool {
+        self.attributes().contains(UefiFileAttribute::SYSTEM)
+    }
+
+    /// Returns `true` if the file is marked for archival by backup software.
+    #[must_use]
+    pub const fn is_archive(&self) -> bool {
+        self.attributes().contains(UefiFileAttribute::ARCHIVE)
+    }
+
+    /// Get the underlying [`UefiFileInfo`].
+    #[must_use]
+    pub fn file_info(&self) -> &UefiFileInfo {
+        &self.0
+    }
+
+    /// Get the underlying [`UefiFileInfo`].
+    #[must_use]
+    pub fn into_file_info(self) -> Box<UefiFileInfo> {
+        self.0
+    }
+}
+
+impl From<Box<UefiFileInfo>> for Metadata {
+    fn from(info: Box<UefiFileInfo>) -> Self {
+        Self::new(info)
+    }
+}
+
+/// Times of a file to set with [`FileSystem::set_times`], similar to
+/// `std::fs::FileTimes`. The times that are not set are kept.
+///
+/// File systems may store times with less precision. FAT, for example,
+/// stores the modification time in units of two seconds and the access time
+/// without the time of day.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FileTimes {
+    pub(super) created: Option<Time>,
+    pub(super) modified: Option<Time>,
+    pub(super) accessed: Option<Time>,
+}
+
+impl FileTimes {
+    /// Create times that keep all times of the file.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the creation time.
+    #[must_use]
+    pub const fn set_created(mut self, time: Time) -> Self {
+        self.created = Some(time);
+        self
+    }
+
+    /// Set the time of the last modification.
+    #[must_use]
+    pub const fn set_modified(mut self, time: Time) -> Self {
+        self.modified = Some(time);
+        self
+    }
+
+    /// Set the time of the last access.
+    #[must_use]
+    pub const fn set_accessed(mut self, time: Time) -> Self {
+        self.accessed = Some(time);
+        self
+    }
+}
+
+/// Take the known times of a file, for example to apply them to a copy.
+impl From<&Metadata> for FileTimes {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            created: metadata.created(),
+            modified: metadata.modified(),
+            accessed: metadata.accessed(),
+        }
+    }
+}
+
+/// File systems set the times that they do not record to zero, which is
+/// not a valid time.
+fn valid_time(time: &Time) -> Option<Time> {
+    time.is_valid().ok().map(|()| *time)
+}
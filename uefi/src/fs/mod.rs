@@ -39,22 +39,58 @@
 //! ```
 //!
 //! # API Hints
-//! There is no `File` abstraction as in the Rust `std` library. Instead, it is
-//! intended to work with the file system via dedicated functions, similar to
-//! the public functions of the `std::fs` module.
+//! It is intended to work with the file system via dedicated functions,
+//! similar to the public functions of the `std::fs` module. To read or write
+//! a file in parts, for example to stream a large file, open it as a
+//! [`File`] with [`FileSystem::open_file`] or [`FileSystem::create_file`].
 //!
 //! There is no automatic synchronization of the file system for concurrent
 //! accesses. This is in the responsibility of the user.
 //!
+//! # Without `alloc`
+//! Most of this module needs the `alloc` feature. Without it, a reduced API
+//! on [`FileSystem`] remains for programs without a global allocator: files
+//! are opened by a plain [`CStr16`] path with [`FileSystem::open_handle`],
+//! read into a caller buffer with [`FileSystem::read_into`], and directories
+//! are iterated with a reusable buffer with
+//! [`FileSystem::read_dir_with_buffer`]. These paths are not validated or
+//! normalized, so they must use `\` as separator. [`FileWriter`], which
+//! writes formatted text to a file, doesn't allocate either.
+//!
 //! [`cstr16!`]: crate::cstr16
+//! [`CStr16`]: crate::CStr16
 
 mod dir_entry_iter;
+#[cfg(feature = "alloc")]
+mod file;
 mod file_system;
+mod file_writer;
+#[cfg(feature = "alloc")]
+mod metadata;
+#[cfg(feature = "alloc")]
+mod open_options;
+#[cfg(feature = "alloc")]
 mod path;
 mod uefi_types;
+#[cfg(feature = "alloc")]
+mod volume_info;
+#[cfg(feature = "alloc")]
+mod walk;
 
 pub use dir_entry_iter::*;
+#[cfg(feature = "alloc")]
+pub use file::*;
 pub use file_system::*;
+pub use file_writer::*;
+#[cfg(feature = "alloc")]
+pub use metadata::*;
+#[cfg(feature = "alloc")]
+pub use open_options::*;
+#[cfg(feature = "alloc")]
 pub use path::*;
+#[cfg(feature = "alloc")]
+pub use volume_info::*;
+#[cfg(feature = "alloc")]
+pub use walk::*;
 
 use uefi_types::*;
@@ -0,0 +1,159 @@
+//! Module for [`OpenOptions`].
+
+use crate::fs::*;
+use crate::Status;
+
+/// Options for opening a [`File`], similar to `std::fs::OpenOptions`.
+///
+/// UEFI only knows three open modes: read, read and write, and create. The
+/// other options are emulated: truncating sets the size of the file to zero
+/// after opening it, and in append mode, the position is moved to the end of
+/// the file before each write. As there is no write-only mode, files opened
+/// for writing can always be read.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::fs::{FileSystem, OpenOptions};
+/// use uefi::{boot, cstr16};
+/// # fn append_log() -> uefi::fs::FileSystemResult<()> {
+///
+/// let mut fs = FileSystem::new(boot::get_image_file_system(boot::image_handle()).unwrap());
+/// let mut log = OpenOptions::new()
+///     .append(true)
+///     .create(true)
+///     .open(&mut fs, cstr16!("\\boot.log"))?;
+/// log.write(b"booted\n")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OpenOptions {
+    pub(super) read: bool,
+    pub(super) write: bool,
+    pub(super) append: bool,
+    pub(super) truncate: bool,
+    pub(super) create: bool,
+    pub(super) create_new: bool,
+}
+
+impl OpenOptions {
+    /// Create options with all options disabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the file for reading.
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.read = read;
+        self
+    }
+
+    /// Open the file for writing.
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.write = write;
+        self
+    }
+
+    /// Open the file for writing at its end. All writes go to the end of the
+    /// file, regardless of the position. Implies [`write`](Self::write).
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.append = append;
+        self
+    }
+
+    /// Truncate the file to a size of zero if it exists. Requires
+    /// [`write`](Self::write), and cannot be combined with
+    /// [`append`](Self::append).
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Create the file if it does not exist. Requires [`write`](Self::write)
+    /// or [`append`](Self::append).
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Create the file, and fail with [`Status::ACCESS_DENIED`] if it exists
+    /// already. Requires [`write`](Self::write) or [`append`](Self::append).
+    /// Overrides [`create`](Self::create).
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Open the file `path` of `fs` with these options. Same as
+    /// [`FileSystem::open_with`].
+    ///
+    /// # Errors
+    ///
+    /// Besides the errors of opening the file, [`Status::INVALID_PARAMETER`]
+    /// is returned in the [`IoErrorContext::OpenError`] context if the
+    /// options are not valid, see the individual options.
+    pub fn open(&self, fs: &mut FileSystem, path: impl AsRef<Path>) -> FileSystemResult<File> {
+        fs.open_with(path, self)
+    }
+
+    /// Get the UEFI open mode for these options.
+    pub(super) const fn mode(&self) -> Result<UefiFileMode, Status> {
+        let write = self.write || self.append;
+        let create = self.create || self.create_new;
+        if !self.read && !write
+            || (self.truncate || create) && !write
+            || self.truncate && self.append
+        {
+            Err(Status::INVALID_PARAMETER)
+        } else if create {
+            Ok(UefiFileMode::CreateReadWrite)
+        } else if write {
+            Ok(UefiFileMode::ReadWrite)
+        } else {
+            Ok(UefiFileMode::Read)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode() {
+        let mode = |options: &mut OpenOptions| options.mode();
+        assert_eq!(mode(OpenOptions::new().read(true)), Ok(UefiFileMode::Read));
+        assert_eq!(
+            mode(OpenOptions::new().write(true).truncate(true)),
+            Ok(UefiFileMode::ReadWrite)
+        );
+        assert_eq!(
+            mode(OpenOptions::new().append(true).create(true)),
+            Ok(UefiFileMode::CreateReadWrite)
+        );
+        assert_eq!(
+            mode(OpenOptions::new().write(true).create_new(true)),
+            Ok(UefiFileMode::CreateReadWrite)
+        );
+
+        // Invalid combinations.
+        assert_eq!(
+            mode(&mut OpenOptions::new()),
+            Err(Status::INVALID_PARAMETER)
+        );
+        assert_eq!(
+            mode(OpenOptions::new().read(true).create(true)),
+            Err(Status::INVALID_PARAMETER)
+        );
+        assert_eq!(
+            mode(OpenOptions::new().read(true).truncate(true)),
+            Err(Status::INVALID_PARAMETER)
+        );
+        assert_eq!(
+            mode(OpenOptions::new().append(true).truncate(true)),
+            Err(Status::INVALID_PARAMETER)
+        );
+    }
+}
@@ -9,21 +9,26 @@
 //!
 //! # Path Structure
 //!
-//! Paths use the [`SEPARATOR`] character as separator. Paths are absolute and
-//! do not contain `.` or `..` components. However, this can be implemented in
-//! the future.
+//! Paths use the [`SEPARATOR`] character as separator. Paths are absolute, so
+//! a leading separator is optional. The file system does not resolve `.` and
+//! `..` components, use [`Path::normalize`] to resolve them first.
 
 mod path;
 mod pathbuf;
 mod validation;
 
+use path::DOT;
 pub use path::{Components, Path};
 pub use pathbuf::PathBuf;
 
 use crate::data_types::chars::NUL_16;
 use crate::{cstr16, CStr16, Char16};
-pub(super) use validation::validate_path;
 pub use validation::PathError;
+pub(super) use validation::{validate_new_path, validate_path};
+
+/// The maximum length of a file name on FAT file systems, in UCS-2
+/// characters.
+pub const MAX_NAME_LEN: usize = 255;
 
 /// The default separator for paths.
 pub const SEPARATOR: Char16 = unsafe { Char16::from_u16_unchecked('\\' as u16) };
@@ -2,10 +2,14 @@
 #![allow(clippy::module_inception)]
 
 use crate::fs::path::{PathBuf, SEPARATOR};
-use crate::{CStr16, CString16};
+use crate::{cstr16, CStr16, CString16, Char16};
+use alloc::vec::Vec;
 use core::fmt::{Display, Formatter};
 use core::ptr;
 
+/// Separator of the extension in a file name.
+pub(super) const DOT: Char16 = unsafe { Char16::from_u16_unchecked('.' as u16) };
+
 /// A path similar to the `Path` of the standard library, but based on
 /// [`CStr16`] strings and [`SEPARATOR`] as separator.
 ///
@@ -83,6 +87,103 @@ impl Path {
     pub const fn is_empty(&self) -> bool {
         self.to_cstr16().is_empty()
     }
+
+    /// Creates a [`PathBuf`] with `path` appended to this path, see
+    /// [`PathBuf::push`].
+    #[must_use]
+    pub fn join<P: AsRef<Self>>(&self, path: P) -> PathBuf {
+        let mut path_buf = self.to_path_buf();
+        path_buf.push(path);
+        path_buf
+    }
+
+    /// Returns the last component of the path. Returns `None` if the path
+    /// is empty, ends with a separator, or ends with `.` or `..`.
+    #[must_use]
+    pub fn file_name(&self) -> Option<&CStr16> {
+        let chars = self.0.as_slice_with_nul();
+        let start = chars
+            .iter()
+            .rposition(|c| *c == SEPARATOR)
+            .map_or(0, |i| i + 1);
+        // The suffix includes the null character of the path.
+        let name = unsafe { CStr16::from_char16_with_nul_unchecked(&chars[start..]) };
+        if name.is_empty() || name == cstr16!(".") || name == cstr16!("..") {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Returns the [`file_name`] without the extension. A leading `.` does
+    /// not start an extension, so the stem of `.config` is `.config`.
+    ///
+    /// [`file_name`]: Self::file_name
+    #[must_use]
+    pub fn file_stem(&self) -> Option<CString16> {
+        let name = self.file_name()?;
+        let stem = match extension_start(name) {
+            Some(start) => &name.as_slice()[..start - 1],
+            None => name.as_slice(),
+        };
+        let mut string = CString16::new();
+        stem.iter().for_each(|c| string.push(*c));
+        Some(string)
+    }
+
+    /// Returns the extension of the [`file_name`], which follows the last
+    /// `.`, if any. A leading `.` does not start an extension, so `.config`
+    /// has no extension.
+    ///
+    /// [`file_name`]: Self::file_name
+    #[must_use]
+    pub fn extension(&self) -> Option<&CStr16> {
+        let name = self.file_name()?;
+        let start = extension_start(name)?;
+        let chars = name.as_slice_with_nul();
+        Some(unsafe { CStr16::from_char16_with_nul_unchecked(&chars[start..]) })
+    }
+
+    /// Returns the path with `.` components and empty components removed,
+    /// and `..` components resolved, without accessing the file system. As
+    /// in the root directory of a FAT file system, `..` in the root refers
+    /// to the root itself. A leading separator is kept.
+    ///
+    /// For example, `\\EFI\\.\\BOOT\\..\\..\\..\\loader.conf` is normalized to
+    /// `\\loader.conf`.
+    #[must_use]
+    pub fn normalize(&self) -> PathBuf {
+        let mut components = Vec::new();
+        for component in self.components() {
+            if component.is_empty() || component == cstr16!(".") {
+                continue;
+            } else if component == cstr16!("..") {
+                components.pop();
+            } else {
+                components.push(component);
+            }
+        }
+
+        let mut path = CString16::new();
+        if self.0.as_slice().first() == Some(&SEPARATOR) {
+            path.push(SEPARATOR);
+        }
+        for (i, component) in components.iter().enumerate() {
+            if i > 0 {
+                path.push(SEPARATOR);
+            }
+            path.push_str(component);
+        }
+        PathBuf::from(path)
+    }
+}
+
+/// Returns the index of the first character of the extension of `name`.
+fn extension_start(name: &CStr16) -> Option<usize> {
+    match name.as_slice().iter().rposition(|c| *c == DOT) {
+        None | Some(0) => None,
+        Some(i) => Some(i + 1),
+    }
 }
 
 impl Display for Path {
@@ -274,6 +375,73 @@ mod tests {
         assert_eq!(Path::new(cstr16!("abc")).parent(), None,);
     }
 
+    #[test]
+    fn test_join() {
+        let path = Path::new(cstr16!("\\EFI"));
+        assert_eq!(
+            path.join(cstr16!("BOOT")).to_cstr16(),
+            cstr16!("\\EFI\\BOOT")
+        );
+        assert_eq!(
+            path.join(cstr16!("BOOT\\BOOTX64.EFI")).to_cstr16(),
+            cstr16!("\\EFI\\BOOT\\BOOTX64.EFI")
+        );
+        assert_eq!(
+            Path::new(cstr16!("\\")).join(cstr16!("EFI")).to_cstr16(),
+            cstr16!("\\EFI")
+        );
+    }
+
+    #[test]
+    fn test_file_name() {
+        let file_name = |path| Path::new(path).file_name();
+        assert_eq!(
+            file_name(cstr16!("\\EFI\\BOOT\\BOOTX64.EFI")),
+            Some(cstr16!("BOOTX64.EFI"))
+        );
+        assert_eq!(
+            file_name(cstr16!("loader.conf")),
+            Some(cstr16!("loader.conf"))
+        );
+        assert_eq!(file_name(cstr16!("")), None);
+        assert_eq!(file_name(cstr16!("\\")), None);
+        assert_eq!(file_name(cstr16!("EFI\\")), None);
+        assert_eq!(file_name(cstr16!("EFI\\..")), None);
+    }
+
+    #[test]
+    fn test_extension() {
+        let path = Path::new(cstr16!("\\EFI\\initrd.img.gz"));
+        assert_eq!(path.file_stem().unwrap(), cstr16!("initrd.img"));
+        assert_eq!(path.extension(), Some(cstr16!("gz")));
+
+        let path = Path::new(cstr16!("\\.config"));
+        assert_eq!(path.file_stem().unwrap(), cstr16!(".config"));
+        assert_eq!(path.extension(), None);
+
+        let path = Path::new(cstr16!("vmlinuz."));
+        assert_eq!(path.file_stem().unwrap(), cstr16!("vmlinuz"));
+        assert_eq!(path.extension(), Some(cstr16!("")));
+
+        assert_eq!(Path::new(cstr16!("\\")).file_stem(), None);
+        assert_eq!(Path::new(cstr16!("\\")).extension(), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalize = |path| Path::new(path).normalize();
+        assert_eq!(
+            normalize(cstr16!("\\EFI\\.\\BOOT\\..\\..\\..\\loader.conf")).to_cstr16(),
+            cstr16!("\\loader.conf")
+        );
+        assert_eq!(
+            normalize(cstr16!("EFI\\\\BOOT\\.\\")).to_cstr16(),
+            cstr16!("EFI\\BOOT")
+        );
+        assert_eq!(normalize(cstr16!("\\EFI\\..")).to_cstr16(), cstr16!("\\"));
+        assert_eq!(normalize(cstr16!("")).to_cstr16(), cstr16!(""));
+    }
+
     #[test]
     fn partial_eq() {
         let path1 = Path::new(cstr16!(r"a\b"));
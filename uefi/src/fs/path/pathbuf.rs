@@ -1,4 +1,4 @@
-use crate::fs::path::Path;
+use crate::fs::path::{Path, DOT};
 use crate::fs::SEPARATOR;
 use crate::{CStr16, CString16, Char16};
 use core::fmt::{Display, Formatter};
@@ -24,7 +24,8 @@ impl PathBuf {
         Self(string)
     }
 
-    /// Extends self with path.
+    /// Extends self with path. A separator is added in between, if needed,
+    /// and a leading separator of `path` is ignored, if self is not empty.
     ///
     /// UNIX separators (`/`) will be replaced by [`SEPARATOR`] on the fly.
     pub fn push<P: AsRef<Path>>(&mut self, path: P) {
@@ -35,19 +36,64 @@ impl PathBuf {
             return;
         }
 
-        let empty = self.0.is_empty();
-        let needs_sep = *self
-            .0
-            .as_slice_with_nul()
-            .last()
-            .expect("Should have at least null character")
-            != SEPARATOR;
-        if !empty && needs_sep {
-            self.0.push(SEPARATOR)
+        let mut path = CString16::from(path.as_ref().to_cstr16());
+        path.replace_char(SEARCH, SEPARATOR);
+        let mut chars = path.as_slice();
+        if !self.0.is_empty() {
+            while let Some((&SEPARATOR, rest)) = chars.split_first() {
+                chars = rest;
+            }
+            if self.0.as_slice().last() != Some(&SEPARATOR) && !chars.is_empty() {
+                self.0.push(SEPARATOR);
+            }
         }
+        chars.iter().for_each(|c| self.0.push(*c));
+    }
+
+    /// Removes the last component of the path. Returns `false` if there is
+    /// nothing to remove, i.e., if the path is empty or the root directory.
+    pub fn pop(&mut self) -> bool {
+        let chars = self.0.as_slice();
+        let len = match chars.iter().rposition(|c| *c == SEPARATOR) {
+            // Keep the separator of the root directory.
+            Some(0) if chars.len() == 1 => return false,
+            Some(0) => 1,
+            Some(i) => i,
+            None if chars.is_empty() => return false,
+            None => 0,
+        };
+        let mut string = CString16::new();
+        chars[..len].iter().for_each(|c| string.push(*c));
+        self.0 = string;
+        true
+    }
+
+    /// Replaces the [`file_name`] with `file_name`, or adds it if the path
+    /// has no file name.
+    ///
+    /// [`file_name`]: Path::file_name
+    pub fn set_file_name(&mut self, file_name: &CStr16) {
+        if self.file_name().is_some() {
+            self.pop();
+        }
+        self.push(file_name);
+    }
 
-        self.0.push_str(path.as_ref().to_cstr16());
-        self.0.replace_char(SEARCH, SEPARATOR);
+    /// Replaces the [`extension`] with `extension`, or adds it if the file
+    /// name has no extension. An empty `extension` removes the extension.
+    /// Returns `false` and does nothing if the path has no file name.
+    ///
+    /// [`extension`]: Path::extension
+    pub fn set_extension(&mut self, extension: &CStr16) -> bool {
+        let Some(mut file_name) = self.file_stem() else {
+            return false;
+        };
+        if !extension.is_empty() {
+            file_name.push(DOT);
+            file_name.push_str(extension);
+        }
+        self.set_file_name(&file_name);
+        true
     }
 }
 
@@ -167,6 +213,48 @@ mod tests {
         assert_eq!(pathbuf.to_cstr16(), cstr16!("first\\second"));
     }
 
+    #[test]
+    fn push_separators() {
+        let mut pathbuf = PathBuf::from(cstr16!("\\"));
+        pathbuf.push(cstr16!("EFI"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\EFI"));
+        pathbuf.push(cstr16!("\\BOOT\\"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\EFI\\BOOT\\"));
+        pathbuf.push(cstr16!("/BOOTX64.EFI"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\EFI\\BOOT\\BOOTX64.EFI"));
+    }
+
+    #[test]
+    fn pop() {
+        let mut pathbuf = PathBuf::from(cstr16!("\\EFI\\BOOT"));
+        assert!(pathbuf.pop());
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\EFI"));
+        assert!(pathbuf.pop());
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\"));
+        assert!(!pathbuf.pop());
+
+        let mut pathbuf = PathBuf::from(cstr16!("EFI"));
+        assert!(pathbuf.pop());
+        assert_eq!(pathbuf.to_cstr16(), cstr16!(""));
+        assert!(!pathbuf.pop());
+    }
+
+    #[test]
+    fn set_file_name_and_extension() {
+        let mut pathbuf = PathBuf::from(cstr16!("\\loader\\loader.conf"));
+        pathbuf.set_file_name(cstr16!("entries"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\loader\\entries"));
+        assert!(pathbuf.set_extension(cstr16!("tmp")));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\loader\\entries.tmp"));
+        assert!(pathbuf.set_extension(cstr16!("")));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\loader\\entries"));
+
+        let mut pathbuf = PathBuf::from(cstr16!("\\"));
+        assert!(!pathbuf.set_extension(cstr16!("tmp")));
+        pathbuf.set_file_name(cstr16!("loader.conf"));
+        assert_eq!(pathbuf.to_cstr16(), cstr16!("\\loader.conf"));
+    }
+
     #[test]
     fn partial_eq() {
         let mut pathbuf1 = PathBuf::new();
@@ -5,11 +5,13 @@
 //! [`PathBuf`]: super::PathBuf
 //! [`fs`]: crate::fs
 
-use super::Path;
-use crate::fs::CHARACTER_DENY_LIST;
+use super::{Path, DOT};
+use crate::fs::{CHARACTER_DENY_LIST, MAX_NAME_LEN};
 use crate::Char16;
 use core::fmt::{self, Display, Formatter};
 
+const SPACE: Char16 = unsafe { Char16::from_u16_unchecked(b' ' as u16) };
+
 /// Errors related to file paths.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum PathError {
@@ -20,6 +22,11 @@ pub enum PathError {
     EmptyComponent,
     /// There are illegal characters in the path.
     IllegalChar(Char16),
+    /// A component of the path is longer than [`MAX_NAME_LEN`] characters.
+    ComponentTooLong,
+    /// A component of the path ends with a dot or space, which FAT file
+    /// systems remove from new names.
+    TrailingChar(Char16),
 }
 
 impl Display for PathError {
@@ -34,6 +41,14 @@ impl Display for PathError {
                     u16::from(*c)
                 )
             }
+            Self::ComponentTooLong => write!(f, "path contains a component that is too long"),
+            Self::TrailingChar(c) => {
+                write!(
+                    f,
+                    "path contains a component ending with an invalid character (value {})",
+                    u16::from(*c)
+                )
+            }
         }
     }
 }
@@ -63,6 +78,31 @@ pub fn validate_path<P: AsRef<Path>>(path: P) -> Result<(), PathError> {
     Ok(())
 }
 
+/// Validates a path of a file or directory to create. Additionally to
+/// [`validate_path`], each component must be a valid name on FAT file
+/// systems: at most [`MAX_NAME_LEN`] characters long, and not ending with a
+/// dot or space. FAT file systems silently remove these, so that the created
+/// name would differ from the requested one.
+///
+/// This is not 8.3 short name validation: long names are accepted, as FAT
+/// file systems store them as long file names, and the file system driver
+/// generates the short names itself.
+pub fn validate_new_path<P: AsRef<Path>>(path: P) -> Result<(), PathError> {
+    let path = path.as_ref();
+    validate_path(path)?;
+    for component in path.components() {
+        let component = component.as_slice();
+        if component.len() > MAX_NAME_LEN {
+            return Err(PathError::ComponentTooLong);
+        }
+        let last = *component.last().unwrap();
+        if last == DOT || last == SPACE {
+            return Err(PathError::TrailingChar(last));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,4 +124,27 @@ mod tests {
         let err = validate_path(path).unwrap_err();
         assert_eq!(err, PathError::Empty)
     }
+
+    #[test]
+    fn test_validate_new_path() {
+        validate_new_path(cstr16!("\\hello\\foo.txt")).unwrap();
+        validate_new_path(cstr16!(".config")).unwrap();
+
+        let err = validate_new_path(cstr16!("hello.\\foo")).unwrap_err();
+        assert_eq!(err, PathError::TrailingChar(DOT));
+        let err = validate_new_path(cstr16!("hello\\foo ")).unwrap_err();
+        assert_eq!(err, PathError::TrailingChar(SPACE));
+        let err = validate_new_path(cstr16!("hello\\..")).unwrap_err();
+        assert_eq!(err, PathError::TrailingChar(DOT));
+
+        let name =
+            |len: usize| PathBuf::from(CString16::try_from("a".repeat(len).as_str()).unwrap());
+        validate_new_path(name(MAX_NAME_LEN)).unwrap();
+        let err = validate_new_path(name(MAX_NAME_LEN + 1)).unwrap_err();
+        assert_eq!(err, PathError::ComponentTooLong);
+
+        // The checks of validate_path apply too.
+        let err = validate_new_path(cstr16!("hello\\\\bar.")).unwrap_err();
+        assert_eq!(err, PathError::EmptyComponent);
+    }
 }
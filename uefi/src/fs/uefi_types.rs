@@ -2,9 +2,12 @@
 //! to differ between high-level and low-level types and interfaces in this
 //! module.
 
+// Without `alloc`, only some of the types are used.
+#[cfg_attr(not(feature = "alloc"), allow(unused_imports))]
 pub use crate::proto::media::file::{
     Directory as UefiDirectoryHandle, File as UefiFileTrait, FileAttribute as UefiFileAttribute,
     FileHandle as UefiFileHandle, FileInfo as UefiFileInfo, FileMode as UefiFileMode,
-    FileType as UefiFileType,
+    FileSystemInfo as UefiFileSystemInfo, FileSystemVolumeLabel as UefiFileSystemVolumeLabel,
+    FileType as UefiFileType, RegularFile as UefiRegularFile,
 };
 pub use crate::proto::media::fs::SimpleFileSystem as SimpleFileSystemProtocol;
@@ -0,0 +1,24 @@
+//! Module for [`VolumeInfo`].
+
+use crate::CString16;
+
+/// Information about a volume, returned by [`FileSystem::volume_info`].
+///
+/// [`FileSystem::volume_info`]: super::FileSystem::volume_info
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VolumeInfo {
+    /// Label of the volume. Empty if the volume has no label.
+    pub label: CString16,
+
+    /// Size of the volume in bytes.
+    pub size: u64,
+
+    /// Number of bytes that are free for new data.
+    pub free_space: u64,
+
+    /// Size of the blocks by which files typically grow, in bytes.
+    pub block_size: u32,
+
+    /// Whether the volume can only be read.
+    pub read_only: bool,
+}
@@ -0,0 +1,220 @@
+//! Module for recursive directory iteration. See [`Walk`].
+
+use crate::fs::*;
+use crate::Status;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// An entry of a directory tree, returned by [`Walk`].
+#[derive(Debug)]
+pub struct WalkEntry {
+    path: PathBuf,
+    depth: usize,
+    metadata: Metadata,
+}
+
+impl WalkEntry {
+    /// Get the full path of the entry, starting with the path that was
+    /// passed to [`FileSystem::walk`].
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the depth of the entry. Entries directly in the walked directory
+    /// have a depth of 1, entries in their sub directories a depth of 2, and
+    /// so on.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the metadata of the entry.
+    #[must_use]
+    pub const fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns `true` if the entry is a directory.
+    #[must_use]
+    pub const fn is_directory(&self) -> bool {
+        self.metadata.is_dir()
+    }
+
+    /// Get the full path and metadata of the entry.
+    #[must_use]
+    pub fn into_parts(self) -> (PathBuf, Metadata) {
+        (self.path, self.metadata)
+    }
+}
+
+/// An open directory of a [`Walk`].
+#[derive(Debug)]
+struct WalkDir {
+    handle: UefiDirectoryHandle,
+    path: PathBuf,
+    depth: usize,
+}
+
+/// Iterates recursively over all entries in a directory tree, returned by
+/// [`FileSystem::walk`].
+///
+/// The tree is walked depth-first: each directory is returned right before
+/// its contents, so that the entries can for example be copied in the
+/// returned order. The entries of a directory are returned in the order in
+/// which the file system returns them, and the entries `.` and `..` are
+/// skipped.
+///
+/// Hidden and system entries can be skipped with [`skip_hidden`] and
+/// [`skip_system`]; skipped directories are not descended into. With
+/// [`max_depth`], only the entries up to a given depth are returned.
+///
+/// If a sub directory cannot be opened, an error is returned in place of the
+/// directory and iteration continues with the next entry of its parent
+/// directory. If reading a directory fails, the error is returned and the
+/// rest of that directory is skipped.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::fs::FileSystem;
+/// use uefi::{boot, cstr16};
+/// # fn list_tree() -> uefi::fs::FileSystemResult<()> {
+///
+/// let mut fs = FileSystem::new(boot::get_image_file_system(boot::image_handle()).unwrap());
+/// for entry in fs.walk(cstr16!("\\EFI"))?.skip_hidden(true) {
+///     let entry = entry?;
+///     log::info!("{:indent$}{}", "", entry.path(), indent = 2 * entry.depth());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`skip_hidden`]: Self::skip_hidden
+/// [`skip_system`]: Self::skip_system
+/// [`max_depth`]: Self::max_depth
+#[derive(Debug)]
+pub struct Walk {
+    stack: Vec<WalkDir>,
+    skip_attributes: UefiFileAttribute,
+    max_depth: usize,
+}
+
+impl Walk {
+    pub(super) fn new(handle: UefiDirectoryHandle, path: PathBuf) -> Self {
+        Self {
+            stack: vec![WalkDir {
+                handle,
+                path,
+                depth: 0,
+            }],
+            skip_attributes: UefiFileAttribute::empty(),
+            max_depth: usize::MAX,
+        }
+    }
+
+    /// Skip entries with the [`HIDDEN`] attribute. Disabled by default.
+    ///
+    /// [`HIDDEN`]: UefiFileAttribute::HIDDEN
+    #[must_use]
+    pub fn skip_hidden(mut self, skip: bool) -> Self {
+        self.skip_attributes.set(UefiFileAttribute::HIDDEN, skip);
+        self
+    }
+
+    /// Skip entries with the [`SYSTEM`] attribute. Disabled by default.
+    ///
+    /// [`SYSTEM`]: UefiFileAttribute::SYSTEM
+    #[must_use]
+    pub fn skip_system(mut self, skip: bool) -> Self {
+        self.skip_attributes.set(UefiFileAttribute::SYSTEM, skip);
+        self
+    }
+
+    /// Only return entries up to depth `depth`, see [`WalkEntry::depth`].
+    /// Not limited by default.
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Open the sub directory `entry` of the directory on top of the stack.
+    fn open_dir(&mut self, entry: &WalkEntry) -> FileSystemResult<UefiDirectoryHandle> {
+        let io_error = |context, uefi_error| {
+            Error::Io(IoError {
+                path: entry.path.clone(),
+                context,
+                uefi_error,
+            })
+        };
+        let parent = &mut self.stack.last_mut().unwrap().handle;
+        parent
+            .open(
+                entry.metadata.file_name(),
+                UefiFileMode::Read,
+                UefiFileAttribute::empty(),
+            )
+            .map_err(|err| io_error(IoErrorContext::OpenError, err))?
+            .into_directory()
+            .ok_or_else(|| {
+                io_error(
+                    IoErrorContext::NotADirectory,
+                    Status::INVALID_PARAMETER.into(),
+                )
+            })
+    }
+}
+
+impl Iterator for Walk {
+    type Item = FileSystemResult<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let dir = self.stack.last_mut()?;
+            let info = match dir.handle.read_entry_boxed() {
+                Ok(Some(info)) => info,
+                Ok(None) => {
+                    self.stack.pop();
+                    continue;
+                }
+                Err(err) => {
+                    // Reading the same directory again would likely fail
+                    // again, so give up on it.
+                    let dir = self.stack.pop().unwrap();
+                    return Some(Err(Error::Io(IoError {
+                        path: dir.path,
+                        context: IoErrorContext::ReadFailure,
+                        uefi_error: err,
+                    })));
+                }
+            };
+            if COMMON_SKIP_DIRS.contains(&info.file_name())
+                || info.attribute().intersects(self.skip_attributes)
+            {
+                continue;
+            }
+
+            let mut path = dir.path.clone();
+            path.push(info.file_name());
+            let entry = WalkEntry {
+                path,
+                depth: dir.depth + 1,
+                metadata: Metadata::new(info),
+            };
+            if entry.is_directory() && entry.depth < self.max_depth {
+                match self.open_dir(&entry) {
+                    Ok(handle) => self.stack.push(WalkDir {
+                        handle,
+                        path: entry.path.clone(),
+                        depth: entry.depth,
+                    }),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+            if entry.depth <= self.max_depth {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
@@ -1,4 +1,14 @@
+#[cfg(not(feature = "global_page_allocator"))]
 use crate::allocator::Allocator;
+#[cfg(feature = "global_page_allocator")]
+use crate::allocator::PageAllocator;
 
+#[cfg(not(feature = "global_page_allocator"))]
 #[global_allocator]
 static ALLOCATOR: Allocator = Allocator;
+
+// The page allocator takes precedence if both features are enabled, for
+// example by different crates in the same build.
+#[cfg(feature = "global_page_allocator")]
+#[global_allocator]
+static ALLOCATOR: PageAllocator = PageAllocator::new();
@@ -0,0 +1,70 @@
+use crate::boot::{self, SearchType};
+use crate::Handle;
+use core::fmt::{self, Write};
+
+/// Write all handles, the protocols installed on each of them, and the agents
+/// that have each protocol open, to `writer`.
+///
+/// This is meant for debugging, for example to find out which driver has a
+/// protocol open when opening it fails with [`Status::ACCESS_DENIED`]. The
+/// output looks like this:
+///
+/// ```text
+/// Handle 0x6e2f018
+///   09576e91-6d3f-11d2-8e39-00a0c969723b
+///   964e5b21-6459-11d2-8e39-00a0c969723b
+///     agent 0x6f5e218, controller 0x6e2f018, BY_DRIVER, open count 1
+/// ```
+///
+/// Errors from the firmware are written to the output and do not stop the
+/// dump. This does not need the `alloc` feature.
+///
+/// # Errors
+///
+/// Returns an error if writing to `writer` fails.
+///
+/// [`Status::ACCESS_DENIED`]: crate::Status::ACCESS_DENIED
+pub fn dump_handle_database(writer: &mut dyn Write) -> fmt::Result {
+    let handles = match boot::locate_handle_buffer(SearchType::AllHandles) {
+        Ok(handles) => handles,
+        Err(err) => return writeln!(writer, "<error: {}>", err.status()),
+    };
+
+    for &handle in handles.iter() {
+        writeln!(writer, "Handle {:?}", handle.as_ptr())?;
+        let protocols = match boot::protocols_per_handle(handle) {
+            Ok(protocols) => protocols,
+            Err(err) => {
+                writeln!(writer, "  <error: {}>", err.status())?;
+                continue;
+            }
+        };
+        for &protocol in protocols.iter() {
+            writeln!(writer, "  {protocol}")?;
+            let entries = match boot::open_protocol_information(handle, protocol) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    writeln!(writer, "    <error: {}>", err.status())?;
+                    continue;
+                }
+            };
+            for entry in entries.iter() {
+                write!(writer, "    agent ")?;
+                write_handle(writer, entry.agent())?;
+                write!(writer, ", controller ")?;
+                write_handle(writer, entry.controller())?;
+                write!(writer, ", ")?;
+                bitflags::parser::to_writer(&entry.attributes(), &mut *writer)?;
+                writeln!(writer, ", open count {}", entry.open_count())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_handle(writer: &mut dyn Write, handle: Option<Handle>) -> fmt::Result {
+    match handle {
+        Some(handle) => write!(writer, "{:?}", handle.as_ptr()),
+        None => write!(writer, "none"),
+    }
+}
@@ -12,6 +12,11 @@
 //! The last part also means that some Unicode characters might not be
 //! supported by the UEFI console. Don't expect emoji output support.
 
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "log-debugcon"
+))]
+use super::DebugconWriter;
 use crate::proto::console::text::Output;
 use crate::system;
 use core::fmt::{self, Write};
@@ -42,40 +47,6 @@ pub fn disable() {
     LOGGER.disable();
 }
 
-/// Writer to the QEMU debugcon device and the debug-console of
-/// cloud-hypervisor.
-///
-/// More info: <https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/>
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-#[derive(Copy, Clone, Debug)]
-struct DebugconWriter;
-
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-impl DebugconWriter {
-    const IO_PORT: u16 = 0xe9;
-}
-
-#[cfg(all(
-    any(target_arch = "x86", target_arch = "x86_64"),
-    feature = "log-debugcon"
-))]
-impl core::fmt::Write for DebugconWriter {
-    fn write_str(&mut self, s: &str) -> fmt::Result {
-        for &byte in s.as_bytes() {
-            unsafe {
-                core::arch::asm!("outb %al, %dx", in("al") byte, in("dx") Self::IO_PORT, options(att_syntax))
-            };
-        }
-        Ok(())
-    }
-}
-
 /// Logging implementation which writes to a UEFI output stream.
 ///
 /// If this logger is used as a global logger, you must disable it using the
@@ -4,28 +4,53 @@
 //!
 //! For now, this includes:
 //! - using [`uefi::allocator::Allocator`] as global allocator (feature `global_allocator`)
+//!   or [`uefi::allocator::PageAllocator`] (feature `global_page_allocator`)
 //! - an implementation of  [`log::Log`] (feature `logger`) which logs to
 //!   the stdout text protocol of UEFI (as long as boot services were not
 //!   excited) and to the [debugcon device](https://phip1611.de/blog/how-to-use-qemus-debugcon-feature/)
 //!   (only on x86)  (feature `log-debugcon`).
 //! - [`print!`][print_macro] and [`println!`][println_macro] macros defaulting
-//!   to the uefi boot service stdout stream
+//!   to the uefi boot service stdout stream, and [`eprint!`][eprint_macro]
+//!   and [`eprintln!`][eprintln_macro] for stderr, with a [`PrintPolicy`] for
+//!   when the console is not available
 //! - default panic handler (feature `panic_handler`)
+//! - [`ConsoleMux`], which writes the same text to several sinks, such as the
+//!   console and a serial device
+//! - [`dump_handle_database`], which writes all handles and their protocols
+//!   for debugging
 //!
 //! **PLEASE NOTE** that these helpers are meant for the pre exit boot service
 //! epoch.
 //!
 //! [print_macro]: uefi::print!
 //! [println_macro]: uefi::println!
+//! [eprint_macro]: uefi::eprint!
+//! [eprintln_macro]: uefi::eprintln!
 
 use crate::Result;
+pub use handle_db::dump_handle_database;
+pub use mux::ConsoleMux;
 #[doc(hidden)]
-pub use println::_print;
+pub use println::{_eprint, _print};
+pub use println::{print_policy, set_print_policy, PrintPolicy};
 
-#[cfg(feature = "global_allocator")]
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "log-debugcon"
+))]
+pub use debugcon::DebugconWriter;
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    feature = "log-debugcon"
+))]
+mod debugcon;
+#[cfg(any(feature = "global_allocator", feature = "global_page_allocator"))]
 mod global_allocator;
+mod handle_db;
 #[cfg(feature = "logger")]
 mod logger;
+mod mux;
 #[cfg(feature = "panic_handler")]
 mod panic_handler;
 mod println;
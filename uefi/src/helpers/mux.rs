@@ -0,0 +1,195 @@
+use core::fmt;
+
+/// [`fmt::Write`] adapter that writes the same text to several sinks, such as
+/// the text console, a serial device, the debugcon device and a log file.
+///
+/// Up to `N` sinks can be added. Each sink can be disabled and enabled again,
+/// for example to stop writing to the screen once a graphics mode is set.
+///
+/// Text is written to all enabled sinks, even if writing to one of them
+/// fails; the write then returns an error, and [`failed`] tells which sinks
+/// failed.
+///
+/// This type does not need the `alloc` feature.
+///
+/// # Example
+///
+/// ```
+/// use core::fmt::Write;
+/// use uefi::helpers::ConsoleMux;
+/// use uefi::proto::console::serial::Serial;
+/// use uefi::proto::console::text::Output;
+///
+/// fn print_both(stdout: &mut Output, serial: &mut Serial) -> core::fmt::Result {
+///     let mut mux = ConsoleMux::<2>::new();
+///     let screen = mux.add(stdout).unwrap();
+///     mux.add(serial).unwrap();
+///     writeln!(mux, "Booting...")?;
+///
+///     // Only write the details to serial.
+///     mux.set_enabled(screen, false);
+///     writeln!(mux, "Loaded 3 drivers")
+/// }
+/// ```
+///
+/// [`failed`]: Self::failed
+pub struct ConsoleMux<'a, const N: usize = 4> {
+    sinks: [Option<Sink<'a>>; N],
+}
+
+struct Sink<'a> {
+    writer: &'a mut dyn fmt::Write,
+    enabled: bool,
+    failed: bool,
+}
+
+impl<'a, const N: usize> ConsoleMux<'a, N> {
+    /// Create a multiplexer without sinks.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sinks: [const { None }; N],
+        }
+    }
+
+    /// Add an enabled sink, and get its index for [`set_enabled`].
+    ///
+    /// Returns `None` if `N` sinks have already been added.
+    ///
+    /// [`set_enabled`]: Self::set_enabled
+    pub fn add(&mut self, writer: &'a mut dyn fmt::Write) -> Option<usize> {
+        let index = self.sinks.iter().position(Option::is_none)?;
+        self.sinks[index] = Some(Sink {
+            writer,
+            enabled: true,
+            failed: false,
+        });
+        Some(index)
+    }
+
+    /// Get the number of sinks.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sinks.iter().flatten().count()
+    }
+
+    /// Check whether no sinks have been added.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Enable or disable writing to the sink at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no sink at `index`.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.sink_mut(index).enabled = enabled;
+    }
+
+    /// Check whether writing to the sink at `index` is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no sink at `index`.
+    #[must_use]
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.sink(index).enabled
+    }
+
+    /// Check whether the last write to the sink at `index` failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no sink at `index`.
+    #[must_use]
+    pub fn failed(&self, index: usize) -> bool {
+        self.sink(index).failed
+    }
+
+    fn sink(&self, index: usize) -> &Sink<'a> {
+        self.sinks[index].as_ref().expect("no sink at this index")
+    }
+
+    fn sink_mut(&mut self, index: usize) -> &mut Sink<'a> {
+        self.sinks[index].as_mut().expect("no sink at this index")
+    }
+}
+
+impl<const N: usize> Default for ConsoleMux<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for ConsoleMux<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.sinks.iter().flatten().map(|sink| {
+                if sink.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                }
+            }))
+            .finish()
+    }
+}
+
+impl<const N: usize> fmt::Write for ConsoleMux<'_, N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut result = Ok(());
+        for sink in self.sinks.iter_mut().flatten() {
+            if sink.enabled {
+                sink.failed = sink.writer.write_str(s).is_err();
+                if sink.failed {
+                    result = Err(fmt::Error);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use core::fmt::Write;
+
+    struct Failing;
+
+    impl Write for Failing {
+        fn write_str(&mut self, _: &str) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[test]
+    fn test_console_mux() {
+        let mut screen = String::new();
+        let mut serial = String::new();
+        let mut failing = Failing;
+        let mut extra = Failing;
+        {
+            let mut mux = ConsoleMux::<3>::new();
+            assert!(mux.is_empty());
+            let screen = mux.add(&mut screen).unwrap();
+            let serial = mux.add(&mut serial).unwrap();
+            write!(mux, "a{}", 1).unwrap();
+            mux.set_enabled(screen, false);
+            assert!(!mux.is_enabled(screen));
+            write!(mux, "b").unwrap();
+
+            let failing = mux.add(&mut failing).unwrap();
+            assert!(mux.add(&mut extra).is_none());
+            assert_eq!(mux.len(), 3);
+            assert!(write!(mux, "c").is_err());
+            assert!(mux.failed(failing));
+            assert!(!mux.failed(serial));
+        }
+        assert_eq!(screen, "a1");
+        assert_eq!(serial, "a1bc");
+    }
+}
@@ -1,17 +1,83 @@
-use crate::{boot, system};
-use core::fmt::Write;
+use crate::{boot, system, table};
+use core::fmt::{Arguments, Write};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the print macros ignore errors, see [`PrintPolicy`].
+static IGNORE_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// What the [`print!`] family of macros does when the console is not
+/// available, or writing to it fails.
+///
+/// If the system table is not set, or boot services have been exited, the
+/// output is always dropped, and a message is logged at the debug level. Depending on the logger, that
+/// message might still be written to serial or debugcon.
+///
+/// [`print!`]: crate::print!
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PrintPolicy {
+    /// Panic. This is the default.
+    #[default]
+    Panic,
+    /// Silently drop the output.
+    Ignore,
+}
+
+/// Set the [`PrintPolicy`] of the print macros.
+pub fn set_print_policy(policy: PrintPolicy) {
+    IGNORE_ERRORS.store(policy == PrintPolicy::Ignore, Ordering::Relaxed);
+}
+
+/// Get the [`PrintPolicy`] of the print macros.
+#[must_use]
+pub fn print_policy() -> PrintPolicy {
+    if IGNORE_ERRORS.load(Ordering::Relaxed) {
+        PrintPolicy::Ignore
+    } else {
+        PrintPolicy::Panic
+    }
+}
 
 /// INTERNAL API! Helper for print macros.
 #[doc(hidden)]
-pub fn _print(args: core::fmt::Arguments) {
-    if boot::are_boot_services_active() {
-        system::with_stdout(|stdout| {
-            stdout.write_fmt(args).expect("Failed to write to stdout");
-        });
-    } else {
+pub fn _print(args: Arguments) {
+    print_to(args, false);
+}
+
+/// INTERNAL API! Helper for eprint macros.
+#[doc(hidden)]
+pub fn _eprint(args: Arguments) {
+    print_to(args, true);
+}
+
+fn print_to(args: Arguments, stderr: bool) {
+    let name = if stderr { "stderr" } else { "stdout" };
+    if !boot::are_boot_services_active() {
         // Ease debugging: Depending on logger, this might write to serial or
         // debugcon.
         log::debug!("You are using `print!` after the boot services have been exited.");
+        return;
+    }
+
+    // SAFETY: `are_boot_services_active` checked that the system table is
+    // set, and it is valid per requirements of `set_system_table`.
+    let st = unsafe { table::system_table_raw().unwrap().as_ref() };
+    let output = if stderr { st.stderr } else { st.stdout };
+    if output.is_null() {
+        return unavailable(format_args!("{name} is not available"));
+    }
+    let result = if stderr {
+        system::with_stderr(|stderr| stderr.write_fmt(args))
+    } else {
+        system::with_stdout(|stdout| stdout.write_fmt(args))
+    };
+    if result.is_err() {
+        unavailable(format_args!("Failed to write to {name}"));
+    }
+}
+
+fn unavailable(message: Arguments) {
+    if print_policy() == PrintPolicy::Panic {
+        panic!("{message}");
     }
 }
 
@@ -25,7 +91,11 @@ pub fn _print(args: core::fmt::Arguments) {
 /// prevent a circular runtime dependency.
 ///
 /// # Panics
-/// Will panic if the system table's `stdout` is not set, or if writing fails.
+/// Will panic if the system table's `stdout` is not set, or if writing fails,
+/// unless the [`PrintPolicy`] is [`PrintPolicy::Ignore`].
+///
+/// [`PrintPolicy`]: crate::helpers::PrintPolicy
+/// [`PrintPolicy::Ignore`]: crate::helpers::PrintPolicy::Ignore
 ///
 /// # Examples
 /// ```
@@ -49,7 +119,11 @@ macro_rules! print {
 /// prevent a circular runtime dependency.
 ///
 /// # Panics
-/// Will panic if the system table's `stdout` is not set, or if writing fails.
+/// Will panic if the system table's `stdout` is not set, or if writing fails,
+/// unless the [`PrintPolicy`] is [`PrintPolicy::Ignore`].
+///
+/// [`PrintPolicy`]: crate::helpers::PrintPolicy
+/// [`PrintPolicy::Ignore`]: crate::helpers::PrintPolicy::Ignore
 ///
 /// # Examples
 /// ```
@@ -62,3 +136,48 @@ macro_rules! println {
     () => ($crate::print!("\n"));
     ($($arg:tt)*) => ($crate::helpers::_print(core::format_args!("{}{}", core::format_args!($($arg)*), "\n")));
 }
+
+/// Prints to the standard error output of the UEFI boot service console.
+///
+/// This works like [`print!`](crate::print!), but writes to `stderr`, which
+/// some firmware shows on the screen and some sends to a serial port.
+///
+/// # Panics
+/// Will panic if the system table's `stderr` is not set, or if writing fails,
+/// unless the [`PrintPolicy`] is [`PrintPolicy::Ignore`].
+///
+/// [`PrintPolicy`]: crate::helpers::PrintPolicy
+/// [`PrintPolicy::Ignore`]: crate::helpers::PrintPolicy::Ignore
+///
+/// # Examples
+/// ```
+/// eprint!("");
+/// eprint!("Error: {}\n", "not found");
+/// ```
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => ($crate::helpers::_eprint(core::format_args!($($arg)*)));
+}
+
+/// Prints to the standard error output of the UEFI boot service console,
+/// but with a newline.
+///
+/// This works like [`println!`](crate::println!), but writes to `stderr`.
+///
+/// # Panics
+/// Will panic if the system table's `stderr` is not set, or if writing fails,
+/// unless the [`PrintPolicy`] is [`PrintPolicy::Ignore`].
+///
+/// [`PrintPolicy`]: crate::helpers::PrintPolicy
+/// [`PrintPolicy::Ignore`]: crate::helpers::PrintPolicy::Ignore
+///
+/// # Examples
+/// ```
+/// eprintln!();
+/// eprintln!("Error: {}", "not found");
+/// ```
+#[macro_export]
+macro_rules! eprintln {
+    () => ($crate::eprint!("\n"));
+    ($($arg:tt)*) => ($crate::helpers::_eprint(core::format_args!("{}{}", core::format_args!($($arg)*), "\n")));
+}
@@ -69,6 +69,8 @@
 //! The minimum supported Rust version is currently 1.79.
 //! Our policy is to support at least the past two stable releases.
 //!
+//! The `smoltcp` feature is an exception: its dependency requires Rust 1.80.
+//!
 //! # API/User Documentation, Documentation Structure, and other Resources
 //!
 //! Down below, you find typical technical documentation of all types, modules,
@@ -124,11 +126,17 @@
 //!   a global allocator; you can use the `global_allocator` feature or
 //!   provide your own. This is independent of internal direct usages of the
 //!   UEFI boot service allocator which may happen anyway, where necessary.
+//! - `async`: Enable the [`executor`] module, with a minimal executor for
+//!   futures that waits for UEFI events. This also enables `alloc`.
 //! - `global_allocator`: Set [`allocator::Allocator`] as the global Rust
 //!   allocator. This is a simple allocator that relies on the UEFI pool
 //!   allocator. You can choose to provide your own allocator instead of
 //!   using this feature, or no allocator at all if you don't need to
 //!   dynamically allocate any memory.
+//! - `global_page_allocator`: Set [`allocator::PageAllocator`] as the global
+//!   Rust allocator. It sub-allocates large page ranges, which is faster than
+//!   the pool allocator of some firmware. It takes precedence over
+//!   `global_allocator` if both are enabled.
 //! - `logger`: Logging implementation for the standard [`log`] crate
 //!   that prints output to the UEFI console. No buffering is done; this
 //!   is not a high-performance logger.
@@ -140,6 +148,17 @@
 //! - `qemu`: Enable some code paths to adapt their execution when executed
 //!   in QEMU, such as using the special `qemu-exit` device when the panic
 //!   handler is called.
+//! - `smoltcp`: Implement [`smoltcp::phy::Device`] for the Simple Network
+//!   Protocol, so that the [`smoltcp`] TCP/IP stack can be used on top of
+//!   it. See [`proto::network::smoltcp`]. This feature requires Rust 1.80.
+//! - `embedded-io`: Implement the [`embedded_io`] `Read`, `Write` and `Seek`
+//!   traits for [`RegularFile`], [`BlockIoReader`] and [`Serial`], so that
+//!   `no_std` libraries built on these traits can read from and write to
+//!   them.
+//! - `embedded-graphics`: Implement the [`embedded-graphics`]
+//!   `DrawTarget` trait for the Graphics Output Protocol, so that its fonts,
+//!   primitives and images can be drawn to the screen. See
+//!   [`proto::console::embedded_graphics`].
 //!
 //! Some of these features, such as the `logger` or `panic_handler` features,
 //! only unfold their potential when you invoke `uefi::helpers::init` as soon
@@ -211,6 +230,10 @@
 //! [template]: https://github.com/rust-osdev/uefi-rs/tree/main/template
 //! [uefi-std-tr-issue]: https://github.com/rust-lang/rust/issues/100499
 //! [unstable features]: https://doc.rust-lang.org/unstable-book/
+//! [`BlockIoReader`]: storage::reader::BlockIoReader
+//! [`RegularFile`]: proto::media::file::RegularFile
+//! [`Serial`]: proto::console::serial::Serial
+//! [`embedded-graphics`]: https://docs.rs/embedded-graphics
 
 #![cfg_attr(all(feature = "unstable", feature = "alloc"), feature(allocator_api))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
@@ -239,18 +262,21 @@ extern crate uefi_raw;
 pub mod data_types;
 pub mod allocator;
 pub mod boot;
-#[cfg(feature = "alloc")]
+#[cfg(feature = "async")]
+pub mod executor;
 pub mod fs;
 pub mod helpers;
 pub mod mem;
 pub mod prelude;
 pub mod proto;
 pub mod runtime;
+pub mod storage;
 pub mod system;
 pub mod table;
 
 pub(crate) mod polyfill;
 
+mod duration;
 mod macros;
 mod result;
 mod util;
@@ -263,5 +289,5 @@ pub use result::{Error, Result, ResultExt, Status, StatusExt};
 /// cstr16 macro. It is hidden since it's not intended to be used directly.
 #[doc(hidden)]
 pub use ucs2::ucs2_cstr;
-pub use uefi_macros::entry;
+pub use uefi_macros::{entry, unload};
 pub use uguid::guid;
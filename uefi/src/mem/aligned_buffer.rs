@@ -0,0 +1,123 @@
+//! Heap buffer with a runtime alignment.
+
+use ::alloc::alloc::{alloc_zeroed, dealloc, handle_alloc_error};
+use core::alloc::{Layout, LayoutError};
+use core::ptr::NonNull;
+use core::slice;
+
+/// Zero-initialized heap buffer with an alignment that is only known at
+/// runtime.
+///
+/// Some protocols require buffers to be aligned to a value reported by the
+/// device, such as the `io_align` of a pass-thru protocol. `Vec<u8>` is only
+/// aligned to one byte, so this type can be used instead.
+#[derive(Debug)]
+pub struct AlignedBuffer {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl AlignedBuffer {
+    /// Allocate a zeroed buffer of `len` bytes, aligned to `alignment` bytes.
+    /// An `alignment` of zero is treated as one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `alignment` is not a power of two, or if `len` rounded up to
+    /// `alignment` overflows `isize`.
+    pub fn from_size_align(len: usize, alignment: usize) -> Result<Self, LayoutError> {
+        Layout::from_size_align(len, alignment.max(1)).map(Self::from_layout)
+    }
+
+    /// Allocate a zeroed buffer with `layout`.
+    ///
+    /// # Panics
+    ///
+    /// Aborts via [`handle_alloc_error`] if the allocation fails.
+    #[must_use]
+    pub fn from_layout(layout: Layout) -> Self {
+        let ptr = if layout.size() == 0 {
+            // Zero-sized allocations are not allowed; use a dangling, but
+            // aligned, pointer instead.
+            unsafe { NonNull::new_unchecked(layout.align() as *mut u8) }
+        } else {
+            let ptr = unsafe { alloc_zeroed(layout) };
+            NonNull::new(ptr).unwrap_or_else(|| handle_alloc_error(layout))
+        };
+        Self { ptr, layout }
+    }
+
+    /// Get the length of the buffer in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Check whether the buffer is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the alignment of the buffer in bytes.
+    #[must_use]
+    pub const fn alignment(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Get a pointer to the start of the buffer.
+    #[must_use]
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Get a mutable pointer to the start of the buffer.
+    #[must_use]
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Get the contents of the buffer.
+    #[must_use]
+    pub const fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len()) }
+    }
+
+    /// Get the contents of the buffer mutably.
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) }
+    }
+}
+
+impl Drop for AlignedBuffer {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aligned_buffer() {
+        for alignment in [0, 1, 2, 64, 4096] {
+            let mut buffer = AlignedBuffer::from_size_align(100, alignment).unwrap();
+            assert_eq!(buffer.len(), 100);
+            assert_eq!(buffer.alignment(), alignment.max(1));
+            assert_eq!(buffer.as_ptr() as usize % alignment.max(1), 0);
+            assert!(buffer.as_slice().iter().all(|&b| b == 0));
+            buffer.as_mut_slice()[99] = 1;
+            assert_eq!(buffer.as_slice()[99], 1);
+        }
+
+        let empty = AlignedBuffer::from_size_align(0, 8).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.as_ptr() as usize % 8, 0);
+
+        assert!(AlignedBuffer::from_size_align(1, 3).is_err());
+    }
+}
@@ -87,6 +87,60 @@ pub trait MemoryMap: Debug + Index<usize, Output = MemoryDescriptor> {
         }
         true
     }
+
+    /// Compares this memory map with a `newer` one, for example to find out
+    /// what changed the memory map between two calls to
+    /// [`boot::memory_map`].
+    ///
+    /// [`boot::memory_map`]: crate::boot::memory_map
+    #[must_use]
+    fn diff<'a>(&'a self, newer: &'a dyn MemoryMap) -> MemoryMapDiff<'a>
+    where
+        Self: Sized,
+    {
+        MemoryMapDiff {
+            old: self,
+            new: newer,
+        }
+    }
+
+    /// Returns the size of the memory map when serialized with
+    /// [`serialize`].
+    ///
+    /// [`serialize`]: Self::serialize
+    #[must_use]
+    fn serialized_size(&self) -> usize {
+        mem::size_of::<SerializedMemoryMapHeader>() + self.len() * self.meta().desc_size
+    }
+
+    /// Writes the memory map into `buffer` in a self-describing format, and
+    /// returns the number of bytes written.
+    ///
+    /// The format is a [`SerializedMemoryMapHeader`] followed by the
+    /// descriptors, which keep their size and version. Use
+    /// [`MemoryMapRef::from_serialized`] to parse it, for example in a
+    /// kernel that gets the buffer from its bootloader.
+    ///
+    /// # Errors
+    ///
+    /// * [`MemoryMapError::InvalidSize`]: `buffer` is smaller than
+    ///   [`serialized_size`].
+    ///
+    /// [`serialized_size`]: Self::serialized_size
+    fn serialize(&self, buffer: &mut [u8]) -> Result<usize, MemoryMapError> {
+        serialize::serialize(self, buffer)
+    }
+
+    /// Computes summary statistics of the memory map, such as the amount of
+    /// installed RAM and the number of pages of each type.
+    #[must_use]
+    fn stats(&self) -> MemoryMapStats {
+        let mut stats = MemoryMapStats::default();
+        for desc in self.entries() {
+            stats.add(desc);
+        }
+        stats
+    }
 }
 
 /// Extension to [`MemoryMap`] that adds mutable operations. This also includes
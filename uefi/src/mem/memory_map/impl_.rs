@@ -10,13 +10,15 @@ use core::{mem, ptr};
 use uefi_raw::PhysicalAddress;
 
 /// Errors that may happen when constructing a [`MemoryMapRef`] or
-/// [`MemoryMapRefMut`].
-#[derive(Copy, Clone, Debug)]
+/// [`MemoryMapRefMut`], or when serializing a memory map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum MemoryMapError {
     /// The buffer is not 8-byte aligned.
     Misaligned,
     /// The memory map size is invalid.
     InvalidSize,
+    /// The header of a serialized memory map is invalid.
+    InvalidHeader,
 }
 
 impl Display for MemoryMapError {
@@ -146,7 +148,9 @@ impl<'a> MemoryMap for MemoryMapRefMut<'a> {
 
 impl<'a> MemoryMapMut for MemoryMapRefMut<'a> {
     fn sort(&mut self) {
-        self.qsort(0, self.len - 1);
+        if self.len > 0 {
+            self.qsort(0, self.len - 1);
+        }
     }
 
     unsafe fn buffer_mut(&mut self) -> &mut [u8] {
@@ -155,6 +159,49 @@ impl<'a> MemoryMapMut for MemoryMapRefMut<'a> {
 }
 
 impl<'a> MemoryMapRefMut<'a> {
+    /// Sorts the memory map by physical address, and merges adjacent
+    /// descriptors of the same type and attributes into one.
+    ///
+    /// Descriptors are adjacent if both their physical and virtual ranges
+    /// are, or if their physical ranges are and both have a virtual start of
+    /// zero. This shrinks [`len`] and the map size of [`meta`]; the key stays
+    /// the same.
+    ///
+    /// [`len`]: MemoryMap::len
+    /// [`meta`]: MemoryMap::meta
+    pub fn merge(&mut self) {
+        if !self.is_sorted() {
+            self.sort();
+        }
+
+        let desc_size = self.meta.desc_size;
+        let mut last = 0;
+        for index in 1..self.len {
+            let desc = self[index];
+            let prev = &mut self[last];
+            if let Some(page_count) = merged_page_count(prev, &desc) {
+                prev.page_count = page_count;
+            } else {
+                last += 1;
+                if last != index {
+                    // SAFETY: both offsets are within the buffer, and
+                    // `last < index`, so the descriptors do not overlap.
+                    unsafe {
+                        let base = self.buf.as_mut_ptr();
+                        ptr::copy_nonoverlapping(
+                            base.add(index * desc_size),
+                            base.add(last * desc_size),
+                            desc_size,
+                        );
+                    }
+                }
+            }
+        }
+
+        self.len = self.len.min(last + 1);
+        self.meta.map_size = self.len * desc_size;
+    }
+
     /// Hoare partition scheme for quicksort.
     /// Must be called with `low` and `high` being indices within bounds.
     fn qsort(&mut self, low: usize, high: usize) {
@@ -224,6 +271,20 @@ impl<'a> MemoryMapRefMut<'a> {
     }
 }
 
+/// Get the page count of `prev` merged with `next`, or `None` if they can't be
+/// merged; see [`MemoryMapRefMut::merge`].
+fn merged_page_count(prev: &MemoryDescriptor, next: &MemoryDescriptor) -> Option<u64> {
+    let size = prev.page_count.checked_mul(boot::PAGE_SIZE as u64)?;
+    let phys_adjacent = prev.phys_start.checked_add(size)? == next.phys_start;
+    let virt_adjacent = prev.virt_start.checked_add(size) == Some(next.virt_start)
+        || (prev.virt_start == 0 && next.virt_start == 0);
+    if prev.ty == next.ty && prev.att == next.att && phys_adjacent && virt_adjacent {
+        prev.page_count.checked_add(next.page_count)
+    } else {
+        None
+    }
+}
+
 impl Index<usize> for MemoryMapRefMut<'_> {
     type Output = MemoryDescriptor;
 
@@ -373,6 +434,156 @@ impl MemoryMapOwned {
     }
 }
 
+impl MemoryMapOwned {
+    /// Sorts the memory map by physical address, and merges adjacent
+    /// descriptors of the same type and attributes into one.
+    ///
+    /// See [`MemoryMapRefMut::merge`].
+    pub fn merge(&mut self) {
+        let mut reference = MemoryMapRefMut {
+            buf: self.buf.as_mut_slice(),
+            meta: self.meta,
+            len: self.len,
+        };
+        reference.merge();
+        self.meta = reference.meta;
+        self.len = reference.len;
+    }
+}
+
+/// The differences between two memory maps, as returned by
+/// [`MemoryMap::diff`].
+///
+/// A descriptor that changed shows up both as removed and as added.
+/// Comparing takes time proportional to the product of the lengths of the two
+/// maps.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryMapDiff<'a> {
+    pub(crate) old: &'a dyn MemoryMap,
+    pub(crate) new: &'a dyn MemoryMap,
+}
+
+impl<'a> MemoryMapDiff<'a> {
+    /// Returns the descriptors of the older map that are not in the newer
+    /// one.
+    pub fn removed(&self) -> impl Iterator<Item = &'a MemoryDescriptor> + 'a {
+        let new = self.new;
+        self.old
+            .entries()
+            .filter(move |desc| !new.entries().any(|other| other == *desc))
+    }
+
+    /// Returns the descriptors of the newer map that are not in the older
+    /// one.
+    pub fn added(&self) -> impl Iterator<Item = &'a MemoryDescriptor> + 'a {
+        let old = self.old;
+        self.new
+            .entries()
+            .filter(move |desc| !old.entries().any(|other| other == *desc))
+    }
+
+    /// Returns `true` if both maps have the same descriptors.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.removed().next().is_none() && self.added().next().is_none()
+    }
+}
+
+/// Summary statistics of a memory map, as returned by [`MemoryMap::stats`].
+///
+/// All sizes are in pages of [`PAGE_SIZE`] bytes.
+///
+/// [`PAGE_SIZE`]: boot::PAGE_SIZE
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryMapStats {
+    pages_by_type: [u64; MemoryType::MAX.0 as usize],
+    custom_pages: u64,
+    largest_free_region: Option<(PhysicalAddress, u64)>,
+}
+
+impl MemoryMapStats {
+    pub(crate) fn add(&mut self, desc: &MemoryDescriptor) {
+        match self.pages_by_type.get_mut(desc.ty.0 as usize) {
+            Some(pages) => *pages += desc.page_count,
+            None => self.custom_pages += desc.page_count,
+        }
+        if desc.ty == MemoryType::CONVENTIONAL
+            && self
+                .largest_free_region
+                .map_or(true, |(_, page_count)| desc.page_count > page_count)
+        {
+            self.largest_free_region = Some((desc.phys_start, desc.page_count));
+        }
+    }
+
+    /// The number of pages of type `ty`.
+    ///
+    /// Pages of the OEM and OS loader types are not counted per type; see
+    /// [`custom_pages`].
+    ///
+    /// [`custom_pages`]: Self::custom_pages
+    #[must_use]
+    pub fn pages(&self, ty: MemoryType) -> u64 {
+        self.pages_by_type
+            .get(ty.0 as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The number of pages of the OEM and OS loader memory types.
+    #[must_use]
+    pub const fn custom_pages(&self) -> u64 {
+        self.custom_pages
+    }
+
+    /// The number of pages of all descriptors.
+    #[must_use]
+    pub fn total_pages(&self) -> u64 {
+        self.pages_by_type.iter().sum::<u64>() + self.custom_pages
+    }
+
+    /// The number of pages of installed RAM: all pages except those of
+    /// [`MemoryType::RESERVED`], [`MemoryType::MMIO`],
+    /// [`MemoryType::MMIO_PORT_SPACE`] and
+    /// [`MemoryType::PERSISTENT_MEMORY`].
+    #[must_use]
+    pub fn ram_pages(&self) -> u64 {
+        self.total_pages()
+            - self.pages(MemoryType::RESERVED)
+            - self.pages(MemoryType::MMIO)
+            - self.pages(MemoryType::MMIO_PORT_SPACE)
+            - self.pages(MemoryType::PERSISTENT_MEMORY)
+    }
+
+    /// The number of pages of [`MemoryType::CONVENTIONAL`], which are free
+    /// now.
+    #[must_use]
+    pub fn conventional_pages(&self) -> u64 {
+        self.pages(MemoryType::CONVENTIONAL)
+    }
+
+    /// The number of pages that are free once boot services are exited: the
+    /// pages of [`MemoryType::CONVENTIONAL`],
+    /// [`MemoryType::BOOT_SERVICES_CODE`] and
+    /// [`MemoryType::BOOT_SERVICES_DATA`].
+    #[must_use]
+    pub fn usable_pages(&self) -> u64 {
+        self.conventional_pages()
+            + self.pages(MemoryType::BOOT_SERVICES_CODE)
+            + self.pages(MemoryType::BOOT_SERVICES_DATA)
+    }
+
+    /// The physical start address and page count of the largest descriptor of
+    /// [`MemoryType::CONVENTIONAL`], if any.
+    ///
+    /// Adjacent free descriptors are not combined; merge the memory map first
+    /// to include them, for example with [`MemoryMapOwned::merge`].
+    #[must_use]
+    pub const fn largest_free_region(&self) -> Option<(PhysicalAddress, u64)> {
+        self.largest_free_region
+    }
+}
+
 impl MemoryMap for MemoryMapOwned {
     fn meta(&self) -> MemoryMapMeta {
         self.meta
@@ -525,4 +736,107 @@ mod tests {
         mmap.sort();
         assert!(mmap.is_sorted());
     }
+
+    fn desc(ty: MemoryType, phys_start: u64, page_count: u64) -> MemoryDescriptor {
+        MemoryDescriptor {
+            ty,
+            phys_start,
+            virt_start: 0,
+            page_count,
+            att: MemoryAttribute::WRITE_BACK,
+        }
+    }
+
+    #[test]
+    fn memory_map_merge() {
+        let mut memory = [
+            desc(MemoryType::CONVENTIONAL, 0x3000, 2),
+            desc(MemoryType::LOADER_DATA, 0x6000, 1),
+            desc(MemoryType::CONVENTIONAL, 0x1000, 2),
+            desc(MemoryType::CONVENTIONAL, 0x5000, 1),
+            desc(MemoryType::CONVENTIONAL, 0x8000, 1),
+        ];
+        let (mmap, meta) = mmap_raw(&mut memory);
+        let mmap = MemoryMapBackingMemory::from_slice(mmap);
+        let mut mmap = MemoryMapOwned::from_initialized_mem(mmap, meta);
+
+        mmap.merge();
+        assert_eq!(
+            mmap.entries().copied().collect::<Vec<_>>(),
+            [
+                desc(MemoryType::CONVENTIONAL, 0x1000, 5),
+                desc(MemoryType::LOADER_DATA, 0x6000, 1),
+                desc(MemoryType::CONVENTIONAL, 0x8000, 1),
+            ]
+        );
+        assert_eq!(mmap.meta().entry_count(), 3);
+
+        // Different attributes are not merged.
+        let mut memory = [
+            desc(MemoryType::CONVENTIONAL, 0x1000, 1),
+            MemoryDescriptor {
+                att: MemoryAttribute::UNCACHEABLE,
+                ..desc(MemoryType::CONVENTIONAL, 0x2000, 1)
+            },
+        ];
+        let (mmap, meta) = mmap_raw(&mut memory);
+        let mut mmap = MemoryMapRefMut::new(mmap, meta).unwrap();
+        mmap.merge();
+        assert_eq!(mmap.len(), 2);
+    }
+
+    #[test]
+    fn memory_map_diff() {
+        let mut old = [
+            desc(MemoryType::CONVENTIONAL, 0x1000, 4),
+            desc(MemoryType::LOADER_DATA, 0x5000, 1),
+        ];
+        let mut new = [
+            desc(MemoryType::BOOT_SERVICES_DATA, 0x1000, 1),
+            desc(MemoryType::CONVENTIONAL, 0x2000, 3),
+            desc(MemoryType::LOADER_DATA, 0x5000, 1),
+        ];
+        let (old, meta) = mmap_raw(&mut old);
+        let old = MemoryMapRef::new(old, meta).unwrap();
+        let (new, meta) = mmap_raw(&mut new);
+        let new = MemoryMapRef::new(new, meta).unwrap();
+
+        let diff = old.diff(&new);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.removed().copied().collect::<Vec<_>>(),
+            [desc(MemoryType::CONVENTIONAL, 0x1000, 4)]
+        );
+        assert_eq!(
+            diff.added().copied().collect::<Vec<_>>(),
+            [
+                desc(MemoryType::BOOT_SERVICES_DATA, 0x1000, 1),
+                desc(MemoryType::CONVENTIONAL, 0x2000, 3),
+            ]
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn memory_map_stats() {
+        let mut memory = [
+            desc(MemoryType::CONVENTIONAL, 0x1000, 4),
+            desc(MemoryType::BOOT_SERVICES_DATA, 0x5000, 2),
+            desc(MemoryType::CONVENTIONAL, 0x7000, 8),
+            desc(MemoryType::MMIO, 0xf000_0000, 16),
+            desc(MemoryType::custom(0x8000_0000), 0x10000, 3),
+        ];
+        let (mmap, meta) = mmap_raw(&mut memory);
+        let stats = MemoryMapRef::new(mmap, meta).unwrap().stats();
+
+        assert_eq!(stats.pages(MemoryType::CONVENTIONAL), 12);
+        assert_eq!(stats.pages(MemoryType::LOADER_DATA), 0);
+        assert_eq!(stats.pages(MemoryType::custom(0x8000_0000)), 0);
+        assert_eq!(stats.custom_pages(), 3);
+        assert_eq!(stats.total_pages(), 33);
+        assert_eq!(stats.ram_pages(), 17);
+        assert_eq!(stats.conventional_pages(), 12);
+        assert_eq!(stats.usable_pages(), 14);
+        assert_eq!(stats.largest_free_region(), Some((0x7000, 8)));
+    }
 }
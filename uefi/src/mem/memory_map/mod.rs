@@ -18,12 +18,20 @@
 //! might be the case if a bootloader such as GRUB or Limine passes its boot
 //! information, you can use [`MemoryMapRef`] or [`MemoryMapRefMut`].
 //!
+//! # Usecase: Pass the Memory Map to a Kernel
+//!
+//! [`MemoryMap::serialize`] writes the memory map into a buffer, with a
+//! [`SerializedMemoryMapHeader`] that describes the descriptors. The kernel
+//! can then parse the buffer with [`MemoryMapRef::from_serialized`] from this
+//! same crate, without copying it.
+//!
 //! # All relevant exports:
 //!
 //! - the traits [`MemoryMap`] and [`MemoryMapMut`],
 //! - the trait implementations [`MemoryMapOwned`], [`MemoryMapRef`], and
 //!   [`MemoryMapRefMut`],
 //! - the iterator [`MemoryMapIter`]
+//! - the serialized format's [`SerializedMemoryMapHeader`]
 //! - various associated helper types, such as [`MemoryMapKey`] and
 //!   [`MemoryMapMeta`],
 //! - re-exports [`MemoryDescriptor`], [`MemoryType`], and [`MemoryAttribute`].
@@ -34,10 +42,12 @@
 mod api;
 mod impl_;
 mod iter;
+mod serialize;
 
 pub use api::*;
 pub use impl_::*;
 pub use iter::*;
+pub use serialize::*;
 pub use uefi_raw::table::boot::{MemoryAttribute, MemoryDescriptor, MemoryType};
 
 use crate::data_types::Align;
@@ -0,0 +1,207 @@
+//! Module for the serialized format of memory maps, see
+//! [`MemoryMap::serialize`] and [`MemoryMapRef::from_serialized`].
+
+use super::*;
+use core::mem::size_of;
+use core::ptr;
+
+/// Header of a memory map serialized with [`MemoryMap::serialize`].
+///
+/// The header is followed by `entry_count` descriptors of `desc_size` bytes,
+/// starting `header_size` bytes after the start of the header. All values are
+/// in the byte order of the platform, as the serialized map is meant to be
+/// passed to a kernel on the same machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct SerializedMemoryMapHeader {
+    /// Always [`SerializedMemoryMapHeader::MAGIC`].
+    pub magic: [u8; 8],
+    /// The size of the header, which may grow in the future. It is a multiple
+    /// of 8, so that the descriptors are aligned.
+    pub header_size: u32,
+    /// The version of the descriptors, see [`MemoryMapMeta::desc_version`].
+    pub desc_version: u32,
+    /// The size of each descriptor, see [`MemoryMapMeta::desc_size`].
+    pub desc_size: u64,
+    /// The number of descriptors.
+    pub entry_count: u64,
+}
+
+impl SerializedMemoryMapHeader {
+    /// The magic bytes at the start of a serialized memory map.
+    pub const MAGIC: [u8; 8] = *b"UEFIMMAP";
+}
+
+/// See [`MemoryMap::serialize`].
+pub(crate) fn serialize<M: MemoryMap + ?Sized>(
+    map: &M,
+    buffer: &mut [u8],
+) -> Result<usize, MemoryMapError> {
+    let meta = map.meta();
+    let descriptors_size = map.len() * meta.desc_size;
+    let size = size_of::<SerializedMemoryMapHeader>() + descriptors_size;
+    if buffer.len() < size {
+        return Err(MemoryMapError::InvalidSize);
+    }
+
+    let header = SerializedMemoryMapHeader {
+        magic: SerializedMemoryMapHeader::MAGIC,
+        header_size: size_of::<SerializedMemoryMapHeader>() as u32,
+        desc_version: meta.desc_version,
+        desc_size: meta.desc_size as u64,
+        entry_count: map.len() as u64,
+    };
+    let (header_buf, descriptors_buf) =
+        buffer[..size].split_at_mut(size_of::<SerializedMemoryMapHeader>());
+    // SAFETY: `header_buf` is large enough for the header. The write does not
+    // need to be aligned.
+    unsafe {
+        ptr::write_unaligned(
+            header_buf.as_mut_ptr().cast::<SerializedMemoryMapHeader>(),
+            header,
+        );
+    }
+    descriptors_buf.copy_from_slice(&map.buffer()[..descriptors_size]);
+    Ok(size)
+}
+
+impl<'a> MemoryMapRef<'a> {
+    /// Parses a memory map serialized with [`MemoryMap::serialize`], without
+    /// copying it.
+    ///
+    /// The descriptors keep the size and version they had when the map was
+    /// serialized. `buffer` must be 8-byte aligned, and may be longer than the
+    /// serialized map.
+    ///
+    /// # Errors
+    ///
+    /// * [`MemoryMapError::InvalidHeader`]: `buffer` does not start with a
+    ///   valid [`SerializedMemoryMapHeader`].
+    /// * [`MemoryMapError::InvalidSize`]: `buffer` is too short for the
+    ///   descriptors.
+    /// * [`MemoryMapError::Misaligned`]: `buffer` is not 8-byte aligned.
+    pub fn from_serialized(buffer: &'a [u8]) -> Result<Self, MemoryMapError> {
+        if buffer.len() < size_of::<SerializedMemoryMapHeader>() {
+            return Err(MemoryMapError::InvalidSize);
+        }
+        // SAFETY: `buffer` is large enough for the header, and any bytes are
+        // a valid header. The read does not need to be aligned.
+        let header =
+            unsafe { ptr::read_unaligned(buffer.as_ptr().cast::<SerializedMemoryMapHeader>()) };
+
+        let header_size = header.header_size as usize;
+        let desc_size =
+            usize::try_from(header.desc_size).map_err(|_| MemoryMapError::InvalidHeader)?;
+        if header.magic != SerializedMemoryMapHeader::MAGIC
+            || header_size < size_of::<SerializedMemoryMapHeader>()
+            || header_size % 8 != 0
+            || desc_size < size_of::<MemoryDescriptor>()
+            || desc_size % 8 != 0
+        {
+            return Err(MemoryMapError::InvalidHeader);
+        }
+        let map_size = usize::try_from(header.entry_count)
+            .ok()
+            .and_then(|count| count.checked_mul(desc_size))
+            .ok_or(MemoryMapError::InvalidSize)?;
+        let descriptors = buffer
+            .get(header_size..)
+            .and_then(|descriptors| descriptors.get(..map_size))
+            .ok_or(MemoryMapError::InvalidSize)?;
+
+        Self::new(
+            descriptors,
+            MemoryMapMeta {
+                map_size,
+                desc_size,
+                map_key: MemoryMapKey::default(),
+                desc_version: header.desc_version,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use core::mem::size_of_val;
+    use core::slice;
+
+    /// A descriptor with padding, like firmware that reports a bigger
+    /// descriptor size.
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    struct PaddedDescriptor {
+        desc: MemoryDescriptor,
+        padding: u64,
+    }
+
+    fn descriptor(phys_start: u64, page_count: u64) -> PaddedDescriptor {
+        PaddedDescriptor {
+            desc: MemoryDescriptor {
+                ty: MemoryType::CONVENTIONAL,
+                phys_start,
+                virt_start: 0,
+                page_count,
+                att: MemoryAttribute::WRITE_BACK,
+            },
+            padding: 0x1234,
+        }
+    }
+
+    fn as_bytes<T>(value: &[T]) -> &[u8] {
+        unsafe { slice::from_raw_parts(value.as_ptr().cast(), size_of_val(value)) }
+    }
+
+    fn as_bytes_mut<T>(value: &mut [T]) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(value.as_mut_ptr().cast(), size_of_val(value)) }
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let descriptors = [descriptor(0x1000, 1), descriptor(0x4000, 3)];
+        let desc_size = size_of::<PaddedDescriptor>();
+        let map = MemoryMapRef::new(
+            as_bytes(&descriptors),
+            MemoryMapMeta {
+                map_size: 2 * desc_size,
+                desc_size,
+                map_key: MemoryMapKey(7),
+                desc_version: MemoryDescriptor::VERSION,
+            },
+        )
+        .unwrap();
+
+        let mut buffer = [0u64; 32];
+        let buffer = as_bytes_mut(&mut buffer);
+        assert_eq!(map.serialized_size(), 32 + 2 * desc_size);
+        assert_eq!(
+            map.serialize(&mut buffer[..40]).unwrap_err(),
+            MemoryMapError::InvalidSize
+        );
+        let size = map.serialize(buffer).unwrap();
+        assert_eq!(size, map.serialized_size());
+        assert_eq!(&buffer[..8], b"UEFIMMAP");
+        // The padding is preserved.
+        assert_eq!(&buffer[32..size], as_bytes(&descriptors));
+
+        let parsed = MemoryMapRef::from_serialized(buffer).unwrap();
+        assert_eq!(parsed.meta().desc_size, desc_size);
+        assert_eq!(parsed.meta().desc_version, MemoryDescriptor::VERSION);
+        assert_eq!(
+            parsed.entries().copied().collect::<Vec<_>>(),
+            [descriptors[0].desc, descriptors[1].desc]
+        );
+
+        assert_eq!(
+            MemoryMapRef::from_serialized(&buffer[..size - 8]).unwrap_err(),
+            MemoryMapError::InvalidSize
+        );
+        buffer[0] = b'X';
+        assert_eq!(
+            MemoryMapRef::from_serialized(buffer).unwrap_err(),
+            MemoryMapError::InvalidHeader
+        );
+    }
+}
@@ -1,9 +1,14 @@
 //! Types, functions, traits, and other helpers to work with memory in UEFI
 //! libraries and applications.
 
+#[cfg(feature = "alloc")]
+mod aligned_buffer;
 pub mod memory_map;
+pub mod tracking;
 #[cfg(feature = "alloc")]
 pub(crate) mod util;
 
+#[cfg(feature = "alloc")]
+pub use aligned_buffer::AlignedBuffer;
 #[cfg(feature = "alloc")]
 pub(crate) use util::*;
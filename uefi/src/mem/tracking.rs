@@ -0,0 +1,545 @@
+//! Opt-in tracking of pool and page allocations, to find out which memory is
+//! still allocated when exiting the boot services.
+//!
+//! Once [`enable`]d, every allocation made with [`boot::allocate_pool`] or
+//! [`boot::allocate_pages`] (and the helpers built on them, such as the
+//! [`Allocator`]) is recorded together with its caller and the current
+//! [tag](set_tag), until it is freed again. The live allocations can then be
+//! listed with [`for_each_live`] or [`report_leaks`].
+//!
+//! After [`freeze`], allocations fail with [`Status::ACCESS_DENIED`], which
+//! helps to find code that still allocates after the point where it should
+//! have stopped, such as right before [`boot::exit_boot_services`]. The memory
+//! map allocated by [`boot::exit_boot_services`] itself is still allowed.
+//! Freezing does not need tracking to be enabled.
+//!
+//! Memory allocated by the firmware or before tracking was enabled is not
+//! recorded. Allocations through the [`Allocator`] are all reported at the
+//! same location in this crate, so use [`set_tag`] to tell them apart.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use core::ptr::addr_of_mut;
+//! use uefi::mem::memory_map::MemoryType;
+//! use uefi::mem::tracking::{self, Allocation};
+//! use uefi::{boot, println};
+//!
+//! static mut STORAGE: [Option<Allocation>; 256] = [const { None }; 256];
+//!
+//! # fn load_kernel() {}
+//! # fn example() {
+//! // SAFETY: `STORAGE` is not used anywhere else.
+//! tracking::enable(unsafe { &mut *addr_of_mut!(STORAGE) });
+//! {
+//!     let _tag = tracking::set_tag("kernel");
+//!     load_kernel();
+//! }
+//!
+//! tracking::freeze();
+//! tracking::for_each_live(|allocation| println!("{allocation}"));
+//! let memory_map = unsafe { boot::exit_boot_services(MemoryType::LOADER_DATA) };
+//! # }
+//! ```
+//!
+//! [`Allocator`]: crate::allocator::Allocator
+//! [`Status::ACCESS_DENIED`]: crate::Status::ACCESS_DENIED
+
+use crate::boot::{self, Tpl, PAGE_SIZE};
+use crate::data_types::PhysicalAddress;
+use crate::mem::memory_map::MemoryType;
+use crate::{Result, Status};
+use core::cell::UnsafeCell;
+use core::fmt::{self, Display, Formatter, Write};
+use core::ops::Range;
+use core::panic::Location;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// Kind of a tracked allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocationKind {
+    /// Allocated with [`boot::allocate_pool`].
+    Pool,
+    /// Allocated with [`boot::allocate_pages`].
+    Pages,
+}
+
+/// A live allocation recorded while tracking is enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    kind: AllocationKind,
+    addr: PhysicalAddress,
+    size: usize,
+    memory_type: MemoryType,
+    tag: Option<&'static str>,
+    location: &'static Location<'static>,
+}
+
+impl Allocation {
+    /// Whether the memory comes from the pool or is a range of pages.
+    #[must_use]
+    pub const fn kind(&self) -> AllocationKind {
+        self.kind
+    }
+
+    /// Address of the allocation.
+    #[must_use]
+    pub const fn addr(&self) -> PhysicalAddress {
+        self.addr
+    }
+
+    /// Size of the allocation in bytes.
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Memory type of the allocation.
+    #[must_use]
+    pub const fn memory_type(&self) -> MemoryType {
+        self.memory_type
+    }
+
+    /// Tag set with [`set_tag`] when the memory was allocated.
+    #[must_use]
+    pub const fn tag(&self) -> Option<&'static str> {
+        self.tag
+    }
+
+    /// Code that allocated the memory.
+    #[must_use]
+    pub const fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl Display for Allocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            AllocationKind::Pool => "pool",
+            AllocationKind::Pages => "pages",
+        };
+        write!(
+            f,
+            "{kind} {:#x}, {} bytes, {:?}",
+            self.addr, self.size, self.memory_type
+        )?;
+        if let Some(tag) = self.tag {
+            write!(f, ", tag {tag:?}")?;
+        }
+        write!(f, ", allocated at {}", self.location)
+    }
+}
+
+/// Guard returned by [`set_tag`], which restores the previous tag when
+/// dropped.
+#[derive(Debug)]
+#[must_use = "the tag is reset when the guard is dropped"]
+pub struct TagGuard {
+    previous: Option<&'static str>,
+}
+
+impl Drop for TagGuard {
+    fn drop(&mut self) {
+        with_state(|state| state.tag = self.previous);
+    }
+}
+
+/// The recorded allocations.
+struct State<'a> {
+    storage: Option<&'a mut [Option<Allocation>]>,
+    tag: Option<&'static str>,
+    dropped: usize,
+}
+
+impl<'a> State<'a> {
+    const fn new() -> Self {
+        Self {
+            storage: None,
+            tag: None,
+            dropped: 0,
+        }
+    }
+
+    fn insert(&mut self, allocation: Allocation) {
+        let slot = self
+            .storage
+            .as_mut()
+            .and_then(|storage| storage.iter_mut().find(|slot| slot.is_none()));
+        match slot {
+            Some(slot) => *slot = Some(allocation),
+            None => self.dropped += 1,
+        }
+    }
+
+    fn remove_pool(&mut self, addr: PhysicalAddress) -> Option<Allocation> {
+        self.storage
+            .as_mut()?
+            .iter_mut()
+            .find(|slot| slot.is_some_and(|a| a.kind == AllocationKind::Pool && a.addr == addr))?
+            .take()
+    }
+
+    /// Remove `range` from the recorded page allocations. Allocations that
+    /// only partially overlap the range are shrunk, or split in two if the
+    /// range is in their middle.
+    fn remove_pages(&mut self, range: Range<PhysicalAddress>) {
+        let Some(storage) = self.storage.as_mut() else {
+            return;
+        };
+        // Allocations don't overlap, so at most one contains the whole range.
+        let mut split_tail = None;
+        for slot in storage.iter_mut() {
+            let Some(allocation) = *slot else {
+                continue;
+            };
+            let end = allocation.addr + allocation.size as u64;
+            if allocation.kind != AllocationKind::Pages
+                || end <= range.start
+                || range.end <= allocation.addr
+            {
+                continue;
+            }
+            let head = (allocation.addr < range.start).then(|| Allocation {
+                size: (range.start - allocation.addr) as usize,
+                ..allocation
+            });
+            let tail = (range.end < end).then(|| Allocation {
+                addr: range.end,
+                size: (end - range.end) as usize,
+                ..allocation
+            });
+            *slot = match (head, tail) {
+                (Some(head), Some(tail)) => {
+                    split_tail = Some(tail);
+                    Some(head)
+                }
+                (head, tail) => head.or(tail),
+            };
+        }
+        if let Some(tail) = split_tail {
+            self.insert(tail);
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<Option<Allocation>> {
+        self.storage.as_ref()?.get(index).copied()
+    }
+}
+
+/// Cell for the [`State`], which must only be accessed with [`with_state`].
+struct StateCell(UnsafeCell<State<'static>>);
+
+// SAFETY: the state is only accessed at `Tpl::NOTIFY`, and allocations can
+// only be made on the bootstrap processor.
+unsafe impl Sync for StateCell {}
+
+static STATE: StateCell = StateCell(UnsafeCell::new(State::new()));
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static FROZEN: AtomicBool = AtomicBool::new(false);
+static DENIED: AtomicPtr<Location<'static>> = AtomicPtr::new(ptr::null_mut());
+
+/// Run `f` with exclusive access to the [`State`]. `f` must not allocate.
+fn with_state<R>(f: impl FnOnce(&mut State<'static>) -> R) -> R {
+    // Allocations are not allowed above `Tpl::NOTIFY`, so no allocation can
+    // interrupt `f` at this level.
+    let _guard = boot::raise_tpl(Tpl::NOTIFY);
+    // SAFETY: see above, and `f` does not call `with_state` again.
+    f(unsafe { &mut *STATE.0.get() })
+}
+
+/// Start recording allocations in `storage`.
+///
+/// Once `storage` is full, further allocations are only counted, see
+/// [`dropped_count`]. If tracking is already enabled, the allocations recorded
+/// so far are forgotten.
+pub fn enable(storage: &'static mut [Option<Allocation>]) {
+    storage.fill(None);
+    with_state(|state| {
+        state.storage = Some(storage);
+        state.dropped = 0;
+    });
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Stop recording allocations, and forget the recorded ones.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Release);
+    with_state(|state| state.storage = None);
+}
+
+/// Whether allocations are being recorded.
+#[must_use]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Tag the allocations made until the returned guard is dropped, for example
+/// with the name of the component that makes them.
+pub fn set_tag(tag: &'static str) -> TagGuard {
+    let previous = with_state(|state| state.tag.replace(tag));
+    TagGuard { previous }
+}
+
+/// Make all further allocations fail with [`Status::ACCESS_DENIED`], until
+/// [`unfreeze`] is called.
+///
+/// Freeing memory is still allowed. The first denied allocation is available
+/// with [`denied_allocation`].
+pub fn freeze() {
+    FROZEN.store(true, Ordering::Release);
+}
+
+/// Allow allocations again after [`freeze`].
+pub fn unfreeze() {
+    FROZEN.store(false, Ordering::Release);
+}
+
+/// Whether allocations are forbidden by [`freeze`].
+#[must_use]
+pub fn is_frozen() -> bool {
+    FROZEN.load(Ordering::Acquire)
+}
+
+/// Get the code that made the first allocation denied by [`freeze`].
+#[must_use]
+pub fn denied_allocation() -> Option<&'static Location<'static>> {
+    // SAFETY: the pointer is null or comes from a `&'static Location`.
+    unsafe { DENIED.load(Ordering::Acquire).as_ref() }
+}
+
+/// Get the number of allocations that were not recorded because the storage
+/// passed to [`enable`] was full.
+#[must_use]
+pub fn dropped_count() -> usize {
+    with_state(|state| state.dropped)
+}
+
+/// Call `f` for each recorded allocation that has not been freed yet.
+///
+/// `f` may allocate and free memory, but allocations it makes while the
+/// storage is iterated may or may not be passed to it.
+pub fn for_each_live(mut f: impl FnMut(&Allocation)) {
+    let mut index = 0;
+    while let Some(slot) = with_state(|state| state.get(index)) {
+        if let Some(allocation) = slot {
+            f(&allocation);
+        }
+        index += 1;
+    }
+}
+
+/// Write all recorded allocations that have not been freed yet to `writer`,
+/// followed by a summary.
+///
+/// This is meant to be called right before [`boot::exit_boot_services`], to
+/// find the code that allocated memory that is still in use. The output looks
+/// like this:
+///
+/// ```text
+/// pool 0x6b81018, 64 bytes, LOADER_DATA, tag "kernel", allocated at src/main.rs:29:17
+/// 1 live allocations, 64 bytes
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if writing to `writer` fails.
+pub fn report_leaks(writer: &mut dyn Write) -> fmt::Result {
+    let mut count = 0;
+    let mut size = 0;
+    let mut result = Ok(());
+    for_each_live(|allocation| {
+        count += 1;
+        size += allocation.size;
+        if result.is_ok() {
+            result = writeln!(writer, "{allocation}");
+        }
+    });
+    result?;
+
+    writeln!(writer, "{count} live allocations, {size} bytes")?;
+    let dropped = dropped_count();
+    if dropped != 0 {
+        writeln!(writer, "{dropped} allocations were not recorded")?;
+    }
+    if let Some(location) = denied_allocation() {
+        writeln!(writer, "an allocation was denied at {location}")?;
+    }
+    Ok(())
+}
+
+/// Fail with [`Status::ACCESS_DENIED`] if allocations are frozen.
+pub(crate) fn check_allowed(location: &'static Location<'static>) -> Result {
+    if is_frozen() {
+        let _ = DENIED.compare_exchange(
+            ptr::null_mut(),
+            ptr::from_ref(location).cast_mut(),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+        return Err(Status::ACCESS_DENIED.into());
+    }
+    Ok(())
+}
+
+/// Run `f`, allowing allocations even if they are frozen.
+pub(crate) fn allow_while_frozen<R>(f: impl FnOnce() -> R) -> R {
+    let frozen = FROZEN.swap(false, Ordering::AcqRel);
+    let result = f();
+    FROZEN.store(frozen, Ordering::Release);
+    result
+}
+
+/// Record an allocation if tracking is enabled.
+pub(crate) fn track(
+    kind: AllocationKind,
+    addr: PhysicalAddress,
+    size: usize,
+    memory_type: MemoryType,
+    location: &'static Location<'static>,
+) {
+    if is_enabled() {
+        with_state(|state| {
+            let tag = state.tag;
+            state.insert(Allocation {
+                kind,
+                addr,
+                size,
+                memory_type,
+                tag,
+                location,
+            });
+        });
+    }
+}
+
+/// Forget a pool allocation when it is freed, if it was recorded.
+pub(crate) fn untrack_pool(addr: PhysicalAddress) {
+    if is_enabled() {
+        with_state(|state| state.remove_pool(addr));
+    }
+}
+
+/// Forget `count` freed pages at `addr`. They may be only part of a recorded
+/// allocation, which then keeps the remaining pages.
+pub(crate) fn untrack_pages(addr: PhysicalAddress, count: usize) {
+    if is_enabled() {
+        let end = addr.saturating_add((count as u64).saturating_mul(PAGE_SIZE as u64));
+        with_state(|state| state.remove_pages(addr..end));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_state() {
+        let allocation = |addr| Allocation {
+            kind: AllocationKind::Pool,
+            addr,
+            size: 16,
+            memory_type: MemoryType::LOADER_DATA,
+            tag: Some("test"),
+            location: Location::caller(),
+        };
+
+        let mut storage = [None; 2];
+        let mut state = State::new();
+        state.insert(allocation(0x1000));
+        assert_eq!(state.dropped, 1);
+
+        state.storage = Some(&mut storage);
+        state.insert(allocation(0x1000));
+        state.insert(allocation(0x2000));
+        state.insert(allocation(0x3000));
+        assert_eq!(state.dropped, 2);
+
+        state.remove_pages(0x1000..0x2000);
+        assert_eq!(state.get(0), Some(Some(allocation(0x1000))));
+        assert_eq!(state.remove_pool(0x4000), None);
+        assert_eq!(state.remove_pool(0x1000), Some(allocation(0x1000)));
+        assert_eq!(state.get(0), Some(None));
+        assert_eq!(state.get(1), Some(Some(allocation(0x2000))));
+        assert_eq!(state.get(2), None);
+
+        state.insert(allocation(0x3000));
+        assert_eq!(state.get(0), Some(Some(allocation(0x3000))));
+    }
+
+    #[test]
+    fn test_remove_pages() {
+        let pages = |addr, count: usize| Allocation {
+            kind: AllocationKind::Pages,
+            addr,
+            size: count * PAGE_SIZE,
+            memory_type: MemoryType::LOADER_DATA,
+            tag: None,
+            location: Location::caller(),
+        };
+        let live = |state: &State| {
+            let mut live = [None; 4];
+            for (index, slot) in live.iter_mut().enumerate() {
+                *slot = state
+                    .get(index)
+                    .unwrap()
+                    .map(|a| (a.addr, a.size / PAGE_SIZE));
+            }
+            live
+        };
+
+        let mut storage = [None; 4];
+        let mut state = State::new();
+        state.storage = Some(&mut storage);
+        state.insert(pages(0x10_0000, 8));
+        state.insert(pages(0x20_0000, 2));
+
+        // Freeing the head or tail shrinks the allocation.
+        state.remove_pages(0x10_0000..0x10_2000);
+        state.remove_pages(0x10_7000..0x10_8000);
+        assert_eq!(
+            live(&state),
+            [Some((0x10_2000, 5)), Some((0x20_0000, 2)), None, None]
+        );
+
+        // Freeing the middle splits it.
+        state.remove_pages(0x10_3000..0x10_4000);
+        assert_eq!(
+            live(&state),
+            [
+                Some((0x10_2000, 1)),
+                Some((0x20_0000, 2)),
+                Some((0x10_4000, 3)),
+                None
+            ]
+        );
+
+        // A range can cover several allocations, and unrecorded pages.
+        state.remove_pages(0x10_0000..0x20_1000);
+        assert_eq!(live(&state), [None, Some((0x20_1000, 1)), None, None]);
+        state.remove_pages(0x20_1000..0x20_2000);
+        assert_eq!(live(&state), [None; 4]);
+    }
+
+    #[test]
+    fn test_display() {
+        let allocation = Allocation {
+            kind: AllocationKind::Pages,
+            addr: 0x20_0000,
+            size: 0x2000,
+            memory_type: MemoryType::BOOT_SERVICES_DATA,
+            tag: Some("gop"),
+            location: Location::caller(),
+        };
+        assert_eq!(
+            allocation.to_string(),
+            format!(
+                "pages 0x200000, 8192 bytes, BOOT_SERVICES_DATA, tag \"gop\", allocated at {}",
+                allocation.location
+            )
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! ATA protocols.
+//!
+//! The [`AtaPassThru`] protocol sends ATA commands to the devices attached to
+//! an ATA controller. This module also contains parsers for the data
+//! returned by the IDENTIFY DEVICE and SMART READ DATA commands, which
+//! [`AtaPassThru::identify_device`] and [`AtaPassThru::smart_data`] return
+//! directly.
+//!
+//! [`AtaPassThru`]: pass_thru::AtaPassThru
+//! [`AtaPassThru::identify_device`]: pass_thru::AtaPassThru::identify_device
+//! [`AtaPassThru::smart_data`]: pass_thru::AtaPassThru::smart_data
+
+use core::fmt::{self, Display, Formatter};
+
+pub mod pass_thru;
+
+/// Size of a sector of data returned by IDENTIFY DEVICE and SMART commands.
+pub const ATA_DATA_LEN: usize = 512;
+
+/// An ASCII string field of ATA data, such as a serial number.
+///
+/// ATA strings are stored with the bytes of each word swapped, and padded
+/// with spaces. This type holds the string in reading order, without the
+/// padding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct AtaString<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> AtaString<N> {
+    fn from_words(field: &[u8]) -> Self {
+        let mut bytes = [0; N];
+        for (dst, src) in bytes.chunks_exact_mut(2).zip(field.chunks_exact(2)) {
+            dst[0] = src[1];
+            dst[1] = src[0];
+        }
+        let len = bytes
+            .iter()
+            .rposition(|&b| b != b' ' && b != 0)
+            .map_or(0, |i| i + 1);
+        Self { bytes, len }
+    }
+
+    /// Get the bytes of the string.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> Display for AtaString<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for &b in self.as_bytes() {
+            let c = if b.is_ascii_graphic() || b == b' ' {
+                char::from(b)
+            } else {
+                char::REPLACEMENT_CHARACTER
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Data returned by the IDENTIFY DEVICE command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdentifyDevice {
+    data: [u8; ATA_DATA_LEN],
+}
+
+impl IdentifyDevice {
+    /// Create from the raw data returned by the device.
+    #[must_use]
+    pub const fn new(data: [u8; ATA_DATA_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; ATA_DATA_LEN] {
+        &self.data
+    }
+
+    /// Get word `index` of the data.
+    #[must_use]
+    pub const fn word(&self, index: usize) -> u16 {
+        u16::from_le_bytes([self.data[index * 2], self.data[index * 2 + 1]])
+    }
+
+    fn words(&self, range: core::ops::Range<usize>) -> &[u8] {
+        &self.data[range.start * 2..range.end * 2]
+    }
+
+    /// Serial number (words 10-19).
+    #[must_use]
+    pub fn serial_number(&self) -> AtaString<20> {
+        AtaString::from_words(self.words(10..20))
+    }
+
+    /// Firmware revision (words 23-26).
+    #[must_use]
+    pub fn firmware_revision(&self) -> AtaString<8> {
+        AtaString::from_words(self.words(23..27))
+    }
+
+    /// Model number (words 27-46).
+    #[must_use]
+    pub fn model_number(&self) -> AtaString<40> {
+        AtaString::from_words(self.words(27..47))
+    }
+
+    /// Check whether the 48-bit address feature set is supported.
+    #[must_use]
+    pub const fn supports_lba48(&self) -> bool {
+        self.word(83) & (1 << 10) != 0
+    }
+
+    /// Number of user-addressable sectors.
+    #[must_use]
+    pub fn sector_count(&self) -> u64 {
+        if self.supports_lba48() {
+            (0..4).fold(0, |count, i| {
+                count | u64::from(self.word(100 + i)) << (16 * i)
+            })
+        } else {
+            u64::from(self.word(60)) | u64::from(self.word(61)) << 16
+        }
+    }
+
+    /// Size of a logical sector in bytes.
+    #[must_use]
+    pub fn logical_sector_size(&self) -> u32 {
+        let word = self.word(106);
+        // Bit 14 set and bit 15 clear mark the word as valid, and bit 12
+        // indicates a logical sector size other than 256 words.
+        if word & 0xc000 == 0x4000 && word & (1 << 12) != 0 {
+            (u32::from(self.word(117)) | u32::from(self.word(118)) << 16) * 2
+        } else {
+            512
+        }
+    }
+
+    /// Check whether the SMART feature set is supported.
+    #[must_use]
+    pub const fn supports_smart(&self) -> bool {
+        self.word(82) & 1 != 0
+    }
+
+    /// Check whether the SMART feature set is enabled.
+    #[must_use]
+    pub const fn smart_enabled(&self) -> bool {
+        self.word(85) & 1 != 0
+    }
+
+    /// Nominal media rotation rate in rpm (word 217). Returns `Some(0)` for
+    /// non-rotating media such as SSDs, and `None` if not reported.
+    #[must_use]
+    pub const fn rotation_rate(&self) -> Option<u16> {
+        match self.word(217) {
+            0x0001 => Some(0),
+            rate @ 0x0401..=0xfffe => Some(rate),
+            _ => None,
+        }
+    }
+}
+
+/// A vendor-specific SMART attribute.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SmartAttribute {
+    /// Attribute ID, such as 5 for the reallocated sector count.
+    pub id: u8,
+
+    /// Status flags. Bit 0 marks attributes that predict failure.
+    pub flags: u16,
+
+    /// Current normalized value.
+    pub current: u8,
+
+    /// Worst normalized value seen so far.
+    pub worst: u8,
+
+    /// 48-bit raw value, whose meaning depends on the attribute.
+    pub raw: u64,
+}
+
+/// Data returned by the SMART READ DATA command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SmartData {
+    data: [u8; ATA_DATA_LEN],
+}
+
+impl SmartData {
+    /// Maximum number of attributes.
+    const MAX_ATTRIBUTES: usize = 30;
+    /// Size of an attribute entry in bytes.
+    const ATTRIBUTE_LEN: usize = 12;
+
+    /// Create from the raw data returned by the device.
+    #[must_use]
+    pub const fn new(data: [u8; ATA_DATA_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; ATA_DATA_LEN] {
+        &self.data
+    }
+
+    /// Revision of the data structure.
+    #[must_use]
+    pub const fn revision(&self) -> u16 {
+        u16::from_le_bytes([self.data[0], self.data[1]])
+    }
+
+    /// Check whether the checksum in the last byte is valid.
+    #[must_use]
+    pub fn is_checksum_valid(&self) -> bool {
+        self.data.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) == 0
+    }
+
+    /// Status of the offline data collection.
+    #[must_use]
+    pub const fn offline_data_collection_status(&self) -> u8 {
+        self.data[362]
+    }
+
+    /// Status of the last or current self-test. The upper four bits hold the
+    /// result, where zero means that the test completed without error.
+    #[must_use]
+    pub const fn self_test_execution_status(&self) -> u8 {
+        self.data[363]
+    }
+
+    /// Get an iterator over the attributes.
+    pub fn attributes(&self) -> impl Iterator<Item = SmartAttribute> + '_ {
+        self.data[2..2 + Self::MAX_ATTRIBUTES * Self::ATTRIBUTE_LEN]
+            .chunks_exact(Self::ATTRIBUTE_LEN)
+            .filter(|entry| entry[0] != 0)
+            .map(|entry| SmartAttribute {
+                id: entry[0],
+                flags: u16::from_le_bytes([entry[1], entry[2]]),
+                current: entry[3],
+                worst: entry[4],
+                raw: entry[5..11]
+                    .iter()
+                    .rev()
+                    .fold(0, |raw, &b| raw << 8 | u64::from(b)),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Store `s` in ATA string format at `word`.
+    fn put_string(data: &mut [u8], word: usize, len: usize, s: &[u8]) {
+        let field = &mut data[word * 2..word * 2 + len];
+        field.fill(b' ');
+        field[..s.len()].copy_from_slice(s);
+        for pair in field.chunks_exact_mut(2) {
+            pair.swap(0, 1);
+        }
+    }
+
+    #[test]
+    fn test_identify_device() {
+        let mut data = [0; ATA_DATA_LEN];
+        put_string(&mut data, 10, 20, b"QM00001");
+        put_string(&mut data, 23, 8, b"2.5+");
+        put_string(&mut data, 27, 40, b"QEMU HARDDISK");
+        // SMART supported and enabled.
+        data[82 * 2] = 1;
+        data[85 * 2] = 1;
+        // LBA48 with 2^32 sectors.
+        data[83 * 2 + 1] = 1 << 2;
+        data[102 * 2] = 1;
+        // Non-rotating media.
+        data[217 * 2] = 1;
+
+        let identify = IdentifyDevice::new(data);
+        assert_eq!(identify.serial_number().as_bytes(), b"QM00001");
+        assert_eq!(identify.firmware_revision().as_bytes(), b"2.5+");
+        assert_eq!(identify.model_number().as_bytes(), b"QEMU HARDDISK");
+        assert!(identify.supports_lba48());
+        assert_eq!(identify.sector_count(), 1 << 32);
+        assert_eq!(identify.logical_sector_size(), 512);
+        assert!(identify.supports_smart());
+        assert!(identify.smart_enabled());
+        assert_eq!(identify.rotation_rate(), Some(0));
+    }
+
+    #[test]
+    fn test_smart_data() {
+        let mut data = [0; ATA_DATA_LEN];
+        data[0] = 1;
+        data[2..14].copy_from_slice(&[5, 0x33, 0, 100, 99, 0x10, 0x20, 0, 0, 0, 0x01, 0]);
+        data[14..26].copy_from_slice(&[194, 0x22, 0, 64, 50, 36, 0, 0, 0, 0, 0, 0]);
+        data[511] = data.iter().fold(0u8, |sum, &b| sum.wrapping_sub(b));
+
+        let smart = SmartData::new(data);
+        assert_eq!(smart.revision(), 1);
+        assert!(smart.is_checksum_valid());
+        assert!(smart.attributes().eq([
+            SmartAttribute {
+                id: 5,
+                flags: 0x33,
+                current: 100,
+                worst: 99,
+                raw: 0x0100_0000_2010,
+            },
+            SmartAttribute {
+                id: 194,
+                flags: 0x22,
+                current: 64,
+                worst: 50,
+                raw: 36,
+            },
+        ]));
+    }
+}
@@ -0,0 +1,527 @@
+//! ATA Pass Thru protocol.
+//!
+//! [`AtaPassThru`] is installed on ATA controller handles, and can send any
+//! ATA command to the attached devices. For example, to report the health of
+//! all disks:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::ata::pass_thru::AtaPassThru;
+//! # fn test() -> uefi::Result {
+//!
+//! for handle in boot::find_handles::<AtaPassThru>()? {
+//!     let mut ata = boot::open_protocol_exclusive::<AtaPassThru>(handle)?;
+//!     let devices: Vec<_> = ata.devices().collect();
+//!     for device in devices {
+//!         let identify = ata.identify_device(device)?;
+//!         if identify.supports_smart() && identify.smart_enabled() {
+//!             let failing = ata.smart_threshold_exceeded(device)?;
+//!             log::info!("{}: failing: {failing}", identify.model_number());
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::device_path::DevicePath;
+use crate::proto::unsafe_protocol;
+use crate::{Result, StatusExt};
+use uefi_raw::protocol::ata::AtaPassThruProtocol;
+
+#[cfg(feature = "alloc")]
+use {
+    super::{IdentifyDevice, SmartData, ATA_DATA_LEN},
+    crate::boot,
+    crate::duration,
+    crate::mem::AlignedBuffer,
+    crate::proto::device_path::FfiDevicePath,
+    crate::Status,
+    alloc::boxed::Box,
+    core::mem,
+    core::ptr::{self, NonNull},
+    core::time::Duration,
+    uefi_raw::protocol::ata::{AtaCommandBlock, AtaPassThruCommandPacket},
+};
+
+pub use uefi_raw::protocol::ata::{
+    AtaPassThruAttributes, AtaPassThruCommandProtocol, AtaPassThruLength, AtaPassThruMode,
+    AtaStatusBlock,
+};
+
+/// Port multiplier port of devices that are attached directly to a port.
+pub const NO_PORT_MULTIPLIER: u16 = 0xffff;
+
+/// Port number that starts port enumeration.
+const PORT_START: u16 = 0xffff;
+
+/// Opcodes and parameters of the commands sent by the typed helpers.
+#[cfg(feature = "alloc")]
+mod command {
+    use core::time::Duration;
+
+    pub const IDENTIFY_DEVICE: u8 = 0xec;
+    pub const SMART: u8 = 0xb0;
+    pub const SMART_READ_DATA: u16 = 0xd0;
+    pub const SMART_RETURN_STATUS: u16 = 0xda;
+    /// Value of the LBA registers required by SMART commands.
+    pub const SMART_LBA: u64 = 0xc2_4f00;
+    pub const TIMEOUT: Duration = Duration::from_secs(5);
+}
+
+/// A device attached to an ATA controller.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AtaDevice {
+    /// Port of the controller that the device is attached to.
+    pub port: u16,
+
+    /// Port of the port multiplier that the device is attached to, or
+    /// [`NO_PORT_MULTIPLIER`].
+    pub port_multiplier_port: u16,
+}
+
+impl AtaDevice {
+    /// Get the value of the device register for commands sent to the device.
+    /// On IDE channels, the port multiplier port selects the primary or
+    /// secondary device.
+    #[must_use]
+    pub const fn device_head(&self) -> u8 {
+        let select = if self.port_multiplier_port == NO_PORT_MULTIPLIER {
+            0
+        } else {
+            (self.port_multiplier_port as u8 & 1) << 4
+        };
+        0xe0 | select
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+enum AtaData<'buf> {
+    None,
+    In(&'buf mut [u8]),
+    Out(&'buf [u8]),
+}
+
+/// An ATA command, submitted with [`AtaPassThru::execute_command`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct AtaRequest<'buf> {
+    protocol: AtaPassThruCommandProtocol,
+    length: AtaPassThruLength,
+    acb: AtaCommandBlock,
+    data: AtaData<'buf>,
+    timeout: Duration,
+}
+
+#[cfg(feature = "alloc")]
+impl<'buf> AtaRequest<'buf> {
+    /// Create a request for `command`, transferred with `protocol`.
+    #[must_use]
+    pub const fn new(protocol: AtaPassThruCommandProtocol, command: u8) -> Self {
+        Self {
+            protocol,
+            length: AtaPassThruLength::NO_DATA_TRANSFER,
+            acb: AtaCommandBlock {
+                reserved1: [0; 2],
+                ata_command: command,
+                ata_features: 0,
+                ata_sector_number: 0,
+                ata_cylinder_low: 0,
+                ata_cylinder_high: 0,
+                ata_device_head: 0,
+                ata_sector_number_exp: 0,
+                ata_cylinder_low_exp: 0,
+                ata_cylinder_high_exp: 0,
+                ata_features_exp: 0,
+                ata_sector_count: 0,
+                ata_sector_count_exp: 0,
+                reserved2: [0; 6],
+            },
+            data: AtaData::None,
+            timeout: Duration::ZERO,
+        }
+    }
+
+    /// Set the features register. The upper byte is only used by 48-bit
+    /// commands.
+    #[must_use]
+    pub const fn with_features(mut self, features: u16) -> Self {
+        let [low, high] = features.to_le_bytes();
+        self.acb.ata_features = low;
+        self.acb.ata_features_exp = high;
+        self
+    }
+
+    /// Set the sector count register. The upper byte is only used by 48-bit
+    /// commands.
+    #[must_use]
+    pub const fn with_sector_count(mut self, count: u16) -> Self {
+        let [low, high] = count.to_le_bytes();
+        self.acb.ata_sector_count = low;
+        self.acb.ata_sector_count_exp = high;
+        self
+    }
+
+    /// Set the LBA registers from the low 48 bits of `lba`. The upper 24
+    /// bits are only used by 48-bit commands.
+    #[must_use]
+    pub const fn with_lba(mut self, lba: u64) -> Self {
+        let bytes = lba.to_le_bytes();
+        self.acb.ata_sector_number = bytes[0];
+        self.acb.ata_cylinder_low = bytes[1];
+        self.acb.ata_cylinder_high = bytes[2];
+        self.acb.ata_sector_number_exp = bytes[3];
+        self.acb.ata_cylinder_low_exp = bytes[4];
+        self.acb.ata_cylinder_high_exp = bytes[5];
+        self
+    }
+
+    /// Set the device register, see [`AtaDevice::device_head`].
+    #[must_use]
+    pub const fn with_device_head(mut self, device_head: u8) -> Self {
+        self.acb.ata_device_head = device_head;
+        self
+    }
+
+    /// Set how the length of the transfer is specified in the command
+    /// block. The default is [`AtaPassThruLength::NO_DATA_TRANSFER`].
+    #[must_use]
+    pub const fn with_length(mut self, length: AtaPassThruLength) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Set the buffer that data is read into. It must be aligned to
+    /// [`AtaPassThru::io_align`].
+    #[must_use]
+    pub fn with_read_buffer(mut self, buffer: &'buf mut [u8]) -> Self {
+        self.data = AtaData::In(buffer);
+        self
+    }
+
+    /// Set the buffer that data is written from. It must be aligned to
+    /// [`AtaPassThru::io_align`].
+    #[must_use]
+    pub const fn with_write_buffer(mut self, buffer: &'buf [u8]) -> Self {
+        self.data = AtaData::Out(buffer);
+        self
+    }
+
+    /// Set the timeout of the command. The default of [`Duration::ZERO`]
+    /// waits indefinitely.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Result of a completed [`AtaRequest`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AtaResponse {
+    /// Register values after the command completed.
+    pub status_block: AtaStatusBlock,
+
+    /// Number of bytes read into the read buffer.
+    pub in_transfer_length: usize,
+
+    /// Number of bytes written from the write buffer.
+    pub out_transfer_length: usize,
+}
+
+/// ATA Pass Thru protocol.
+///
+/// Corresponds to the `EFI_ATA_PASS_THRU_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(AtaPassThruProtocol::GUID)]
+pub struct AtaPassThru(AtaPassThruProtocol);
+
+impl AtaPassThru {
+    /// Get the mode of the controller.
+    #[must_use]
+    pub const fn mode(&self) -> AtaPassThruMode {
+        unsafe { *self.0.mode }
+    }
+
+    /// Get the alignment required for data buffers. Values of 0 and 1 mean
+    /// that buffers don't need to be aligned.
+    #[must_use]
+    pub const fn io_align(&self) -> u32 {
+        self.mode().io_align
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes aligned to
+    /// [`io_align`](Self::io_align).
+    #[cfg(feature = "alloc")]
+    pub fn alloc_io_buffer(&self, len: usize) -> Result<AlignedBuffer> {
+        AlignedBuffer::from_size_align(len, self.io_align() as usize)
+            .map_err(|_| Status::BAD_BUFFER_SIZE.into())
+    }
+
+    /// Get an iterator over the ports of the controller that have a device
+    /// attached.
+    #[must_use]
+    pub const fn ports(&self) -> AtaPortIter<'_> {
+        AtaPortIter {
+            pass_thru: self,
+            port: PORT_START,
+        }
+    }
+
+    /// Get an iterator over the devices attached to the controller.
+    #[must_use]
+    pub const fn devices(&self) -> AtaDeviceIter<'_> {
+        AtaDeviceIter {
+            ports: self.ports(),
+            device: None,
+        }
+    }
+
+    /// Get the device path node of `device`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the device does not exist.
+    #[cfg(feature = "alloc")]
+    pub fn device_path(&self, device: AtaDevice) -> Result<Box<DevicePath>> {
+        let mut device_path = ptr::null_mut();
+        unsafe {
+            (self.0.build_device_path)(
+                &self.0,
+                device.port,
+                device.port_multiplier_port,
+                &mut device_path,
+            )
+        }
+        .to_result()?;
+        let device_path = NonNull::new(device_path).ok_or(Status::NOT_FOUND)?;
+        let boxed =
+            unsafe { DevicePath::from_ffi_ptr(device_path.as_ptr().cast::<FfiDevicePath>()) }
+                .to_boxed();
+        let _ = unsafe { boot::free_pool(device_path.cast()) };
+        Ok(boxed)
+    }
+
+    /// Get the device with the device path node `device_path`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `device_path` is not an ATA device node.
+    /// * [`Status::NOT_FOUND`]: the device does not exist.
+    pub fn device(&self, device_path: &DevicePath) -> Result<AtaDevice> {
+        let mut port = 0;
+        let mut port_multiplier_port = 0;
+        unsafe {
+            (self.0.get_device)(
+                &self.0,
+                device_path.as_ffi_ptr().cast(),
+                &mut port,
+                &mut port_multiplier_port,
+            )
+        }
+        .to_result_with_val(|| AtaDevice {
+            port,
+            port_multiplier_port,
+        })
+    }
+
+    /// Reset `port` and the devices attached to it.
+    pub fn reset_port(&mut self, port: u16) -> Result {
+        unsafe { (self.0.reset_port)(&mut self.0, port) }.to_result()
+    }
+
+    /// Reset `device`.
+    pub fn reset_device(&mut self, device: AtaDevice) -> Result {
+        unsafe { (self.0.reset_device)(&mut self.0, device.port, device.port_multiplier_port) }
+            .to_result()
+    }
+
+    /// Send `request` to `device`, and wait for it to complete.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the buffer is not aligned to
+    ///   [`io_align`](Self::io_align), or the command is invalid.
+    /// * [`Status::BAD_BUFFER_SIZE`]: the buffer is too large for the
+    ///   controller.
+    /// * [`Status::NOT_READY`]: the command could not be sent because the
+    ///   controller is busy.
+    /// * [`Status::DEVICE_ERROR`]: the command failed.
+    /// * [`Status::TIMEOUT`]: the command did not complete in time.
+    #[cfg(feature = "alloc")]
+    pub fn execute_command(
+        &mut self,
+        device: AtaDevice,
+        request: AtaRequest<'_>,
+    ) -> Result<AtaResponse> {
+        let align = self.io_align().max(1) as usize;
+        let (in_buffer, in_len, out_buffer, out_len) = match request.data {
+            AtaData::None => (ptr::null_mut(), 0, ptr::null(), 0),
+            AtaData::In(buffer) => (buffer.as_mut_ptr(), buffer.len(), ptr::null(), 0),
+            AtaData::Out(buffer) => (ptr::null_mut(), 0, buffer.as_ptr(), buffer.len()),
+        };
+        if in_buffer as usize % align != 0 || out_buffer as usize % align != 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let in_len = u32::try_from(in_len).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+        let out_len = u32::try_from(out_len).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+
+        // The status block must be aligned like the data buffers.
+        let mut asb = self.alloc_io_buffer(mem::size_of::<AtaStatusBlock>())?;
+        let mut packet = AtaPassThruCommandPacket {
+            asb: asb.as_mut_ptr().cast(),
+            acb: &request.acb,
+            timeout: duration::as_timer_units(request.timeout),
+            in_data_buffer: in_buffer.cast(),
+            out_data_buffer: out_buffer.cast(),
+            in_transfer_length: in_len,
+            out_transfer_length: out_len,
+            protocol: request.protocol,
+            length: request.length,
+        };
+        unsafe {
+            (self.0.pass_thru)(
+                &mut self.0,
+                device.port,
+                device.port_multiplier_port,
+                &mut packet,
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| AtaResponse {
+            status_block: unsafe { asb.as_ptr().cast::<AtaStatusBlock>().read_unaligned() },
+            in_transfer_length: packet.in_transfer_length as usize,
+            out_transfer_length: packet.out_transfer_length as usize,
+        })
+    }
+
+    /// Send a PIO data-in `request` that reads one sector of data.
+    #[cfg(feature = "alloc")]
+    fn read_sector(
+        &mut self,
+        device: AtaDevice,
+        request: AtaRequest<'_>,
+    ) -> Result<[u8; ATA_DATA_LEN]> {
+        let mut buffer = self.alloc_io_buffer(ATA_DATA_LEN)?;
+        let request = request
+            .with_sector_count(1)
+            .with_length(AtaPassThruLength::BYTES | AtaPassThruLength::SECTOR_COUNT)
+            .with_device_head(device.device_head())
+            .with_read_buffer(buffer.as_mut_slice())
+            .with_timeout(command::TIMEOUT);
+        self.execute_command(device, request)?;
+
+        let mut data = [0; ATA_DATA_LEN];
+        data.copy_from_slice(buffer.as_slice());
+        Ok(data)
+    }
+
+    /// Get the IDENTIFY DEVICE data of `device`.
+    #[cfg(feature = "alloc")]
+    pub fn identify_device(&mut self, device: AtaDevice) -> Result<IdentifyDevice> {
+        let request = AtaRequest::new(
+            AtaPassThruCommandProtocol::PIO_DATA_IN,
+            command::IDENTIFY_DEVICE,
+        );
+        self.read_sector(device, request).map(IdentifyDevice::new)
+    }
+
+    /// Get the SMART attributes and self-test status of `device`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: the device does not support SMART, or
+    ///   SMART is disabled.
+    #[cfg(feature = "alloc")]
+    pub fn smart_data(&mut self, device: AtaDevice) -> Result<SmartData> {
+        let request = AtaRequest::new(AtaPassThruCommandProtocol::PIO_DATA_IN, command::SMART)
+            .with_features(command::SMART_READ_DATA)
+            .with_lba(command::SMART_LBA);
+        self.read_sector(device, request).map(SmartData::new)
+    }
+
+    /// Check whether `device` reports that a SMART threshold has been
+    /// exceeded, which means that it is likely to fail soon.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: the device does not support SMART, or
+    ///   SMART is disabled.
+    /// * [`Status::UNSUPPORTED`]: the controller did not return the status.
+    #[cfg(feature = "alloc")]
+    pub fn smart_threshold_exceeded(&mut self, device: AtaDevice) -> Result<bool> {
+        let request = AtaRequest::new(AtaPassThruCommandProtocol::ATA_NON_DATA, command::SMART)
+            .with_features(command::SMART_RETURN_STATUS)
+            .with_lba(command::SMART_LBA)
+            .with_device_head(device.device_head())
+            .with_timeout(command::TIMEOUT);
+        let asb = self.execute_command(device, request)?.status_block;
+        match (asb.ata_cylinder_low, asb.ata_cylinder_high) {
+            (0x4f, 0xc2) => Ok(false),
+            (0xf4, 0x2c) => Ok(true),
+            _ => Err(Status::UNSUPPORTED.into()),
+        }
+    }
+}
+
+/// Iterator over the ports of an [`AtaPassThru`] controller, returned by
+/// [`AtaPassThru::ports`].
+#[derive(Debug)]
+pub struct AtaPortIter<'a> {
+    pass_thru: &'a AtaPassThru,
+    port: u16,
+}
+
+impl Iterator for AtaPortIter<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        let proto = &self.pass_thru.0;
+        unsafe { (proto.get_next_port)(proto, &mut self.port) }
+            .to_result()
+            .ok()?;
+        Some(self.port)
+    }
+}
+
+/// Iterator over the devices of an [`AtaPassThru`] controller, returned by
+/// [`AtaPassThru::devices`].
+#[derive(Debug)]
+pub struct AtaDeviceIter<'a> {
+    ports: AtaPortIter<'a>,
+    /// Last device returned on the current port, or `None` if the next port
+    /// must be enumerated.
+    device: Option<AtaDevice>,
+}
+
+impl Iterator for AtaDeviceIter<'_> {
+    type Item = AtaDevice;
+
+    fn next(&mut self) -> Option<AtaDevice> {
+        let proto = &self.ports.pass_thru.0;
+        loop {
+            let (port, mut port_multiplier_port) = match self.device {
+                Some(device) => (device.port, device.port_multiplier_port),
+                None => (self.ports.next()?, NO_PORT_MULTIPLIER),
+            };
+            let start = port_multiplier_port;
+            let status = unsafe { (proto.get_next_device)(proto, port, &mut port_multiplier_port) };
+            if status.is_success() && (start == NO_PORT_MULTIPLIER || port_multiplier_port != start)
+            {
+                let device = AtaDevice {
+                    port,
+                    port_multiplier_port,
+                };
+                // A device attached directly to the port is reported with the
+                // same value that starts the enumeration, so it must be the
+                // only device on the port.
+                self.device = (port_multiplier_port != NO_PORT_MULTIPLIER).then_some(device);
+                return Some(device);
+            }
+            self.device = None;
+        }
+    }
+}
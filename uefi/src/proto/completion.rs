@@ -0,0 +1,283 @@
+//! Helpers for waiting on the completion events of asynchronous tokens.
+//!
+//! Several protocols (network, disk I/O 2, ...) take a token containing an
+//! event that is signaled when the operation completes. The helpers here are
+//! used to implement blocking variants of those operations.
+
+use crate::boot::{self, EventType, TimerTrigger, Tpl};
+use crate::{duration, Event, Result, Status};
+use core::time::Duration;
+
+#[cfg(feature = "alloc")]
+use {
+    crate::StatusExt,
+    alloc::boxed::Box,
+    alloc::vec::Vec,
+    core::task::{Context, Poll},
+};
+
+/// An event without a notification function, suitable for use as the
+/// completion event of a token. The event is closed on drop.
+#[derive(Debug)]
+pub(crate) struct CompletionEvent(Event);
+
+impl CompletionEvent {
+    /// Create a new unsignaled event.
+    pub(crate) fn new() -> Result<Self> {
+        // Safety: the event has no notification function, so there is no
+        // callback that could outlive boot services.
+        let event = unsafe { boot::create_event(EventType::empty(), Tpl::CALLBACK, None, None) }?;
+        Ok(Self(event))
+    }
+
+    /// Get the raw event pointer for placing in a token.
+    pub(crate) const fn as_raw(&self) -> uefi_raw::Event {
+        self.0.as_ptr()
+    }
+
+    /// Get the event, such as for passing to [`boot::wait_for_event`].
+    pub(crate) const fn event(&self) -> &Event {
+        &self.0
+    }
+
+    /// Check whether the event has been signaled.
+    pub(crate) fn is_signaled(&self) -> Result<bool> {
+        // Safety: the clone does not outlive `self`.
+        boot::check_event(unsafe { self.0.unsafe_clone() })
+    }
+
+    /// Wait for the event to be signaled, calling `poll` between checks.
+    ///
+    /// `poll` is typically the protocol's `Poll` function, which improves the
+    /// latency of the operation on some implementations.
+    ///
+    /// If `timeout` elapses before the event is signaled, an error with
+    /// [`Status::TIMEOUT`] is returned. In that case the caller is responsible
+    /// for cancelling the operation before the token goes out of scope.
+    pub(crate) fn wait(&self, timeout: Option<Duration>, mut poll: impl FnMut()) -> Result {
+        let timer = match timeout {
+            Some(timeout) => Some(Timer::new(timeout)?),
+            None => None,
+        };
+
+        loop {
+            if self.is_signaled()? {
+                return Ok(());
+            }
+            if let Some(timer) = &timer {
+                if timer.is_expired()? {
+                    return Err(Status::TIMEOUT.into());
+                }
+            }
+            poll();
+        }
+    }
+
+    /// Wait for the event like [`wait`], but if waiting fails, for example
+    /// because `timeout` elapsed, call `cancel` to cancel the token, and
+    /// return the error.
+    ///
+    /// If `cancel` succeeds, the cancellation is signaled, which is waited
+    /// for. If it fails, the token is not queued anymore, and no further
+    /// signal is coming. If the token completed in the meantime, `Ok` is
+    /// returned. Either way, the implementation no longer references the
+    /// token afterwards, so it can go out of scope.
+    ///
+    /// [`wait`]: Self::wait
+    pub(crate) fn wait_or_cancel(
+        &self,
+        timeout: Option<Duration>,
+        mut poll: impl FnMut(),
+        cancel: impl FnOnce() -> Status,
+    ) -> Result {
+        let err = match self.wait(timeout, &mut poll) {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        };
+        if cancel().is_success() {
+            self.wait(None, poll)?;
+        } else if self.is_signaled()? {
+            return Ok(());
+        }
+        Err(err)
+    }
+}
+
+impl Drop for CompletionEvent {
+    fn drop(&mut self) {
+        // Safety: the event is not used after this point.
+        let _ = boot::close_event(unsafe { self.0.unsafe_clone() });
+    }
+}
+
+/// One-shot timer event, closed on drop.
+#[derive(Debug)]
+pub(crate) struct Timer(CompletionEvent);
+
+impl Timer {
+    /// Create a timer that expires after `timeout`.
+    pub(crate) fn new(timeout: Duration) -> Result<Self> {
+        // Safety: the event has no notification function.
+        let event = unsafe { boot::create_event(EventType::TIMER, Tpl::CALLBACK, None, None) }?;
+        let event = CompletionEvent(event);
+        let trigger = TimerTrigger::Relative(duration::as_timer_units(timeout));
+        boot::set_timer(&event.0, trigger)?;
+        Ok(Self(event))
+    }
+
+    /// Check whether the timer has expired.
+    pub(crate) fn is_expired(&self) -> Result<bool> {
+        self.0.is_signaled()
+    }
+
+    /// Get the timer event, such as for passing to [`boot::wait_for_event`].
+    pub(crate) const fn event(&self) -> &Event {
+        self.0.event()
+    }
+}
+
+/// Token of the non-blocking I/O functions of the disk I/O 2 and block I/O 2
+/// protocols, which share this layout.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+#[repr(C)]
+pub(crate) struct IoToken {
+    event: uefi_raw::Event,
+    transaction_status: Status,
+}
+
+/// State of a [`PendingIo`] that the firmware accesses until the operation
+/// completes.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct PendingIoState {
+    token: IoToken,
+    event: CompletionEvent,
+    buffer: Vec<u8>,
+    complete: bool,
+    /// Registration of `event` with the running executor, dropped along
+    /// with it.
+    #[cfg(feature = "async")]
+    registration: crate::executor::Registration,
+}
+
+/// A non-blocking I/O operation that owns its token and buffer.
+///
+/// If the operation is dropped before it completes, the token and buffer are
+/// leaked, since the firmware may still write to them.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub(crate) struct PendingIo(Option<Box<PendingIoState>>);
+
+#[cfg(feature = "alloc")]
+impl PendingIo {
+    /// Start an operation by calling `start` with the token and buffer.
+    pub(crate) fn start(
+        buffer: Vec<u8>,
+        start: impl FnOnce(*mut IoToken, &mut Vec<u8>) -> Status,
+    ) -> Result<Self> {
+        let event = CompletionEvent::new()?;
+        let mut state = Box::new(PendingIoState {
+            token: IoToken {
+                event: event.as_raw(),
+                transaction_status: Status::NOT_READY,
+            },
+            event,
+            buffer,
+            complete: false,
+            #[cfg(feature = "async")]
+            registration: Default::default(),
+        });
+        let state_mut = &mut *state;
+        // If starting fails, nothing is pending and the state can be freed.
+        start(&mut state_mut.token, &mut state_mut.buffer).to_result()?;
+        Ok(Self(Some(state)))
+    }
+
+    fn state(&self) -> &PendingIoState {
+        // The state is only taken out when the operation is consumed.
+        self.0.as_ref().expect("operation already completed")
+    }
+
+    fn state_mut(&mut self) -> &mut PendingIoState {
+        self.0.as_mut().expect("operation already completed")
+    }
+
+    /// Check whether the operation has completed, without blocking.
+    pub(crate) fn is_complete(&mut self) -> Result<bool> {
+        let state = self.state_mut();
+        if !state.complete {
+            state.complete = state.event.is_signaled()?;
+        }
+        Ok(state.complete)
+    }
+
+    /// Get the result of a completed operation.
+    fn take_result(&mut self) -> Result<Vec<u8>> {
+        let state = self.0.take().expect("operation already completed");
+        let PendingIoState { token, buffer, .. } = *state;
+        token.transaction_status.to_result_with_val(|| buffer)
+    }
+
+    /// Wait for the operation to complete, and get the buffer back.
+    pub(crate) fn wait(mut self) -> Result<Vec<u8>> {
+        while !self.is_complete()? {}
+        self.take_result()
+    }
+
+    /// Wait for the first of `operations` to complete, and get its index.
+    pub(crate) fn wait_any<T>(
+        operations: &mut [T],
+        pending: impl Fn(&mut T) -> &mut Self,
+    ) -> Result<usize> {
+        let mut events = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.iter_mut().enumerate() {
+            let state = pending(operation).state();
+            if state.complete {
+                return Ok(index);
+            }
+            // Safety: the clones do not outlive the operations.
+            events.push(unsafe { state.event.event().unsafe_clone() });
+        }
+        let index =
+            boot::wait_for_event(&mut events).map_err(|err| err.to_err_without_payload())?;
+        // Waiting clears the signal, so remember that the operation is done.
+        pending(&mut operations[index]).state_mut().complete = true;
+        Ok(index)
+    }
+
+    /// Poll the operation as a future. If the operation is still in
+    /// progress, its event is registered with the running [`executor`], or
+    /// the waker is woken right away so that other executors keep polling.
+    ///
+    /// [`executor`]: crate::executor
+    pub(crate) fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Result<Vec<u8>>> {
+        match self.is_complete() {
+            Ok(true) => Poll::Ready(self.take_result()),
+            Ok(false) => {
+                #[cfg(feature = "async")]
+                {
+                    let state = self.state_mut();
+                    state.registration = crate::executor::register(state.event.event(), cx.waker());
+                }
+                #[cfg(not(feature = "async"))]
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for PendingIo {
+    fn drop(&mut self) {
+        if let Some(state) = self.0.take() {
+            if !state.complete && !state.event.is_signaled().unwrap_or(false) {
+                // The firmware may still access the token and buffer, and
+                // signal the event.
+                Box::leak(state);
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Absolute pointer device access, such as touchscreens.
+
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use uefi_raw::protocol::console::{AbsolutePointerProtocol, AbsolutePointerState as RawState};
+
+pub use uefi_raw::protocol::console::{AbsolutePointerMode, AbsolutePointerModeAttributes};
+
+/// Bit of [`RawState::active_buttons`] set while the pointer touches the
+/// device.
+const TOUCH_ACTIVE: u32 = 0x1;
+
+/// Bit of [`RawState::active_buttons`] set while the alternate button is
+/// pressed.
+const ALT_ACTIVE: u32 = 0x2;
+
+/// Provides access to a pointer device that reports absolute coordinates,
+/// such as a touchscreen or a tablet.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(AbsolutePointerProtocol::GUID)]
+pub struct AbsolutePointer(AbsolutePointerProtocol);
+
+impl AbsolutePointer {
+    /// Resets the pointer device hardware.
+    ///
+    /// The `extended_verification` parameter is used to request that UEFI
+    /// performs an extended check and reset of the input device.
+    ///
+    /// # Errors
+    ///
+    /// - `DeviceError` if the device is malfunctioning and cannot be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, u8::from(extended_verification)) }.to_result()
+    }
+
+    /// Retrieves the pointer device's current state, if a state change occurred
+    /// since the last time this function was called.
+    ///
+    /// Use `wait_for_input_event()` with the [`boot::wait_for_event`]
+    /// interface in order to wait for input from the pointer device.
+    ///
+    /// # Errors
+    /// - `DeviceError` if there was an issue with the pointer device.
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    pub fn read_state(&mut self) -> Result<Option<AbsolutePointerState>> {
+        let mut state = RawState::default();
+
+        match unsafe { (self.0.get_state)(&self.0, &mut state) } {
+            Status::NOT_READY => Ok(None),
+            other => other.to_result_with_val(|| Some(AbsolutePointerState(state))),
+        }
+    }
+
+    /// Event to be used with [`boot::wait_for_event`] in order to wait
+    /// for input from the pointer device
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    #[must_use]
+    pub fn wait_for_input_event(&self) -> Option<Event> {
+        unsafe { Event::from_ptr(self.0.wait_for_input) }
+    }
+
+    /// Returns a reference to the pointer device information.
+    #[must_use]
+    pub const fn mode(&self) -> &AbsolutePointerMode {
+        unsafe { &*self.0.mode }
+    }
+
+    /// Scale the position of `state` to a screen of `resolution` pixels,
+    /// such as the resolution of the current graphics mode.
+    ///
+    /// The position is clamped to the range of the device. Returns `None` if
+    /// the device does not report the X or Y axis, or if the resolution is
+    /// empty.
+    #[must_use]
+    pub const fn to_screen(
+        &self,
+        state: &AbsolutePointerState,
+        resolution: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        state.to_screen(self.mode(), resolution)
+    }
+}
+
+/// The state of an absolute pointer device.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct AbsolutePointerState(RawState);
+
+impl AbsolutePointerState {
+    /// The position on the X and Y axes, in the range given by the
+    /// [`AbsolutePointerMode`].
+    #[must_use]
+    pub const fn position(&self) -> (u64, u64) {
+        (self.0.current_x, self.0.current_y)
+    }
+
+    /// The position on the Z axis, or the pressure if the device has
+    /// [`AbsolutePointerModeAttributes::SUPPORTS_PRESSURE_AS_Z`].
+    #[must_use]
+    pub const fn z(&self) -> u64 {
+        self.0.current_z
+    }
+
+    /// Whether the pointer touches the device.
+    #[must_use]
+    pub const fn touch_active(&self) -> bool {
+        self.0.active_buttons & TOUCH_ACTIVE != 0
+    }
+
+    /// Whether the alternate button is pressed, if the device has
+    /// [`AbsolutePointerModeAttributes::SUPPORTS_ALT_ACTIVE`].
+    #[must_use]
+    pub const fn alt_active(&self) -> bool {
+        self.0.active_buttons & ALT_ACTIVE != 0
+    }
+
+    /// Scale the position to a screen of `resolution` pixels, using the range
+    /// of the device described by `mode`.
+    ///
+    /// See [`AbsolutePointer::to_screen`].
+    #[must_use]
+    pub const fn to_screen(
+        &self,
+        mode: &AbsolutePointerMode,
+        resolution: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let Some(x) = scale(
+            self.0.current_x,
+            mode.absolute_min_x,
+            mode.absolute_max_x,
+            resolution.0,
+        ) else {
+            return None;
+        };
+        let Some(y) = scale(
+            self.0.current_y,
+            mode.absolute_min_y,
+            mode.absolute_max_y,
+            resolution.1,
+        ) else {
+            return None;
+        };
+        Some((x, y))
+    }
+}
+
+impl From<RawState> for AbsolutePointerState {
+    fn from(state: RawState) -> Self {
+        Self(state)
+    }
+}
+
+impl From<AbsolutePointerState> for RawState {
+    fn from(state: AbsolutePointerState) -> Self {
+        state.0
+    }
+}
+
+/// Scale `value` from `min..=max` to `0..size`, clamping out-of-range values.
+const fn scale(value: u64, min: u64, max: u64, size: usize) -> Option<usize> {
+    if max <= min || size == 0 {
+        return None;
+    }
+    let value = if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    };
+    let scaled = (value - min) as u128 * (size as u128 - 1) / (max - min) as u128;
+    Some(scaled as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_screen() {
+        let mode = AbsolutePointerMode {
+            absolute_max_x: 0x7fff,
+            absolute_max_y: 0x7fff,
+            ..Default::default()
+        };
+        let state = |x, y| {
+            AbsolutePointerState::from(RawState {
+                current_x: x,
+                current_y: y,
+                ..Default::default()
+            })
+        };
+        assert_eq!(state(0, 0).to_screen(&mode, (1024, 768)), Some((0, 0)));
+        assert_eq!(
+            state(0x7fff, 0x7fff).to_screen(&mode, (1024, 768)),
+            Some((1023, 767))
+        );
+        assert_eq!(
+            state(0x4000, 0x2000).to_screen(&mode, (1024, 768)),
+            Some((511, 191))
+        );
+        assert_eq!(
+            state(u64::MAX, 0).to_screen(&mode, (1024, 768)),
+            Some((1023, 0))
+        );
+        assert_eq!(state(0, 0).to_screen(&mode, (0, 768)), None);
+
+        // The device has a minimum, and no Z axis.
+        let mode = AbsolutePointerMode {
+            absolute_min_x: 100,
+            absolute_max_x: 200,
+            absolute_min_y: 100,
+            absolute_max_y: 200,
+            ..Default::default()
+        };
+        assert_eq!(state(50, 150).to_screen(&mode, (101, 11)), Some((0, 5)));
+
+        // The Y axis is not supported.
+        let mode = AbsolutePointerMode {
+            absolute_max_x: 100,
+            ..Default::default()
+        };
+        assert_eq!(state(50, 0).to_screen(&mode, (1024, 768)), None);
+    }
+
+    #[test]
+    fn test_buttons() {
+        let state = AbsolutePointerState::from(RawState {
+            active_buttons: TOUCH_ACTIVE,
+            ..Default::default()
+        });
+        assert!(state.touch_active());
+        assert!(!state.alt_active());
+    }
+}
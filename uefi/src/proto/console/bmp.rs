@@ -0,0 +1,467 @@
+//! Decoding of BMP images, for example to draw a boot logo.
+//!
+//! [`Bitmap::from_bmp`] decodes a BMP file into a buffer of [`BltPixel`]s,
+//! which [`Bitmap::draw`] copies to the screen of a [`GraphicsOutput`] with
+//! a single blit:
+//!
+//! ```no_run
+//! use uefi::boot::ScopedProtocol;
+//! use uefi::proto::console::bmp::Bitmap;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//! # fn test(mut gop: ScopedProtocol<GraphicsOutput>, logo: &[u8]) -> uefi::Result {
+//! let logo = Bitmap::from_bmp(logo).expect("invalid logo");
+//! let (width, height) = gop.current_mode_info().resolution();
+//! let x = width.saturating_sub(logo.width()) / 2;
+//! let y = height.saturating_sub(logo.height()) / 2;
+//! logo.draw(&mut gop, (x, y))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Uncompressed images with 1, 4, 8, 16, 24 or 32 bits per pixel are
+//! supported, which covers the output of common image editors. Run-length
+//! encoded images are not supported. The alpha channel of 32-bit images is
+//! ignored, as the screen has no transparency.
+
+use super::gop::{BltOp, BltPixel, BltRegion, GraphicsOutput};
+use super::surface::Surface;
+use crate::Result;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Errors that can happen when decoding a BMP image.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BmpError {
+    /// The data is not a BMP image, or its header is invalid.
+    InvalidHeader,
+    /// The image uses a BMP feature that is not supported, such as
+    /// compression.
+    Unsupported,
+    /// The data ends before the end of the image.
+    Truncated,
+    /// The image is too large to be decoded.
+    TooLarge,
+}
+
+impl Display for BmpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::InvalidHeader => "invalid BMP header",
+            Self::Unsupported => "unsupported BMP format",
+            Self::Truncated => "BMP data is truncated",
+            Self::TooLarge => "BMP image is too large",
+        };
+        f.write_str(s)
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl core::error::Error for BmpError {}
+
+/// An image as a buffer of [`BltPixel`]s, stored row by row from the top.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<BltPixel>,
+}
+
+impl Bitmap {
+    /// Create a bitmap from its pixels, stored row by row from the top.
+    /// Returns `None` if `pixels` doesn't have `width * height` elements.
+    #[must_use]
+    pub fn new(width: usize, height: usize, pixels: Vec<BltPixel>) -> Option<Self> {
+        (width.checked_mul(height) == Some(pixels.len())).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Decode a BMP file.
+    ///
+    /// # Errors
+    ///
+    /// See [`BmpError`].
+    pub fn from_bmp(data: &[u8]) -> core::result::Result<Self, BmpError> {
+        let header = BmpHeader::parse(data)?;
+        let width = header.width;
+        let height = header.height;
+        let len = width.checked_mul(height).ok_or(BmpError::TooLarge)?;
+        let stride = width
+            .checked_mul(usize::from(header.bits_per_pixel))
+            .and_then(|bits| bits.checked_add(31))
+            .ok_or(BmpError::TooLarge)?
+            / 32
+            * 4;
+
+        // Check the size before allocating for the pixels.
+        let end = stride
+            .checked_mul(height)
+            .and_then(|len| len.checked_add(header.data_offset))
+            .ok_or(BmpError::TooLarge)?;
+        if end > data.len() {
+            return Err(BmpError::Truncated);
+        }
+
+        let mut pixels = Vec::new();
+        pixels
+            .try_reserve_exact(len)
+            .map_err(|_| BmpError::TooLarge)?;
+        for y in 0..height {
+            // Rows are stored from the bottom, unless the height is negative.
+            let row = if header.top_down { y } else { height - 1 - y };
+            let start = header.data_offset + row * stride;
+            let row = &data[start..start + stride];
+            pixels.extend((0..width).map(|x| header.pixel(row, x)));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Get the width in pixels.
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the height in pixels.
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the pixels, row by row from the top.
+    #[must_use]
+    pub fn pixels(&self) -> &[BltPixel] {
+        &self.pixels
+    }
+
+    /// Get the pixels back.
+    #[must_use]
+    pub fn into_pixels(self) -> Vec<BltPixel> {
+        self.pixels
+    }
+
+    /// Copy the bitmap to the screen of `gop`, with its top-left corner at
+    /// `dest`. Parts that are outside of the screen are not drawn.
+    ///
+    /// # Errors
+    ///
+    /// See [`GraphicsOutput::blt`].
+    pub fn draw(&self, gop: &mut GraphicsOutput, dest: (usize, usize)) -> Result {
+        let (screen_width, screen_height) = gop.current_mode_info().resolution();
+        let width = self.width.min(screen_width.saturating_sub(dest.0));
+        let height = self.height.min(screen_height.saturating_sub(dest.1));
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        gop.blt(BltOp::BufferToVideo {
+            buffer: &self.pixels,
+            src: BltRegion::SubRectangle {
+                coords: (0, 0),
+                px_stride: self.width,
+            },
+            dest,
+            dims: (width, height),
+        })
+    }
+
+    /// Copy the bitmap into `surface`, with its top-left corner at `dest`.
+    /// Parts that are outside of the surface are not drawn.
+    pub fn draw_to_surface(&self, surface: &mut Surface, dest: (usize, usize)) {
+        surface.copy_from(dest, (self.width, self.height), &self.pixels);
+    }
+}
+
+/// `BI_RGB`: uncompressed.
+const COMPRESSION_RGB: u32 = 0;
+/// `BI_BITFIELDS`: uncompressed, with color masks.
+const COMPRESSION_BITFIELDS: u32 = 3;
+/// `BI_ALPHABITFIELDS`: uncompressed, with color and alpha masks.
+const COMPRESSION_ALPHABITFIELDS: u32 = 6;
+
+/// The parts of the headers of a BMP file that are needed for decoding.
+#[derive(Debug)]
+struct BmpHeader<'a> {
+    width: usize,
+    height: usize,
+    top_down: bool,
+    bits_per_pixel: u16,
+    data_offset: usize,
+    /// Palette entries in BGR order, with padding if `palette_entry_len`
+    /// is 4.
+    palette: &'a [u8],
+    palette_entry_len: usize,
+    masks: [u32; 3],
+}
+
+impl<'a> BmpHeader<'a> {
+    fn parse(data: &'a [u8]) -> core::result::Result<Self, BmpError> {
+        if data.get(..2) != Some(b"BM") {
+            return Err(BmpError::InvalidHeader);
+        }
+        let data_offset = read_u32(data, 10)? as usize;
+        let info_len = read_u32(data, 14)? as usize;
+
+        let (width, height, bits_per_pixel, compression, colors_used, palette_entry_len) =
+            if info_len == 12 {
+                // BITMAPCOREHEADER, from OS/2.
+                let width = i32::from(read_u16(data, 18)?);
+                let height = i32::from(read_u16(data, 20)?);
+                (width, height, read_u16(data, 24)?, COMPRESSION_RGB, 0, 3)
+            } else if info_len >= 40 {
+                // BITMAPINFOHEADER and its successors.
+                let width = read_u32(data, 18)? as i32;
+                let height = read_u32(data, 22)? as i32;
+                let bits_per_pixel = read_u16(data, 28)?;
+                let compression = read_u32(data, 30)?;
+                let colors_used = read_u32(data, 46)? as usize;
+                (width, height, bits_per_pixel, compression, colors_used, 4)
+            } else {
+                return Err(BmpError::InvalidHeader);
+            };
+        if width <= 0 || height == 0 {
+            return Err(BmpError::InvalidHeader);
+        }
+
+        let masks = match (compression, bits_per_pixel) {
+            (COMPRESSION_RGB, 16) => [0x7c00, 0x03e0, 0x001f],
+            (COMPRESSION_RGB, 24 | 32) => [0xff_0000, 0x00_ff00, 0x00_00ff],
+            (COMPRESSION_RGB, 1 | 4 | 8) => [0; 3],
+            (COMPRESSION_BITFIELDS | COMPRESSION_ALPHABITFIELDS, 16 | 32) => {
+                // The masks directly follow the BITMAPINFOHEADER, where the
+                // later headers have the same fields.
+                [
+                    read_u32(data, 54)?,
+                    read_u32(data, 58)?,
+                    read_u32(data, 62)?,
+                ]
+            }
+            (COMPRESSION_RGB | COMPRESSION_BITFIELDS | COMPRESSION_ALPHABITFIELDS, _) => {
+                return Err(BmpError::InvalidHeader)
+            }
+            _ => return Err(BmpError::Unsupported),
+        };
+
+        let palette = if bits_per_pixel <= 8 {
+            let colors = match colors_used {
+                0 => 1 << bits_per_pixel,
+                colors => colors.min(1 << bits_per_pixel),
+            };
+            let start = 14 + info_len;
+            data.get(start..start + colors * palette_entry_len)
+                .ok_or(BmpError::Truncated)?
+        } else {
+            &[]
+        };
+
+        Ok(Self {
+            width: width as usize,
+            height: height.unsigned_abs() as usize,
+            top_down: height < 0,
+            bits_per_pixel,
+            data_offset,
+            palette,
+            palette_entry_len,
+            masks,
+        })
+    }
+
+    /// Decode pixel `x` of a row.
+    fn pixel(&self, row: &[u8], x: usize) -> BltPixel {
+        match self.bits_per_pixel {
+            1 | 4 | 8 => {
+                let bits = usize::from(self.bits_per_pixel);
+                let byte = row[x * bits / 8];
+                // The leftmost pixel is in the most significant bits.
+                let shift = 8 - bits - (x * bits % 8);
+                let index = usize::from(byte >> shift) & ((1 << bits) - 1);
+                // Indices outside of the palette are black.
+                let start = index * self.palette_entry_len;
+                match self.palette.get(start..start + 3) {
+                    Some(&[blue, green, red]) => BltPixel::new(red, green, blue),
+                    _ => BltPixel::new(0, 0, 0),
+                }
+            }
+            24 => BltPixel::new(row[x * 3 + 2], row[x * 3 + 1], row[x * 3]),
+            16 => {
+                let value = u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]);
+                self.masked_pixel(u32::from(value))
+            }
+            _ => {
+                let bytes = [row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]];
+                self.masked_pixel(u32::from_le_bytes(bytes))
+            }
+        }
+    }
+
+    fn masked_pixel(&self, value: u32) -> BltPixel {
+        let [red, green, blue] = self.masks.map(|mask| channel(value, mask));
+        BltPixel::new(red, green, blue)
+    }
+}
+
+/// Extract the channel `mask` of a pixel, scaled to 8 bits.
+const fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let max = (mask >> mask.trailing_zeros()) as u64;
+    let value = ((value & mask) >> mask.trailing_zeros()) as u64;
+    (value * 255 / max) as u8
+}
+
+fn read_u16(data: &[u8], offset: usize) -> core::result::Result<u16, BmpError> {
+    match data.get(offset..offset + 2) {
+        Some(&[a, b]) => Ok(u16::from_le_bytes([a, b])),
+        _ => Err(BmpError::Truncated),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> core::result::Result<u32, BmpError> {
+    match data.get(offset..offset + 4) {
+        Some(&[a, b, c, d]) => Ok(u32::from_le_bytes([a, b, c, d])),
+        _ => Err(BmpError::Truncated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const BLACK: BltPixel = BltPixel::new(0, 0, 0);
+    const WHITE: BltPixel = BltPixel::new(0xff, 0xff, 0xff);
+    const RED: BltPixel = BltPixel::new(0xff, 0, 0);
+    const BLUE: BltPixel = BltPixel::new(0, 0, 0xff);
+
+    /// Build a BMP file with a BITMAPINFOHEADER.
+    fn bmp(
+        width: i32,
+        height: i32,
+        bits_per_pixel: u16,
+        compression: u32,
+        extra: &[u8],
+        rows: &[u8],
+    ) -> Vec<u8> {
+        let data_offset = 14 + 40 + extra.len() as u32;
+        let mut data = Vec::new();
+        data.extend(b"BM");
+        data.extend((data_offset + rows.len() as u32).to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(data_offset.to_le_bytes());
+        data.extend(40u32.to_le_bytes());
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(bits_per_pixel.to_le_bytes());
+        data.extend(compression.to_le_bytes());
+        data.extend([0; 20]);
+        data.extend(extra);
+        data.extend(rows);
+        data
+    }
+
+    #[test]
+    fn test_24_bit() {
+        // Bottom-up, with rows padded to four bytes.
+        let rows = [
+            0, 0, 0xff, 0xff, 0xff, 0xff, 0, 0, //
+            0xff, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let bitmap = Bitmap::from_bmp(&bmp(2, 2, 24, 0, &[], &rows)).unwrap();
+        assert_eq!((bitmap.width(), bitmap.height()), (2, 2));
+        assert_eq!(bitmap.pixels(), [BLUE, BLACK, RED, WHITE]);
+
+        // Top-down.
+        let bitmap = Bitmap::from_bmp(&bmp(2, -2, 24, 0, &[], &rows)).unwrap();
+        assert_eq!(bitmap.pixels(), [RED, WHITE, BLUE, BLACK]);
+    }
+
+    #[test]
+    fn test_palette() {
+        let palette = [0, 0, 0xff, 0, 0xff, 0xff, 0xff, 0];
+        let rows = [0b1000_0000, 0, 0, 0, 0b0100_0000, 0, 0, 0];
+        let bitmap = Bitmap::from_bmp(&bmp(2, 2, 1, 0, &palette, &rows)).unwrap();
+        assert_eq!(bitmap.pixels(), [RED, WHITE, WHITE, RED]);
+
+        // With two colors out of 16.
+        let rows = [0x10, 0, 0, 0, 0x01, 0, 0, 0];
+        let mut data = bmp(2, 2, 4, 0, &palette, &rows);
+        data[46..50].copy_from_slice(&2u32.to_le_bytes());
+        let bitmap = Bitmap::from_bmp(&data).unwrap();
+        assert_eq!(bitmap.pixels(), [RED, WHITE, WHITE, RED]);
+
+        // Without the number of colors, the palette has 256 entries.
+        let palette = [0, 0, 0, 0, 0xff, 0, 0, 0];
+        let rows = [1, 200, 0, 0];
+        let mut data = bmp(2, 1, 8, 0, &palette, &rows);
+        assert_eq!(Bitmap::from_bmp(&data), Err(BmpError::Truncated));
+
+        // Indices beyond the palette are black.
+        data[46..50].copy_from_slice(&2u32.to_le_bytes());
+        let bitmap = Bitmap::from_bmp(&data).unwrap();
+        assert_eq!(bitmap.pixels(), [BLUE, BLACK]);
+    }
+
+    #[test]
+    fn test_bitfields() {
+        // RGB565 and 32-bit with alpha, with explicit masks.
+        let masks: Vec<u8> = [0xf800u32, 0x07e0, 0x001f]
+            .iter()
+            .flat_map(|mask| mask.to_le_bytes())
+            .collect();
+        let rows = [0x00, 0xf8, 0x1f, 0x00];
+        let bitmap = Bitmap::from_bmp(&bmp(2, 1, 16, 3, &masks, &rows)).unwrap();
+        assert_eq!(bitmap.pixels(), [RED, BLUE]);
+
+        let rows = [0xff, 0xff, 0xff, 0x80, 0xff, 0, 0, 0xff];
+        let bitmap = Bitmap::from_bmp(&bmp(2, 1, 32, 0, &[], &rows)).unwrap();
+        assert_eq!(bitmap.pixels(), [WHITE, BLUE]);
+
+        // 16-bit without masks is 5-5-5.
+        let rows = [0x00, 0x7c, 0xff, 0x7f];
+        let bitmap = Bitmap::from_bmp(&bmp(2, 1, 16, 0, &[], &rows)).unwrap();
+        assert_eq!(bitmap.pixels(), [RED, WHITE]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let rows = [0; 8];
+        assert_eq!(
+            Bitmap::from_bmp(b"PNG, not BMP"),
+            Err(BmpError::InvalidHeader)
+        );
+        assert_eq!(
+            Bitmap::from_bmp(&bmp(0, 2, 24, 0, &[], &rows)),
+            Err(BmpError::InvalidHeader)
+        );
+        // RLE8
+        assert_eq!(
+            Bitmap::from_bmp(&bmp(2, 1, 8, 1, &[], &rows)),
+            Err(BmpError::Unsupported)
+        );
+        assert_eq!(
+            Bitmap::from_bmp(&bmp(2, 2, 24, 0, &[], &rows[..7])),
+            Err(BmpError::Truncated)
+        );
+        assert_eq!(
+            Bitmap::from_bmp(&bmp(0x10000, 0x10000, 32, 0, &[], &rows)),
+            Err(BmpError::Truncated)
+        );
+        assert_eq!(
+            Bitmap::from_bmp(&bmp(2, 2, 24, 0, &[], &rows)[..30]),
+            Err(BmpError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(Bitmap::new(2, 2, vec![BLACK; 4]).is_some());
+        assert!(Bitmap::new(2, 2, vec![BLACK; 3]).is_none());
+    }
+}
@@ -0,0 +1,347 @@
+//! EDID protocols, which provide the capabilities of the display connected
+//! to a video output.
+//!
+//! [`EdidDiscovered`] and [`EdidActive`] are installed on the same handles
+//! as the [`GraphicsOutput`] protocol of the output, if the firmware can read
+//! the EDID of the display. [`Edid`] parses the data, for example to get
+//! the name and the preferred resolution of the display. The preferred
+//! resolution is usually the native resolution, which can be passed to
+//! [`ModeCriteria::set_native_resolution`] to select a graphics mode that
+//! looks sharp:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::console::edid::EdidActive;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//! # fn test() -> uefi::Result {
+//! let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+//! let edid = boot::open_protocol_exclusive::<EdidActive>(handle)?;
+//! if let Some(edid) = edid.parse() {
+//!     let name = edid.display_name().unwrap_or("unknown display");
+//!     let resolution = edid.preferred_timing().map(|timing| timing.resolution());
+//!     log::info!("{name}: {resolution:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`GraphicsOutput`]: super::gop::GraphicsOutput
+//! [`ModeCriteria::set_native_resolution`]: super::gop::ModeCriteria::set_native_resolution
+
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, StatusExt};
+use core::{ptr, slice, str};
+use uefi_raw::protocol::console::{
+    EdidActiveProtocol, EdidDiscoveredProtocol, EdidOverrideProtocol,
+};
+
+pub use uefi_raw::protocol::console::EdidOverrideAttributes;
+
+/// The EDID that is read from the display.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidDiscoveredProtocol::GUID)]
+pub struct EdidDiscovered(EdidDiscoveredProtocol);
+
+impl EdidDiscovered {
+    /// Get the EDID data, which is empty if the display did not provide it.
+    #[must_use]
+    pub fn edid(&self) -> &[u8] {
+        // SAFETY: The firmware provides a valid buffer of the given size.
+        unsafe { edid_slice(self.0.edid, self.0.size_of_edid as usize) }
+    }
+
+    /// Parse the EDID data, see [`Edid::new`].
+    #[must_use]
+    pub fn parse(&self) -> Option<Edid<'_>> {
+        Edid::new(self.edid())
+    }
+
+    /// Get the preferred resolution of the display, see
+    /// [`preferred_resolution`].
+    #[must_use]
+    pub fn preferred_resolution(&self) -> Option<(usize, usize)> {
+        preferred_resolution(self.edid())
+    }
+}
+
+/// The EDID of the display that is in use. This is the [`EdidDiscovered`]
+/// data, unless the platform overrides it with [`EdidOverride`].
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidActiveProtocol::GUID)]
+pub struct EdidActive(EdidActiveProtocol);
+
+impl EdidActive {
+    /// Get the EDID data, which is empty if there is no active display.
+    #[must_use]
+    pub fn edid(&self) -> &[u8] {
+        // SAFETY: The firmware provides a valid buffer of the given size.
+        unsafe { edid_slice(self.0.edid, self.0.size_of_edid as usize) }
+    }
+
+    /// Parse the EDID data, see [`Edid::new`].
+    #[must_use]
+    pub fn parse(&self) -> Option<Edid<'_>> {
+        Edid::new(self.edid())
+    }
+
+    /// Get the preferred resolution of the display, see
+    /// [`preferred_resolution`].
+    #[must_use]
+    pub fn preferred_resolution(&self) -> Option<(usize, usize)> {
+        preferred_resolution(self.edid())
+    }
+}
+
+/// Provided by the platform to override the EDID of displays, for example
+/// for panels with a broken EDID. Graphics drivers use it when they install
+/// [`EdidActive`].
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EdidOverrideProtocol::GUID)]
+pub struct EdidOverride(EdidOverrideProtocol);
+
+impl EdidOverride {
+    /// Get the EDID override for the video output with the handle `child`.
+    ///
+    /// The EDID is empty if the platform only sets attributes. If the
+    /// attributes contain [`EdidOverrideAttributes::DONT_OVERRIDE`], the
+    /// discovered EDID is used instead of the returned one.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::UNSUPPORTED`] if there is no override for `child`.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    pub fn get_edid(&self, child: Handle) -> Result<(EdidOverrideAttributes, &[u8])> {
+        let mut attributes = EdidOverrideAttributes::empty();
+        let mut size = 0;
+        let mut edid = ptr::null();
+        unsafe {
+            (self.0.get_edid)(
+                &self.0,
+                &child.as_ptr(),
+                &mut attributes,
+                &mut size,
+                &mut edid,
+            )
+        }
+        // SAFETY: The platform provides a valid buffer of the given size,
+        // which it owns.
+        .to_result_with_val(|| (attributes, unsafe { edid_slice(edid, size) }))
+    }
+}
+
+unsafe fn edid_slice<'a>(edid: *const u8, size: usize) -> &'a [u8] {
+    if edid.is_null() || size == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(edid, size) }
+    }
+}
+
+/// Header of every EDID base block.
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// Size of the EDID base block, which may be followed by extension blocks.
+const BLOCK_SIZE: usize = 128;
+
+/// Offset of the four 18-byte descriptors in the base block.
+const DESCRIPTORS: usize = 54;
+
+/// Tag of the display descriptor with the product name.
+const TAG_PRODUCT_NAME: u8 = 0xfc;
+
+/// The base block of an EDID, with accessors for the fields that boot
+/// loaders need. Extension blocks are ignored.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Edid<'a>(&'a [u8; BLOCK_SIZE]);
+
+impl<'a> Edid<'a> {
+    /// Parse the base block at the start of `data`.
+    ///
+    /// Returns `None` if the data is shorter than a base block, or if the
+    /// header or the checksum are invalid.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let block: &[u8; BLOCK_SIZE] = data.get(..BLOCK_SIZE)?.try_into().ok()?;
+        let sum = block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b));
+        (block[..8] == EDID_HEADER && sum == 0).then_some(Self(block))
+    }
+
+    /// Get the three-letter PNP ID of the manufacturer, for example `"DEL"`.
+    #[must_use]
+    pub fn manufacturer_id(&self) -> [u8; 3] {
+        let id = u16::from_be_bytes([self.0[8], self.0[9]]);
+        [10, 5, 0].map(|shift| b'A' - 1 + ((id >> shift) & 0x1f) as u8)
+    }
+
+    /// Get the product code that the manufacturer assigned.
+    #[must_use]
+    pub const fn product_code(&self) -> u16 {
+        u16::from_le_bytes([self.0[10], self.0[11]])
+    }
+
+    /// Get the EDID version as `(version, revision)`, for example `(1, 4)`.
+    #[must_use]
+    pub const fn version(&self) -> (u8, u8) {
+        (self.0[18], self.0[19])
+    }
+
+    /// Get the preferred timing of the display, which is the native
+    /// resolution of flat panels.
+    ///
+    /// This is the first detailed timing descriptor, or `None` if the first
+    /// descriptor is not a timing.
+    #[must_use]
+    pub fn preferred_timing(&self) -> Option<DetailedTiming> {
+        DetailedTiming::new(self.descriptor(0))
+    }
+
+    /// Get the product name of the display, with trailing padding removed.
+    ///
+    /// Returns `None` if the EDID has no product name descriptor, or the
+    /// name is not ASCII.
+    #[must_use]
+    pub fn display_name(&self) -> Option<&'a str> {
+        let descriptor = (0..4)
+            .map(|index| self.descriptor(index))
+            .find(|descriptor| descriptor[..3] == [0; 3] && descriptor[3] == TAG_PRODUCT_NAME)?;
+        let name = &descriptor[5..];
+        let end = name.iter().position(|b| *b == b'\n').unwrap_or(name.len());
+        let name = str::from_utf8(&name[..end]).ok()?.trim_end_matches(' ');
+        name.is_ascii().then_some(name)
+    }
+
+    fn descriptor(&self, index: usize) -> &'a [u8; 18] {
+        let start = DESCRIPTORS + index * 18;
+        self.0[start..start + 18].try_into().unwrap()
+    }
+}
+
+/// A detailed timing descriptor of an [`Edid`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DetailedTiming {
+    pixel_clock: u32,
+    horizontal_active: u16,
+    horizontal_blanking: u16,
+    vertical_active: u16,
+    vertical_blanking: u16,
+}
+
+impl DetailedTiming {
+    /// Parse a timing descriptor, which has a non-zero pixel clock.
+    fn new(descriptor: &[u8; 18]) -> Option<Self> {
+        let pixel_clock = u16::from_le_bytes([descriptor[0], descriptor[1]]);
+        if pixel_clock == 0 {
+            return None;
+        }
+        let low_high = |low: u8, high: u8| u16::from(low) | u16::from(high & 0x0f) << 8;
+        Some(Self {
+            pixel_clock: u32::from(pixel_clock) * 10,
+            horizontal_active: low_high(descriptor[2], descriptor[4] >> 4),
+            horizontal_blanking: low_high(descriptor[3], descriptor[4]),
+            vertical_active: low_high(descriptor[5], descriptor[7] >> 4),
+            vertical_blanking: low_high(descriptor[6], descriptor[7]),
+        })
+    }
+
+    /// Get the visible `(width, height)` in pixels.
+    #[must_use]
+    pub const fn resolution(&self) -> (usize, usize) {
+        (
+            self.horizontal_active as usize,
+            self.vertical_active as usize,
+        )
+    }
+
+    /// Get the pixel clock in kHz.
+    #[must_use]
+    pub const fn pixel_clock_khz(&self) -> u32 {
+        self.pixel_clock
+    }
+
+    /// Get the refresh rate in Hz, rounded to the nearest integer.
+    #[must_use]
+    pub const fn refresh_rate(&self) -> u32 {
+        let total = (self.horizontal_active as u32 + self.horizontal_blanking as u32)
+            * (self.vertical_active as u32 + self.vertical_blanking as u32);
+        if total == 0 {
+            0
+        } else {
+            ((self.pixel_clock as u64 * 1000 + total as u64 / 2) / total as u64) as u32
+        }
+    }
+}
+
+/// Get the `(width, height)` of the [preferred timing] of an EDID, which is
+/// the native resolution of flat panels.
+///
+/// Returns `None` if the data is not a valid EDID or does not have a
+/// preferred timing.
+///
+/// [preferred timing]: Edid::preferred_timing
+#[must_use]
+pub fn preferred_resolution(edid: &[u8]) -> Option<(usize, usize)> {
+    let resolution = Edid::new(edid)?.preferred_timing()?.resolution();
+    (resolution.0 != 0 && resolution.1 != 0).then_some(resolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a base block of a 1920x1080 display at 60 Hz called "TEST".
+    fn edid() -> [u8; 128] {
+        let mut edid = [0; 128];
+        edid[..8].copy_from_slice(&EDID_HEADER);
+        // "DEL", product 0x1234, version 1.4.
+        edid[8..12].copy_from_slice(&[0x10, 0xac, 0x34, 0x12]);
+        edid[18..20].copy_from_slice(&[1, 4]);
+        edid[54..72].copy_from_slice(&[
+            0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40, 0x58, 0x2c, 0x45, 0x00, 0x0f, 0x48,
+            0x42, 0x00, 0x00, 0x1e,
+        ]);
+        edid[90..108].copy_from_slice(b"\0\0\0\xfc\0TEST\n        ");
+        fix_checksum(&mut edid);
+        edid
+    }
+
+    fn fix_checksum(edid: &mut [u8; 128]) {
+        edid[127] = 0;
+        edid[127] = 0u8.wrapping_sub(edid.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)));
+    }
+
+    #[test]
+    fn test_edid() {
+        let data = edid();
+        let edid = Edid::new(&data).unwrap();
+        assert_eq!(&edid.manufacturer_id(), b"DEL");
+        assert_eq!(edid.product_code(), 0x1234);
+        assert_eq!(edid.version(), (1, 4));
+        assert_eq!(edid.display_name(), Some("TEST"));
+
+        let timing = edid.preferred_timing().unwrap();
+        assert_eq!(timing.resolution(), (1920, 1080));
+        assert_eq!(timing.pixel_clock_khz(), 148_500);
+        assert_eq!(timing.refresh_rate(), 60);
+        assert_eq!(preferred_resolution(&data), Some((1920, 1080)));
+
+        // Too short, or an invalid checksum.
+        assert_eq!(Edid::new(&data[..127]), None);
+        let mut invalid = data;
+        invalid[127] ^= 1;
+        assert_eq!(Edid::new(&invalid), None);
+
+        // A display descriptor instead of a timing, and no name.
+        let mut data = data;
+        data[54..56].fill(0);
+        data[93] = 0xff;
+        fix_checksum(&mut data);
+        let edid = Edid::new(&data).unwrap();
+        assert_eq!(edid.preferred_timing(), None);
+        assert_eq!(edid.display_name(), None);
+        assert_eq!(preferred_resolution(&data), None);
+    }
+}
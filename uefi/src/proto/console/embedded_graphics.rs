@@ -0,0 +1,201 @@
+//! [`embedded-graphics`] draw target for the Graphics Output Protocol.
+//!
+//! [`GopDisplay`] implements [`DrawTarget`] on top of a [`GraphicsOutput`]
+//! in its current mode, so that the fonts, primitives and image decoders of
+//! the `embedded-graphics` ecosystem can draw boot splashes and menus:
+//!
+//! ```no_run
+//! use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+//! use embedded_graphics_core::prelude::*;
+//! use embedded_graphics_core::primitives::Rectangle;
+//! use uefi::boot::ScopedProtocol;
+//! use uefi::proto::console::embedded_graphics::GopDisplay;
+//! use uefi::proto::console::gop::GraphicsOutput;
+//! # fn test(mut gop: ScopedProtocol<GraphicsOutput>) -> uefi::Result {
+//! let mut display = GopDisplay::new(&mut gop);
+//! display.clear(Rgb888::BLACK)?;
+//! let area = Rectangle::new(Point::new(10, 10), Size::new(100, 50));
+//! display.fill_solid(&area, Rgb888::new(0x20, 0x60, 0xc0))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Pixels are written to the frame buffer directly, translating the colors
+//! to the pixel format of the mode. Filled rectangles are drawn with a
+//! [`BltOp::VideoFill`], which is much faster. In [`PixelFormat::BltOnly`]
+//! modes, where the frame buffer can't be accessed, every pixel is drawn with
+//! a blit; draw into a buffer and blit it with [`BltOp::BufferToVideo`]
+//! instead if that is too slow.
+//!
+//! With the `alloc` feature, [`Surface`] is a draw target too, which draws
+//! into a back buffer that is copied to the screen by blits. This is the
+//! faster choice on most firmware.
+//!
+//! This module is only available with the `embedded-graphics` feature.
+//!
+//! [`embedded-graphics`]: https://docs.rs/embedded-graphics
+//! [`PixelFormat::BltOnly`]: super::gop::PixelFormat::BltOnly
+//! [`Surface`]: super::surface::Surface
+
+use super::gop::{BltOp, BltPixel, GraphicsOutput, ModeInfo, PixelConverter};
+#[cfg(feature = "alloc")]
+use super::surface::Surface;
+use crate::Result;
+#[cfg(feature = "alloc")]
+use core::convert::Infallible;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Pixel;
+
+/// A [`DrawTarget`] for the current mode of a [`GraphicsOutput`], with
+/// [`Rgb888`] colors.
+///
+/// The mode can't be changed while the display exists, so the resolution
+/// and pixel format are read once when it is created.
+#[derive(Debug)]
+pub struct GopDisplay<'gop> {
+    gop: &'gop mut GraphicsOutput,
+    info: ModeInfo,
+    encoder: Option<PixelConverter>,
+    frame_buffer: *mut u8,
+    frame_buffer_size: usize,
+}
+
+impl<'gop> GopDisplay<'gop> {
+    /// Create a display for the current mode of `gop`.
+    pub fn new(gop: &'gop mut GraphicsOutput) -> Self {
+        let info = gop.current_mode_info();
+        let encoder = PixelConverter::new(&info);
+        let (frame_buffer, frame_buffer_size) = if encoder.is_some() {
+            let mut frame_buffer = gop.frame_buffer();
+            (frame_buffer.as_mut_ptr(), frame_buffer.size())
+        } else {
+            (core::ptr::null_mut(), 0)
+        };
+        Self {
+            gop,
+            info,
+            encoder,
+            frame_buffer,
+            frame_buffer_size,
+        }
+    }
+
+    /// Get the mode info that the display draws with.
+    #[must_use]
+    pub const fn mode_info(&self) -> &ModeInfo {
+        &self.info
+    }
+
+    /// Get the protocol back, for example to blit into it directly.
+    pub fn gop(&mut self) -> &mut GraphicsOutput {
+        self.gop
+    }
+
+    /// Write one pixel into the frame buffer. Pixels outside of the screen
+    /// are ignored.
+    fn write_pixel(&mut self, encoder: PixelConverter, x: usize, y: usize, color: Rgb888) {
+        let (width, height) = self.info.resolution();
+        if x >= width || y >= height {
+            return;
+        }
+        let offset = (y * self.info.stride() + x) * encoder.bytes_per_pixel();
+        if offset + encoder.bytes_per_pixel() > self.frame_buffer_size {
+            return;
+        }
+        let value = encoder.encode(BltPixel::new(color.r(), color.g(), color.b()));
+        let value = value.to_le_bytes();
+        for (i, byte) in value[..encoder.bytes_per_pixel()].iter().enumerate() {
+            // SAFETY: The offset is within the frame buffer, which is valid
+            // as long as the mode doesn't change, which requires `self.gop`.
+            unsafe { self.frame_buffer.add(offset + i).write_volatile(*byte) };
+        }
+    }
+}
+
+impl OriginDimensions for GopDisplay<'_> {
+    fn size(&self) -> Size {
+        let (width, height) = self.info.resolution();
+        Size::new(width as u32, height as u32)
+    }
+}
+
+impl DrawTarget for GopDisplay<'_> {
+    type Color = Rgb888;
+    type Error = crate::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            match self.encoder {
+                Some(encoder) => self.write_pixel(encoder, x, y, color),
+                None => {
+                    let area = Rectangle::new(point, Size::new(1, 1));
+                    self.fill_solid(&area, color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result {
+        let area = area.intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.gop.blt(BltOp::VideoFill {
+            color: BltPixel::new(color.r(), color.g(), color.b()),
+            dest: (area.top_left.x as usize, area.top_left.y as usize),
+            dims: (area.size.width as usize, area.size.height as usize),
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl OriginDimensions for Surface {
+    fn size(&self) -> Size {
+        let (width, height) = self.resolution();
+        Size::new(width as u32, height as u32)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DrawTarget for Surface {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Infallible>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) {
+                self.set_pixel(x, y, BltPixel::new(color.r(), color.g(), color.b()));
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(
+        &mut self,
+        area: &Rectangle,
+        color: Self::Color,
+    ) -> core::result::Result<(), Infallible> {
+        let area = area.intersection(&self.bounding_box());
+        if !area.is_zero_sized() {
+            self.fill_rect(
+                (area.top_left.x as usize, area.top_left.y as usize),
+                (area.size.width as usize, area.size.height as usize),
+                BltPixel::new(color.r(), color.g(), color.b()),
+            );
+        }
+        Ok(())
+    }
+}
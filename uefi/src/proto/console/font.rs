@@ -0,0 +1,503 @@
+//! Bitmap font text rendering, for example for graphical boot menus.
+//!
+//! The text console of [`Output`] may use a different resolution than the
+//! graphics mode, and can't be mixed with graphics or colored freely. A
+//! [`TextRenderer`] instead draws strings into a [`Surface`], with any
+//! foreground and background color, which is then flushed to the screen:
+//!
+//! ```no_run
+//! use uefi::boot::ScopedProtocol;
+//! use uefi::proto::console::font::TextRenderer;
+//! use uefi::proto::console::gop::{BltPixel, GraphicsOutput};
+//! use uefi::proto::console::surface::Surface;
+//! # fn test(mut gop: ScopedProtocol<GraphicsOutput>) -> uefi::Result {
+//! let mut surface = Surface::for_mode(&gop.current_mode_info());
+//! let text = TextRenderer::default()
+//!     .set_foreground(BltPixel::new(0xff, 0xff, 0xff))
+//!     .set_background(Some(BltPixel::new(0x20, 0x60, 0xc0)));
+//! text.draw(&mut surface, (16, 16), "Boot menu");
+//! text.draw_wrapped(&mut surface, (16, 48), 320, "A longer help text that is wrapped.");
+//! surface.flush(&mut gop)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The built-in [`FONT_8X16`] covers printable ASCII. Other fonts, for
+//! example with more characters or larger glyphs for high resolutions, can
+//! be used with [`BitmapFont::new`], or by implementing [`Font`].
+//!
+//! [`Output`]: super::text::Output
+
+use super::gop::BltPixel;
+use super::surface::Surface;
+use alloc::vec::Vec;
+
+/// A monospace font of bitmap glyphs.
+pub trait Font {
+    /// Get the `(width, height)` of every glyph in pixels.
+    fn glyph_size(&self) -> (usize, usize);
+
+    /// Get the bitmap of the glyph for `c`, or `None` if the font has no
+    /// glyph for it.
+    ///
+    /// The bitmap is stored row by row, with `width.div_ceil(8)` bytes per
+    /// row. The most significant bit of the first byte of a row is the
+    /// leftmost pixel, and set bits are drawn in the foreground color.
+    fn glyph(&self, c: char) -> Option<&[u8]>;
+}
+
+/// A [`Font`] with glyphs for a contiguous range of characters, stored one
+/// after the other.
+#[derive(Clone, Copy, Debug)]
+pub struct BitmapFont<'a> {
+    width: usize,
+    height: usize,
+    first: u32,
+    data: &'a [u8],
+}
+
+impl<'a> BitmapFont<'a> {
+    /// Create a font of glyphs of `width` by `height` pixels, in the format
+    /// described in [`Font::glyph`]. The first glyph of `data` is the glyph
+    /// for `first`, the next one for the following character, and so on.
+    ///
+    /// Returns `None` if the glyphs are empty, or if the length of `data` is
+    /// not a multiple of the size of a glyph.
+    #[must_use]
+    pub const fn new(width: usize, height: usize, first: char, data: &'a [u8]) -> Option<Self> {
+        let glyph_len = width.div_ceil(8) * height;
+        if glyph_len == 0 || data.len() % glyph_len != 0 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            first: first as u32,
+            data,
+        })
+    }
+
+    /// Get the number of glyphs in the font.
+    #[must_use]
+    pub const fn glyph_count(&self) -> usize {
+        self.data.len() / self.glyph_len()
+    }
+
+    const fn glyph_len(&self) -> usize {
+        self.width.div_ceil(8) * self.height
+    }
+}
+
+impl Font for BitmapFont<'_> {
+    fn glyph_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn glyph(&self, c: char) -> Option<&[u8]> {
+        let index = (c as u32).checked_sub(self.first)? as usize;
+        let len = self.glyph_len();
+        self.data.get(index * len..(index + 1) * len)
+    }
+}
+
+/// The built-in font, with 8 by 16 pixel glyphs for the printable ASCII
+/// characters from `' '` to `'~'`.
+pub const FONT_8X16: BitmapFont<'static> = BitmapFont {
+    width: 8,
+    height: 16,
+    first: ' ' as u32,
+    data: &FONT_8X16_DATA,
+};
+
+/// Draws text into a [`Surface`] with a [`Font`], see the
+/// [module documentation](self).
+///
+/// Text is drawn in lines of glyphs from the top-left corner that it is
+/// drawn at, and `'\n'` starts a new line. Characters that the font has no
+/// glyph for are drawn as `'?'`, or left blank if that is missing too.
+/// Drawing outside of the surface is clipped.
+#[derive(Clone, Copy)]
+pub struct TextRenderer<'font> {
+    font: &'font dyn Font,
+    foreground: BltPixel,
+    background: Option<BltPixel>,
+}
+
+impl<'font> TextRenderer<'font> {
+    /// Create a renderer for `font` that draws white text on a transparent
+    /// background.
+    #[must_use]
+    pub const fn new(font: &'font dyn Font) -> Self {
+        Self {
+            font,
+            foreground: BltPixel::new(0xff, 0xff, 0xff),
+            background: None,
+        }
+    }
+
+    /// Set the color of the text.
+    #[must_use]
+    pub const fn set_foreground(mut self, color: BltPixel) -> Self {
+        self.foreground = color;
+        self
+    }
+
+    /// Set the color that the cells of the glyphs are filled with, or `None`
+    /// to keep the pixels around the text.
+    #[must_use]
+    pub const fn set_background(mut self, color: Option<BltPixel>) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Get the `(width, height)` in pixels that `text` takes up when drawn
+    /// with [`draw`](Self::draw).
+    #[must_use]
+    pub fn text_size(&self, text: &str) -> (usize, usize) {
+        self.measure(Lines::new(text, usize::MAX))
+    }
+
+    /// Get the `(width, height)` in pixels that `text` takes up when drawn
+    /// with [`draw_wrapped`](Self::draw_wrapped).
+    #[must_use]
+    pub fn wrapped_text_size(&self, max_width: usize, text: &str) -> (usize, usize) {
+        self.measure(Lines::new(text, self.columns(max_width)))
+    }
+
+    /// Draw `text` with its top-left corner at `dest`. Returns the
+    /// `(width, height)` of the text in pixels.
+    pub fn draw(&self, surface: &mut Surface, dest: (usize, usize), text: &str) -> (usize, usize) {
+        self.draw_lines(surface, dest, Lines::new(text, usize::MAX))
+    }
+
+    /// Draw `text` with its top-left corner at `dest`, wrapped into lines
+    /// of at most `max_width` pixels. Returns the `(width, height)` of the
+    /// text in pixels.
+    ///
+    /// Lines are broken at spaces, which are not drawn at the start and end
+    /// of wrapped lines. Words that don't fit into a line on their own are
+    /// broken between characters. At least one character is drawn in every
+    /// line, even if its glyph is wider than `max_width`.
+    pub fn draw_wrapped(
+        &self,
+        surface: &mut Surface,
+        dest: (usize, usize),
+        max_width: usize,
+        text: &str,
+    ) -> (usize, usize) {
+        self.draw_lines(surface, dest, Lines::new(text, self.columns(max_width)))
+    }
+
+    fn columns(&self, max_width: usize) -> usize {
+        let (width, _) = self.font.glyph_size();
+        max_width.checked_div(width).unwrap_or(usize::MAX).max(1)
+    }
+
+    fn measure(&self, lines: Lines<'_>) -> (usize, usize) {
+        let (glyph_width, glyph_height) = self.font.glyph_size();
+        let (columns, rows) = lines.fold((0, 0), |(columns, rows), line| {
+            (columns.max(line.chars().count()), rows + 1)
+        });
+        (columns * glyph_width, rows * glyph_height)
+    }
+
+    fn draw_lines(
+        &self,
+        surface: &mut Surface,
+        dest: (usize, usize),
+        lines: Lines<'_>,
+    ) -> (usize, usize) {
+        let (glyph_width, glyph_height) = self.font.glyph_size();
+        let mut buffer = Vec::new();
+        let mut size = (0, 0);
+        for line in lines {
+            let y = dest.1.saturating_add(size.1);
+            let width = line.chars().count() * glyph_width;
+            self.render_line(surface, (dest.0, y), line, &mut buffer);
+            surface.copy_from((dest.0, y), (width, glyph_height), &buffer);
+            size = (size.0.max(width), size.1 + glyph_height);
+        }
+        size
+    }
+
+    /// Render one line of text into `buffer`, with the pixels of the surface
+    /// at `dest` as background if it is transparent.
+    fn render_line(
+        &self,
+        surface: &Surface,
+        dest: (usize, usize),
+        line: &str,
+        buffer: &mut Vec<BltPixel>,
+    ) {
+        let (glyph_width, glyph_height) = self.font.glyph_size();
+        let width = line.chars().count() * glyph_width;
+        let bytes_per_row = glyph_width.div_ceil(8);
+        buffer.clear();
+        buffer.reserve(width * glyph_height);
+        for y in 0..glyph_height {
+            let row = dest.1.saturating_add(y);
+            for (i, c) in line.chars().enumerate() {
+                let glyph = self.font.glyph(c).or_else(|| self.font.glyph('?'));
+                let bits =
+                    glyph.and_then(|glyph| glyph.get(y * bytes_per_row..(y + 1) * bytes_per_row));
+                for x in 0..glyph_width {
+                    let set = bits.is_some_and(|bits| bits[x / 8] & (0x80 >> (x % 8)) != 0);
+                    let pixel = if set {
+                        self.foreground
+                    } else if let Some(background) = self.background {
+                        background
+                    } else {
+                        let column = dest.0.saturating_add(i * glyph_width + x);
+                        surface.pixel(column, row).unwrap_or(BltPixel::new(0, 0, 0))
+                    };
+                    buffer.push(pixel);
+                }
+            }
+        }
+    }
+}
+
+/// Create a renderer for [`FONT_8X16`].
+impl Default for TextRenderer<'static> {
+    fn default() -> Self {
+        Self::new(&FONT_8X16)
+    }
+}
+
+impl core::fmt::Debug for TextRenderer<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TextRenderer")
+            .field("glyph_size", &self.font.glyph_size())
+            .field("foreground", &self.foreground)
+            .field("background", &self.background)
+            .finish()
+    }
+}
+
+/// Splits text into the lines to draw, with at most `columns` characters.
+struct Lines<'a> {
+    rest: Option<&'a str>,
+    columns: usize,
+}
+
+impl<'a> Lines<'a> {
+    const fn new(text: &'a str, columns: usize) -> Self {
+        Self {
+            rest: Some(text),
+            columns,
+        }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.rest?;
+        let (line, next) = match rest.find('\n') {
+            Some(end) => (&rest[..end], Some(&rest[end + 1..])),
+            None => (rest, None),
+        };
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let Some((limit, c)) = line.char_indices().nth(self.columns) else {
+            self.rest = next;
+            return Some(line);
+        };
+
+        // Break at the last space that fits, including a space right after
+        // the last character that fits, unless the line would be empty.
+        let search = if c == ' ' {
+            &line[..=limit]
+        } else {
+            &line[..limit]
+        };
+        let (end, start) = match search
+            .rfind(' ')
+            .filter(|&space| !line[..space].trim_end_matches(' ').is_empty())
+        {
+            Some(space) => (space, space + 1),
+            None => (limit, limit),
+        };
+        let tail = line[start..].trim_start_matches(' ');
+        self.rest = if tail.is_empty() {
+            next
+        } else {
+            Some(&rest[line.len() - tail.len()..])
+        };
+        Some(line[..end].trim_end_matches(' '))
+    }
+}
+
+/// Glyphs of [`FONT_8X16`].
+#[rustfmt::skip]
+static FONT_8X16_DATA: [u8; 95 * 16] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+    0x00, 0x00, 0x18, 0x3c, 0x3c, 0x3c, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, // '!'
+    0x00, 0x00, 0x66, 0x66, 0x66, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '"'
+    0x00, 0x00, 0x00, 0x6c, 0x6c, 0xfe, 0x6c, 0x6c, 0x6c, 0xfe, 0x6c, 0x6c, 0x00, 0x00, 0x00, 0x00, // '#'
+    0x00, 0x00, 0x18, 0x7c, 0xc6, 0xc2, 0xc0, 0x7c, 0x06, 0x86, 0xc6, 0x7c, 0x18, 0x18, 0x00, 0x00, // '$'
+    0x00, 0x00, 0x00, 0x00, 0xc2, 0xc6, 0x0c, 0x18, 0x30, 0x60, 0xcc, 0x8c, 0x00, 0x00, 0x00, 0x00, // '%'
+    0x00, 0x00, 0x38, 0x6c, 0x6c, 0x38, 0x76, 0xdc, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00, 0x00, // '&'
+    0x00, 0x00, 0x30, 0x30, 0x30, 0x60, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // "'"
+    0x00, 0x00, 0x0c, 0x18, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x18, 0x0c, 0x00, 0x00, 0x00, 0x00, // '('
+    0x00, 0x00, 0x30, 0x18, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x18, 0x30, 0x00, 0x00, 0x00, 0x00, // ')'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x66, 0x3c, 0xff, 0x3c, 0x66, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '*'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x7e, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '+'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x18, 0x30, 0x00, 0x00, 0x00, // ','
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '-'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, // '.'
+    0x00, 0x00, 0x00, 0x00, 0x02, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0x80, 0x00, 0x00, 0x00, 0x00, // '/'
+    0x00, 0x00, 0x38, 0x6c, 0xc6, 0xce, 0xde, 0xf6, 0xe6, 0xc6, 0x6c, 0x38, 0x00, 0x00, 0x00, 0x00, // '0'
+    0x00, 0x00, 0x18, 0x38, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00, 0x00, 0x00, 0x00, // '1'
+    0x00, 0x00, 0x7c, 0xc6, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0xc6, 0xfe, 0x00, 0x00, 0x00, 0x00, // '2'
+    0x00, 0x00, 0x7c, 0xc6, 0x06, 0x06, 0x3c, 0x06, 0x06, 0x06, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // '3'
+    0x00, 0x00, 0x0c, 0x1c, 0x3c, 0x6c, 0xcc, 0xfe, 0x0c, 0x0c, 0x0c, 0x1e, 0x00, 0x00, 0x00, 0x00, // '4'
+    0x00, 0x00, 0xfe, 0xc0, 0xc0, 0xc0, 0xfc, 0x06, 0x06, 0x06, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // '5'
+    0x00, 0x00, 0x38, 0x60, 0xc0, 0xc0, 0xfc, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // '6'
+    0x00, 0x00, 0xfe, 0xc6, 0x06, 0x0c, 0x18, 0x30, 0x30, 0x30, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00, // '7'
+    0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // '8'
+    0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x06, 0x06, 0x0c, 0x78, 0x00, 0x00, 0x00, 0x00, // '9'
+    0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, // ':'
+    0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x18, 0x18, 0x30, 0x00, 0x00, 0x00, 0x00, // ';'
+    0x00, 0x00, 0x00, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x00, 0x00, 0x00, 0x00, // '<'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '='
+    0x00, 0x00, 0x00, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x00, 0x00, 0x00, 0x00, // '>'
+    0x00, 0x00, 0x7c, 0xc6, 0xc6, 0x0c, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, // '?'
+    0x00, 0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xde, 0xde, 0xde, 0xdc, 0xc0, 0x7c, 0x00, 0x00, 0x00, 0x00, // '@'
+    0x00, 0x00, 0x10, 0x38, 0x6c, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'A'
+    0x00, 0x00, 0xfc, 0x66, 0x66, 0x66, 0x7c, 0x66, 0x66, 0x66, 0x66, 0xfc, 0x00, 0x00, 0x00, 0x00, // 'B'
+    0x00, 0x00, 0x3c, 0x66, 0xc2, 0xc0, 0xc0, 0xc0, 0xc0, 0xc2, 0x66, 0x3c, 0x00, 0x00, 0x00, 0x00, // 'C'
+    0x00, 0x00, 0xf8, 0x6c, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x6c, 0xf8, 0x00, 0x00, 0x00, 0x00, // 'D'
+    0x00, 0x00, 0xfe, 0x66, 0x62, 0x68, 0x78, 0x68, 0x60, 0x62, 0x66, 0xfe, 0x00, 0x00, 0x00, 0x00, // 'E'
+    0x00, 0x00, 0xfe, 0x66, 0x62, 0x68, 0x78, 0x68, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00, 0x00, // 'F'
+    0x00, 0x00, 0x3c, 0x66, 0xc2, 0xc0, 0xc0, 0xde, 0xc6, 0xc6, 0x66, 0x3a, 0x00, 0x00, 0x00, 0x00, // 'G'
+    0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'H'
+    0x00, 0x00, 0x3c, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00, 0x00, // 'I'
+    0x00, 0x00, 0x1e, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0xcc, 0xcc, 0xcc, 0x78, 0x00, 0x00, 0x00, 0x00, // 'J'
+    0x00, 0x00, 0xe6, 0x66, 0x6c, 0x6c, 0x78, 0x78, 0x6c, 0x66, 0x66, 0xe6, 0x00, 0x00, 0x00, 0x00, // 'K'
+    0x00, 0x00, 0xf0, 0x60, 0x60, 0x60, 0x60, 0x60, 0x60, 0x62, 0x66, 0xfe, 0x00, 0x00, 0x00, 0x00, // 'L'
+    0x00, 0x00, 0xc6, 0xee, 0xfe, 0xfe, 0xd6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'M'
+    0x00, 0x00, 0xc6, 0xe6, 0xf6, 0xfe, 0xde, 0xce, 0xc6, 0xc6, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'N'
+    0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'O'
+    0x00, 0x00, 0xfc, 0x66, 0x66, 0x66, 0x7c, 0x60, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00, 0x00, // 'P'
+    0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xd6, 0xde, 0x7c, 0x0c, 0x0e, 0x00, 0x00, // 'Q'
+    0x00, 0x00, 0xfc, 0x66, 0x66, 0x66, 0x7c, 0x6c, 0x66, 0x66, 0x66, 0xe6, 0x00, 0x00, 0x00, 0x00, // 'R'
+    0x00, 0x00, 0x7c, 0xc6, 0xc6, 0x60, 0x38, 0x0c, 0x06, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'S'
+    0x00, 0x00, 0xfc, 0xfc, 0xb4, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x78, 0x00, 0x00, 0x00, 0x00, // 'T'
+    0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'U'
+    0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x10, 0x00, 0x00, 0x00, 0x00, // 'V'
+    0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xd6, 0xd6, 0xd6, 0xfe, 0xee, 0x6c, 0x00, 0x00, 0x00, 0x00, // 'W'
+    0x00, 0x00, 0xc6, 0xc6, 0x6c, 0x7c, 0x38, 0x38, 0x7c, 0x6c, 0xc6, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'X'
+    0x00, 0x00, 0xcc, 0xcc, 0xcc, 0xcc, 0x78, 0x30, 0x30, 0x30, 0x30, 0x78, 0x00, 0x00, 0x00, 0x00, // 'Y'
+    0x00, 0x00, 0xfe, 0xc6, 0x86, 0x0c, 0x18, 0x30, 0x60, 0xc2, 0xc6, 0xfe, 0x00, 0x00, 0x00, 0x00, // 'Z'
+    0x00, 0x00, 0x3c, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3c, 0x00, 0x00, 0x00, 0x00, // '['
+    0x00, 0x00, 0x00, 0x00, 0x80, 0xc0, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x02, 0x00, 0x00, 0x00, 0x00, // '\\'
+    0x00, 0x00, 0x3c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x0c, 0x3c, 0x00, 0x00, 0x00, 0x00, // ']'
+    0x00, 0x00, 0x10, 0x38, 0x6c, 0xc6, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '^'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, // '_'
+    0x00, 0x00, 0x30, 0x30, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '`'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x78, 0x0c, 0x7c, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00, 0x00, // 'a'
+    0x00, 0x00, 0xe0, 0x60, 0x60, 0x78, 0x6c, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'b'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0xc0, 0xc0, 0xc0, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'c'
+    0x00, 0x00, 0x1c, 0x0c, 0x0c, 0x3c, 0x6c, 0xcc, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00, 0x00, // 'd'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0xfe, 0xc0, 0xc0, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'e'
+    0x00, 0x00, 0x38, 0x6c, 0x64, 0x60, 0xf0, 0x60, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00, 0x00, // 'f'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x76, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0x7c, 0x0c, 0xcc, 0x78, 0x00, // 'g'
+    0x00, 0x00, 0xe0, 0x60, 0x60, 0x6c, 0x76, 0x66, 0x66, 0x66, 0x66, 0xe6, 0x00, 0x00, 0x00, 0x00, // 'h'
+    0x00, 0x00, 0x18, 0x18, 0x00, 0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00, 0x00, // 'i'
+    0x00, 0x00, 0x06, 0x06, 0x00, 0x0e, 0x06, 0x06, 0x06, 0x06, 0x06, 0x06, 0x66, 0x66, 0x3c, 0x00, // 'j'
+    0x00, 0x00, 0xe0, 0x60, 0x60, 0x66, 0x6c, 0x78, 0x78, 0x6c, 0x66, 0xe6, 0x00, 0x00, 0x00, 0x00, // 'k'
+    0x00, 0x00, 0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, 0x00, 0x00, 0x00, 0x00, // 'l'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xec, 0xfe, 0xd6, 0xd6, 0xd6, 0xd6, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'm'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xdc, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00, // 'n'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 'o'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xdc, 0x66, 0x66, 0x66, 0x66, 0x66, 0x7c, 0x60, 0x60, 0xf0, 0x00, // 'p'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x76, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0x7c, 0x0c, 0x0c, 0x1e, 0x00, // 'q'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xdc, 0x76, 0x66, 0x60, 0x60, 0x60, 0xf0, 0x00, 0x00, 0x00, 0x00, // 'r'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x7c, 0xc6, 0x60, 0x38, 0x0c, 0xc6, 0x7c, 0x00, 0x00, 0x00, 0x00, // 's'
+    0x00, 0x00, 0x10, 0x30, 0x30, 0xfc, 0x30, 0x30, 0x30, 0x30, 0x36, 0x1c, 0x00, 0x00, 0x00, 0x00, // 't'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0xcc, 0x76, 0x00, 0x00, 0x00, 0x00, // 'u'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x6c, 0x38, 0x00, 0x00, 0x00, 0x00, // 'v'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0xc6, 0xd6, 0xd6, 0xd6, 0xfe, 0x6c, 0x00, 0x00, 0x00, 0x00, // 'w'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0x6c, 0x38, 0x38, 0x38, 0x6c, 0xc6, 0x00, 0x00, 0x00, 0x00, // 'x'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x0c, 0xf8, 0x00, // 'y'
+    0x00, 0x00, 0x00, 0x00, 0x00, 0xfe, 0xcc, 0x18, 0x30, 0x60, 0xc6, 0xfe, 0x00, 0x00, 0x00, 0x00, // 'z'
+    0x00, 0x00, 0x0e, 0x18, 0x18, 0x18, 0x70, 0x18, 0x18, 0x18, 0x18, 0x0e, 0x00, 0x00, 0x00, 0x00, // '{'
+    0x00, 0x00, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x00, 0x00, 0x00, // '|'
+    0x00, 0x00, 0x70, 0x18, 0x18, 0x18, 0x0e, 0x18, 0x18, 0x18, 0x18, 0x70, 0x00, 0x00, 0x00, 0x00, // '}'
+    0x00, 0x00, 0x76, 0xdc, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const WHITE: BltPixel = BltPixel::new(0xff, 0xff, 0xff);
+    const BLUE: BltPixel = BltPixel::new(0, 0, 0xff);
+
+    fn lines(text: &str, columns: usize) -> Vec<&str> {
+        Lines::new(text, columns).collect()
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(lines("", 10), [""]);
+        assert_eq!(lines("a\r\nb\n", usize::MAX), ["a", "b", ""]);
+        assert_eq!(lines("the quick brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(lines("the quick  brown", 9), ["the quick", "brown"]);
+        assert_eq!(lines("abcdefghij klm", 4), ["abcd", "efgh", "ij", "klm"]);
+        assert_eq!(lines("  abcdef", 4), ["  ab", "cdef"]);
+        assert_eq!(lines("ab cd \nef", 5), ["ab cd", "ef"]);
+        assert_eq!(lines("äöü", 1), ["ä", "ö", "ü"]);
+    }
+
+    #[test]
+    fn test_bitmap_font() {
+        assert_eq!(FONT_8X16.glyph_count(), 95);
+        assert_eq!(FONT_8X16.glyph(' '), Some(&[0; 16][..]));
+        assert_eq!(FONT_8X16.glyph('~').map(|glyph| glyph.len()), Some(16));
+        assert_eq!(FONT_8X16.glyph('\n'), None);
+        assert_eq!(FONT_8X16.glyph('\u{7f}'), None);
+
+        assert!(BitmapFont::new(0, 16, ' ', &[]).is_none());
+        assert!(BitmapFont::new(12, 2, 'a', &[0; 6]).is_none());
+        let font = BitmapFont::new(12, 2, 'a', &[0, 0, 0xff, 0xf0, 1, 2, 3, 4]).unwrap();
+        assert_eq!(font.glyph_count(), 2);
+        assert_eq!(font.glyph('b'), Some(&[1, 2, 3, 4][..]));
+    }
+
+    #[test]
+    fn test_draw() {
+        // A 2x2 font with a diagonal for 'a' and a full block for '?'.
+        let font = BitmapFont::new(2, 2, 'a', &[0x80, 0x40]).unwrap();
+        let renderer = TextRenderer::new(&font).set_background(Some(BLUE));
+        let mut surface = Surface::new(6, 5);
+
+        assert_eq!(renderer.text_size("aa\na"), (4, 4));
+        assert_eq!(renderer.draw(&mut surface, (1, 1), "aa\na"), (4, 4));
+        let black = BltPixel::new(0, 0, 0);
+        #[rustfmt::skip]
+        let expected = [
+            black, black, black, black, black, black,
+            black, WHITE, BLUE,  WHITE, BLUE,  black,
+            black, BLUE,  WHITE, BLUE,  WHITE, black,
+            black, WHITE, BLUE,  black, black, black,
+            black, BLUE,  WHITE, black, black, black,
+        ];
+        assert_eq!(surface.pixels(), expected);
+
+        // Missing glyphs, a transparent background and clipping.
+        let renderer = TextRenderer::new(&font).set_foreground(BLUE);
+        surface.clear(WHITE);
+        assert_eq!(renderer.draw(&mut surface, (4, 3), "az"), (4, 2));
+        assert_eq!(surface.pixels()[..3 * 6], vec![WHITE; 3 * 6][..]);
+        assert_eq!(surface.pixel(4, 3), Some(BLUE));
+        assert_eq!(surface.pixel(4, 4), Some(WHITE));
+        assert_eq!(surface.pixel(5, 4), Some(BLUE));
+
+        assert_eq!(renderer.wrapped_text_size(5, "aaa aa"), (4, 6));
+        assert_eq!(renderer.draw_wrapped(&mut surface, (0, 0), 1, "aa"), (2, 4));
+        assert_eq!(surface.pixel(0, 2), Some(BLUE));
+    }
+}
@@ -52,7 +52,7 @@
 
 use crate::proto::unsafe_protocol;
 use crate::util::usize_from_u32;
-use crate::{boot, Result, StatusExt};
+use crate::{boot, Result, Status, StatusExt};
 use core::fmt::{Debug, Formatter};
 use core::marker::PhantomData;
 use core::mem;
@@ -119,6 +119,38 @@ impl GraphicsOutput {
         unsafe { (self.0.set_mode)(&mut self.0, mode.index) }.to_result()
     }
 
+    /// Returns the mode that fits `criteria` best, or `None` if no mode
+    /// meets its requirements. See [`ModeCriteria`] for how modes are
+    /// ranked.
+    #[must_use]
+    pub fn best_mode(&self, criteria: &ModeCriteria) -> Option<Mode> {
+        criteria.select(self.modes(), &self.current_mode())
+    }
+
+    /// Sets the video device into the mode that fits `criteria` best, see
+    /// [`best_mode`], and returns that mode.
+    ///
+    /// The mode is not set again if it already is the current mode, so the
+    /// screen is not cleared. With [`ModeCriteria::set_keep_current`], the
+    /// current mode is also kept if it satisfies all of the criteria, even
+    /// if another mode ranks higher.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::NOT_FOUND`] if no mode meets the requirements of
+    ///   `criteria`.
+    /// - See [`set_mode`] for errors when setting the mode.
+    ///
+    /// [`best_mode`]: Self::best_mode
+    /// [`set_mode`]: Self::set_mode
+    pub fn set_best_mode(&mut self, criteria: &ModeCriteria) -> Result<Mode> {
+        let mode = self.best_mode(criteria).ok_or(Status::NOT_FOUND)?;
+        if mode.index != self.mode().mode {
+            self.set_mode(&mode)?;
+        }
+        Ok(mode)
+    }
+
     /// Performs a blt (block transfer) operation on the frame buffer.
     ///
     /// Every operation requires different parameters.
@@ -312,6 +344,14 @@ impl GraphicsOutput {
     const fn mode(&self) -> &GraphicsOutputProtocolMode {
         unsafe { &*self.0.mode.cast_const() }
     }
+
+    const fn current_mode(&self) -> Mode {
+        Mode {
+            index: self.mode().mode,
+            info_sz: self.mode().size_of_info,
+            info: self.current_mode_info(),
+        }
+    }
 }
 
 /// Represents the format of the pixels in a frame buffer.
@@ -446,11 +486,181 @@ impl<'gop> Debug for ModeIter<'gop> {
 
 impl ExactSizeIterator for ModeIter<'_> {}
 
+/// Preferences for selecting a graphics mode with
+/// [`GraphicsOutput::set_best_mode`].
+///
+/// Modes outside of the minimum and maximum resolution are never selected.
+/// The other modes are ranked by these preferences, in order:
+///
+/// 1. The resolution is the [native resolution] of the display.
+/// 2. The resolution has the [aspect ratio], or that of the native
+///    resolution if no aspect ratio is set. Ratios within 1% match, so
+///    that 1366x768 counts as 16:9.
+/// 3. The mode has the [pixel format].
+/// 4. The resolution is not larger than the native resolution, as the
+///    display would have to scale it.
+/// 5. The resolution is larger.
+///
+/// Modes that rank the same are ordered by their mode number. Without any
+/// preferences, the mode with the highest resolution is selected.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot::{self, ScopedProtocol};
+/// use uefi::proto::console::edid::EdidActive;
+/// use uefi::proto::console::gop::{GraphicsOutput, ModeCriteria, PixelFormat};
+/// # fn test() -> uefi::Result {
+/// let handle = boot::get_handle_for_protocol::<GraphicsOutput>()?;
+/// let mut gop = boot::open_protocol_exclusive::<GraphicsOutput>(handle)?;
+///
+/// let mut criteria = ModeCriteria::new()
+///     .set_min_resolution((800, 600))
+///     .set_pixel_format(PixelFormat::Bgr)
+///     .set_keep_current(true);
+/// if let Ok(edid) = boot::open_protocol_exclusive::<EdidActive>(handle) {
+///     if let Some(native) = edid.preferred_resolution() {
+///         criteria = criteria.set_native_resolution(native);
+///     }
+/// }
+/// let mode = gop.set_best_mode(&criteria)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [native resolution]: Self::set_native_resolution
+/// [aspect ratio]: Self::set_aspect_ratio
+/// [pixel format]: Self::set_pixel_format
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ModeCriteria {
+    native_resolution: Option<(usize, usize)>,
+    min_resolution: Option<(usize, usize)>,
+    max_resolution: Option<(usize, usize)>,
+    aspect_ratio: Option<(usize, usize)>,
+    pixel_format: Option<PixelFormat>,
+    keep_current: bool,
+}
+
+impl ModeCriteria {
+    /// Create criteria without any requirements or preferences.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefer the native resolution of the display, usually the
+    /// [preferred resolution] of its EDID.
+    ///
+    /// [preferred resolution]: super::edid::EdidActive::preferred_resolution
+    #[must_use]
+    pub const fn set_native_resolution(mut self, resolution: (usize, usize)) -> Self {
+        self.native_resolution = Some(resolution);
+        self
+    }
+
+    /// Only select modes with at least this `(width, height)`.
+    #[must_use]
+    pub const fn set_min_resolution(mut self, resolution: (usize, usize)) -> Self {
+        self.min_resolution = Some(resolution);
+        self
+    }
+
+    /// Only select modes with at most this `(width, height)`.
+    #[must_use]
+    pub const fn set_max_resolution(mut self, resolution: (usize, usize)) -> Self {
+        self.max_resolution = Some(resolution);
+        self
+    }
+
+    /// Prefer resolutions with this `(width, height)` ratio, for example
+    /// `(16, 9)`.
+    #[must_use]
+    pub const fn set_aspect_ratio(mut self, ratio: (usize, usize)) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+
+    /// Prefer modes with this pixel format, for example to draw directly to
+    /// the frame buffer in a known format.
+    #[must_use]
+    pub const fn set_pixel_format(mut self, format: PixelFormat) -> Self {
+        self.pixel_format = Some(format);
+        self
+    }
+
+    /// Keep the current mode if it meets the requirements and all of the
+    /// preferences that are set, to avoid the flicker of a mode switch.
+    #[must_use]
+    pub const fn set_keep_current(mut self, keep_current: bool) -> Self {
+        self.keep_current = keep_current;
+        self
+    }
+
+    /// Select the best of `modes`, or `current` if it is kept.
+    fn select(&self, modes: impl Iterator<Item = Mode>, current: &Mode) -> Option<Mode> {
+        if self.keep_current && self.is_satisfied_by(current.info()) {
+            return Some(*current);
+        }
+        let mut best: Option<(Mode, _)> = None;
+        for mode in modes.filter(|mode| self.accepts(mode.info())) {
+            let rank = self.rank(mode.info());
+            if best.as_ref().map_or(true, |(_, best)| rank > *best) {
+                best = Some((mode, rank));
+            }
+        }
+        best.map(|(mode, _)| mode)
+    }
+
+    /// Check the minimum and maximum resolution.
+    fn accepts(&self, info: &ModeInfo) -> bool {
+        let (width, height) = info.resolution();
+        self.min_resolution.map_or(true, |(min_width, min_height)| {
+            width >= min_width && height >= min_height
+        }) && self.max_resolution.map_or(true, |(max_width, max_height)| {
+            width <= max_width && height <= max_height
+        })
+    }
+
+    /// Check the requirements and all preferences that are set.
+    fn is_satisfied_by(&self, info: &ModeInfo) -> bool {
+        let (native, aspect, format, _, _) = self.rank(info);
+        self.accepts(info)
+            && (native || self.native_resolution.is_none())
+            && (aspect || self.aspect_ratio().is_none())
+            && (format || self.pixel_format.is_none())
+    }
+
+    fn aspect_ratio(&self) -> Option<(usize, usize)> {
+        self.aspect_ratio.or(self.native_resolution)
+    }
+
+    /// Rank a mode by the preferences, in order. Higher is better.
+    fn rank(&self, info: &ModeInfo) -> (bool, bool, bool, bool, usize) {
+        let resolution = info.resolution();
+        let (width, height) = resolution;
+        let native = self.native_resolution == Some(resolution);
+        let aspect = self
+            .aspect_ratio()
+            .is_some_and(|(ratio_width, ratio_height)| {
+                let scaled_width = width.saturating_mul(ratio_height);
+                let scaled_height = height.saturating_mul(ratio_width);
+                scaled_width.abs_diff(scaled_height).saturating_mul(100) <= scaled_height
+            });
+        let format = self.pixel_format == Some(info.pixel_format());
+        let fits = self
+            .native_resolution
+            .map_or(true, |(native_width, native_height)| {
+                width <= native_width && height <= native_height
+            });
+        (native, aspect, format, fits, width.saturating_mul(height))
+    }
+}
+
 /// Format of pixel data used for blitting.
 ///
 /// This is a BGR 24-bit format with an 8-bit padding, to keep each pixel 32-bit in size.
 #[allow(missing_docs)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub struct BltPixel {
     pub blue: u8,
@@ -483,6 +693,263 @@ impl From<u32> for BltPixel {
     }
 }
 
+/// Convert to a `0xRRGGBB` value.
+impl From<BltPixel> for u32 {
+    fn from(pixel: BltPixel) -> Self {
+        Self::from(pixel.red) << 16 | Self::from(pixel.green) << 8 | Self::from(pixel.blue)
+    }
+}
+
+/// Convert from `[red, green, blue]`.
+impl From<[u8; 3]> for BltPixel {
+    fn from([red, green, blue]: [u8; 3]) -> Self {
+        Self::new(red, green, blue)
+    }
+}
+
+/// Convert to `[red, green, blue]`.
+impl From<BltPixel> for [u8; 3] {
+    fn from(pixel: BltPixel) -> Self {
+        [pixel.red, pixel.green, pixel.blue]
+    }
+}
+
+/// Converts [`BltPixel`]s to and from the pixel format of a frame buffer.
+///
+/// The [`PixelFormat::Rgb`] and [`PixelFormat::Bgr`] formats have 32-bit
+/// pixels. [`PixelFormat::Bitmask`] pixels are as large as needed for the
+/// highest bit of the masks, and can have channels with more or less than 8
+/// bits, which are scaled. For example, the 5-bit red channel of RGB565
+/// maps 0xff to 0x1f, and back to 0xff.
+///
+/// ```no_run
+/// use uefi::boot::ScopedProtocol;
+/// use uefi::proto::console::gop::{BltPixel, GraphicsOutput, PixelConverter};
+/// # fn test(mut gop: ScopedProtocol<GraphicsOutput>) {
+/// let info = gop.current_mode_info();
+/// let converter = PixelConverter::new(&info).expect("no frame buffer access");
+/// let row = [BltPixel::new(0xff, 0x80, 0); 100];
+/// let mut frame_buffer = gop.frame_buffer();
+/// for y in 0..50 {
+///     let offset = y * info.stride() * converter.bytes_per_pixel();
+///     converter.write_row(&mut frame_buffer, offset, &row);
+/// }
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PixelConverter {
+    red: Channel,
+    green: Channel,
+    blue: Channel,
+    bytes_per_pixel: usize,
+}
+
+impl PixelConverter {
+    /// The converter for [`PixelFormat::Rgb`].
+    pub const RGB: Self = Self::from_bitmask(PixelBitmask {
+        red: 0x0000ff,
+        green: 0x00ff00,
+        blue: 0xff0000,
+        reserved: 0xff000000,
+    });
+
+    /// The converter for [`PixelFormat::Bgr`].
+    pub const BGR: Self = Self::from_bitmask(PixelBitmask {
+        red: 0xff0000,
+        green: 0x00ff00,
+        blue: 0x0000ff,
+        reserved: 0xff000000,
+    });
+
+    /// Get the converter for the pixel format of a mode, or `None` for
+    /// [`PixelFormat::BltOnly`] modes, whose frame buffer can't be accessed.
+    #[must_use]
+    pub const fn new(info: &ModeInfo) -> Option<Self> {
+        match info.pixel_format() {
+            PixelFormat::Rgb => Some(Self::RGB),
+            PixelFormat::Bgr => Some(Self::BGR),
+            PixelFormat::Bitmask => match info.pixel_bitmask() {
+                Some(mask) => Some(Self::from_bitmask(mask)),
+                None => None,
+            },
+            PixelFormat::BltOnly => None,
+        }
+    }
+
+    /// Get the converter for a [`PixelFormat::Bitmask`] format. The masks
+    /// must be contiguous runs of bits.
+    #[must_use]
+    pub const fn from_bitmask(mask: PixelBitmask) -> Self {
+        let bits = 32 - (mask.red | mask.green | mask.blue | mask.reserved).leading_zeros();
+        Self {
+            red: Channel::new(mask.red),
+            green: Channel::new(mask.green),
+            blue: Channel::new(mask.blue),
+            bytes_per_pixel: bits.div_ceil(8) as usize,
+        }
+    }
+
+    /// Get the size of a pixel in the frame buffer in bytes.
+    #[must_use]
+    pub const fn bytes_per_pixel(&self) -> usize {
+        self.bytes_per_pixel
+    }
+
+    /// Convert a pixel to the value that is stored in the frame buffer, in
+    /// the low [`bytes_per_pixel`] bytes in little-endian order.
+    ///
+    /// [`bytes_per_pixel`]: Self::bytes_per_pixel
+    #[must_use]
+    pub const fn encode(&self, pixel: BltPixel) -> u32 {
+        self.red.encode(pixel.red) | self.green.encode(pixel.green) | self.blue.encode(pixel.blue)
+    }
+
+    /// Convert a value from the frame buffer to a pixel.
+    #[must_use]
+    pub const fn decode(&self, value: u32) -> BltPixel {
+        BltPixel::new(
+            self.red.decode(value),
+            self.green.decode(value),
+            self.blue.decode(value),
+        )
+    }
+
+    /// Convert `pixels` into `dest`, which holds as many pixels as fit into
+    /// it. Returns the number of converted pixels.
+    pub fn encode_row(&self, pixels: &[BltPixel], dest: &mut [u8]) -> usize {
+        let bpp = self.bytes_per_pixel;
+        let mut count = 0;
+        for (pixel, dest) in pixels.iter().zip(dest.chunks_exact_mut(bpp)) {
+            dest.copy_from_slice(&self.encode(*pixel).to_le_bytes()[..bpp]);
+            count += 1;
+        }
+        count
+    }
+
+    /// Convert the pixels in `src` into `dest`. Returns the number of
+    /// converted pixels, which is limited by the shorter of the two.
+    pub fn decode_row(&self, src: &[u8], dest: &mut [BltPixel]) -> usize {
+        let bpp = self.bytes_per_pixel;
+        let mut count = 0;
+        for (src, dest) in src.chunks_exact(bpp).zip(dest.iter_mut()) {
+            let mut value = [0; 4];
+            value[..bpp].copy_from_slice(src);
+            *dest = self.decode(u32::from_le_bytes(value));
+            count += 1;
+        }
+        count
+    }
+
+    /// Write `pixels` to the frame buffer, starting at the byte `offset`.
+    /// Pixels that would be written past the end of the frame buffer are
+    /// skipped, so rows need to be clipped to the width of the mode by the
+    /// caller. Returns the number of written pixels.
+    ///
+    /// Aligned 32-bit pixels are written with one volatile write each,
+    /// which is much faster than writing their bytes one by one.
+    pub fn write_row(
+        &self,
+        frame_buffer: &mut FrameBuffer,
+        offset: usize,
+        pixels: &[BltPixel],
+    ) -> usize {
+        let bpp = self.bytes_per_pixel;
+        let count = pixels
+            .len()
+            .min(frame_buffer.size().saturating_sub(offset) / bpp);
+        let base = frame_buffer.as_mut_ptr().wrapping_add(offset);
+        for (i, pixel) in pixels[..count].iter().enumerate() {
+            let value = self.encode(*pixel);
+            // SAFETY: The pixel is within the bounds of the frame buffer.
+            unsafe {
+                let ptr = base.add(i * bpp);
+                if bpp == 4 && ptr.cast::<u32>().is_aligned() {
+                    ptr.cast::<u32>().write_volatile(value.to_le());
+                } else {
+                    for (j, byte) in value.to_le_bytes()[..bpp].iter().enumerate() {
+                        ptr.add(j).write_volatile(*byte);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Read pixels from the frame buffer into `dest`, starting at the byte
+    /// `offset`. Returns the number of read pixels, which is less than the
+    /// length of `dest` at the end of the frame buffer.
+    pub fn read_row(
+        &self,
+        frame_buffer: &FrameBuffer,
+        offset: usize,
+        dest: &mut [BltPixel],
+    ) -> usize {
+        let bpp = self.bytes_per_pixel;
+        let count = dest
+            .len()
+            .min(frame_buffer.size().saturating_sub(offset) / bpp);
+        for (i, dest) in dest[..count].iter_mut().enumerate() {
+            let mut value = [0; 4];
+            for (j, byte) in value[..bpp].iter_mut().enumerate() {
+                // SAFETY: The pixel is within the bounds of the frame buffer.
+                *byte = unsafe { frame_buffer.read_byte(offset + i * bpp + j) };
+            }
+            *dest = self.decode(u32::from_le_bytes(value));
+        }
+        count
+    }
+}
+
+/// Position and width of a color channel within a pixel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Channel {
+    shift: u32,
+    bits: u32,
+}
+
+impl Channel {
+    const fn new(mask: u32) -> Self {
+        Self {
+            shift: if mask == 0 { 0 } else { mask.trailing_zeros() },
+            bits: mask.count_ones(),
+        }
+    }
+
+    /// Scale an 8-bit channel value to the width of the channel.
+    const fn encode(&self, value: u8) -> u32 {
+        let value = value as u32;
+        let scaled = if self.bits >= 8 {
+            value << (self.bits - 8)
+        } else {
+            value >> (8 - self.bits)
+        };
+        if self.bits == 0 {
+            0
+        } else {
+            scaled << self.shift
+        }
+    }
+
+    /// Scale the channel of a pixel value to 8 bits. Narrower channels are
+    /// scaled so that their maximum maps to 0xff.
+    const fn decode(&self, value: u32) -> u8 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let max = if self.bits >= 32 {
+            u32::MAX
+        } else {
+            (1 << self.bits) - 1
+        };
+        let value = (value >> self.shift) & max;
+        if self.bits >= 8 {
+            (value >> (self.bits - 8)) as u8
+        } else {
+            ((value * 0xff + max / 2) / max) as u8
+        }
+    }
+}
+
 /// Region of the `BltBuffer` which we are operating on
 ///
 /// Some `Blt` operations can operate on either the full `BltBuffer` or a
@@ -649,3 +1116,154 @@ impl<'gop> FrameBuffer<'gop> {
         (self.base.add(index) as *const T).read_volatile()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use uefi_raw::protocol::console::GraphicsPixelFormat;
+
+    fn mode(index: u32, resolution: (u32, u32), pixel_format: GraphicsPixelFormat) -> Mode {
+        Mode {
+            index,
+            info_sz: mem::size_of::<GraphicsOutputModeInformation>(),
+            info: ModeInfo(GraphicsOutputModeInformation {
+                horizontal_resolution: resolution.0,
+                vertical_resolution: resolution.1,
+                pixel_format,
+                pixels_per_scan_line: resolution.0,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn select(criteria: ModeCriteria, modes: &[Mode], current: usize) -> Option<u32> {
+        criteria
+            .select(modes.iter().copied(), &modes[current])
+            .map(|mode| mode.index)
+    }
+
+    #[test]
+    fn test_pixel_converter() {
+        let pixel = BltPixel::new(0x12, 0x34, 0x56);
+        assert_eq!(u32::from(pixel), 0x123456);
+        assert_eq!(BltPixel::from(0x123456), pixel);
+        assert_eq!(<[u8; 3]>::from(pixel), [0x12, 0x34, 0x56]);
+        assert_eq!(BltPixel::from([0x12, 0x34, 0x56]), pixel);
+
+        assert_eq!(PixelConverter::RGB.bytes_per_pixel(), 4);
+        assert_eq!(
+            PixelConverter::RGB.encode(pixel).to_le_bytes(),
+            [0x12, 0x34, 0x56, 0]
+        );
+        assert_eq!(
+            PixelConverter::BGR.encode(pixel).to_le_bytes(),
+            [0x56, 0x34, 0x12, 0]
+        );
+        assert_eq!(PixelConverter::RGB.decode(0xff56_3412), pixel);
+
+        // RGB565, with narrower channels.
+        let rgb565 = PixelConverter::from_bitmask(PixelBitmask {
+            red: 0xf800,
+            green: 0x07e0,
+            blue: 0x001f,
+            reserved: 0,
+        });
+        assert_eq!(rgb565.bytes_per_pixel(), 2);
+        let white = BltPixel::new(0xff, 0xff, 0xff);
+        assert_eq!(rgb565.encode(white), 0xffff);
+        assert_eq!(rgb565.decode(0xffff), white);
+        assert_eq!(rgb565.encode(BltPixel::new(0xff, 0, 0)), 0xf800);
+        assert_eq!(
+            rgb565.encode(pixel),
+            (0x12 >> 3) << 11 | (0x34 >> 2) << 5 | 0x56 >> 3
+        );
+        assert_eq!(rgb565.decode(0x0010), BltPixel::new(0, 0, 0x84));
+
+        // 10 bits per channel.
+        let rgb30 = PixelConverter::from_bitmask(PixelBitmask {
+            red: 0x3ff0_0000,
+            green: 0x000f_fc00,
+            blue: 0x0000_03ff,
+            reserved: 0xc000_0000,
+        });
+        assert_eq!(rgb30.bytes_per_pixel(), 4);
+        assert_eq!(rgb30.encode(BltPixel::new(0xff, 0, 0x01)), 0x3fc0_0004);
+        assert_eq!(rgb30.decode(0x3fc0_0004), BltPixel::new(0xff, 0, 0x01));
+
+        // Rows, limited by the shorter buffer.
+        let mut bytes = [0; 5];
+        assert_eq!(rgb565.encode_row(&[white, pixel, white], &mut bytes), 2);
+        assert_eq!(bytes[..2], [0xff, 0xff]);
+        let mut pixels = [BltPixel::new(0, 0, 0); 3];
+        assert_eq!(rgb565.decode_row(&bytes, &mut pixels), 2);
+        assert_eq!(pixels[0], white);
+        assert_eq!(pixels[1], rgb565.decode(rgb565.encode(pixel)));
+    }
+
+    #[test]
+    fn test_mode_criteria() {
+        let bgr = GraphicsPixelFormat::PIXEL_BLUE_GREEN_RED_RESERVED_8_BIT_PER_COLOR;
+        let blt = GraphicsPixelFormat::PIXEL_BLT_ONLY;
+        let modes: Vec<_> = [
+            ((640, 480), bgr),
+            ((800, 600), bgr),
+            ((1024, 768), blt),
+            ((1366, 768), bgr),
+            ((1920, 1080), bgr),
+            ((2560, 1600), bgr),
+        ]
+        .into_iter()
+        .zip(0..)
+        .map(|((resolution, format), index)| mode(index, resolution, format))
+        .collect();
+
+        // The largest mode by default.
+        assert_eq!(select(ModeCriteria::new(), &modes, 0), Some(5));
+        assert_eq!(
+            select(
+                ModeCriteria::new().set_max_resolution((1600, 1200)),
+                &modes,
+                0
+            ),
+            Some(3)
+        );
+        assert_eq!(
+            select(ModeCriteria::new().set_min_resolution((4000, 0)), &modes, 0),
+            None
+        );
+
+        // The native resolution wins, then modes with its aspect ratio that
+        // fit into it.
+        let native = ModeCriteria::new().set_native_resolution((1920, 1080));
+        assert_eq!(select(native, &modes, 0), Some(4));
+        assert_eq!(
+            select(native.set_max_resolution((1600, 900)), &modes, 0),
+            Some(3)
+        );
+        assert_eq!(
+            select(ModeCriteria::new().set_aspect_ratio((4, 3)), &modes, 0),
+            Some(2)
+        );
+        assert_eq!(
+            select(
+                ModeCriteria::new()
+                    .set_aspect_ratio((4, 3))
+                    .set_pixel_format(PixelFormat::Bgr),
+                &modes,
+                0
+            ),
+            Some(1)
+        );
+
+        // The current mode is only kept if it satisfies everything.
+        let keep = native.set_keep_current(true);
+        assert_eq!(select(keep, &modes, 3), Some(4));
+        assert_eq!(select(keep, &modes, 4), Some(4));
+        let keep = ModeCriteria::new()
+            .set_min_resolution((800, 600))
+            .set_keep_current(true);
+        assert_eq!(select(keep, &modes, 1), Some(1));
+        assert_eq!(select(keep, &modes, 0), Some(5));
+    }
+}
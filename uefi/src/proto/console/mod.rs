@@ -3,7 +3,18 @@
 //! The console represents the various input and output methods
 //! used by the user to interact with the early boot platform.
 
+pub mod absolute_pointer;
+#[cfg(feature = "alloc")]
+pub mod bmp;
+pub mod edid;
+#[cfg(feature = "embedded-graphics")]
+pub mod embedded_graphics;
+#[cfg(feature = "alloc")]
+pub mod font;
 pub mod gop;
 pub mod pointer;
 pub mod serial;
+#[cfg(feature = "alloc")]
+pub mod surface;
 pub mod text;
+pub mod tui;
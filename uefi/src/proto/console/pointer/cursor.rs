@@ -0,0 +1,393 @@
+use super::{Pointer, PointerMode, PointerState};
+use crate::{boot, Result, Status};
+
+#[cfg(feature = "alloc")]
+use {
+    crate::proto::console::gop::{BltOp, BltPixel, BltRegion, GraphicsOutput},
+    alloc::vec,
+    alloc::vec::Vec,
+};
+
+/// Tracks a cursor position by accumulating the relative movement reported
+/// by a [`Pointer`].
+///
+/// The position is clamped to a screen of the given resolution, typically
+/// the resolution of the current graphics mode. Movement is converted from
+/// the device's counts to pixels using the [`PointerMode`] resolution and
+/// the speed set with [`set_speed`].
+///
+/// [`set_speed`]: Self::set_speed
+///
+/// # Examples
+///
+/// ```
+/// use uefi::proto::console::gop::GraphicsOutput;
+/// use uefi::proto::console::pointer::{Cursor, Pointer};
+/// use uefi::Result;
+///
+/// fn wait_for_click(pointer: &mut Pointer, gop: &GraphicsOutput) -> Result<(usize, usize)> {
+///     let resolution = gop.current_mode_info().resolution();
+///     let mut cursor = Cursor::new(pointer.mode(), resolution);
+///     for event in pointer.cursor_events(&mut cursor) {
+///         let event = event?;
+///         if event.pressed[0] {
+///             return Ok(event.position);
+///         }
+///     }
+///     unreachable!()
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Cursor {
+    position: (usize, usize),
+    bounds: (usize, usize),
+    resolution: [u64; 2],
+    speed: u32,
+    remainder: [i64; 2],
+    buttons: [bool; 2],
+}
+
+impl Cursor {
+    /// Default speed, in pixels per millimeter of movement.
+    pub const DEFAULT_SPEED: u32 = 4;
+
+    /// Create a cursor in the center of a screen of `bounds` pixels, for a
+    /// pointer device described by `mode`.
+    #[must_use]
+    pub const fn new(mode: &PointerMode, bounds: (usize, usize)) -> Self {
+        Self {
+            position: (bounds.0 / 2, bounds.1 / 2),
+            bounds,
+            resolution: [mode.resolution[0], mode.resolution[1]],
+            speed: Self::DEFAULT_SPEED,
+            remainder: [0; 2],
+            buttons: [false; 2],
+        }
+    }
+
+    /// Set the speed of the cursor, in pixels per millimeter of movement.
+    #[must_use]
+    pub const fn set_speed(mut self, pixels_per_mm: u32) -> Self {
+        self.speed = pixels_per_mm;
+        self
+    }
+
+    /// Get the position of the cursor.
+    #[must_use]
+    pub const fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Move the cursor to `position`, clamped to the bounds.
+    pub fn set_position(&mut self, position: (usize, usize)) {
+        self.position = (
+            position.0.min(self.bounds.0.saturating_sub(1)),
+            position.1.min(self.bounds.1.saturating_sub(1)),
+        );
+        self.remainder = [0; 2];
+    }
+
+    /// Get the size of the screen the cursor is clamped to.
+    #[must_use]
+    pub const fn bounds(&self) -> (usize, usize) {
+        self.bounds
+    }
+
+    /// Change the size of the screen, such as after a graphics mode change.
+    /// The position is clamped to the new bounds.
+    pub fn set_bounds(&mut self, bounds: (usize, usize)) {
+        self.bounds = bounds;
+        self.set_position(self.position);
+    }
+
+    /// Get whether the left and right buttons are pressed.
+    #[must_use]
+    pub const fn buttons(&self) -> [bool; 2] {
+        self.buttons
+    }
+
+    /// Apply a state read from the pointer device, and get the resulting
+    /// event.
+    pub fn apply(&mut self, state: &PointerState) -> CursorEvent {
+        let x = self.move_axis(
+            0,
+            state.relative_movement[0],
+            self.position.0,
+            self.bounds.0,
+        );
+        let y = self.move_axis(
+            1,
+            state.relative_movement[1],
+            self.position.1,
+            self.bounds.1,
+        );
+        self.position = (x, y);
+
+        let previous = self.buttons;
+        self.buttons = state.button;
+        CursorEvent {
+            position: self.position,
+            buttons: self.buttons,
+            pressed: [
+                self.buttons[0] && !previous[0],
+                self.buttons[1] && !previous[1],
+            ],
+            released: [
+                !self.buttons[0] && previous[0],
+                !self.buttons[1] && previous[1],
+            ],
+        }
+    }
+
+    /// Read the state of `pointer` and apply it, if it changed.
+    ///
+    /// This does not block, so it can be called in a loop that also handles
+    /// other input.
+    ///
+    /// # Errors
+    ///
+    /// See [`Pointer::read_state`].
+    pub fn update(&mut self, pointer: &mut Pointer) -> Result<Option<CursorEvent>> {
+        Ok(pointer.read_state()?.map(|state| self.apply(&state)))
+    }
+
+    fn move_axis(&mut self, axis: usize, movement: i32, position: usize, bound: usize) -> usize {
+        let resolution = self.resolution[axis];
+        // The device does not support this axis.
+        if resolution == 0 {
+            return position;
+        }
+        let resolution = i64::try_from(resolution).unwrap_or(i64::MAX);
+        let counts = self.remainder[axis] + i64::from(movement) * i64::from(self.speed);
+        let pixels = counts / resolution;
+        self.remainder[axis] = counts % resolution;
+
+        let max = bound.saturating_sub(1);
+        let position = i64::try_from(position).unwrap_or(i64::MAX);
+        let position = position.saturating_add(pixels).max(0);
+        usize::try_from(position).unwrap_or(usize::MAX).min(max)
+    }
+}
+
+/// A change of the cursor position or buttons, see [`Cursor::apply`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct CursorEvent {
+    /// The new position of the cursor.
+    pub position: (usize, usize),
+    /// Whether the left / right button is currently pressed.
+    pub buttons: [bool; 2],
+    /// Whether the left / right button has been pressed since the last event.
+    pub pressed: [bool; 2],
+    /// Whether the left / right button has been released since the last event.
+    pub released: [bool; 2],
+}
+
+/// Iterator over the events of a [`Cursor`], see [`Pointer::cursor_events`].
+#[derive(Debug)]
+pub struct CursorEvents<'a> {
+    pub(super) pointer: &'a mut Pointer,
+    pub(super) cursor: &'a mut Cursor,
+}
+
+impl Iterator for CursorEvents<'_> {
+    type Item = Result<CursorEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.cursor.update(self.pointer) {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+            let Some(event) = self.pointer.wait_for_input_event() else {
+                return Some(Err(Status::UNSUPPORTED.into()));
+            };
+            if let Err(err) = boot::wait_for_event(&mut [event]) {
+                return Some(Err(err.to_err_without_payload()));
+            }
+        }
+    }
+}
+
+/// Arrow image of [`SoftwareCursor`]: `B` is the outline, `W` the fill and
+/// anything else is transparent.
+#[cfg(feature = "alloc")]
+const ARROW: [&[u8]; 19] = [
+    b"B",
+    b"BB",
+    b"BWB",
+    b"BWWB",
+    b"BWWWB",
+    b"BWWWWB",
+    b"BWWWWWB",
+    b"BWWWWWWB",
+    b"BWWWWWWWB",
+    b"BWWWWWWWWB",
+    b"BWWWWWWWWWB",
+    b"BWWWWWWBBBBB",
+    b"BWWWBWWB",
+    b"BWWBBWWB",
+    b"BWB  BWWB",
+    b"BB   BWWB",
+    b"B     BWWB",
+    b"      BWWB",
+    b"       BB",
+];
+
+/// A mouse cursor drawn by software on a [`GraphicsOutput`].
+///
+/// Drawing the cursor saves the pixels below it, which are restored when the
+/// cursor is moved or hidden. The screen must therefore not be drawn below
+/// the cursor while it is shown; hide it first, and show it again afterwards.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default)]
+pub struct SoftwareCursor {
+    /// Position and size of the saved background, if the cursor is shown.
+    shown: Option<((usize, usize), (usize, usize))>,
+    background: Vec<BltPixel>,
+}
+
+#[cfg(feature = "alloc")]
+impl SoftwareCursor {
+    /// Size of the cursor image.
+    pub const SIZE: (usize, usize) = (12, 19);
+
+    /// Create a hidden cursor.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            shown: None,
+            background: Vec::new(),
+        }
+    }
+
+    /// Whether the cursor is shown.
+    #[must_use]
+    pub const fn is_shown(&self) -> bool {
+        self.shown.is_some()
+    }
+
+    /// Draw the cursor with its tip at `position`, removing it from its
+    /// previous position. The cursor is clipped at the edges of the screen.
+    ///
+    /// # Errors
+    ///
+    /// See [`GraphicsOutput::blt`].
+    pub fn show(&mut self, gop: &mut GraphicsOutput, position: (usize, usize)) -> Result {
+        self.hide(gop)?;
+
+        let (width, height) = gop.current_mode_info().resolution();
+        if position.0 >= width || position.1 >= height {
+            return Ok(());
+        }
+        let dims = (
+            Self::SIZE.0.min(width - position.0),
+            Self::SIZE.1.min(height - position.1),
+        );
+
+        self.background = vec![BltPixel::new(0, 0, 0); dims.0 * dims.1];
+        gop.blt(BltOp::VideoToBltBuffer {
+            buffer: &mut self.background,
+            src: position,
+            dest: BltRegion::Full,
+            dims,
+        })?;
+        self.shown = Some((position, dims));
+
+        let mut pixels = self.background.clone();
+        for (y, row) in pixels.chunks_exact_mut(dims.0).enumerate() {
+            for (pixel, shade) in row.iter_mut().zip(ARROW[y]) {
+                match shade {
+                    b'B' => *pixel = BltPixel::new(0, 0, 0),
+                    b'W' => *pixel = BltPixel::new(255, 255, 255),
+                    _ => {}
+                }
+            }
+        }
+        gop.blt(BltOp::BufferToVideo {
+            buffer: &pixels,
+            src: BltRegion::Full,
+            dest: position,
+            dims,
+        })
+    }
+
+    /// Remove the cursor from the screen, restoring the pixels below it.
+    ///
+    /// # Errors
+    ///
+    /// See [`GraphicsOutput::blt`].
+    pub fn hide(&mut self, gop: &mut GraphicsOutput) -> Result {
+        let Some((position, dims)) = self.shown.take() else {
+            return Ok(());
+        };
+        gop.blt(BltOp::BufferToVideo {
+            buffer: &self.background,
+            src: BltRegion::Full,
+            dest: position,
+            dims,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(x: i32, y: i32, left: bool) -> PointerState {
+        PointerState {
+            relative_movement: [x, y, 0],
+            button: [left, false],
+        }
+    }
+
+    #[test]
+    fn test_cursor_movement() {
+        let mode = PointerMode {
+            resolution: [8, 8, 0],
+            has_button: [true, true],
+        };
+        let mut cursor = Cursor::new(&mode, (100, 50)).set_speed(2);
+        assert_eq!(cursor.position(), (50, 25));
+
+        // 8 counts are one millimeter, or two pixels.
+        cursor.apply(&state(8, -16, false));
+        assert_eq!(cursor.position(), (52, 21));
+
+        // Movement below a pixel is accumulated.
+        cursor.apply(&state(3, 0, false));
+        assert_eq!(cursor.position(), (52, 21));
+        cursor.apply(&state(1, 0, false));
+        assert_eq!(cursor.position(), (53, 21));
+
+        // The position is clamped to the screen.
+        cursor.apply(&state(10_000, -10_000, false));
+        assert_eq!(cursor.position(), (99, 0));
+
+        cursor.set_bounds((20, 10));
+        assert_eq!(cursor.position(), (19, 0));
+    }
+
+    #[test]
+    fn test_cursor_buttons() {
+        let mode = PointerMode {
+            resolution: [8, 0, 0],
+            has_button: [true, false],
+        };
+        let mut cursor = Cursor::new(&mode, (100, 50));
+
+        let event = cursor.apply(&state(0, 100, true));
+        // The Y axis is not supported, so the movement is ignored.
+        assert_eq!(event.position, (50, 25));
+        assert_eq!(event.pressed, [true, false]);
+        assert_eq!(event.released, [false, false]);
+
+        let event = cursor.apply(&state(0, 0, true));
+        assert_eq!(event.pressed, [false, false]);
+        assert_eq!(event.buttons, [true, false]);
+
+        let event = cursor.apply(&state(0, 0, false));
+        assert_eq!(event.released, [true, false]);
+        assert_eq!(cursor.buttons(), [false, false]);
+    }
+}
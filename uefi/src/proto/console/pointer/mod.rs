@@ -4,6 +4,13 @@ use crate::proto::unsafe_protocol;
 use crate::{Event, Result, Status, StatusExt};
 use uefi_raw::protocol::console::SimplePointerProtocol;
 
+mod cursor;
+
+pub use cursor::{Cursor, CursorEvent, CursorEvents};
+
+#[cfg(feature = "alloc")]
+pub use cursor::SoftwareCursor;
+
 /// Provides information about a pointer device.
 #[derive(Debug)]
 #[repr(transparent)]
@@ -52,6 +59,19 @@ impl Pointer {
         unsafe { Event::from_ptr(self.0.wait_for_input) }
     }
 
+    /// Returns an iterator that waits for input from the pointer device and
+    /// applies it to `cursor`.
+    ///
+    /// The iterator never ends; each call to `next` blocks until the pointer
+    /// state changes. Use [`Cursor::update`] instead to also handle other
+    /// input, such as the keyboard.
+    pub fn cursor_events<'a>(&'a mut self, cursor: &'a mut Cursor) -> CursorEvents<'a> {
+        CursorEvents {
+            pointer: self,
+            cursor,
+        }
+    }
+
     /// Returns a reference to the pointer device information.
     #[must_use]
     pub const fn mode(&self) -> &PointerMode {
@@ -1,8 +1,10 @@
 //! Abstraction over byte stream devices, also known as serial I/O devices.
 
 use crate::proto::unsafe_protocol;
-use crate::{Result, StatusExt};
+use crate::Status;
+use crate::{duration, Result, StatusExt};
 use core::fmt::Write;
+use core::time::Duration;
 use uefi_raw::protocol::console::serial::SerialIoProtocol;
 
 pub use uefi_raw::protocol::console::serial::{
@@ -61,6 +63,40 @@ impl Serial {
         .to_result()
     }
 
+    /// Applies the line settings of `config`.
+    ///
+    /// Settings that are not set in `config` are reset to the device's
+    /// defaults, except for flow control, which is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::INVALID_PARAMETER`] if the device does not support a
+    ///   setting
+    /// - [`Status::UNSUPPORTED`] if the device does not support flow control
+    /// - [`Status::DEVICE_ERROR`] if the device is not functioning correctly
+    pub fn configure(&mut self, config: &SerialConfig) -> Result {
+        unsafe {
+            (self.0.set_attributes)(
+                &mut self.0,
+                config.baud_rate,
+                config.receive_fifo_depth,
+                config.timeout,
+                config.parity,
+                config.data_bits,
+                config.stop_bits,
+            )
+        }
+        .to_result()?;
+
+        if let Some(flow_control) = config.flow_control {
+            self.update_control_bits(
+                ControlBits::HARDWARE_FLOW_CONTROL_ENABLE,
+                flow_control == FlowControl::Hardware,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Retrieve the device's current control bits.
     pub fn get_control_bits(&self) -> Result<ControlBits> {
         let mut bits = ControlBits::empty();
@@ -75,6 +111,34 @@ impl Serial {
         unsafe { (self.0.set_control_bits)(&mut self.0, bits) }.to_result()
     }
 
+    /// Sets or clears some of the device's control bits, such as
+    /// [`ControlBits::DATA_TERMINAL_READY`], leaving the others unchanged.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::INVALID_PARAMETER`] if `bits` are not all in
+    ///   [`ControlBits::SETTABLE`]
+    /// - [`Status::UNSUPPORTED`] if the device does not support a bit
+    /// - [`Status::DEVICE_ERROR`] if the device is not functioning correctly
+    pub fn update_control_bits(&mut self, bits: ControlBits, enabled: bool) -> Result {
+        if !ControlBits::SETTABLE.contains(bits) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let mut current = self.get_control_bits()? & ControlBits::SETTABLE;
+        current.set(bits, enabled);
+        self.set_control_bits(current)
+    }
+
+    /// Checks whether the device reports `bit` and it is set. For bits the
+    /// device does not report, `not_reported` is returned instead.
+    #[cfg(feature = "embedded-io")]
+    fn has_control_bit(&self, bit: ControlBits, not_reported: bool) -> Result<bool> {
+        if !self.io_mode().control_mask.contains(bit) {
+            return Ok(not_reported);
+        }
+        Ok(self.get_control_bits()?.contains(bit))
+    }
+
     /// Reads data from this device.
     ///
     /// This operation will block until the buffer has been filled with data or
@@ -102,8 +166,189 @@ impl Serial {
     }
 }
 
+/// Flow control of a serial device, see [`SerialConfig::set_flow_control`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FlowControl {
+    /// No flow control.
+    None,
+    /// Hardware flow control, using the RTS and CTS lines.
+    Hardware,
+}
+
+/// Line settings of a serial device, applied with [`Serial::configure`].
+///
+/// Settings that are not set use the device's default, which for UART
+/// devices is 115,200 baud, 8 data bits, no parity and 1 stop bit.
+///
+/// # Examples
+///
+/// ```
+/// use uefi::proto::console::serial::{FlowControl, Parity, Serial, SerialConfig, StopBits};
+/// use uefi::Result;
+///
+/// fn configure_console(serial: &mut Serial) -> Result {
+///     let config = SerialConfig::new()
+///         .set_baud_rate(9600)
+///         .set_data_bits(7)
+///         .set_parity(Parity::EVEN)
+///         .set_stop_bits(StopBits::TWO)
+///         .set_flow_control(FlowControl::Hardware);
+///     serial.configure(&config)
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SerialConfig {
+    baud_rate: u64,
+    receive_fifo_depth: u32,
+    timeout: u32,
+    parity: Parity,
+    data_bits: u8,
+    stop_bits: StopBits,
+    flow_control: Option<FlowControl>,
+}
+
+impl SerialConfig {
+    /// Create a configuration with the device's default settings.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            baud_rate: 0,
+            receive_fifo_depth: 0,
+            timeout: 0,
+            parity: Parity::DEFAULT,
+            data_bits: 0,
+            stop_bits: StopBits::DEFAULT,
+            flow_control: None,
+        }
+    }
+
+    /// Set the baud rate. The device rounds it down to the nearest rate it
+    /// supports.
+    #[must_use]
+    pub const fn set_baud_rate(mut self, baud_rate: u64) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// Set the number of characters the receive FIFO holds.
+    #[must_use]
+    pub const fn set_receive_fifo_depth(mut self, depth: u32) -> Self {
+        self.receive_fifo_depth = depth;
+        self
+    }
+
+    /// Set the time to wait for characters before timing out on reads and
+    /// writes. The timeout is rounded up to microseconds, with a minimum of
+    /// one.
+    #[must_use]
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = u32::try_from(duration::as_micros_ceil(timeout))
+            .unwrap_or(u32::MAX)
+            .max(1);
+        self
+    }
+
+    /// Set the parity of each character.
+    #[must_use]
+    pub const fn set_parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    /// Set the number of data bits of each character, usually 5 to 8.
+    #[must_use]
+    pub const fn set_data_bits(mut self, data_bits: u8) -> Self {
+        self.data_bits = data_bits;
+        self
+    }
+
+    /// Set the number of stop bits of each character.
+    #[must_use]
+    pub const fn set_stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+
+    /// Set the flow control.
+    #[must_use]
+    pub const fn set_flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = Some(flow_control);
+        self
+    }
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Write for Serial {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
         self.write(s.as_bytes()).map_err(|_| core::fmt::Error)
     }
 }
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ErrorType for Serial {
+    type Error = crate::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Read for Serial {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // Reads time out if fewer bytes than requested are available, so
+        // retry until at least one byte has been read.
+        loop {
+            match Self::read(self, buf) {
+                Ok(()) => return Ok(buf.len()),
+                Err(err) if *err.data() > 0 => return Ok(*err.data()),
+                Err(err) if err.status() == Status::TIMEOUT => {}
+                Err(err) => return Err(err.to_err_without_payload()),
+            }
+        }
+    }
+}
+
+/// Devices that do not report whether their input buffer is empty are
+/// always considered ready, reads then wait up to the timeout.
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ReadReady for Serial {
+    fn read_ready(&mut self) -> Result<bool> {
+        Ok(!self.has_control_bit(ControlBits::INPUT_BUFFER_EMPTY, false)?)
+    }
+}
+
+/// Devices that do not report whether their output buffer is empty are
+/// always considered ready.
+#[cfg(feature = "embedded-io")]
+impl embedded_io::WriteReady for Serial {
+    fn write_ready(&mut self) -> Result<bool> {
+        self.has_control_bit(ControlBits::OUTPUT_BUFFER_EMPTY, true)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Write for Serial {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match Self::write(self, buf) {
+                Ok(()) => return Ok(buf.len()),
+                Err(err) if *err.data() > 0 => return Ok(*err.data()),
+                Err(err) if err.status() == Status::TIMEOUT => {}
+                Err(err) => return Err(err.to_err_without_payload()),
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result {
+        // Writes block until the data has been written.
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+//! Double-buffered drawing for the Graphics Output Protocol.
+//!
+//! Writing to the frame buffer pixel by pixel is very slow on some firmware,
+//! and not possible at all in [`PixelFormat::BltOnly`] modes. A [`Surface`]
+//! is a back buffer in system memory instead: drawing into it is as fast as
+//! writing to memory, and [`Surface::flush`] copies only the changed regions
+//! to the screen, with one blit per region:
+//!
+//! ```no_run
+//! use uefi::boot::ScopedProtocol;
+//! use uefi::proto::console::gop::{BltPixel, GraphicsOutput};
+//! use uefi::proto::console::surface::Surface;
+//! # fn test(mut gop: ScopedProtocol<GraphicsOutput>) -> uefi::Result {
+//! let mut surface = Surface::for_mode(&gop.current_mode_info());
+//! surface.fill_rect((10, 10), (100, 50), BltPixel::new(0x20, 0x60, 0xc0));
+//! surface.set_pixel(200, 100, BltPixel::new(0xff, 0xff, 0xff));
+//! surface.flush(&mut gop)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `embedded-graphics` feature, a `Surface` is also an
+//! `embedded-graphics` draw target.
+//!
+//! [`PixelFormat::BltOnly`]: super::gop::PixelFormat::BltOnly
+
+use super::gop::{BltOp, BltPixel, BltRegion, GraphicsOutput, ModeInfo};
+use crate::Result;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Number of separate dirty regions that are tracked. Further regions are
+/// merged into the existing ones, so that a flush never needs more blits.
+const MAX_DIRTY_REGIONS: usize = 8;
+
+/// A back buffer of [`BltPixel`]s that tracks which regions changed since the
+/// last [`flush`], see the [module documentation](self).
+///
+/// Coordinates are `(x, y)` and sizes `(width, height)` in pixels, like in
+/// [`BltOp`]. Drawing outside of the surface is clipped.
+///
+/// [`flush`]: Self::flush
+#[derive(Clone, Debug)]
+pub struct Surface {
+    pixels: Vec<BltPixel>,
+    width: usize,
+    height: usize,
+    dirty: Vec<Region>,
+}
+
+impl Surface {
+    /// Create a black surface of `width` by `height` pixels. All of it is
+    /// flushed on the first [`flush`](Self::flush).
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut surface = Self {
+            pixels: vec![BltPixel::new(0, 0, 0); width * height],
+            width,
+            height,
+            dirty: Vec::with_capacity(MAX_DIRTY_REGIONS + 1),
+        };
+        surface.mark_all_dirty();
+        surface
+    }
+
+    /// Create a black surface with the resolution of a graphics mode.
+    #[must_use]
+    pub fn for_mode(info: &ModeInfo) -> Self {
+        let (width, height) = info.resolution();
+        Self::new(width, height)
+    }
+
+    /// Get the `(width, height)` of the surface.
+    #[must_use]
+    pub const fn resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Get all pixels, row by row.
+    #[must_use]
+    pub fn pixels(&self) -> &[BltPixel] {
+        &self.pixels
+    }
+
+    /// Get the pixel at `(x, y)`, or `None` if it is outside of the surface.
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<BltPixel> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Set the pixel at `(x, y)`.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: BltPixel) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+            self.mark_dirty((x, y), (1, 1));
+        }
+    }
+
+    /// Fill the rectangle at `dest` of size `dims` with `color`.
+    pub fn fill_rect(&mut self, dest: (usize, usize), dims: (usize, usize), color: BltPixel) {
+        let Some(region) = self.clip(dest, dims) else {
+            return;
+        };
+        for y in region.y..region.y + region.height {
+            let row = y * self.width;
+            self.pixels[row + region.x..row + region.x + region.width].fill(color);
+        }
+        self.add_dirty(region);
+    }
+
+    /// Copy `pixels`, an image of size `dims` stored row by row, into the
+    /// rectangle at `dest`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pixels` has fewer than `dims.0 * dims.1` elements.
+    pub fn copy_from(&mut self, dest: (usize, usize), dims: (usize, usize), pixels: &[BltPixel]) {
+        assert!(
+            dims.0
+                .checked_mul(dims.1)
+                .is_some_and(|len| len <= pixels.len()),
+            "the pixels are smaller than the image"
+        );
+        let Some(region) = self.clip(dest, dims) else {
+            return;
+        };
+        for row in 0..region.height {
+            let src = row * dims.0;
+            let dest = (region.y + row) * self.width + region.x;
+            self.pixels[dest..dest + region.width]
+                .copy_from_slice(&pixels[src..src + region.width]);
+        }
+        self.add_dirty(region);
+    }
+
+    /// Fill the whole surface with `color`.
+    pub fn clear(&mut self, color: BltPixel) {
+        self.pixels.fill(color);
+        self.mark_all_dirty();
+    }
+
+    /// Get the pixels of row `y` for modifying them. The whole row is marked
+    /// as changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is outside of the surface.
+    pub fn row_mut(&mut self, y: usize) -> &mut [BltPixel] {
+        assert!(y < self.height, "row {y} is outside of the surface");
+        self.mark_dirty((0, y), (self.width, 1));
+        &mut self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Mark a rectangle as changed, so that it is copied on the next
+    /// [`flush`](Self::flush).
+    pub fn mark_dirty(&mut self, dest: (usize, usize), dims: (usize, usize)) {
+        if let Some(region) = self.clip(dest, dims) {
+            self.add_dirty(region);
+        }
+    }
+
+    /// Mark the whole surface as changed.
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.clear();
+        self.mark_dirty((0, 0), (self.width, self.height));
+    }
+
+    /// Get the regions that changed since the last flush, as
+    /// `((x, y), (width, height))`. Regions don't overlap.
+    pub fn dirty_regions(&self) -> impl Iterator<Item = ((usize, usize), (usize, usize))> + '_ {
+        self.dirty
+            .iter()
+            .map(|region| ((region.x, region.y), (region.width, region.height)))
+    }
+
+    /// Copy the changed regions to the top-left corner of the screen of
+    /// `gop`. See [`flush_at`](Self::flush_at).
+    pub fn flush(&mut self, gop: &mut GraphicsOutput) -> Result {
+        self.flush_at(gop, (0, 0))
+    }
+
+    /// Copy the changed regions to the screen of `gop`, with the top-left
+    /// corner of the surface at `dest`. Parts that are outside of the screen
+    /// are not copied.
+    ///
+    /// # Errors
+    ///
+    /// See [`GraphicsOutput::blt`]. The regions that were not copied stay
+    /// marked as changed.
+    pub fn flush_at(&mut self, gop: &mut GraphicsOutput, dest: (usize, usize)) -> Result {
+        let (screen_width, screen_height) = gop.current_mode_info().resolution();
+        while let Some(region) = self.dirty.last().copied() {
+            let x = dest.0.saturating_add(region.x);
+            let y = dest.1.saturating_add(region.y);
+            let width = region.width.min(screen_width.saturating_sub(x));
+            let height = region.height.min(screen_height.saturating_sub(y));
+            if width > 0 && height > 0 {
+                gop.blt(BltOp::BufferToVideo {
+                    buffer: &self.pixels,
+                    src: BltRegion::SubRectangle {
+                        coords: (region.x, region.y),
+                        px_stride: self.width,
+                    },
+                    dest: (x, y),
+                    dims: (width, height),
+                })?;
+            }
+            self.dirty.pop();
+        }
+        Ok(())
+    }
+
+    /// Clip a rectangle to the surface.
+    fn clip(&self, dest: (usize, usize), dims: (usize, usize)) -> Option<Region> {
+        let (x, y) = dest;
+        let width = dims.0.min(self.width.saturating_sub(x));
+        let height = dims.1.min(self.height.saturating_sub(y));
+        (width > 0 && height > 0).then_some(Region {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Add a region to the dirty regions, merging overlapping regions, and
+    /// the regions that grow the least if there are too many.
+    fn add_dirty(&mut self, mut region: Region) {
+        // Merging can make the region overlap others, so repeat until it
+        // doesn't.
+        while let Some(i) = self.dirty.iter().position(|other| other.overlaps(&region)) {
+            region = region.union(&self.dirty.swap_remove(i));
+        }
+        self.dirty.push(region);
+
+        if self.dirty.len() > MAX_DIRTY_REGIONS {
+            let region = self.dirty.pop().unwrap();
+            let (i, _) = self
+                .dirty
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, other)| other.union(&region).area() - other.area())
+                .unwrap();
+            let region = region.union(&self.dirty.swap_remove(i));
+            self.add_dirty(region);
+        }
+    }
+}
+
+/// A non-empty rectangle within a [`Surface`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Region {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Region {
+    const fn overlaps(&self, other: &Self) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+
+    const fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: BltPixel = BltPixel::new(0xff, 0xff, 0xff);
+
+    fn flushed(width: usize, height: usize) -> Surface {
+        let mut surface = Surface::new(width, height);
+        surface.dirty.clear();
+        surface
+    }
+
+    fn dirty(surface: &Surface) -> Vec<((usize, usize), (usize, usize))> {
+        let mut regions: Vec<_> = surface.dirty_regions().collect();
+        regions.sort();
+        regions
+    }
+
+    #[test]
+    fn test_drawing() {
+        let mut surface = Surface::new(4, 3);
+        assert_eq!(dirty(&surface), [((0, 0), (4, 3))]);
+
+        surface.fill_rect((2, 1), (10, 10), WHITE);
+        surface.set_pixel(0, 0, WHITE);
+        surface.set_pixel(4, 0, WHITE);
+        assert_eq!(surface.pixel(0, 0), Some(WHITE));
+        assert_eq!(surface.pixel(1, 0), Some(BltPixel::new(0, 0, 0)));
+        assert_eq!(surface.pixel(3, 2), Some(WHITE));
+        assert_eq!(surface.pixel(4, 0), None);
+        surface.row_mut(1)[0] = WHITE;
+        let white = surface.pixels().iter().filter(|p| **p == WHITE).count();
+        assert_eq!(white, 1 + 4 + 1);
+
+        // Copied images are clipped too.
+        let mut surface = flushed(4, 3);
+        let red = BltPixel::new(0xff, 0, 0);
+        surface.copy_from((3, 1), (2, 2), &[red, WHITE, WHITE, red]);
+        assert_eq!(surface.pixel(3, 1), Some(red));
+        assert_eq!(surface.pixel(3, 2), Some(WHITE));
+        assert_eq!(dirty(&surface), [((3, 1), (1, 2))]);
+    }
+
+    #[test]
+    fn test_dirty_regions() {
+        let mut surface = flushed(100, 100);
+        assert_eq!(dirty(&surface), []);
+
+        // Separate regions are kept apart, and clipped to the surface.
+        surface.mark_dirty((0, 0), (10, 10));
+        surface.mark_dirty((90, 90), (20, 20));
+        surface.mark_dirty((200, 0), (10, 10));
+        assert_eq!(dirty(&surface), [((0, 0), (10, 10)), ((90, 90), (10, 10))]);
+
+        // A region that overlaps both is merged with both.
+        surface.mark_dirty((5, 5), (90, 90));
+        assert_eq!(dirty(&surface), [((0, 0), (100, 100))]);
+
+        // Too many regions are merged with their nearest neighbor.
+        let mut surface = flushed(100, 100);
+        for i in 0..MAX_DIRTY_REGIONS {
+            surface.set_pixel(i * 10, 0, WHITE);
+        }
+        surface.set_pixel(1, 1, WHITE);
+        let regions = dirty(&surface);
+        assert_eq!(regions.len(), MAX_DIRTY_REGIONS);
+        assert_eq!(regions[0], ((0, 0), (2, 2)));
+    }
+}
@@ -0,0 +1,640 @@
+//! Module for [`AnsiWriter`].
+
+use super::{Color, Output};
+use crate::{CStr16, Result, Status};
+use core::fmt;
+
+/// Maximum number of parameters of an escape sequence. Further parameters
+/// are ignored.
+const MAX_PARAMS: usize = 16;
+
+/// The colors of the UEFI console, in the order of the ANSI color numbers.
+const ANSI_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Brown,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightGray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Approximate RGB values of [`ANSI_COLORS`], to map other colors to the
+/// nearest one.
+const ANSI_RGB: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [170, 0, 0],
+    [0, 170, 0],
+    [170, 85, 0],
+    [0, 0, 170],
+    [170, 0, 170],
+    [0, 170, 170],
+    [170, 170, 170],
+    [85, 85, 85],
+    [255, 85, 85],
+    [85, 255, 85],
+    [255, 255, 85],
+    [85, 85, 255],
+    [255, 85, 255],
+    [85, 255, 255],
+    [255, 255, 255],
+];
+
+/// [`fmt::Write`] adapter for an [`Output`] that interprets ANSI escape
+/// sequences, so that text written for terminals, such as colored log
+/// messages or the output of terminal UI crates, can be shown on the UEFI
+/// console unchanged.
+///
+/// These sequences are translated:
+///
+/// - Colors with `ESC [ … m`: the 16 standard colors in the foreground and
+///   the background, bold as bright foreground colors, reverse video, and
+///   256-color and RGB colors, which are mapped to the nearest standard
+///   color. The console only supports 8 background colors, so bright
+///   background colors are drawn as their dark variants.
+/// - Cursor movement with `ESC [ A`/`B`/`C`/`D`/`E`/`F`/`G`/`H`/`d`/`f`,
+///   which is limited to the screen.
+/// - Saving and restoring the cursor with `ESC [ s`/`u` and `ESC 7`/`8`.
+/// - Erasing with `ESC [ J` and `ESC [ K`. The bottom-right cell of the
+///   screen is not erased unless the whole screen is cleared, because
+///   writing to it scrolls the screen.
+/// - Showing and hiding the cursor with `ESC [ ? 25 h`/`l`.
+/// - Resetting the colors and clearing the screen with `ESC c`.
+///
+/// Other escape sequences are skipped. Sequences may be split over several
+/// writes.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::fmt::Write;
+/// use uefi::proto::console::text::AnsiWriter;
+/// use uefi::system;
+///
+/// system::with_stdout(|stdout| {
+///     let mut stdout = AnsiWriter::new(stdout);
+///     writeln!(stdout, "\x1b[1;32mOK\x1b[0m booted").unwrap();
+/// });
+/// ```
+#[derive(Debug)]
+pub struct AnsiWriter<'out> {
+    output: &'out mut Output,
+    parser: Parser,
+    attributes: Attributes,
+    default_colors: (Color, Color),
+    saved_cursor: (usize, usize),
+}
+
+impl<'out> AnsiWriter<'out> {
+    /// Create a writer for `output`. The current colors of `output` are
+    /// used as the default colors.
+    pub fn new(output: &'out mut Output) -> Self {
+        let default_colors = output.colors();
+        Self {
+            output,
+            parser: Parser::default(),
+            attributes: Attributes::default(),
+            default_colors,
+            saved_cursor: (0, 0),
+        }
+    }
+
+    /// Get the output.
+    pub fn get_mut(&mut self) -> &mut Output {
+        self.output
+    }
+
+    /// Get the output back.
+    #[must_use]
+    pub fn into_inner(self) -> &'out mut Output {
+        self.output
+    }
+
+    /// Get the `(columns, rows)` of the screen.
+    fn screen_size(&self) -> (usize, usize) {
+        match self.output.current_mode() {
+            Ok(Some(mode)) => (mode.columns(), mode.rows()),
+            _ => (80, 25),
+        }
+    }
+
+    fn execute(&mut self, command: Command) -> Result {
+        let (column, row) = self.output.cursor_position();
+        let (columns, rows) = self.screen_size();
+        let (last_column, last_row) = (columns.saturating_sub(1), rows.saturating_sub(1));
+        let move_to = |output: &mut Output, column: usize, row: usize| {
+            output.set_cursor_position(column.min(last_column), row.min(last_row))
+        };
+        match command {
+            Command::Sgr(params) => {
+                self.attributes.apply_sgr(params.as_slice());
+                let (foreground, background) = self.attributes.colors(self.default_colors);
+                self.output.set_color(foreground, background)
+            }
+            Command::Up(n) => move_to(self.output, column, row.saturating_sub(n)),
+            Command::Down(n) => move_to(self.output, column, row.saturating_add(n)),
+            Command::Forward(n) => move_to(self.output, column.saturating_add(n), row),
+            Command::Back(n) => move_to(self.output, column.saturating_sub(n), row),
+            Command::NextLine(n) => move_to(self.output, 0, row.saturating_add(n)),
+            Command::PreviousLine(n) => move_to(self.output, 0, row.saturating_sub(n)),
+            Command::Column(column) => move_to(self.output, column, row),
+            Command::Row(row) => move_to(self.output, column, row),
+            Command::Position { column, row } => move_to(self.output, column, row),
+            Command::EraseDisplay(mode) => {
+                match mode {
+                    0 => self.erase((column, row), (last_column, last_row), (columns, rows))?,
+                    1 => self.erase((0, 0), (column, row), (columns, rows))?,
+                    _ => self.output.clear()?,
+                }
+                move_to(self.output, column, row)
+            }
+            Command::EraseLine(mode) => {
+                match mode {
+                    0 => self.erase((column, row), (last_column, row), (columns, rows))?,
+                    1 => self.erase((0, row), (column, row), (columns, rows))?,
+                    _ => self.erase((0, row), (last_column, row), (columns, rows))?,
+                }
+                move_to(self.output, column, row)
+            }
+            Command::SaveCursor => {
+                self.saved_cursor = (column, row);
+                Ok(())
+            }
+            Command::RestoreCursor => {
+                let (column, row) = self.saved_cursor;
+                move_to(self.output, column, row)
+            }
+            Command::ShowCursor(visible) => match self.output.enable_cursor(visible) {
+                Err(err) if err.status() == Status::UNSUPPORTED => Ok(()),
+                result => result,
+            },
+            Command::Reset => {
+                self.attributes = Attributes::default();
+                let (foreground, background) = self.default_colors;
+                self.output.set_color(foreground, background)?;
+                self.output.clear()
+            }
+        }
+    }
+
+    /// Write spaces from `start` to `end`, inclusive, except for the
+    /// bottom-right cell of the screen.
+    fn erase(
+        &mut self,
+        start: (usize, usize),
+        end: (usize, usize),
+        screen: (usize, usize),
+    ) -> Result {
+        let (columns, rows) = screen;
+        for row in start.1..=end.1 {
+            let first = if row == start.1 { start.0 } else { 0 };
+            let mut end_column = if row == end.1 { end.0 + 1 } else { columns };
+            if row + 1 == rows && end_column == columns {
+                end_column -= 1;
+            }
+            if first < end_column {
+                self.output.set_cursor_position(first, row)?;
+                self.write_spaces(end_column - first)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_spaces(&mut self, mut count: usize) -> Result {
+        const CHUNK: usize = 32;
+        let mut buf = [u16::from(b' '); CHUNK + 1];
+        while count > 0 {
+            let len = count.min(CHUNK);
+            buf[len] = 0;
+            let spaces = CStr16::from_u16_with_nul(&buf[..=len]).unwrap();
+            self.output.output_string(spaces)?;
+            buf[len] = u16::from(b' ');
+            count -= len;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Write for AnsiWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Plain text is written in runs between the escape sequences.
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            let step = self.parser.advance(c);
+            if matches!(step, Step::Print) {
+                continue;
+            }
+            if start < i {
+                self.output.write_str(&s[start..i])?;
+            }
+            start = i + c.len_utf8();
+            if let Step::Command(command) = step {
+                self.execute(command).map_err(|_| fmt::Error)?;
+            }
+        }
+        if start < s.len() {
+            self.output.write_str(&s[start..])?;
+        }
+        Ok(())
+    }
+}
+
+/// Colors and attributes that are set with SGR sequences.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Attributes {
+    /// ANSI color number, or `None` for the default color.
+    foreground: Option<u8>,
+    background: Option<u8>,
+    bold: bool,
+    reverse: bool,
+}
+
+impl Attributes {
+    fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Self::default();
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                7 => self.reverse = true,
+                27 => self.reverse = false,
+                p @ 30..=37 => self.foreground = Some(p as u8 - 30),
+                39 => self.foreground = None,
+                p @ 40..=47 => self.background = Some(p as u8 - 40),
+                49 => self.background = None,
+                p @ 90..=97 => self.foreground = Some(p as u8 - 90 + 8),
+                p @ 100..=107 => self.background = Some(p as u8 - 100 + 8),
+                p @ (38 | 48) => {
+                    let color = match params.get(i + 1) {
+                        Some(5) => {
+                            let color = params.get(i + 2).map(|n| color_256(*n));
+                            i += 2;
+                            color
+                        }
+                        Some(2) => {
+                            let color = params
+                                .get(i + 2..i + 5)
+                                .map(|rgb| nearest_color([0, 1, 2].map(|i| rgb[i].min(255) as u8)));
+                            i += 4;
+                            color
+                        }
+                        _ => None,
+                    };
+                    if let Some(color) = color {
+                        if p == 38 {
+                            self.foreground = Some(color);
+                        } else {
+                            self.background = Some(color);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// Get the console colors, with `defaults` for colors that are not set.
+    fn colors(&self, defaults: (Color, Color)) -> (Color, Color) {
+        let mut foreground = self.foreground.map_or(defaults.0 as usize, |color| {
+            ANSI_COLORS[usize::from(color)] as usize
+        });
+        if self.bold {
+            foreground |= 8;
+        }
+        let background = self.background.map_or(defaults.1 as usize, |color| {
+            ANSI_COLORS[usize::from(color)] as usize
+        });
+        let (foreground, background) = if self.reverse {
+            (background, foreground)
+        } else {
+            (foreground, background)
+        };
+        // Only the dark colors can be used as background.
+        (
+            Color::from_index(foreground),
+            Color::from_index(background & 0x7),
+        )
+    }
+}
+
+/// Map a color of the 256-color palette to an ANSI color number.
+fn color_256(n: u16) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match n {
+        0..=15 => n as u8,
+        16..=231 => {
+            let n = usize::from(n - 16);
+            nearest_color([LEVELS[n / 36], LEVELS[n / 6 % 6], LEVELS[n % 6]])
+        }
+        232..=255 => {
+            let gray = 8 + 10 * (n - 232) as u8;
+            nearest_color([gray; 3])
+        }
+        _ => 7,
+    }
+}
+
+/// Get the ANSI color number of the standard color that is nearest to `rgb`.
+fn nearest_color(rgb: [u8; 3]) -> u8 {
+    let distance = |color: &[u8; 3]| -> u32 {
+        color
+            .iter()
+            .zip(rgb)
+            .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+            .sum()
+    };
+    (0..16u8)
+        .min_by_key(|index| distance(&ANSI_RGB[usize::from(*index)]))
+        .unwrap()
+}
+
+/// Parameters of an escape sequence.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Params {
+    values: [u16; MAX_PARAMS],
+    len: usize,
+}
+
+impl Params {
+    fn as_slice(&self) -> &[u16] {
+        &self.values[..self.len]
+    }
+
+    /// Get parameter `index`, or `default` if it is missing or zero.
+    fn get_or(&self, index: usize, default: u16) -> usize {
+        match self.as_slice().get(index) {
+            Some(0) | None => usize::from(default),
+            Some(value) => usize::from(*value),
+        }
+    }
+
+    fn get(&self, index: usize) -> u16 {
+        self.as_slice().get(index).copied().unwrap_or(0)
+    }
+
+    fn push_digit(&mut self, digit: u16) {
+        if self.len == 0 {
+            self.len = 1;
+        }
+        let value = &mut self.values[self.len - 1];
+        *value = value.saturating_mul(10).saturating_add(digit);
+    }
+
+    fn next(&mut self) {
+        if self.len == 0 {
+            self.len = 1;
+        }
+        if self.len < MAX_PARAMS {
+            self.len += 1;
+        }
+    }
+}
+
+/// A translated escape sequence. Positions are 0-based.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Command {
+    Sgr(Params),
+    Up(usize),
+    Down(usize),
+    Forward(usize),
+    Back(usize),
+    NextLine(usize),
+    PreviousLine(usize),
+    Column(usize),
+    Row(usize),
+    Position { column: usize, row: usize },
+    EraseDisplay(u16),
+    EraseLine(u16),
+    SaveCursor,
+    RestoreCursor,
+    ShowCursor(bool),
+    Reset,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// What to do with a character.
+#[derive(Debug, Eq, PartialEq)]
+enum Step {
+    /// The character is text.
+    Print,
+    /// The character is part of an escape sequence.
+    Consumed,
+    /// The character completes an escape sequence.
+    Command(Command),
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Parser {
+    state: State,
+    params: Params,
+    private: bool,
+    intermediate: bool,
+}
+
+impl Parser {
+    fn advance(&mut self, c: char) -> Step {
+        match self.state {
+            State::Ground => {
+                if c == '\x1b' {
+                    self.state = State::Escape;
+                    Step::Consumed
+                } else {
+                    Step::Print
+                }
+            }
+            State::Escape => {
+                self.state = State::Ground;
+                match c {
+                    '[' => {
+                        *self = Self {
+                            state: State::Csi,
+                            ..Self::default()
+                        };
+                        Step::Consumed
+                    }
+                    '7' => Step::Command(Command::SaveCursor),
+                    '8' => Step::Command(Command::RestoreCursor),
+                    'c' => Step::Command(Command::Reset),
+                    // Intermediate characters, as in `ESC ( B`, are followed
+                    // by the final character.
+                    ' '..='/' => {
+                        self.state = State::Escape;
+                        Step::Consumed
+                    }
+                    _ => Step::Consumed,
+                }
+            }
+            State::Csi => match c {
+                '0'..='9' => {
+                    self.params.push_digit(c as u16 - u16::from(b'0'));
+                    Step::Consumed
+                }
+                ';' => {
+                    self.params.next();
+                    Step::Consumed
+                }
+                '<'..='?' => {
+                    self.private = true;
+                    Step::Consumed
+                }
+                ' '..='/' => {
+                    self.intermediate = true;
+                    Step::Consumed
+                }
+                '@'..='~' => {
+                    self.state = State::Ground;
+                    match self.command(c) {
+                        Some(command) => Step::Command(command),
+                        None => Step::Consumed,
+                    }
+                }
+                _ => {
+                    // Invalid sequence.
+                    self.state = State::Ground;
+                    Step::Consumed
+                }
+            },
+        }
+    }
+
+    fn command(&self, c: char) -> Option<Command> {
+        let params = &self.params;
+        if self.intermediate {
+            return None;
+        }
+        if self.private {
+            return match (c, params.as_slice()) {
+                ('h', [25]) => Some(Command::ShowCursor(true)),
+                ('l', [25]) => Some(Command::ShowCursor(false)),
+                _ => None,
+            };
+        }
+        let n = params.get_or(0, 1);
+        Some(match c {
+            'm' => Command::Sgr(*params),
+            'A' => Command::Up(n),
+            'B' => Command::Down(n),
+            'C' => Command::Forward(n),
+            'D' => Command::Back(n),
+            'E' => Command::NextLine(n),
+            'F' => Command::PreviousLine(n),
+            'G' => Command::Column(n - 1),
+            'd' => Command::Row(n - 1),
+            'H' | 'f' => Command::Position {
+                column: params.get_or(1, 1) - 1,
+                row: n - 1,
+            },
+            'J' => Command::EraseDisplay(params.get(0)),
+            'K' => Command::EraseLine(params.get(0)),
+            's' => Command::SaveCursor,
+            'u' => Command::RestoreCursor,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// Parse `text`, returning the printed text and the commands.
+    fn parse(parser: &mut Parser, text: &str) -> (String, Vec<Command>) {
+        let mut printed = String::new();
+        let mut commands = Vec::new();
+        for c in text.chars() {
+            match parser.advance(c) {
+                Step::Print => printed.push(c),
+                Step::Consumed => {}
+                Step::Command(command) => commands.push(command),
+            }
+        }
+        (printed, commands)
+    }
+
+    fn sgr(params: &[u16]) -> Command {
+        let mut sgr = Params::default();
+        sgr.values[..params.len()].copy_from_slice(params);
+        sgr.len = params.len();
+        Command::Sgr(sgr)
+    }
+
+    #[test]
+    fn test_parser() {
+        let mut parser = Parser::default();
+        let (printed, commands) = parse(
+            &mut parser,
+            "a\x1b[1;31mb\x1b[mc\x1b[;5H\x1b[2J\x1b[K\x1b[3A\x1b[?25l\x1b7\x1b(B\x1b[1 qd",
+        );
+        assert_eq!(printed, "abcd");
+        assert_eq!(
+            commands,
+            [
+                sgr(&[1, 31]),
+                sgr(&[]),
+                Command::Position { column: 4, row: 0 },
+                Command::EraseDisplay(2),
+                Command::EraseLine(0),
+                Command::Up(3),
+                Command::ShowCursor(false),
+                Command::SaveCursor,
+            ]
+        );
+
+        // Sequences split over several writes.
+        assert_eq!(parse(&mut parser, "x\x1b[4").0, "x");
+        assert_eq!(parse(&mut parser, "2mB"), ("B".into(), [sgr(&[42])].into()));
+    }
+
+    #[test]
+    fn test_sgr() {
+        let defaults = (Color::LightGray, Color::Black);
+        let mut attributes = Attributes::default();
+        assert_eq!(attributes.colors(defaults), defaults);
+
+        attributes.apply_sgr(&[1, 34, 43]);
+        assert_eq!(
+            attributes.colors(defaults),
+            (Color::LightBlue, Color::Brown)
+        );
+        attributes.apply_sgr(&[22, 7]);
+        assert_eq!(attributes.colors(defaults), (Color::Brown, Color::Blue));
+        attributes.apply_sgr(&[0, 97, 101]);
+        assert_eq!(attributes.colors(defaults), (Color::White, Color::Red));
+        attributes.apply_sgr(&[39, 49]);
+        assert_eq!(attributes.colors(defaults), defaults);
+
+        // 256 colors and RGB colors.
+        attributes.apply_sgr(&[38, 5, 196, 48, 2, 0, 0, 160, 1]);
+        assert_eq!(attributes.colors(defaults), (Color::LightRed, Color::Blue));
+        attributes.apply_sgr(&[38, 5, 2]);
+        assert_eq!(
+            attributes.colors(defaults),
+            (Color::LightGreen, Color::Blue)
+        );
+        attributes.apply_sgr(&[]);
+        assert_eq!(attributes, Attributes::default());
+    }
+}
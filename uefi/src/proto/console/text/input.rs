@@ -1,6 +1,8 @@
+use crate::proto::completion::Timer;
 use crate::proto::unsafe_protocol;
-use crate::{Char16, Event, Result, Status, StatusExt};
+use crate::{boot, Char16, Event, Result, Status, StatusExt};
 use core::mem::MaybeUninit;
+use core::time::Duration;
 use uefi_raw::protocol::console::{InputKey, SimpleTextInputProtocol};
 
 /// Interface for text-based input devices.
@@ -76,10 +78,60 @@ impl Input {
         }
     }
 
+    /// Waits up to `timeout` for a keystroke and reads it.
+    ///
+    /// Returns `None` if no key was pressed before the timeout elapsed. A key
+    /// that is already available is returned right away. This is intended for
+    /// the "press any key within N seconds" pattern of boot menus.
+    ///
+    /// This must be called at [`Tpl::APPLICATION`], see
+    /// [`boot::wait_for_event`].
+    ///
+    /// [`Tpl::APPLICATION`]: crate::boot::Tpl::APPLICATION
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if there was an issue with the input device
+    /// - [`Status::UNSUPPORTED`] if the device has no key event
+    /// - [`Status::OUT_OF_RESOURCES`] if the timer event could not be created
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use uefi::proto::console::text::Input;
+    /// use uefi::Result;
+    ///
+    /// fn interrupt_boot(input: &mut Input) -> Result<bool> {
+    ///     let key = input.read_key_timeout(Duration::from_secs(3))?;
+    ///     Ok(key.is_some())
+    /// }
+    /// ```
+    pub fn read_key_timeout(&mut self, timeout: Duration) -> Result<Option<Key>> {
+        if let Some(key) = self.read_key()? {
+            return Ok(Some(key));
+        }
+
+        let key_event = self.wait_for_key_event().ok_or(Status::UNSUPPORTED)?;
+        let timer = Timer::new(timeout)?;
+        // Safety: the clone of the timer event does not outlive `timer`.
+        let mut events = [key_event, unsafe { timer.event().unsafe_clone() }];
+        loop {
+            let index =
+                boot::wait_for_event(&mut events).map_err(|err| err.to_err_without_payload())?;
+            if index == 1 {
+                return Ok(None);
+            }
+            // The key event may be signaled without a key being available,
+            // in which case keep waiting.
+            if let Some(key) = self.read_key()? {
+                return Ok(Some(key));
+            }
+        }
+    }
+
     /// Event to be used with [`boot::wait_for_event`] in order to wait
     /// for a key to be available
-    ///
-    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
     #[must_use]
     pub fn wait_for_key_event(&self) -> Option<Event> {
         unsafe { Event::from_ptr(self.0.wait_for_key) }
@@ -106,6 +158,21 @@ impl From<InputKey> for Key {
     }
 }
 
+impl From<Key> for InputKey {
+    fn from(key: Key) -> Self {
+        match key {
+            Key::Printable(c) => Self {
+                scan_code: ScanCode::NULL.0,
+                unicode_char: c.into(),
+            },
+            Key::Special(scan_code) => Self {
+                scan_code: scan_code.0,
+                unicode_char: 0,
+            },
+        }
+    }
+}
+
 newtype_enum! {
 /// A keyboard scan code
 ///
@@ -0,0 +1,302 @@
+use super::Key;
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::mem::MaybeUninit;
+use core::ptr;
+use uefi_raw::protocol::console::{InputKey, KeyData, KeyState, SimpleTextInputExProtocol};
+
+pub use uefi_raw::protocol::console::{KeyShiftState, KeyToggleState};
+
+/// Extended interface for text-based input devices.
+///
+/// In addition to [`Input`], this reports the state of the modifier and lock
+/// keys with each keystroke, allows setting the lock keys, and can call a
+/// function whenever a specific key is pressed.
+///
+/// [`Input`]: super::Input
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(SimpleTextInputExProtocol::GUID)]
+pub struct InputEx(SimpleTextInputExProtocol);
+
+impl InputEx {
+    /// Resets the input device hardware.
+    ///
+    /// The `extended_verification` parameter is used to request that UEFI
+    /// performs an extended check and reset of the input device.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if the device is malfunctioning and cannot be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification) }.to_result()
+    }
+
+    /// Reads the next keystroke from the input device, if any, along with the
+    /// state of the modifier and lock keys.
+    ///
+    /// If [`KeyToggleState::KEY_STATE_EXPOSED`] has been enabled with
+    /// [`set_toggle_state`], a keystroke is also returned when only a modifier
+    /// key changed. [`KeyStroke::key`] is `None` for those.
+    ///
+    /// Use [`wait_for_key_event`] with the [`boot::wait_for_event`]
+    /// interface in order to wait for a key to be pressed.
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    /// [`set_toggle_state`]: Self::set_toggle_state
+    /// [`wait_for_key_event`]: Self::wait_for_key_event
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if there was an issue with the input device
+    pub fn read_key_stroke(&mut self) -> Result<Option<KeyStroke>> {
+        let mut key_data = MaybeUninit::<KeyData>::uninit();
+
+        match unsafe { (self.0.read_key_stroke_ex)(&mut self.0, key_data.as_mut_ptr()) } {
+            Status::NOT_READY => Ok(None),
+            other => {
+                other.to_result_with_val(|| Some(KeyStroke(unsafe { key_data.assume_init() })))
+            }
+        }
+    }
+
+    /// Event to be used with [`boot::wait_for_event`] in order to wait
+    /// for a key to be available
+    ///
+    /// [`boot::wait_for_event`]: crate::boot::wait_for_event
+    #[must_use]
+    pub fn wait_for_key_event(&self) -> Option<Event> {
+        unsafe { Event::from_ptr(self.0.wait_for_key_ex) }
+    }
+
+    /// Sets the state of the lock keys, such as caps lock.
+    ///
+    /// Including [`KeyToggleState::KEY_STATE_EXPOSED`] enables reporting of
+    /// partial keystrokes by [`read_key_stroke`]. The
+    /// [`KeyToggleState::VALID`] bit is added automatically.
+    ///
+    /// [`read_key_stroke`]: Self::read_key_stroke
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if the device is not functioning correctly
+    /// - [`Status::UNSUPPORTED`] if the device does not support the state
+    pub fn set_toggle_state(&mut self, state: KeyToggleState) -> Result {
+        let state = state | KeyToggleState::VALID;
+        unsafe { (self.0.set_state)(&mut self.0, &state) }.to_result()
+    }
+
+    /// Registers `notify` to be called whenever a key matching `key` is
+    /// pressed.
+    ///
+    /// The shift and toggle states of `key` are only compared if they have
+    /// been set with [`KeyStroke::set_shift_state`] and
+    /// [`KeyStroke::set_toggle_state`]. The function is called at
+    /// [`Tpl::CALLBACK`], unlike most code using this crate.
+    ///
+    /// The returned handle must be passed to [`unregister_key_notify`] to
+    /// stop the notifications.
+    ///
+    /// [`Tpl::CALLBACK`]: crate::boot::Tpl::CALLBACK
+    /// [`unregister_key_notify`]: Self::unregister_key_notify
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::OUT_OF_RESOURCES`] if the notification could not be allocated
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uefi::proto::console::text::{InputEx, Key, KeyNotifyHandle, KeyStroke, ScanCode};
+    /// use uefi::{Result, Status};
+    ///
+    /// extern "efiapi" fn on_f2(_key: &KeyStroke) -> Status {
+    ///     // Remember that setup was requested.
+    ///     Status::SUCCESS
+    /// }
+    ///
+    /// fn watch_f2(input: &mut InputEx) -> Result<KeyNotifyHandle> {
+    ///     let key = KeyStroke::new(Key::Special(ScanCode::FUNCTION_2));
+    ///     input.register_key_notify(&key, on_f2)
+    /// }
+    /// ```
+    pub fn register_key_notify(
+        &mut self,
+        key: &KeyStroke,
+        notify: KeyNotifyFn,
+    ) -> Result<KeyNotifyHandle> {
+        let mut handle = ptr::null_mut();
+        // Safety: `KeyStroke` is a transparent wrapper of `KeyData`, and
+        // references have the same layout as pointers, so the signatures are
+        // compatible. The firmware always passes a valid key.
+        let notify = unsafe {
+            core::mem::transmute::<KeyNotifyFn, uefi_raw::protocol::console::KeyNotifyFunction>(
+                notify,
+            )
+        };
+        unsafe { (self.0.register_key_notify)(&mut self.0, &key.0, notify, &mut handle) }
+            .to_result_with_val(|| KeyNotifyHandle(handle))
+    }
+
+    /// Removes a notification registered with [`register_key_notify`].
+    ///
+    /// [`register_key_notify`]: Self::register_key_notify
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::INVALID_PARAMETER`] if the handle is not registered with
+    ///   this device
+    pub fn unregister_key_notify(&mut self, handle: KeyNotifyHandle) -> Result {
+        unsafe { (self.0.unregister_key_notify)(&mut self.0, handle.0) }.to_result()
+    }
+}
+
+/// Function called when a registered key is pressed, see
+/// [`InputEx::register_key_notify`].
+pub type KeyNotifyFn = extern "efiapi" fn(key: &KeyStroke) -> Status;
+
+/// Handle of a key notification, see [`InputEx::register_key_notify`].
+#[derive(Debug)]
+pub struct KeyNotifyHandle(*mut c_void);
+
+/// A keystroke with the state of the modifier and lock keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct KeyStroke(KeyData);
+
+impl KeyStroke {
+    /// Create a keystroke for `key`, with unknown modifier and lock states.
+    ///
+    /// This is used to match keys in [`InputEx::register_key_notify`].
+    #[must_use]
+    pub fn new(key: Key) -> Self {
+        Self(KeyData {
+            key: key.into(),
+            key_state: KeyState::default(),
+        })
+    }
+
+    /// Set the state of the modifier keys.
+    #[must_use]
+    pub fn set_shift_state(mut self, state: KeyShiftState) -> Self {
+        self.0.key_state.key_shift_state = state | KeyShiftState::VALID;
+        self
+    }
+
+    /// Set the state of the lock keys.
+    #[must_use]
+    pub fn set_toggle_state(mut self, state: KeyToggleState) -> Self {
+        self.0.key_state.key_toggle_state = state | KeyToggleState::VALID;
+        self
+    }
+
+    /// Get the key, or `None` for a partial keystroke where only the state
+    /// of a modifier key changed.
+    #[must_use]
+    pub fn key(&self) -> Option<Key> {
+        if self.0.key == InputKey::default() {
+            None
+        } else {
+            Some(self.0.key.into())
+        }
+    }
+
+    /// Get the state of the modifier keys, if the device reports it.
+    #[must_use]
+    pub fn shift_state(&self) -> Option<KeyShiftState> {
+        let state = self.0.key_state.key_shift_state;
+        state
+            .contains(KeyShiftState::VALID)
+            .then(|| state - KeyShiftState::VALID)
+    }
+
+    /// Get the state of the lock keys, if the device reports it.
+    #[must_use]
+    pub fn toggle_state(&self) -> Option<KeyToggleState> {
+        let state = self.0.key_state.key_toggle_state;
+        state
+            .contains(KeyToggleState::VALID)
+            .then(|| state - KeyToggleState::VALID)
+    }
+
+    /// Check whether either shift key is pressed.
+    #[must_use]
+    pub fn shift(&self) -> bool {
+        self.any_pressed(KeyShiftState::LEFT_SHIFT_PRESSED | KeyShiftState::RIGHT_SHIFT_PRESSED)
+    }
+
+    /// Check whether either control key is pressed.
+    #[must_use]
+    pub fn ctrl(&self) -> bool {
+        self.any_pressed(KeyShiftState::LEFT_CONTROL_PRESSED | KeyShiftState::RIGHT_CONTROL_PRESSED)
+    }
+
+    /// Check whether either alt key is pressed.
+    #[must_use]
+    pub fn alt(&self) -> bool {
+        self.any_pressed(KeyShiftState::LEFT_ALT_PRESSED | KeyShiftState::RIGHT_ALT_PRESSED)
+    }
+
+    fn any_pressed(&self, keys: KeyShiftState) -> bool {
+        self.shift_state()
+            .is_some_and(|state| state.intersects(keys))
+    }
+}
+
+impl From<KeyStroke> for KeyData {
+    fn from(key: KeyStroke) -> Self {
+        key.0
+    }
+}
+
+impl From<KeyData> for KeyStroke {
+    fn from(key_data: KeyData) -> Self {
+        Self(key_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::console::text::ScanCode;
+    use crate::Char16;
+
+    #[test]
+    fn test_key_stroke() {
+        let key = Key::Printable(Char16::try_from('a').unwrap());
+        let stroke = KeyStroke::new(key);
+        assert_eq!(stroke.key(), Some(key));
+        assert_eq!(stroke.shift_state(), None);
+        assert!(!stroke.ctrl());
+
+        let stroke = stroke
+            .set_shift_state(KeyShiftState::LEFT_CONTROL_PRESSED)
+            .set_toggle_state(KeyToggleState::CAPS_LOCK_ACTIVE);
+        assert_eq!(
+            stroke.shift_state(),
+            Some(KeyShiftState::LEFT_CONTROL_PRESSED)
+        );
+        assert_eq!(
+            stroke.toggle_state(),
+            Some(KeyToggleState::CAPS_LOCK_ACTIVE)
+        );
+        assert!(stroke.ctrl());
+        assert!(!stroke.shift());
+        assert!(!stroke.alt());
+
+        let f2 = Key::Special(ScanCode::FUNCTION_2);
+        assert_eq!(KeyStroke::new(f2).key(), Some(f2));
+
+        // A partial keystroke, reporting only a modifier change.
+        let partial = KeyStroke::from(KeyData {
+            key: InputKey::default(),
+            key_state: KeyState {
+                key_shift_state: KeyShiftState::VALID | KeyShiftState::RIGHT_ALT_PRESSED,
+                key_toggle_state: KeyToggleState::empty(),
+            },
+        });
+        assert_eq!(partial.key(), None);
+        assert!(partial.alt());
+    }
+}
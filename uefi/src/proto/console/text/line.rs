@@ -0,0 +1,246 @@
+//! Module for [`LineReader`].
+
+use super::{Input, Key, Output, ScanCode};
+use crate::{boot, system, CString16, Char16, Result, ResultExt};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Write;
+
+/// How [`LineReader`] shows the typed characters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Echo {
+    /// Show the characters.
+    Visible,
+    /// Show this character for every typed character, for example `'*'`
+    /// for passwords.
+    Masked(char),
+    /// Don't show anything.
+    Hidden,
+}
+
+/// Reads a line of text from an [`Input`], for example a passphrase to
+/// unlock a disk or the command line of a boot menu entry.
+///
+/// The line ends with the enter key, and the escape key cancels it.
+/// Backspace deletes the last character. Other special keys and control
+/// characters are ignored, as are characters beyond the maximum length.
+///
+/// The typed line is kept in a single buffer, and deleted characters are
+/// overwritten with zero, so that no stray copies of passwords are left in
+/// memory. With a maximum length, the buffer is never reallocated.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::println;
+/// use uefi::proto::console::text::{Echo, LineReader};
+/// # fn test() -> uefi::Result {
+/// let reader = LineReader::new().set_echo(Echo::Masked('*')).set_max_len(64);
+/// uefi::print!("Passphrase: ");
+/// match reader.read_console()? {
+///     Some(passphrase) => println!("\n{} characters", passphrase.num_chars()),
+///     None => println!("\ncanceled"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LineReader {
+    echo: Echo,
+    max_len: Option<usize>,
+}
+
+impl LineReader {
+    /// Create a reader that shows the typed characters and has no maximum
+    /// length.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            echo: Echo::Visible,
+            max_len: None,
+        }
+    }
+
+    /// Set how the typed characters are shown.
+    #[must_use]
+    pub const fn set_echo(mut self, echo: Echo) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// Set the maximum number of characters of the line.
+    #[must_use]
+    pub const fn set_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Read a line from `input`, echoing to `output`. Returns `None` if the
+    /// escape key was pressed.
+    ///
+    /// # Errors
+    ///
+    /// See [`Input::read_key`] and [`boot::wait_for_event`]. Errors of the
+    /// echo are ignored.
+    pub fn read(&self, input: &mut Input, output: &mut Output) -> Result<Option<CString16>> {
+        let mut line: Vec<u16> = Vec::with_capacity(self.max_len.map_or(32, |len| len + 1));
+        let result = self.read_into(input, output, &mut line);
+        match result {
+            Ok(true) => {
+                line.push(0);
+                // The line only contains printable characters.
+                Ok(Some(CString16::try_from(line).unwrap()))
+            }
+            Ok(false) | Err(_) => {
+                line.fill(0);
+                result.map(|_| None)
+            }
+        }
+    }
+
+    /// Read a line like [`read`], and convert it to a [`String`].
+    ///
+    /// # Errors
+    ///
+    /// See [`read`].
+    ///
+    /// [`read`]: Self::read
+    pub fn read_string(&self, input: &mut Input, output: &mut Output) -> Result<Option<String>> {
+        self.read(input, output)
+            .map(|line| line.map(|line| String::from(&line)))
+    }
+
+    /// Read a line from the console, with [`system::with_stdin`] and
+    /// [`system::with_stdout`].
+    ///
+    /// # Errors
+    ///
+    /// See [`read`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if boot services have been exited, or the console is not
+    /// available.
+    ///
+    /// [`read`]: Self::read
+    pub fn read_console(&self) -> Result<Option<CString16>> {
+        system::with_stdin(|stdin| {
+            let stdin = RefCell::new(stdin);
+            system::with_stdout(|stdout| self.read(&mut stdin.borrow_mut(), stdout))
+        })
+    }
+
+    /// Read keys into `line` until enter, returning `true`, or escape,
+    /// returning `false`, is pressed.
+    fn read_into(
+        &self,
+        input: &mut Input,
+        output: &mut Output,
+        line: &mut Vec<u16>,
+    ) -> Result<bool> {
+        loop {
+            let Some(key) = input.read_key()? else {
+                if let Some(event) = input.wait_for_key_event() {
+                    boot::wait_for_event(&mut [event]).discard_errdata()?;
+                }
+                continue;
+            };
+            match self.edit(line, key) {
+                Edit::Done => return Ok(true),
+                Edit::Canceled => return Ok(false),
+                Edit::Inserted(c) => {
+                    let _ = match self.echo {
+                        Echo::Visible => output.write_char(c),
+                        Echo::Masked(mask) => output.write_char(mask),
+                        Echo::Hidden => Ok(()),
+                    };
+                }
+                Edit::Deleted => {
+                    if self.echo != Echo::Hidden {
+                        let _ = output.write_str("\u{8} \u{8}");
+                    }
+                }
+                Edit::Ignored => {}
+            }
+        }
+    }
+
+    /// Apply a key to `line`.
+    fn edit(&self, line: &mut Vec<u16>, key: Key) -> Edit {
+        let c = match key {
+            Key::Special(ScanCode::ESCAPE) => return Edit::Canceled,
+            Key::Special(_) => return Edit::Ignored,
+            Key::Printable(c) => u16::from(c),
+        };
+        match c {
+            0x0d | 0x0a => Edit::Done,
+            0x08 => {
+                if line.pop().is_none() {
+                    return Edit::Ignored;
+                }
+                // Overwrite the deleted character.
+                line.spare_capacity_mut()[0].write(0);
+                Edit::Deleted
+            }
+            c if c < 0x20 => Edit::Ignored,
+            _ if self.max_len.is_some_and(|max_len| line.len() >= max_len) => Edit::Ignored,
+            _ => {
+                line.push(c);
+                Edit::Inserted(char::from(Char16::try_from(c).unwrap()))
+            }
+        }
+    }
+}
+
+impl Default for LineReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The effect of a key on the line.
+#[derive(Debug, Eq, PartialEq)]
+enum Edit {
+    Done,
+    Canceled,
+    Inserted(char),
+    Deleted,
+    Ignored,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> Key {
+        Key::Printable(Char16::try_from(c).unwrap())
+    }
+
+    #[test]
+    fn test_edit() {
+        let reader = LineReader::new().set_max_len(2);
+        let mut line = Vec::with_capacity(3);
+        assert_eq!(reader.edit(&mut line, key('\u{8}')), Edit::Ignored);
+        assert_eq!(reader.edit(&mut line, key('a')), Edit::Inserted('a'));
+        assert_eq!(reader.edit(&mut line, key('\t')), Edit::Ignored);
+        assert_eq!(reader.edit(&mut line, key('b')), Edit::Inserted('b'));
+        assert_eq!(reader.edit(&mut line, key('c')), Edit::Ignored);
+        assert_eq!(line, [u16::from(b'a'), u16::from(b'b')]);
+
+        // Deleted characters are overwritten.
+        assert_eq!(reader.edit(&mut line, key('\u{8}')), Edit::Deleted);
+        assert_eq!(line, [u16::from(b'a')]);
+        assert_eq!(unsafe { line.spare_capacity_mut()[0].assume_init() }, 0);
+
+        assert_eq!(
+            reader.edit(&mut line, Key::Special(ScanCode::LEFT)),
+            Edit::Ignored
+        );
+        assert_eq!(reader.edit(&mut line, key('\r')), Edit::Done);
+        assert_eq!(
+            reader.edit(&mut line, Key::Special(ScanCode::ESCAPE)),
+            Edit::Canceled
+        );
+    }
+}
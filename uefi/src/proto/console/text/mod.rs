@@ -1,7 +1,20 @@
 //! Text I/O.
 
+mod ansi;
+pub use ansi::AnsiWriter;
+
 mod input;
 pub use input::{Input, Key, ScanCode};
 
+mod input_ex;
+pub use input_ex::{
+    InputEx, KeyNotifyFn, KeyNotifyHandle, KeyShiftState, KeyStroke, KeyToggleState,
+};
+
+#[cfg(feature = "alloc")]
+mod line;
+#[cfg(feature = "alloc")]
+pub use line::{Echo, LineReader};
+
 mod output;
-pub use output::{Color, Output, OutputMode};
+pub use output::{Color, CursorState, Output, OutputMode};
@@ -1,5 +1,6 @@
 use crate::proto::unsafe_protocol;
 use crate::{CStr16, Result, ResultExt, Status, StatusExt};
+use core::cmp::Reverse;
 use core::fmt;
 use uefi_raw::protocol::console::{SimpleTextOutputMode, SimpleTextOutputProtocol};
 
@@ -109,11 +110,51 @@ impl Output {
         }
     }
 
+    /// Returns the number of columns and rows of the current text mode.
+    ///
+    /// If the device has no current mode, the 80x25 mode that all devices
+    /// support is assumed.
+    pub fn dimensions(&self) -> Result<(usize, usize)> {
+        Ok(self.current_mode()?.map_or((80, 25), |mode| mode.dims))
+    }
+
     /// Sets a mode as current.
     pub fn set_mode(&mut self, mode: OutputMode) -> Result {
         unsafe { (self.0.set_mode)(&mut self.0, mode.index) }.to_result()
     }
 
+    /// Returns the largest text mode with at least `min_columns` columns and
+    /// `min_rows` rows, or `None` if there is no such mode.
+    ///
+    /// Modes are ranked by their number of characters, then by their number
+    /// of columns. On ties, the mode with the lower index wins.
+    pub fn best_mode(&mut self, min_columns: usize, min_rows: usize) -> Option<OutputMode> {
+        best_mode(self.modes(), min_columns, min_rows)
+    }
+
+    /// Sets the largest text mode with at least `min_columns` columns and
+    /// `min_rows` rows, see [`best_mode`], and returns that mode.
+    ///
+    /// The mode is not set again if it already is the current mode, so the
+    /// screen is not cleared.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::NOT_FOUND`] if there is no such mode.
+    /// - See [`set_mode`] for errors when setting the mode.
+    ///
+    /// [`best_mode`]: Self::best_mode
+    /// [`set_mode`]: Self::set_mode
+    pub fn set_best_mode(&mut self, min_columns: usize, min_rows: usize) -> Result<OutputMode> {
+        let mode = self
+            .best_mode(min_columns, min_rows)
+            .ok_or(Status::NOT_FOUND)?;
+        if self.current_mode()? != Some(mode) {
+            self.set_mode(mode)?;
+        }
+        Ok(mode)
+    }
+
     /// Returns whether the cursor is currently shown or not.
     #[must_use]
     pub const fn cursor_visible(&self) -> bool {
@@ -136,6 +177,35 @@ impl Output {
         (column as usize, row as usize)
     }
 
+    /// Returns the position and visibility of the cursor, and the current
+    /// colors, for restoring them with [`restore_cursor`] after drawing.
+    ///
+    /// [`restore_cursor`]: Self::restore_cursor
+    #[must_use]
+    pub const fn save_cursor(&self) -> CursorState {
+        CursorState {
+            position: self.cursor_position(),
+            visible: self.cursor_visible(),
+            colors: self.colors(),
+        }
+    }
+
+    /// Restores the position and visibility of the cursor, and the colors,
+    /// saved with [`save_cursor`].
+    ///
+    /// The visibility is only changed if it differs from the saved one, as
+    /// not all devices support hiding the cursor.
+    ///
+    /// [`save_cursor`]: Self::save_cursor
+    pub fn restore_cursor(&mut self, state: &CursorState) -> Result {
+        self.set_color(state.colors.0, state.colors.1)?;
+        self.set_cursor_position(state.position.0, state.position.1)?;
+        if self.cursor_visible() != state.visible {
+            self.enable_cursor(state.visible)?;
+        }
+        Ok(())
+    }
+
     /// Sets the cursor's position, relative to the top-left corner, which is (0, 0).
     ///
     /// This function will fail if the cursor's new position would exceed the screen's bounds.
@@ -157,6 +227,16 @@ impl Output {
         unsafe { (self.0.set_attribute)(&mut self.0, attr) }.to_result()
     }
 
+    /// Returns the current `(foreground, background)` colors.
+    #[must_use]
+    pub const fn colors(&self) -> (Color, Color) {
+        let attribute = self.data().attribute as usize;
+        (
+            Color::from_index(attribute & 0xf),
+            Color::from_index((attribute >> 4) & 0x7),
+        )
+    }
+
     /// Get a reference to `OutputData`. The lifetime of the reference is tied
     /// to `self`.
     const fn data(&self) -> &SimpleTextOutputMode {
@@ -247,6 +327,55 @@ impl OutputMode {
     }
 }
 
+/// Position and visibility of the cursor, and the colors of an [`Output`],
+/// see [`Output::save_cursor`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CursorState {
+    position: (usize, usize),
+    visible: bool,
+    colors: (Color, Color),
+}
+
+impl CursorState {
+    /// Returns the column and row of the cursor.
+    #[must_use]
+    pub const fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Returns whether the cursor is shown.
+    #[must_use]
+    pub const fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns the `(foreground, background)` colors.
+    #[must_use]
+    pub const fn colors(&self) -> (Color, Color) {
+        self.colors
+    }
+}
+
+/// Pick the largest of `modes` with at least `min_columns` columns and
+/// `min_rows` rows.
+fn best_mode(
+    modes: impl Iterator<Item = OutputMode>,
+    min_columns: usize,
+    min_rows: usize,
+) -> Option<OutputMode> {
+    modes
+        .filter(|mode| mode.columns() >= min_columns && mode.rows() >= min_rows)
+        // `max_by_key` returns the last maximum, so prefer lower indices
+        // explicitly.
+        .max_by_key(|mode| {
+            (
+                mode.columns() * mode.rows(),
+                mode.columns(),
+                Reverse(mode.index),
+            )
+        })
+}
+
 /// An iterator of the text modes (possibly) supported by a device.
 #[derive(Debug)]
 pub struct OutputModeIter<'out> {
@@ -279,7 +408,7 @@ impl<'out> Iterator for OutputModeIter<'out> {
 /// All colors can be used as foreground colors.
 /// The first 8 colors can also be used as background colors.
 #[allow(missing_docs)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Color {
     Black = 0,
     Blue,
@@ -298,3 +427,54 @@ pub enum Color {
     Yellow,
     White,
 }
+
+impl Color {
+    /// Get the color with the index `index & 0xf`.
+    pub(super) const fn from_index(index: usize) -> Self {
+        match index & 0xf {
+            0 => Self::Black,
+            1 => Self::Blue,
+            2 => Self::Green,
+            3 => Self::Cyan,
+            4 => Self::Red,
+            5 => Self::Magenta,
+            6 => Self::Brown,
+            7 => Self::LightGray,
+            8 => Self::DarkGray,
+            9 => Self::LightBlue,
+            10 => Self::LightGreen,
+            11 => Self::LightCyan,
+            12 => Self::LightRed,
+            13 => Self::LightMagenta,
+            14 => Self::Yellow,
+            _ => Self::White,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_mode() {
+        let modes = [
+            (80, 25),
+            (80, 50),
+            (100, 31),
+            (128, 40),
+            (160, 20),
+            (80, 50),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, dims)| OutputMode { index, dims });
+        let best = |min_columns, min_rows| {
+            best_mode(modes.clone(), min_columns, min_rows).map(|mode| mode.index())
+        };
+        assert_eq!(best(0, 0), Some(3));
+        assert_eq!(best(0, 45), Some(1));
+        assert_eq!(best(150, 0), Some(4));
+        assert_eq!(best(200, 0), None);
+    }
+}
@@ -0,0 +1,539 @@
+//! Minimal text user interface elements, drawn on a text [`Output`].
+//!
+//! This provides the pieces most boot managers need: a selection [`Menu`]
+//! with keyboard navigation and an optional timeout, a [`ProgressBar`], and
+//! a [`message_box`].
+
+use crate::proto::console::text::{Color, Input, Key, Output, ScanCode};
+use crate::{boot, duration, CStr16, Char16, Result, ResultExt, Status};
+use core::fmt::{self, Write};
+use core::time::Duration;
+
+/// A selection menu, navigated with the arrow, home, end and page keys.
+///
+/// Enter selects the highlighted entry, and the digits 1 to 9 highlight the
+/// corresponding entry. Escape cancels the menu. If a timeout is set, it is
+/// counted down on screen until a key is pressed, and the default entry is
+/// chosen when it elapses.
+///
+/// The menu uses the whole screen, which is cleared when the menu is closed.
+///
+/// # Example
+///
+/// ```
+/// use core::time::Duration;
+/// use uefi::proto::console::text::{Input, Output};
+/// use uefi::proto::console::tui::{Menu, MenuChoice};
+/// use uefi::Result;
+///
+/// fn choose_entry(input: &mut Input, output: &mut Output) -> Result<Option<usize>> {
+///     let entries = ["Linux", "Linux (recovery mode)", "Firmware setup"];
+///     let menu = Menu::new(&entries)
+///         .set_title("Boot menu")
+///         .set_timeout(Duration::from_secs(5));
+///     Ok(match menu.run(input, output)? {
+///         MenuChoice::Selected(index) | MenuChoice::TimedOut(index) => Some(index),
+///         MenuChoice::Canceled => None,
+///     })
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Menu<'a> {
+    title: Option<&'a str>,
+    entries: &'a [&'a str],
+    default: usize,
+    timeout: Option<Duration>,
+}
+
+impl<'a> Menu<'a> {
+    /// Create a menu of `entries`, with the first entry as the default.
+    #[must_use]
+    pub const fn new(entries: &'a [&'a str]) -> Self {
+        Self {
+            title: None,
+            entries,
+            default: 0,
+            timeout: None,
+        }
+    }
+
+    /// Set the title shown above the entries.
+    #[must_use]
+    pub const fn set_title(mut self, title: &'a str) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    /// Set the entry that is highlighted first, and chosen on timeout.
+    #[must_use]
+    pub const fn set_default(mut self, index: usize) -> Self {
+        self.default = index;
+        self
+    }
+
+    /// Choose the default entry if no key is pressed within `timeout`.
+    #[must_use]
+    pub const fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Show the menu and wait for a choice.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::INVALID_PARAMETER`] if the menu has no entries
+    /// - [`Status::DEVICE_ERROR`] if there was an issue with the console
+    pub fn run(&self, input: &mut Input, output: &mut Output) -> Result<MenuChoice> {
+        if self.entries.is_empty() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+
+        let saved = output.save_cursor();
+        // Not all consoles can hide the cursor.
+        let _ = output.enable_cursor(false);
+        output.clear()?;
+
+        let choice = self.run_loop(input, output);
+
+        let _ = output.restore_cursor(&saved);
+        output.clear()?;
+        choice
+    }
+
+    fn run_loop(&self, input: &mut Input, output: &mut Output) -> Result<MenuChoice> {
+        let (columns, rows) = output.dimensions()?;
+        let header = if self.title.is_some() { 2 } else { 0 };
+        // Leave room for an empty line, the help and the countdown.
+        let visible = rows.saturating_sub(header + 3).max(1);
+        let mut state = MenuState::new(self.entries.len(), self.default, visible);
+        let mut remaining = self.timeout;
+
+        loop {
+            self.draw(output, &state, columns, header, remaining)?;
+
+            let key = match remaining {
+                Some(left) => {
+                    // Wake up when the number of seconds shown changes.
+                    let wait = left.saturating_sub(Duration::from_secs(
+                        duration::as_secs_ceil(left).saturating_sub(1),
+                    ));
+                    match input.read_key_timeout(wait)? {
+                        Some(key) => {
+                            remaining = None;
+                            key
+                        }
+                        None => {
+                            let left = left.saturating_sub(wait);
+                            if left.is_zero() {
+                                return Ok(MenuChoice::TimedOut(state.selected));
+                            }
+                            remaining = Some(left);
+                            continue;
+                        }
+                    }
+                }
+                None => wait_for_key(input)?,
+            };
+            if let Some(choice) = state.handle(key) {
+                return Ok(choice);
+            }
+        }
+    }
+
+    fn draw(
+        &self,
+        output: &mut Output,
+        state: &MenuState,
+        columns: usize,
+        header: usize,
+        remaining: Option<Duration>,
+    ) -> Result {
+        // Writing the last column could scroll the screen.
+        let width = columns.saturating_sub(1);
+        let (foreground, background) = output.colors();
+
+        if let Some(title) = self.title {
+            output.set_cursor_position(0, 0)?;
+            Line::new(width).text(title).write_to(output)?;
+        }
+
+        let end = (state.top + state.visible).min(self.entries.len());
+        for (row, index) in (state.top..end).enumerate() {
+            output.set_cursor_position(0, header + row)?;
+            if index == state.selected {
+                output.set_color(Color::Black, Color::LightGray)?;
+            }
+            Line::new(width)
+                .text("  ")
+                .text(self.entries[index])
+                .write_to(output)?;
+            if index == state.selected {
+                output.set_color(foreground, background)?;
+            }
+        }
+
+        let footer = header + state.visible + 1;
+        output.set_cursor_position(0, footer)?;
+        Line::new(width)
+            .text("Up/Down: select  Enter: confirm  Esc: cancel")
+            .write_to(output)?;
+        output.set_cursor_position(0, footer + 1)?;
+        let mut line = Line::new(width);
+        if let Some(left) = remaining {
+            let _ = write!(
+                line,
+                "The default entry is chosen in {} s",
+                duration::as_secs_ceil(left)
+            );
+        }
+        line.write_to(output)
+    }
+}
+
+/// Result of [`Menu::run`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MenuChoice {
+    /// The entry with this index was selected with the enter key.
+    Selected(usize),
+    /// The timeout elapsed, and the entry with this index was highlighted.
+    TimedOut(usize),
+    /// The menu was canceled with the escape key.
+    Canceled,
+}
+
+/// Navigation state of a [`Menu`].
+#[derive(Debug)]
+struct MenuState {
+    len: usize,
+    selected: usize,
+    /// First entry shown on screen.
+    top: usize,
+    /// Number of entries shown on screen.
+    visible: usize,
+}
+
+impl MenuState {
+    fn new(len: usize, selected: usize, visible: usize) -> Self {
+        let mut state = Self {
+            len,
+            selected: 0,
+            top: 0,
+            visible: visible.min(len),
+        };
+        state.select(selected.min(len - 1));
+        state
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        if index < self.top {
+            self.top = index;
+        } else if index >= self.top + self.visible {
+            self.top = index + 1 - self.visible;
+        }
+    }
+
+    fn handle(&mut self, key: Key) -> Option<MenuChoice> {
+        let last = self.len - 1;
+        match key {
+            Key::Special(ScanCode::UP) => {
+                self.select(self.selected.checked_sub(1).unwrap_or(last));
+            }
+            Key::Special(ScanCode::DOWN) => {
+                self.select(if self.selected == last {
+                    0
+                } else {
+                    self.selected + 1
+                });
+            }
+            Key::Special(ScanCode::HOME) => self.select(0),
+            Key::Special(ScanCode::END) => self.select(last),
+            Key::Special(ScanCode::PAGE_UP) => {
+                self.select(self.selected.saturating_sub(self.visible));
+            }
+            Key::Special(ScanCode::PAGE_DOWN) => {
+                self.select((self.selected + self.visible).min(last));
+            }
+            Key::Special(ScanCode::ESCAPE) => return Some(MenuChoice::Canceled),
+            Key::Printable(c) => match char::from(c) {
+                '\r' | '\n' => return Some(MenuChoice::Selected(self.selected)),
+                c @ '1'..='9' => {
+                    let index = c as usize - '1' as usize;
+                    if index < self.len {
+                        self.select(index);
+                    }
+                }
+                _ => {}
+            },
+            Key::Special(_) => {}
+        }
+        None
+    }
+}
+
+/// A progress bar drawn on a single line, such as
+/// `[##########          ]  50%`.
+///
+/// # Example
+///
+/// ```
+/// use uefi::proto::console::text::Output;
+/// use uefi::proto::console::tui::ProgressBar;
+/// use uefi::Result;
+///
+/// fn load_files(output: &mut Output, files: &[&str]) -> Result {
+///     let progress = ProgressBar::new(files.len() as u64);
+///     for (done, _file) in files.iter().enumerate() {
+///         progress.draw(output, done as u64)?;
+///         // Load the file.
+///     }
+///     progress.draw(output, files.len() as u64)
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProgressBar {
+    total: u64,
+    width: usize,
+}
+
+impl ProgressBar {
+    /// Create a progress bar for `total` steps.
+    #[must_use]
+    pub const fn new(total: u64) -> Self {
+        Self { total, width: 40 }
+    }
+
+    /// Set the number of characters between the brackets.
+    #[must_use]
+    pub const fn set_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Draw the progress bar at the start of the cursor's line, showing `done`
+    /// of the total steps. The bar is truncated to the width of the screen.
+    ///
+    /// # Errors
+    ///
+    /// - [`Status::DEVICE_ERROR`] if there was an issue with the console
+    pub fn draw(&self, output: &mut Output, done: u64) -> Result {
+        let (columns, _) = output.dimensions()?;
+        let filled = self.filled(done);
+        let mut line = Line::new(columns.saturating_sub(1));
+        line.push('[');
+        (0..self.width).for_each(|i| line.push(if i < filled { '#' } else { ' ' }));
+        let _ = write!(line, "] {:3}%", self.percent(done));
+
+        output.set_cursor_position(0, output.cursor_position().1)?;
+        line.write_to(output)
+    }
+
+    /// Number of filled characters of the bar.
+    fn filled(&self, done: u64) -> usize {
+        if self.total == 0 {
+            return self.width;
+        }
+        let filled = u128::from(done.min(self.total)) * self.width as u128 / u128::from(self.total);
+        filled as usize
+    }
+
+    fn percent(&self, done: u64) -> u64 {
+        if self.total == 0 {
+            return 100;
+        }
+        (u128::from(done.min(self.total)) * 100 / u128::from(self.total)) as u64
+    }
+}
+
+/// Show `text` in a box with a `title` in the center of the screen, and wait
+/// for a key.
+///
+/// The box is drawn over the current contents of the screen, which are not
+/// restored; redraw the screen afterwards if needed. Lines that don't fit on
+/// the screen are truncated.
+///
+/// # Errors
+///
+/// - [`Status::DEVICE_ERROR`] if there was an issue with the console
+pub fn message_box(input: &mut Input, output: &mut Output, title: &str, text: &str) -> Result<Key> {
+    const HINT: &str = "Press any key to continue";
+
+    let (columns, rows) = output.dimensions()?;
+    // The border and a space on either side take 4 columns, and the last
+    // column is left free so that the screen does not scroll.
+    let max_inner = columns.saturating_sub(5);
+    let inner = text
+        .lines()
+        .map(|line| line.chars().count())
+        .chain([title.chars().count() + 3, HINT.len()])
+        .max()
+        .unwrap_or(0)
+        .min(max_inner);
+    // The text and hint, with an empty line between them.
+    let max_lines = rows.saturating_sub(5);
+    let lines = text.lines().count().min(max_lines);
+    let height = lines + 4;
+    let left = (columns.saturating_sub(inner + 4)) / 2;
+    let mut row = rows.saturating_sub(height) / 2;
+
+    let mut draw = |output: &mut Output, line: Line| -> Result {
+        output.set_cursor_position(left, row)?;
+        row += 1;
+        line.write_to(output)
+    };
+
+    let width = inner + 4;
+    draw(
+        output,
+        Line::new(width)
+            .text("\u{250c}\u{2500} ")
+            .text(title)
+            .text(" ")
+            .fill('\u{2500}', width - 1)
+            .text("\u{2510}"),
+    )?;
+    for text in text.lines().take(lines).chain(["", HINT]) {
+        draw(
+            output,
+            Line::new(width)
+                .text("\u{2502} ")
+                .text(text)
+                .fill(' ', width - 1)
+                .text("\u{2502}"),
+        )?;
+    }
+    draw(
+        output,
+        Line::new(width)
+            .text("\u{2514}")
+            .fill('\u{2500}', width - 1)
+            .text("\u{2518}"),
+    )?;
+
+    wait_for_key(input)
+}
+
+/// Wait for a key to be pressed, and read it.
+fn wait_for_key(input: &mut Input) -> Result<Key> {
+    loop {
+        if let Some(key) = input.read_key()? {
+            return Ok(key);
+        }
+        let event = input.wait_for_key_event().ok_or(Status::UNSUPPORTED)?;
+        boot::wait_for_event(&mut [event]).discard_errdata()?;
+    }
+}
+
+/// A line of UCS-2 text of a fixed width, padded with spaces when written.
+struct Line {
+    buf: [u16; Self::CAPACITY + 1],
+    len: usize,
+    width: usize,
+}
+
+impl Line {
+    const CAPACITY: usize = 255;
+
+    fn new(width: usize) -> Self {
+        Self {
+            buf: [0; Self::CAPACITY + 1],
+            len: 0,
+            width: width.min(Self::CAPACITY),
+        }
+    }
+
+    /// Append `c`, if the line is not full. Control characters are replaced
+    /// by spaces, and characters outside of UCS-2 by `?`.
+    fn push(&mut self, c: char) {
+        if self.len < self.width {
+            let c = if c.is_control() { ' ' } else { c };
+            self.buf[self.len] = Char16::try_from(c).map_or(u16::from(b'?'), u16::from);
+            self.len += 1;
+        }
+    }
+
+    fn text(mut self, text: &str) -> Self {
+        text.chars().for_each(|c| self.push(c));
+        self
+    }
+
+    /// Append `c` until the line has `len` characters.
+    fn fill(mut self, c: char, len: usize) -> Self {
+        while self.len < len.min(self.width) {
+            self.push(c);
+        }
+        self
+    }
+
+    fn write_to(self, output: &mut Output) -> Result {
+        let mut line = self.fill(' ', usize::MAX);
+        line.buf[line.len] = 0;
+        let text = CStr16::from_u16_with_nul(&line.buf[..=line.len]).unwrap();
+        output.output_string(text)
+    }
+}
+
+impl Write for Line {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().for_each(|c| self.push(c));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> Key {
+        Key::Printable(Char16::try_from(c).unwrap())
+    }
+
+    #[test]
+    fn test_menu_navigation() {
+        let mut state = MenuState::new(5, 7, 3);
+        assert_eq!((state.selected, state.top), (4, 2));
+
+        assert_eq!(state.handle(Key::Special(ScanCode::DOWN)), None);
+        assert_eq!((state.selected, state.top), (0, 0));
+        state.handle(Key::Special(ScanCode::UP));
+        assert_eq!((state.selected, state.top), (4, 2));
+        state.handle(Key::Special(ScanCode::PAGE_UP));
+        assert_eq!((state.selected, state.top), (1, 1));
+        state.handle(Key::Special(ScanCode::HOME));
+        assert_eq!((state.selected, state.top), (0, 0));
+        state.handle(Key::Special(ScanCode::PAGE_DOWN));
+        assert_eq!((state.selected, state.top), (3, 1));
+        state.handle(Key::Special(ScanCode::END));
+        assert_eq!((state.selected, state.top), (4, 2));
+
+        state.handle(key('2'));
+        assert_eq!(state.selected, 1);
+        // There is no ninth entry.
+        state.handle(key('9'));
+        assert_eq!(state.selected, 1);
+
+        assert_eq!(state.handle(key('\r')), Some(MenuChoice::Selected(1)));
+        assert_eq!(
+            state.handle(Key::Special(ScanCode::ESCAPE)),
+            Some(MenuChoice::Canceled)
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let bar = ProgressBar::new(3).set_width(10);
+        assert_eq!((bar.filled(0), bar.percent(0)), (0, 0));
+        assert_eq!((bar.filled(1), bar.percent(1)), (3, 33));
+        assert_eq!((bar.filled(3), bar.percent(3)), (10, 100));
+        assert_eq!((bar.filled(4), bar.percent(4)), (10, 100));
+        assert_eq!(ProgressBar::new(0).filled(0), 40);
+    }
+
+    #[test]
+    fn test_line() {
+        let line = Line::new(6).text("a\tb").fill('-', 5).text("xyz");
+        assert_eq!(line.len, 6);
+        assert_eq!(&line.buf[..6], b"a b--x".map(u16::from));
+    }
+}
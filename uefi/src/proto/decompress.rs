@@ -0,0 +1,130 @@
+//! Decompress protocol.
+//!
+//! The [`Decompress`] protocol decompresses data compressed with the UEFI
+//! compression algorithm, a combination of LZ77 and Huffman coding that is
+//! also known as EFI or Tiano compression. It is used for example by
+//! compressed firmware volume sections and capsule payloads.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::decompress::Decompress;
+//! # fn expand(compressed: &[u8]) -> uefi::Result {
+//!
+//! let handle = boot::get_handle_for_protocol::<Decompress>()?;
+//! let decompress = boot::open_protocol_exclusive::<Decompress>(handle)?;
+//! let data = decompress.decompress(compressed)?;
+//! log::info!("Decompressed {} bytes to {} bytes", compressed.len(), data.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::unsafe_protocol;
+use crate::{Result, Status, StatusExt};
+use uefi_raw::protocol::decompress::DecompressProtocol;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+/// Sizes of the buffers needed to decompress data, returned by
+/// [`Decompress::get_info`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecompressInfo {
+    /// Size of the decompressed data in bytes.
+    pub destination_size: usize,
+
+    /// Size of the scratch buffer needed while decompressing, in bytes.
+    pub scratch_size: usize,
+}
+
+/// The Decompress protocol.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(DecompressProtocol::GUID)]
+pub struct Decompress(DecompressProtocol);
+
+impl Decompress {
+    /// Get the sizes of the buffers needed to decompress `source`. Only the
+    /// header of the compressed data is checked.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `source` is too short, or larger than
+    ///   4 GiB.
+    pub fn get_info(&self, source: &[u8]) -> Result<DecompressInfo> {
+        let source_size = u32::try_from(source.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        let mut destination_size = 0;
+        let mut scratch_size = 0;
+        unsafe {
+            (self.0.get_info)(
+                &self.0,
+                source.as_ptr().cast(),
+                source_size,
+                &mut destination_size,
+                &mut scratch_size,
+            )
+        }
+        .to_result_with_val(|| DecompressInfo {
+            destination_size: destination_size as usize,
+            scratch_size: scratch_size as usize,
+        })
+    }
+
+    /// Decompress `source` into `destination`, using `scratch` as temporary
+    /// storage, and get the size of the decompressed data. The required
+    /// sizes of the buffers are returned by [`get_info`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `destination` or `scratch` is too
+    ///   small.
+    /// * [`Status::INVALID_PARAMETER`]: `source` is not valid compressed
+    ///   data.
+    ///
+    /// [`get_info`]: Self::get_info
+    pub fn decompress_into(
+        &self,
+        source: &[u8],
+        destination: &mut [u8],
+        scratch: &mut [u8],
+    ) -> Result<usize> {
+        let info = self.get_info(source)?;
+        let destination = destination
+            .get_mut(..info.destination_size)
+            .ok_or(Status::BUFFER_TOO_SMALL)?;
+        let scratch = scratch
+            .get_mut(..info.scratch_size)
+            .ok_or(Status::BUFFER_TOO_SMALL)?;
+
+        // The sizes fit in a `u32`, since they were returned by `get_info`.
+        unsafe {
+            (self.0.decompress)(
+                &self.0,
+                source.as_ptr().cast(),
+                source.len() as u32,
+                destination.as_mut_ptr().cast(),
+                destination.len() as u32,
+                scratch.as_mut_ptr().cast(),
+                scratch.len() as u32,
+            )
+        }
+        .to_result_with_val(|| destination.len())
+    }
+
+    /// Decompress `source`. The buffers needed are allocated on the heap.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `source` is not valid compressed
+    ///   data.
+    #[cfg(feature = "alloc")]
+    pub fn decompress(&self, source: &[u8]) -> Result<Vec<u8>> {
+        let info = self.get_info(source)?;
+        let mut destination = vec![0; info.destination_size];
+        let mut scratch = vec![0; info.scratch_size];
+        let len = self.decompress_into(source, &mut destination, &mut scratch)?;
+        destination.truncate(len);
+        Ok(destination)
+    }
+}
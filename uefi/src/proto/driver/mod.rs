@@ -1,5 +1,7 @@
 //! UEFI driver model protocols.
 
 mod component_name;
+mod service_binding;
 
 pub use component_name::*;
+pub use service_binding::*;
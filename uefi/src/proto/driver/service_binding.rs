@@ -0,0 +1,171 @@
+use crate::boot::{self, ScopedProtocol};
+use crate::proto::{Protocol, ProtocolPointer};
+use crate::{Guid, Handle, Identify, Result, StatusExt};
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use uefi_raw::protocol::driver::ServiceBindingProtocol;
+
+/// A protocol that is produced on child handles created through a
+/// [`ServiceBinding`].
+///
+/// Many network protocols (TCP, UDP, DNS, HTTP, ...) are not installed on
+/// the network interface handle directly. Instead, the interface handle
+/// carries an `EFI_SERVICE_BINDING_PROTOCOL` instance for each of them, which
+/// is used to create a new child handle per protocol instance.
+///
+/// # Safety
+///
+/// [`SERVICE_BINDING_GUID`] must be the GUID of the service binding protocol
+/// whose children produce `Self`.
+///
+/// [`SERVICE_BINDING_GUID`]: Self::SERVICE_BINDING_GUID
+pub unsafe trait ServiceBindingChild: Protocol {
+    /// GUID of the service binding protocol that creates children for this
+    /// protocol.
+    const SERVICE_BINDING_GUID: Guid;
+}
+
+/// The service binding protocol for the child protocol `P`.
+///
+/// Corresponds to `EFI_SERVICE_BINDING_PROTOCOL` in the C API. Each
+/// [`ServiceBindingChild`] protocol has its own binding GUID, so the binding
+/// type is generic over the child protocol it creates.
+#[repr(transparent)]
+pub struct ServiceBinding<P: ServiceBindingChild> {
+    raw: ServiceBindingProtocol,
+    _child: PhantomData<fn() -> P>,
+}
+
+unsafe impl<P: ServiceBindingChild> Identify for ServiceBinding<P> {
+    const GUID: Guid = P::SERVICE_BINDING_GUID;
+}
+
+impl<P: ServiceBindingChild> Protocol for ServiceBinding<P> {}
+
+impl<P: ServiceBindingChild> Debug for ServiceBinding<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceBinding")
+            .field("guid", &P::SERVICE_BINDING_GUID)
+            .field("raw", &self.raw)
+            .finish()
+    }
+}
+
+impl<P: ServiceBindingChild> ServiceBinding<P> {
+    /// Create a new child handle with the protocol `P` installed on it.
+    ///
+    /// The child should be destroyed with [`destroy_child`] once it is no
+    /// longer needed. See [`ServiceChild`] for a wrapper that does this
+    /// automatically.
+    ///
+    /// [`destroy_child`]: Self::destroy_child
+    pub fn create_child(&mut self) -> Result<Handle> {
+        let mut child = ptr::null_mut();
+        unsafe { (self.raw.create_child)(&mut self.raw, &mut child) }
+            // OK to unwrap: the handle is non-null on success.
+            .to_result_with_val(|| unsafe { Handle::from_ptr(child) }.unwrap())
+    }
+
+    /// Destroy a child handle previously created with [`create_child`].
+    ///
+    /// [`create_child`]: Self::create_child
+    pub fn destroy_child(&mut self, child: Handle) -> Result {
+        unsafe { (self.raw.destroy_child)(&mut self.raw, child.as_ptr()) }.to_result()
+    }
+}
+
+/// A child handle created through a [`ServiceBinding`], with its protocol
+/// `P` opened.
+///
+/// When dropped, the protocol is closed and the child handle is destroyed.
+/// `ServiceChild` dereferences to `P`.
+pub struct ServiceChild<P: ServiceBindingChild + ProtocolPointer> {
+    binding: ScopedProtocol<ServiceBinding<P>>,
+    handle: Handle,
+    protocol: ManuallyDrop<ScopedProtocol<P>>,
+}
+
+impl<P: ServiceBindingChild + ProtocolPointer> ServiceChild<P> {
+    /// Create a new child on the service binding installed on `controller`
+    /// (usually a network interface handle) and open `P` on it.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `controller` does not support the service
+    ///   binding protocol for `P`.
+    /// * [`Status::OUT_OF_RESOURCES`]: the child could not be created.
+    ///
+    /// [`Status::UNSUPPORTED`]: crate::Status::UNSUPPORTED
+    /// [`Status::OUT_OF_RESOURCES`]: crate::Status::OUT_OF_RESOURCES
+    pub fn new(controller: Handle) -> Result<Self> {
+        let mut binding = boot::open_protocol_exclusive::<ServiceBinding<P>>(controller)?;
+        let handle = binding.create_child()?;
+        match boot::open_protocol_exclusive::<P>(handle) {
+            Ok(protocol) => Ok(Self {
+                binding,
+                handle,
+                protocol: ManuallyDrop::new(protocol),
+            }),
+            Err(err) => {
+                let _ = binding.destroy_child(handle);
+                Err(err)
+            }
+        }
+    }
+
+    /// Create a new child on the first handle that supports the service
+    /// binding protocol for `P`.
+    ///
+    /// # Errors
+    ///
+    /// See [`new`]. Additionally, [`Status::NOT_FOUND`] is returned if no
+    /// handle supports the service binding protocol.
+    ///
+    /// [`new`]: Self::new
+    /// [`Status::NOT_FOUND`]: crate::Status::NOT_FOUND
+    pub fn new_on_any() -> Result<Self> {
+        Self::new(boot::get_handle_for_protocol::<ServiceBinding<P>>()?)
+    }
+
+    /// Get the child handle.
+    #[must_use]
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+}
+
+impl<P: ServiceBindingChild + ProtocolPointer> Deref for ServiceChild<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.protocol
+    }
+}
+
+impl<P: ServiceBindingChild + ProtocolPointer> DerefMut for ServiceChild<P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.protocol
+    }
+}
+
+impl<P: ServiceBindingChild + ProtocolPointer> Debug for ServiceChild<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceChild")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl<P: ServiceBindingChild + ProtocolPointer> Drop for ServiceChild<P> {
+    fn drop(&mut self) {
+        // The protocol must be closed before the child handle is destroyed,
+        // since destroying the child uninstalls the protocol.
+        unsafe { ManuallyDrop::drop(&mut self.protocol) };
+        if let Err(err) = self.binding.destroy_child(self.handle) {
+            log::warn!("failed to destroy service binding child: {err:?}");
+        }
+    }
+}
@@ -1,10 +1,21 @@
 //! Block I/O protocols.
 
 use crate::proto::unsafe_protocol;
-use crate::{Result, StatusExt};
+use crate::{Result, Status, StatusExt};
+use core::ptr;
+use uefi_raw::protocol::block::{BlockIo2Protocol, EraseBlockProtocol, EraseBlockToken};
 
 pub use uefi_raw::protocol::block::{BlockIoProtocol, Lba};
 
+#[cfg(feature = "alloc")]
+use {
+    crate::proto::completion::PendingIo,
+    alloc::vec::Vec,
+    core::future::Future,
+    core::pin::Pin,
+    core::task::{Context, Poll},
+};
+
 /// The Block I/O protocol.
 #[derive(Debug)]
 #[repr(transparent)]
@@ -101,6 +112,259 @@ impl BlockIO {
     }
 }
 
+/// The Block I/O 2 protocol.
+///
+/// This protocol offers the same block access as [`BlockIO`], but reads,
+/// writes, and flushes can also be started without waiting for them to
+/// complete. See [`BlockIO2Operation`] for how to get the result of such an
+/// operation.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(BlockIo2Protocol::GUID)]
+pub struct BlockIO2(BlockIo2Protocol);
+
+impl BlockIO2 {
+    /// Pointer for block IO media.
+    #[must_use]
+    pub const fn media(&self) -> &BlockIOMedia {
+        unsafe { &*self.0.media.cast::<BlockIOMedia>() }
+    }
+
+    /// Resets the block device hardware. Any pending non-blocking operations
+    /// are aborted.
+    ///
+    /// # Arguments
+    /// * `extended_verification`   Indicates that the driver may perform a more exhaustive verification operation of
+    ///   the device during reset.
+    ///
+    /// # Errors
+    /// * `uefi::Status::DEVICE_ERROR`  The block device is not functioning correctly and could not be reset.
+    pub fn reset(&mut self, extended_verification: bool) -> Result {
+        unsafe { (self.0.reset)(&mut self.0, extended_verification) }.to_result()
+    }
+
+    /// Reads the requested number of blocks from the device, blocking until
+    /// the read completes.
+    ///
+    /// # Arguments
+    /// * `media_id` - The media ID that the read request is for.
+    /// * `lba` - The starting logical block address to read from on the device.
+    /// * `buffer` - The target buffer of the read operation
+    ///
+    /// # Errors
+    /// * `uefi::Status::DEVICE_ERROR`       The device reported an error while attempting to perform the read
+    ///   operation.
+    /// * `uefi::Status::NO_MEDIA`           There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`      The `media_id` is not for the current media.
+    /// * `uefi::Status::BAD_BUFFER_SIZE`    The buffer size parameter is not a multiple of the intrinsic block size of
+    ///   the device.
+    /// * `uefi::Status::INVALID_PARAMETER`  The read request contains LBAs that are not valid, or the buffer is not on
+    ///   proper alignment.
+    /// * `uefi::Status::OUT_OF_RESOURCES`   The request could not be completed due to a lack of resources.
+    pub fn read_blocks(&self, media_id: u32, lba: Lba, buffer: &mut [u8]) -> Result {
+        // Without a token, the read completes before returning.
+        unsafe {
+            (self.0.read_blocks_ex)(
+                &self.0,
+                media_id,
+                lba,
+                ptr::null_mut(),
+                buffer.len(),
+                buffer.as_mut_ptr().cast(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Writes the requested number of blocks to the device, blocking until
+    /// the write completes.
+    ///
+    /// # Arguments
+    /// * `media_id`    The media ID that the write request is for.
+    /// * `lba`         The starting logical block address to be written.
+    /// * `buffer`      Buffer to be written
+    ///
+    /// # Errors
+    /// * `uefi::Status::WRITE_PROTECTED`       The device cannot be written to.
+    /// * `uefi::Status::NO_MEDIA`              There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`         The `media_id` is not for the current media.
+    /// * `uefi::Status::DEVICE_ERROR`          The device reported an error while attempting to perform the write
+    ///   operation.
+    /// * `uefi::Status::BAD_BUFFER_SIZE`       The buffer size parameter is not a multiple of the intrinsic block size
+    ///   of the device.
+    /// * `uefi::Status::INVALID_PARAMETER`     The write request contains LBAs that are not valid, or the buffer is not
+    ///   on proper alignment.
+    /// * `uefi::Status::OUT_OF_RESOURCES`      The request could not be completed due to a lack of resources.
+    pub fn write_blocks(&mut self, media_id: u32, lba: Lba, buffer: &[u8]) -> Result {
+        // Without a token, the write completes before returning.
+        unsafe {
+            (self.0.write_blocks_ex)(
+                &mut self.0,
+                media_id,
+                lba,
+                ptr::null_mut(),
+                buffer.len(),
+                buffer.as_ptr().cast(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Flushes all modified data to a physical block device, blocking until
+    /// the flush completes.
+    ///
+    /// # Errors
+    /// * `uefi::Status::DEVICE_ERROR`          The device reported an error while attempting to write data.
+    /// * `uefi::Status::WRITE_PROTECTED`       The device cannot be written to.
+    /// * `uefi::Status::NO_MEDIA`              There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`         The media in the device has changed.
+    /// * `uefi::Status::OUT_OF_RESOURCES`      The request could not be completed due to a lack of resources.
+    pub fn flush_blocks(&mut self) -> Result {
+        unsafe { (self.0.flush_blocks_ex)(&mut self.0, ptr::null_mut()) }.to_result()
+    }
+
+    /// Starts reading `buffer.len()` bytes from the device, without waiting
+    /// for the read to complete.
+    ///
+    /// The returned operation owns `buffer` until the read completes, and
+    /// gives it back from [`BlockIO2Operation::wait`].
+    ///
+    /// # Arguments
+    /// * `media_id` - The media ID that the read request is for.
+    /// * `lba` - The starting logical block address to read from on the device.
+    /// * `buffer` - The target buffer of the read operation
+    ///
+    /// # Errors
+    /// See [`read_blocks`](Self::read_blocks), and
+    /// [`boot::create_event`](crate::boot::create_event). Errors of the read
+    /// itself may also be returned by [`BlockIO2Operation::wait`].
+    #[cfg(feature = "alloc")]
+    pub fn read_blocks_async(
+        &self,
+        media_id: u32,
+        lba: Lba,
+        buffer: Vec<u8>,
+    ) -> Result<BlockIO2Operation> {
+        PendingIo::start(buffer, |token, buffer| unsafe {
+            (self.0.read_blocks_ex)(
+                &self.0,
+                media_id,
+                lba,
+                token.cast(),
+                buffer.len(),
+                buffer.as_mut_ptr().cast(),
+            )
+        })
+        .map(BlockIO2Operation)
+    }
+
+    /// Starts writing `buffer` to the device, without waiting for the write
+    /// to complete.
+    ///
+    /// The returned operation owns `buffer` until the write completes, and
+    /// gives it back from [`BlockIO2Operation::wait`].
+    ///
+    /// # Arguments
+    /// * `media_id`    The media ID that the write request is for.
+    /// * `lba`         The starting logical block address to be written.
+    /// * `buffer`      Buffer to be written
+    ///
+    /// # Errors
+    /// See [`write_blocks`](Self::write_blocks), and
+    /// [`boot::create_event`](crate::boot::create_event). Errors of the write
+    /// itself may also be returned by [`BlockIO2Operation::wait`].
+    #[cfg(feature = "alloc")]
+    pub fn write_blocks_async(
+        &mut self,
+        media_id: u32,
+        lba: Lba,
+        buffer: Vec<u8>,
+    ) -> Result<BlockIO2Operation> {
+        PendingIo::start(buffer, |token, buffer| unsafe {
+            (self.0.write_blocks_ex)(
+                &mut self.0,
+                media_id,
+                lba,
+                token.cast(),
+                buffer.len(),
+                buffer.as_ptr().cast(),
+            )
+        })
+        .map(BlockIO2Operation)
+    }
+
+    /// Starts flushing all modified data to the physical device, without
+    /// waiting for the flush to complete. The buffer returned by
+    /// [`BlockIO2Operation::wait`] is empty.
+    ///
+    /// # Errors
+    /// See [`flush_blocks`](Self::flush_blocks), and
+    /// [`boot::create_event`](crate::boot::create_event). Errors of the flush
+    /// itself may also be returned by [`BlockIO2Operation::wait`].
+    #[cfg(feature = "alloc")]
+    pub fn flush_blocks_async(&mut self) -> Result<BlockIO2Operation> {
+        PendingIo::start(Vec::new(), |token, _| unsafe {
+            (self.0.flush_blocks_ex)(&mut self.0, token.cast())
+        })
+        .map(BlockIO2Operation)
+    }
+}
+
+/// A non-blocking block I/O 2 operation, started by
+/// [`BlockIO2::read_blocks_async`], [`BlockIO2::write_blocks_async`], or
+/// [`BlockIO2::flush_blocks_async`].
+///
+/// Use [`wait`](Self::wait) to get the result and the buffer back, or
+/// [`wait_any`](Self::wait_any) to wait for the first of several operations.
+/// The operation can also be awaited as a [`Future`], which completes with
+/// the same result as [`wait`](Self::wait). Since UEFI has no interrupts to
+/// wake a task, the future asks to be polled again while the operation is in
+/// progress.
+///
+/// If the operation is dropped before it completes, its buffer is leaked,
+/// since the firmware may still write to it.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct BlockIO2Operation(PendingIo);
+
+#[cfg(feature = "alloc")]
+impl BlockIO2Operation {
+    /// Check whether the operation has completed, without blocking.
+    pub fn is_complete(&mut self) -> Result<bool> {
+        self.0.is_complete()
+    }
+
+    /// Wait for the operation to complete, and get the buffer back.
+    ///
+    /// # Errors
+    /// The errors of the operation, see [`BlockIO2::read_blocks`],
+    /// [`BlockIO2::write_blocks`], and [`BlockIO2::flush_blocks`].
+    pub fn wait(self) -> Result<Vec<u8>> {
+        self.0.wait()
+    }
+
+    /// Wait for any of `operations` to complete, and get its index. The
+    /// operation can then be removed from the list and [`wait`]ed on without
+    /// blocking.
+    ///
+    /// # Errors
+    /// * `uefi::Status::INVALID_PARAMETER` `operations` is empty.
+    ///
+    /// [`wait`]: Self::wait
+    pub fn wait_any(operations: &mut [Self]) -> Result<usize> {
+        PendingIo::wait_any(operations, |operation| &mut operation.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Future for BlockIO2Operation {
+    type Output = Result<Vec<u8>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().0.poll(cx)
+    }
+}
+
 /// Media information structure
 #[repr(transparent)]
 #[derive(Debug)]
@@ -181,3 +445,51 @@ impl BlockIOMedia {
         self.0.optimal_transfer_length_granularity
     }
 }
+
+/// The Erase Block protocol.
+///
+/// This protocol erases blocks of a device, for example to securely wipe or
+/// trim flash media. Firmware installs it alongside the [`BlockIO`] protocol
+/// of devices that support erasing.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EraseBlockProtocol::GUID)]
+pub struct EraseBlock(EraseBlockProtocol);
+
+impl EraseBlock {
+    /// Returns the erase length granularity as a number of logical blocks.
+    ///
+    /// Erasing a range that is not aligned to this granularity may be slower,
+    /// or leave parts of the range unerased on some devices.
+    #[must_use]
+    pub const fn erase_length_granularity(&self) -> u32 {
+        self.0.erase_length_granularity
+    }
+
+    /// Erases a range of blocks, blocking until the erase completes.
+    ///
+    /// # Arguments
+    /// * `media_id`    The media ID that the erase request is for.
+    /// * `lba`         The starting logical block address to be erased.
+    /// * `size`        The size in bytes to be erased. This must be a multiple of the physical block size of the
+    ///   device.
+    ///
+    /// # Errors
+    /// * `uefi::Status::WRITE_PROTECTED`       The device cannot be erased.
+    /// * `uefi::Status::NO_MEDIA`              There is no media in the device.
+    /// * `uefi::Status::MEDIA_CHANGED`         The `media_id` is not for the current media.
+    /// * `uefi::Status::DEVICE_ERROR`          The device reported an error while attempting to perform the erase
+    ///   operation.
+    /// * `uefi::Status::INVALID_PARAMETER`     The erase request contains LBAs that are not valid.
+    pub fn erase_lba_range(&mut self, media_id: u32, lba: Lba, size: usize) -> Result {
+        // A token without an event requests a blocking erase. Passing a token
+        // rather than null is required by some implementations.
+        let mut token = EraseBlockToken {
+            event: ptr::null_mut(),
+            transaction_status: Status::SUCCESS,
+        };
+        unsafe { (self.0.erase_blocks)(&mut self.0, media_id, lba, &mut token, size) }
+            .to_result()?;
+        token.transaction_status.to_result()
+    }
+}
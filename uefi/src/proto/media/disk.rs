@@ -6,6 +6,15 @@ use crate::{Event, Result, Status, StatusExt};
 use core::ptr::NonNull;
 use uefi_raw::protocol::disk::{DiskIo2Protocol, DiskIoProtocol};
 
+#[cfg(feature = "alloc")]
+use {
+    crate::proto::completion::PendingIo,
+    alloc::vec::Vec,
+    core::future::Future,
+    core::pin::Pin,
+    core::task::{Context, Poll},
+};
+
 /// The disk I/O protocol.
 ///
 /// This protocol is used to abstract the block accesses of the block I/O
@@ -183,6 +192,116 @@ impl DiskIo2 {
         .to_result()
     }
 
+    /// Reads bytes from the disk device, blocking until the read completes.
+    ///
+    /// # Arguments:
+    /// * `media_id` - ID of the medium to be read from.
+    /// * `offset` - Starting byte offset on the logical block I/O device to read from.
+    /// * `buffer` - Buffer to read into.
+    ///
+    /// # Errors:
+    /// See [`read_disk_raw`](Self::read_disk_raw).
+    pub fn read_disk(&self, media_id: u32, offset: u64, buffer: &mut [u8]) -> Result {
+        // Safety: without a token, the read completes before returning.
+        unsafe { self.read_disk_raw(media_id, offset, None, buffer.len(), buffer.as_mut_ptr()) }
+    }
+
+    /// Writes bytes to the disk device, blocking until the write completes.
+    ///
+    /// # Arguments:
+    /// * `media_id` - ID of the medium to write to.
+    /// * `offset` - Starting byte offset on the logical block I/O device to write to.
+    /// * `buffer` - Buffer to write from.
+    ///
+    /// # Errors:
+    /// See [`write_disk_raw`](Self::write_disk_raw).
+    pub fn write_disk(&mut self, media_id: u32, offset: u64, buffer: &[u8]) -> Result {
+        // Safety: without a token, the write completes before returning.
+        unsafe { self.write_disk_raw(media_id, offset, None, buffer.len(), buffer.as_ptr()) }
+    }
+
+    /// Starts reading `buffer.len()` bytes from the disk device, without
+    /// waiting for the read to complete.
+    ///
+    /// The returned operation owns `buffer` until the read completes, and
+    /// gives it back from [`DiskIo2Operation::wait`].
+    ///
+    /// # Arguments:
+    /// * `media_id` - ID of the medium to be read from.
+    /// * `offset` - Starting byte offset on the logical block I/O device to read from.
+    /// * `buffer` - Buffer to read into.
+    ///
+    /// # Errors:
+    /// See [`read_disk_raw`](Self::read_disk_raw). Errors of the read itself
+    /// are returned by [`DiskIo2Operation::wait`].
+    #[cfg(feature = "alloc")]
+    pub fn read_disk_async(
+        &self,
+        media_id: u32,
+        offset: u64,
+        buffer: Vec<u8>,
+    ) -> Result<DiskIo2Operation> {
+        PendingIo::start(buffer, |token, buffer| unsafe {
+            (self.0.read_disk_ex)(
+                &self.0,
+                media_id,
+                offset,
+                token.cast(),
+                buffer.len(),
+                buffer.as_mut_ptr().cast(),
+            )
+        })
+        .map(DiskIo2Operation)
+    }
+
+    /// Starts writing `buffer` to the disk device, without waiting for the
+    /// write to complete.
+    ///
+    /// The returned operation owns `buffer` until the write completes, and
+    /// gives it back from [`DiskIo2Operation::wait`].
+    ///
+    /// # Arguments:
+    /// * `media_id` - ID of the medium to write to.
+    /// * `offset` - Starting byte offset on the logical block I/O device to write to.
+    /// * `buffer` - Buffer to write from.
+    ///
+    /// # Errors:
+    /// See [`write_disk_raw`](Self::write_disk_raw). Errors of the write
+    /// itself are returned by [`DiskIo2Operation::wait`].
+    #[cfg(feature = "alloc")]
+    pub fn write_disk_async(
+        &mut self,
+        media_id: u32,
+        offset: u64,
+        buffer: Vec<u8>,
+    ) -> Result<DiskIo2Operation> {
+        PendingIo::start(buffer, |token, buffer| unsafe {
+            (self.0.write_disk_ex)(
+                &mut self.0,
+                media_id,
+                offset,
+                token.cast(),
+                buffer.len(),
+                buffer.as_ptr().cast(),
+            )
+        })
+        .map(DiskIo2Operation)
+    }
+
+    /// Starts flushing all modified data to the physical device, without
+    /// waiting for the flush to complete.
+    ///
+    /// # Errors:
+    /// See [`flush_disk`](Self::flush_disk). Errors of the flush itself are
+    /// returned by [`DiskIo2Operation::wait`].
+    #[cfg(feature = "alloc")]
+    pub fn flush_disk_async(&mut self) -> Result<DiskIo2Operation> {
+        PendingIo::start(Vec::new(), |token, _| unsafe {
+            (self.0.flush_disk_ex)(&mut self.0, token.cast())
+        })
+        .map(DiskIo2Operation)
+    }
+
     /// Flushes all modified data to the physical device.
     ///
     /// # Arguments:
@@ -202,3 +321,58 @@ impl DiskIo2 {
         unsafe { (self.0.flush_disk_ex)(&mut self.0, token.cast()) }.to_result()
     }
 }
+
+/// A non-blocking disk I/O 2 operation, started by
+/// [`DiskIo2::read_disk_async`], [`DiskIo2::write_disk_async`], or
+/// [`DiskIo2::flush_disk_async`].
+///
+/// Other work can be done while the operation is in progress, for example
+/// decompressing the previous chunk of a file while the next one is read.
+/// Use [`wait`](Self::wait) to get the result and the buffer back, or
+/// [`wait_any`](Self::wait_any) to wait for the first of several operations.
+/// The operation can also be awaited as a [`Future`], which completes with
+/// the same result as [`wait`](Self::wait).
+///
+/// If the operation is dropped before it completes, its buffer is leaked,
+/// since the firmware may still write to it.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct DiskIo2Operation(PendingIo);
+
+#[cfg(feature = "alloc")]
+impl DiskIo2Operation {
+    /// Check whether the operation has completed, without blocking.
+    pub fn is_complete(&mut self) -> Result<bool> {
+        self.0.is_complete()
+    }
+
+    /// Wait for the operation to complete, and get the buffer back.
+    ///
+    /// # Errors:
+    /// The errors of the operation, see [`DiskIo2::read_disk_raw`],
+    /// [`DiskIo2::write_disk_raw`], and [`DiskIo2::flush_disk`].
+    pub fn wait(self) -> Result<Vec<u8>> {
+        self.0.wait()
+    }
+
+    /// Wait for any of `operations` to complete, and get its index. The
+    /// operation can then be removed from the list and [`wait`]ed on without
+    /// blocking.
+    ///
+    /// # Errors:
+    /// * `uefi::status::INVALID_PARAMETER` `operations` is empty.
+    ///
+    /// [`wait`]: Self::wait
+    pub fn wait_any(operations: &mut [Self]) -> Result<usize> {
+        PendingIo::wait_any(operations, |operation| &mut operation.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Future for DiskIo2Operation {
+    type Output = Result<Vec<u8>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().0.poll(cx)
+    }
+}
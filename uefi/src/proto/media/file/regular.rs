@@ -148,6 +148,55 @@ impl File for RegularFile {
     }
 }
 
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ErrorType for RegularFile {
+    type Error = Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Read for RegularFile {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Self::read(self, buf)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Write for RegularFile {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        match Self::write(self, buf) {
+            Ok(()) => Ok(buf.len()),
+            // Report a partial write, the error recurs on the next write.
+            Err(err) if *err.data() > 0 => Ok(*err.data()),
+            Err(err) => Err(err.to_err_without_payload()),
+        }
+    }
+
+    fn flush(&mut self) -> Result {
+        File::flush(self)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Seek for RegularFile {
+    fn seek(&mut self, pos: embedded_io::SeekFrom) -> Result<u64> {
+        use embedded_io::SeekFrom;
+
+        let (base, offset) = match pos {
+            SeekFrom::Start(position) => (position, 0),
+            SeekFrom::Current(offset) => (self.get_position()?, offset),
+            SeekFrom::End(offset) => {
+                self.set_position(Self::END_OF_FILE)?;
+                (self.get_position()?, offset)
+            }
+        };
+        let position = base
+            .checked_add_signed(offset)
+            .ok_or(Status::INVALID_PARAMETER)?;
+        self.set_position(position)?;
+        Ok(position)
+    }
+}
+
 /// Read data into `buffer` in chunks of `chunk_size`. Reading is done by
 /// calling `read`, which takes a pointer to a byte buffer and the buffer's
 /// size.
@@ -1,16 +1,34 @@
 //! LoadFile and LoadFile2 protocols.
+//!
+//! Besides the protocol wrappers, this module contains [`LinuxInitrd`],
+//! which provides an initrd to the Linux kernel with the LoadFile2 protocol.
 
+use crate::proto::device_path::DevicePath;
 use crate::proto::unsafe_protocol;
+use crate::proto::BootPolicy;
+use crate::{guid, Guid, Result, Status, StatusExt};
 #[cfg(all(feature = "alloc", feature = "unstable"))]
 use alloc::alloc::Global;
+use core::ptr;
 use uefi_raw::protocol::media::{LoadFile2Protocol, LoadFileProtocol};
 #[cfg(feature = "alloc")]
 use {
-    crate::{mem::make_boxed, proto::device_path::DevicePath, Result, StatusExt},
+    crate::boot,
+    crate::mem::make_boxed,
+    crate::proto::device_path::build::{self, DevicePathBuilder},
+    crate::Handle,
     alloc::boxed::Box,
-    uefi::proto::BootPolicy,
+    alloc::vec::Vec,
+    core::ffi::c_void,
+    core::mem,
+    uefi_raw::protocol::device_path::DevicePathProtocol,
 };
 
+/// GUID of the vendor media device path node that identifies the initrd of
+/// the Linux kernel. The kernel loads the initrd with the LoadFile2 protocol
+/// on the handle with this device path, see [`LinuxInitrd`].
+pub const LINUX_EFI_INITRD_MEDIA_GUID: Guid = guid!("5568e427-68fc-4f3d-ac74-ca555231cc68");
+
 /// Load File Protocol.
 ///
 /// Used to obtain files, that are primarily boot options, from arbitrary
@@ -91,6 +109,61 @@ impl LoadFile {
 
         Ok(file)
     }
+
+    /// Load the file `file_path` into `buffer`, and get the size of the
+    /// file. Unlike [`load_file`], this does not allocate.
+    ///
+    /// # Errors
+    ///
+    /// See [`load_file`]. If `buffer` is too small,
+    /// [`Status::BUFFER_TOO_SMALL`] is returned with the required size.
+    ///
+    /// [`load_file`]: Self::load_file
+    pub fn load_file_into(
+        &mut self,
+        file_path: &DevicePath,
+        boot_policy: BootPolicy,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        // A null buffer only queries the size of the file.
+        let buffer_ptr = if buffer.is_empty() {
+            ptr::null_mut()
+        } else {
+            buffer.as_mut_ptr().cast()
+        };
+        unsafe {
+            (self.0.load_file)(
+                &mut self.0,
+                file_path.as_ffi_ptr().cast(),
+                boot_policy.into(),
+                &mut size,
+                buffer_ptr,
+            )
+        }
+        .to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Get the size of the file `file_path`, without loading it into
+    /// memory. Depending on the device, this may still transfer the file.
+    ///
+    /// # Errors
+    ///
+    /// See [`load_file`].
+    ///
+    /// [`load_file`]: Self::load_file
+    pub fn file_size(&mut self, file_path: &DevicePath, boot_policy: BootPolicy) -> Result<usize> {
+        match self.load_file_into(file_path, boot_policy, &mut []) {
+            Ok(size) => Ok(size),
+            Err(err) => match *err.data() {
+                Some(size) => Ok(size),
+                None => Err(err.to_err_without_payload()),
+            },
+        }
+    }
 }
 
 /// Load File2 Protocol.
@@ -157,4 +230,268 @@ impl LoadFile2 {
 
         Ok(file)
     }
+
+    /// Load the file `file_path` into `buffer`, and get the size of the
+    /// file. Unlike [`load_file`], this does not allocate.
+    ///
+    /// # Errors
+    ///
+    /// See [`load_file`]. If `buffer` is too small,
+    /// [`Status::BUFFER_TOO_SMALL`] is returned with the required size.
+    ///
+    /// [`load_file`]: Self::load_file
+    pub fn load_file_into(
+        &mut self,
+        file_path: &DevicePath,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        // A null buffer only queries the size of the file.
+        let buffer_ptr = if buffer.is_empty() {
+            ptr::null_mut()
+        } else {
+            buffer.as_mut_ptr().cast()
+        };
+        unsafe {
+            (self.0.load_file)(
+                &mut self.0,
+                file_path.as_ffi_ptr().cast(),
+                false, /* always false - see spec */
+                &mut size,
+                buffer_ptr,
+            )
+        }
+        .to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Get the size of the file `file_path`, without loading it into
+    /// memory. Depending on the device, this may still transfer the file.
+    ///
+    /// # Errors
+    ///
+    /// See [`load_file`].
+    ///
+    /// [`load_file`]: Self::load_file
+    pub fn file_size(&mut self, file_path: &DevicePath) -> Result<usize> {
+        match self.load_file_into(file_path, &mut []) {
+            Ok(size) => Ok(size),
+            Err(err) => match *err.data() {
+                Some(size) => Ok(size),
+                None => Err(err.to_err_without_payload()),
+            },
+        }
+    }
+}
+
+/// An initrd provided to the Linux kernel.
+///
+/// Since version 5.8, the Linux EFI stub loads its initrd with the
+/// [`LoadFile2`] protocol installed on a handle with a vendor media device
+/// path identified by [`LINUX_EFI_INITRD_MEDIA_GUID`]. [`install`] creates
+/// such a handle that serves a buffer in memory. The protocols are
+/// uninstalled again when the `LinuxInitrd` is dropped.
+///
+/// The `LinuxInitrd` must be kept alive until the kernel has loaded the
+/// initrd, which happens when the kernel image is started with
+/// [`boot::start_image`]. Only one initrd can be installed at a time.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot::{self, LoadImageSource};
+/// use uefi::proto::media::load_file::LinuxInitrd;
+/// # fn boot_linux(kernel: &[u8], initrd: Vec<u8>) -> uefi::Result {
+///
+/// let initrd = LinuxInitrd::install(initrd.into_boxed_slice())?;
+/// let image = boot::load_image(
+///     boot::image_handle(),
+///     LoadImageSource::FromBuffer {
+///         buffer: kernel,
+///         file_path: None,
+///     },
+/// )?;
+/// boot::start_image(image)?;
+/// drop(initrd);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`install`]: Self::install
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct LinuxInitrd {
+    handle: Handle,
+    instance: Option<InitrdInstance>,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct InitrdInstance {
+    device_path: Box<DevicePath>,
+    load_file: Box<InitrdLoadFile2>,
+}
+
+/// LoadFile2 protocol implementation, followed by the data it serves.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+#[repr(C)]
+struct InitrdLoadFile2 {
+    protocol: LoadFile2Protocol,
+    data: Box<[u8]>,
+}
+
+#[cfg(feature = "alloc")]
+unsafe extern "efiapi" fn initrd_load_file(
+    this: *mut LoadFile2Protocol,
+    _file_path: *const DevicePathProtocol,
+    boot_policy: bool,
+    buffer_size: *mut usize,
+    buffer: *mut c_void,
+) -> Status {
+    if boot_policy {
+        return Status::UNSUPPORTED;
+    }
+    if this.is_null() || buffer_size.is_null() {
+        return Status::INVALID_PARAMETER;
+    }
+
+    // Safety: the protocol is the first field of `InitrdLoadFile2`.
+    let data = unsafe { &(*this.cast::<InitrdLoadFile2>()).data };
+    let buffer_size = unsafe { &mut *buffer_size };
+    if buffer.is_null() || *buffer_size < data.len() {
+        *buffer_size = data.len();
+        return Status::BUFFER_TOO_SMALL;
+    }
+    unsafe { ptr::copy_nonoverlapping(data.as_ptr(), buffer.cast(), data.len()) };
+    *buffer_size = data.len();
+    Status::SUCCESS
+}
+
+#[cfg(feature = "alloc")]
+impl LinuxInitrd {
+    /// Install the device path and the LoadFile2 protocol that serve `data`
+    /// as the initrd on a new handle.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ALREADY_STARTED`]: an initrd is already installed.
+    /// * [`Status::OUT_OF_RESOURCES`]: the handle could not be created.
+    pub fn install(data: Box<[u8]>) -> Result<Self> {
+        let mut storage = Vec::new();
+        let device_path = DevicePathBuilder::with_vec(&mut storage)
+            .push(&build::media::Vendor {
+                vendor_guid: LINUX_EFI_INITRD_MEDIA_GUID,
+                vendor_defined_data: &[],
+            })
+            .and_then(DevicePathBuilder::finalize)
+            .expect("the initrd device path fits in a vector")
+            .to_boxed();
+
+        let mut remaining = &*device_path;
+        if boot::locate_device_path::<DevicePath>(&mut remaining).is_ok() {
+            return Err(Status::ALREADY_STARTED.into());
+        }
+
+        let load_file = Box::new(InitrdLoadFile2 {
+            protocol: LoadFile2Protocol {
+                load_file: initrd_load_file,
+            },
+            data,
+        });
+
+        // Safety: the interfaces stay valid until they are uninstalled.
+        let handle = unsafe {
+            boot::install_protocol_interface(
+                None,
+                &DevicePathProtocol::GUID,
+                device_path.as_ffi_ptr().cast(),
+            )
+        }?;
+        let result = unsafe {
+            boot::install_protocol_interface(
+                Some(handle),
+                &LoadFile2Protocol::GUID,
+                ptr::from_ref(&*load_file).cast(),
+            )
+        };
+        if let Err(err) = result {
+            let uninstalled = unsafe {
+                boot::uninstall_protocol_interface(
+                    handle,
+                    &DevicePathProtocol::GUID,
+                    device_path.as_ffi_ptr().cast(),
+                )
+            };
+            if uninstalled.is_err() {
+                mem::forget(device_path);
+            }
+            return Err(err);
+        }
+
+        Ok(Self {
+            handle,
+            instance: Some(InitrdInstance {
+                device_path,
+                load_file,
+            }),
+        })
+    }
+
+    /// Get the handle that the protocols are installed on.
+    #[must_use]
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Get the data of the initrd.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.instance.as_ref().unwrap().load_file.data
+    }
+
+    /// Uninstall the protocols, and get the data of the initrd back.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: a protocol is still in use and cannot be
+    ///   uninstalled. The data is leaked, since it may still be accessed.
+    pub fn uninstall(mut self) -> Result<Box<[u8]>> {
+        Self::uninstall_instance(self.handle, self.instance.take().unwrap())
+    }
+
+    fn uninstall_instance(handle: Handle, instance: InitrdInstance) -> Result<Box<[u8]>> {
+        let result = unsafe {
+            boot::uninstall_protocol_interface(
+                handle,
+                &LoadFile2Protocol::GUID,
+                ptr::from_ref(&*instance.load_file).cast(),
+            )
+        }
+        .and_then(|()| unsafe {
+            boot::uninstall_protocol_interface(
+                handle,
+                &DevicePathProtocol::GUID,
+                instance.device_path.as_ffi_ptr().cast(),
+            )
+        });
+        match result {
+            Ok(()) => Ok(instance.load_file.data),
+            Err(err) => {
+                mem::forget(instance);
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for LinuxInitrd {
+    fn drop(&mut self) {
+        if let Some(instance) = self.instance.take() {
+            let _ = Self::uninstall_instance(self.handle, instance);
+        }
+    }
 }
@@ -11,3 +11,4 @@ pub mod disk;
 pub mod fs;
 pub mod load_file;
 pub mod partition;
+pub mod ram_disk;
@@ -3,6 +3,9 @@
 use crate::proto::unsafe_protocol;
 use crate::{guid, Char16, Guid};
 
+#[cfg(feature = "alloc")]
+use {crate::data_types::chars::NUL_16, crate::CString16};
+
 newtype_enum! {
     /// MBR OS type.
     ///
@@ -190,6 +193,24 @@ impl GptPartitionEntry {
             .checked_sub(self.starting_lba)?
             .checked_add(1)
     }
+
+    /// Get the human-readable name of the partition. The name is terminated
+    /// by the first null character, or by the end of the field if all 36
+    /// characters are used.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn partition_name(&self) -> CString16 {
+        // Copy the field, since it may not be aligned.
+        let chars = self.partition_name;
+        let mut name = CString16::new();
+        for c in chars {
+            if c == NUL_16 {
+                break;
+            }
+            name.push(c);
+        }
+        name
+    }
 }
 
 newtype_enum! {
@@ -204,6 +225,17 @@ newtype_enum! {
     }
 }
 
+/// Partition table record of a partition, returned by
+/// [`PartitionInfo::record`].
+#[derive(Clone, Copy, Debug)]
+pub enum PartitionRecord<'a> {
+    /// Record of a partition in a legacy MBR.
+    Mbr(&'a MbrPartitionRecord),
+
+    /// Entry of a partition in a GPT.
+    Gpt(&'a GptPartitionEntry),
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 union PartitionInfoRecord {
@@ -243,6 +275,18 @@ impl PartitionInfo {
         self.system == 1
     }
 
+    /// Get the partition table record of the partition. Returns None if the
+    /// partition type is neither MBR nor GPT, or if the protocol revision
+    /// is not supported.
+    #[must_use]
+    pub fn record(&self) -> Option<PartitionRecord<'_>> {
+        if let Some(mbr) = self.mbr_partition_record() {
+            Some(PartitionRecord::Mbr(mbr))
+        } else {
+            self.gpt_partition_entry().map(PartitionRecord::Gpt)
+        }
+    }
+
     /// Get the MBR partition record. Returns None if the partition
     /// type is not MBR.
     #[must_use]
@@ -277,6 +321,7 @@ impl PartitionInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cstr16;
 
     #[test]
     fn test_partition_attributes() {
@@ -284,4 +329,29 @@ mod tests {
             GptPartitionAttributes::from_bits_retain(0xabcd_0000_0000_0007);
         assert_eq!(attr.type_specific_bits(), 0xabcd);
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_gpt_partition_name() {
+        let mut entry = GptPartitionEntry {
+            partition_type_guid: GptPartitionType::EFI_SYSTEM_PARTITION,
+            unique_partition_guid: Guid::ZERO,
+            starting_lba: 34,
+            ending_lba: 2047,
+            attributes: GptPartitionAttributes::empty(),
+            partition_name: [NUL_16; 36],
+        };
+        assert!(entry.partition_name().is_empty());
+
+        let mut name = entry.partition_name;
+        for (c, b) in name.iter_mut().zip("ESP".chars()) {
+            *c = Char16::try_from(b).unwrap();
+        }
+        entry.partition_name = name;
+        assert_eq!(entry.partition_name(), cstr16!("ESP"));
+
+        // A name that uses the whole field is not null-terminated.
+        entry.partition_name = [Char16::try_from('a').unwrap(); 36];
+        assert_eq!(entry.partition_name().num_chars(), 36);
+    }
 }
@@ -0,0 +1,175 @@
+//! RAM Disk protocol.
+//!
+//! The [`RamDisk`] protocol registers a buffer in memory as a virtual disk
+//! or CD, for example an ISO image downloaded during HTTP boot. The firmware
+//! installs a [`BlockIO`] protocol for the new device, so that it can be
+//! accessed like any other disk, and its file systems can be mounted.
+//!
+//! [`BlockIO`]: crate::proto::media::block::BlockIO
+
+use crate::proto::unsafe_protocol;
+use uefi_raw::protocol::media::RamDiskProtocol;
+
+#[cfg(feature = "alloc")]
+use {
+    crate::boot,
+    crate::proto::device_path::media::RamDiskType,
+    crate::proto::device_path::{DevicePath, FfiDevicePath},
+    crate::{Result, Status, StatusExt},
+    alloc::boxed::Box,
+    core::ptr::{self, NonNull},
+};
+
+/// The RAM Disk protocol.
+///
+/// Use [`register`] to register a buffer as a virtual disk, which is
+/// unregistered again when the returned [`RamDiskRegistration`] is dropped.
+///
+/// [`register`]: Self::register
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(RamDiskProtocol::GUID)]
+pub struct RamDisk(RamDiskProtocol);
+
+#[cfg(feature = "alloc")]
+impl RamDisk {
+    /// Register `buffer` as a RAM disk of type `disk_type`. The returned
+    /// registration owns the buffer, and unregisters the RAM disk when it is
+    /// dropped.
+    ///
+    /// If `parent` is set, the device path of the RAM disk is a child of it.
+    /// Otherwise it is a child of the device path of the RAM disk driver.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `disk_type` is not supported.
+    /// * [`Status::INVALID_PARAMETER`]: `buffer` is empty.
+    /// * [`Status::ALREADY_STARTED`]: a RAM disk with the same device path is
+    ///   already registered.
+    /// * [`Status::OUT_OF_RESOURCES`]: there are not enough resources to
+    ///   register the RAM disk.
+    pub fn register(
+        &self,
+        buffer: Box<[u8]>,
+        disk_type: RamDiskType,
+        parent: Option<&DevicePath>,
+    ) -> Result<RamDiskRegistration<'_>> {
+        // Safety: the registration owns the buffer until the RAM disk is
+        // unregistered.
+        let device_path = unsafe {
+            self.register_raw(
+                buffer.as_ptr() as u64,
+                buffer.len() as u64,
+                disk_type,
+                parent,
+            )
+        }?;
+        Ok(RamDiskRegistration {
+            ram_disk: self,
+            device_path,
+            buffer: Some(buffer),
+        })
+    }
+
+    /// Register `size` bytes of memory starting at `base` as a RAM disk of
+    /// type `disk_type`, and get the device path of the RAM disk. See
+    /// [`register`] for the other parameters and the errors.
+    ///
+    /// # Safety
+    ///
+    /// The memory must remain valid, and must not be used otherwise, until
+    /// the RAM disk is unregistered with [`unregister_raw`].
+    ///
+    /// [`register`]: Self::register
+    /// [`unregister_raw`]: Self::unregister_raw
+    pub unsafe fn register_raw(
+        &self,
+        base: u64,
+        size: u64,
+        disk_type: RamDiskType,
+        parent: Option<&DevicePath>,
+    ) -> Result<Box<DevicePath>> {
+        let parent = parent.map_or(ptr::null(), |parent| parent.as_ffi_ptr().cast());
+        let mut device_path = ptr::null_mut();
+        (self.0.register)(base, size, &disk_type.0, parent, &mut device_path).to_result()?;
+
+        // The device path is allocated by the driver.
+        let device_path = NonNull::new(device_path).ok_or(Status::NOT_FOUND)?;
+        let boxed =
+            DevicePath::from_ffi_ptr(device_path.as_ptr().cast::<FfiDevicePath>()).to_boxed();
+        let _ = boot::free_pool(device_path.cast());
+        Ok(boxed)
+    }
+
+    /// Unregister the RAM disk with the device path `device_path`.
+    ///
+    /// # Safety
+    ///
+    /// If the RAM disk is owned by a [`RamDiskRegistration`], its buffer is
+    /// freed while the registration still refers to the RAM disk.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `device_path` is not a RAM disk.
+    /// * [`Status::NOT_FOUND`]: the RAM disk is not registered.
+    pub unsafe fn unregister_raw(&self, device_path: &DevicePath) -> Result {
+        (self.0.unregister)(device_path.as_ffi_ptr().cast()).to_result()
+    }
+}
+
+/// A buffer registered as a RAM disk with [`RamDisk::register`].
+///
+/// The RAM disk is unregistered when the registration is dropped. If
+/// unregistering fails, the buffer is leaked, since the RAM disk may still
+/// be accessed. Use [`unregister`] to handle errors, or to get the buffer
+/// back.
+///
+/// [`unregister`]: Self::unregister
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct RamDiskRegistration<'a> {
+    ram_disk: &'a RamDisk,
+    device_path: Box<DevicePath>,
+    buffer: Option<Box<[u8]>>,
+}
+
+#[cfg(feature = "alloc")]
+impl RamDiskRegistration<'_> {
+    /// Get the device path of the RAM disk. It can be used to find the
+    /// handle of the RAM disk with [`boot::locate_device_path`].
+    #[must_use]
+    pub fn device_path(&self) -> &DevicePath {
+        &self.device_path
+    }
+
+    /// Unregister the RAM disk, and get the buffer back.
+    ///
+    /// # Errors
+    ///
+    /// See [`RamDisk::unregister_raw`]. The buffer is leaked on error.
+    pub fn unregister(mut self) -> Result<Box<[u8]>> {
+        self.unregister_impl()?;
+        Ok(self.buffer.take().unwrap())
+    }
+
+    fn unregister_impl(&mut self) -> Result {
+        // Safety: the buffer is only freed after the RAM disk has been
+        // unregistered.
+        let result = unsafe { self.ram_disk.unregister_raw(&self.device_path) };
+        if result.is_err() {
+            if let Some(buffer) = self.buffer.take() {
+                Box::leak(buffer);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for RamDiskRegistration<'_> {
+    fn drop(&mut self) {
+        if self.buffer.is_some() {
+            let _ = self.unregister_impl();
+        }
+    }
+}
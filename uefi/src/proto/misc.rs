@@ -8,9 +8,14 @@ use crate::proto::unsafe_protocol;
 use crate::{Result, StatusExt};
 
 /// Protocol for retrieving a high-resolution timestamp counter.
+///
+/// [`boot::Instant`] uses this protocol to measure durations.
+///
 /// **Note:**
 /// If your UEFI firmware not support timestamp protocol which first added at UEFI spec 2.4 2013.
 /// you also could use `RDTSC` in rust, here is a demo [Slint-UI](https://github.com/slint-ui/slint/blob/2c0ba2bc0f151eba8d1fa17839fa2ac58832ca80/examples/uefi-demo/main.rs#L28-L62) who use uefi-rs.
+///
+/// [`boot::Instant`]: crate::boot::Instant
 #[derive(Debug)]
 #[repr(transparent)]
 #[unsafe_protocol(TimestampProtocol::GUID)]
@@ -8,23 +8,30 @@
 //!
 //! [`boot`]: crate::boot#accessing-protocols
 
+pub mod ata;
 pub mod console;
 pub mod debug;
+pub mod decompress;
 pub mod device_path;
 pub mod driver;
 pub mod loaded_image;
 pub mod media;
 pub mod misc;
 pub mod network;
+pub mod nvme;
 pub mod pi;
 pub mod rng;
+pub mod scsi;
+pub mod sd_mmc;
 pub mod security;
 pub mod shell_params;
 pub mod shim;
+pub mod storage_security;
 pub mod string;
 pub mod tcg;
 
 mod boot_policy;
+pub(crate) mod completion;
 
 pub use boot_policy::{BootPolicy, BootPolicyError};
 pub use uefi_macros::unsafe_protocol;
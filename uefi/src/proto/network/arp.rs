@@ -0,0 +1,456 @@
+//! ARP protocol.
+//!
+//! ARP instances resolve IPv4 addresses to hardware addresses and manage the
+//! ARP cache of a network interface. They are created through the
+//! [`ArpServiceBinding`] protocol installed on a network interface handle;
+//! the [`ArpResolver`] type takes care of creating and configuring the child
+//! instance:
+//!
+//! ```no_run
+//! use core::net::Ipv4Addr;
+//! use core::time::Duration;
+//! use uefi::proto::network::arp::{ArpConfig, ArpResolver};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let config = ArpConfig::new(Ipv4Addr::new(192, 168, 0, 10));
+//! let mut arp = ArpResolver::new(nic_handle, &config)?;
+//! let mac = arp.resolve(Ipv4Addr::new(192, 168, 0, 1), Some(Duration::from_secs(1)))?;
+//! log::info!("gateway is at {:02x?}", &mac.0[..6]);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::MacAddress;
+use crate::duration;
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{boot, Handle, Result, Status, StatusExt};
+use core::mem;
+use core::net::Ipv4Addr;
+use core::ptr::{self, NonNull};
+use core::slice;
+use core::time::Duration;
+use uefi_raw::protocol::network::arp::{ArpConfigData, ArpFindData, ArpProtocol};
+
+/// Protocol type (EtherType) of IPv4, the only protocol supported by the
+/// safe wrapper.
+const PROTOCOL_TYPE_IPV4: u16 = 0x0800;
+
+/// Service binding protocol for creating [`Arp`] instances.
+pub type ArpServiceBinding = ServiceBinding<Arp>;
+
+/// ARP protocol.
+///
+/// Corresponds to the `EFI_ARP_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(ArpProtocol::GUID)]
+pub struct Arp(ArpProtocol);
+
+unsafe impl ServiceBindingChild for Arp {
+    const SERVICE_BINDING_GUID: crate::Guid = ArpProtocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of an [`Arp`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArpConfig {
+    /// IPv4 address of the station, used as the sender address of requests.
+    pub station_address: Ipv4Addr,
+
+    /// Time that dynamic entries remain in the cache. If zero, the
+    /// implementation's default is used.
+    pub entry_timeout: Duration,
+
+    /// Number of times a request is retried. If zero, the implementation's
+    /// default is used.
+    pub retry_count: u32,
+
+    /// Time to wait for a reply before retrying a request. If zero, the
+    /// implementation's default is used.
+    pub retry_timeout: Duration,
+}
+
+impl ArpConfig {
+    /// Create a configuration for `station_address` with the
+    /// implementation's default timeouts.
+    #[must_use]
+    pub const fn new(station_address: Ipv4Addr) -> Self {
+        Self {
+            station_address,
+            entry_timeout: Duration::ZERO,
+            retry_count: 0,
+            retry_timeout: Duration::ZERO,
+        }
+    }
+}
+
+/// An entry of the ARP cache.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArpEntry {
+    /// IPv4 address of the entry.
+    pub ip: Ipv4Addr,
+
+    /// Hardware address of the entry. Unused for deny entries.
+    pub mac: MacAddress,
+
+    /// The entry blocks resolution of `ip` instead of resolving it.
+    pub deny: bool,
+
+    /// The entry does not expire.
+    pub is_static: bool,
+}
+
+/// Convert `duration` to the 100ns units used by the ARP protocol,
+/// saturating on overflow.
+fn timeout_units(duration: Duration) -> u32 {
+    u32::try_from(duration::as_timer_units(duration)).unwrap_or(u32::MAX)
+}
+
+impl Arp {
+    /// Configure the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: the station address is already used by
+    ///   another instance with a different configuration.
+    pub fn configure(&mut self, config: &ArpConfig) -> Result {
+        let mut station_address = config.station_address.octets();
+        let config_data = ArpConfigData {
+            sw_address_type: PROTOCOL_TYPE_IPV4,
+            sw_address_length: 4,
+            station_address: station_address.as_mut_ptr().cast(),
+            entry_time_out: timeout_units(config.entry_timeout),
+            retry_count: config.retry_count,
+            retry_time_out: timeout_units(config.retry_timeout),
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. Pending requests are
+    /// cancelled.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Add an entry resolving `ip` to `mac` to the cache.
+    ///
+    /// If `timeout` is `None`, the entry does not expire. Otherwise it is
+    /// dynamic and expires after `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::ACCESS_DENIED`]: an entry for `ip` already exists and
+    ///   `overwrite` is false.
+    pub fn add(
+        &mut self,
+        ip: Ipv4Addr,
+        mac: MacAddress,
+        timeout: Option<Duration>,
+        overwrite: bool,
+    ) -> Result {
+        let ip = ip.octets();
+        let timeout = timeout.map_or(0, |timeout| timeout_units(timeout).max(1));
+        unsafe {
+            (self.0.add)(
+                &mut self.0,
+                false,
+                ip.as_ptr().cast(),
+                mac.0.as_ptr().cast(),
+                timeout,
+                overwrite,
+            )
+        }
+        .to_result()
+    }
+
+    /// Add a permanent entry that prevents `ip` from being resolved.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::ACCESS_DENIED`]: an entry for `ip` already exists and
+    ///   `overwrite` is false.
+    pub fn deny(&mut self, ip: Ipv4Addr, overwrite: bool) -> Result {
+        let ip = ip.octets();
+        unsafe {
+            (self.0.add)(
+                &mut self.0,
+                true,
+                ip.as_ptr().cast(),
+                ptr::null(),
+                0,
+                overwrite,
+            )
+        }
+        .to_result()
+    }
+
+    /// Get all entries of the cache.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    pub fn entries(&mut self) -> Result<ArpEntries> {
+        self.find(ptr::null())
+    }
+
+    /// Look up the hardware address of `ip` in the cache, without sending a
+    /// request. Returns `None` if there is no entry for `ip`, or if it is a
+    /// deny entry.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    pub fn lookup(&mut self, ip: Ipv4Addr) -> Result<Option<MacAddress>> {
+        let ip = ip.octets();
+        let entries = self.find(ip.as_ptr().cast())?;
+        let entry = entries.iter().find(|entry| !entry.deny);
+        Ok(entry.map(|entry| entry.mac))
+    }
+
+    fn find(&mut self, address: *const u8) -> Result<ArpEntries> {
+        let mut entry_len = 0;
+        let mut count = 0;
+        let mut entries = ptr::null_mut();
+        let status = unsafe {
+            (self.0.find)(
+                &mut self.0,
+                true,
+                address.cast(),
+                &mut entry_len,
+                &mut count,
+                &mut entries,
+                false,
+            )
+        };
+        // No matching entries is not an error for the caller.
+        if status == Status::NOT_FOUND {
+            return Ok(ArpEntries::empty());
+        }
+        status.to_result()?;
+
+        Ok(ArpEntries {
+            data: NonNull::new(entries.cast()),
+            entry_len: entry_len as usize,
+            count: count as usize,
+        })
+    }
+
+    /// Delete the entry for `ip` from the cache, or all entries if `ip` is
+    /// `None`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NOT_FOUND`]: there is no entry for `ip`.
+    pub fn delete(&mut self, ip: Option<Ipv4Addr>) -> Result {
+        let ip = ip.map(|ip| ip.octets());
+        let address = ip.as_ref().map_or(ptr::null(), |ip| ip.as_ptr());
+        unsafe { (self.0.delete)(&mut self.0, true, address.cast()) }.to_result()
+    }
+
+    /// Delete all dynamic entries from the cache.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NOT_FOUND`]: there are no dynamic entries.
+    pub fn flush(&mut self) -> Result {
+        unsafe { (self.0.flush)(&mut self.0) }.to_result()
+    }
+
+    /// Resolve `ip` to a hardware address, sending ARP requests if it is not
+    /// in the cache, and wait for the result.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::ACCESS_DENIED`]: `ip` is in the cache as a deny entry.
+    /// * [`Status::NOT_FOUND`]: no reply was received after all retries.
+    /// * [`Status::TIMEOUT`]: `timeout` elapsed before the address was
+    ///   resolved.
+    pub fn request(&mut self, ip: Ipv4Addr, timeout: Option<Duration>) -> Result<MacAddress> {
+        let ip = ip.octets();
+        let mut mac = MacAddress([0; 32]);
+        let event = CompletionEvent::new()?;
+        let status = unsafe {
+            (self.0.request)(
+                &mut self.0,
+                ip.as_ptr().cast(),
+                event.as_raw(),
+                mac.0.as_mut_ptr().cast(),
+            )
+        };
+        if status == Status::NOT_READY {
+            // The request has been sent, and `event` is signaled once the
+            // address has been resolved or all retries have failed.
+            if let Err(err) = event.wait(timeout, || {}) {
+                let _ = unsafe { (self.0.cancel)(&mut self.0, ip.as_ptr().cast(), event.as_raw()) };
+                return Err(err);
+            }
+            // The address is left untouched if resolution failed.
+            if mac.0 == [0; 32] {
+                return Err(Status::NOT_FOUND.into());
+            }
+        } else {
+            status.to_result()?;
+        }
+        Ok(mac)
+    }
+}
+
+/// Entries of the ARP cache, as returned by [`Arp::entries`].
+///
+/// The entries are allocated by the ARP implementation and freed on drop.
+#[derive(Debug)]
+pub struct ArpEntries {
+    data: Option<NonNull<u8>>,
+    entry_len: usize,
+    count: usize,
+}
+
+impl ArpEntries {
+    const fn empty() -> Self {
+        Self {
+            data: None,
+            entry_len: 0,
+            count: 0,
+        }
+    }
+
+    const fn as_bytes(&self) -> &[u8] {
+        match self.data {
+            Some(data) => unsafe {
+                slice::from_raw_parts(data.as_ptr(), self.entry_len * self.count)
+            },
+            None => &[],
+        }
+    }
+
+    /// Get the number of entries.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Check whether there are no entries.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Iterate over the entries.
+    pub fn iter(&self) -> impl Iterator<Item = ArpEntry> + '_ {
+        let entry_len = self.entry_len.max(1);
+        self.as_bytes()
+            .chunks_exact(entry_len)
+            .filter_map(parse_entry)
+    }
+}
+
+impl Drop for ArpEntries {
+    fn drop(&mut self) {
+        if let Some(data) = self.data {
+            let _ = unsafe { boot::free_pool(data) };
+        }
+    }
+}
+
+/// Parse an ARP cache entry: an [`ArpFindData`] header followed by the
+/// protocol and hardware addresses. Returns `None` for entries that are not
+/// IPv4 entries.
+fn parse_entry(entry: &[u8]) -> Option<ArpEntry> {
+    let header_len = mem::size_of::<ArpFindData>();
+    let header = entry.get(..header_len)?;
+    // Entries are packed, so the header may be unaligned.
+    let header = unsafe { header.as_ptr().cast::<ArpFindData>().read_unaligned() };
+    if header.sw_address_type != PROTOCOL_TYPE_IPV4 || header.sw_address_length != 4 {
+        return None;
+    }
+
+    let addresses = &entry[header_len..];
+    let ip = <[u8; 4]>::try_from(addresses.get(..4)?).ok()?;
+    let hw_len = usize::from(header.hw_address_length).min(32);
+    let hw = addresses.get(4..4 + hw_len)?;
+    let mut mac = MacAddress([0; 32]);
+    mac.0[..hw_len].copy_from_slice(hw);
+
+    Some(ArpEntry {
+        ip: Ipv4Addr::from(ip),
+        mac,
+        deny: header.deny_flag,
+        is_static: header.static_flag,
+    })
+}
+
+/// An ARP resolver, including the [`Arp`] child instance it uses.
+///
+/// The child instance is destroyed when the `ArpResolver` is dropped.
+#[derive(Debug)]
+pub struct ArpResolver {
+    arp: ServiceChild<Arp>,
+}
+
+impl ArpResolver {
+    /// Create an [`Arp`] instance on the network interface `nic_handle` and
+    /// configure it with `config`.
+    pub fn new(nic_handle: Handle, config: &ArpConfig) -> Result<Self> {
+        let mut arp = ServiceChild::<Arp>::new(nic_handle)?;
+        arp.configure(config)?;
+        Ok(Self { arp })
+    }
+
+    /// Resolve `ip` to a hardware address. See [`Arp::request`].
+    pub fn resolve(&mut self, ip: Ipv4Addr, timeout: Option<Duration>) -> Result<MacAddress> {
+        self.arp.request(ip, timeout)
+    }
+
+    /// Get the underlying [`Arp`] protocol.
+    pub fn protocol(&mut self) -> &mut Arp {
+        &mut self.arp
+    }
+}
+
+impl Drop for ArpResolver {
+    fn drop(&mut self) {
+        // Cancel any pending requests before the child is destroyed.
+        let _ = self.arp.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        let mut entry = [0u8; 22];
+        entry[..4].copy_from_slice(&22u32.to_ne_bytes());
+        entry[5] = 1; // static
+        entry[6..8].copy_from_slice(&1u16.to_ne_bytes());
+        entry[8..10].copy_from_slice(&PROTOCOL_TYPE_IPV4.to_ne_bytes());
+        entry[10] = 6;
+        entry[11] = 4;
+        entry[12..16].copy_from_slice(&[10, 0, 2, 2]);
+        entry[16..22].copy_from_slice(&[0x52, 0x55, 0x0a, 0x00, 0x02, 0x02]);
+
+        let parsed = parse_entry(&entry).unwrap();
+        assert_eq!(parsed.ip, Ipv4Addr::new(10, 0, 2, 2));
+        assert_eq!(
+            parsed.mac,
+            MacAddress::from([0x52, 0x55, 0x0a, 0x00, 0x02, 0x02])
+        );
+        assert!(!parsed.deny);
+        assert!(parsed.is_static);
+
+        // Truncated entries and entries for other protocols are skipped.
+        assert!(parse_entry(&entry[..20]).is_none());
+        entry[8..10].copy_from_slice(&0x86ddu16.to_ne_bytes());
+        assert!(parse_entry(&entry).is_none());
+    }
+}
@@ -0,0 +1,593 @@
+//! DHCPv4 protocol.
+//!
+//! DHCPv4 instances are created through the [`Dhcp4ServiceBinding`]
+//! protocol installed on a network interface handle. The [`Dhcp4Client`]
+//! type takes care of creating and configuring the child instance:
+//!
+//! ```no_run
+//! use uefi::proto::network::dhcp4::{Dhcp4Client, Dhcp4Config, Dhcp4OptionCode};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let config = Dhcp4Config {
+//!     request_options: &[Dhcp4OptionCode::TFTP_SERVER_NAME.0],
+//!     ..Default::default()
+//! };
+//! let mut dhcp = Dhcp4Client::new(nic_handle, &config)?;
+//! dhcp.start()?;
+//!
+//! let lease = dhcp.lease()?;
+//! log::info!("got address {}", lease.client_address);
+//! if let Some(ack) = dhcp.reply_packet()? {
+//!     log::info!("next server: {}", ack.server_address());
+//!     log::info!("boot file: {:?}", ack.boot_file_name());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, Status, StatusExt};
+use core::fmt::{self, Debug, Formatter};
+use core::iter::FusedIterator;
+use core::mem;
+use core::net::Ipv4Addr;
+use core::ptr::{self, addr_of};
+use core::slice;
+use uefi_raw::protocol::network::dhcp4::{
+    Dhcp4ConfigData, Dhcp4Header, Dhcp4ModeData, Dhcp4Packet, Dhcp4PacketOption, Dhcp4Protocol,
+};
+use uefi_raw::{Ipv4Address, MacAddress};
+
+pub use uefi_raw::protocol::network::dhcp4::Dhcp4State;
+
+/// Service binding protocol for creating [`Dhcp4`] instances.
+pub type Dhcp4ServiceBinding = ServiceBinding<Dhcp4>;
+
+/// DHCPv4 protocol.
+///
+/// Corresponds to the `EFI_DHCP4_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Dhcp4Protocol::GUID)]
+pub struct Dhcp4(Dhcp4Protocol);
+
+unsafe impl ServiceBindingChild for Dhcp4 {
+    const SERVICE_BINDING_GUID: crate::Guid = Dhcp4Protocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of a [`Dhcp4`] instance.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Dhcp4Config<'a> {
+    /// Number of times DHCPDISCOVER is sent. If zero, the implementation's
+    /// default is used.
+    pub discover_try_count: u32,
+
+    /// Number of times DHCPREQUEST is sent. If zero, the implementation's
+    /// default is used.
+    pub request_try_count: u32,
+
+    /// Previously leased address to request with INIT-REBOOT. If `None`,
+    /// the DHCP process starts in the INIT state.
+    pub client_address: Option<Ipv4Addr>,
+
+    /// Option codes to request from the server in the parameter request list
+    /// option. If empty, no parameter request list is sent.
+    pub request_options: &'a [u8],
+}
+
+/// State of a DHCP lease, as returned by [`Dhcp4::lease`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Dhcp4Lease {
+    /// State of the DHCP process.
+    pub state: Dhcp4State,
+
+    /// Leased address, or the unspecified address if none.
+    pub client_address: Ipv4Addr,
+
+    /// Address of the server that granted the lease.
+    pub server_address: Ipv4Addr,
+
+    /// Default router address.
+    pub router_address: Ipv4Addr,
+
+    /// Subnet mask of the leased address.
+    pub subnet_mask: Ipv4Addr,
+
+    /// Lease time in seconds. `u32::MAX` means infinite.
+    pub lease_time: u32,
+
+    /// Hardware address of the interface.
+    pub client_mac_address: crate::proto::network::MacAddress,
+}
+
+impl Dhcp4 {
+    fn mode_data(&self) -> Result<Dhcp4ModeData> {
+        let mut mode = Dhcp4ModeData {
+            state: Dhcp4State::STOPPED,
+            config_data: Dhcp4ConfigData {
+                discover_try_count: 0,
+                discover_timeout: ptr::null_mut(),
+                request_try_count: 0,
+                request_timeout: ptr::null_mut(),
+                client_address: Ipv4Address::default(),
+                callback: None,
+                callback_context: ptr::null_mut(),
+                option_count: 0,
+                option_list: ptr::null_mut(),
+            },
+            client_address: Ipv4Address::default(),
+            client_mac_address: MacAddress::default(),
+            server_address: Ipv4Address::default(),
+            router_address: Ipv4Address::default(),
+            subnet_mask: Ipv4Address::default(),
+            lease_time: 0,
+            reply_packet: ptr::null(),
+        };
+        unsafe { (self.0.get_mode_data)(&self.0, &mut mode) }.to_result_with_val(|| mode)
+    }
+
+    /// Get the current state of the lease.
+    pub fn lease(&self) -> Result<Dhcp4Lease> {
+        let mode = self.mode_data()?;
+        Ok(Dhcp4Lease {
+            state: mode.state,
+            client_address: Ipv4Addr::from(mode.client_address),
+            server_address: Ipv4Addr::from(mode.server_address),
+            router_address: Ipv4Addr::from(mode.router_address),
+            subnet_mask: Ipv4Addr::from(mode.subnet_mask),
+            lease_time: mode.lease_time,
+            client_mac_address: crate::proto::network::MacAddress(mode.client_mac_address.0),
+        })
+    }
+
+    /// Get the DHCPACK packet of the current lease, if any.
+    ///
+    /// The packet is owned by the implementation and remains valid until the
+    /// lease changes, which can only happen through a `&mut self` method.
+    pub fn reply_packet(&self) -> Result<Option<Dhcp4PacketRef<'_>>> {
+        let mode = self.mode_data()?;
+        Ok(unsafe { mode.reply_packet.as_ref() }.map(Dhcp4PacketRef::new))
+    }
+
+    /// Configure the instance. The instance must be in the
+    /// [`Dhcp4State::STOPPED`], [`Dhcp4State::INIT`] or
+    /// [`Dhcp4State::INIT_REBOOT`] state.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: the instance is in the wrong state, or
+    ///   another instance is already configured on the interface.
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    pub fn configure(&mut self, config: &Dhcp4Config) -> Result {
+        let request_len =
+            u8::try_from(config.request_options.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+
+        // Parameter request list option, as a (code, length, data) triple.
+        let mut request_option = [0u8; 2 + u8::MAX as usize];
+        request_option[0] = Dhcp4OptionCode::PARAMETER_REQUEST_LIST.0;
+        request_option[1] = request_len;
+        request_option[2..2 + config.request_options.len()].copy_from_slice(config.request_options);
+        let mut option_list = [request_option.as_ptr().cast::<Dhcp4PacketOption>()];
+
+        let config_data = Dhcp4ConfigData {
+            discover_try_count: config.discover_try_count,
+            discover_timeout: ptr::null_mut(),
+            request_try_count: config.request_try_count,
+            request_timeout: ptr::null_mut(),
+            client_address: Ipv4Address(
+                config
+                    .client_address
+                    .unwrap_or(Ipv4Addr::UNSPECIFIED)
+                    .octets(),
+            ),
+            callback: None,
+            callback_context: ptr::null_mut(),
+            option_count: u32::from(request_len != 0),
+            option_list: if request_len == 0 {
+                ptr::null_mut()
+            } else {
+                option_list.as_mut_ptr()
+            },
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Start the DHCP process and wait until a lease has been acquired.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::ALREADY_STARTED`]: the DHCP process has already started.
+    /// * [`Status::TIMEOUT`]: no lease was acquired.
+    /// * [`Status::NO_MEDIA`]: the network cable is disconnected.
+    pub fn start(&mut self) -> Result {
+        // Without a completion event, `Start` runs synchronously.
+        unsafe { (self.0.start)(&mut self.0, ptr::null_mut()) }.to_result()
+    }
+
+    /// Extend the current lease, and wait until it has been extended. If
+    /// `rebind` is true, the request is broadcast to any server instead of
+    /// being sent to the server that granted the lease.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: no lease has been acquired.
+    /// * [`Status::TIMEOUT`]: the lease was not extended.
+    pub fn renew(&mut self, rebind: bool) -> Result {
+        unsafe { (self.0.renew_rebind)(&mut self.0, rebind, ptr::null_mut()) }.to_result()
+    }
+
+    /// Release the current lease and return to the [`Dhcp4State::INIT`]
+    /// state.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: no lease has been acquired.
+    pub fn release(&mut self) -> Result {
+        unsafe { (self.0.release)(&mut self.0) }.to_result()
+    }
+
+    /// Stop the DHCP process without releasing the lease.
+    pub fn stop(&mut self) -> Result {
+        unsafe { (self.0.stop)(&mut self.0) }.to_result()
+    }
+}
+
+/// A DHCPv4 client, including the [`Dhcp4`] child instance it uses.
+///
+/// The lease is not released when the client is dropped; the child instance
+/// is stopped and destroyed.
+#[derive(Debug)]
+pub struct Dhcp4Client {
+    dhcp: ServiceChild<Dhcp4>,
+}
+
+impl Dhcp4Client {
+    /// Create a [`Dhcp4`] instance on the network interface `nic_handle` and
+    /// configure it with `config`.
+    pub fn new(nic_handle: Handle, config: &Dhcp4Config) -> Result<Self> {
+        let mut dhcp = ServiceChild::<Dhcp4>::new(nic_handle)?;
+        dhcp.configure(config)?;
+        Ok(Self { dhcp })
+    }
+
+    /// Acquire a lease. See [`Dhcp4::start`].
+    pub fn start(&mut self) -> Result {
+        self.dhcp.start()
+    }
+
+    /// Extend the lease. See [`Dhcp4::renew`].
+    pub fn renew(&mut self, rebind: bool) -> Result {
+        self.dhcp.renew(rebind)
+    }
+
+    /// Release the lease. See [`Dhcp4::release`].
+    pub fn release(&mut self) -> Result {
+        self.dhcp.release()
+    }
+
+    /// Get the current state of the lease.
+    pub fn lease(&self) -> Result<Dhcp4Lease> {
+        self.dhcp.lease()
+    }
+
+    /// Get the DHCPACK packet of the current lease, if any.
+    pub fn reply_packet(&self) -> Result<Option<Dhcp4PacketRef<'_>>> {
+        self.dhcp.reply_packet()
+    }
+
+    /// Get the underlying [`Dhcp4`] protocol.
+    pub fn protocol(&mut self) -> &mut Dhcp4 {
+        &mut self.dhcp
+    }
+}
+
+impl Drop for Dhcp4Client {
+    fn drop(&mut self) {
+        let _ = self.dhcp.stop();
+        let _ = self.dhcp.reset();
+    }
+}
+
+newtype_enum! {
+/// DHCP option code, as defined by RFC 2132.
+pub enum Dhcp4OptionCode: u8 => {
+    /// Padding.
+    PAD = 0,
+    /// Subnet mask.
+    SUBNET_MASK = 1,
+    /// Routers.
+    ROUTER = 3,
+    /// DNS servers.
+    DNS_SERVER = 6,
+    /// Host name of the client.
+    HOST_NAME = 12,
+    /// Domain name.
+    DOMAIN_NAME = 15,
+    /// Vendor-specific information.
+    VENDOR_SPECIFIC = 43,
+    /// Requested IP address.
+    REQUESTED_ADDRESS = 50,
+    /// IP address lease time.
+    LEASE_TIME = 51,
+    /// Option overload.
+    OVERLOAD = 52,
+    /// DHCP message type.
+    MESSAGE_TYPE = 53,
+    /// Server identifier.
+    SERVER_IDENTIFIER = 54,
+    /// Parameter request list.
+    PARAMETER_REQUEST_LIST = 55,
+    /// Vendor class identifier.
+    VENDOR_CLASS_ID = 60,
+    /// TFTP server name.
+    TFTP_SERVER_NAME = 66,
+    /// Boot file name.
+    BOOTFILE_NAME = 67,
+    /// End of the options.
+    END = 255,
+}}
+
+/// A borrowed DHCPv4 packet.
+#[derive(Clone, Copy)]
+pub struct Dhcp4PacketRef<'a> {
+    header: Dhcp4Header,
+    options: &'a [u8],
+}
+
+impl<'a> Dhcp4PacketRef<'a> {
+    const fn new(packet: &'a Dhcp4Packet) -> Self {
+        // `Length` counts the bytes from the start of the header to the end
+        // of the options.
+        let fixed_len = mem::size_of::<Dhcp4Header>() + mem::size_of::<u32>();
+        let options_len = (packet.length as usize).saturating_sub(fixed_len);
+        let options =
+            unsafe { slice::from_raw_parts(addr_of!(packet.option).cast::<u8>(), options_len) };
+        Self {
+            header: packet.header,
+            options,
+        }
+    }
+
+    /// Get the address assigned to the client (`yiaddr`).
+    #[must_use]
+    pub fn your_address(&self) -> Ipv4Addr {
+        Ipv4Addr::from(self.header.your_addr)
+    }
+
+    /// Get the address of the next server in the boot process (`siaddr`).
+    #[must_use]
+    pub fn server_address(&self) -> Ipv4Addr {
+        Ipv4Addr::from(self.header.server_addr)
+    }
+
+    /// Get the relay agent address (`giaddr`).
+    #[must_use]
+    pub fn gateway_address(&self) -> Ipv4Addr {
+        Ipv4Addr::from(self.header.gateway_addr)
+    }
+
+    /// Get the server host name (`sname`), without the trailing nul bytes.
+    #[must_use]
+    pub fn server_name(&self) -> &[u8] {
+        until_nul(&self.header.server_name)
+    }
+
+    /// Get the boot file name (`file`), without the trailing nul bytes.
+    ///
+    /// If the header field is empty, the [`BOOTFILE_NAME`] option is
+    /// returned instead.
+    ///
+    /// [`BOOTFILE_NAME`]: Dhcp4OptionCode::BOOTFILE_NAME
+    #[must_use]
+    pub fn boot_file_name(&self) -> &[u8] {
+        let file = until_nul(&self.header.boot_file_name);
+        if file.is_empty() {
+            self.option(Dhcp4OptionCode::BOOTFILE_NAME)
+                .map(until_nul)
+                .unwrap_or_default()
+        } else {
+            file
+        }
+    }
+
+    /// Iterate over the options of the packet.
+    #[must_use]
+    pub const fn options(&self) -> Dhcp4Options<'a> {
+        Dhcp4Options::new(self.options)
+    }
+
+    /// Get the data of the first option with `code`.
+    #[must_use]
+    pub fn option(&self, code: Dhcp4OptionCode) -> Option<&'a [u8]> {
+        self.options().find(|o| o.code == code).map(|o| o.data)
+    }
+
+    /// Get the DHCP message type (1 = DHCPDISCOVER, ..., 5 = DHCPACK).
+    #[must_use]
+    pub fn message_type(&self) -> Option<u8> {
+        match self.option(Dhcp4OptionCode::MESSAGE_TYPE)? {
+            [ty] => Some(*ty),
+            _ => None,
+        }
+    }
+
+    /// Get the subnet mask option.
+    #[must_use]
+    pub fn subnet_mask(&self) -> Option<Ipv4Addr> {
+        self.option(Dhcp4OptionCode::SUBNET_MASK)
+            .and_then(|data| Some(Ipv4Addr::from(<[u8; 4]>::try_from(data).ok()?)))
+    }
+
+    /// Get the lease time option, in seconds.
+    #[must_use]
+    pub fn lease_time(&self) -> Option<u32> {
+        self.option(Dhcp4OptionCode::LEASE_TIME)
+            .and_then(|data| Some(u32::from_be_bytes(data.try_into().ok()?)))
+    }
+
+    /// Iterate over the addresses of the router option.
+    pub fn routers(&self) -> impl Iterator<Item = Ipv4Addr> + 'a {
+        addresses(self.option(Dhcp4OptionCode::ROUTER))
+    }
+
+    /// Iterate over the addresses of the DNS server option.
+    pub fn dns_servers(&self) -> impl Iterator<Item = Ipv4Addr> + 'a {
+        addresses(self.option(Dhcp4OptionCode::DNS_SERVER))
+    }
+
+    /// Get the TFTP server name option.
+    #[must_use]
+    pub fn tftp_server_name(&self) -> Option<&'a [u8]> {
+        self.option(Dhcp4OptionCode::TFTP_SERVER_NAME)
+            .map(until_nul)
+    }
+
+    /// Iterate over the sub-options of the vendor-specific information
+    /// option, assuming they use the same encoding as the top-level options.
+    #[must_use]
+    pub fn vendor_options(&self) -> Dhcp4Options<'a> {
+        Dhcp4Options::new(
+            self.option(Dhcp4OptionCode::VENDOR_SPECIFIC)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+impl Debug for Dhcp4PacketRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dhcp4PacketRef")
+            .field("your_address", &self.your_address())
+            .field("server_address", &self.server_address())
+            .field("options", &self.options())
+            .finish()
+    }
+}
+
+/// A DHCP option.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Dhcp4Option<'a> {
+    /// Option code.
+    pub code: Dhcp4OptionCode,
+
+    /// Option data.
+    pub data: &'a [u8],
+}
+
+/// Iterator over the options of a DHCP packet.
+///
+/// Pad options are skipped, and iteration stops at the end option or at the
+/// first truncated option.
+#[derive(Clone)]
+pub struct Dhcp4Options<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Dhcp4Options<'a> {
+    /// Create an iterator over the options encoded in `data`.
+    #[must_use]
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for Dhcp4Options<'a> {
+    type Item = Dhcp4Option<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (&code, rest) = self.data.split_first()?;
+            let code = Dhcp4OptionCode(code);
+            if code == Dhcp4OptionCode::PAD {
+                self.data = rest;
+                continue;
+            }
+            let len = match rest.split_first() {
+                Some((&len, _)) if code != Dhcp4OptionCode::END => usize::from(len),
+                _ => {
+                    self.data = &[];
+                    return None;
+                }
+            };
+            let Some(data) = rest.get(1..1 + len) else {
+                self.data = &[];
+                return None;
+            };
+            self.data = &rest[1 + len..];
+            return Some(Dhcp4Option { code, data });
+        }
+    }
+}
+
+impl FusedIterator for Dhcp4Options<'_> {}
+
+impl Debug for Dhcp4Options<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// Get the bytes of `data` up to the first nul byte.
+fn until_nul(data: &[u8]) -> &[u8] {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    &data[..end]
+}
+
+/// Iterate over the IPv4 addresses in an option.
+fn addresses(data: Option<&[u8]>) -> impl Iterator<Item = Ipv4Addr> + '_ {
+    data.unwrap_or_default()
+        .chunks_exact(4)
+        .map(|b| Ipv4Addr::new(b[0], b[1], b[2], b[3]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options() {
+        let data = [
+            0, 0, // padding
+            53, 1, 5, // message type: ACK
+            3, 8, 10, 0, 0, 1, 10, 0, 0, 2, // routers
+            51, 4, 0, 0, 0x0e, 0x10, // lease time: 3600
+            255,  // end
+            1, 4, 255, 255, 255, 0, // ignored after the end
+        ];
+        let mut options = Dhcp4Options::new(&data);
+        assert_eq!(
+            options.next(),
+            Some(Dhcp4Option {
+                code: Dhcp4OptionCode::MESSAGE_TYPE,
+                data: &[5]
+            })
+        );
+        assert_eq!(options.next().unwrap().code, Dhcp4OptionCode::ROUTER);
+        assert_eq!(options.next().unwrap().data, &[0, 0, 0x0e, 0x10]);
+        assert_eq!(options.next(), None);
+        assert_eq!(options.next(), None);
+    }
+
+    #[test]
+    fn test_truncated_option() {
+        let data = [6, 8, 1, 2, 3, 4];
+        assert_eq!(Dhcp4Options::new(&data).count(), 0);
+        assert_eq!(Dhcp4Options::new(&[12]).count(), 0);
+    }
+
+    #[test]
+    fn test_addresses() {
+        let data = [10, 0, 0, 1, 10, 0, 0, 2, 99];
+        assert!(addresses(Some(&data)).eq([Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)]));
+        assert_eq!(addresses(None).count(), 0);
+    }
+}
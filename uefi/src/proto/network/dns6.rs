@@ -0,0 +1,272 @@
+//! DNSv6 protocol.
+//!
+//! DNSv6 instances are created through the [`Dns6ServiceBinding`]
+//! protocol installed on a network interface handle. The [`Dns6Resolver`]
+//! type takes care of creating and configuring the child instance:
+//!
+//! ```no_run
+//! use uefi::cstr16;
+//! use uefi::proto::network::dns6::{Dns6Config, Dns6Resolver};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut resolver = Dns6Resolver::new(nic_handle, &Dns6Config::default())?;
+//! let addresses = resolver.resolve(cstr16!("example.com"))?;
+//! for addr in addresses.iter() {
+//!     log::info!("example.com has address {addr}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{boot, CStr16, Handle, Result, Status, StatusExt};
+use core::net::Ipv6Addr;
+use core::ptr::{self, NonNull};
+use core::slice;
+use uefi_raw::protocol::network::dns6::{
+    Dns6CompletionToken, Dns6ConfigData, Dns6HostToAddrData, Dns6Protocol, Dns6ResponseData,
+};
+use uefi_raw::Ipv6Address;
+
+/// Maximum number of DNS servers in a [`Dns6Config`].
+pub const MAX_DNS_SERVERS: usize = 8;
+
+/// IP protocol number of UDP, the only transport supported by the DNS
+/// protocol.
+const IP_PROTOCOL_UDP: u8 = 17;
+
+/// Service binding protocol for creating [`Dns6`] instances.
+pub type Dns6ServiceBinding = ServiceBinding<Dns6>;
+
+/// DNSv6 protocol.
+///
+/// Corresponds to the `EFI_DNS6_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Dns6Protocol::GUID)]
+pub struct Dns6(Dns6Protocol);
+
+unsafe impl ServiceBindingChild for Dns6 {
+    const SERVICE_BINDING_GUID: crate::Guid = Dns6Protocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of a [`Dns6`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Dns6Config<'a> {
+    /// DNS servers to query. If empty, the servers provided by DHCPv6 or the
+    /// IPv6 configuration protocol are used. At most [`MAX_DNS_SERVERS`]
+    /// servers may be given.
+    pub dns_servers: &'a [Ipv6Addr],
+
+    /// Local address of the instance. If this is the unspecified address,
+    /// the implementation selects a source address based on the DNS server
+    /// address.
+    pub station_address: Ipv6Addr,
+
+    /// Local port. If zero, an ephemeral port is chosen by the
+    /// implementation.
+    pub local_port: u16,
+
+    /// Cache the results of lookups.
+    pub enable_cache: bool,
+
+    /// Number of times a query is retried. If zero, the implementation's
+    /// default is used.
+    pub retry_count: u32,
+
+    /// Minimum interval between retries, in seconds. If zero, the
+    /// implementation's default is used.
+    pub retry_interval: u32,
+}
+
+impl Default for Dns6Config<'_> {
+    fn default() -> Self {
+        Self {
+            dns_servers: &[],
+            station_address: Ipv6Addr::UNSPECIFIED,
+            local_port: 0,
+            enable_cache: false,
+            retry_count: 0,
+            retry_interval: 0,
+        }
+    }
+}
+
+impl Dns6 {
+    /// Configure the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid, or
+    ///   more than [`MAX_DNS_SERVERS`] DNS servers were given.
+    /// * [`Status::NO_MAPPING`]: address configuration has not finished yet.
+    /// * [`Status::UNSUPPORTED`]: no DNS servers were given and none are
+    ///   available from the IPv6 configuration.
+    pub fn configure(&mut self, config: &Dns6Config) -> Result {
+        if config.dns_servers.len() > MAX_DNS_SERVERS {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let mut servers = [Ipv6Address::default(); MAX_DNS_SERVERS];
+        for (dst, src) in servers.iter_mut().zip(config.dns_servers) {
+            *dst = (*src).into();
+        }
+
+        let config_data = Dns6ConfigData {
+            enable_dns_cache: config.enable_cache,
+            protocol: IP_PROTOCOL_UDP,
+            station_ip: config.station_address.into(),
+            local_port: config.local_port,
+            dns_server_count: config.dns_servers.len() as u32,
+            dns_server_list: if config.dns_servers.is_empty() {
+                ptr::null_mut()
+            } else {
+                servers.as_mut_ptr()
+            },
+            retry_count: config.retry_count,
+            retry_interval: config.retry_interval,
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. Pending lookups are
+    /// cancelled.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Poll for incoming data packets and process outgoing data packets.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: no incoming or outgoing data was processed.
+    /// * [`Status::TIMEOUT`]: data was dropped out of the queues.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Look up the IPv6 addresses of `host_name` and wait for the result.
+    ///
+    /// The lookup is retried according to the configured retry count and
+    /// interval.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TIMEOUT`]: no response was received from the DNS servers.
+    /// * [`Status::NOT_FOUND`]: the host name does not exist.
+    /// * [`Status::DEVICE_ERROR`]: the DNS server returned an error.
+    pub fn host_name_to_ip(&mut self, host_name: &CStr16) -> Result<Dns6HostAddresses> {
+        let event = CompletionEvent::new()?;
+        let mut token = Dns6CompletionToken {
+            event: event.as_raw(),
+            status: Status::SUCCESS,
+            retry_count: 0,
+            retry_interval: 0,
+            rsp_data: Dns6ResponseData {
+                h2a_data: ptr::null_mut(),
+            },
+        };
+        unsafe { (self.0.host_name_to_ip)(&mut self.0, host_name.as_ptr().cast(), &mut token) }
+            .to_result()?;
+
+        let this = ptr::from_mut(&mut self.0);
+        let poll = self.0.poll;
+        event.wait(None, || {
+            // Errors from `poll` just mean no progress was made.
+            let _ = unsafe { poll(this) };
+        })?;
+        token.status.to_result()?;
+
+        NonNull::new(unsafe { token.rsp_data.h2a_data })
+            .map(|data| Dns6HostAddresses { data })
+            .ok_or_else(|| Status::NOT_FOUND.into())
+    }
+}
+
+/// IPv6 addresses returned by [`Dns6::host_name_to_ip`].
+///
+/// The list is allocated by the DNS implementation and freed on drop.
+#[derive(Debug)]
+pub struct Dns6HostAddresses {
+    data: NonNull<Dns6HostToAddrData>,
+}
+
+impl Dns6HostAddresses {
+    fn as_slice(&self) -> &[Ipv6Address] {
+        let data = unsafe { self.data.as_ref() };
+        if data.ip_list.is_null() {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(data.ip_list, data.ip_count as usize) }
+        }
+    }
+
+    /// Get the number of addresses.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Check whether the list of addresses is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    /// Iterate over the addresses.
+    pub fn iter(&self) -> impl Iterator<Item = Ipv6Addr> + '_ {
+        self.as_slice().iter().map(|&addr| Ipv6Addr::from(addr))
+    }
+}
+
+impl Drop for Dns6HostAddresses {
+    fn drop(&mut self) {
+        // Both the address list and the structure pointing to it are owned
+        // by the caller once the lookup has completed.
+        let ip_list = unsafe { self.data.as_ref() }.ip_list;
+        if let Some(ip_list) = NonNull::new(ip_list) {
+            let _ = unsafe { boot::free_pool(ip_list.cast()) };
+        }
+        let _ = unsafe { boot::free_pool(self.data.cast()) };
+    }
+}
+
+/// A DNSv6 resolver, including the [`Dns6`] child instance it uses.
+///
+/// The child instance is destroyed when the `Dns6Resolver` is dropped.
+#[derive(Debug)]
+pub struct Dns6Resolver {
+    dns: ServiceChild<Dns6>,
+}
+
+impl Dns6Resolver {
+    /// Create a [`Dns6`] instance on the network interface `nic_handle` and
+    /// configure it with `config`.
+    pub fn new(nic_handle: Handle, config: &Dns6Config) -> Result<Self> {
+        let mut dns = ServiceChild::<Dns6>::new(nic_handle)?;
+        dns.configure(config)?;
+        Ok(Self { dns })
+    }
+
+    /// Look up the IPv6 addresses of `host_name`.
+    pub fn resolve(&mut self, host_name: &CStr16) -> Result<Dns6HostAddresses> {
+        self.dns.host_name_to_ip(host_name)
+    }
+
+    /// Get the underlying [`Dns6`] protocol.
+    pub fn protocol(&mut self) -> &mut Dns6 {
+        &mut self.dns
+    }
+}
+
+impl Drop for Dns6Resolver {
+    fn drop(&mut self) {
+        // Cancel any pending lookups before the child is destroyed.
+        let _ = self.dns.reset();
+    }
+}
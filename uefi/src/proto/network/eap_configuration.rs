@@ -0,0 +1,198 @@
+//! EAP configuration protocol.
+//!
+//! The [`EapConfiguration`] protocol sets the credentials used for 802.1X
+//! authentication, such as joining a WPA2-Enterprise network with a
+//! [`Supplicant`]. It is installed on wireless network interface handles:
+//!
+//! ```no_run
+//! use uefi::{boot, cstr16};
+//! use uefi::proto::network::eap_configuration::{EapConfiguration, EapType};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//! # let ca_cert: &[u8] = get_fake_val();
+//!
+//! let mut eap = boot::open_protocol_exclusive::<EapConfiguration>(nic_handle)?;
+//! eap.set_auth_method(EapType::PEAP)?;
+//! eap.set_second_auth_method(EapType::PEAP, EapType::MS_CHAP_V2)?;
+//! eap.set_identity(b"user@example.com")?;
+//! eap.set_ms_chap_v2_password(cstr16!("secret"))?;
+//! eap.set_ca_cert(ca_cert)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Supplicant`]: super::supplicant::Supplicant
+
+use crate::proto::unsafe_protocol;
+use crate::{CStr16, CStr8, Result, Status, StatusExt};
+use core::ffi::c_void;
+use uefi_raw::protocol::network::eap_configuration::EapConfigurationProtocol;
+
+pub use uefi_raw::protocol::network::eap_configuration::{EapConfigDataType, EapType};
+
+/// EAP configuration protocol.
+///
+/// Corresponds to the `EFI_EAP_CONFIGURATION_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(EapConfigurationProtocol::GUID)]
+pub struct EapConfiguration(EapConfigurationProtocol);
+
+impl EapConfiguration {
+    /// Set data of type `data_type` for the EAP method `eap_type` from the
+    /// raw bytes `data`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must contain a valid value of the type expected for
+    /// `data_type`, as described in the UEFI Specification.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `data` is invalid.
+    /// * [`Status::UNSUPPORTED`]: `eap_type` or `data_type` is not
+    ///   supported.
+    /// * [`Status::OUT_OF_RESOURCES`]: the data could not be stored.
+    pub unsafe fn set_data(
+        &mut self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        data: &[u8],
+    ) -> Result {
+        unsafe {
+            (self.0.set_data)(
+                &mut self.0,
+                eap_type,
+                data_type,
+                data.as_ptr().cast::<c_void>(),
+                data.len(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Get data of type `data_type` for the EAP method `eap_type` into
+    /// `buffer`. Returns the size of the data.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::UNSUPPORTED`]: `eap_type` or `data_type` is not
+    ///   supported.
+    /// * [`Status::NOT_FOUND`]: no data of this type has been set.
+    pub fn get_data(
+        &mut self,
+        eap_type: EapType,
+        data_type: EapConfigDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        let status = unsafe {
+            (self.0.get_data)(
+                &mut self.0,
+                eap_type,
+                data_type,
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Set the EAP method used for authentication, such as
+    /// [`EapType::EAP_TLS`] or [`EapType::PEAP`].
+    pub fn set_auth_method(&mut self, method: EapType) -> Result {
+        unsafe {
+            self.set_data(
+                EapType::ATTRIBUTE,
+                EapConfigDataType::EAP_AUTH_METHOD,
+                &[method.0],
+            )
+        }
+    }
+
+    /// Get the EAP method used for authentication.
+    pub fn auth_method(&mut self) -> Result<EapType> {
+        let mut method = [0];
+        self.get_data(
+            EapType::ATTRIBUTE,
+            EapConfigDataType::EAP_AUTH_METHOD,
+            &mut method,
+        )
+        .map_err(|err| err.to_err_without_payload())?;
+        Ok(EapType(method[0]))
+    }
+
+    /// Set the method used inside the tunnel of a tunneled EAP `method`,
+    /// such as [`EapType::MS_CHAP_V2`] inside [`EapType::PEAP`].
+    pub fn set_second_auth_method(&mut self, method: EapType, inner: EapType) -> Result {
+        unsafe { self.set_data(method, EapConfigDataType::EAP_2ND_AUTH_METHOD, &[inner.0]) }
+    }
+
+    /// Set the identity sent in EAP identity responses.
+    pub fn set_identity(&mut self, identity: &[u8]) -> Result {
+        unsafe {
+            self.set_data(
+                EapType::IDENTITY,
+                EapConfigDataType::IDENTITY_STRING,
+                identity,
+            )
+        }
+    }
+
+    /// Set the CA certificate used to verify the authentication server.
+    pub fn set_ca_cert(&mut self, cert: &[u8]) -> Result {
+        unsafe { self.set_data(EapType::EAP_TLS, EapConfigDataType::EAP_TLS_CA_CERT, cert) }
+    }
+
+    /// Set the client certificate for [`EapType::EAP_TLS`].
+    pub fn set_client_cert(&mut self, cert: &[u8]) -> Result {
+        unsafe {
+            self.set_data(
+                EapType::EAP_TLS,
+                EapConfigDataType::EAP_TLS_CLIENT_CERT,
+                cert,
+            )
+        }
+    }
+
+    /// Set the private key of the client certificate for
+    /// [`EapType::EAP_TLS`].
+    pub fn set_client_private_key(&mut self, key: &[u8]) -> Result {
+        unsafe {
+            self.set_data(
+                EapType::EAP_TLS,
+                EapConfigDataType::EAP_TLS_CLIENT_PRIVATE_KEY_FILE,
+                key,
+            )
+        }
+    }
+
+    /// Set the password of an encrypted client private key.
+    pub fn set_client_private_key_password(&mut self, password: &CStr8) -> Result {
+        unsafe {
+            self.set_data(
+                EapType::EAP_TLS,
+                EapConfigDataType::EAP_TLS_CLIENT_PRIVATE_KEY_FILE_PASSWORD,
+                password.as_bytes(),
+            )
+        }
+    }
+
+    /// Set the password for [`EapType::MS_CHAP_V2`].
+    pub fn set_ms_chap_v2_password(&mut self, password: &CStr16) -> Result {
+        unsafe {
+            self.set_data(
+                EapType::MS_CHAP_V2,
+                EapConfigDataType::EAP_MS_CHAP_V2_PASSWORD,
+                password.as_bytes(),
+            )
+        }
+    }
+}
@@ -0,0 +1,674 @@
+//! HTTP protocol.
+//!
+//! HTTP instances are created through the [`HttpServiceBinding`] protocol
+//! installed on a network interface handle. The [`HttpClient`] type takes
+//! care of creating and configuring the child instance, and provides a
+//! small blocking client with streaming body reads:
+//!
+//! ```no_run
+//! use uefi::cstr16;
+//! use uefi::proto::network::http::HttpClient;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut client = HttpClient::new(nic_handle)?;
+//! let mut response = client.get(cstr16!("http://192.168.0.1/boot.efi"))?;
+//! log::info!("status: {:?}", response.status());
+//!
+//! let mut buf = [0; 4096];
+//! loop {
+//!     let len = response.read(&mut buf)?;
+//!     if len == 0 {
+//!         break;
+//!     }
+//!     // Process `buf[..len]`...
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{boot, cstr8, CStr16, CStr8, Handle, Result, Status, StatusExt};
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+use core::net::{Ipv4Addr, Ipv6Addr};
+use core::ptr::{self, NonNull};
+use core::slice;
+use uefi_raw::protocol::network::http::{
+    HttpAccessPoint, HttpConfigData, HttpHeader as RawHttpHeader, HttpMessage, HttpProtocol,
+    HttpRequestData, HttpRequestOrResponse, HttpResponseData, HttpToken, HttpV4AccessPoint,
+    HttpV6AccessPoint,
+};
+use uefi_raw::Ipv4Address;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+pub use uefi_raw::protocol::network::http::{HttpMethod, HttpStatusCode, HttpVersion};
+
+/// Service binding protocol for creating [`Http`] instances.
+pub type HttpServiceBinding = ServiceBinding<Http>;
+
+/// HTTP protocol.
+///
+/// [`request`], [`receive_response`] and [`receive_body`] block until the
+/// operation finishes, calling [`poll`] in between checks of the token's
+/// event. The timeout of a single operation is set in the [`HttpConfig`].
+///
+/// Corresponds to the `EFI_HTTP_PROTOCOL` in the C API.
+///
+/// [`request`]: Self::request
+/// [`receive_response`]: Self::receive_response
+/// [`receive_body`]: Self::receive_body
+/// [`poll`]: Self::poll
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(HttpProtocol::GUID)]
+pub struct Http(HttpProtocol);
+
+unsafe impl ServiceBindingChild for Http {
+    const SERVICE_BINDING_GUID: crate::Guid = HttpProtocol::SERVICE_BINDING_GUID;
+}
+
+/// Local endpoint of an [`Http`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HttpLocalAddress {
+    /// Use the default IPv4 address of the interface, as configured by DHCP
+    /// or the IPv4 configuration protocol, and an ephemeral port.
+    DefaultV4,
+
+    /// Use an explicit IPv4 address. If `port` is zero, an ephemeral port is
+    /// chosen by the implementation.
+    V4 {
+        /// Local address.
+        address: Ipv4Addr,
+        /// Subnet mask of the local address.
+        subnet_mask: Ipv4Addr,
+        /// Local port.
+        port: u16,
+    },
+
+    /// Use IPv6. If `address` is the unspecified address, the implementation
+    /// selects a source address. If `port` is zero, an ephemeral port is
+    /// chosen by the implementation.
+    V6 {
+        /// Local address.
+        address: Ipv6Addr,
+        /// Local port.
+        port: u16,
+    },
+}
+
+/// Configuration of an [`Http`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HttpConfig {
+    /// HTTP version used for requests.
+    pub version: HttpVersion,
+
+    /// Timeout of a single request or response operation, in milliseconds.
+    /// If zero, the operations do not time out.
+    pub timeout_ms: u32,
+
+    /// Local endpoint used for connections.
+    pub local_address: HttpLocalAddress,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            version: HttpVersion::HTTP_VERSION_11,
+            timeout_ms: 10_000,
+            local_address: HttpLocalAddress::DefaultV4,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Call `f` with the raw configuration data, which borrows from the
+    /// stack of this function.
+    pub(super) fn with_raw(&self, f: impl FnOnce(HttpConfigData) -> Status) -> Result {
+        let v4_node;
+        let v6_node;
+        let (local_addr_is_ipv6, access_point) = match self.local_address {
+            HttpLocalAddress::DefaultV4 => {
+                v4_node = HttpV4AccessPoint {
+                    use_default_addr: true,
+                    local_address: Ipv4Address::default(),
+                    local_subnet: Ipv4Address::default(),
+                    local_port: 0,
+                };
+                (
+                    false,
+                    HttpAccessPoint {
+                        ipv4_node: &v4_node,
+                    },
+                )
+            }
+            HttpLocalAddress::V4 {
+                address,
+                subnet_mask,
+                port,
+            } => {
+                v4_node = HttpV4AccessPoint {
+                    use_default_addr: false,
+                    local_address: address.into(),
+                    local_subnet: subnet_mask.into(),
+                    local_port: port,
+                };
+                (
+                    false,
+                    HttpAccessPoint {
+                        ipv4_node: &v4_node,
+                    },
+                )
+            }
+            HttpLocalAddress::V6 { address, port } => {
+                v6_node = HttpV6AccessPoint {
+                    local_address: address.into(),
+                    local_port: port,
+                };
+                (
+                    true,
+                    HttpAccessPoint {
+                        ipv6_node: &v6_node,
+                    },
+                )
+            }
+        };
+        let config_data = HttpConfigData {
+            http_version: self.version,
+            time_out_millisec: self.timeout_ms,
+            local_addr_is_ipv6,
+            access_point,
+        };
+        f(config_data).to_result()
+    }
+}
+
+/// A header of an HTTP request.
+///
+/// This type has the same layout as `EFI_HTTP_HEADER`, so a slice of headers
+/// can be passed to the implementation without copying.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct HttpHeader<'a> {
+    raw: RawHttpHeader,
+    _lifetime: PhantomData<&'a CStr8>,
+}
+
+impl<'a> HttpHeader<'a> {
+    /// Create a new header.
+    #[must_use]
+    pub const fn new(name: &'a CStr8, value: &'a CStr8) -> Self {
+        Self {
+            raw: RawHttpHeader {
+                field_name: name.as_ptr().cast(),
+                field_value: value.as_ptr().cast(),
+            },
+            _lifetime: PhantomData,
+        }
+    }
+
+    /// Get the header name.
+    #[must_use]
+    pub fn name(&self) -> &'a CStr8 {
+        unsafe { CStr8::from_ptr(self.raw.field_name.cast()) }
+    }
+
+    /// Get the header value.
+    #[must_use]
+    pub fn value(&self) -> &'a CStr8 {
+        unsafe { CStr8::from_ptr(self.raw.field_value.cast()) }
+    }
+}
+
+impl Debug for HttpHeader<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpHeader")
+            .field("name", &self.name())
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+/// Headers of an HTTP response, as returned by [`Http::receive_response`].
+///
+/// The headers are allocated by the HTTP implementation and freed on drop.
+pub struct HttpHeaders {
+    headers: *mut RawHttpHeader,
+    count: usize,
+}
+
+impl HttpHeaders {
+    /// Take ownership of `count` headers at `headers`, allocated from pool
+    /// memory by the implementation.
+    pub(super) const unsafe fn from_raw(headers: *mut RawHttpHeader, count: usize) -> Self {
+        Self { headers, count }
+    }
+
+    fn as_slice(&self) -> &[HttpHeader<'_>] {
+        if self.headers.is_null() {
+            &[]
+        } else {
+            // `HttpHeader` is a transparent wrapper around the raw header.
+            unsafe { slice::from_raw_parts(self.headers.cast(), self.count) }
+        }
+    }
+
+    /// Get the number of headers.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Check whether there are no headers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    /// Iterate over the headers.
+    pub fn iter(&self) -> impl Iterator<Item = &HttpHeader<'_>> {
+        self.as_slice().iter()
+    }
+
+    /// Get the value of the first header named `name`. Header names are
+    /// compared case-insensitively.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&CStr8> {
+        self.iter()
+            .find(|header| without_nul(header.name()).eq_ignore_ascii_case(name.as_bytes()))
+            .map(HttpHeader::value)
+    }
+}
+
+impl Debug for HttpHeaders {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Drop for HttpHeaders {
+    fn drop(&mut self) {
+        // The header array and each of the strings it points to are separate
+        // pool allocations owned by the caller.
+        for header in self.as_slice() {
+            for field in [header.raw.field_name, header.raw.field_value] {
+                if let Some(field) = NonNull::new(field.cast_mut()) {
+                    let _ = unsafe { boot::free_pool(field) };
+                }
+            }
+        }
+        if let Some(headers) = NonNull::new(self.headers) {
+            let _ = unsafe { boot::free_pool(headers.cast()) };
+        }
+    }
+}
+
+impl Http {
+    /// Configure the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::ALREADY_STARTED`]: the instance is already configured.
+    /// * [`Status::UNSUPPORTED`]: the HTTP version is not supported.
+    pub fn configure(&mut self, config: &HttpConfig) -> Result {
+        config.with_raw(|config_data| unsafe { (self.0.configure)(&mut self.0, &config_data) })
+    }
+
+    /// Reset the instance to the unconfigured state. Any open connection is
+    /// closed and pending operations are cancelled.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Poll for incoming data packets and process outgoing data packets.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: no incoming or outgoing data was processed.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Send a request and wait until it has been transmitted.
+    ///
+    /// `headers` are sent as-is; they must include a `Host` header. For
+    /// methods without a body, `body` should be empty.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::UNSUPPORTED`]: the URL scheme or method is not supported.
+    /// * [`Status::TIMEOUT`]: the request was not transmitted in time.
+    /// * [`Status::DEVICE_ERROR`]: a network or connection error occurred.
+    pub fn request(
+        &mut self,
+        method: HttpMethod,
+        url: &CStr16,
+        headers: &[HttpHeader<'_>],
+        body: &[u8],
+    ) -> Result {
+        let request_data = HttpRequestData {
+            method,
+            url: url.as_ptr().cast(),
+        };
+        let mut message = HttpMessage {
+            data: HttpRequestOrResponse {
+                request: &request_data,
+            },
+            header_count: headers.len(),
+            header: headers.as_ptr().cast_mut().cast(),
+            body_length: body.len(),
+            body: body.as_ptr().cast_mut().cast(),
+        };
+        self.run_token(&mut message, self.0.request)
+    }
+
+    /// Wait for the response to a request and receive its status code and
+    /// headers. The body can then be read with [`receive_body`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TIMEOUT`]: no response was received in time.
+    /// * [`Status::DEVICE_ERROR`]: a network or connection error occurred.
+    ///
+    /// [`receive_body`]: Self::receive_body
+    pub fn receive_response(&mut self) -> Result<(HttpStatusCode, HttpHeaders)> {
+        let mut response_data = HttpResponseData {
+            status_code: HttpStatusCode::STATUS_UNSUPPORTED,
+        };
+        let mut message = HttpMessage {
+            data: HttpRequestOrResponse {
+                response: ptr::from_mut(&mut response_data),
+            },
+            header_count: 0,
+            header: ptr::null_mut(),
+            body_length: 0,
+            body: ptr::null_mut(),
+        };
+        let status = self.run_token(&mut message, self.0.response);
+        let headers = unsafe { HttpHeaders::from_raw(message.header, message.header_count) };
+        status?;
+        Ok((response_data.status_code, headers))
+    }
+
+    /// Receive body data of the current response into `buffer`. Returns the
+    /// number of bytes received, or zero if the connection was closed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TIMEOUT`]: no data was received in time.
+    /// * [`Status::DEVICE_ERROR`]: a network or connection error occurred.
+    pub fn receive_body(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let mut message = HttpMessage {
+            data: HttpRequestOrResponse {
+                response: ptr::null(),
+            },
+            header_count: 0,
+            header: ptr::null_mut(),
+            body_length: buffer.len(),
+            body: buffer.as_mut_ptr().cast(),
+        };
+        match self.run_token(&mut message, self.0.response) {
+            Ok(()) => Ok(message.body_length),
+            Err(err) if err.status() == Status::CONNECTION_FIN => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Submit a token for `message` with `op` (either `Request` or
+    /// `Response`) and wait for it to complete.
+    fn run_token(
+        &mut self,
+        message: &mut HttpMessage,
+        op: unsafe extern "efiapi" fn(*mut HttpProtocol, *mut HttpToken) -> Status,
+    ) -> Result {
+        let event = CompletionEvent::new()?;
+        let mut token = HttpToken {
+            event: event.as_raw(),
+            status: Status::SUCCESS,
+            message,
+        };
+        let this = ptr::from_mut(&mut self.0);
+        unsafe { op(this, &mut token) }.to_result()?;
+
+        let poll = self.0.poll;
+        event.wait(None, || {
+            // Errors from `poll` just mean no progress was made.
+            let _ = unsafe { poll(this) };
+        })?;
+        token.status.to_result()
+    }
+}
+
+/// A small blocking HTTP client, including the [`Http`] child instance it
+/// uses.
+///
+/// The child instance is destroyed when the `HttpClient` is dropped.
+#[derive(Debug)]
+pub struct HttpClient {
+    http: ServiceChild<Http>,
+}
+
+impl HttpClient {
+    /// Create an [`Http`] instance on the network interface `nic_handle`
+    /// using HTTP/1.1 over the interface's default IPv4 address.
+    pub fn new(nic_handle: Handle) -> Result<Self> {
+        Self::with_config(nic_handle, &HttpConfig::default())
+    }
+
+    /// Create an [`Http`] instance on the network interface `nic_handle` and
+    /// configure it with `config`.
+    pub fn with_config(nic_handle: Handle, config: &HttpConfig) -> Result<Self> {
+        let mut http = ServiceChild::<Http>::new(nic_handle)?;
+        http.configure(config)?;
+        Ok(Self { http })
+    }
+
+    /// Send a `GET` request for `url` and wait for the response headers.
+    ///
+    /// The `Host` header is derived from `url`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `url` does not contain a valid host.
+    ///
+    /// See also [`Http::request`] and [`Http::receive_response`].
+    pub fn get(&mut self, url: &CStr16) -> Result<HttpResponse<'_>> {
+        let mut host_buf = [0; 256];
+        let host = host_from_url(url, &mut host_buf).ok_or(Status::INVALID_PARAMETER)?;
+        let headers = [
+            HttpHeader::new(cstr8!("Host"), host),
+            HttpHeader::new(cstr8!("Accept"), cstr8!("*/*")),
+        ];
+        self.request(HttpMethod::GET, url, &headers, &[])
+    }
+
+    /// Send a request and wait for the response headers.
+    ///
+    /// `headers` are sent as-is; they must include a `Host` header.
+    pub fn request(
+        &mut self,
+        method: HttpMethod,
+        url: &CStr16,
+        headers: &[HttpHeader<'_>],
+        body: &[u8],
+    ) -> Result<HttpResponse<'_>> {
+        self.http.request(method, url, headers, body)?;
+        let (status, headers) = self.http.receive_response()?;
+        let remaining = headers.get("Content-Length").and_then(parse_content_length);
+        Ok(HttpResponse {
+            http: &mut self.http,
+            status,
+            headers,
+            remaining,
+        })
+    }
+
+    /// Get the underlying [`Http`] protocol.
+    pub fn protocol(&mut self) -> &mut Http {
+        &mut self.http
+    }
+}
+
+impl Drop for HttpClient {
+    fn drop(&mut self) {
+        // Close the connection before the child is destroyed.
+        let _ = self.http.reset();
+    }
+}
+
+/// The response to a request sent through an [`HttpClient`].
+///
+/// The body is not received until it is read with [`read`].
+///
+/// [`read`]: Self::read
+#[derive(Debug)]
+pub struct HttpResponse<'a> {
+    http: &'a mut Http,
+    status: HttpStatusCode,
+    headers: HttpHeaders,
+    remaining: Option<usize>,
+}
+
+impl HttpResponse<'_> {
+    /// Get the status code of the response.
+    #[must_use]
+    pub const fn status(&self) -> HttpStatusCode {
+        self.status
+    }
+
+    /// Get the headers of the response.
+    #[must_use]
+    pub const fn headers(&self) -> &HttpHeaders {
+        &self.headers
+    }
+
+    /// Get the length of the body as given by the `Content-Length` header,
+    /// if present.
+    #[must_use]
+    pub fn content_length(&self) -> Option<usize> {
+        self.headers
+            .get("Content-Length")
+            .and_then(parse_content_length)
+    }
+
+    /// Read the next part of the body into `buffer`. Returns the number of
+    /// bytes read, or zero at the end of the body.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let buffer = match self.remaining {
+            Some(0) => return Ok(0),
+            Some(remaining) => {
+                let len = buffer.len().min(remaining);
+                &mut buffer[..len]
+            }
+            None => buffer,
+        };
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        let len = self.http.receive_body(buffer)?;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= len;
+        }
+        Ok(len)
+    }
+
+    /// Read the rest of the body into `buffer`.
+    #[cfg(feature = "alloc")]
+    pub fn read_to_end(&mut self, buffer: &mut Vec<u8>) -> Result<usize> {
+        let start = buffer.len();
+        let mut chunk = [0; 4096];
+        loop {
+            let len = self.read(&mut chunk)?;
+            if len == 0 {
+                return Ok(buffer.len() - start);
+            }
+            buffer.extend_from_slice(&chunk[..len]);
+        }
+    }
+}
+
+/// Get the bytes of `s` without the trailing nul.
+fn without_nul(s: &CStr8) -> &[u8] {
+    let bytes = s.as_bytes();
+    &bytes[..bytes.len() - 1]
+}
+
+/// Extract the host (and port, if any) of `url` as an ASCII string in `buf`.
+fn host_from_url<'b>(url: &CStr16, buf: &'b mut [u8]) -> Option<&'b CStr8> {
+    let url = url.to_u16_slice();
+    let start = url
+        .windows(3)
+        .position(|w| w == [b':' as u16, b'/' as u16, b'/' as u16])
+        .map_or(0, |pos| pos + 3);
+    let host = &url[start..];
+    let end = host
+        .iter()
+        .position(|&c| b"/?#".iter().any(|&d| c == u16::from(d)))
+        .unwrap_or(host.len());
+    let host = &host[..end];
+
+    if host.is_empty() || host.len() >= buf.len() {
+        return None;
+    }
+    for (dst, &src) in buf.iter_mut().zip(host) {
+        *dst = u8::try_from(src).ok().filter(u8::is_ascii_graphic)?;
+    }
+    buf[host.len()] = 0;
+    CStr8::from_bytes_with_nul(&buf[..=host.len()]).ok()
+}
+
+/// Parse the value of a `Content-Length` header.
+fn parse_content_length(value: &CStr8) -> Option<usize> {
+    core::str::from_utf8(without_nul(value))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr16;
+
+    #[test]
+    fn test_host_from_url() {
+        let mut buf = [0; 32];
+        assert_eq!(
+            host_from_url(cstr16!("http://example.com/path?q"), &mut buf),
+            Some(cstr8!("example.com"))
+        );
+        assert_eq!(
+            host_from_url(cstr16!("https://10.0.0.1:8080"), &mut buf),
+            Some(cstr8!("10.0.0.1:8080"))
+        );
+        assert_eq!(
+            host_from_url(cstr16!("example.com#frag"), &mut buf),
+            Some(cstr8!("example.com"))
+        );
+        assert_eq!(host_from_url(cstr16!("http:///path"), &mut buf), None);
+        assert_eq!(host_from_url(cstr16!("http://ünicode/"), &mut buf), None);
+
+        let mut small = [0; 4];
+        assert_eq!(host_from_url(cstr16!("http://abcd/"), &mut small), None);
+    }
+
+    #[test]
+    fn test_parse_content_length() {
+        assert_eq!(parse_content_length(cstr8!("1234")), Some(1234));
+        assert_eq!(parse_content_length(cstr8!(" 42 ")), Some(42));
+        assert_eq!(parse_content_length(cstr8!("-1")), None);
+        assert_eq!(parse_content_length(cstr8!("")), None);
+    }
+}
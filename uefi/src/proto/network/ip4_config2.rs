@@ -0,0 +1,371 @@
+//! IPv4 configuration protocol.
+//!
+//! The [`Ip4Config2`] protocol is installed on each network interface handle
+//! and controls how the interface gets its IPv4 address. It must be
+//! configured before protocols that use the default address (such as
+//! [`Tcp4`] or [`Http`]) can be used:
+//!
+//! ```no_run
+//! use core::time::Duration;
+//! use uefi::boot;
+//! use uefi::proto::network::ip4_config2::Ip4Config2;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut ip4_config = boot::open_protocol_exclusive::<Ip4Config2>(nic_handle)?;
+//! let address = ip4_config.ifup(Duration::from_secs(10))?;
+//! log::info!("got address {address}");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Tcp4`]: super::tcp4::Tcp4
+//! [`Http`]: super::http::Http
+
+use crate::proto::unsafe_protocol;
+use crate::{Event, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+use uefi_raw::protocol::network::ip4_config2::Ip4Config2Protocol;
+
+#[cfg(feature = "alloc")]
+use {
+    super::MacAddress,
+    crate::{boot, CStr16, CString16},
+    alloc::vec::Vec,
+    core::net::Ipv4Addr,
+    core::time::Duration,
+    uefi_raw::protocol::network::ip4_config2::{Ip4Config2InterfaceInfo, Ip4Config2ManualAddress},
+    uefi_raw::Ipv4Address,
+};
+
+pub use uefi_raw::protocol::network::ip4::Ip4RouteTable;
+pub use uefi_raw::protocol::network::ip4_config2::{Ip4Config2DataType, Ip4Config2Policy};
+
+/// IPv4 configuration protocol.
+///
+/// Corresponds to the `EFI_IP4_CONFIG2_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Ip4Config2Protocol::GUID)]
+pub struct Ip4Config2(Ip4Config2Protocol);
+
+/// Information about a network interface, as returned by
+/// [`Ip4Config2::interface_info`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ip4InterfaceInfo {
+    /// Name of the interface.
+    pub name: CString16,
+
+    /// Hardware type of the interface, as defined by RFC 1700.
+    pub if_type: u8,
+
+    /// Hardware address of the interface.
+    pub hw_addr: MacAddress,
+
+    /// Size of the hardware address in bytes.
+    pub hw_addr_size: u32,
+
+    /// Current IPv4 address of the interface, or the unspecified address if
+    /// no address is assigned.
+    pub station_address: Ipv4Addr,
+
+    /// Subnet mask of the current address.
+    pub subnet_mask: Ipv4Addr,
+
+    /// Routing table of the interface.
+    pub route_table: Vec<Ip4RouteTable>,
+}
+
+impl Ip4Config2 {
+    /// Set configuration data of type `data_type` from the raw bytes `data`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must contain a valid value of the type expected for
+    /// `data_type`, as described in the UEFI Specification.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `data` is invalid.
+    /// * [`Status::WRITE_PROTECTED`]: `data_type` is read-only.
+    /// * [`Status::NOT_READY`]: the data will be applied asynchronously.
+    ///   Register an event with [`register_data_notify`] to be notified when
+    ///   it has been applied.
+    ///
+    /// [`register_data_notify`]: Self::register_data_notify
+    pub unsafe fn set_data(&mut self, data_type: Ip4Config2DataType, data: &[u8]) -> Result {
+        unsafe {
+            (self.0.set_data)(
+                &mut self.0,
+                data_type,
+                data.len(),
+                data.as_ptr().cast::<c_void>(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Get configuration data of type `data_type` into `buffer`. Returns the
+    /// size of the data.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::NOT_READY`]: the data is being updated asynchronously.
+    /// * [`Status::NOT_FOUND`]: no data of this type is available.
+    pub fn get_data(
+        &mut self,
+        data_type: Ip4Config2DataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        let status = unsafe {
+            (self.0.get_data)(
+                &mut self.0,
+                data_type,
+                &mut size,
+                buffer.as_mut_ptr().cast(),
+            )
+        };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Get configuration data of type `data_type` into a new buffer.
+    #[cfg(feature = "alloc")]
+    fn get_data_vec(&mut self, data_type: Ip4Config2DataType) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        loop {
+            match self.get_data(data_type, &mut buffer) {
+                Ok(size) => {
+                    buffer.truncate(size);
+                    return Ok(buffer);
+                }
+                Err(err) => match err.data() {
+                    Some(size) => buffer.resize(*size, 0),
+                    None => return Err(err.to_err_without_payload()),
+                },
+            }
+        }
+    }
+
+    /// Set a plain-old-data configuration value.
+    fn set_value<T: ?Sized>(&mut self, data_type: Ip4Config2DataType, value: &T) -> Result {
+        // Safety: callers pass the type matching `data_type`.
+        let data = unsafe {
+            core::slice::from_raw_parts(ptr::from_ref(value).cast::<u8>(), mem::size_of_val(value))
+        };
+        unsafe { self.set_data(data_type, data) }
+    }
+
+    /// Get information about the interface, including the current address.
+    #[cfg(feature = "alloc")]
+    pub fn interface_info(&mut self) -> Result<Ip4InterfaceInfo> {
+        let buffer = self.get_data_vec(Ip4Config2DataType::INTERFACE_INFO)?;
+        if buffer.len() < mem::size_of::<Ip4Config2InterfaceInfo>() {
+            return Err(Status::BAD_BUFFER_SIZE.into());
+        }
+        // Safety: the buffer holds an `EFI_IP4_CONFIG2_INTERFACE_INFO`, which
+        // may not be suitably aligned.
+        let info = unsafe {
+            buffer
+                .as_ptr()
+                .cast::<Ip4Config2InterfaceInfo>()
+                .read_unaligned()
+        };
+        let route_table = (0..info.route_table_size as usize)
+            .map(|i| unsafe { info.route_table.add(i).read_unaligned() })
+            .collect();
+
+        Ok(Ip4InterfaceInfo {
+            name: CStr16::from_u16_until_nul(&info.name)
+                .map(CString16::from)
+                .unwrap_or_default(),
+            if_type: info.if_type,
+            hw_addr: MacAddress(info.hw_addr.0),
+            hw_addr_size: info.hw_addr_size,
+            station_address: Ipv4Addr::from(info.station_addr),
+            subnet_mask: Ipv4Addr::from(info.subnet_mask),
+            route_table,
+        })
+    }
+
+    /// Get the address configuration policy.
+    pub fn policy(&mut self) -> Result<Ip4Config2Policy> {
+        let mut policy = Ip4Config2Policy::STATIC;
+        let mut size = mem::size_of_val(&policy);
+        unsafe {
+            (self.0.get_data)(
+                &mut self.0,
+                Ip4Config2DataType::POLICY,
+                &mut size,
+                ptr::from_mut(&mut policy).cast(),
+            )
+        }
+        .to_result_with_val(|| policy)
+    }
+
+    /// Set the address configuration policy.
+    ///
+    /// Switching the policy clears the current address, gateways and DNS
+    /// servers. Switching to [`Ip4Config2Policy::DHCP`] starts DHCP.
+    pub fn set_policy(&mut self, policy: Ip4Config2Policy) -> Result {
+        self.set_value(Ip4Config2DataType::POLICY, &policy)
+    }
+
+    /// Get the manually configured addresses and subnet masks.
+    #[cfg(feature = "alloc")]
+    pub fn manual_addresses(&mut self) -> Result<Vec<(Ipv4Addr, Ipv4Addr)>> {
+        let buffer = self.get_data_vec(Ip4Config2DataType::MANUAL_ADDRESS)?;
+        Ok(buffer
+            .chunks_exact(mem::size_of::<Ip4Config2ManualAddress>())
+            .map(|chunk| {
+                let octets = |b: &[u8]| [b[0], b[1], b[2], b[3]];
+                (
+                    Ipv4Addr::from(octets(&chunk[..4])),
+                    Ipv4Addr::from(octets(&chunk[4..])),
+                )
+            })
+            .collect())
+    }
+
+    /// Set the manually configured addresses, as pairs of address and subnet
+    /// mask. The policy must be [`Ip4Config2Policy::STATIC`].
+    ///
+    /// The addresses may be applied asynchronously, in which case a
+    /// [`Status::NOT_READY`] error is returned. See [`set_data`].
+    ///
+    /// [`set_data`]: Self::set_data
+    #[cfg(feature = "alloc")]
+    pub fn set_manual_addresses(&mut self, addresses: &[(Ipv4Addr, Ipv4Addr)]) -> Result {
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|&(address, subnet_mask)| Ip4Config2ManualAddress {
+                address: address.into(),
+                subnet_mask: subnet_mask.into(),
+            })
+            .collect();
+        self.set_value(Ip4Config2DataType::MANUAL_ADDRESS, addresses.as_slice())
+    }
+
+    /// Get the configured gateway addresses.
+    #[cfg(feature = "alloc")]
+    pub fn gateways(&mut self) -> Result<Vec<Ipv4Addr>> {
+        self.get_addresses(Ip4Config2DataType::GATEWAY)
+    }
+
+    /// Set the gateway addresses. The policy must be
+    /// [`Ip4Config2Policy::STATIC`].
+    #[cfg(feature = "alloc")]
+    pub fn set_gateways(&mut self, gateways: &[Ipv4Addr]) -> Result {
+        self.set_addresses(Ip4Config2DataType::GATEWAY, gateways)
+    }
+
+    /// Get the configured DNS server addresses.
+    #[cfg(feature = "alloc")]
+    pub fn dns_servers(&mut self) -> Result<Vec<Ipv4Addr>> {
+        self.get_addresses(Ip4Config2DataType::DNS_SERVER)
+    }
+
+    /// Set the DNS server addresses. The policy must be
+    /// [`Ip4Config2Policy::STATIC`].
+    #[cfg(feature = "alloc")]
+    pub fn set_dns_servers(&mut self, servers: &[Ipv4Addr]) -> Result {
+        self.set_addresses(Ip4Config2DataType::DNS_SERVER, servers)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn get_addresses(&mut self, data_type: Ip4Config2DataType) -> Result<Vec<Ipv4Addr>> {
+        let buffer = match self.get_data_vec(data_type) {
+            Ok(buffer) => buffer,
+            // An empty list is reported as "not found".
+            Err(err) if err.status() == Status::NOT_FOUND => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(buffer
+            .chunks_exact(4)
+            .map(|b| Ipv4Addr::new(b[0], b[1], b[2], b[3]))
+            .collect())
+    }
+
+    #[cfg(feature = "alloc")]
+    fn set_addresses(&mut self, data_type: Ip4Config2DataType, addresses: &[Ipv4Addr]) -> Result {
+        let addresses: Vec<_> = addresses
+            .iter()
+            .map(|&addr| Ipv4Address::from(addr))
+            .collect();
+        self.set_value(data_type, addresses.as_slice())
+    }
+
+    /// Register `event` to be signaled when configuration data of type
+    /// `data_type` changes.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::ACCESS_DENIED`]: `event` is already registered for
+    ///   `data_type`.
+    pub fn register_data_notify(&mut self, data_type: Ip4Config2DataType, event: &Event) -> Result {
+        unsafe { (self.0.register_data_notify)(&mut self.0, data_type, event.as_ptr()) }.to_result()
+    }
+
+    /// Unregister an event registered with [`register_data_notify`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: `event` is not registered for `data_type`.
+    ///
+    /// [`register_data_notify`]: Self::register_data_notify
+    pub fn unregister_data_notify(
+        &mut self,
+        data_type: Ip4Config2DataType,
+        event: &Event,
+    ) -> Result {
+        unsafe { (self.0.unregister_data_notify)(&mut self.0, data_type, event.as_ptr()) }
+            .to_result()
+    }
+
+    /// Bring the interface up using DHCP and wait until it has an address.
+    /// Returns the assigned address.
+    ///
+    /// If the interface already has an address, it is returned immediately.
+    /// Otherwise the policy is switched to [`Ip4Config2Policy::DHCP`] (which
+    /// starts DHCP), and the interface is polled until an address has been
+    /// assigned or `timeout` elapses.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: no address was assigned within `timeout`.
+    #[cfg(feature = "alloc")]
+    pub fn ifup(&mut self, timeout: Duration) -> Result<Ipv4Addr> {
+        let address = self.interface_info()?.station_address;
+        if !address.is_unspecified() {
+            return Ok(address);
+        }
+
+        if self.policy()? == Ip4Config2Policy::DHCP {
+            // DHCP is only (re)started by a policy change.
+            self.set_policy(Ip4Config2Policy::STATIC)?;
+        }
+        self.set_policy(Ip4Config2Policy::DHCP)?;
+
+        // Poll in 100ms steps.
+        const STEP: Duration = Duration::from_millis(100);
+        let steps = timeout.as_millis() / STEP.as_millis();
+        for _ in 0..=steps {
+            let address = self.interface_info()?.station_address;
+            if !address.is_unspecified() {
+                return Ok(address);
+            }
+            boot::stall_for(STEP);
+        }
+        Err(Status::TIMEOUT.into())
+    }
+}
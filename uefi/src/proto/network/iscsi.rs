@@ -0,0 +1,176 @@
+//! iSCSI initiator name protocol.
+//!
+//! The [`IScsiInitiatorName`] protocol gets and sets the iSCSI name the
+//! firmware's initiator uses to identify itself to targets. Provisioning
+//! tools set it before an iSCSI boot:
+//!
+//! ```no_run
+//! use uefi::{boot, cstr8, ResultExt};
+//! use uefi::proto::network::iscsi::{IScsiInitiatorName, MAX_ISCSI_NAME_LEN};
+//! # fn test() -> uefi::Result {
+//!
+//! let handle = boot::get_handle_for_protocol::<IScsiInitiatorName>()?;
+//! let mut iscsi = boot::open_protocol_exclusive::<IScsiInitiatorName>(handle)?;
+//! iscsi.set_name(cstr8!("iqn.2024-01.com.example:host-1"))?;
+//!
+//! let mut buf = [0; MAX_ISCSI_NAME_LEN + 1];
+//! log::info!("initiator name: {}", iscsi.name(&mut buf).discard_errdata()?);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::unsafe_protocol;
+use crate::{CStr8, Error, Result, Status, StatusExt};
+use uefi_raw::protocol::network::iscsi::IScsiInitiatorNameProtocol;
+
+/// Maximum length of an iSCSI name in bytes, not including the trailing
+/// nul.
+pub const MAX_ISCSI_NAME_LEN: usize = 223;
+
+/// iSCSI initiator name protocol.
+///
+/// Corresponds to the `EFI_ISCSI_INITIATOR_NAME_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(IScsiInitiatorNameProtocol::GUID)]
+pub struct IScsiInitiatorName(IScsiInitiatorNameProtocol);
+
+impl IScsiInitiatorName {
+    /// Get the initiator name, using `buffer` as storage. A buffer of
+    /// [`MAX_ISCSI_NAME_LEN`] + 1 bytes is always large enough.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::NOT_FOUND`]: no initiator name has been set.
+    /// * [`Status::DEVICE_ERROR`]: the name could not be read.
+    pub fn name<'buf>(&mut self, buffer: &'buf mut [u8]) -> Result<&'buf CStr8, Option<usize>> {
+        let mut size = buffer.len();
+        unsafe { (self.0.get)(&mut self.0, &mut size, buffer.as_mut_ptr().cast()) }
+            .to_result_with_err(|status| {
+                if status == Status::BUFFER_TOO_SMALL {
+                    Some(size)
+                } else {
+                    None
+                }
+            })?;
+
+        // Some implementations don't include the nul in `size`, so look for
+        // it instead.
+        let buffer = &buffer[..size.min(buffer.len())];
+        let len = buffer
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(Error::new(Status::DEVICE_ERROR, None))?;
+        Ok(unsafe { CStr8::from_bytes_with_nul_unchecked(&buffer[..=len]) })
+    }
+
+    /// Set the initiator name.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `name` is not a valid iSCSI name. See
+    ///   [`is_valid_iscsi_name`].
+    /// * [`Status::DEVICE_ERROR`]: the name could not be stored.
+    pub fn set_name(&mut self, name: &CStr8) -> Result {
+        let bytes = name.as_bytes();
+        if !is_valid_iscsi_name(&bytes[..bytes.len() - 1]) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let mut size = bytes.len();
+        unsafe { (self.0.set)(&mut self.0, &mut size, bytes.as_ptr().cast()) }.to_result()
+    }
+}
+
+/// Check whether `name` is a valid iSCSI name, as defined by RFC 3720
+/// section 3.2.6.3. Names must already be normalized, i.e. lowercase except
+/// for hexadecimal digits.
+///
+/// Three formats are accepted:
+/// * `iqn.yyyy-mm.<reversed domain name>[:<identifier>]`
+/// * `eui.<16 hex digits>`
+/// * `naa.<16 or 32 hex digits>`
+#[must_use]
+pub fn is_valid_iscsi_name(name: &[u8]) -> bool {
+    if name.len() > MAX_ISCSI_NAME_LEN {
+        return false;
+    }
+    if let Some(rest) = name.strip_prefix(b"iqn.") {
+        is_valid_iqn(rest)
+    } else if let Some(rest) = name.strip_prefix(b"eui.") {
+        rest.len() == 16 && rest.iter().all(u8::is_ascii_hexdigit)
+    } else if let Some(rest) = name.strip_prefix(b"naa.") {
+        (rest.len() == 16 || rest.len() == 32) && rest.iter().all(u8::is_ascii_hexdigit)
+    } else {
+        false
+    }
+}
+
+/// Check the part of an `iqn.` name following the prefix.
+fn is_valid_iqn(name: &[u8]) -> bool {
+    let is_name_char =
+        |&c: &u8| c.is_ascii_lowercase() || c.is_ascii_digit() || b".-:".contains(&c);
+    if !name.iter().all(is_name_char) {
+        return false;
+    }
+
+    // Date: `yyyy-mm.`
+    if name.len() < 8 {
+        return false;
+    }
+    let (date, rest) = name.split_at(8);
+    let digits = |s: &[u8]| s.iter().all(u8::is_ascii_digit);
+    if !(digits(&date[..4]) && date[4] == b'-' && digits(&date[5..7]) && date[7] == b'.') {
+        return false;
+    }
+    let month = (date[5] - b'0') * 10 + (date[6] - b'0');
+    if !(1..=12).contains(&month) {
+        return false;
+    }
+
+    // Naming authority, optionally followed by `:` and an identifier that
+    // may contain any name characters.
+    let authority = rest.split(|&c| c == b':').next().unwrap_or_default();
+    !authority.is_empty()
+        && authority
+            .split(|&c| c == b'.')
+            .all(|label| !label.is_empty() && !label.starts_with(b"-") && !label.ends_with(b"-"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_iscsi_name() {
+        assert!(is_valid_iscsi_name(b"iqn.2001-04.com.example"));
+        assert!(is_valid_iscsi_name(
+            b"iqn.2001-04.com.example:storage.disk2.sys1.xyz"
+        ));
+        assert!(is_valid_iscsi_name(b"iqn.1991-05.com.microsoft:host-1"));
+        assert!(is_valid_iscsi_name(b"eui.02004567A425678D"));
+        assert!(is_valid_iscsi_name(b"naa.52004567BA64678D"));
+        assert!(is_valid_iscsi_name(b"naa.62004567BA64678D0123456789ABCDEF"));
+
+        assert!(!is_valid_iscsi_name(b""));
+        assert!(!is_valid_iscsi_name(b"iqn."));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-04"));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-04."));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-13.com.example"));
+        assert!(!is_valid_iscsi_name(b"iqn.01-04.com.example"));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-04.com..example"));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-04.Com.Example"));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-04.com.example:host 1"));
+        assert!(!is_valid_iscsi_name(b"iqn.2001-04.:host"));
+        assert!(!is_valid_iscsi_name(b"eui.02004567A425678"));
+        assert!(!is_valid_iscsi_name(b"eui.02004567A425678G"));
+        assert!(!is_valid_iscsi_name(b"naa.52004567BA64678D01"));
+        assert!(!is_valid_iscsi_name(b"iscsi.2001-04.com.example"));
+
+        let mut long = [b'a'; MAX_ISCSI_NAME_LEN + 1];
+        long[..24].copy_from_slice(b"iqn.2001-04.com.example:");
+        assert!(!is_valid_iscsi_name(&long));
+        assert!(is_valid_iscsi_name(&long[..MAX_ISCSI_NAME_LEN]));
+    }
+}
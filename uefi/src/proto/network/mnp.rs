@@ -0,0 +1,467 @@
+//! Managed Network Protocol (MNP).
+//!
+//! MNP provides shared access to a network interface at the frame level.
+//! Unlike the [`SimpleNetwork`] protocol, multiple MNP instances can use the
+//! same interface at once, so it can be used without disrupting the network
+//! stack of the firmware.
+//!
+//! MNP instances are created through the [`ManagedNetworkServiceBinding`]
+//! protocol installed on a network interface handle. The [`MnpSocket`] type
+//! takes care of creating and configuring the child instance:
+//!
+//! ```no_run
+//! use core::time::Duration;
+//! use uefi::proto::network::mnp::MnpSocket;
+//! use uefi::proto::network::MacAddress;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! // Send and receive frames with a local experimental EtherType.
+//! let mut socket = MnpSocket::open(nic_handle, 0x88b5)?;
+//! let mut broadcast = MacAddress([0; 32]);
+//! broadcast.0[..6].fill(0xff);
+//! socket.send(broadcast, b"hello")?;
+//!
+//! let mut buf = [0; 1500];
+//! let frame = socket.recv(&mut buf, Some(Duration::from_secs(1)))?;
+//! log::info!("received {} bytes from {:?}", frame.len, frame.source);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`SimpleNetwork`]: super::snp::SimpleNetwork
+
+use super::snp::NetworkMode;
+use super::{IpAddress, MacAddress};
+use crate::boot;
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{Event, Handle, Result, Status, StatusExt};
+use core::mem::MaybeUninit;
+use core::net::IpAddr;
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::network::mnp::{
+    ManagedNetworkCompletionToken, ManagedNetworkConfigData, ManagedNetworkFragmentData,
+    ManagedNetworkPacket, ManagedNetworkProtocol, ManagedNetworkTransmitData,
+};
+
+/// Service binding protocol for creating [`ManagedNetwork`] instances.
+pub type ManagedNetworkServiceBinding = ServiceBinding<ManagedNetwork>;
+
+/// Managed Network Protocol.
+///
+/// [`transmit`] and [`receive`] block until the operation finishes, calling
+/// [`poll`] in between checks of the token's event.
+///
+/// Corresponds to the `EFI_MANAGED_NETWORK_PROTOCOL` in the C API.
+///
+/// [`transmit`]: Self::transmit
+/// [`receive`]: Self::receive
+/// [`poll`]: Self::poll
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(ManagedNetworkProtocol::GUID)]
+pub struct ManagedNetwork(ManagedNetworkProtocol);
+
+unsafe impl ServiceBindingChild for ManagedNetwork {
+    const SERVICE_BINDING_GUID: crate::Guid = ManagedNetworkProtocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of a [`ManagedNetwork`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MnpConfig {
+    /// Time in microseconds after which received frames that have not been
+    /// retrieved are dropped. If zero, the implementation's default is used.
+    pub receive_queue_timeout: u32,
+
+    /// Time in microseconds after which frames that have not been
+    /// transmitted are dropped. If zero, the implementation's default is
+    /// used.
+    pub transmit_queue_timeout: u32,
+
+    /// Only receive frames with this protocol type (EtherType). If zero,
+    /// frames of all protocol types are received.
+    pub protocol_type: u16,
+
+    /// Receive frames sent to the interface's address.
+    pub receive_unicast: bool,
+
+    /// Receive frames sent to the multicast groups that have been joined.
+    pub receive_multicast: bool,
+
+    /// Receive broadcast frames.
+    pub receive_broadcast: bool,
+
+    /// Receive all frames, regardless of their destination address.
+    pub receive_promiscuous: bool,
+
+    /// Drop queued frames when the instance is reset.
+    pub flush_queues_on_reset: bool,
+
+    /// Record the time at which frames are received.
+    pub receive_timestamps: bool,
+
+    /// Disable the polling of the interface from a timer event. If set,
+    /// frames are only received while [`ManagedNetwork::poll`] is called.
+    pub disable_background_polling: bool,
+}
+
+impl MnpConfig {
+    /// Create a configuration that receives unicast and broadcast frames of
+    /// `protocol_type`.
+    #[must_use]
+    pub const fn new(protocol_type: u16) -> Self {
+        Self {
+            receive_queue_timeout: 0,
+            transmit_queue_timeout: 0,
+            protocol_type,
+            receive_unicast: true,
+            receive_multicast: false,
+            receive_broadcast: true,
+            receive_promiscuous: false,
+            flush_queues_on_reset: true,
+            receive_timestamps: false,
+            disable_background_polling: false,
+        }
+    }
+}
+
+impl From<ManagedNetworkConfigData> for MnpConfig {
+    fn from(data: ManagedNetworkConfigData) -> Self {
+        Self {
+            receive_queue_timeout: data.received_queue_timeout_value,
+            transmit_queue_timeout: data.transmit_queue_timeout_value,
+            protocol_type: data.protocol_type_filter,
+            receive_unicast: data.enable_unicast_receive,
+            receive_multicast: data.enable_multicast_receive,
+            receive_broadcast: data.enable_broadcast_receive,
+            receive_promiscuous: data.enable_promiscuous_receive,
+            flush_queues_on_reset: data.flush_queues_on_reset,
+            receive_timestamps: data.enable_receive_timestamps,
+            disable_background_polling: data.disable_background_polling,
+        }
+    }
+}
+
+impl From<MnpConfig> for ManagedNetworkConfigData {
+    fn from(config: MnpConfig) -> Self {
+        Self {
+            received_queue_timeout_value: config.receive_queue_timeout,
+            transmit_queue_timeout_value: config.transmit_queue_timeout,
+            protocol_type_filter: config.protocol_type,
+            enable_unicast_receive: config.receive_unicast,
+            enable_multicast_receive: config.receive_multicast,
+            enable_broadcast_receive: config.receive_broadcast,
+            enable_promiscuous_receive: config.receive_promiscuous,
+            flush_queues_on_reset: config.flush_queues_on_reset,
+            enable_receive_timestamps: config.receive_timestamps,
+            disable_background_polling: config.disable_background_polling,
+        }
+    }
+}
+
+/// Information about a frame received by [`ManagedNetwork::receive`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MnpFrame {
+    /// Number of payload bytes copied into the buffer.
+    pub len: usize,
+
+    /// Length of the payload, which may be larger than the buffer.
+    pub data_len: usize,
+
+    /// Source hardware address.
+    pub source: MacAddress,
+
+    /// Destination hardware address.
+    pub destination: MacAddress,
+
+    /// Protocol type (EtherType) of the frame.
+    pub protocol_type: u16,
+
+    /// The frame was sent to the broadcast address.
+    pub broadcast: bool,
+
+    /// The frame was sent to a multicast address.
+    pub multicast: bool,
+
+    /// The frame was only received because of promiscuous mode.
+    pub promiscuous: bool,
+}
+
+impl ManagedNetwork {
+    /// Get the current configuration of the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    pub fn config(&self) -> Result<MnpConfig> {
+        let mut data = ManagedNetworkConfigData::default();
+        unsafe { (self.0.get_mode_data)(&self.0, &mut data, ptr::null_mut()) }
+            .to_result_with_val(|| data.into())
+    }
+
+    /// Get the mode of the underlying network interface. This works whether
+    /// or not the instance has been configured.
+    pub fn snp_mode(&self) -> Result<NetworkMode> {
+        let mut mode = MaybeUninit::<NetworkMode>::zeroed();
+        let status =
+            unsafe { (self.0.get_mode_data)(&self.0, ptr::null_mut(), mode.as_mut_ptr().cast()) };
+        // The mode is returned even if the instance is not configured.
+        if status.is_success() || status == Status::NOT_STARTED {
+            Ok(unsafe { mode.assume_init() })
+        } else {
+            Err(status.into())
+        }
+    }
+
+    /// Configure the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::UNSUPPORTED`]: the requested receive filters are not
+    ///   supported by the interface.
+    pub fn configure(&mut self, config: &MnpConfig) -> Result {
+        let data = ManagedNetworkConfigData::from(*config);
+        unsafe { (self.0.configure)(&mut self.0, &data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. Pending transmit and
+    /// receive operations are cancelled and all multicast groups are left.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Get the hardware address of the multicast IP address `ip`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::INVALID_PARAMETER`]: `ip` is not a multicast address.
+    pub fn mcast_ip_to_mac(&mut self, ip: IpAddr) -> Result<MacAddress> {
+        let (ipv6, ip) = match ip {
+            IpAddr::V4(ip) => (false, IpAddress::new_v4(ip.octets())),
+            IpAddr::V6(ip) => (true, IpAddress::new_v6(ip.octets())),
+        };
+        let mut mac = uefi_raw::MacAddress::default();
+        unsafe { (self.0.mcast_ip_to_mac)(&mut self.0, ipv6, ptr::from_ref(&ip).cast(), &mut mac) }
+            .to_result_with_val(|| MacAddress(mac.0))
+    }
+
+    /// Join the multicast group with hardware address `mac`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::INVALID_PARAMETER`]: `mac` is not a multicast address.
+    /// * [`Status::ALREADY_STARTED`]: the group has already been joined.
+    pub fn join_group(&mut self, mac: MacAddress) -> Result {
+        let mac = uefi_raw::MacAddress(mac.0);
+        unsafe { (self.0.groups)(&mut self.0, true, &mac) }.to_result()
+    }
+
+    /// Leave the multicast group with hardware address `mac`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NOT_FOUND`]: the group has not been joined.
+    pub fn leave_group(&mut self, mac: MacAddress) -> Result {
+        let mac = uefi_raw::MacAddress(mac.0);
+        unsafe { (self.0.groups)(&mut self.0, false, &mac) }.to_result()
+    }
+
+    /// Leave all multicast groups.
+    pub fn leave_all_groups(&mut self) -> Result {
+        unsafe { (self.0.groups)(&mut self.0, false, ptr::null()) }.to_result()
+    }
+
+    /// Poll the interface for incoming frames and process outgoing frames.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NOT_READY`]: no incoming or outgoing frames were
+    ///   processed.
+    /// * [`Status::TIMEOUT`]: frames were dropped out of the queues.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Send `data` in a frame to `destination` and wait until it has been
+    /// transmitted. The media header is built by the implementation.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::INVALID_PARAMETER`]: `data` is too large for a frame.
+    /// * [`Status::NOT_READY`]: the transmit queue is full.
+    pub fn transmit(&mut self, destination: MacAddress, protocol_type: u16, data: &[u8]) -> Result {
+        let len = u32::try_from(data.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+        let mut destination = uefi_raw::MacAddress(destination.0);
+
+        let event = CompletionEvent::new()?;
+        let mut tx_data = ManagedNetworkTransmitData {
+            destination_address: &mut destination,
+            source_address: ptr::null_mut(),
+            protocol_type,
+            data_length: len,
+            header_length: 0,
+            fragment_count: 1,
+            fragment_table: [ManagedNetworkFragmentData {
+                fragment_length: len,
+                fragment_buffer: data.as_ptr().cast_mut().cast(),
+            }],
+        };
+        let mut token = ManagedNetworkCompletionToken {
+            event: event.as_raw(),
+            status: Status::SUCCESS,
+            packet: ManagedNetworkPacket {
+                tx_data: &mut tx_data,
+            },
+        };
+        unsafe { (self.0.transmit)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token, None)
+    }
+
+    /// Wait for a frame and copy its payload into `buffer`.
+    ///
+    /// If the payload is larger than `buffer`, the excess bytes are
+    /// discarded.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TIMEOUT`]: `timeout` elapsed before a frame arrived.
+    pub fn receive(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<MnpFrame> {
+        let event = CompletionEvent::new()?;
+        let mut token = ManagedNetworkCompletionToken {
+            event: event.as_raw(),
+            status: Status::SUCCESS,
+            packet: ManagedNetworkPacket {
+                rx_data: ptr::null_mut(),
+            },
+        };
+        unsafe { (self.0.receive)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token, timeout)?;
+
+        // Safety: on success, the implementation has filled in `rx_data`,
+        // which stays valid until `recycle_event` is signaled.
+        let rx_data = unsafe { &*token.packet.rx_data };
+        let data_len = rx_data.data_length as usize;
+        let len = data_len.min(buffer.len());
+        unsafe {
+            ptr::copy_nonoverlapping(rx_data.packet_data.cast::<u8>(), buffer.as_mut_ptr(), len);
+        }
+
+        let address_len = (rx_data.address_length as usize).min(32);
+        let read_address = |address: *const u8| {
+            let mut mac = MacAddress([0; 32]);
+            if !address.is_null() {
+                unsafe { ptr::copy_nonoverlapping(address, mac.0.as_mut_ptr(), address_len) };
+            }
+            mac
+        };
+        let frame = MnpFrame {
+            len,
+            data_len,
+            source: read_address(rx_data.source_address.cast()),
+            destination: read_address(rx_data.destination_address.cast()),
+            protocol_type: rx_data.protocol_type,
+            broadcast: rx_data.broadcast_flag,
+            multicast: rx_data.multicast_flag,
+            promiscuous: rx_data.promiscuous_flag,
+        };
+
+        // Give the receive buffer back to the implementation.
+        if let Some(recycle) = unsafe { Event::from_ptr(rx_data.recycle_event) } {
+            boot::signal_event(&recycle)?;
+        }
+
+        Ok(frame)
+    }
+
+    /// Wait for an outstanding `token` to complete. If `timeout` elapses
+    /// first, the token is cancelled before returning.
+    fn wait_for_token(
+        &mut self,
+        event: &CompletionEvent,
+        token: &mut ManagedNetworkCompletionToken,
+        timeout: Option<Duration>,
+    ) -> Result {
+        let this = ptr::from_mut(&mut self.0);
+        let (poll, cancel) = (self.0.poll, self.0.cancel);
+        event.wait_or_cancel(
+            timeout,
+            // Errors from `poll` just mean no progress was made.
+            || {
+                let _ = unsafe { poll(this) };
+            },
+            || unsafe { cancel(this, token) },
+        )?;
+        token.status.to_result()
+    }
+}
+
+/// A frame-level socket, including the [`ManagedNetwork`] child instance it
+/// uses.
+///
+/// The child instance is destroyed when the `MnpSocket` is dropped.
+#[derive(Debug)]
+pub struct MnpSocket {
+    mnp: ServiceChild<ManagedNetwork>,
+    protocol_type: u16,
+}
+
+impl MnpSocket {
+    /// Create a [`ManagedNetwork`] instance on the network interface
+    /// `nic_handle` that sends and receives unicast and broadcast frames of
+    /// `protocol_type`.
+    pub fn open(nic_handle: Handle, protocol_type: u16) -> Result<Self> {
+        Self::open_with_config(nic_handle, &MnpConfig::new(protocol_type))
+    }
+
+    /// Create a [`ManagedNetwork`] instance on the network interface
+    /// `nic_handle` and configure it with `config`. Frames are sent with the
+    /// protocol type of `config`.
+    pub fn open_with_config(nic_handle: Handle, config: &MnpConfig) -> Result<Self> {
+        let mut mnp = ServiceChild::<ManagedNetwork>::new(nic_handle)?;
+        mnp.configure(config)?;
+        Ok(Self {
+            mnp,
+            protocol_type: config.protocol_type,
+        })
+    }
+
+    /// Get the hardware address of the interface.
+    pub fn local_addr(&self) -> Result<MacAddress> {
+        Ok(self.mnp.snp_mode()?.current_address)
+    }
+
+    /// Send `data` in a frame to `destination`.
+    pub fn send(&mut self, destination: MacAddress, data: &[u8]) -> Result {
+        self.mnp.transmit(destination, self.protocol_type, data)
+    }
+
+    /// Wait for a frame and copy its payload into `buffer`. See
+    /// [`ManagedNetwork::receive`].
+    pub fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<MnpFrame> {
+        self.mnp.receive(buffer, timeout)
+    }
+
+    /// Get the underlying [`ManagedNetwork`] protocol.
+    pub fn protocol(&mut self) -> &mut ManagedNetwork {
+        &mut self.mnp
+    }
+}
+
+impl Drop for MnpSocket {
+    fn drop(&mut self) {
+        // Cancel any pending operations before the child is destroyed.
+        let _ = self.mnp.reset();
+    }
+}
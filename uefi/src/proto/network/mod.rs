@@ -2,12 +2,47 @@
 //!
 //! These protocols can be used to interact with network resources.
 
+pub mod arp;
+pub mod dhcp4;
+pub mod dns4;
+pub mod dns6;
+pub mod eap_configuration;
+pub mod http;
+pub mod ip4_config2;
+pub mod iscsi;
+pub mod mnp;
+pub mod mtftp4;
 pub mod pxe;
+pub mod rest;
+#[cfg(feature = "smoltcp")]
+pub mod smoltcp;
 pub mod snp;
+pub mod supplicant;
+pub mod tcp;
+pub mod tcp4;
+pub mod tcp6;
+pub mod tls;
+pub mod udp4;
+pub mod udp6;
+pub mod vlan_config;
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 /// Represents an IPv4/v6 address.
 ///
 /// Corresponds to the `EFI_IP_ADDRESS` type in the C API.
+///
+/// An `IpAddress` can be created from the [`core::net`] address types, but
+/// there is no `TryFrom<IpAddress>` for them: the type does not record the
+/// address family, and an IPv6 address whose last twelve bytes are zero is
+/// indistinguishable from an IPv4 address. Use [`to_ip_addr`] with the
+/// family known from context instead. Likewise, there are no conversions
+/// to or from [`SocketAddr`], since no EFI address type holds a port; APIs
+/// that take an address and a port use the [`core::net`] socket types
+/// directly.
+///
+/// [`to_ip_addr`]: Self::to_ip_addr
+/// [`SocketAddr`]: core::net::SocketAddr
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(C, align(4))]
 pub struct IpAddress(pub [u8; 16]);
@@ -29,6 +64,35 @@ impl IpAddress {
     pub const fn new_v6(ip_addr: [u8; 16]) -> Self {
         Self(ip_addr)
     }
+
+    /// Convert to a [`core::net::IpAddr`], interpreting the contents as an
+    /// IPv6 address if `is_ipv6` is true and as an IPv4 address otherwise.
+    #[must_use]
+    pub fn to_ip_addr(self, is_ipv6: bool) -> IpAddr {
+        // SAFETY: the conversion initializes all 16 bytes.
+        unsafe { uefi_raw::IpAddress::from(self).to_ip_addr(is_ipv6) }
+    }
+}
+
+impl From<Ipv4Addr> for IpAddress {
+    fn from(ip: Ipv4Addr) -> Self {
+        uefi_raw::Ipv4Address::from(ip).into()
+    }
+}
+
+impl From<Ipv6Addr> for IpAddress {
+    fn from(ip: Ipv6Addr) -> Self {
+        uefi_raw::Ipv6Address::from(ip).into()
+    }
+}
+
+impl From<IpAddr> for IpAddress {
+    fn from(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(ip) => ip.into(),
+            IpAddr::V6(ip) => ip.into(),
+        }
+    }
 }
 
 /// Represents a MAC (media access control) address.
@@ -37,3 +101,79 @@ impl IpAddress {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct MacAddress(pub [u8; 32]);
+
+impl From<uefi_raw::Ipv4Address> for IpAddress {
+    fn from(ip: uefi_raw::Ipv4Address) -> Self {
+        Self::new_v4(ip.0)
+    }
+}
+
+impl From<uefi_raw::Ipv6Address> for IpAddress {
+    fn from(ip: uefi_raw::Ipv6Address) -> Self {
+        Self::new_v6(ip.0)
+    }
+}
+
+impl From<IpAddress> for uefi_raw::IpAddress {
+    fn from(ip: IpAddress) -> Self {
+        // All 16 bytes are copied, so this works for both address families.
+        Self::new_v6(ip.0)
+    }
+}
+
+impl From<[u8; 6]> for MacAddress {
+    /// Create a MAC address from an Ethernet address, zero-filling the
+    /// remaining bytes.
+    fn from(addr: [u8; 6]) -> Self {
+        uefi_raw::MacAddress::from(addr).into()
+    }
+}
+
+impl From<MacAddress> for [u8; 6] {
+    /// Get the Ethernet address stored in the first six bytes of a MAC
+    /// address.
+    fn from(mac: MacAddress) -> Self {
+        uefi_raw::MacAddress::from(mac).into()
+    }
+}
+
+impl From<uefi_raw::MacAddress> for MacAddress {
+    fn from(mac: uefi_raw::MacAddress) -> Self {
+        Self(mac.0)
+    }
+}
+
+impl From<MacAddress> for uefi_raw::MacAddress {
+    fn from(mac: MacAddress) -> Self {
+        Self(mac.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_addr_conversions() {
+        let v4 = Ipv4Addr::new(192, 168, 0, 1);
+        let ip = IpAddress::from(v4);
+        assert_eq!(ip, IpAddress::new_v4([192, 168, 0, 1]));
+        assert_eq!(ip.to_ip_addr(false), IpAddr::V4(v4));
+
+        let v6 = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        let ip = IpAddress::from(IpAddr::V6(v6));
+        assert_eq!(ip.to_ip_addr(true), IpAddr::V6(v6));
+
+        let raw = uefi_raw::IpAddress::from(ip);
+        assert_eq!(unsafe { raw.to_ip_addr(true) }, IpAddr::V6(v6));
+    }
+
+    #[test]
+    fn test_mac_addr_conversions() {
+        let ethernet = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+        let mac = MacAddress::from(ethernet);
+        assert_eq!(&mac.0[..6], &ethernet);
+        assert!(mac.0[6..].iter().all(|&b| b == 0));
+        assert_eq!(<[u8; 6]>::from(mac), ethernet);
+    }
+}
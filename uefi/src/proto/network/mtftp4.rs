@@ -0,0 +1,453 @@
+//! MTFTPv4 protocol.
+//!
+//! MTFTPv4 instances are created through the [`Mtftp4ServiceBinding`]
+//! protocol installed on a network interface handle. The [`Mtftp4Client`]
+//! type takes care of creating and configuring the child instance, and
+//! streams downloaded files into a callback:
+//!
+//! ```no_run
+//! use core::net::Ipv4Addr;
+//! use uefi::cstr8;
+//! use uefi::proto::network::mtftp4::Mtftp4Client;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut tftp = Mtftp4Client::new(nic_handle)?;
+//! let server = Ipv4Addr::new(192, 168, 0, 1);
+//! let mut checksum = 0u8;
+//! let size = tftp.read_file_with_progress(
+//!     server,
+//!     cstr8!("vmlinuz"),
+//!     &mut |chunk| {
+//!         checksum = chunk.iter().fold(checksum, |acc, b| acc.wrapping_add(*b));
+//!         Ok(())
+//!     },
+//!     &mut |progress| log::info!("{} of {:?} bytes", progress.received, progress.total),
+//! )?;
+//! log::info!("downloaded {size} bytes");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{cstr8, CStr8, Error, Handle, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::net::{Ipv4Addr, SocketAddrV4};
+use core::ptr;
+use core::slice;
+use uefi_raw::protocol::network::mtftp4::{
+    Mtftp4ConfigData, Mtftp4OpCode, Mtftp4Option, Mtftp4OverrideData, Mtftp4Packet, Mtftp4Protocol,
+    Mtftp4Token,
+};
+use uefi_raw::Ipv4Address;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Well-known TFTP server port.
+pub const TFTP_PORT: u16 = 69;
+
+/// Service binding protocol for creating [`Mtftp4`] instances.
+pub type Mtftp4ServiceBinding = ServiceBinding<Mtftp4>;
+
+/// MTFTPv4 protocol.
+///
+/// Corresponds to the `EFI_MTFTP4_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Mtftp4Protocol::GUID)]
+pub struct Mtftp4(Mtftp4Protocol);
+
+unsafe impl ServiceBindingChild for Mtftp4 {
+    const SERVICE_BINDING_GUID: crate::Guid = Mtftp4Protocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of an [`Mtftp4`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Mtftp4Config {
+    /// Local address and subnet mask of the instance. If `None`, the default
+    /// address of the interface is used.
+    pub station_address: Option<(Ipv4Addr, Ipv4Addr)>,
+
+    /// Local port. If zero, an ephemeral port is chosen by the
+    /// implementation.
+    pub local_port: u16,
+
+    /// Gateway used to reach the server, or the unspecified address if the
+    /// server is on the local subnet.
+    pub gateway: Ipv4Addr,
+
+    /// Default server address and port, used when a transfer does not
+    /// specify one.
+    pub server: SocketAddrV4,
+
+    /// Number of times a request is sent. If zero, the implementation's
+    /// default is used.
+    pub try_count: u16,
+
+    /// Time to wait for a response to a request, in seconds. If zero, the
+    /// implementation's default is used.
+    pub timeout: u16,
+}
+
+impl Mtftp4Config {
+    /// Create a configuration that uses the default address of the
+    /// interface and `server` on the well-known TFTP port.
+    #[must_use]
+    pub const fn new(server: Ipv4Addr) -> Self {
+        Self {
+            station_address: None,
+            local_port: 0,
+            gateway: Ipv4Addr::UNSPECIFIED,
+            server: SocketAddrV4::new(server, TFTP_PORT),
+            try_count: 0,
+            timeout: 0,
+        }
+    }
+}
+
+impl Default for Mtftp4Config {
+    fn default() -> Self {
+        Self::new(Ipv4Addr::UNSPECIFIED)
+    }
+}
+
+/// Progress of a download, passed to the progress callback of
+/// [`Mtftp4::read_file`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Mtftp4Progress {
+    /// Number of bytes received so far.
+    pub received: u64,
+
+    /// Size of the file, if the server reported it in the `tsize` option.
+    pub total: Option<u64>,
+}
+
+impl Mtftp4 {
+    /// Configure the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::ACCESS_DENIED`]: a transfer is in progress.
+    /// * [`Status::NO_MAPPING`]: the default address is used but address
+    ///   configuration (e.g. DHCP) has not finished yet.
+    pub fn configure(&mut self, config: &Mtftp4Config) -> Result {
+        let (station_ip, subnet_mask) = config
+            .station_address
+            .unwrap_or((Ipv4Addr::UNSPECIFIED, Ipv4Addr::UNSPECIFIED));
+        let config_data = Mtftp4ConfigData {
+            use_default_setting: config.station_address.is_none(),
+            station_ip: station_ip.into(),
+            subnet_mask: subnet_mask.into(),
+            local_port: config.local_port,
+            gateway_ip: config.gateway.into(),
+            server_ip: (*config.server.ip()).into(),
+            initial_server_port: config.server.port(),
+            try_count: config.try_count,
+            timeout_value: config.timeout,
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. A transfer in progress
+    /// is aborted.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Poll for incoming data packets and process outgoing data packets.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TIMEOUT`]: data was dropped out of the queues.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Download the file at `path` and wait until the download has
+    /// finished. Returns the size of the file.
+    ///
+    /// The file is downloaded from `server`, or from the configured server if
+    /// `None`. Each block of data is passed to `write` in order; if it fails,
+    /// the transfer is aborted and its error is returned. `progress` is
+    /// called after each block.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TFTP_ERROR`]: the server returned an error, for example
+    ///   because the file does not exist.
+    /// * [`Status::TIMEOUT`]: the server did not respond.
+    /// * [`Status::ICMP_ERROR`]: the server is unreachable.
+    pub fn read_file(
+        &mut self,
+        server: Option<SocketAddrV4>,
+        path: &CStr8,
+        write: &mut dyn FnMut(&[u8]) -> Result,
+        progress: &mut dyn FnMut(Mtftp4Progress),
+    ) -> Result<u64> {
+        let mut override_data = server.map(|server| Mtftp4OverrideData {
+            gateway_ip: Ipv4Address::default(),
+            server_ip: (*server.ip()).into(),
+            server_port: server.port(),
+            try_count: 0,
+            timeout_value: 0,
+        });
+
+        // Ask the server for the file size so it can be reported to the
+        // progress callback.
+        let mut options = [Mtftp4Option {
+            option_str: cstr8!("tsize").as_ptr().cast::<u8>().cast_mut(),
+            value_str: cstr8!("0").as_ptr().cast::<u8>().cast_mut(),
+        }];
+
+        let mut context = ReadContext {
+            write,
+            progress,
+            received: 0,
+            total: None,
+            error: None,
+        };
+        let mut token = Mtftp4Token {
+            status: Status::SUCCESS,
+            // Without an event, `ReadFile` runs synchronously.
+            event: ptr::null_mut(),
+            override_data: override_data
+                .as_mut()
+                .map_or(ptr::null_mut(), ptr::from_mut),
+            filename: path.as_ptr().cast::<u8>().cast_mut(),
+            mode_str: ptr::null_mut(),
+            option_count: options.len() as u32,
+            option_list: options.as_mut_ptr(),
+            buffer_size: 0,
+            buffer: ptr::null_mut(),
+            context: ptr::from_mut(&mut context).cast::<c_void>(),
+            check_packet: Some(check_packet),
+            timeout_callback: None,
+            packet_needed: None,
+        };
+        let status = unsafe { (self.0.read_file)(&mut self.0, &mut token) };
+        if let Some(err) = context.error {
+            return Err(err);
+        }
+        status.to_result_with_val(|| context.received)
+    }
+}
+
+/// State of a download, shared with [`check_packet`] through the token
+/// context.
+struct ReadContext<'a> {
+    write: &'a mut dyn FnMut(&[u8]) -> Result,
+    progress: &'a mut dyn FnMut(Mtftp4Progress),
+    received: u64,
+    total: Option<u64>,
+    error: Option<Error>,
+}
+
+impl ReadContext<'_> {
+    /// Handle a packet received from the server. Returns an error status to
+    /// abort the transfer.
+    fn handle_packet(&mut self, packet: &[u8]) -> Status {
+        let Some((op_code, rest)) = packet.split_first_chunk::<2>() else {
+            return Status::SUCCESS;
+        };
+        let data = match Mtftp4OpCode(u16::from_be_bytes(*op_code)) {
+            Mtftp4OpCode::OACK => {
+                if let Some(total) = parse_tsize(rest) {
+                    self.total = Some(total);
+                }
+                return Status::SUCCESS;
+            }
+            Mtftp4OpCode::DATA => rest.get(2..),
+            Mtftp4OpCode::DATA8 => rest.get(8..),
+            _ => return Status::SUCCESS,
+        };
+        let data = data.unwrap_or_default();
+
+        if let Err(err) = (self.write)(data) {
+            self.error = Some(err);
+            return Status::ABORTED;
+        }
+        self.received += data.len() as u64;
+        (self.progress)(Mtftp4Progress {
+            received: self.received,
+            total: self.total,
+        });
+        Status::SUCCESS
+    }
+}
+
+unsafe extern "efiapi" fn check_packet(
+    _this: *mut Mtftp4Protocol,
+    token: *mut Mtftp4Token,
+    packet_len: u16,
+    packet: *const Mtftp4Packet,
+) -> Status {
+    let context = unsafe { &mut *(*token).context.cast::<ReadContext>() };
+    let packet = unsafe { slice::from_raw_parts(packet.cast::<u8>(), usize::from(packet_len)) };
+    context.handle_packet(packet)
+}
+
+/// Get the value of the `tsize` option from the options of an OACK packet.
+fn parse_tsize(options: &[u8]) -> Option<u64> {
+    let mut strings = options.split(|&b| b == 0);
+    while let (Some(name), Some(value)) = (strings.next(), strings.next()) {
+        if name.eq_ignore_ascii_case(b"tsize") {
+            return core::str::from_utf8(value).ok()?.parse().ok();
+        }
+    }
+    None
+}
+
+/// A TFTP client, including the [`Mtftp4`] child instance it uses.
+///
+/// The child instance is destroyed when the `Mtftp4Client` is dropped.
+#[derive(Debug)]
+pub struct Mtftp4Client {
+    tftp: ServiceChild<Mtftp4>,
+}
+
+impl Mtftp4Client {
+    /// Create an [`Mtftp4`] instance on the network interface `nic_handle`,
+    /// using the default address of the interface.
+    pub fn new(nic_handle: Handle) -> Result<Self> {
+        Self::with_config(nic_handle, &Mtftp4Config::default())
+    }
+
+    /// Create an [`Mtftp4`] instance on the network interface `nic_handle`
+    /// and configure it with `config`.
+    pub fn with_config(nic_handle: Handle, config: &Mtftp4Config) -> Result<Self> {
+        let mut tftp = ServiceChild::<Mtftp4>::new(nic_handle)?;
+        tftp.configure(config)?;
+        Ok(Self { tftp })
+    }
+
+    /// Download the file at `path` from `server`, passing each block of data
+    /// to `write`. Returns the size of the file.
+    pub fn read_file(
+        &mut self,
+        server: Ipv4Addr,
+        path: &CStr8,
+        write: &mut dyn FnMut(&[u8]) -> Result,
+    ) -> Result<u64> {
+        self.read_file_with_progress(server, path, write, &mut |_| {})
+    }
+
+    /// Download the file at `path` from `server`, passing each block of data
+    /// to `write` and reporting progress to `progress`. Returns the size of
+    /// the file.
+    pub fn read_file_with_progress(
+        &mut self,
+        server: Ipv4Addr,
+        path: &CStr8,
+        write: &mut dyn FnMut(&[u8]) -> Result,
+        progress: &mut dyn FnMut(Mtftp4Progress),
+    ) -> Result<u64> {
+        let server = SocketAddrV4::new(server, TFTP_PORT);
+        self.tftp.read_file(Some(server), path, write, progress)
+    }
+
+    /// Download the file at `path` from `server` into memory.
+    #[cfg(feature = "alloc")]
+    pub fn read_file_to_vec(&mut self, server: Ipv4Addr, path: &CStr8) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.read_file(server, path, &mut |chunk| {
+            data.extend_from_slice(chunk);
+            Ok(())
+        })?;
+        Ok(data)
+    }
+
+    /// Get the underlying [`Mtftp4`] protocol.
+    pub fn protocol(&mut self) -> &mut Mtftp4 {
+        &mut self.tftp
+    }
+}
+
+impl Drop for Mtftp4Client {
+    fn drop(&mut self) {
+        // Abort any transfer in progress before the child is destroyed.
+        let _ = self.tftp.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tsize() {
+        assert_eq!(
+            parse_tsize(b"blksize\x001468\x00tsize\x0012345\x00"),
+            Some(12345)
+        );
+        assert_eq!(parse_tsize(b"TSIZE\x007\x00"), Some(7));
+        assert_eq!(parse_tsize(b"blksize\x001468\x00"), None);
+        assert_eq!(parse_tsize(b"tsize\x00abc\x00"), None);
+        assert_eq!(parse_tsize(b""), None);
+    }
+
+    #[test]
+    fn test_handle_packet() {
+        let mut written = [0u8; 8];
+        let mut written_len = 0;
+        let mut reports = 0;
+        let mut context = ReadContext {
+            write: &mut |chunk| {
+                written[written_len..written_len + chunk.len()].copy_from_slice(chunk);
+                written_len += chunk.len();
+                Ok(())
+            },
+            progress: &mut |progress| {
+                assert_eq!(progress.total, Some(5));
+                reports += 1;
+            },
+            received: 0,
+            total: None,
+            error: None,
+        };
+
+        assert_eq!(
+            context.handle_packet(b"\x00\x06tsize\x005\x00"),
+            Status::SUCCESS
+        );
+        assert_eq!(
+            context.handle_packet(b"\x00\x03\x00\x01abc"),
+            Status::SUCCESS
+        );
+        assert_eq!(
+            context.handle_packet(b"\x00\x03\x00\x02de"),
+            Status::SUCCESS
+        );
+        // Error packets are left to the implementation.
+        assert_eq!(
+            context.handle_packet(b"\x00\x05\x00\x01oops\x00"),
+            Status::SUCCESS
+        );
+        assert_eq!(context.received, 5);
+
+        assert_eq!(&written[..written_len], b"abcde");
+        assert_eq!(reports, 2);
+    }
+
+    #[test]
+    fn test_handle_packet_write_error() {
+        let mut context = ReadContext {
+            write: &mut |_| Err(Status::VOLUME_FULL.into()),
+            progress: &mut |_| panic!("no progress expected"),
+            received: 0,
+            total: None,
+            error: None,
+        };
+        assert_eq!(
+            context.handle_packet(b"\x00\x03\x00\x01abc"),
+            Status::ABORTED
+        );
+        assert_eq!(context.error.unwrap().status(), Status::VOLUME_FULL);
+    }
+}
@@ -14,8 +14,12 @@ use ptr_meta::Pointee;
 
 use crate::{CStr8, Char8, Result, Status, StatusExt};
 
+use super::dhcp4::{Dhcp4OptionCode, Dhcp4Options};
 use super::{IpAddress, MacAddress};
 
+#[cfg(feature = "alloc")]
+use {alloc::vec, alloc::vec::Vec};
+
 /// PXE Base Code protocol
 #[derive(Debug)]
 #[repr(C)]
@@ -925,6 +929,46 @@ impl Debug for Packet {
     }
 }
 
+impl Packet {
+    /// Offset of the options in a DHCPv4 packet, after the fixed BOOTP
+    /// header and the magic cookie.
+    const DHCPV4_OPTIONS_OFFSET: usize = 240;
+
+    /// Iterate over the options of the packet, interpreted as a DHCPv4
+    /// packet. Unlike [`DhcpV4Packet::dhcp_options`], this includes options
+    /// beyond the first 56 bytes.
+    #[must_use]
+    pub fn dhcpv4_options(&self) -> Dhcp4Options<'_> {
+        let raw: &[u8; 1472] = self.as_ref();
+        Dhcp4Options::new(&raw[Self::DHCPV4_OPTIONS_OFFSET..])
+    }
+
+    /// Get the boot file name of the packet, interpreted as a DHCPv4 packet,
+    /// without the trailing nul bytes.
+    ///
+    /// This is the `file` field of the header, or the boot file name option
+    /// if that field is empty.
+    #[must_use]
+    pub fn dhcpv4_boot_file(&self) -> &[u8] {
+        let dhcp: &DhcpV4Packet = self.as_ref();
+        let file = until_nul(&dhcp.bootp_boot_file);
+        if file.is_empty() {
+            self.dhcpv4_options()
+                .find(|o| o.code == Dhcp4OptionCode::BOOTFILE_NAME)
+                .map(|o| until_nul(o.data))
+                .unwrap_or_default()
+        } else {
+            file
+        }
+    }
+}
+
+impl From<[u8; 1472]> for Packet {
+    fn from(raw: [u8; 1472]) -> Self {
+        Self { raw }
+    }
+}
+
 impl AsRef<[u8; 1472]> for Packet {
     fn as_ref(&self) -> &[u8; 1472] {
         unsafe { &self.raw }
@@ -1339,3 +1383,215 @@ impl Display for ReadDirParseError {
 
 #[cfg(feature = "unstable")]
 impl core::error::Error for ReadDirParseError {}
+
+/// Get the bytes of `data` up to the first nul byte.
+fn until_nul(data: &[u8]) -> &[u8] {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    &data[..end]
+}
+
+/// A boot file downloaded by [`PxeBoot::run`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PxeBootFile {
+    /// Address of the TFTP server the file was downloaded from.
+    pub server_ip: IpAddress,
+    /// Name of the boot file, without a trailing nul byte.
+    pub file_name: Vec<u8>,
+    /// Contents of the boot file.
+    pub data: Vec<u8>,
+}
+
+/// Helper that performs the usual PXE boot sequence with a [`BaseCode`]:
+/// DHCP, optionally boot server discovery, and download of the boot file.
+///
+/// The boot server and boot file name are taken from the PXE reply if boot
+/// server discovery was performed, otherwise from the proxy DHCP offer or
+/// the DHCP acknowledgement. Both can be overridden.
+///
+/// Only IPv4 is supported.
+///
+/// ```no_run
+/// use uefi::boot;
+/// use uefi::proto::network::pxe::{BaseCode, PxeBoot};
+/// # fn test() -> uefi::Result {
+/// # let handle = boot::get_handle_for_protocol::<BaseCode>()?;
+///
+/// let mut base_code = boot::open_protocol_exclusive::<BaseCode>(handle)?;
+/// let file = PxeBoot::new().sort_offers(true).run(&mut base_code)?;
+/// log::info!("downloaded {} bytes", file.data.len());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub struct PxeBoot<'a> {
+    sort_offers: bool,
+    discover: Option<BootstrapType>,
+    server_ip: Option<IpAddress>,
+    boot_file: Option<&'a CStr8>,
+    dhcp: Option<&'a mut dyn FnMut(&mut BaseCode) -> Result>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> PxeBoot<'a> {
+    /// Create a helper with the default settings: offers are not sorted and
+    /// boot server discovery is skipped.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sort the received DHCP offers before selecting one. See
+    /// [`BaseCode::dhcp`].
+    #[must_use]
+    pub const fn sort_offers(mut self, sort_offers: bool) -> Self {
+        self.sort_offers = sort_offers;
+        self
+    }
+
+    /// Perform boot server discovery of type `ty` after DHCP. See
+    /// [`BaseCode::discover`].
+    #[must_use]
+    pub const fn discover(mut self, ty: BootstrapType) -> Self {
+        self.discover = Some(ty);
+        self
+    }
+
+    /// Download the boot file from `server_ip` instead of the server
+    /// provided by DHCP.
+    #[must_use]
+    pub const fn server_ip(mut self, server_ip: IpAddress) -> Self {
+        self.server_ip = Some(server_ip);
+        self
+    }
+
+    /// Download `boot_file` instead of the boot file provided by DHCP.
+    #[must_use]
+    pub const fn boot_file(mut self, boot_file: &'a CStr8) -> Self {
+        self.boot_file = Some(boot_file);
+        self
+    }
+
+    /// Replace the [`BaseCode::dhcp`] step with `dhcp`.
+    ///
+    /// This allows customizing the DHCP options, for example by performing
+    /// DHCP with the [`Dhcp4`] protocol and passing the results to the
+    /// base code with [`BaseCode::set_station_ip`] and
+    /// [`BaseCode::set_packets`].
+    ///
+    /// [`Dhcp4`]: super::dhcp4::Dhcp4
+    #[must_use]
+    pub fn dhcp_with(mut self, dhcp: &'a mut dyn FnMut(&mut BaseCode) -> Result) -> Self {
+        self.dhcp = Some(dhcp);
+        self
+    }
+
+    /// Run the boot sequence on `base_code`, starting it if necessary, and
+    /// download the boot file into memory.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: no boot server or boot file name was
+    ///   provided by DHCP, and none was set.
+    /// * Any error returned by [`BaseCode::start`], [`BaseCode::dhcp`],
+    ///   [`BaseCode::discover`] or the TFTP operations.
+    pub fn run(mut self, base_code: &mut BaseCode) -> Result<PxeBootFile> {
+        match base_code.start(false) {
+            Err(err) if err.status() != Status::ALREADY_STARTED => return Err(err),
+            _ => {}
+        }
+
+        match self.dhcp.as_mut() {
+            Some(dhcp) => dhcp(base_code)?,
+            None => base_code.dhcp(self.sort_offers)?,
+        }
+
+        if let Some(ty) = self.discover {
+            let mut layer = 0;
+            base_code.discover(ty, &mut layer, false, None)?;
+        }
+
+        let (server_ip, mut file_name) = self.boot_source(base_code.mode())?;
+
+        file_name.push(0);
+        let name = CStr8::from_bytes_with_nul(&file_name).map_err(|_| Status::INVALID_PARAMETER)?;
+        let size = base_code.tftp_get_file_size(&server_ip, name)?;
+        let size = usize::try_from(size).map_err(|_| Status::OUT_OF_RESOURCES)?;
+        let mut data = vec![0; size];
+        let len = base_code.tftp_read_file(&server_ip, name, Some(&mut data))?;
+        data.truncate(usize::try_from(len).unwrap_or(size));
+
+        file_name.pop();
+        Ok(PxeBootFile {
+            server_ip,
+            file_name,
+            data,
+        })
+    }
+    /// Get the boot server and boot file name from the cached packets of
+    /// `mode`, unless they are overridden.
+    fn boot_source(&self, mode: &Mode) -> Result<(IpAddress, Vec<u8>)> {
+        let packets = [
+            (mode.pxe_reply_received, &mode.pxe_reply),
+            (mode.proxy_offer_received, &mode.proxy_offer),
+            (mode.dhcp_ack_received, &mode.dhcp_ack),
+        ];
+        let packets = || {
+            packets
+                .into_iter()
+                .filter(|(received, _)| *received)
+                .map(|(_, packet)| packet)
+        };
+
+        // Prefer the server of the packet providing the file name.
+        let packet = packets().find(|packet| !packet.dhcpv4_boot_file().is_empty());
+        let file_name = match (self.boot_file, packet) {
+            (Some(boot_file), _) => until_nul(boot_file.as_bytes()).to_vec(),
+            (None, Some(packet)) => packet.dhcpv4_boot_file().to_vec(),
+            (None, None) => return Err(Status::NOT_FOUND.into()),
+        };
+        let server_ip = self
+            .server_ip
+            .or_else(|| {
+                packet
+                    .into_iter()
+                    .chain(packets())
+                    .map(|packet| AsRef::<DhcpV4Packet>::as_ref(packet).bootp_si_addr)
+                    .find(|addr| *addr != [0; 4])
+                    .map(IpAddress::new_v4)
+            })
+            .ok_or(Status::NOT_FOUND)?;
+        Ok((server_ip, file_name))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Debug for PxeBoot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PxeBoot")
+            .field("sort_offers", &self.sort_offers)
+            .field("discover", &self.discover)
+            .field("server_ip", &self.server_ip)
+            .field("boot_file", &self.boot_file)
+            .field("dhcp", &self.dhcp.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dhcpv4_boot_file() {
+        let mut raw = [0; 1472];
+        let options = [53, 1, 5, 67, 7, b'b', b'o', b'o', b't', b'.', b'e', 0, 255];
+        raw[240..240 + options.len()].copy_from_slice(&options);
+        assert_eq!(Packet::from(raw).dhcpv4_boot_file(), b"boot.e");
+
+        // The header field takes precedence over the option.
+        raw[108..112].copy_from_slice(b"file");
+        assert_eq!(Packet::from(raw).dhcpv4_boot_file(), b"file");
+    }
+}
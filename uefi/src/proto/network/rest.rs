@@ -0,0 +1,312 @@
+//! REST protocols.
+//!
+//! These protocols send requests to a REST service, such as the Redfish
+//! service of a platform's management controller. Unlike the [`Http`]
+//! protocol, they exchange whole messages: each request returns the complete
+//! response, including its body.
+//!
+//! [`RestEx`] instances are created through the [`RestExServiceBinding`]
+//! protocol, and must be configured before use:
+//!
+//! ```no_run
+//! use uefi::proto::driver::ServiceChild;
+//! use uefi::proto::network::http::{HttpConfig, HttpMethod};
+//! use uefi::proto::network::rest::{RestEx, RestRequest};
+//! use uefi::cstr16;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let handle: Handle = get_fake_val();
+//!
+//! let mut rest = ServiceChild::<RestEx>::new(handle)?;
+//! rest.configure(&HttpConfig::default(), 5000)?;
+//! let request = RestRequest::new(HttpMethod::GET, cstr16!("/redfish/v1/Systems"));
+//! let response = rest.send_receive(&request)?;
+//! log::info!("{:?}: {} bytes", response.status(), response.body().len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Http`]: super::http::Http
+
+use super::http::{HttpConfig, HttpHeader, HttpHeaders, HttpMethod, HttpStatusCode};
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild};
+use crate::proto::unsafe_protocol;
+use crate::runtime::Time;
+use crate::{boot, CStr16, Guid, Result, Status, StatusExt};
+use core::ptr::{self, NonNull};
+use core::slice;
+use uefi_raw::protocol::network::http::{HttpMessage, HttpRequestData, HttpRequestOrResponse};
+use uefi_raw::protocol::network::rest::{RestExHttpConfigData, RestExProtocol, RestProtocol};
+
+pub use uefi_raw::protocol::network::rest::{
+    RestExConfigType, RestExServiceAccessMode, RestExServiceType,
+};
+
+/// Service binding protocol for creating [`RestEx`] instances.
+pub type RestExServiceBinding = ServiceBinding<RestEx>;
+
+/// A request sent through [`RestEx::send_receive`] or
+/// [`Rest::send_receive`].
+#[derive(Clone, Copy, Debug)]
+pub struct RestRequest<'a> {
+    /// HTTP method of the request.
+    pub method: HttpMethod,
+
+    /// URL of the request. For in-band Redfish services, this is usually a
+    /// path relative to the service root.
+    pub url: &'a CStr16,
+
+    /// Headers sent with the request.
+    pub headers: &'a [HttpHeader<'a>],
+
+    /// Body of the request; empty for methods without a body.
+    pub body: &'a [u8],
+}
+
+impl<'a> RestRequest<'a> {
+    /// Create a request without headers or body.
+    #[must_use]
+    pub const fn new(method: HttpMethod, url: &'a CStr16) -> Self {
+        Self {
+            method,
+            url,
+            headers: &[],
+            body: &[],
+        }
+    }
+}
+
+/// The response to a [`RestRequest`].
+///
+/// The headers and body are allocated by the implementation and freed on
+/// drop.
+#[derive(Debug)]
+pub struct RestResponse {
+    status: HttpStatusCode,
+    headers: HttpHeaders,
+    body: Option<NonNull<u8>>,
+    body_len: usize,
+}
+
+impl RestResponse {
+    /// Get the status code of the response.
+    #[must_use]
+    pub const fn status(&self) -> HttpStatusCode {
+        self.status
+    }
+
+    /// Get the headers of the response.
+    #[must_use]
+    pub const fn headers(&self) -> &HttpHeaders {
+        &self.headers
+    }
+
+    /// Get the body of the response.
+    #[must_use]
+    pub const fn body(&self) -> &[u8] {
+        match self.body {
+            Some(body) => unsafe { slice::from_raw_parts(body.as_ptr(), self.body_len) },
+            None => &[],
+        }
+    }
+}
+
+impl Drop for RestResponse {
+    fn drop(&mut self) {
+        if let Some(body) = self.body {
+            let _ = unsafe { boot::free_pool(body) };
+        }
+    }
+}
+
+/// Send `request` with `send_receive`, and take ownership of the response
+/// allocated by the implementation.
+fn send_receive(
+    request: &RestRequest<'_>,
+    send_receive: impl FnOnce(*mut HttpMessage, *mut HttpMessage) -> Status,
+) -> Result<RestResponse> {
+    let request_data = HttpRequestData {
+        method: request.method,
+        url: request.url.as_ptr().cast(),
+    };
+    let mut request_message = HttpMessage {
+        data: HttpRequestOrResponse {
+            request: &request_data,
+        },
+        header_count: request.headers.len(),
+        header: request.headers.as_ptr().cast_mut().cast(),
+        body_length: request.body.len(),
+        body: request.body.as_ptr().cast_mut().cast(),
+    };
+    let mut response_message = HttpMessage {
+        data: HttpRequestOrResponse {
+            response: ptr::null(),
+        },
+        header_count: 0,
+        header: ptr::null_mut(),
+        body_length: 0,
+        body: ptr::null_mut(),
+    };
+    let status = send_receive(&mut request_message, &mut response_message);
+
+    // The response data, headers and body are separate pool allocations,
+    // which may be returned even if the request failed.
+    let mut status_code = HttpStatusCode::STATUS_UNSUPPORTED;
+    let response_data = unsafe { response_message.data.response };
+    if let Some(response_data) = NonNull::new(response_data.cast_mut()) {
+        status_code = unsafe { response_data.as_ref() }.status_code;
+        let _ = unsafe { boot::free_pool(response_data.cast::<u8>()) };
+    }
+    let response = RestResponse {
+        status: status_code,
+        headers: unsafe {
+            HttpHeaders::from_raw(response_message.header, response_message.header_count)
+        },
+        body: NonNull::new(response_message.body.cast()),
+        body_len: response_message.body_length,
+    };
+
+    status.to_result()?;
+    Ok(response)
+}
+
+/// Information about the REST service of a [`RestEx`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RestExServiceInfo {
+    /// Major version of the service information structure.
+    pub major_version: u8,
+
+    /// Minor version of the service information structure.
+    pub minor_version: u8,
+
+    /// Type of the REST service.
+    pub service_type: RestExServiceType,
+
+    /// Whether the service is reached through the platform (in-band) or
+    /// through a network (out-of-band).
+    pub access_mode: RestExServiceAccessMode,
+
+    /// Vendor-specific name of the service, for
+    /// [`RestExServiceType::VENDOR_SPECIFIC`] services.
+    pub vendor_service_name: Guid,
+
+    /// Type of the configuration data of the instance.
+    pub config_type: RestExConfigType,
+}
+
+/// REST EX protocol.
+///
+/// Corresponds to the `EFI_REST_EX_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(RestExProtocol::GUID)]
+pub struct RestEx(RestExProtocol);
+
+unsafe impl ServiceBindingChild for RestEx {
+    const SERVICE_BINDING_GUID: crate::Guid = RestExProtocol::SERVICE_BINDING_GUID;
+}
+
+impl RestEx {
+    /// Get information about the REST service.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: the information is not available.
+    pub fn service_info(&mut self) -> Result<RestExServiceInfo> {
+        let mut info = ptr::null_mut();
+        unsafe { (self.0.get_service)(&mut self.0, &mut info) }.to_result()?;
+        let info = NonNull::new(info).ok_or(Status::UNSUPPORTED)?;
+
+        // Version 1.0 is the only version defined so far, and later versions
+        // are expected to extend it.
+        let raw = unsafe { info.as_ref() };
+        let header = unsafe { raw.header };
+        let v1 = unsafe { raw.v1 };
+        let _ = unsafe { boot::free_pool(info.cast::<u8>()) };
+        Ok(RestExServiceInfo {
+            major_version: header.rest_service_info_ver.major,
+            minor_version: header.rest_service_info_ver.minor,
+            service_type: v1.rest_ex_service_type,
+            access_mode: v1.rest_service_access_mode,
+            vendor_service_name: v1.vendor_rest_service_name,
+            config_type: v1.rest_ex_config_type,
+        })
+    }
+
+    /// Configure an instance with [`RestExConfigType::HTTP`].
+    ///
+    /// `send_receive_timeout_ms` is the timeout of [`send_receive`] in
+    /// milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::UNSUPPORTED`]: the instance does not use HTTP
+    ///   configuration data.
+    ///
+    /// [`send_receive`]: Self::send_receive
+    pub fn configure(&mut self, config: &HttpConfig, send_receive_timeout_ms: u32) -> Result {
+        config.with_raw(|http_config_data| {
+            let config_data = RestExHttpConfigData {
+                http_config_data,
+                send_receive_timeout: send_receive_timeout_ms,
+            };
+            unsafe { (self.0.configure)(&mut self.0, ptr::from_ref(&config_data).cast()) }
+        })
+    }
+
+    /// Reset the instance to the unconfigured state.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Send `request` and wait for the response.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::TIMEOUT`]: no response was received in time.
+    /// * [`Status::DEVICE_ERROR`]: a network or service error occurred.
+    pub fn send_receive(&mut self, request: &RestRequest<'_>) -> Result<RestResponse> {
+        send_receive(request, |request, response| unsafe {
+            (self.0.send_receive)(&mut self.0, request, response)
+        })
+    }
+}
+
+/// REST protocol.
+///
+/// This is the predecessor of [`RestEx`]. It is installed directly on a
+/// handle and does not need to be configured.
+///
+/// Corresponds to the `EFI_REST_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(RestProtocol::GUID)]
+pub struct Rest(RestProtocol);
+
+impl Rest {
+    /// Send `request` and wait for the response.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: a network or service error occurred.
+    pub fn send_receive(&mut self, request: &RestRequest<'_>) -> Result<RestResponse> {
+        send_receive(request, |request, response| unsafe {
+            (self.0.send_receive)(&mut self.0, request, response)
+        })
+    }
+
+    /// Get the current time of the REST service.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::DEVICE_ERROR`]: the time could not be read.
+    pub fn service_time(&mut self) -> Result<Time> {
+        let mut time = Time::invalid();
+        let time_ptr: *mut Time = &mut time;
+        unsafe { (self.0.get_service_time)(&mut self.0, time_ptr.cast()) }
+            .to_result_with_val(|| time)
+    }
+}
@@ -0,0 +1,243 @@
+//! [`smoltcp`] device adapter for the Simple Network Protocol.
+//!
+//! [`SnpDevice`] implements [`smoltcp::phy::Device`] on top of a
+//! [`SimpleNetwork`] interface, so that a complete TCP/IP stack can be run in
+//! the pre-boot environment even if the firmware does not provide working
+//! TCP or HTTP protocols:
+//!
+//! ```no_run
+//! use smoltcp::iface::{Config, Interface};
+//! use smoltcp::time::Instant;
+//! use uefi::boot::ScopedProtocol;
+//! use uefi::proto::network::smoltcp::SnpDevice;
+//! use uefi::proto::network::snp::SimpleNetwork;
+//! # fn test(snp: ScopedProtocol<SimpleNetwork>) -> uefi::Result {
+//! let mut device = SnpDevice::new(&snp)?;
+//! let config = Config::new(device.hardware_address());
+//! let mut iface = Interface::new(config, &mut device, Instant::ZERO);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The interface should be opened exclusively, so that the firmware's own
+//! network stack does not compete for received frames.
+//!
+//! This module is only available with the `smoltcp` feature.
+
+use super::snp::{NetworkState, ReceiveFlags, Receiver, SimpleNetwork};
+use crate::boot::{self, MemoryType};
+use crate::Result;
+use core::ptr::NonNull;
+use core::slice;
+use smoltcp::phy::{self, Device, DeviceCapabilities, Medium};
+use smoltcp::time::Instant;
+use smoltcp::wire::{EthernetAddress, HardwareAddress};
+
+/// Number of frames that can be queued for transmission at the same time.
+const TX_BUFFER_COUNT: usize = 8;
+
+/// Alignment of each transmit buffer. Some network drivers use the buffers
+/// for DMA directly and are faster, or only work, with aligned buffers.
+const TX_BUFFER_ALIGN: usize = 64;
+
+/// A [`smoltcp::phy::Device`] sending and receiving Ethernet frames through
+/// a [`SimpleNetwork`] interface.
+///
+/// Transmitted frames are copied into buffers owned by the device, which
+/// stay valid until the network driver has finished sending them.
+#[derive(Debug)]
+pub struct SnpDevice<'a> {
+    rx: Receiver<'a>,
+    tx: TxBuffers<'a>,
+}
+
+impl<'a> SnpDevice<'a> {
+    /// Create a device for `snp`. The interface is started and initialized
+    /// if necessary, and reception of unicast and broadcast frames is
+    /// enabled.
+    pub fn new(snp: &'a SimpleNetwork) -> Result<Self> {
+        if snp.mode().state == NetworkState::STOPPED {
+            snp.start()?;
+        }
+        if snp.mode().state == NetworkState::STARTED {
+            snp.initialize(0, 0)?;
+        }
+        snp.receive_filters(
+            ReceiveFlags::UNICAST | ReceiveFlags::BROADCAST,
+            ReceiveFlags::empty(),
+            false,
+            None,
+        )?;
+
+        let rx = snp.receiver()?;
+        let tx = TxBuffers::new(snp)?;
+        Ok(Self { rx, tx })
+    }
+
+    /// Get the hardware address of the interface, for use in the
+    /// [`smoltcp::iface::Config`] of the interface.
+    #[must_use]
+    pub fn hardware_address(&self) -> HardwareAddress {
+        let mac = self.network().mode().current_address;
+        HardwareAddress::Ethernet(EthernetAddress::from_bytes(&mac.0[..6]))
+    }
+
+    /// Get the underlying network interface.
+    #[must_use]
+    pub const fn network(&self) -> &'a SimpleNetwork {
+        self.tx.snp
+    }
+}
+
+impl Device for SnpDevice<'_> {
+    type RxToken<'b>
+        = RxToken<'b>
+    where
+        Self: 'b;
+    type TxToken<'b>
+        = TxToken<'b>
+    where
+        Self: 'b;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(RxToken<'_>, TxToken<'_>)> {
+        // Only receive if a reply could be sent.
+        let slot = self.tx.reserve()?;
+        let frame = match self.rx.try_recv() {
+            Ok(frame) => frame?,
+            Err(err) => {
+                log::debug!("failed to receive frame: {err:?}");
+                return None;
+            }
+        };
+        Some((RxToken { frame: frame.data }, self.tx.token(slot)))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<TxToken<'_>> {
+        let slot = self.tx.reserve()?;
+        Some(self.tx.token(slot))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.medium = Medium::Ethernet;
+        caps.max_transmission_unit = self.tx.frame_size;
+        caps.max_burst_size = Some(TX_BUFFER_COUNT);
+        caps
+    }
+}
+
+/// Token for a frame received by an [`SnpDevice`].
+#[derive(Debug)]
+pub struct RxToken<'a> {
+    frame: &'a [u8],
+}
+
+impl phy::RxToken for RxToken<'_> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        f(self.frame)
+    }
+}
+
+/// Token for a frame transmitted by an [`SnpDevice`].
+#[derive(Debug)]
+pub struct TxToken<'a> {
+    snp: &'a SimpleNetwork,
+    buffer: &'a mut [u8],
+    in_flight: &'a mut bool,
+}
+
+impl phy::TxToken for TxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let len = len.min(self.buffer.len());
+        let buffer = &mut self.buffer[..len];
+        let result = f(buffer);
+        // The frame includes the media header, which the driver must not
+        // fill in.
+        match self.snp.transmit(0, buffer, None, None, None) {
+            Ok(()) => *self.in_flight = true,
+            Err(err) => log::debug!("failed to transmit frame: {err:?}"),
+        }
+        result
+    }
+}
+
+/// Transmit buffers of an [`SnpDevice`].
+///
+/// The network driver owns a buffer from the call to
+/// [`SimpleNetwork::transmit`] until it is returned by
+/// [`SimpleNetwork::get_recycled_transmit_buffer_status`].
+#[derive(Debug)]
+struct TxBuffers<'a> {
+    snp: &'a SimpleNetwork,
+    allocation: NonNull<u8>,
+    base: NonNull<u8>,
+    stride: usize,
+    frame_size: usize,
+    in_flight: [bool; TX_BUFFER_COUNT],
+}
+
+impl<'a> TxBuffers<'a> {
+    fn new(snp: &'a SimpleNetwork) -> Result<Self> {
+        let mode = snp.mode();
+        let frame_size = (mode.media_header_size + mode.max_packet_size) as usize;
+        let stride = frame_size.next_multiple_of(TX_BUFFER_ALIGN);
+        let allocation = boot::allocate_pool(
+            MemoryType::LOADER_DATA,
+            stride * TX_BUFFER_COUNT + TX_BUFFER_ALIGN,
+        )?;
+        let offset = allocation.as_ptr().align_offset(TX_BUFFER_ALIGN);
+        let base = unsafe { NonNull::new_unchecked(allocation.as_ptr().add(offset)) };
+        Ok(Self {
+            snp,
+            allocation,
+            base,
+            stride,
+            frame_size,
+            in_flight: [false; TX_BUFFER_COUNT],
+        })
+    }
+
+    /// Mark the buffers that the driver has finished sending as free.
+    fn reclaim(&mut self) {
+        while let Ok(Some(buffer)) = self.snp.get_recycled_transmit_buffer_status() {
+            let offset = (buffer.as_ptr() as usize).wrapping_sub(self.base.as_ptr() as usize);
+            // Ignore buffers transmitted by other users of the interface.
+            if offset % self.stride == 0 && offset / self.stride < TX_BUFFER_COUNT {
+                self.in_flight[offset / self.stride] = false;
+            }
+        }
+    }
+
+    /// Get the index of a free buffer. Buffers are reclaimed first, since
+    /// some drivers stop transmitting if recycled buffers are not collected.
+    fn reserve(&mut self) -> Option<usize> {
+        self.reclaim();
+        self.in_flight.iter().position(|&busy| !busy)
+    }
+
+    /// Create a token for transmitting a frame from buffer `slot`.
+    fn token(&mut self, slot: usize) -> TxToken<'_> {
+        let buffer = unsafe { self.base.as_ptr().add(slot * self.stride) };
+        TxToken {
+            snp: self.snp,
+            buffer: unsafe { slice::from_raw_parts_mut(buffer, self.frame_size) },
+            in_flight: &mut self.in_flight[slot],
+        }
+    }
+}
+
+impl Drop for TxBuffers<'_> {
+    fn drop(&mut self) {
+        self.reclaim();
+        // Leak the buffers if the driver still uses some of them.
+        if !self.in_flight.contains(&true) {
+            let _ = unsafe { boot::free_pool(self.allocation) };
+        }
+    }
+}
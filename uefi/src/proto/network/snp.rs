@@ -8,13 +8,16 @@
 //! called first.
 
 use super::{IpAddress, MacAddress};
+use crate::boot::{self, MemoryType};
 use crate::data_types::Event;
+use crate::proto::completion::Timer;
 use crate::proto::unsafe_protocol;
 use crate::{Result, Status, StatusExt};
 use bitflags::bitflags;
 use core::ffi::c_void;
 use core::ptr;
 use core::ptr::NonNull;
+use core::time::Duration;
 
 /// The Simple Network Protocol
 #[derive(Debug)]
@@ -145,6 +148,68 @@ impl SimpleNetwork {
         }
     }
 
+    /// Get the receive filters supported by the network interface.
+    #[must_use]
+    pub const fn supported_receive_filters(&self) -> ReceiveFlags {
+        ReceiveFlags::from_bits_truncate(self.mode().receive_filter_mask)
+    }
+
+    /// Get the receive filters currently enabled on the network interface.
+    #[must_use]
+    pub const fn current_receive_filters(&self) -> ReceiveFlags {
+        ReceiveFlags::from_bits_truncate(self.mode().receive_filter_setting)
+    }
+
+    /// Enable exactly the receive filters in `filters`, disabling all other
+    /// supported filters. The multicast address list is left unchanged.
+    ///
+    /// Enabling [`ReceiveFlags::MULTICAST`] requires a multicast address
+    /// list; use [`set_multicast_filter`] for that instead.
+    ///
+    /// [`set_multicast_filter`]: Self::set_multicast_filter
+    pub fn set_receive_filters(&self, filters: ReceiveFlags) -> Result {
+        let disable = self.supported_receive_filters().difference(filters);
+        self.receive_filters(filters, disable, false, None)
+    }
+
+    /// Replace the list of multicast addresses received by the network
+    /// interface with `addresses`, and enable [`ReceiveFlags::MULTICAST`].
+    /// If `addresses` is empty, the list is cleared and multicast reception
+    /// is disabled.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: more addresses were given than the
+    ///   interface supports, or an address is not a multicast address.
+    pub fn set_multicast_filter(&self, addresses: &[MacAddress]) -> Result {
+        if addresses.is_empty() {
+            return self.receive_filters(
+                ReceiveFlags::empty(),
+                ReceiveFlags::MULTICAST,
+                true,
+                None,
+            );
+        }
+        if addresses.len() > self.mode().max_mcast_filter_count as usize {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.receive_filters(
+            ReceiveFlags::MULTICAST,
+            ReceiveFlags::empty(),
+            false,
+            Some(addresses),
+        )
+    }
+
+    /// Get the multicast addresses currently received by the network
+    /// interface.
+    #[must_use]
+    pub fn multicast_filter(&self) -> &[MacAddress] {
+        let mode = self.mode();
+        let len = (mode.mcast_filter_count as usize).min(mode.mcast_filter.len());
+        &mode.mcast_filter[..len]
+    }
+
     /// Modify or reset the current station address, if supported.
     pub fn station_address(&self, reset: bool, new: Option<&MacAddress>) -> Result {
         (self.station_address)(self, reset, new).to_result()
@@ -272,6 +337,156 @@ impl SimpleNetwork {
     }
 }
 
+/// Receives frames from a [`SimpleNetwork`] into an internal buffer large
+/// enough for any frame supported by the network interface.
+///
+/// The buffer is allocated from pool memory, which is suitably aligned for
+/// network drivers that require it. Received frames borrow the buffer, so
+/// they must be dropped before the next frame is received:
+///
+/// ```no_run
+/// use uefi::proto::network::snp::SimpleNetwork;
+/// # fn test(snp: &SimpleNetwork) -> uefi::Result {
+/// let mut receiver = snp.receiver()?;
+/// while let Some(frame) = receiver.try_recv()? {
+///     log::info!("received {} bytes of protocol {:#x}", frame.data.len(), frame.protocol);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Receiver<'a> {
+    snp: &'a SimpleNetwork,
+    buffer: NonNull<u8>,
+    size: usize,
+}
+
+/// A frame received by a [`Receiver`].
+#[derive(Debug)]
+pub struct ReceivedFrame<'a> {
+    /// The whole frame, including the media header.
+    pub data: &'a [u8],
+    /// Size of the media header at the start of [`data`](Self::data).
+    pub header_size: usize,
+    /// Source hardware address.
+    pub source: MacAddress,
+    /// Destination hardware address.
+    pub destination: MacAddress,
+    /// Protocol type (EtherType) of the frame.
+    pub protocol: u16,
+}
+
+impl ReceivedFrame<'_> {
+    /// Get the frame without its media header.
+    #[must_use]
+    pub fn payload(&self) -> &[u8] {
+        self.data.get(self.header_size..).unwrap_or_default()
+    }
+}
+
+/// Metadata of a frame in the buffer of a [`Receiver`].
+struct FrameInfo {
+    len: usize,
+    header_size: usize,
+    source: MacAddress,
+    destination: MacAddress,
+    protocol: u16,
+}
+
+impl SimpleNetwork {
+    /// Create a [`Receiver`] for this network interface. The interface must
+    /// have been initialized.
+    pub fn receiver(&self) -> Result<Receiver<'_>> {
+        let mode = self.mode();
+        let size = (mode.media_header_size + mode.max_packet_size) as usize;
+        let buffer = boot::allocate_pool(MemoryType::LOADER_DATA, size)?;
+        Ok(Receiver {
+            snp: self,
+            buffer,
+            size,
+        })
+    }
+}
+
+impl<'a> Receiver<'a> {
+    /// Receive a frame if one is available, without waiting.
+    pub fn try_recv(&mut self) -> Result<Option<ReceivedFrame<'_>>> {
+        Ok(self.receive_frame()?.map(|info| self.frame(info)))
+    }
+
+    /// Wait for a frame, polling the network interface.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::TIMEOUT`]: `timeout` elapsed before a frame arrived.
+    pub fn recv(&mut self, timeout: Option<Duration>) -> Result<ReceivedFrame<'_>> {
+        let timer = match timeout {
+            Some(timeout) => Some(Timer::new(timeout)?),
+            None => None,
+        };
+        loop {
+            if let Some(info) = self.receive_frame()? {
+                return Ok(self.frame(info));
+            }
+            if let Some(timer) = &timer {
+                if timer.is_expired()? {
+                    return Err(Status::TIMEOUT.into());
+                }
+            }
+        }
+    }
+
+    /// Get the network interface of the receiver.
+    #[must_use]
+    pub const fn network(&self) -> &'a SimpleNetwork {
+        self.snp
+    }
+
+    /// Receive a frame into the buffer. Returns `None` if no frame is
+    /// available.
+    fn receive_frame(&mut self) -> Result<Option<FrameInfo>> {
+        let buffer = unsafe { core::slice::from_raw_parts_mut(self.buffer.as_ptr(), self.size) };
+        let mut header_size = 0;
+        let mut source = MacAddress([0; 32]);
+        let mut destination = MacAddress([0; 32]);
+        let mut protocol = 0;
+        match self.snp.receive(
+            buffer,
+            Some(&mut header_size),
+            Some(&mut source),
+            Some(&mut destination),
+            Some(&mut protocol),
+        ) {
+            Ok(len) => Ok(Some(FrameInfo {
+                len,
+                header_size,
+                source,
+                destination,
+                protocol,
+            })),
+            Err(err) if err.status() == Status::NOT_READY => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn frame(&self, info: FrameInfo) -> ReceivedFrame<'_> {
+        let len = info.len.min(self.size);
+        ReceivedFrame {
+            data: unsafe { core::slice::from_raw_parts(self.buffer.as_ptr(), len) },
+            header_size: info.header_size,
+            source: info.source,
+            destination: info.destination,
+            protocol: info.protocol,
+        }
+    }
+}
+
+impl Drop for Receiver<'_> {
+    fn drop(&mut self) {
+        let _ = unsafe { boot::free_pool(self.buffer) };
+    }
+}
+
 bitflags! {
     /// Flags to pass to receive_filters to enable/disable reception of some kinds of packets.
     #[repr(transparent)]
@@ -0,0 +1,347 @@
+//! Supplicant protocol.
+//!
+//! [`Supplicant`] instances perform WPA/WPA2 authentication for a wireless
+//! network interface. They are created through the
+//! [`SupplicantServiceBinding`] protocol on the interface's handle. The
+//! parameters of the network must be set before the interface associates
+//! with it, for example to join a WPA2-Personal network:
+//!
+//! ```no_run
+//! use uefi::cstr8;
+//! use uefi::proto::driver::ServiceChild;
+//! use uefi::proto::network::supplicant::{Supplicant, AKM_SUITE_PSK, CIPHER_SUITE_CCMP};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut supplicant = ServiceChild::<Supplicant>::new(nic_handle)?;
+//! supplicant.set_target_ssid(b"example-network")?;
+//! supplicant.set_akm_suite(AKM_SUITE_PSK)?;
+//! supplicant.set_pairwise_cipher_suite(CIPHER_SUITE_CCMP)?;
+//! supplicant.set_group_cipher_suite(CIPHER_SUITE_CCMP)?;
+//! supplicant.set_psk_password(cstr8!("correct horse battery staple"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Networks using 802.1X authentication (WPA2-Enterprise) are configured
+//! with the [`EapConfiguration`] protocol instead of a password.
+//!
+//! [`EapConfiguration`]: super::eap_configuration::EapConfiguration
+
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild};
+use crate::proto::unsafe_protocol;
+use crate::{CStr8, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use uefi_raw::protocol::network::supplicant::{Ieee80211Ssid, SupplicantProtocol};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+pub use uefi_raw::protocol::network::supplicant::{
+    EapolSupplicantPaeState, Ieee80211LinkState, Ieee80211SuiteSelector, SupplicantDataType,
+};
+
+/// Service binding protocol for creating [`Supplicant`] instances.
+pub type SupplicantServiceBinding = ServiceBinding<Supplicant>;
+
+/// OUI of the suites defined by IEEE 802.11.
+const IEEE_80211_OUI: [u8; 3] = [0x00, 0x0f, 0xac];
+
+/// AKM suite for 802.1X authentication (WPA2-Enterprise).
+pub const AKM_SUITE_8021X: Ieee80211SuiteSelector = Ieee80211SuiteSelector {
+    oui: IEEE_80211_OUI,
+    suite_type: 1,
+};
+
+/// AKM suite for pre-shared key authentication (WPA2-Personal).
+pub const AKM_SUITE_PSK: Ieee80211SuiteSelector = Ieee80211SuiteSelector {
+    oui: IEEE_80211_OUI,
+    suite_type: 2,
+};
+
+/// AKM suite for SAE authentication (WPA3-Personal).
+pub const AKM_SUITE_SAE: Ieee80211SuiteSelector = Ieee80211SuiteSelector {
+    oui: IEEE_80211_OUI,
+    suite_type: 8,
+};
+
+/// TKIP cipher suite (WPA).
+pub const CIPHER_SUITE_TKIP: Ieee80211SuiteSelector = Ieee80211SuiteSelector {
+    oui: IEEE_80211_OUI,
+    suite_type: 2,
+};
+
+/// CCMP-128 cipher suite (WPA2).
+pub const CIPHER_SUITE_CCMP: Ieee80211SuiteSelector = Ieee80211SuiteSelector {
+    oui: IEEE_80211_OUI,
+    suite_type: 4,
+};
+
+/// Maximum length of an SSID in bytes.
+pub const MAX_SSID_LEN: usize = 32;
+
+/// Supplicant protocol.
+///
+/// Corresponds to the `EFI_SUPPLICANT_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(SupplicantProtocol::GUID)]
+pub struct Supplicant(SupplicantProtocol);
+
+unsafe impl ServiceBindingChild for Supplicant {
+    const SERVICE_BINDING_GUID: crate::Guid = SupplicantProtocol::SERVICE_BINDING_GUID;
+}
+
+impl Supplicant {
+    /// Set data of type `data_type` from the raw bytes `data`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must contain a valid value of the type expected for
+    /// `data_type`, as described in the UEFI Specification.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `data` is invalid.
+    /// * [`Status::UNSUPPORTED`]: `data_type` is not supported, or is
+    ///   read-only.
+    /// * [`Status::OUT_OF_RESOURCES`]: the data could not be stored.
+    pub unsafe fn set_data(&mut self, data_type: SupplicantDataType, data: &[u8]) -> Result {
+        unsafe {
+            (self.0.set_data)(
+                &mut self.0,
+                data_type,
+                data.as_ptr().cast::<c_void>(),
+                data.len(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Get data of type `data_type` into `buffer`. Returns the size of the
+    /// data.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::UNSUPPORTED`]: `data_type` is not supported.
+    /// * [`Status::NOT_FOUND`]: no data of this type has been set.
+    pub fn get_data(
+        &mut self,
+        data_type: SupplicantDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        let status =
+            unsafe { (self.0.get_data)(&mut self.0, data_type, buffer.as_mut_ptr(), &mut size) };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Get data of type `data_type` into a new buffer.
+    #[cfg(feature = "alloc")]
+    fn get_data_vec(&mut self, data_type: SupplicantDataType) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        loop {
+            match self.get_data(data_type, &mut buffer) {
+                Ok(size) => {
+                    buffer.truncate(size);
+                    return Ok(buffer);
+                }
+                Err(err) => match err.data() {
+                    Some(size) => buffer.resize(*size, 0),
+                    None => return Err(err.to_err_without_payload()),
+                },
+            }
+        }
+    }
+
+    /// Set a plain-old-data value.
+    fn set_value<T: ?Sized>(&mut self, data_type: SupplicantDataType, value: &T) -> Result {
+        // Safety: callers pass the type matching `data_type`.
+        let data = unsafe {
+            core::slice::from_raw_parts(ptr::from_ref(value).cast::<u8>(), mem::size_of_val(value))
+        };
+        unsafe { self.set_data(data_type, data) }
+    }
+
+    /// Get a plain-old-data value.
+    ///
+    /// # Safety
+    ///
+    /// `T` must match `data_type`, and all bit patterns must be valid for
+    /// `T`.
+    unsafe fn get_value<T: Copy>(&mut self, data_type: SupplicantDataType) -> Result<T> {
+        let mut value = MaybeUninit::<T>::zeroed();
+        let mut size = mem::size_of::<T>();
+        unsafe { (self.0.get_data)(&mut self.0, data_type, value.as_mut_ptr().cast(), &mut size) }
+            .to_result_with_val(|| unsafe { value.assume_init() })
+    }
+
+    /// Set the authentication and key management suite, such as
+    /// [`AKM_SUITE_PSK`].
+    pub fn set_akm_suite(&mut self, suite: Ieee80211SuiteSelector) -> Result {
+        self.set_value(SupplicantDataType::AKM_SUITE, &suite)
+    }
+
+    /// Set the cipher suite for unicast traffic, such as
+    /// [`CIPHER_SUITE_CCMP`].
+    pub fn set_pairwise_cipher_suite(&mut self, suite: Ieee80211SuiteSelector) -> Result {
+        self.set_value(SupplicantDataType::PAIRWISE_CIPHER_SUITE, &suite)
+    }
+
+    /// Set the cipher suite for multicast and broadcast traffic, such as
+    /// [`CIPHER_SUITE_CCMP`].
+    pub fn set_group_cipher_suite(&mut self, suite: Ieee80211SuiteSelector) -> Result {
+        self.set_value(SupplicantDataType::GROUP_DATA_CIPHER_SUITE, &suite)
+    }
+
+    /// Set the passphrase of a network using [`AKM_SUITE_PSK`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `password` is not between 8 and 63
+    ///   characters long.
+    pub fn set_psk_password(&mut self, password: &CStr8) -> Result {
+        let bytes = password.as_bytes();
+        if !(8..=63).contains(&(bytes.len() - 1)) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        unsafe { self.set_data(SupplicantDataType::PSK_PASSWORD, bytes) }
+    }
+
+    /// Set the SSID of the network to join.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `ssid` is longer than
+    ///   [`MAX_SSID_LEN`].
+    pub fn set_target_ssid(&mut self, ssid: &[u8]) -> Result {
+        let mut value = Ieee80211Ssid {
+            ssid_len: u8::try_from(ssid.len()).map_err(|_| Status::INVALID_PARAMETER)?,
+            ssid: [0; MAX_SSID_LEN],
+        };
+        value
+            .ssid
+            .get_mut(..ssid.len())
+            .ok_or(Status::INVALID_PARAMETER)?
+            .copy_from_slice(ssid);
+        self.set_value(SupplicantDataType::TARGET_SSID_NAME, &value)
+    }
+
+    /// Set the MAC address of the access point to join.
+    pub fn set_target_mac(&mut self, mac: [u8; 6]) -> Result {
+        self.set_value(SupplicantDataType::TARGET_SSID_MAC, &mac)
+    }
+
+    /// Set the MAC address of the wireless interface.
+    pub fn set_station_mac(&mut self, mac: [u8; 6]) -> Result {
+        self.set_value(SupplicantDataType::STATION_MAC, &mac)
+    }
+
+    /// Set the pairwise master key directly, instead of deriving it from a
+    /// passphrase.
+    pub fn set_pmk(&mut self, pmk: &[u8; 32]) -> Result {
+        self.set_value(SupplicantDataType::PMK, pmk)
+    }
+
+    /// Get the state of the 802.1X authentication.
+    pub fn state(&mut self) -> Result<EapolSupplicantPaeState> {
+        unsafe { self.get_value(SupplicantDataType::STATE) }
+    }
+
+    /// Get the state of the 802.11 link.
+    pub fn link_state(&mut self) -> Result<Ieee80211LinkState> {
+        unsafe { self.get_value(SupplicantDataType::LINK_STATE) }
+    }
+
+    /// Get the AKM suites supported by the supplicant.
+    #[cfg(feature = "alloc")]
+    pub fn supported_akm_suites(&mut self) -> Result<Vec<Ieee80211SuiteSelector>> {
+        let buffer = self.get_data_vec(SupplicantDataType::SUPPORTED_AKM_SUITES)?;
+        parse_suite_list(&buffer).ok_or_else(|| Status::BAD_BUFFER_SIZE.into())
+    }
+
+    /// Get the cipher suites the supplicant implements in software.
+    #[cfg(feature = "alloc")]
+    pub fn supported_software_cipher_suites(&mut self) -> Result<Vec<Ieee80211SuiteSelector>> {
+        let buffer = self.get_data_vec(SupplicantDataType::SUPPORTED_SOFTWARE_CIPHER_SUITES)?;
+        parse_suite_list(&buffer).ok_or_else(|| Status::BAD_BUFFER_SIZE.into())
+    }
+
+    /// Process an EAPOL frame received from the access point, and write the
+    /// frame to send in response into `buffer`. Returns the size of the
+    /// response, which is zero if no response is needed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::INVALID_PARAMETER`]: the supplicant has not been
+    ///   configured.
+    /// * [`Status::UNSUPPORTED`]: the frame is not supported.
+    pub fn build_response_packet(
+        &mut self,
+        request: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        let status = unsafe {
+            (self.0.build_response_packet)(
+                &mut self.0,
+                request.as_ptr(),
+                request.len(),
+                buffer.as_mut_ptr(),
+                &mut size,
+            )
+        };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+}
+
+/// Parse an `EFI_80211_AKM_SUITE_SELECTOR` or
+/// `EFI_80211_CIPHER_SUITE_SELECTOR`: a `u16` count followed by the suites.
+#[cfg(feature = "alloc")]
+fn parse_suite_list(buffer: &[u8]) -> Option<Vec<Ieee80211SuiteSelector>> {
+    let count = buffer.get(..2)?;
+    let count = usize::from(u16::from_ne_bytes([count[0], count[1]]));
+    let suites = buffer.get(2..2 + count * 4)?;
+    Some(
+        suites
+            .chunks_exact(4)
+            .map(|suite| Ieee80211SuiteSelector {
+                oui: [suite[0], suite[1], suite[2]],
+                suite_type: suite[3],
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_parse_suite_list() {
+        let mut buffer = Vec::from(2u16.to_ne_bytes());
+        buffer.extend([0x00, 0x0f, 0xac, 2, 0x00, 0x0f, 0xac, 8]);
+        assert_eq!(
+            parse_suite_list(&buffer),
+            Some(alloc::vec![AKM_SUITE_PSK, AKM_SUITE_SAE])
+        );
+        assert_eq!(parse_suite_list(&0u16.to_ne_bytes()), Some(Vec::new()));
+        assert_eq!(parse_suite_list(&buffer[..9]), None);
+        assert_eq!(parse_suite_list(&[1]), None);
+    }
+}
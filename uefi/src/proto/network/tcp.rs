@@ -0,0 +1,116 @@
+//! IP-version agnostic TCP connections.
+//!
+//! [`TcpConnection`] is implemented by both [`Tcp4Connection`] and
+//! [`Tcp6Connection`], so code that only sends and receives data can be
+//! written once for both IP versions. [`AnyTcpConnection::connect`] picks the
+//! right protocol based on the remote address:
+//!
+//! ```no_run
+//! use core::net::SocketAddr;
+//! use uefi::proto::network::tcp::{AnyTcpConnection, TcpConnection};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//! # let remote: SocketAddr = get_fake_val();
+//!
+//! fn request(conn: &mut impl TcpConnection) -> uefi::Result<usize> {
+//!     conn.send(b"ping")?;
+//!     let mut buf = [0; 4];
+//!     conn.recv(&mut buf, None)
+//! }
+//!
+//! let mut conn = AnyTcpConnection::connect(nic_handle, remote)?;
+//! request(&mut conn)?;
+//! conn.close()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::tcp4::{Tcp4Config, Tcp4Connection};
+use super::tcp6::{Tcp6Config, Tcp6Connection};
+use crate::{Handle, Result};
+use core::net::SocketAddr;
+use core::time::Duration;
+
+/// An established TCP connection.
+pub trait TcpConnection {
+    /// Send all of `data` to the remote host.
+    fn send(&mut self, data: &[u8]) -> Result;
+
+    /// Receive data into `buffer`, waiting until at least one byte is
+    /// available. Returns the number of bytes received, or zero if the remote
+    /// host closed the connection.
+    ///
+    /// If `timeout` is `Some`, the wait is bounded and a
+    /// [`Status::TIMEOUT`] error is returned if no data arrives in time.
+    ///
+    /// [`Status::TIMEOUT`]: crate::Status::TIMEOUT
+    fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize>;
+
+    /// Get the address of the remote host.
+    fn peer_addr(&self) -> SocketAddr;
+
+    /// Gracefully close the connection.
+    fn close(self) -> Result
+    where
+        Self: Sized;
+}
+
+/// A TCP connection over either IPv4 or IPv6.
+#[derive(Debug)]
+pub enum AnyTcpConnection {
+    /// A connection using the TCPv4 protocol.
+    V4(Tcp4Connection),
+    /// A connection using the TCPv6 protocol.
+    V6(Tcp6Connection),
+}
+
+impl AnyTcpConnection {
+    /// Connect to `remote` through the network interface `nic_handle`, using
+    /// the TCPv4 or TCPv6 protocol depending on the type of address.
+    ///
+    /// The default configuration ([`Tcp4Config::new`] or
+    /// [`Tcp6Config::new`]) is used. Use [`Tcp4Connection::connect`] or
+    /// [`Tcp6Connection::connect`] directly for more control.
+    pub fn connect(nic_handle: Handle, remote: SocketAddr) -> Result<Self> {
+        match remote {
+            SocketAddr::V4(remote) => {
+                Tcp4Connection::connect(nic_handle, &Tcp4Config::new(remote)).map(Self::V4)
+            }
+            SocketAddr::V6(remote) => {
+                Tcp6Connection::connect(nic_handle, &Tcp6Config::new(remote)).map(Self::V6)
+            }
+        }
+    }
+}
+
+impl TcpConnection for AnyTcpConnection {
+    fn send(&mut self, data: &[u8]) -> Result {
+        match self {
+            Self::V4(conn) => conn.send(data),
+            Self::V6(conn) => conn.send(data),
+        }
+    }
+
+    fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        match self {
+            Self::V4(conn) => conn.recv(buffer, timeout),
+            Self::V6(conn) => conn.recv(buffer, timeout),
+        }
+    }
+
+    fn peer_addr(&self) -> SocketAddr {
+        match self {
+            Self::V4(conn) => SocketAddr::V4(conn.peer_addr()),
+            Self::V6(conn) => SocketAddr::V6(conn.peer_addr()),
+        }
+    }
+
+    fn close(self) -> Result {
+        match self {
+            Self::V4(conn) => conn.close(),
+            Self::V6(conn) => conn.close(),
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! TCPv4 protocol.
+//!
+//! TCPv4 instances are created through the [`Tcp4ServiceBinding`]
+//! protocol installed on a network interface handle. The [`Tcp4Connection`]
+//! type takes care of creating the child instance, configuring it, and
+//! connecting to a remote host:
+//!
+//! ```no_run
+//! use core::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+//! use uefi::proto::network::tcp4::{Tcp4Config, Tcp4Connection};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let remote = SocketAddrV4::new(Ipv4Addr::new(192, 168, 0, 1), 80);
+//! let mut conn = Tcp4Connection::connect(nic_handle, &Tcp4Config::new(remote))?;
+//! conn.send(b"GET / HTTP/1.0\r\n\r\n")?;
+//!
+//! let mut buf = [0; 512];
+//! let len = conn.recv(&mut buf, None)?;
+//! conn.close()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::network::tcp::TcpConnection;
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, Status, StatusExt};
+use core::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::network::tcp4::{
+    Tcp4AccessPoint, Tcp4CloseToken, Tcp4CompletionToken, Tcp4ConfigData, Tcp4ConnectionToken,
+    Tcp4FragmentData, Tcp4IoToken, Tcp4Packet, Tcp4Protocol, Tcp4ReceiveData, Tcp4TransmitData,
+};
+use uefi_raw::Ipv4Address;
+
+pub use uefi_raw::protocol::network::tcp4::{Tcp4ConnectionState, Tcp4Option};
+
+/// Service binding protocol for creating [`Tcp4`] instances.
+pub type Tcp4ServiceBinding = ServiceBinding<Tcp4>;
+
+/// TCPv4 protocol.
+///
+/// The methods of this type that wait for a completion token (such as
+/// [`connect`] and [`receive`]) block until the operation finishes, calling
+/// [`poll`] in between checks of the token's event.
+///
+/// Corresponds to the `EFI_TCP4_PROTOCOL` in the C API.
+///
+/// [`connect`]: Self::connect
+/// [`receive`]: Self::receive
+/// [`poll`]: Self::poll
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Tcp4Protocol::GUID)]
+pub struct Tcp4(Tcp4Protocol);
+
+unsafe impl ServiceBindingChild for Tcp4 {
+    const SERVICE_BINDING_GUID: crate::Guid = Tcp4Protocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of a [`Tcp4`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Tcp4Config {
+    /// Local address of the instance. If `None`, the default address of the
+    /// interface is used, as configured by DHCP or the IPv4 configuration
+    /// protocol.
+    pub station_address: Option<Ipv4Addr>,
+
+    /// Subnet mask of the local address. Ignored if `station_address` is
+    /// `None`.
+    pub subnet_mask: Ipv4Addr,
+
+    /// Local port. If zero, an ephemeral port is chosen by the
+    /// implementation.
+    pub station_port: u16,
+
+    /// Address and port of the remote host.
+    pub remote: SocketAddrV4,
+
+    /// Type of service field in transmitted IPv4 packets.
+    pub type_of_service: u8,
+
+    /// Time-to-live field in transmitted IPv4 packets.
+    pub time_to_live: u8,
+
+    /// Advanced TCP options. If `None`, the implementation's defaults are
+    /// used.
+    pub options: Option<Tcp4Option>,
+}
+
+impl Tcp4Config {
+    /// Create a configuration for an active connection to `remote`, using the
+    /// interface's default address and an ephemeral local port.
+    #[must_use]
+    pub const fn new(remote: SocketAddrV4) -> Self {
+        Self {
+            station_address: None,
+            subnet_mask: Ipv4Addr::UNSPECIFIED,
+            station_port: 0,
+            remote,
+            type_of_service: 0,
+            time_to_live: 64,
+            options: None,
+        }
+    }
+}
+
+impl Tcp4 {
+    /// Get the current state of the TCP connection.
+    pub fn connection_state(&self) -> Result<Tcp4ConnectionState> {
+        let mut state = Tcp4ConnectionState::CLOSED;
+        unsafe {
+            (self.0.get_mode_data)(
+                &self.0,
+                &mut state,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| state)
+    }
+
+    /// Configure the instance for an active connection.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NO_MAPPING`]: the default address is used but address
+    ///   configuration (e.g. DHCP) has not finished yet.
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::ACCESS_DENIED`]: the instance is already configured.
+    pub fn configure(&mut self, config: &Tcp4Config) -> Result {
+        let mut options = config.options;
+        let config_data = Tcp4ConfigData {
+            type_of_service: config.type_of_service,
+            time_to_live: config.time_to_live,
+            access_point: Tcp4AccessPoint {
+                use_default_address: config.station_address.is_none(),
+                station_address: Ipv4Address(
+                    config
+                        .station_address
+                        .unwrap_or(Ipv4Addr::UNSPECIFIED)
+                        .octets(),
+                ),
+                subnet_mask: config.subnet_mask.into(),
+                station_port: config.station_port,
+                remote_address: (*config.remote.ip()).into(),
+                remote_port: config.remote.port(),
+                active_flag: true,
+            },
+            control_option: options
+                .as_mut()
+                .map(ptr::from_mut)
+                .unwrap_or(ptr::null_mut()),
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. Any active connection
+    /// is aborted.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Poll for incoming data packets and process outgoing data packets.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: no incoming or outgoing data was processed.
+    /// * [`Status::TIMEOUT`]: data was dropped out of the queues.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Initiate the three-way handshake with the configured remote host and
+    /// wait for it to finish.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::CONNECTION_REFUSED`]: the remote host refused the
+    ///   connection.
+    /// * [`Status::CONNECTION_RESET`]: the connection was reset by the remote
+    ///   host.
+    /// * [`Status::TIMEOUT`]: the connection timed out.
+    pub fn connect(&mut self) -> Result {
+        let event = CompletionEvent::new()?;
+        let mut token = Tcp4ConnectionToken {
+            completion_token: Tcp4CompletionToken {
+                event: event.as_raw(),
+                status: Status::SUCCESS,
+            },
+        };
+        unsafe { (self.0.connect)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token.completion_token, None)
+    }
+
+    /// Queue `data` for transmission and wait until it has been accepted by
+    /// the implementation. The data is transmitted with the `PUSH` flag set.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::CONNECTION_FIN`]: the connection is being closed.
+    /// * [`Status::CONNECTION_RESET`]: the connection was reset by the remote
+    ///   host.
+    pub fn transmit(&mut self, data: &[u8]) -> Result {
+        // The fragment and total lengths are `u32`, so split very large
+        // buffers into several transmissions.
+        for chunk in data.chunks(u32::MAX as usize) {
+            let event = CompletionEvent::new()?;
+            let len = chunk.len() as u32;
+            let mut tx_data = Tcp4TransmitData {
+                push: true,
+                urgent: false,
+                data_length: len,
+                fragment_count: 1,
+                fragment_table: [Tcp4FragmentData {
+                    fragment_length: len,
+                    fragment_buffer: chunk.as_ptr().cast_mut().cast(),
+                }],
+            };
+            let mut token = Tcp4IoToken {
+                completion_token: Tcp4CompletionToken {
+                    event: event.as_raw(),
+                    status: Status::SUCCESS,
+                },
+                packet: Tcp4Packet {
+                    tx_data: &mut tx_data,
+                },
+            };
+            unsafe { (self.0.transmit)(&mut self.0, &mut token) }.to_result()?;
+            self.wait_for_token(&event, &mut token.completion_token, None)?;
+        }
+        Ok(())
+    }
+
+    /// Receive data into `buffer`, waiting until at least one byte is
+    /// available. Returns the number of bytes received.
+    ///
+    /// If the remote host has closed the connection, `Ok(0)` is returned.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::CONNECTION_RESET`]: the connection was reset by the remote
+    ///   host.
+    /// * [`Status::TIMEOUT`]: `timeout` elapsed before any data arrived.
+    pub fn receive(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        let event = CompletionEvent::new()?;
+        let len = u32::try_from(buffer.len()).unwrap_or(u32::MAX);
+        let mut rx_data = Tcp4ReceiveData {
+            urgent_flag: false,
+            data_length: len,
+            fragment_count: 1,
+            fragment_table: [Tcp4FragmentData {
+                fragment_length: len,
+                fragment_buffer: buffer.as_mut_ptr().cast(),
+            }],
+        };
+        let mut token = Tcp4IoToken {
+            completion_token: Tcp4CompletionToken {
+                event: event.as_raw(),
+                status: Status::SUCCESS,
+            },
+            packet: Tcp4Packet {
+                rx_data: &mut rx_data,
+            },
+        };
+        let status = unsafe { (self.0.receive)(&mut self.0, &mut token) };
+        if status == Status::CONNECTION_FIN {
+            return Ok(0);
+        }
+        status.to_result()?;
+        match self.wait_for_token(&event, &mut token.completion_token, timeout) {
+            Ok(()) => Ok(rx_data.data_length as usize),
+            Err(err) if err.status() == Status::CONNECTION_FIN => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Gracefully close the connection (or abort it, if `abort` is true) and
+    /// wait for it to finish.
+    pub fn close(&mut self, abort: bool) -> Result {
+        let event = CompletionEvent::new()?;
+        let mut token = Tcp4CloseToken {
+            completion_token: Tcp4CompletionToken {
+                event: event.as_raw(),
+                status: Status::SUCCESS,
+            },
+            abort_on_close: abort,
+        };
+        unsafe { (self.0.close)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token.completion_token, None)
+    }
+
+    /// Wait for an outstanding `token` to complete. If `timeout` elapses
+    /// first, the token is cancelled before returning.
+    fn wait_for_token(
+        &mut self,
+        event: &CompletionEvent,
+        token: &mut Tcp4CompletionToken,
+        timeout: Option<Duration>,
+    ) -> Result {
+        let this = ptr::from_mut(&mut self.0);
+        let (poll, cancel) = (self.0.poll, self.0.cancel);
+        event.wait_or_cancel(
+            timeout,
+            // Errors from `poll` just mean no progress was made.
+            || {
+                let _ = unsafe { poll(this) };
+            },
+            || unsafe { cancel(this, token) },
+        )?;
+        token.status.to_result()
+    }
+}
+
+/// A TCPv4 connection, including the [`Tcp4`] child instance it uses.
+///
+/// The connection is aborted and the child instance destroyed when the
+/// `Tcp4Connection` is dropped. Use [`close`] to close the connection
+/// gracefully.
+///
+/// [`close`]: Self::close
+#[derive(Debug)]
+pub struct Tcp4Connection {
+    tcp: ServiceChild<Tcp4>,
+    remote: SocketAddrV4,
+}
+
+impl Tcp4Connection {
+    /// Create a [`Tcp4`] instance on the network interface `nic_handle`,
+    /// configure it with `config`, and connect to the remote host.
+    pub fn connect(nic_handle: Handle, config: &Tcp4Config) -> Result<Self> {
+        let mut tcp = ServiceChild::<Tcp4>::new(nic_handle)?;
+        tcp.configure(config)?;
+        tcp.connect()?;
+        Ok(Self {
+            tcp,
+            remote: config.remote,
+        })
+    }
+
+    /// Get the address of the remote host.
+    #[must_use]
+    pub const fn peer_addr(&self) -> SocketAddrV4 {
+        self.remote
+    }
+
+    /// Send all of `data` to the remote host.
+    pub fn send(&mut self, data: &[u8]) -> Result {
+        self.tcp.transmit(data)
+    }
+
+    /// Receive data into `buffer`. Returns the number of bytes received, or
+    /// zero if the remote host closed the connection.
+    ///
+    /// If `timeout` is `Some`, the wait is bounded and a [`Status::TIMEOUT`]
+    /// error is returned if no data arrives in time.
+    pub fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        self.tcp.receive(buffer, timeout)
+    }
+
+    /// Get the current state of the connection.
+    pub fn state(&self) -> Result<Tcp4ConnectionState> {
+        self.tcp.connection_state()
+    }
+
+    /// Gracefully close the connection.
+    pub fn close(mut self) -> Result {
+        self.tcp.close(false)
+    }
+
+    /// Get the underlying [`Tcp4`] protocol.
+    pub fn protocol(&mut self) -> &mut Tcp4 {
+        &mut self.tcp
+    }
+}
+
+impl TcpConnection for Tcp4Connection {
+    fn send(&mut self, data: &[u8]) -> Result {
+        self.send(data)
+    }
+
+    fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        self.recv(buffer, timeout)
+    }
+
+    fn peer_addr(&self) -> SocketAddr {
+        SocketAddr::V4(self.remote)
+    }
+
+    fn close(self) -> Result {
+        self.close()
+    }
+}
+
+impl Drop for Tcp4Connection {
+    fn drop(&mut self) {
+        // Abort the connection if it is still open. This is a no-op if the
+        // instance has already been reset.
+        let _ = self.tcp.reset();
+    }
+}
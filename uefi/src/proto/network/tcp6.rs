@@ -0,0 +1,397 @@
+//! TCPv6 protocol.
+//!
+//! TCPv6 instances are created through the [`Tcp6ServiceBinding`]
+//! protocol installed on a network interface handle. The [`Tcp6Connection`]
+//! type takes care of creating the child instance, configuring it, and
+//! connecting to a remote host:
+//!
+//! ```no_run
+//! use core::net::{Ipv6Addr, SocketAddr, SocketAddrV6};
+//! use uefi::proto::network::tcp6::{Tcp6Config, Tcp6Connection};
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let remote = SocketAddrV6::new(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1), 80, 0, 0);
+//! let mut conn = Tcp6Connection::connect(nic_handle, &Tcp6Config::new(remote))?;
+//! conn.send(b"GET / HTTP/1.0\r\n\r\n")?;
+//!
+//! let mut buf = [0; 512];
+//! let len = conn.recv(&mut buf, None)?;
+//! conn.close()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::network::tcp::TcpConnection;
+use crate::proto::unsafe_protocol;
+use crate::{Handle, Result, Status, StatusExt};
+use core::net::{Ipv6Addr, SocketAddr, SocketAddrV6};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::network::tcp6::{
+    Tcp6AccessPoint, Tcp6CloseToken, Tcp6CompletionToken, Tcp6ConfigData, Tcp6ConnectionToken,
+    Tcp6FragmentData, Tcp6IoToken, Tcp6Packet, Tcp6Protocol, Tcp6ReceiveData, Tcp6TransmitData,
+};
+
+pub use uefi_raw::protocol::network::tcp6::{Tcp6ConnectionState, Tcp6Option};
+
+/// Service binding protocol for creating [`Tcp6`] instances.
+pub type Tcp6ServiceBinding = ServiceBinding<Tcp6>;
+
+/// TCPv6 protocol.
+///
+/// The methods of this type that wait for a completion token (such as
+/// [`connect`] and [`receive`]) block until the operation finishes, calling
+/// [`poll`] in between checks of the token's event.
+///
+/// Corresponds to the `EFI_TCP6_PROTOCOL` in the C API.
+///
+/// [`connect`]: Self::connect
+/// [`receive`]: Self::receive
+/// [`poll`]: Self::poll
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Tcp6Protocol::GUID)]
+pub struct Tcp6(Tcp6Protocol);
+
+unsafe impl ServiceBindingChild for Tcp6 {
+    const SERVICE_BINDING_GUID: crate::Guid = Tcp6Protocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of a [`Tcp6`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Tcp6Config {
+    /// Local address of the instance. If this is the unspecified address, the
+    /// implementation selects a source address based on the remote address.
+    pub station_address: Ipv6Addr,
+
+    /// Local port. If zero, an ephemeral port is chosen by the
+    /// implementation.
+    pub station_port: u16,
+
+    /// Address and port of the remote host. The flow info and scope ID of
+    /// the socket address are ignored.
+    pub remote: SocketAddrV6,
+
+    /// Traffic class field in transmitted IPv6 packets.
+    pub traffic_class: u8,
+
+    /// Hop limit field in transmitted IPv6 packets.
+    pub hop_limit: u8,
+
+    /// Advanced TCP options. If `None`, the implementation's defaults are
+    /// used.
+    pub options: Option<Tcp6Option>,
+}
+
+impl Tcp6Config {
+    /// Create a configuration for an active connection to `remote`, using an
+    /// implementation-selected local address and an ephemeral local port.
+    #[must_use]
+    pub const fn new(remote: SocketAddrV6) -> Self {
+        Self {
+            station_address: Ipv6Addr::UNSPECIFIED,
+            station_port: 0,
+            remote,
+            traffic_class: 0,
+            hop_limit: 64,
+            options: None,
+        }
+    }
+}
+
+impl Tcp6 {
+    /// Get the current state of the TCP connection.
+    pub fn connection_state(&self) -> Result<Tcp6ConnectionState> {
+        let mut state = Tcp6ConnectionState::CLOSED;
+        unsafe {
+            (self.0.get_mode_data)(
+                &self.0,
+                &mut state,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result_with_val(|| state)
+    }
+
+    /// Configure the instance for an active connection.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NO_MAPPING`]: address configuration has not finished yet.
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::ACCESS_DENIED`]: the instance is already configured.
+    pub fn configure(&mut self, config: &Tcp6Config) -> Result {
+        let mut options = config.options;
+        let config_data = Tcp6ConfigData {
+            traffic_class: config.traffic_class,
+            hop_limit: config.hop_limit,
+            access_point: Tcp6AccessPoint {
+                station_address: config.station_address.into(),
+                station_port: config.station_port,
+                remote_address: (*config.remote.ip()).into(),
+                remote_port: config.remote.port(),
+                active_flag: true,
+            },
+            control_option: options
+                .as_mut()
+                .map(ptr::from_mut)
+                .unwrap_or(ptr::null_mut()),
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. Any active connection
+    /// is aborted.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Poll for incoming data packets and process outgoing data packets.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: no incoming or outgoing data was processed.
+    /// * [`Status::TIMEOUT`]: data was dropped out of the queues.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Initiate the three-way handshake with the configured remote host and
+    /// wait for it to finish.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::CONNECTION_REFUSED`]: the remote host refused the
+    ///   connection.
+    /// * [`Status::CONNECTION_RESET`]: the connection was reset by the remote
+    ///   host.
+    /// * [`Status::TIMEOUT`]: the connection timed out.
+    pub fn connect(&mut self) -> Result {
+        let event = CompletionEvent::new()?;
+        let mut token = Tcp6ConnectionToken {
+            completion_token: Tcp6CompletionToken {
+                event: event.as_raw(),
+                status: Status::SUCCESS,
+            },
+        };
+        unsafe { (self.0.connect)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token.completion_token, None)
+    }
+
+    /// Queue `data` for transmission and wait until it has been accepted by
+    /// the implementation. The data is transmitted with the `PUSH` flag set.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::CONNECTION_FIN`]: the connection is being closed.
+    /// * [`Status::CONNECTION_RESET`]: the connection was reset by the remote
+    ///   host.
+    pub fn transmit(&mut self, data: &[u8]) -> Result {
+        // The fragment and total lengths are `u32`, so split very large
+        // buffers into several transmissions.
+        for chunk in data.chunks(u32::MAX as usize) {
+            let event = CompletionEvent::new()?;
+            let len = chunk.len() as u32;
+            let mut tx_data = Tcp6TransmitData {
+                push: true,
+                urgent: false,
+                data_length: len,
+                fragment_count: 1,
+                fragment_table: [Tcp6FragmentData {
+                    fragment_length: len,
+                    fragment_buffer: chunk.as_ptr().cast_mut().cast(),
+                }],
+            };
+            let mut token = Tcp6IoToken {
+                completion_token: Tcp6CompletionToken {
+                    event: event.as_raw(),
+                    status: Status::SUCCESS,
+                },
+                packet: Tcp6Packet {
+                    tx_data: &mut tx_data,
+                },
+            };
+            unsafe { (self.0.transmit)(&mut self.0, &mut token) }.to_result()?;
+            self.wait_for_token(&event, &mut token.completion_token, None)?;
+        }
+        Ok(())
+    }
+
+    /// Receive data into `buffer`, waiting until at least one byte is
+    /// available. Returns the number of bytes received.
+    ///
+    /// If the remote host has closed the connection, `Ok(0)` is returned.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::CONNECTION_RESET`]: the connection was reset by the remote
+    ///   host.
+    /// * [`Status::TIMEOUT`]: `timeout` elapsed before any data arrived.
+    pub fn receive(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        let event = CompletionEvent::new()?;
+        let len = u32::try_from(buffer.len()).unwrap_or(u32::MAX);
+        let mut rx_data = Tcp6ReceiveData {
+            urgent_flag: false,
+            data_length: len,
+            fragment_count: 1,
+            fragment_table: [Tcp6FragmentData {
+                fragment_length: len,
+                fragment_buffer: buffer.as_mut_ptr().cast(),
+            }],
+        };
+        let mut token = Tcp6IoToken {
+            completion_token: Tcp6CompletionToken {
+                event: event.as_raw(),
+                status: Status::SUCCESS,
+            },
+            packet: Tcp6Packet {
+                rx_data: &mut rx_data,
+            },
+        };
+        let status = unsafe { (self.0.receive)(&mut self.0, &mut token) };
+        if status == Status::CONNECTION_FIN {
+            return Ok(0);
+        }
+        status.to_result()?;
+        match self.wait_for_token(&event, &mut token.completion_token, timeout) {
+            Ok(()) => Ok(rx_data.data_length as usize),
+            Err(err) if err.status() == Status::CONNECTION_FIN => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Gracefully close the connection (or abort it, if `abort` is true) and
+    /// wait for it to finish.
+    pub fn close(&mut self, abort: bool) -> Result {
+        let event = CompletionEvent::new()?;
+        let mut token = Tcp6CloseToken {
+            completion_token: Tcp6CompletionToken {
+                event: event.as_raw(),
+                status: Status::SUCCESS,
+            },
+            abort_on_close: abort,
+        };
+        unsafe { (self.0.close)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token.completion_token, None)
+    }
+
+    /// Wait for an outstanding `token` to complete. If `timeout` elapses
+    /// first, the token is cancelled before returning.
+    fn wait_for_token(
+        &mut self,
+        event: &CompletionEvent,
+        token: &mut Tcp6CompletionToken,
+        timeout: Option<Duration>,
+    ) -> Result {
+        let this = ptr::from_mut(&mut self.0);
+        let (poll, cancel) = (self.0.poll, self.0.cancel);
+        event.wait_or_cancel(
+            timeout,
+            // Errors from `poll` just mean no progress was made.
+            || {
+                let _ = unsafe { poll(this) };
+            },
+            || unsafe { cancel(this, token) },
+        )?;
+        token.status.to_result()
+    }
+}
+
+/// A TCPv6 connection, including the [`Tcp6`] child instance it uses.
+///
+/// The connection is aborted and the child instance destroyed when the
+/// `Tcp6Connection` is dropped. Use [`close`] to close the connection
+/// gracefully.
+///
+/// [`close`]: Self::close
+#[derive(Debug)]
+pub struct Tcp6Connection {
+    tcp: ServiceChild<Tcp6>,
+    remote: SocketAddrV6,
+}
+
+impl Tcp6Connection {
+    /// Create a [`Tcp6`] instance on the network interface `nic_handle`,
+    /// configure it with `config`, and connect to the remote host.
+    pub fn connect(nic_handle: Handle, config: &Tcp6Config) -> Result<Self> {
+        let mut tcp = ServiceChild::<Tcp6>::new(nic_handle)?;
+        tcp.configure(config)?;
+        tcp.connect()?;
+        Ok(Self {
+            tcp,
+            remote: config.remote,
+        })
+    }
+
+    /// Get the address of the remote host.
+    #[must_use]
+    pub const fn peer_addr(&self) -> SocketAddrV6 {
+        self.remote
+    }
+
+    /// Send all of `data` to the remote host.
+    pub fn send(&mut self, data: &[u8]) -> Result {
+        self.tcp.transmit(data)
+    }
+
+    /// Receive data into `buffer`. Returns the number of bytes received, or
+    /// zero if the remote host closed the connection.
+    ///
+    /// If `timeout` is `Some`, the wait is bounded and a [`Status::TIMEOUT`]
+    /// error is returned if no data arrives in time.
+    pub fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        self.tcp.receive(buffer, timeout)
+    }
+
+    /// Get the current state of the connection.
+    pub fn state(&self) -> Result<Tcp6ConnectionState> {
+        self.tcp.connection_state()
+    }
+
+    /// Gracefully close the connection.
+    pub fn close(mut self) -> Result {
+        self.tcp.close(false)
+    }
+
+    /// Get the underlying [`Tcp6`] protocol.
+    pub fn protocol(&mut self) -> &mut Tcp6 {
+        &mut self.tcp
+    }
+}
+
+impl TcpConnection for Tcp6Connection {
+    fn send(&mut self, data: &[u8]) -> Result {
+        self.send(data)
+    }
+
+    fn recv(&mut self, buffer: &mut [u8], timeout: Option<Duration>) -> Result<usize> {
+        self.recv(buffer, timeout)
+    }
+
+    fn peer_addr(&self) -> SocketAddr {
+        SocketAddr::V6(self.remote)
+    }
+
+    fn close(self) -> Result {
+        self.close()
+    }
+}
+
+impl Drop for Tcp6Connection {
+    fn drop(&mut self) {
+        // Abort the connection if it is still open. This is a no-op if the
+        // instance has already been reset.
+        let _ = self.tcp.reset();
+    }
+}
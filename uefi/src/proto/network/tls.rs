@@ -0,0 +1,452 @@
+//! TLS protocols.
+//!
+//! TLS instances are created through the [`TlsServiceBinding`] protocol.
+//! Each child handle carries both the [`Tls`] protocol, which runs a TLS
+//! session, and the [`TlsConfiguration`] protocol, which holds the
+//! certificates used by that session. [`TlsSession`] creates a child and
+//! opens both protocols.
+//!
+//! The TLS protocol does not do any network I/O itself: records produced by
+//! [`Tls::build_response_packet`] and [`Tls::process_packet`] must be sent
+//! to the peer by the caller, for example over a [`Tcp4Connection`].
+//!
+//! The HTTP driver uses its own TLS instances for HTTPS connections. Their
+//! CA certificates are taken from the `TlsCaCertificate` variable, which can
+//! be set with [`set_http_ca_certificates`].
+//!
+//! [`Tcp4Connection`]: super::tcp4::Tcp4Connection
+
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::runtime::{self, VariableAttributes, VariableVendor};
+use crate::{boot, cstr16, CStr8, Handle, Result, Status, StatusExt};
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Formatter};
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use core::slice;
+use uefi_raw::protocol::network::tls::{
+    TlsConfigurationProtocol, TlsFragmentData, TlsProtocol, TlsVerifyHost, TLS_CA_CERTIFICATE_GUID,
+};
+
+pub use uefi_raw::protocol::network::tls::{
+    TlsCipher, TlsConfigDataType, TlsConnectionEnd, TlsCryptMode, TlsSessionDataType,
+    TlsSessionState, TlsVerify, TlsVerifyHostFlags, TlsVersion,
+};
+
+/// Service binding protocol for creating [`Tls`] instances.
+pub type TlsServiceBinding = ServiceBinding<Tls>;
+
+/// TLS protocol.
+///
+/// Corresponds to the `EFI_TLS_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(TlsProtocol::GUID)]
+pub struct Tls(TlsProtocol);
+
+unsafe impl ServiceBindingChild for Tls {
+    const SERVICE_BINDING_GUID: crate::Guid = TlsProtocol::SERVICE_BINDING_GUID;
+}
+
+impl Tls {
+    /// Set session data of type `data_type` from the raw bytes `data`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must contain a valid value of the type expected for
+    /// `data_type`, as described in the UEFI Specification.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `data` is invalid.
+    /// * [`Status::UNSUPPORTED`]: `data_type` is not supported.
+    /// * [`Status::ACCESS_DENIED`]: `data_type` can't be set in the current
+    ///   session state.
+    pub unsafe fn set_session_data(
+        &mut self,
+        data_type: TlsSessionDataType,
+        data: &[u8],
+    ) -> Result {
+        unsafe {
+            (self.0.set_session_data)(&mut self.0, data_type, data.as_ptr().cast(), data.len())
+        }
+        .to_result()
+    }
+
+    /// Get session data of type `data_type` into `buffer`. Returns the size
+    /// of the data.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::UNSUPPORTED`]: `data_type` is not supported.
+    /// * [`Status::NOT_FOUND`]: the data is not available in the current
+    ///   session state.
+    pub fn get_session_data(
+        &mut self,
+        data_type: TlsSessionDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        let status = unsafe {
+            (self.0.get_session_data)(
+                &mut self.0,
+                data_type,
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Set a plain-old-data session value.
+    fn set_value<T>(&mut self, data_type: TlsSessionDataType, value: &T) -> Result {
+        // Safety: callers pass the type matching `data_type`.
+        let data = unsafe {
+            slice::from_raw_parts(ptr::from_ref(value).cast::<u8>(), mem::size_of::<T>())
+        };
+        unsafe { self.set_session_data(data_type, data) }
+    }
+
+    /// Set the TLS version of the session.
+    pub fn set_version(&mut self, version: TlsVersion) -> Result {
+        self.set_value(TlsSessionDataType::VERSION, &version)
+    }
+
+    /// Set whether this end of the session is the client or the server.
+    pub fn set_connection_end(&mut self, end: TlsConnectionEnd) -> Result {
+        self.set_value(TlsSessionDataType::CONNECTION_END, &end)
+    }
+
+    /// Set the cipher suites offered in the handshake, in order of
+    /// preference.
+    pub fn set_cipher_list(&mut self, ciphers: &[TlsCipher]) -> Result {
+        // Safety: `TlsCipher` is two bytes without padding.
+        let data = unsafe {
+            slice::from_raw_parts(ciphers.as_ptr().cast::<u8>(), mem::size_of_val(ciphers))
+        };
+        unsafe { self.set_session_data(TlsSessionDataType::CIPHER_LIST, data) }
+    }
+
+    /// Set how the peer is verified.
+    pub fn set_verify_method(&mut self, verify: TlsVerify) -> Result {
+        self.set_value(TlsSessionDataType::VERIFY_METHOD, &verify)
+    }
+
+    /// Set the host name that the peer certificate is checked against.
+    pub fn set_verify_host(&mut self, flags: TlsVerifyHostFlags, host_name: &CStr8) -> Result {
+        let verify_host = TlsVerifyHost {
+            flags,
+            host_name: host_name.as_ptr().cast_mut().cast(),
+        };
+        self.set_value(TlsSessionDataType::VERIFY_HOST, &verify_host)
+    }
+
+    /// Get the current state of the session.
+    pub fn session_state(&mut self) -> Result<TlsSessionState> {
+        let mut state = TlsSessionState::NOT_STARTED;
+        let mut size = mem::size_of_val(&state);
+        unsafe {
+            (self.0.get_session_data)(
+                &mut self.0,
+                TlsSessionDataType::SESSION_STATE,
+                ptr::from_mut(&mut state).cast(),
+                &mut size,
+            )
+        }
+        .to_result_with_val(|| state)
+    }
+
+    /// Drive the handshake or shut down the connection.
+    ///
+    /// `request` is a TLS record received from the peer, or `None` to start
+    /// the handshake (on the client) or to build a close-notify alert (once
+    /// the session is closed). The record to send to the peer in response is
+    /// written to `buffer`, and its size is returned. A size of zero means no
+    /// response is needed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::INVALID_PARAMETER`]: `request` is not a valid record.
+    /// * [`Status::ABORTED`]: the handshake failed. The response (usually an
+    ///   alert) is still written to `buffer`.
+    pub fn build_response_packet(
+        &mut self,
+        request: Option<&[u8]>,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let (request_ptr, request_size) =
+            request.map_or((ptr::null(), 0), |r| (r.as_ptr(), r.len()));
+        let mut size = buffer.len();
+        let status = unsafe {
+            (self.0.build_response_packet)(
+                &mut self.0,
+                request_ptr,
+                request_size,
+                buffer.as_mut_ptr(),
+                &mut size,
+            )
+        };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Encrypt application data into TLS records, or decrypt TLS records
+    /// into application data, depending on `mode`. The session must be
+    /// connected.
+    ///
+    /// The input data is not modified. The output is allocated by the TLS
+    /// implementation and returned as [`TlsFragments`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the input is invalid or larger than
+    ///   `u32::MAX` bytes.
+    /// * [`Status::ABORTED`]: the session is not connected, or the records
+    ///   could not be decrypted.
+    pub fn process_packet(&mut self, input: &[u8], mode: TlsCryptMode) -> Result<TlsFragments> {
+        let mut fragment = TlsFragmentData {
+            fragment_length: u32::try_from(input.len()).map_err(|_| Status::INVALID_PARAMETER)?,
+            fragment_buffer: input.as_ptr().cast_mut().cast(),
+        };
+        let mut table = ptr::from_mut(&mut fragment);
+        let mut count = 1;
+        unsafe { (self.0.process_packet)(&mut self.0, &mut table, &mut count, mode) }
+            .to_result()?;
+
+        // On success, `table` points to a new table allocated by the
+        // implementation.
+        if table == ptr::from_mut(&mut fragment) {
+            return Err(Status::ABORTED.into());
+        }
+        Ok(TlsFragments {
+            table: NonNull::new(table).ok_or(Status::ABORTED)?,
+            count: count as usize,
+        })
+    }
+}
+
+/// Output of [`Tls::process_packet`].
+///
+/// The fragment table and the fragment buffers are allocated by the TLS
+/// implementation and freed on drop.
+pub struct TlsFragments {
+    table: NonNull<TlsFragmentData>,
+    count: usize,
+}
+
+impl TlsFragments {
+    const fn table(&self) -> &[TlsFragmentData] {
+        unsafe { slice::from_raw_parts(self.table.as_ptr(), self.count) }
+    }
+
+    /// Iterate over the output fragments.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.table().iter().map(|fragment| {
+            if fragment.fragment_buffer.is_null() {
+                &[][..]
+            } else {
+                unsafe {
+                    slice::from_raw_parts(
+                        fragment.fragment_buffer.cast::<u8>(),
+                        fragment.fragment_length as usize,
+                    )
+                }
+            }
+        })
+    }
+
+    /// Get the total length of the output fragments.
+    #[must_use]
+    pub fn total_len(&self) -> usize {
+        self.iter().map(<[u8]>::len).sum()
+    }
+
+    /// Copy the output fragments into `buffer`. Returns the number of bytes
+    /// copied, which is less than [`total_len`] if `buffer` is too small.
+    ///
+    /// [`total_len`]: Self::total_len
+    pub fn copy_to(&self, buffer: &mut [u8]) -> usize {
+        let mut copied = 0;
+        for fragment in self.iter() {
+            let len = fragment.len().min(buffer.len() - copied);
+            buffer[copied..copied + len].copy_from_slice(&fragment[..len]);
+            copied += len;
+        }
+        copied
+    }
+}
+
+impl Debug for TlsFragments {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsFragments")
+            .field("count", &self.count)
+            .field("total_len", &self.total_len())
+            .finish()
+    }
+}
+
+impl Drop for TlsFragments {
+    fn drop(&mut self) {
+        for fragment in self.table() {
+            if let Some(buffer) = NonNull::new(fragment.fragment_buffer) {
+                let _ = unsafe { boot::free_pool(buffer.cast()) };
+            }
+        }
+        let _ = unsafe { boot::free_pool(self.table.cast()) };
+    }
+}
+
+/// TLS configuration protocol.
+///
+/// Holds the certificates and keys used by the [`Tls`] instance on the same
+/// handle.
+///
+/// Corresponds to the `EFI_TLS_CONFIGURATION_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(TlsConfigurationProtocol::GUID)]
+pub struct TlsConfiguration(TlsConfigurationProtocol);
+
+impl TlsConfiguration {
+    /// Set configuration data of type `data_type`.
+    ///
+    /// Certificates are DER or PEM encoded X.509 certificates, depending on
+    /// the implementation; keys are PEM encoded.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `data` is invalid.
+    /// * [`Status::UNSUPPORTED`]: `data_type` is not supported.
+    /// * [`Status::OUT_OF_RESOURCES`]: out of memory.
+    pub fn set_data(&mut self, data_type: TlsConfigDataType, data: &[u8]) -> Result {
+        unsafe {
+            (self.0.set_data)(
+                &mut self.0,
+                data_type,
+                data.as_ptr().cast::<c_void>(),
+                data.len(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Get configuration data of type `data_type` into `buffer`. Returns the
+    /// size of the data.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::BUFFER_TOO_SMALL`]: `buffer` is too small. The required
+    ///   size is returned in the error data.
+    /// * [`Status::UNSUPPORTED`]: `data_type` is not supported.
+    /// * [`Status::NOT_FOUND`]: no data of this type has been set.
+    pub fn get_data(
+        &self,
+        data_type: TlsConfigDataType,
+        buffer: &mut [u8],
+    ) -> Result<usize, Option<usize>> {
+        let mut size = buffer.len();
+        let status =
+            unsafe { (self.0.get_data)(&self.0, data_type, buffer.as_mut_ptr().cast(), &mut size) };
+        status.to_result_with(
+            || size,
+            |status| (status == Status::BUFFER_TOO_SMALL).then_some(size),
+        )
+    }
+
+    /// Add a CA certificate used to verify the peer.
+    pub fn set_ca_certificate(&mut self, cert: &[u8]) -> Result {
+        self.set_data(TlsConfigDataType::CA_CERTIFICATE, cert)
+    }
+
+    /// Set the certificate presented to the peer.
+    pub fn set_host_public_cert(&mut self, cert: &[u8]) -> Result {
+        self.set_data(TlsConfigDataType::HOST_PUBLIC_CERT, cert)
+    }
+
+    /// Set the private key matching the host certificate.
+    pub fn set_host_private_key(&mut self, key: &[u8]) -> Result {
+        self.set_data(TlsConfigDataType::HOST_PRIVATE_KEY, key)
+    }
+
+    /// Set the certificate revocation list.
+    pub fn set_cert_revocation_list(&mut self, crl: &[u8]) -> Result {
+        self.set_data(TlsConfigDataType::CERT_REVOCATION_LIST, crl)
+    }
+}
+
+/// A TLS child instance with both the [`Tls`] and [`TlsConfiguration`]
+/// protocols opened.
+///
+/// `TlsSession` dereferences to [`Tls`]. The child instance is destroyed
+/// when the `TlsSession` is dropped.
+#[derive(Debug)]
+pub struct TlsSession {
+    // Declared first so that it is closed before the child is destroyed.
+    config: boot::ScopedProtocol<TlsConfiguration>,
+    tls: ServiceChild<Tls>,
+}
+
+impl TlsSession {
+    /// Create a TLS child instance through the service binding on
+    /// `controller`.
+    pub fn new(controller: Handle) -> Result<Self> {
+        let tls = ServiceChild::<Tls>::new(controller)?;
+        let config = boot::open_protocol_exclusive::<TlsConfiguration>(tls.handle())?;
+        Ok(Self { config, tls })
+    }
+
+    /// Create a TLS child instance on the first handle that supports the
+    /// TLS service binding protocol.
+    pub fn new_on_any() -> Result<Self> {
+        Self::new(boot::get_handle_for_protocol::<TlsServiceBinding>()?)
+    }
+
+    /// Get the configuration protocol of the session.
+    pub fn configuration(&mut self) -> &mut TlsConfiguration {
+        &mut self.config
+    }
+}
+
+impl Deref for TlsSession {
+    type Target = Tls;
+
+    fn deref(&self) -> &Tls {
+        &self.tls
+    }
+}
+
+impl DerefMut for TlsSession {
+    fn deref_mut(&mut self) -> &mut Tls {
+        &mut self.tls
+    }
+}
+
+/// Set the CA certificates used by the HTTP driver for HTTPS connections.
+///
+/// The certificates are stored in the volatile `TlsCaCertificate` variable,
+/// which the HTTP driver reads when it sets up a TLS session. `cert_db` must
+/// be a sequence of `EFI_SIGNATURE_LIST` structures containing X.509
+/// certificates.
+///
+/// # Errors
+///
+/// See [`runtime::set_variable`].
+pub fn set_http_ca_certificates(cert_db: &[u8]) -> Result {
+    runtime::set_variable(
+        cstr16!("TlsCaCertificate"),
+        &VariableVendor(TLS_CA_CERTIFICATE_GUID),
+        VariableAttributes::BOOTSERVICE_ACCESS,
+        cert_db,
+    )
+}
@@ -0,0 +1,454 @@
+//! UDPv4 protocol.
+//!
+//! UDPv4 instances are created through the [`Udp4ServiceBinding`]
+//! protocol installed on a network interface handle. The [`Udp4Socket`]
+//! type takes care of creating and configuring the child instance, and
+//! provides an API similar to `std::net::UdpSocket`:
+//!
+//! ```no_run
+//! use core::net::{Ipv4Addr, SocketAddrV4};
+//! use core::time::Duration;
+//! use uefi::proto::network::udp4::Udp4Socket;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut socket = Udp4Socket::bind(nic_handle, SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))?;
+//! let server = SocketAddrV4::new(Ipv4Addr::new(192, 168, 0, 1), 21572);
+//! socket.send_to(b"hello", server)?;
+//!
+//! let mut buf = [0; 512];
+//! let (len, from) = socket.recv_from(&mut buf, Some(Duration::from_secs(1)))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::boot;
+use crate::proto::completion::CompletionEvent;
+use crate::proto::driver::{ServiceBinding, ServiceBindingChild, ServiceChild};
+use crate::proto::unsafe_protocol;
+use crate::{Event, Handle, Result, Status, StatusExt};
+use core::net::{Ipv4Addr, SocketAddrV4};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::network::udp4::{
+    Udp4CompletionToken, Udp4ConfigData, Udp4FragmentData, Udp4Packet, Udp4Protocol,
+    Udp4SessionData, Udp4TransmitData,
+};
+use uefi_raw::Ipv4Address;
+
+/// Service binding protocol for creating [`Udp4`] instances.
+pub type Udp4ServiceBinding = ServiceBinding<Udp4>;
+
+/// UDPv4 protocol.
+///
+/// [`transmit`] and [`receive`] block until the operation finishes, calling
+/// [`poll`] in between checks of the token's event.
+///
+/// Corresponds to the `EFI_UDP4_PROTOCOL` in the C API.
+///
+/// [`transmit`]: Self::transmit
+/// [`receive`]: Self::receive
+/// [`poll`]: Self::poll
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(Udp4Protocol::GUID)]
+pub struct Udp4(Udp4Protocol);
+
+unsafe impl ServiceBindingChild for Udp4 {
+    const SERVICE_BINDING_GUID: crate::Guid = Udp4Protocol::SERVICE_BINDING_GUID;
+}
+
+/// Configuration of a [`Udp4`] instance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Udp4Config {
+    /// Local address of the instance. If `None`, the default address of the
+    /// interface is used, as configured by DHCP or the IPv4 configuration
+    /// protocol.
+    pub station_address: Option<Ipv4Addr>,
+
+    /// Subnet mask of the local address. Ignored if `station_address` is
+    /// `None`.
+    pub subnet_mask: Ipv4Addr,
+
+    /// Local port. If zero, an ephemeral port is chosen by the
+    /// implementation.
+    pub station_port: u16,
+
+    /// Remote address and port. If `Some`, only datagrams from this peer are
+    /// received, and it is the default destination for transmitted
+    /// datagrams.
+    pub remote: Option<SocketAddrV4>,
+
+    /// Receive broadcast datagrams.
+    pub accept_broadcast: bool,
+
+    /// Receive datagrams regardless of their destination address.
+    pub accept_promiscuous: bool,
+
+    /// Receive datagrams regardless of their destination port.
+    pub accept_any_port: bool,
+
+    /// Allow other instances to use the same local port.
+    pub allow_duplicate_port: bool,
+
+    /// Type of service field in transmitted IPv4 packets.
+    pub type_of_service: u8,
+
+    /// Time-to-live field in transmitted IPv4 packets.
+    pub time_to_live: u8,
+
+    /// Set the "don't fragment" flag in transmitted IPv4 packets.
+    pub do_not_fragment: bool,
+}
+
+impl Udp4Config {
+    /// Create a configuration bound to `local`. If the address of `local` is
+    /// unspecified, the interface's default address is used.
+    #[must_use]
+    pub const fn new(local: SocketAddrV4) -> Self {
+        let station_address = if local.ip().is_unspecified() {
+            None
+        } else {
+            Some(*local.ip())
+        };
+        Self {
+            station_address,
+            subnet_mask: Ipv4Addr::UNSPECIFIED,
+            station_port: local.port(),
+            remote: None,
+            accept_broadcast: false,
+            accept_promiscuous: false,
+            accept_any_port: false,
+            allow_duplicate_port: false,
+            type_of_service: 0,
+            time_to_live: 64,
+            do_not_fragment: false,
+        }
+    }
+}
+
+impl Udp4 {
+    /// Get the current configuration of the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    pub fn config(&self) -> Result<Udp4Config> {
+        let mut data = Udp4ConfigData::default();
+        unsafe {
+            (self.0.get_mode_data)(
+                &self.0,
+                &mut data,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .to_result()?;
+
+        let remote = Ipv4Addr::from(data.remote_address);
+        Ok(Udp4Config {
+            station_address: (!data.use_default_address)
+                .then(|| Ipv4Addr::from(data.station_address)),
+            subnet_mask: Ipv4Addr::from(data.subnet_mask),
+            station_port: data.station_port,
+            remote: (!remote.is_unspecified()).then(|| SocketAddrV4::new(remote, data.remote_port)),
+            accept_broadcast: data.accept_broadcast,
+            accept_promiscuous: data.accept_promiscuous,
+            accept_any_port: data.accept_any_port,
+            allow_duplicate_port: data.allow_duplicate_port,
+            type_of_service: data.type_of_service,
+            time_to_live: data.time_to_live,
+            do_not_fragment: data.do_not_fragment,
+        })
+    }
+
+    /// Configure the instance.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NO_MAPPING`]: the default address is used but address
+    ///   configuration (e.g. DHCP) has not finished yet.
+    /// * [`Status::INVALID_PARAMETER`]: the configuration is invalid.
+    /// * [`Status::ALREADY_STARTED`]: the instance is already configured.
+    /// * [`Status::ACCESS_DENIED`]: the local port is already in use.
+    pub fn configure(&mut self, config: &Udp4Config) -> Result {
+        let remote = config
+            .remote
+            .unwrap_or(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let config_data = Udp4ConfigData {
+            accept_broadcast: config.accept_broadcast,
+            accept_promiscuous: config.accept_promiscuous,
+            accept_any_port: config.accept_any_port,
+            allow_duplicate_port: config.allow_duplicate_port,
+            type_of_service: config.type_of_service,
+            time_to_live: config.time_to_live,
+            do_not_fragment: config.do_not_fragment,
+            // The timeouts are unsupported by the spec and must be zero.
+            receive_timeout: 0,
+            transmit_timeout: 0,
+            use_default_address: config.station_address.is_none(),
+            station_address: Ipv4Address(
+                config
+                    .station_address
+                    .unwrap_or(Ipv4Addr::UNSPECIFIED)
+                    .octets(),
+            ),
+            subnet_mask: config.subnet_mask.into(),
+            station_port: config.station_port,
+            remote_address: (*remote.ip()).into(),
+            remote_port: remote.port(),
+        };
+        unsafe { (self.0.configure)(&mut self.0, &config_data) }.to_result()
+    }
+
+    /// Reset the instance to the unconfigured state. Pending transmit and
+    /// receive operations are cancelled.
+    pub fn reset(&mut self) -> Result {
+        unsafe { (self.0.configure)(&mut self.0, ptr::null()) }.to_result()
+    }
+
+    /// Join the multicast group `group`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::INVALID_PARAMETER`]: `group` is not a multicast address.
+    /// * [`Status::ALREADY_STARTED`]: the group has already been joined.
+    pub fn join_multicast(&mut self, group: Ipv4Addr) -> Result {
+        let group = Ipv4Address::from(group);
+        unsafe { (self.0.groups)(&mut self.0, true, &group) }.to_result()
+    }
+
+    /// Leave the multicast group `group`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NOT_FOUND`]: the group has not been joined.
+    pub fn leave_multicast(&mut self, group: Ipv4Addr) -> Result {
+        let group = Ipv4Address::from(group);
+        unsafe { (self.0.groups)(&mut self.0, false, &group) }.to_result()
+    }
+
+    /// Poll for incoming data packets and process outgoing data packets.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: no incoming or outgoing data was processed.
+    /// * [`Status::TIMEOUT`]: data was dropped out of the queues.
+    pub fn poll(&mut self) -> Result {
+        unsafe { (self.0.poll)(&mut self.0) }.to_result()
+    }
+
+    /// Send `data` as a single datagram and wait until it has been
+    /// transmitted.
+    ///
+    /// If `destination` is `None`, the datagram is sent to the remote address
+    /// the instance was configured with.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NO_MAPPING`]: address configuration has not finished yet.
+    /// * [`Status::BAD_BUFFER_SIZE`]: `data` is too large for a datagram.
+    /// * [`Status::NOT_FOUND`]: no route to the destination was found.
+    pub fn transmit(&mut self, data: &[u8], destination: Option<SocketAddrV4>) -> Result {
+        let len = u32::try_from(data.len()).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+        let mut session = destination.map(|dest| Udp4SessionData {
+            source_address: Ipv4Address([0; 4]),
+            source_port: 0,
+            destination_address: (*dest.ip()).into(),
+            destination_port: dest.port(),
+        });
+
+        let event = CompletionEvent::new()?;
+        let mut tx_data = Udp4TransmitData {
+            udp_session_data: session
+                .as_mut()
+                .map(ptr::from_mut)
+                .unwrap_or(ptr::null_mut()),
+            gateway_address: ptr::null_mut(),
+            data_length: len,
+            fragment_count: 1,
+            fragment_table: [Udp4FragmentData {
+                fragment_length: len,
+                fragment_buffer: data.as_ptr().cast_mut().cast(),
+            }],
+        };
+        let mut token = Udp4CompletionToken {
+            event: event.as_raw(),
+            status: Status::SUCCESS,
+            packet: Udp4Packet {
+                tx_data: &mut tx_data,
+            },
+        };
+        unsafe { (self.0.transmit)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token, None)
+    }
+
+    /// Wait for a datagram and copy its payload into `buffer`. Returns the
+    /// number of bytes copied and the address of the sender.
+    ///
+    /// If the datagram is larger than `buffer`, the excess bytes are
+    /// discarded.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: the instance has not been configured.
+    /// * [`Status::NO_MAPPING`]: address configuration has not finished yet.
+    /// * [`Status::TIMEOUT`]: `timeout` elapsed before a datagram arrived.
+    /// * [`Status::ICMP_ERROR`]: an ICMP error was received.
+    pub fn receive(
+        &mut self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<(usize, SocketAddrV4)> {
+        let event = CompletionEvent::new()?;
+        let mut token = Udp4CompletionToken {
+            event: event.as_raw(),
+            status: Status::SUCCESS,
+            packet: Udp4Packet {
+                rx_data: ptr::null_mut(),
+            },
+        };
+        unsafe { (self.0.receive)(&mut self.0, &mut token) }.to_result()?;
+        self.wait_for_token(&event, &mut token, timeout)?;
+
+        // Safety: on success, the implementation has filled in `rx_data`,
+        // which stays valid until `recycle_signal` is signaled.
+        let rx_data = unsafe { &*token.packet.rx_data };
+        let fragments = unsafe {
+            core::slice::from_raw_parts(
+                rx_data.fragment_table.as_ptr(),
+                rx_data.fragment_count as usize,
+            )
+        };
+
+        let mut copied = 0;
+        for fragment in fragments {
+            let remaining = buffer.len() - copied;
+            if remaining == 0 {
+                break;
+            }
+            let len = remaining.min(fragment.fragment_length as usize);
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    fragment.fragment_buffer.cast::<u8>(),
+                    buffer[copied..].as_mut_ptr(),
+                    len,
+                );
+            }
+            copied += len;
+        }
+
+        let session = rx_data.udp_session;
+        let source = SocketAddrV4::new(Ipv4Addr::from(session.source_address), session.source_port);
+
+        // Give the receive buffers back to the implementation.
+        if let Some(recycle) = unsafe { Event::from_ptr(rx_data.recycle_signal) } {
+            boot::signal_event(&recycle)?;
+        }
+
+        Ok((copied, source))
+    }
+
+    /// Wait for an outstanding `token` to complete. If `timeout` elapses
+    /// first, the token is cancelled before returning.
+    fn wait_for_token(
+        &mut self,
+        event: &CompletionEvent,
+        token: &mut Udp4CompletionToken,
+        timeout: Option<Duration>,
+    ) -> Result {
+        let this = ptr::from_mut(&mut self.0);
+        let (poll, cancel) = (self.0.poll, self.0.cancel);
+        event.wait_or_cancel(
+            timeout,
+            // Errors from `poll` just mean no progress was made.
+            || {
+                let _ = unsafe { poll(this) };
+            },
+            || unsafe { cancel(this, token) },
+        )?;
+        token.status.to_result()
+    }
+}
+
+/// A UDPv4 socket, including the [`Udp4`] child instance it uses.
+///
+/// The child instance is destroyed when the `Udp4Socket` is dropped.
+#[derive(Debug)]
+pub struct Udp4Socket {
+    udp: ServiceChild<Udp4>,
+}
+
+impl Udp4Socket {
+    /// Create a [`Udp4`] instance on the network interface `nic_handle` and
+    /// bind it to `local`.
+    ///
+    /// If the address of `local` is unspecified, the interface's default
+    /// address is used. If the port is zero, an ephemeral port is chosen.
+    pub fn bind(nic_handle: Handle, local: SocketAddrV4) -> Result<Self> {
+        Self::bind_with_config(nic_handle, &Udp4Config::new(local))
+    }
+
+    /// Create a [`Udp4`] instance on the network interface `nic_handle` and
+    /// configure it with `config`.
+    pub fn bind_with_config(nic_handle: Handle, config: &Udp4Config) -> Result<Self> {
+        let mut udp = ServiceChild::<Udp4>::new(nic_handle)?;
+        udp.configure(config)?;
+        Ok(Self { udp })
+    }
+
+    /// Get the local address the socket is bound to.
+    pub fn local_addr(&self) -> Result<SocketAddrV4> {
+        let config = self.udp.config()?;
+        Ok(SocketAddrV4::new(
+            config.station_address.unwrap_or(Ipv4Addr::UNSPECIFIED),
+            config.station_port,
+        ))
+    }
+
+    /// Send `data` as a single datagram to `destination`.
+    pub fn send_to(&mut self, data: &[u8], destination: SocketAddrV4) -> Result {
+        self.udp.transmit(data, Some(destination))
+    }
+
+    /// Receive a single datagram into `buffer`. Returns the number of bytes
+    /// received and the address of the sender.
+    ///
+    /// If `timeout` is `Some`, the wait is bounded and a [`Status::TIMEOUT`]
+    /// error is returned if no datagram arrives in time.
+    pub fn recv_from(
+        &mut self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<(usize, SocketAddrV4)> {
+        self.udp.receive(buffer, timeout)
+    }
+
+    /// Join the multicast group `group`.
+    pub fn join_multicast(&mut self, group: Ipv4Addr) -> Result {
+        self.udp.join_multicast(group)
+    }
+
+    /// Leave the multicast group `group`.
+    pub fn leave_multicast(&mut self, group: Ipv4Addr) -> Result {
+        self.udp.leave_multicast(group)
+    }
+
+    /// Get the underlying [`Udp4`] protocol.
+    pub fn protocol(&mut self) -> &mut Udp4 {
+        &mut self.udp
+    }
+}
+
+impl Drop for Udp4Socket {
+    fn drop(&mut self) {
+        // Cancel any pending operations before the child is destroyed.
+        let _ = self.udp.reset();
+    }
+}
@@ -0,0 +1,168 @@
+//! VLAN configuration protocol.
+//!
+//! The [`VlanConfig`] protocol is installed on network interface handles that
+//! support IEEE 802.1Q VLAN tagging. Each configured VLAN gets its own child
+//! handle with a separate network stack, so that traffic can be tagged
+//! before a network boot continues:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::network::vlan_config::VlanConfig;
+//! # use uefi::Handle;
+//! # fn get_fake_val<T>() -> T { todo!() }
+//! # fn test() -> uefi::Result {
+//! # let nic_handle: Handle = get_fake_val();
+//!
+//! let mut vlan_config = boot::open_protocol_exclusive::<VlanConfig>(nic_handle)?;
+//! vlan_config.set(100, 0)?;
+//! for vlan in vlan_config.vlans()?.iter() {
+//!     log::info!("VLAN {} (priority {})", vlan.id, vlan.priority);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::unsafe_protocol;
+use crate::{boot, Result, Status, StatusExt};
+use core::ptr::{self, NonNull};
+use core::slice;
+use uefi_raw::protocol::network::vlan_config::{VlanConfigProtocol, VlanFindData};
+
+/// Largest valid VLAN ID.
+pub const MAX_VLAN_ID: u16 = 4094;
+
+/// Largest valid VLAN priority.
+pub const MAX_VLAN_PRIORITY: u8 = 7;
+
+/// VLAN configuration protocol.
+///
+/// Corresponds to the `EFI_VLAN_CONFIG_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(VlanConfigProtocol::GUID)]
+pub struct VlanConfig(VlanConfigProtocol);
+
+/// A VLAN configured on a network interface.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Vlan {
+    /// VLAN ID, in the range `1..=4094`.
+    pub id: u16,
+
+    /// Default priority of frames sent on the VLAN, in the range `0..=7`.
+    pub priority: u8,
+}
+
+impl From<VlanFindData> for Vlan {
+    fn from(data: VlanFindData) -> Self {
+        Self {
+            id: data.vlan_id,
+            priority: data.priority,
+        }
+    }
+}
+
+impl VlanConfig {
+    /// Add the VLAN `id` to the interface, or update its priority if it
+    /// already exists.
+    ///
+    /// Adding a VLAN creates a child handle for it with its own network
+    /// stack. VLAN ID 0 is reserved for untagged priority frames and only
+    /// changes the priority of the interface itself.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `id` is larger than [`MAX_VLAN_ID`]
+    ///   or `priority` is larger than [`MAX_VLAN_PRIORITY`].
+    /// * [`Status::OUT_OF_RESOURCES`]: the VLAN could not be created.
+    pub fn set(&mut self, id: u16, priority: u8) -> Result {
+        unsafe { (self.0.set)(&mut self.0, id, priority) }.to_result()
+    }
+
+    /// Remove the VLAN `id` from the interface, destroying its child handle.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `id` is larger than [`MAX_VLAN_ID`].
+    /// * [`Status::NOT_FOUND`]: the VLAN is not configured.
+    pub fn remove(&mut self, id: u16) -> Result {
+        unsafe { (self.0.remove)(&mut self.0, id) }.to_result()
+    }
+
+    /// Get all VLANs configured on the interface.
+    pub fn vlans(&mut self) -> Result<VlanList> {
+        self.find(ptr::null())
+    }
+
+    /// Get the VLAN `id`, or `None` if it is not configured.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `id` is larger than [`MAX_VLAN_ID`].
+    pub fn vlan(&mut self, id: u16) -> Result<Option<Vlan>> {
+        let vlans = self.find(&id)?;
+        let vlan = vlans.iter().next();
+        Ok(vlan)
+    }
+
+    fn find(&mut self, id: *const u16) -> Result<VlanList> {
+        let mut count = 0;
+        let mut entries = ptr::null_mut();
+        let status = unsafe { (self.0.find)(&mut self.0, id, &mut count, &mut entries) };
+        // No configured VLANs is not an error for the caller.
+        if status == Status::NOT_FOUND {
+            return Ok(VlanList {
+                data: None,
+                count: 0,
+            });
+        }
+        status.to_result()?;
+
+        Ok(VlanList {
+            data: NonNull::new(entries),
+            count: usize::from(count),
+        })
+    }
+}
+
+/// VLANs configured on an interface, as returned by [`VlanConfig::vlans`].
+///
+/// The list is allocated by the VLAN configuration driver and freed on drop.
+#[derive(Debug)]
+pub struct VlanList {
+    data: Option<NonNull<VlanFindData>>,
+    count: usize,
+}
+
+impl VlanList {
+    const fn as_slice(&self) -> &[VlanFindData] {
+        match self.data {
+            Some(data) => unsafe { slice::from_raw_parts(data.as_ptr(), self.count) },
+            None => &[],
+        }
+    }
+
+    /// Get the number of VLANs.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Check whether there are no VLANs.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Iterate over the VLANs.
+    pub fn iter(&self) -> impl Iterator<Item = Vlan> + '_ {
+        self.as_slice().iter().copied().map(Vlan::from)
+    }
+}
+
+impl Drop for VlanList {
+    fn drop(&mut self) {
+        if let Some(data) = self.data {
+            let _ = unsafe { boot::free_pool(data.cast()) };
+        }
+    }
+}
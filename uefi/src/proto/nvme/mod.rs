@@ -0,0 +1,561 @@
+//! NVM Express protocols.
+//!
+//! The [`NvmePassThru`] protocol submits NVMe commands to a controller and
+//! its namespaces. This module also contains parsers for the data returned by
+//! the Identify and Get Log Page commands, which helpers such as
+//! [`NvmePassThru::identify_controller`] and [`NvmePassThru::smart_log`]
+//! return directly.
+//!
+//! [`NvmePassThru`]: pass_thru::NvmePassThru
+//! [`NvmePassThru::identify_controller`]: pass_thru::NvmePassThru::identify_controller
+//! [`NvmePassThru::smart_log`]: pass_thru::NvmePassThru::smart_log
+
+pub mod pass_thru;
+
+use bitflags::bitflags;
+
+/// Size of the data returned by the Identify command.
+pub const IDENTIFY_DATA_LEN: usize = 4096;
+
+/// Size of the SMART / Health Information log page.
+pub const SMART_LOG_LEN: usize = 512;
+
+/// Get the `N` bytes of `data` at `offset`.
+fn field<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(&data[offset..offset + N]);
+    bytes
+}
+
+/// Strip the space padding from an ASCII field.
+fn trim_ascii_field(field: &[u8]) -> &[u8] {
+    let len = field
+        .iter()
+        .rposition(|&b| b != b' ' && b != 0)
+        .map_or(0, |i| i + 1);
+    &field[..len]
+}
+
+/// Identify Controller data structure, returned by the Identify command with
+/// CNS 01h.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdentifyController {
+    data: [u8; IDENTIFY_DATA_LEN],
+}
+
+impl IdentifyController {
+    /// Create from the raw data returned by the controller.
+    #[must_use]
+    pub const fn new(data: [u8; IDENTIFY_DATA_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; IDENTIFY_DATA_LEN] {
+        &self.data
+    }
+
+    /// PCI vendor ID (VID).
+    #[must_use]
+    pub fn vendor_id(&self) -> u16 {
+        u16::from_le_bytes(field(&self.data, 0))
+    }
+
+    /// PCI subsystem vendor ID (SSVID).
+    #[must_use]
+    pub fn subsystem_vendor_id(&self) -> u16 {
+        u16::from_le_bytes(field(&self.data, 2))
+    }
+
+    /// Serial number (SN), without padding.
+    #[must_use]
+    pub fn serial_number(&self) -> &[u8] {
+        trim_ascii_field(&self.data[4..24])
+    }
+
+    /// Model number (MN), without padding.
+    #[must_use]
+    pub fn model_number(&self) -> &[u8] {
+        trim_ascii_field(&self.data[24..64])
+    }
+
+    /// Firmware revision (FR), without padding.
+    #[must_use]
+    pub fn firmware_revision(&self) -> &[u8] {
+        trim_ascii_field(&self.data[64..72])
+    }
+
+    /// IEEE OUI identifier of the vendor (IEEE).
+    #[must_use]
+    pub fn ieee_oui(&self) -> [u8; 3] {
+        field(&self.data, 73)
+    }
+
+    /// Controller ID (CNTLID).
+    #[must_use]
+    pub fn controller_id(&self) -> u16 {
+        u16::from_le_bytes(field(&self.data, 78))
+    }
+
+    /// Version of the NVMe specification implemented by the controller
+    /// (VER), or zero for controllers older than version 1.2.
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        u32::from_le_bytes(field(&self.data, 80))
+    }
+
+    /// Optional admin commands supported by the controller (OACS). For
+    /// example, bit 1 indicates support for the Format NVM command.
+    #[must_use]
+    pub fn optional_admin_commands(&self) -> u16 {
+        u16::from_le_bytes(field(&self.data, 256))
+    }
+
+    /// Total NVM capacity in bytes (TNVMCAP), or zero if not reported.
+    #[must_use]
+    pub fn total_capacity(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 280))
+    }
+
+    /// Sanitize capabilities (SANICAP). Bits 0-2 indicate support for the
+    /// crypto erase, block erase, and overwrite operations.
+    #[must_use]
+    pub fn sanitize_capabilities(&self) -> u32 {
+        u32::from_le_bytes(field(&self.data, 328))
+    }
+
+    /// Number of namespaces supported by the controller (NN).
+    #[must_use]
+    pub fn namespace_count(&self) -> u32 {
+        u32::from_le_bytes(field(&self.data, 516))
+    }
+
+    /// Format NVM attributes (FNA). Bit 2 indicates support for
+    /// cryptographic erase as part of a format or secure erase.
+    #[must_use]
+    pub const fn format_nvm_attributes(&self) -> u8 {
+        self.data[524]
+    }
+}
+
+/// Format of the logical blocks of a namespace.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct LbaFormat {
+    /// Number of metadata bytes per block.
+    pub metadata_size: u16,
+
+    /// Block size as a power of two.
+    pub data_size_shift: u8,
+
+    /// Relative performance of the format, from 0 (best) to 3 (degraded).
+    pub relative_performance: u8,
+}
+
+impl LbaFormat {
+    /// Get the size of a block in bytes.
+    #[must_use]
+    pub const fn block_size(&self) -> u64 {
+        1 << self.data_size_shift
+    }
+}
+
+/// Identify Namespace data structure, returned by the Identify command with
+/// CNS 00h.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdentifyNamespace {
+    data: [u8; IDENTIFY_DATA_LEN],
+}
+
+impl IdentifyNamespace {
+    /// Maximum number of block formats of a namespace.
+    const MAX_LBA_FORMATS: usize = 64;
+
+    /// Create from the raw data returned by the controller.
+    #[must_use]
+    pub const fn new(data: [u8; IDENTIFY_DATA_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; IDENTIFY_DATA_LEN] {
+        &self.data
+    }
+
+    /// Size of the namespace in blocks (NSZE).
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        u64::from_le_bytes(field(&self.data, 0))
+    }
+
+    /// Maximum number of blocks that can be allocated (NCAP).
+    #[must_use]
+    pub fn capacity(&self) -> u64 {
+        u64::from_le_bytes(field(&self.data, 8))
+    }
+
+    /// Number of blocks currently allocated (NUSE).
+    #[must_use]
+    pub fn utilization(&self) -> u64 {
+        u64::from_le_bytes(field(&self.data, 16))
+    }
+
+    /// Namespace features (NSFEAT).
+    #[must_use]
+    pub const fn features(&self) -> u8 {
+        self.data[24]
+    }
+
+    /// Number of supported block formats.
+    #[must_use]
+    pub fn lba_format_count(&self) -> usize {
+        // NLBAF is zero-based.
+        usize::from(self.data[25]) + 1
+    }
+
+    /// Get the block format with `index`, or `None` if `index` is out of
+    /// range.
+    #[must_use]
+    pub fn lba_format(&self, index: usize) -> Option<LbaFormat> {
+        if index >= self.lba_format_count().min(Self::MAX_LBA_FORMATS) {
+            return None;
+        }
+        let format = u32::from_le_bytes(field(&self.data, 128 + index * 4));
+        Some(LbaFormat {
+            metadata_size: format as u16,
+            data_size_shift: (format >> 16) as u8,
+            relative_performance: ((format >> 24) & 0b11) as u8,
+        })
+    }
+
+    /// Get the index of the current block format (FLBAS).
+    #[must_use]
+    pub fn current_lba_format_index(&self) -> usize {
+        // Bits 0-3 hold the low and bits 5-6 the high bits of the index.
+        let flbas = self.data[26];
+        usize::from(flbas & 0xf) | (usize::from((flbas >> 5) & 0b11) << 4)
+    }
+
+    /// Get the current block format.
+    #[must_use]
+    pub fn current_lba_format(&self) -> Option<LbaFormat> {
+        self.lba_format(self.current_lba_format_index())
+    }
+}
+
+/// Active Namespace ID list, returned by the Identify command with CNS 02h.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NamespaceList {
+    data: [u8; IDENTIFY_DATA_LEN],
+}
+
+impl NamespaceList {
+    /// Maximum number of namespace IDs in a list.
+    pub const MAX_IDS: usize = IDENTIFY_DATA_LEN / 4;
+
+    /// Create from the raw data returned by the controller.
+    #[must_use]
+    pub const fn new(data: [u8; IDENTIFY_DATA_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; IDENTIFY_DATA_LEN] {
+        &self.data
+    }
+
+    /// Iterate over the namespace IDs in the list, in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        // Unused entries at the end of the list are zero.
+        self.data
+            .chunks_exact(4)
+            .map(|id| u32::from_le_bytes(id.try_into().unwrap()))
+            .take_while(|&id| id != 0)
+    }
+}
+
+newtype_enum! {
+    /// Identifier of a log page, read with the Get Log Page command.
+    pub enum LogPageId: u8 => {
+        /// Error Information.
+        ERROR_INFORMATION = 0x01,
+        /// SMART / Health Information, see [`SmartLog`].
+        SMART = 0x02,
+        /// Firmware Slot Information.
+        FIRMWARE_SLOT = 0x03,
+        /// Changed Namespace List.
+        CHANGED_NAMESPACE_LIST = 0x04,
+        /// Commands Supported and Effects.
+        COMMAND_EFFECTS = 0x05,
+        /// Device Self-test.
+        DEVICE_SELF_TEST = 0x06,
+    }
+}
+
+bitflags! {
+    /// Critical warnings of the [`SmartLog`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct CriticalWarning: u8 {
+        /// The available spare capacity is below the threshold.
+        const AVAILABLE_SPARE = 1 << 0;
+        /// A temperature is outside of its threshold.
+        const TEMPERATURE = 1 << 1;
+        /// Reliability is degraded due to media or internal errors.
+        const RELIABILITY = 1 << 2;
+        /// All media is read-only.
+        const READ_ONLY = 1 << 3;
+        /// The volatile memory backup device has failed.
+        const VOLATILE_BACKUP = 1 << 4;
+        /// The persistent memory region is read-only or unreliable.
+        const PERSISTENT_MEMORY = 1 << 5;
+    }
+}
+
+/// SMART / Health Information log page, returned by the Get Log Page
+/// command with [`LogPageId::SMART`].
+///
+/// Temperatures are in Kelvin, and counters of data units count thousands
+/// of 512-byte blocks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SmartLog {
+    data: [u8; SMART_LOG_LEN],
+}
+
+impl SmartLog {
+    /// Create from the raw data returned by the controller.
+    #[must_use]
+    pub const fn new(data: [u8; SMART_LOG_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; SMART_LOG_LEN] {
+        &self.data
+    }
+
+    /// Critical warnings about the state of the controller.
+    #[must_use]
+    pub const fn critical_warning(&self) -> CriticalWarning {
+        CriticalWarning::from_bits_retain(self.data[0])
+    }
+
+    /// Composite temperature of the controller and its namespaces.
+    #[must_use]
+    pub fn composite_temperature(&self) -> u16 {
+        u16::from_le_bytes(field(&self.data, 1))
+    }
+
+    /// Remaining spare capacity in percent.
+    #[must_use]
+    pub const fn available_spare(&self) -> u8 {
+        self.data[3]
+    }
+
+    /// Threshold of the available spare capacity in percent, below which a
+    /// critical warning is reported.
+    #[must_use]
+    pub const fn available_spare_threshold(&self) -> u8 {
+        self.data[4]
+    }
+
+    /// Estimate of the used life of the device in percent. The value can
+    /// exceed 100.
+    #[must_use]
+    pub const fn percentage_used(&self) -> u8 {
+        self.data[5]
+    }
+
+    /// Number of data units read by the host.
+    #[must_use]
+    pub fn data_units_read(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 32))
+    }
+
+    /// Number of data units written by the host.
+    #[must_use]
+    pub fn data_units_written(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 48))
+    }
+
+    /// Number of read commands completed by the controller.
+    #[must_use]
+    pub fn host_read_commands(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 64))
+    }
+
+    /// Number of write commands completed by the controller.
+    #[must_use]
+    pub fn host_write_commands(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 80))
+    }
+
+    /// Time the controller was busy with I/O commands, in minutes.
+    #[must_use]
+    pub fn controller_busy_time(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 96))
+    }
+
+    /// Number of power cycles.
+    #[must_use]
+    pub fn power_cycles(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 112))
+    }
+
+    /// Number of power-on hours.
+    #[must_use]
+    pub fn power_on_hours(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 128))
+    }
+
+    /// Number of unsafe shutdowns.
+    #[must_use]
+    pub fn unsafe_shutdowns(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 144))
+    }
+
+    /// Number of unrecovered data integrity errors.
+    #[must_use]
+    pub fn media_errors(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 160))
+    }
+
+    /// Number of entries in the Error Information log over the life of the
+    /// controller.
+    #[must_use]
+    pub fn error_log_entries(&self) -> u128 {
+        u128::from_le_bytes(field(&self.data, 176))
+    }
+
+    /// Time the composite temperature was above the warning threshold, in
+    /// minutes.
+    #[must_use]
+    pub fn warning_temperature_time(&self) -> u32 {
+        u32::from_le_bytes(field(&self.data, 192))
+    }
+
+    /// Time the composite temperature was above the critical threshold, in
+    /// minutes.
+    #[must_use]
+    pub fn critical_temperature_time(&self) -> u32 {
+        u32::from_le_bytes(field(&self.data, 196))
+    }
+
+    /// Get the temperature of sensor `index` (0 to 7), or `None` if the
+    /// sensor is not implemented.
+    #[must_use]
+    pub fn temperature_sensor(&self, index: usize) -> Option<u16> {
+        if index >= 8 {
+            return None;
+        }
+        let temperature = u16::from_le_bytes(field(&self.data, 200 + index * 2));
+        (temperature != 0).then_some(temperature)
+    }
+}
+
+newtype_enum! {
+    /// Secure erase setting of the Format NVM command.
+    pub enum SecureErase: u8 => {
+        /// No secure erase.
+        NONE = 0,
+        /// Erase all user data.
+        USER_DATA = 1,
+        /// Erase all user data by deleting the encryption key.
+        CRYPTOGRAPHIC = 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_controller() {
+        let mut data = [0; IDENTIFY_DATA_LEN];
+        data[0..2].copy_from_slice(&0x1b36u16.to_le_bytes());
+        data[4..24].copy_from_slice(b"deadbeef            ");
+        data[24..64].copy_from_slice(b"QEMU NVMe Ctrl                          ");
+        data[64..72].copy_from_slice(b"8.0.0   ");
+        data[80..84].copy_from_slice(&0x0001_0400u32.to_le_bytes());
+        data[280..296].copy_from_slice(&(1u128 << 40).to_le_bytes());
+        data[516..520].copy_from_slice(&256u32.to_le_bytes());
+        data[524] = 0b100;
+
+        let ctrl = IdentifyController::new(data);
+        assert_eq!(ctrl.vendor_id(), 0x1b36);
+        assert_eq!(ctrl.serial_number(), b"deadbeef");
+        assert_eq!(ctrl.model_number(), b"QEMU NVMe Ctrl");
+        assert_eq!(ctrl.firmware_revision(), b"8.0.0");
+        assert_eq!(ctrl.version(), 0x0001_0400);
+        assert_eq!(ctrl.total_capacity(), 1 << 40);
+        assert_eq!(ctrl.namespace_count(), 256);
+        assert_eq!(ctrl.format_nvm_attributes(), 0b100);
+    }
+
+    #[test]
+    fn test_identify_namespace() {
+        let mut data = [0; IDENTIFY_DATA_LEN];
+        data[0..8].copy_from_slice(&0x20000u64.to_le_bytes());
+        data[25] = 1;
+        data[26] = 1;
+        data[128..132].copy_from_slice(&(9u32 << 16).to_le_bytes());
+        data[132..136].copy_from_slice(&((1u32 << 24) | (12 << 16) | 8).to_le_bytes());
+
+        let ns = IdentifyNamespace::new(data);
+        assert_eq!(ns.size(), 0x20000);
+        assert_eq!(ns.lba_format_count(), 2);
+        assert_eq!(ns.lba_format(0).unwrap().block_size(), 512);
+        assert_eq!(
+            ns.current_lba_format(),
+            Some(LbaFormat {
+                metadata_size: 8,
+                data_size_shift: 12,
+                relative_performance: 1,
+            })
+        );
+        assert_eq!(ns.lba_format(2), None);
+    }
+    #[test]
+    fn test_namespace_list() {
+        let mut data = [0; IDENTIFY_DATA_LEN];
+        data[0..4].copy_from_slice(&1u32.to_le_bytes());
+        data[4..8].copy_from_slice(&3u32.to_le_bytes());
+
+        let list = NamespaceList::new(data);
+        assert!(list.ids().eq([1, 3]));
+        assert_eq!(NamespaceList::new([0; IDENTIFY_DATA_LEN]).ids().count(), 0);
+    }
+
+    #[test]
+    fn test_smart_log() {
+        let mut data = [0; SMART_LOG_LEN];
+        data[0] = 0b1010;
+        data[1..3].copy_from_slice(&300u16.to_le_bytes());
+        data[3] = 100;
+        data[4] = 10;
+        data[5] = 3;
+        data[48..64].copy_from_slice(&1234u128.to_le_bytes());
+        data[128..144].copy_from_slice(&42u128.to_le_bytes());
+        data[196..200].copy_from_slice(&7u32.to_le_bytes());
+        data[202..204].copy_from_slice(&310u16.to_le_bytes());
+
+        let log = SmartLog::new(data);
+        assert_eq!(
+            log.critical_warning(),
+            CriticalWarning::TEMPERATURE | CriticalWarning::READ_ONLY
+        );
+        assert_eq!(log.composite_temperature(), 300);
+        assert_eq!(log.available_spare(), 100);
+        assert_eq!(log.available_spare_threshold(), 10);
+        assert_eq!(log.percentage_used(), 3);
+        assert_eq!(log.data_units_written(), 1234);
+        assert_eq!(log.power_on_hours(), 42);
+        assert_eq!(log.critical_temperature_time(), 7);
+        assert_eq!(log.temperature_sensor(0), None);
+        assert_eq!(log.temperature_sensor(1), Some(310));
+        assert_eq!(log.temperature_sensor(8), None);
+    }
+}
@@ -0,0 +1,453 @@
+//! NVM Express Pass Thru protocol.
+//!
+//! [`NvmePassThru`] is installed on NVMe controller handles, and can send
+//! any admin or I/O command to the controller:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::nvme::pass_thru::NvmePassThru;
+//! # fn test() -> uefi::Result {
+//!
+//! let handle = boot::get_handle_for_protocol::<NvmePassThru>()?;
+//! let mut nvme = boot::open_protocol_exclusive::<NvmePassThru>(handle)?;
+//! let controller = nvme.identify_controller()?;
+//! log::info!("model: {:?}", core::str::from_utf8(controller.model_number()));
+//!
+//! let namespaces: Vec<u32> = nvme.namespaces().collect();
+//! for namespace_id in namespaces {
+//!     let namespace = nvme.identify_namespace(namespace_id)?;
+//!     log::info!("namespace {namespace_id}: {} blocks", namespace.size());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{LogPageId, SecureErase};
+use crate::duration;
+use crate::proto::device_path::DevicePath;
+use crate::proto::unsafe_protocol;
+use crate::{Result, Status, StatusExt};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::nvme::{
+    NvmExpressCdw0, NvmExpressCommand, NvmExpressCommandFlags, NvmExpressPassThruCommandPacket,
+    NvmExpressPassThruProtocol,
+};
+
+#[cfg(feature = "alloc")]
+use {
+    super::{
+        IdentifyController, IdentifyNamespace, NamespaceList, SmartLog, IDENTIFY_DATA_LEN,
+        SMART_LOG_LEN,
+    },
+    crate::boot,
+    crate::mem::AlignedBuffer,
+    crate::proto::device_path::FfiDevicePath,
+    alloc::boxed::Box,
+    alloc::vec::Vec,
+    core::ptr::NonNull,
+};
+
+pub use uefi_raw::protocol::nvme::{
+    NvmExpressCompletion, NvmExpressPassThruAttributes, NvmExpressPassThruMode, NvmExpressQueueType,
+};
+
+/// Namespace ID used for admin commands that target the controller rather
+/// than a namespace.
+pub const NVME_CONTROLLER_NAMESPACE_ID: u32 = 0;
+
+/// Namespace ID that applies a command to all namespaces.
+pub const NVME_BROADCAST_NAMESPACE_ID: u32 = 0xffff_ffff;
+
+/// Opcode of the admin Get Log Page command.
+const OPCODE_GET_LOG_PAGE: u8 = 0x02;
+
+/// Opcode of the admin Identify command.
+#[cfg(feature = "alloc")]
+const OPCODE_IDENTIFY: u8 = 0x06;
+
+/// Opcode of the admin Format NVM command.
+const OPCODE_FORMAT_NVM: u8 = 0x80;
+
+/// Timeout of the commands sent by the typed helpers.
+const HELPER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An NVMe command, submitted with [`NvmePassThru::execute_command`].
+///
+/// Command dwords that are not set are filled in by the driver, and the
+/// namespace ID of the command is the one passed to `execute_command`.
+#[derive(Debug)]
+pub struct NvmeRequest<'buf> {
+    queue_type: NvmExpressQueueType,
+    command: NvmExpressCommand,
+    transfer_buffer: Option<&'buf mut [u8]>,
+    metadata_buffer: Option<&'buf mut [u8]>,
+    timeout: Duration,
+}
+
+macro_rules! cdw_setter {
+    ($name:ident, $field:ident, $flag:ident, $index:literal) => {
+        #[doc = concat!("Set command dword ", $index, ".")]
+        #[must_use]
+        pub const fn $name(mut self, value: u32) -> Self {
+            self.command.$field = value;
+            self.command.flags = self.command.flags.union(NvmExpressCommandFlags::$flag);
+            self
+        }
+    };
+}
+
+impl<'buf> NvmeRequest<'buf> {
+    const fn new(queue_type: NvmExpressQueueType, opcode: u8) -> Self {
+        Self {
+            queue_type,
+            command: NvmExpressCommand {
+                cdw0: NvmExpressCdw0::new(opcode, 0),
+                flags: NvmExpressCommandFlags::empty(),
+                nsid: 0,
+                cdw2: 0,
+                cdw3: 0,
+                cdw10: 0,
+                cdw11: 0,
+                cdw12: 0,
+                cdw13: 0,
+                cdw14: 0,
+                cdw15: 0,
+            },
+            transfer_buffer: None,
+            metadata_buffer: None,
+            timeout: Duration::ZERO,
+        }
+    }
+
+    /// Create a command for the admin queue.
+    #[must_use]
+    pub const fn admin(opcode: u8) -> Self {
+        Self::new(NvmExpressQueueType::ADMIN, opcode)
+    }
+
+    /// Create a command for an I/O queue.
+    #[must_use]
+    pub const fn io(opcode: u8) -> Self {
+        Self::new(NvmExpressQueueType::IO, opcode)
+    }
+
+    cdw_setter!(with_cdw2, cdw2, CDW2_VALID, "2");
+    cdw_setter!(with_cdw3, cdw3, CDW3_VALID, "3");
+    cdw_setter!(with_cdw10, cdw10, CDW10_VALID, "10");
+    cdw_setter!(with_cdw11, cdw11, CDW11_VALID, "11");
+    cdw_setter!(with_cdw12, cdw12, CDW12_VALID, "12");
+    cdw_setter!(with_cdw13, cdw13, CDW13_VALID, "13");
+    cdw_setter!(with_cdw14, cdw14, CDW14_VALID, "14");
+    cdw_setter!(with_cdw15, cdw15, CDW15_VALID, "15");
+
+    /// Set the buffer that data is transferred to or from, depending on the
+    /// command. It must be aligned to [`NvmePassThru::io_align`].
+    #[must_use]
+    pub fn with_transfer_buffer(mut self, buffer: &'buf mut [u8]) -> Self {
+        self.transfer_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the buffer that metadata is transferred to or from, depending on
+    /// the command. It must be aligned to [`NvmePassThru::io_align`].
+    #[must_use]
+    pub fn with_metadata_buffer(mut self, buffer: &'buf mut [u8]) -> Self {
+        self.metadata_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the timeout of the command. The default of [`Duration::ZERO`]
+    /// waits indefinitely.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Result of a completed [`NvmeRequest`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NvmeResponse {
+    /// Completion queue entry of the command.
+    pub completion: NvmExpressCompletion,
+
+    /// Number of bytes transferred to or from the transfer buffer.
+    pub transfer_length: usize,
+
+    /// Number of bytes transferred to or from the metadata buffer.
+    pub metadata_length: usize,
+}
+
+/// NVM Express Pass Thru protocol.
+///
+/// Corresponds to the `EFI_NVM_EXPRESS_PASS_THRU_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(NvmExpressPassThruProtocol::GUID)]
+pub struct NvmePassThru(NvmExpressPassThruProtocol);
+
+impl NvmePassThru {
+    /// Get the mode of the controller.
+    #[must_use]
+    pub const fn mode(&self) -> NvmExpressPassThruMode {
+        unsafe { *self.0.mode }
+    }
+
+    /// Get the alignment required for transfer and metadata buffers. Values
+    /// of 0 and 1 mean that buffers don't need to be aligned.
+    #[must_use]
+    pub const fn io_align(&self) -> u32 {
+        self.mode().io_align
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes aligned to
+    /// [`io_align`](Self::io_align).
+    #[cfg(feature = "alloc")]
+    pub fn alloc_io_buffer(&self, len: usize) -> Result<AlignedBuffer> {
+        AlignedBuffer::from_size_align(len, self.io_align() as usize)
+            .map_err(|_| Status::BAD_BUFFER_SIZE.into())
+    }
+
+    /// Get an iterator over the IDs of the namespaces of the controller.
+    #[must_use]
+    pub const fn namespaces(&self) -> NvmeNamespaceIter<'_> {
+        NvmeNamespaceIter {
+            pass_thru: self,
+            namespace_id: NVME_BROADCAST_NAMESPACE_ID,
+        }
+    }
+
+    /// Get the device path node of the namespace `namespace_id`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the namespace does not exist.
+    #[cfg(feature = "alloc")]
+    pub fn device_path(&self, namespace_id: u32) -> Result<Box<DevicePath>> {
+        let mut device_path = ptr::null_mut();
+        unsafe { (self.0.build_device_path)(&self.0, namespace_id, &mut device_path) }
+            .to_result()?;
+        let device_path = NonNull::new(device_path).ok_or(Status::NOT_FOUND)?;
+        let boxed =
+            unsafe { DevicePath::from_ffi_ptr(device_path.as_ptr().cast::<FfiDevicePath>()) }
+                .to_boxed();
+        let _ = unsafe { boot::free_pool(device_path.cast()) };
+        Ok(boxed)
+    }
+
+    /// Get the ID of the namespace with the device path node
+    /// `device_path`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `device_path` is not an NVMe namespace
+    ///   node.
+    /// * [`Status::NOT_FOUND`]: the namespace does not exist.
+    pub fn namespace_id(&self, device_path: &DevicePath) -> Result<u32> {
+        let mut namespace_id = 0;
+        unsafe {
+            (self.0.get_namespace)(&self.0, device_path.as_ffi_ptr().cast(), &mut namespace_id)
+        }
+        .to_result_with_val(|| namespace_id)
+    }
+
+    /// Submit `request` to the namespace `namespace_id`, and wait for it to
+    /// complete.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: a buffer is not aligned to
+    ///   [`io_align`](Self::io_align), or the command is invalid.
+    /// * [`Status::BAD_BUFFER_SIZE`]: a buffer is too large for the
+    ///   controller. The number of bytes that can be transferred is reported
+    ///   by the driver.
+    /// * [`Status::NOT_READY`]: the command could not be sent because the
+    ///   controller is busy.
+    /// * [`Status::DEVICE_ERROR`]: the command failed. The error status is
+    ///   reported in the completion queue entry.
+    /// * [`Status::TIMEOUT`]: the command did not complete in time.
+    pub fn execute_command(
+        &mut self,
+        namespace_id: u32,
+        request: NvmeRequest<'_>,
+    ) -> Result<NvmeResponse> {
+        let align = self.io_align().max(1) as usize;
+        let (transfer_buffer, transfer_length) = raw_buffer(request.transfer_buffer, align)?;
+        let (metadata_buffer, metadata_length) = raw_buffer(request.metadata_buffer, align)?;
+
+        let mut command = request.command;
+        command.nsid = namespace_id;
+        let mut completion = NvmExpressCompletion::default();
+        let mut packet = NvmExpressPassThruCommandPacket {
+            command_timeout: duration::as_timer_units(request.timeout),
+            transfer_buffer: transfer_buffer.cast(),
+            transfer_length,
+            metadata_buffer: metadata_buffer.cast(),
+            metadata_length,
+            queue_type: request.queue_type,
+            nvme_cmd: &command,
+            nvme_completion: &mut completion,
+        };
+        unsafe { (self.0.pass_thru)(&mut self.0, namespace_id, &mut packet, ptr::null_mut()) }
+            .to_result_with_val(|| NvmeResponse {
+                completion,
+                transfer_length: packet.transfer_length as usize,
+                metadata_length: packet.metadata_length as usize,
+            })
+    }
+
+    /// Send an Identify command with `cns` for `namespace_id`.
+    #[cfg(feature = "alloc")]
+    fn identify(&mut self, namespace_id: u32, cns: u32) -> Result<[u8; IDENTIFY_DATA_LEN]> {
+        let mut buffer = self.alloc_io_buffer(IDENTIFY_DATA_LEN)?;
+        let request = NvmeRequest::admin(OPCODE_IDENTIFY)
+            .with_cdw10(cns)
+            .with_transfer_buffer(buffer.as_mut_slice())
+            .with_timeout(HELPER_TIMEOUT);
+        self.execute_command(namespace_id, request)?;
+
+        let mut data = [0; IDENTIFY_DATA_LEN];
+        data.copy_from_slice(buffer.as_slice());
+        Ok(data)
+    }
+
+    /// Get the Identify Controller data of the controller.
+    #[cfg(feature = "alloc")]
+    pub fn identify_controller(&mut self) -> Result<IdentifyController> {
+        self.identify(NVME_CONTROLLER_NAMESPACE_ID, 0x01)
+            .map(IdentifyController::new)
+    }
+
+    /// Get the Identify Namespace data of the namespace `namespace_id`.
+    #[cfg(feature = "alloc")]
+    pub fn identify_namespace(&mut self, namespace_id: u32) -> Result<IdentifyNamespace> {
+        self.identify(namespace_id, 0x00)
+            .map(IdentifyNamespace::new)
+    }
+
+    /// Get the IDs of the active namespaces of the controller, reported by
+    /// the controller itself. Unlike [`namespaces`](Self::namespaces), this
+    /// includes namespaces that the driver does not expose.
+    #[cfg(feature = "alloc")]
+    pub fn active_namespace_ids(&mut self) -> Result<Vec<u32>> {
+        let mut ids = Vec::new();
+        loop {
+            // The list contains the active namespaces with a greater ID than
+            // the one in the command.
+            let start = ids.last().copied().unwrap_or(0);
+            let list = NamespaceList::new(self.identify(start, 0x02)?);
+            let len = ids.len();
+            ids.extend(list.ids());
+            if ids.len() - len < NamespaceList::MAX_IDS {
+                return Ok(ids);
+            }
+        }
+    }
+
+    /// Read the log page `log_id` for `namespace_id` into `buffer`, starting
+    /// at the beginning of the log page. Use [`NVME_BROADCAST_NAMESPACE_ID`]
+    /// for log pages of the controller.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: the length of `buffer` is zero or not
+    ///   a multiple of four bytes.
+    ///
+    /// See [`execute_command`](Self::execute_command) for other errors.
+    pub fn get_log_page(
+        &mut self,
+        namespace_id: u32,
+        log_id: LogPageId,
+        buffer: &mut [u8],
+    ) -> Result {
+        if buffer.is_empty() || buffer.len() % 4 != 0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        // The number of dwords is zero-based.
+        let num_dwords =
+            u32::try_from(buffer.len() / 4 - 1).map_err(|_| Status::INVALID_PARAMETER)?;
+        let request = NvmeRequest::admin(OPCODE_GET_LOG_PAGE)
+            .with_cdw10(u32::from(log_id.0) | (num_dwords << 16))
+            .with_cdw11(num_dwords >> 16)
+            .with_transfer_buffer(buffer)
+            .with_timeout(HELPER_TIMEOUT);
+        self.execute_command(namespace_id, request).map(|_| ())
+    }
+
+    /// Get the SMART / Health Information log page of the controller.
+    #[cfg(feature = "alloc")]
+    pub fn smart_log(&mut self) -> Result<SmartLog> {
+        let mut buffer = self.alloc_io_buffer(SMART_LOG_LEN)?;
+        self.get_log_page(
+            NVME_BROADCAST_NAMESPACE_ID,
+            LogPageId::SMART,
+            buffer.as_mut_slice(),
+        )?;
+
+        let mut data = [0; SMART_LOG_LEN];
+        data.copy_from_slice(buffer.as_slice());
+        Ok(SmartLog::new(data))
+    }
+
+    /// Format the namespace `namespace_id` with the block format
+    /// `lba_format_index` of [`IdentifyNamespace::lba_format`], erasing all
+    /// of its data. Use [`NVME_BROADCAST_NAMESPACE_ID`] to format all
+    /// namespaces. Metadata and protection information are disabled.
+    ///
+    /// This waits until the format has completed, which can take a long
+    /// time for a secure erase.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: `lba_format_index` is out of range.
+    ///
+    /// See [`execute_command`](Self::execute_command) for other errors.
+    pub fn format_nvm(
+        &mut self,
+        namespace_id: u32,
+        lba_format_index: usize,
+        secure_erase: SecureErase,
+    ) -> Result {
+        // The index is split into the low bits 0-3 and the high bits 12-13.
+        let index = u32::try_from(lba_format_index)
+            .ok()
+            .filter(|&index| index < 64)
+            .ok_or(Status::INVALID_PARAMETER)?;
+        let cdw10 = (index & 0xf) | (u32::from(secure_erase.0 & 0b111) << 9) | ((index >> 4) << 12);
+        let request = NvmeRequest::admin(OPCODE_FORMAT_NVM).with_cdw10(cdw10);
+        self.execute_command(namespace_id, request).map(|_| ())
+    }
+}
+
+/// Get the pointer and length of an optional buffer for a command packet.
+fn raw_buffer(buffer: Option<&mut [u8]>, align: usize) -> Result<(*mut u8, u32)> {
+    let Some(buffer) = buffer else {
+        return Ok((ptr::null_mut(), 0));
+    };
+    if buffer.as_ptr() as usize % align != 0 {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let len = u32::try_from(buffer.len()).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+    Ok((buffer.as_mut_ptr(), len))
+}
+
+/// Iterator over the namespace IDs of an [`NvmePassThru`] controller,
+/// returned by [`NvmePassThru::namespaces`].
+#[derive(Debug)]
+pub struct NvmeNamespaceIter<'a> {
+    pass_thru: &'a NvmePassThru,
+    namespace_id: u32,
+}
+
+impl Iterator for NvmeNamespaceIter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let proto = &self.pass_thru.0;
+        unsafe { (proto.get_next_namespace)(proto, &mut self.namespace_id) }
+            .to_result()
+            .ok()?;
+        Some(self.namespace_id)
+    }
+}
@@ -0,0 +1,259 @@
+//! Firmware Volume 2 protocol.
+//!
+//! A firmware volume is a flash region, or a copy of one in memory, that
+//! contains files identified by GUIDs. Each file consists of sections, such
+//! as the PE32 image of a driver, or raw data like a boot logo. The
+//! [`FirmwareVolume2`] protocol reads the files and sections of a firmware
+//! volume, including sections in compressed or otherwise encapsulated
+//! sections.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::pi::firmware_volume::{FirmwareVolume2, FvFileType, SectionType};
+//! # fn list_drivers() -> uefi::Result {
+//!
+//! for handle in boot::find_handles::<FirmwareVolume2>()? {
+//!     let fv = boot::open_protocol_exclusive::<FirmwareVolume2>(handle)?;
+//!     for file in fv.files(FvFileType::DRIVER) {
+//!         let file = file?;
+//!         if let Ok(image) = fv.read_section(&file.name, SectionType::PE32, 0) {
+//!             log::info!("Driver {} is {} bytes", file.name, image.len());
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::proto::unsafe_protocol;
+use crate::{Guid, Handle, Result, StatusExt};
+use core::ptr;
+use uefi_raw::protocol::firmware_volume::FirmwareVolume2Protocol;
+
+#[cfg(feature = "alloc")]
+use {
+    crate::boot, crate::mem::AlignedBuffer, crate::Status, alloc::vec, alloc::vec::Vec,
+    core::ffi::c_void, core::ptr::NonNull, core::slice,
+};
+
+pub use uefi_raw::protocol::firmware_volume::{
+    FvAttributes, FvFileAttributes, FvFileType, SectionType,
+};
+
+/// Information about a file in a firmware volume.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FvFileInfo {
+    /// GUID identifying the file.
+    pub name: Guid,
+
+    /// Type of the file.
+    pub file_type: FvFileType,
+
+    /// Attributes of the file.
+    pub attributes: FvFileAttributes,
+
+    /// Size of the file data in bytes, without the file header.
+    pub size: usize,
+}
+
+/// The Firmware Volume 2 protocol.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(FirmwareVolume2Protocol::GUID)]
+pub struct FirmwareVolume2(FirmwareVolume2Protocol);
+
+impl FirmwareVolume2 {
+    /// Get the attributes of the firmware volume.
+    pub fn attributes(&self) -> Result<FvAttributes> {
+        let mut attributes = FvAttributes::empty();
+        unsafe { (self.0.get_volume_attributes)(&self.0, &mut attributes) }
+            .to_result_with_val(|| attributes)
+    }
+
+    /// Get the handle of the firmware volume block device that the firmware
+    /// volume was produced from, if any.
+    #[must_use]
+    pub fn parent_handle(&self) -> Option<Handle> {
+        unsafe { Handle::from_ptr(self.0.parent_handle) }
+    }
+
+    /// Get information about the file `name`, without reading it.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the file does not exist.
+    /// * [`Status::DEVICE_ERROR`]: the firmware volume could not be read.
+    /// * [`Status::ACCESS_DENIED`]: the firmware volume is configured to
+    ///   disallow reads.
+    pub fn file_info(&self, name: &Guid) -> Result<FvFileInfo> {
+        let mut size = 0;
+        let mut file_type = FvFileType::ALL;
+        let mut attributes = FvFileAttributes::empty();
+        let mut authentication_status = 0;
+        // Without a buffer, only the information is returned.
+        unsafe {
+            (self.0.read_file)(
+                &self.0,
+                name,
+                ptr::null_mut(),
+                &mut size,
+                &mut file_type,
+                &mut attributes,
+                &mut authentication_status,
+            )
+        }
+        .to_result_with_val(|| FvFileInfo {
+            name: *name,
+            file_type,
+            attributes,
+            size,
+        })
+    }
+
+    /// Read the data of the file `name`, without the file header.
+    ///
+    /// # Errors
+    ///
+    /// See [`file_info`](Self::file_info).
+    #[cfg(feature = "alloc")]
+    pub fn read_file(&self, name: &Guid) -> Result<Vec<u8>> {
+        let info = self.file_info(name)?;
+        let mut data = vec![0; info.size];
+        let mut buffer = data.as_mut_ptr().cast::<c_void>();
+        let mut size = data.len();
+        let mut file_type = FvFileType::ALL;
+        let mut attributes = FvFileAttributes::empty();
+        let mut authentication_status = 0;
+        unsafe {
+            (self.0.read_file)(
+                &self.0,
+                name,
+                &mut buffer,
+                &mut size,
+                &mut file_type,
+                &mut attributes,
+                &mut authentication_status,
+            )
+        }
+        .to_result()?;
+        data.truncate(size);
+        Ok(data)
+    }
+
+    /// Read instance `instance` of the sections of type `section_type` in
+    /// the file `name`. Encapsulated sections are searched as well, and
+    /// compressed sections are decompressed.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the file or section does not exist.
+    /// * [`Status::OUT_OF_RESOURCES`]: there was not enough memory to read
+    ///   the section.
+    /// * [`Status::DEVICE_ERROR`]: the firmware volume could not be read.
+    /// * [`Status::ACCESS_DENIED`]: the firmware volume is configured to
+    ///   disallow reads.
+    #[cfg(feature = "alloc")]
+    pub fn read_section(
+        &self,
+        name: &Guid,
+        section_type: SectionType,
+        instance: usize,
+    ) -> Result<Vec<u8>> {
+        // With a null buffer, the section is read into a pool allocation.
+        let mut buffer = ptr::null_mut();
+        let mut size = 0;
+        let mut authentication_status = 0;
+        unsafe {
+            (self.0.read_section)(
+                &self.0,
+                name,
+                section_type,
+                instance,
+                &mut buffer,
+                &mut size,
+                &mut authentication_status,
+            )
+        }
+        .to_result()?;
+        let Some(buffer) = NonNull::new(buffer.cast::<u8>()) else {
+            return Ok(Vec::new());
+        };
+        let data = unsafe { slice::from_raw_parts(buffer.as_ptr(), size) }.to_vec();
+        let _ = unsafe { boot::free_pool(buffer) };
+        Ok(data)
+    }
+
+    /// Iterate over the files of type `file_type` in the firmware volume.
+    /// Use [`FvFileType::ALL`] to iterate over all files.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn files(&self, file_type: FvFileType) -> FvFileIter<'_> {
+        FvFileIter {
+            fv: self,
+            file_type,
+            // An error is returned by the first call to `next`.
+            key: AlignedBuffer::from_size_align(self.0.key_size as usize, 8).ok(),
+            done: false,
+        }
+    }
+}
+
+/// Iterator over the files of a firmware volume, returned by
+/// [`FirmwareVolume2::files`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct FvFileIter<'a> {
+    fv: &'a FirmwareVolume2,
+    file_type: FvFileType,
+    key: Option<AlignedBuffer>,
+    done: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for FvFileIter<'_> {
+    type Item = Result<FvFileInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let Some(key) = &mut self.key else {
+            self.done = true;
+            return Some(Err(Status::OUT_OF_RESOURCES.into()));
+        };
+
+        // The file type is an input to filter by, and an output.
+        let mut file_type = self.file_type;
+        let mut name = Guid::ZERO;
+        let mut attributes = FvFileAttributes::empty();
+        let mut size = 0;
+        let status = unsafe {
+            (self.fv.0.get_next_file)(
+                &self.fv.0,
+                key.as_mut_ptr().cast(),
+                &mut file_type,
+                &mut name,
+                &mut attributes,
+                &mut size,
+            )
+        };
+        match status {
+            Status::SUCCESS => Some(Ok(FvFileInfo {
+                name,
+                file_type,
+                attributes,
+                size,
+            })),
+            Status::NOT_FOUND => {
+                self.done = true;
+                None
+            }
+            status => {
+                self.done = true;
+                Some(Err(status.into()))
+            }
+        }
+    }
+}
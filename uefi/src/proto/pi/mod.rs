@@ -3,4 +3,5 @@
 //! Contains protocols defined in UEFI's
 //! Platform Initialization (PI) Specification.
 
+pub mod firmware_volume;
 pub mod mp;
@@ -13,7 +13,7 @@
 
 use crate::data_types::Event;
 use crate::proto::unsafe_protocol;
-use crate::{Result, Status, StatusExt};
+use crate::{duration, Result, Status, StatusExt};
 use bitflags::bitflags;
 use core::ffi::c_void;
 use core::ptr;
@@ -155,6 +155,13 @@ impl MpServices {
         (self.get_processor_info)(self, processor_number, &mut pi).to_result_with_val(|| pi)
     }
 
+    /// Gets detailed information on each processor, in order of the processor
+    /// number.
+    pub fn processors(&self) -> Result<impl Iterator<Item = Result<ProcessorInformation>> + '_> {
+        let count = self.get_number_of_processors()?;
+        Ok((0..count.total).map(|processor_number| self.get_processor_info(processor_number)))
+    }
+
     /// Executes provided function on all APs.
     pub fn startup_all_aps(
         &self,
@@ -164,10 +171,7 @@ impl MpServices {
         event: Option<Event>,
         timeout: Option<Duration>,
     ) -> Result {
-        let timeout_arg = match timeout {
-            Some(timeout) => timeout.as_micros().try_into().unwrap(),
-            None => 0,
-        };
+        let timeout_arg = timeout_to_micros(timeout);
 
         let event_arg = match event {
             Some(event) => event.as_ptr(),
@@ -195,10 +199,7 @@ impl MpServices {
         event: Option<Event>,
         timeout: Option<Duration>,
     ) -> Result {
-        let timeout_arg = match timeout {
-            Some(timeout) => timeout.as_micros().try_into().unwrap(),
-            None => 0,
-        };
+        let timeout_arg = timeout_to_micros(timeout);
 
         let event_arg = match event {
             Some(event) => event.as_ptr(),
@@ -217,6 +218,81 @@ impl MpServices {
         .to_result()
     }
 
+    /// Runs the closure `f` on all enabled APs, and waits until it returns.
+    ///
+    /// If `single_thread` is `true`, the APs run `f` one after another,
+    /// otherwise at the same time. This is the blocking mode of
+    /// [`startup_all_aps`].
+    ///
+    /// # Safety
+    ///
+    /// `f` runs on the APs, where almost nothing of UEFI can be used:
+    /// * `f` must not call boot services, runtime services or protocols,
+    ///   except for the functions of [`MpServices`] that APs may call, such as
+    ///   [`who_am_i`]. This rules out allocating memory and logging.
+    /// * `f` must not panic, since the panic handler is not safe to call on an
+    ///   AP.
+    ///
+    /// `f` is terminated on APs that have not finished it within `timeout`,
+    /// leaving any data it was modifying in an inconsistent state.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_STARTED`]: there are no enabled APs.
+    /// * [`Status::NOT_READY`]: an AP is busy.
+    /// * [`Status::TIMEOUT`]: not all APs finished within `timeout`.
+    ///
+    /// [`startup_all_aps`]: Self::startup_all_aps
+    /// [`who_am_i`]: Self::who_am_i
+    pub unsafe fn run_on_all_aps<F: Fn() + Sync>(
+        &self,
+        single_thread: bool,
+        timeout: Option<Duration>,
+        f: &F,
+    ) -> Result {
+        self.startup_all_aps(
+            single_thread,
+            call_fn::<F>,
+            ptr::from_ref(f).cast_mut().cast(),
+            None,
+            timeout,
+        )
+    }
+
+    /// Runs the closure `f` on the AP `processor_number`, and waits until it
+    /// returns.
+    ///
+    /// This is the blocking mode of [`startup_this_ap`].
+    ///
+    /// # Safety
+    ///
+    /// See [`run_on_all_aps`].
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_READY`]: the AP is busy.
+    /// * [`Status::TIMEOUT`]: the AP did not finish within `timeout`.
+    /// * [`Status::NOT_FOUND`] or [`Status::INVALID_PARAMETER`]:
+    ///   `processor_number` is not an enabled AP.
+    ///
+    /// [`startup_this_ap`]: Self::startup_this_ap
+    /// [`run_on_all_aps`]: Self::run_on_all_aps
+    pub unsafe fn run_on_ap<F: FnOnce() + Send>(
+        &self,
+        processor_number: usize,
+        timeout: Option<Duration>,
+        f: F,
+    ) -> Result {
+        let mut f = Some(f);
+        self.startup_this_ap(
+            processor_number,
+            call_fn_once::<F>,
+            ptr::from_mut(&mut f).cast(),
+            None,
+            timeout,
+        )
+    }
+
     /// Switches the requested AP to be the BSP from that point onward.
     pub fn switch_bsp(&self, processor_number: usize, enable_old_bsp: bool) -> Result {
         (self.switch_bsp)(self, processor_number, enable_old_bsp).to_result()
@@ -250,3 +326,43 @@ impl MpServices {
         (self.who_am_i)(self, &mut processor_number).to_result_with_val(|| processor_number)
     }
 }
+
+/// Calls the closure `arg`, which points to an `F`, on an AP.
+extern "efiapi" fn call_fn<F: Fn()>(arg: *mut c_void) {
+    // Safety: `arg` is the closure passed to `run_on_all_aps`, which waits for
+    // the APs to finish.
+    let f = unsafe { &*arg.cast::<F>() };
+    f();
+}
+
+/// Calls the closure `arg`, which points to an `Option<F>`, on an AP.
+extern "efiapi" fn call_fn_once<F: FnOnce()>(arg: *mut c_void) {
+    // Safety: `arg` is the closure passed to `run_on_ap`, which waits for the
+    // AP to finish.
+    let f = unsafe { &mut *arg.cast::<Option<F>>() };
+    if let Some(f) = f.take() {
+        f();
+    }
+}
+
+/// Convert a timeout to microseconds, rounding up. Zero microseconds means
+/// no timeout, so a timeout is at least one microsecond.
+fn timeout_to_micros(timeout: Option<Duration>) -> usize {
+    match timeout {
+        Some(timeout) => duration::as_micros_usize(timeout).max(1),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_to_micros() {
+        assert_eq!(timeout_to_micros(None), 0);
+        assert_eq!(timeout_to_micros(Some(Duration::ZERO)), 1);
+        assert_eq!(timeout_to_micros(Some(Duration::from_nanos(1500))), 2);
+        assert_eq!(timeout_to_micros(Some(Duration::from_millis(50))), 50_000);
+    }
+}
@@ -0,0 +1,276 @@
+//! SCSI protocols.
+//!
+//! The [`ExtScsiPassThru`] protocol sends SCSI commands to the targets and
+//! logical units of a SCSI controller. This module also contains parsers for
+//! the data returned by the INQUIRY and READ CAPACITY commands, which
+//! [`ExtScsiPassThru::inquiry`] and [`ExtScsiPassThru::read_capacity`] return
+//! directly, and for the sense data reported by failed commands.
+//!
+//! [`ExtScsiPassThru`]: pass_thru::ExtScsiPassThru
+//! [`ExtScsiPassThru::inquiry`]: pass_thru::ExtScsiPassThru::inquiry
+//! [`ExtScsiPassThru::read_capacity`]: pass_thru::ExtScsiPassThru::read_capacity
+
+pub mod pass_thru;
+
+/// Size of the standard INQUIRY data.
+pub const INQUIRY_DATA_LEN: usize = 36;
+
+/// Strip the space padding from an ASCII field.
+fn trim_ascii_field(field: &[u8]) -> &[u8] {
+    let len = field
+        .iter()
+        .rposition(|&b| b != b' ' && b != 0)
+        .map_or(0, |i| i + 1);
+    &field[..len]
+}
+
+/// Standard data returned by the INQUIRY command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InquiryData {
+    data: [u8; INQUIRY_DATA_LEN],
+}
+
+impl InquiryData {
+    /// Peripheral device type of direct access block devices, such as disks.
+    pub const DEVICE_TYPE_DISK: u8 = 0x00;
+    /// Peripheral device type of CD and DVD devices.
+    pub const DEVICE_TYPE_CD_DVD: u8 = 0x05;
+
+    /// Create from the raw data returned by the device.
+    #[must_use]
+    pub const fn new(data: [u8; INQUIRY_DATA_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; INQUIRY_DATA_LEN] {
+        &self.data
+    }
+
+    /// Peripheral qualifier. Zero means that a device is connected to the
+    /// logical unit.
+    #[must_use]
+    pub const fn peripheral_qualifier(&self) -> u8 {
+        self.data[0] >> 5
+    }
+
+    /// Peripheral device type, such as [`DEVICE_TYPE_DISK`].
+    ///
+    /// [`DEVICE_TYPE_DISK`]: Self::DEVICE_TYPE_DISK
+    #[must_use]
+    pub const fn peripheral_device_type(&self) -> u8 {
+        self.data[0] & 0x1f
+    }
+
+    /// Check whether the medium is removable.
+    #[must_use]
+    pub const fn is_removable(&self) -> bool {
+        self.data[1] & 0x80 != 0
+    }
+
+    /// Version of the SCSI standard implemented by the device.
+    #[must_use]
+    pub const fn version(&self) -> u8 {
+        self.data[2]
+    }
+
+    /// Vendor identification, without padding.
+    #[must_use]
+    pub fn vendor_id(&self) -> &[u8] {
+        trim_ascii_field(&self.data[8..16])
+    }
+
+    /// Product identification, without padding.
+    #[must_use]
+    pub fn product_id(&self) -> &[u8] {
+        trim_ascii_field(&self.data[16..32])
+    }
+
+    /// Product revision level, without padding.
+    #[must_use]
+    pub fn product_revision(&self) -> &[u8] {
+        trim_ascii_field(&self.data[32..36])
+    }
+}
+
+/// Capacity of a logical unit, returned by the READ CAPACITY commands.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Capacity {
+    /// Address of the last logical block.
+    pub last_lba: u64,
+
+    /// Size of a logical block in bytes.
+    pub block_size: u32,
+}
+
+impl Capacity {
+    /// Parse the data returned by READ CAPACITY (10).
+    #[must_use]
+    pub const fn from_read_capacity_10(data: &[u8; 8]) -> Self {
+        let [a, b, c, d, e, f, g, h] = *data;
+        Self {
+            last_lba: u32::from_be_bytes([a, b, c, d]) as u64,
+            block_size: u32::from_be_bytes([e, f, g, h]),
+        }
+    }
+
+    /// Parse the data returned by READ CAPACITY (16).
+    #[must_use]
+    pub const fn from_read_capacity_16(data: &[u8; 32]) -> Self {
+        let d = data;
+        Self {
+            last_lba: u64::from_be_bytes([d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]]),
+            block_size: u32::from_be_bytes([d[8], d[9], d[10], d[11]]),
+        }
+    }
+
+    /// Number of logical blocks.
+    #[must_use]
+    pub const fn block_count(&self) -> u64 {
+        self.last_lba.saturating_add(1)
+    }
+}
+
+newtype_enum! {
+    /// Category of the error reported in [`SenseData`].
+    pub enum SenseKey: u8 => {
+        /// No error.
+        NO_SENSE = 0x00,
+        /// The command succeeded after recovering from an error.
+        RECOVERED_ERROR = 0x01,
+        /// The logical unit is not accessible.
+        NOT_READY = 0x02,
+        /// The command failed because of a flaw in the medium.
+        MEDIUM_ERROR = 0x03,
+        /// The command failed because of a hardware failure.
+        HARDWARE_ERROR = 0x04,
+        /// The command or its parameters are invalid.
+        ILLEGAL_REQUEST = 0x05,
+        /// The device was reset or its medium changed, and the command
+        /// should be retried.
+        UNIT_ATTENTION = 0x06,
+        /// The medium is write protected.
+        DATA_PROTECT = 0x07,
+        /// Blank or non-blank medium was found unexpectedly.
+        BLANK_CHECK = 0x08,
+        /// Vendor-specific error.
+        VENDOR_SPECIFIC = 0x09,
+        /// A copy command was aborted.
+        COPY_ABORTED = 0x0a,
+        /// The device aborted the command.
+        ABORTED_COMMAND = 0x0b,
+        /// The end of the medium was reached.
+        VOLUME_OVERFLOW = 0x0d,
+        /// The data on the medium does not match the source data.
+        MISCOMPARE = 0x0e,
+        /// The command completed.
+        COMPLETED = 0x0f,
+    }
+}
+
+/// Sense data describing why a command failed, returned along with the
+/// [`CHECK_CONDITION`] target status.
+///
+/// [`CHECK_CONDITION`]: pass_thru::TargetStatus::CHECK_CONDITION
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct SenseData {
+    /// Category of the error.
+    pub key: SenseKey,
+
+    /// Additional sense code (ASC) describing the error.
+    pub additional_code: u8,
+
+    /// Additional sense code qualifier (ASCQ) with details on the
+    /// additional sense code.
+    pub additional_code_qualifier: u8,
+}
+
+impl SenseData {
+    /// Parse sense data in fixed or descriptor format. Returns `None` if the
+    /// data is truncated or in an unknown format.
+    #[must_use]
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let (key, asc, ascq) = match data.first()? & 0x7f {
+            0x70 | 0x71 => (*data.get(2)?, *data.get(12)?, *data.get(13)?),
+            0x72 | 0x73 => (*data.get(1)?, *data.get(2)?, *data.get(3)?),
+            _ => return None,
+        };
+        Some(Self {
+            key: SenseKey(key & 0x0f),
+            additional_code: asc,
+            additional_code_qualifier: ascq,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inquiry_data() {
+        let mut data = [0; INQUIRY_DATA_LEN];
+        data[1] = 0x80;
+        data[2] = 5;
+        data[8..16].copy_from_slice(b"QEMU    ");
+        data[16..32].copy_from_slice(b"QEMU HARDDISK   ");
+        data[32..36].copy_from_slice(b"2.5+");
+
+        let inquiry = InquiryData::new(data);
+        assert_eq!(inquiry.peripheral_qualifier(), 0);
+        assert_eq!(
+            inquiry.peripheral_device_type(),
+            InquiryData::DEVICE_TYPE_DISK
+        );
+        assert!(inquiry.is_removable());
+        assert_eq!(inquiry.version(), 5);
+        assert_eq!(inquiry.vendor_id(), b"QEMU");
+        assert_eq!(inquiry.product_id(), b"QEMU HARDDISK");
+        assert_eq!(inquiry.product_revision(), b"2.5+");
+    }
+
+    #[test]
+    fn test_capacity() {
+        let capacity = Capacity::from_read_capacity_10(&[0, 0, 0x07, 0xff, 0, 0, 0x02, 0]);
+        assert_eq!(capacity.last_lba, 0x7ff);
+        assert_eq!(capacity.block_size, 512);
+        assert_eq!(capacity.block_count(), 0x800);
+
+        let mut data = [0; 32];
+        data[0..8].copy_from_slice(&0x1_0000_0000u64.to_be_bytes());
+        data[8..12].copy_from_slice(&4096u32.to_be_bytes());
+        let capacity = Capacity::from_read_capacity_16(&data);
+        assert_eq!(capacity.last_lba, 0x1_0000_0000);
+        assert_eq!(capacity.block_size, 4096);
+    }
+
+    #[test]
+    fn test_sense_data() {
+        let mut fixed = [0; 18];
+        fixed[0] = 0xf0;
+        fixed[2] = 0x06;
+        fixed[12] = 0x29;
+        assert_eq!(
+            SenseData::parse(&fixed),
+            Some(SenseData {
+                key: SenseKey::UNIT_ATTENTION,
+                additional_code: 0x29,
+                additional_code_qualifier: 0,
+            })
+        );
+
+        let descriptor = [0x72, 0x05, 0x24, 0x01];
+        assert_eq!(
+            SenseData::parse(&descriptor),
+            Some(SenseData {
+                key: SenseKey::ILLEGAL_REQUEST,
+                additional_code: 0x24,
+                additional_code_qualifier: 0x01,
+            })
+        );
+
+        assert_eq!(SenseData::parse(&fixed[..10]), None);
+        assert_eq!(SenseData::parse(&[]), None);
+    }
+}
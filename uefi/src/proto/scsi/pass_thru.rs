@@ -0,0 +1,488 @@
+//! Extended SCSI Pass Thru protocol.
+//!
+//! [`ExtScsiPassThru`] is installed on SCSI controller handles, and can send
+//! any SCSI command to the logical units of the attached targets. For
+//! example, to list the disks of all controllers:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::scsi::pass_thru::ExtScsiPassThru;
+//! # fn test() -> uefi::Result {
+//!
+//! for handle in boot::find_handles::<ExtScsiPassThru>()? {
+//!     let mut scsi = boot::open_protocol_exclusive::<ExtScsiPassThru>(handle)?;
+//!     let devices: Vec<_> = scsi.devices().collect();
+//!     for device in devices {
+//!         let inquiry = scsi.inquiry(device)?;
+//!         let capacity = scsi.read_capacity(device)?;
+//!         log::info!(
+//!             "LUN {}: {:?}, {} blocks",
+//!             device.lun,
+//!             core::str::from_utf8(inquiry.product_id()),
+//!             capacity.block_count()
+//!         );
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::duration;
+use crate::proto::device_path::DevicePath;
+use crate::proto::unsafe_protocol;
+use crate::{Error, Result, Status, StatusExt};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::scsi::{
+    ExtScsiDataDirection, ExtScsiPassThruProtocol, ExtScsiPassThruScsiRequestPacket,
+};
+
+#[cfg(feature = "alloc")]
+use {
+    super::{Capacity, InquiryData, SenseData, SenseKey, INQUIRY_DATA_LEN},
+    crate::boot,
+    crate::mem::AlignedBuffer,
+    crate::proto::device_path::FfiDevicePath,
+    alloc::boxed::Box,
+    core::ptr::NonNull,
+};
+
+pub use uefi_raw::protocol::scsi::{
+    ExtScsiPassThruAttributes, ExtScsiPassThruMode, HostAdapterStatus, TargetStatus,
+    TARGET_MAX_BYTES,
+};
+
+/// Target address that starts target enumeration.
+const TARGET_START: [u8; TARGET_MAX_BYTES] = [0xff; TARGET_MAX_BYTES];
+
+/// Size of the sense buffer used by the typed helpers.
+#[cfg(feature = "alloc")]
+const HELPER_SENSE_DATA_LEN: usize = 96;
+
+/// Timeout of the commands sent by the typed helpers.
+#[cfg(feature = "alloc")]
+const HELPER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of times the typed helpers send a command that fails with
+/// [`SenseKey::UNIT_ATTENTION`], which devices report once after a reset.
+#[cfg(feature = "alloc")]
+const HELPER_ATTEMPTS: usize = 3;
+
+/// A logical unit of a target attached to a SCSI controller.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ScsiDevice {
+    /// Address of the target. Its format depends on the bus, and unused
+    /// bytes are zero.
+    pub target: [u8; TARGET_MAX_BYTES],
+
+    /// Logical unit number (LUN).
+    pub lun: u64,
+}
+
+/// A SCSI command, submitted with [`ExtScsiPassThru::execute_command`].
+///
+/// The data direction of the command follows from its buffers: setting both
+/// a read and a write buffer sends a bidirectional command.
+#[derive(Debug)]
+pub struct ScsiRequest<'buf> {
+    cdb: &'buf [u8],
+    read_buffer: Option<&'buf mut [u8]>,
+    write_buffer: Option<&'buf [u8]>,
+    sense_buffer: Option<&'buf mut [u8]>,
+    timeout: Duration,
+}
+
+impl<'buf> ScsiRequest<'buf> {
+    /// Create a request for the command descriptor block `cdb`.
+    #[must_use]
+    pub const fn new(cdb: &'buf [u8]) -> Self {
+        Self {
+            cdb,
+            read_buffer: None,
+            write_buffer: None,
+            sense_buffer: None,
+            timeout: Duration::ZERO,
+        }
+    }
+
+    /// Set the buffer that data is read into. It must be aligned to
+    /// [`ExtScsiPassThru::io_align`].
+    #[must_use]
+    pub fn with_read_buffer(mut self, buffer: &'buf mut [u8]) -> Self {
+        self.read_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the buffer that data is written from. It must be aligned to
+    /// [`ExtScsiPassThru::io_align`].
+    #[must_use]
+    pub const fn with_write_buffer(mut self, buffer: &'buf [u8]) -> Self {
+        self.write_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the buffer that receives the sense data if the command fails,
+    /// which can be parsed with [`SenseData::parse`]. It must be aligned to
+    /// [`ExtScsiPassThru::io_align`], and at most 255 bytes are used.
+    ///
+    /// [`SenseData::parse`]: super::SenseData::parse
+    #[must_use]
+    pub fn with_sense_buffer(mut self, buffer: &'buf mut [u8]) -> Self {
+        self.sense_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the timeout of the command. The default of [`Duration::ZERO`]
+    /// waits indefinitely.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Result of a sent [`ScsiRequest`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScsiResponse {
+    /// Status reported by the controller.
+    pub host_adapter_status: HostAdapterStatus,
+
+    /// Status reported by the target.
+    pub target_status: TargetStatus,
+
+    /// Number of bytes read into the read buffer.
+    pub in_transfer_length: usize,
+
+    /// Number of bytes written from the write buffer.
+    pub out_transfer_length: usize,
+
+    /// Number of bytes of sense data in the sense buffer.
+    pub sense_data_length: usize,
+}
+
+impl ScsiResponse {
+    /// Check whether both the controller and the target reported success.
+    #[must_use]
+    pub fn is_good(&self) -> bool {
+        self.host_adapter_status == HostAdapterStatus::OK
+            && self.target_status == TargetStatus::GOOD
+    }
+}
+
+/// Extended SCSI Pass Thru protocol.
+///
+/// Corresponds to the `EFI_EXT_SCSI_PASS_THRU_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(ExtScsiPassThruProtocol::GUID)]
+pub struct ExtScsiPassThru(ExtScsiPassThruProtocol);
+
+impl ExtScsiPassThru {
+    /// Get the mode of the controller.
+    #[must_use]
+    pub const fn mode(&self) -> ExtScsiPassThruMode {
+        unsafe { *self.0.mode }
+    }
+
+    /// Get the alignment required for data and sense buffers. Values of 0
+    /// and 1 mean that buffers don't need to be aligned.
+    #[must_use]
+    pub const fn io_align(&self) -> u32 {
+        self.mode().io_align
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes aligned to
+    /// [`io_align`](Self::io_align).
+    #[cfg(feature = "alloc")]
+    pub fn alloc_io_buffer(&self, len: usize) -> Result<AlignedBuffer> {
+        AlignedBuffer::from_size_align(len, self.io_align() as usize)
+            .map_err(|_| Status::BAD_BUFFER_SIZE.into())
+    }
+
+    /// Get an iterator over the addresses of the targets attached to the
+    /// controller.
+    #[must_use]
+    pub const fn targets(&self) -> ScsiTargetIter<'_> {
+        ScsiTargetIter {
+            pass_thru: self,
+            target: TARGET_START,
+        }
+    }
+
+    /// Get an iterator over the logical units of all targets attached to
+    /// the controller.
+    #[must_use]
+    pub const fn devices(&self) -> ScsiDeviceIter<'_> {
+        ScsiDeviceIter {
+            pass_thru: self,
+            device: ScsiDevice {
+                target: TARGET_START,
+                lun: 0,
+            },
+        }
+    }
+
+    /// Get the device path node of `device`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the device does not exist.
+    #[cfg(feature = "alloc")]
+    pub fn device_path(&self, device: ScsiDevice) -> Result<Box<DevicePath>> {
+        let mut device_path = ptr::null_mut();
+        unsafe {
+            (self.0.build_device_path)(
+                &self.0,
+                device.target.as_ptr(),
+                device.lun,
+                &mut device_path,
+            )
+        }
+        .to_result()?;
+        let device_path = NonNull::new(device_path).ok_or(Status::NOT_FOUND)?;
+        let boxed =
+            unsafe { DevicePath::from_ffi_ptr(device_path.as_ptr().cast::<FfiDevicePath>()) }
+                .to_boxed();
+        let _ = unsafe { boot::free_pool(device_path.cast()) };
+        Ok(boxed)
+    }
+
+    /// Get the device with the device path node `device_path`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `device_path` is not supported by the
+    ///   controller.
+    /// * [`Status::NOT_FOUND`]: the device does not exist.
+    pub fn device(&self, device_path: &DevicePath) -> Result<ScsiDevice> {
+        let mut device = ScsiDevice {
+            target: [0; TARGET_MAX_BYTES],
+            lun: 0,
+        };
+        let mut target = device.target.as_mut_ptr();
+        unsafe {
+            (self.0.get_target_lun)(
+                &self.0,
+                device_path.as_ffi_ptr().cast(),
+                &mut target,
+                &mut device.lun,
+            )
+        }
+        .to_result_with_val(|| device)
+    }
+
+    /// Reset the SCSI channel of the controller.
+    pub fn reset_channel(&mut self) -> Result {
+        unsafe { (self.0.reset_channel)(&mut self.0) }.to_result()
+    }
+
+    /// Reset `device`.
+    pub fn reset_device(&mut self, device: ScsiDevice) -> Result {
+        unsafe { (self.0.reset_target_lun)(&mut self.0, device.target.as_ptr(), device.lun) }
+            .to_result()
+    }
+
+    /// Send `request` to `device`, and wait for it to complete.
+    ///
+    /// A command that was sent can still fail, so check
+    /// [`ScsiResponse::is_good`]. If the controller reports an error, the
+    /// error data holds the response if the command was sent.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: a buffer is not aligned to
+    ///   [`io_align`](Self::io_align), or the command is invalid.
+    /// * [`Status::BAD_BUFFER_SIZE`]: a buffer is too large for the
+    ///   controller.
+    /// * [`Status::NOT_READY`]: the command could not be sent because the
+    ///   controller is busy.
+    /// * [`Status::DEVICE_ERROR`]: the controller failed to send the
+    ///   command.
+    /// * [`Status::UNSUPPORTED`]: the command is not supported by the
+    ///   controller.
+    /// * [`Status::TIMEOUT`]: the command did not complete in time.
+    pub fn execute_command(
+        &mut self,
+        device: ScsiDevice,
+        request: ScsiRequest<'_>,
+    ) -> Result<ScsiResponse, Option<ScsiResponse>> {
+        let align = self.io_align().max(1) as usize;
+        let mut packet =
+            request_packet(request, align).map_err(|err| Error::new(err.status(), None))?;
+        let status = unsafe {
+            (self.0.pass_thru)(
+                &mut self.0,
+                device.target.as_ptr(),
+                device.lun,
+                &mut packet,
+                ptr::null_mut(),
+            )
+        };
+        let response = ScsiResponse {
+            host_adapter_status: packet.host_adapter_status,
+            target_status: packet.target_status,
+            in_transfer_length: packet.in_transfer_length as usize,
+            out_transfer_length: packet.out_transfer_length as usize,
+            sense_data_length: usize::from(packet.sense_data_length),
+        };
+        // The packet's status fields are only valid if the command was sent.
+        status.to_result_with(
+            || response,
+            |status| (status == Status::DEVICE_ERROR).then_some(response),
+        )
+    }
+
+    /// Send a command that reads `N` bytes, retrying if the device reports
+    /// a unit attention.
+    #[cfg(feature = "alloc")]
+    fn read_data<const N: usize>(&mut self, device: ScsiDevice, cdb: &[u8]) -> Result<[u8; N]> {
+        let mut buffer = self.alloc_io_buffer(N)?;
+        let mut sense = self.alloc_io_buffer(HELPER_SENSE_DATA_LEN)?;
+        for _ in 0..HELPER_ATTEMPTS {
+            let request = ScsiRequest::new(cdb)
+                .with_read_buffer(buffer.as_mut_slice())
+                .with_sense_buffer(sense.as_mut_slice())
+                .with_timeout(HELPER_TIMEOUT);
+            let response = self
+                .execute_command(device, request)
+                .map_err(|err| Error::from(err.status()))?;
+            if response.is_good() {
+                let mut data = [0; N];
+                data.copy_from_slice(buffer.as_slice());
+                return Ok(data);
+            }
+
+            let sense = SenseData::parse(&sense.as_slice()[..response.sense_data_length]);
+            if !sense.is_some_and(|sense| sense.key == SenseKey::UNIT_ATTENTION) {
+                break;
+            }
+        }
+        Err(Status::DEVICE_ERROR.into())
+    }
+
+    /// Get the standard INQUIRY data of `device`.
+    #[cfg(feature = "alloc")]
+    pub fn inquiry(&mut self, device: ScsiDevice) -> Result<InquiryData> {
+        let cdb = [0x12, 0, 0, 0, INQUIRY_DATA_LEN as u8, 0];
+        self.read_data(device, &cdb).map(InquiryData::new)
+    }
+
+    /// Get the capacity of `device`. READ CAPACITY (16) is used if the
+    /// device has too many blocks for READ CAPACITY (10).
+    #[cfg(feature = "alloc")]
+    pub fn read_capacity(&mut self, device: ScsiDevice) -> Result<Capacity> {
+        let cdb = [0x25, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let capacity = Capacity::from_read_capacity_10(&self.read_data(device, &cdb)?);
+        if capacity.last_lba != u64::from(u32::MAX) {
+            return Ok(capacity);
+        }
+
+        // SERVICE ACTION IN (16) with the READ CAPACITY (16) service action,
+        // and an allocation length of 32 bytes.
+        let cdb = [0x9e, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0];
+        self.read_data(device, &cdb)
+            .map(|data| Capacity::from_read_capacity_16(&data))
+    }
+}
+
+/// Build the request packet for `request`, checking that its buffers are
+/// aligned to `align`.
+fn request_packet(
+    request: ScsiRequest<'_>,
+    align: usize,
+) -> Result<ExtScsiPassThruScsiRequestPacket> {
+    let cdb_length = u8::try_from(request.cdb.len()).map_err(|_| Status::INVALID_PARAMETER)?;
+    let data_direction = match (&request.read_buffer, &request.write_buffer) {
+        (Some(_), Some(_)) => ExtScsiDataDirection::BIDIRECTIONAL,
+        (None, Some(_)) => ExtScsiDataDirection::WRITE,
+        _ => ExtScsiDataDirection::READ,
+    };
+    let (in_buffer, in_len) = raw_buffer(request.read_buffer, align)?;
+    let (out_buffer, out_len) = match request.write_buffer {
+        Some(buffer) => {
+            if buffer.as_ptr() as usize % align != 0 {
+                return Err(Status::INVALID_PARAMETER.into());
+            }
+            let len = u32::try_from(buffer.len()).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+            (buffer.as_ptr(), len)
+        }
+        None => (ptr::null(), 0),
+    };
+    let (sense_buffer, sense_len) = match request.sense_buffer {
+        // The sense data length is a `u8`, so use at most 255 bytes.
+        Some(buffer) => {
+            let len = buffer.len().min(usize::from(u8::MAX));
+            raw_buffer(Some(&mut buffer[..len]), align)?
+        }
+        None => (ptr::null_mut(), 0),
+    };
+
+    Ok(ExtScsiPassThruScsiRequestPacket {
+        timeout: duration::as_timer_units(request.timeout),
+        in_data_buffer: in_buffer.cast(),
+        out_data_buffer: out_buffer.cast(),
+        sense_data: sense_buffer.cast(),
+        cdb: request.cdb.as_ptr().cast(),
+        in_transfer_length: in_len,
+        out_transfer_length: out_len,
+        cdb_length,
+        data_direction,
+        host_adapter_status: HostAdapterStatus::OK,
+        target_status: TargetStatus::GOOD,
+        sense_data_length: sense_len as u8,
+    })
+}
+
+/// Get the pointer and length of an optional buffer for a request packet.
+fn raw_buffer(buffer: Option<&mut [u8]>, align: usize) -> Result<(*mut u8, u32)> {
+    let Some(buffer) = buffer else {
+        return Ok((ptr::null_mut(), 0));
+    };
+    if buffer.as_ptr() as usize % align != 0 {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let len = u32::try_from(buffer.len()).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+    Ok((buffer.as_mut_ptr(), len))
+}
+
+/// Iterator over the target addresses of an [`ExtScsiPassThru`] controller,
+/// returned by [`ExtScsiPassThru::targets`].
+#[derive(Debug)]
+pub struct ScsiTargetIter<'a> {
+    pass_thru: &'a ExtScsiPassThru,
+    target: [u8; TARGET_MAX_BYTES],
+}
+
+impl Iterator for ScsiTargetIter<'_> {
+    type Item = [u8; TARGET_MAX_BYTES];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let proto = &self.pass_thru.0;
+        let mut target = self.target.as_mut_ptr();
+        unsafe { (proto.get_next_target)(proto, &mut target) }
+            .to_result()
+            .ok()?;
+        Some(self.target)
+    }
+}
+
+/// Iterator over the logical units of an [`ExtScsiPassThru`] controller,
+/// returned by [`ExtScsiPassThru::devices`].
+#[derive(Debug)]
+pub struct ScsiDeviceIter<'a> {
+    pass_thru: &'a ExtScsiPassThru,
+    device: ScsiDevice,
+}
+
+impl Iterator for ScsiDeviceIter<'_> {
+    type Item = ScsiDevice;
+
+    fn next(&mut self) -> Option<ScsiDevice> {
+        let proto = &self.pass_thru.0;
+        let mut target = self.device.target.as_mut_ptr();
+        unsafe { (proto.get_next_target_lun)(proto, &mut target, &mut self.device.lun) }
+            .to_result()
+            .ok()?;
+        Some(self.device)
+    }
+}
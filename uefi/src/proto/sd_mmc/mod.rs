@@ -0,0 +1,439 @@
+//! SD and MMC protocols.
+//!
+//! The [`SdMmcPassThru`] protocol sends SD and eMMC commands to the cards in
+//! the slots of a host controller. This module also contains parsers for the
+//! card registers returned by those commands.
+//!
+//! [`SdMmcPassThru`]: pass_thru::SdMmcPassThru
+
+pub mod pass_thru;
+
+/// Size of the eMMC EXT_CSD register.
+pub const EXT_CSD_LEN: usize = 512;
+
+/// Get bits `high` to `low` (inclusive) of a 128-bit card register.
+const fn bits(value: u128, high: u32, low: u32) -> u32 {
+    ((value >> low) & ((1 << (high - low + 1)) - 1)) as u32
+}
+
+/// Get the `N` ASCII characters of a 128-bit card register that end at bit
+/// `low`.
+const fn chars<const N: usize>(value: u128, low: u32) -> [u8; N] {
+    let mut chars = [0; N];
+    let mut i = 0;
+    while i < N {
+        chars[i] = (value >> (low + 8 * (N - 1 - i) as u32)) as u8;
+        i += 1;
+    }
+    chars
+}
+
+/// Card status, returned in R1 responses.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+pub struct CardStatus(pub u32);
+
+impl CardStatus {
+    /// Bits that report an error in the previous or current command.
+    pub const ERROR_MASK: u32 = 0xfdf9_8088;
+
+    /// State of the card, such as 3 for stand-by or 4 for transfer.
+    #[must_use]
+    pub const fn current_state(self) -> u8 {
+        ((self.0 >> 9) & 0xf) as u8
+    }
+
+    /// Check whether the card is ready to receive data.
+    #[must_use]
+    pub const fn is_ready_for_data(self) -> bool {
+        self.0 & (1 << 8) != 0
+    }
+
+    /// Check whether any error bit is set.
+    #[must_use]
+    pub const fn has_error(self) -> bool {
+        self.0 & Self::ERROR_MASK != 0
+    }
+}
+
+/// Card identification (CID) register of an SD card.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SdCid(u128);
+
+impl SdCid {
+    /// Create from the raw register value.
+    #[must_use]
+    pub const fn new(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Get the raw register value.
+    #[must_use]
+    pub const fn raw(&self) -> u128 {
+        self.0
+    }
+
+    /// Manufacturer ID (MID).
+    #[must_use]
+    pub const fn manufacturer_id(&self) -> u8 {
+        bits(self.0, 127, 120) as u8
+    }
+
+    /// OEM and application ID (OID), as two ASCII characters.
+    #[must_use]
+    pub const fn oem_id(&self) -> [u8; 2] {
+        chars(self.0, 104)
+    }
+
+    /// Product name (PNM), as five ASCII characters.
+    #[must_use]
+    pub const fn product_name(&self) -> [u8; 5] {
+        chars(self.0, 64)
+    }
+
+    /// Product revision (PRV), with the major version in the upper four
+    /// bits.
+    #[must_use]
+    pub const fn product_revision(&self) -> u8 {
+        bits(self.0, 63, 56) as u8
+    }
+
+    /// Product serial number (PSN).
+    #[must_use]
+    pub const fn serial_number(&self) -> u32 {
+        bits(self.0, 55, 24)
+    }
+
+    /// Manufacturing date (MDT) as year and month.
+    #[must_use]
+    pub const fn manufacturing_date(&self) -> (u16, u8) {
+        (
+            2000 + bits(self.0, 19, 12) as u16,
+            bits(self.0, 11, 8) as u8,
+        )
+    }
+}
+
+/// Card identification (CID) register of an eMMC device.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MmcCid(u128);
+
+impl MmcCid {
+    /// Create from the raw register value.
+    #[must_use]
+    pub const fn new(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Get the raw register value.
+    #[must_use]
+    pub const fn raw(&self) -> u128 {
+        self.0
+    }
+
+    /// Manufacturer ID (MID).
+    #[must_use]
+    pub const fn manufacturer_id(&self) -> u8 {
+        bits(self.0, 127, 120) as u8
+    }
+
+    /// Device type (CBX): 0 for removable, 1 for BGA, and 2 for POP devices.
+    #[must_use]
+    pub const fn device_type(&self) -> u8 {
+        bits(self.0, 113, 112) as u8
+    }
+
+    /// OEM and application ID (OID).
+    #[must_use]
+    pub const fn oem_id(&self) -> u8 {
+        bits(self.0, 111, 104) as u8
+    }
+
+    /// Product name (PNM), as six ASCII characters.
+    #[must_use]
+    pub const fn product_name(&self) -> [u8; 6] {
+        chars(self.0, 56)
+    }
+
+    /// Product revision (PRV), with the major version in the upper four
+    /// bits.
+    #[must_use]
+    pub const fn product_revision(&self) -> u8 {
+        bits(self.0, 55, 48) as u8
+    }
+
+    /// Product serial number (PSN).
+    #[must_use]
+    pub const fn serial_number(&self) -> u32 {
+        bits(self.0, 47, 16)
+    }
+
+    /// Manufacturing date (MDT) as year and month. The encoding of the year
+    /// depends on the [`ExtCsd::revision`] of the device.
+    #[must_use]
+    pub const fn manufacturing_date(&self, ext_csd_revision: u8) -> (u16, u8) {
+        let year = bits(self.0, 11, 8) as u16;
+        // Devices newer than revision 4 count from 2013, wrapping around to
+        // the years 2010-2012 of older devices.
+        let year = if ext_csd_revision > 4 && year <= 12 {
+            2013 + year
+        } else {
+            1997 + year
+        };
+        (year, bits(self.0, 15, 12) as u8)
+    }
+}
+
+/// Card-specific data (CSD) register of an SD card.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SdCsd(u128);
+
+impl SdCsd {
+    /// Create from the raw register value.
+    #[must_use]
+    pub const fn new(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Get the raw register value.
+    #[must_use]
+    pub const fn raw(&self) -> u128 {
+        self.0
+    }
+
+    /// Version of the register layout (CSD_STRUCTURE): 0 for standard
+    /// capacity, 1 for high and extended capacity, and 2 for ultra capacity
+    /// cards.
+    #[must_use]
+    pub const fn structure(&self) -> u8 {
+        bits(self.0, 127, 126) as u8
+    }
+
+    /// Maximum read block length as a power of two (READ_BL_LEN).
+    #[must_use]
+    pub const fn read_block_length_shift(&self) -> u8 {
+        bits(self.0, 83, 80) as u8
+    }
+
+    /// Capacity of the card in bytes, or `None` if the register layout is
+    /// unknown.
+    #[must_use]
+    pub const fn capacity(&self) -> Option<u64> {
+        match self.structure() {
+            0 => {
+                let c_size = bits(self.0, 73, 62) as u64;
+                let c_size_mult = bits(self.0, 49, 47);
+                Some((c_size + 1) << (c_size_mult + 2 + self.read_block_length_shift() as u32))
+            }
+            1 => Some((bits(self.0, 69, 48) as u64 + 1) * 512 * 1024),
+            2 => Some((bits(self.0, 75, 48) as u64 + 1) * 512 * 1024),
+            _ => None,
+        }
+    }
+
+    /// Check whether the card is permanently or temporarily write protected.
+    #[must_use]
+    pub const fn is_write_protected(&self) -> bool {
+        bits(self.0, 13, 12) != 0
+    }
+}
+
+/// Card-specific data (CSD) register of an eMMC device.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MmcCsd(u128);
+
+impl MmcCsd {
+    /// Create from the raw register value.
+    #[must_use]
+    pub const fn new(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Get the raw register value.
+    #[must_use]
+    pub const fn raw(&self) -> u128 {
+        self.0
+    }
+
+    /// Version of the register layout (CSD_STRUCTURE).
+    #[must_use]
+    pub const fn structure(&self) -> u8 {
+        bits(self.0, 127, 126) as u8
+    }
+
+    /// Version of the specification implemented by the device (SPEC_VERS).
+    #[must_use]
+    pub const fn spec_version(&self) -> u8 {
+        bits(self.0, 125, 122) as u8
+    }
+
+    /// Capacity of the device in bytes, or `None` for devices larger than
+    /// 2 GiB, whose capacity is reported by [`ExtCsd::sector_count`].
+    #[must_use]
+    pub const fn capacity(&self) -> Option<u64> {
+        let c_size = bits(self.0, 73, 62) as u64;
+        if c_size == 0xfff {
+            return None;
+        }
+        let c_size_mult = bits(self.0, 49, 47);
+        let read_bl_len = bits(self.0, 83, 80);
+        Some((c_size + 1) << (c_size_mult + 2 + read_bl_len))
+    }
+}
+
+/// Extended card-specific data (EXT_CSD) register of an eMMC device.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtCsd {
+    data: [u8; EXT_CSD_LEN],
+}
+
+impl ExtCsd {
+    /// Create from the raw data returned by the device.
+    #[must_use]
+    pub const fn new(data: [u8; EXT_CSD_LEN]) -> Self {
+        Self { data }
+    }
+
+    /// Get the raw data.
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; EXT_CSD_LEN] {
+        &self.data
+    }
+
+    /// Revision of the register layout (EXT_CSD_REV).
+    #[must_use]
+    pub const fn revision(&self) -> u8 {
+        self.data[192]
+    }
+
+    /// Partition configuration (PARTITION_CONFIG). Bits 0-2 select the
+    /// partition that is accessed, where 3 is the RPMB partition.
+    #[must_use]
+    pub const fn partition_config(&self) -> u8 {
+        self.data[179]
+    }
+
+    /// Size of the replay protected memory block (RPMB) partition in bytes.
+    #[must_use]
+    pub const fn rpmb_size(&self) -> u64 {
+        self.data[168] as u64 * 128 * 1024
+    }
+
+    /// Size of each boot partition in bytes.
+    #[must_use]
+    pub const fn boot_size(&self) -> u64 {
+        self.data[226] as u64 * 128 * 1024
+    }
+
+    /// Number of 512-byte sectors of the user data area (SEC_COUNT).
+    #[must_use]
+    pub const fn sector_count(&self) -> u32 {
+        u32::from_le_bytes([
+            self.data[212],
+            self.data[213],
+            self.data[214],
+            self.data[215],
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Set bits `high` to `low` of `value` to `field`.
+    fn set_bits(value: &mut u128, high: u32, low: u32, field: u128) {
+        let mask = ((1 << (high - low + 1)) - 1) << low;
+        *value = (*value & !mask) | ((field << low) & mask);
+    }
+
+    #[test]
+    fn test_card_status() {
+        let status = CardStatus((4 << 9) | (1 << 8));
+        assert_eq!(status.current_state(), 4);
+        assert!(status.is_ready_for_data());
+        assert!(!status.has_error());
+        assert!(CardStatus(1 << 22).has_error());
+    }
+
+    #[test]
+    fn test_sd_cid() {
+        let mut raw = 0;
+        set_bits(&mut raw, 127, 120, 0x03);
+        set_bits(&mut raw, 119, 104, u128::from(u16::from_be_bytes(*b"SD")));
+        set_bits(&mut raw, 103, 64, 0x53_55_33_32_47); // "SU32G"
+        set_bits(&mut raw, 63, 56, 0x80);
+        set_bits(&mut raw, 55, 24, 0x1234_5678);
+        set_bits(&mut raw, 19, 12, 23);
+        set_bits(&mut raw, 11, 8, 6);
+
+        let cid = SdCid::new(raw);
+        assert_eq!(cid.manufacturer_id(), 0x03);
+        assert_eq!(&cid.oem_id(), b"SD");
+        assert_eq!(&cid.product_name(), b"SU32G");
+        assert_eq!(cid.product_revision(), 0x80);
+        assert_eq!(cid.serial_number(), 0x1234_5678);
+        assert_eq!(cid.manufacturing_date(), (2023, 6));
+    }
+
+    #[test]
+    fn test_mmc_cid() {
+        let mut raw = 0;
+        set_bits(&mut raw, 127, 120, 0x15);
+        set_bits(&mut raw, 113, 112, 1);
+        set_bits(&mut raw, 103, 56, 0x38_47_54_46_34_52); // "8GTF4R"
+        set_bits(&mut raw, 15, 12, 3);
+        set_bits(&mut raw, 11, 8, 5);
+
+        let cid = MmcCid::new(raw);
+        assert_eq!(cid.manufacturer_id(), 0x15);
+        assert_eq!(cid.device_type(), 1);
+        assert_eq!(&cid.product_name(), b"8GTF4R");
+        assert_eq!(cid.manufacturing_date(8), (2018, 3));
+        assert_eq!(cid.manufacturing_date(4), (2002, 3));
+    }
+
+    #[test]
+    fn test_sd_csd() {
+        let mut raw = 0;
+        set_bits(&mut raw, 127, 126, 1);
+        set_bits(&mut raw, 69, 48, 0xedc7);
+        assert_eq!(SdCsd::new(raw).capacity(), Some(0xedc8 * 512 * 1024));
+
+        let mut raw = 0;
+        set_bits(&mut raw, 83, 80, 9);
+        set_bits(&mut raw, 73, 62, 0xf13);
+        set_bits(&mut raw, 49, 47, 7);
+        set_bits(&mut raw, 12, 12, 1);
+        let csd = SdCsd::new(raw);
+        assert_eq!(csd.capacity(), Some(0xf14 << 18));
+        assert!(csd.is_write_protected());
+    }
+
+    #[test]
+    fn test_mmc_csd() {
+        let mut raw = 0;
+        set_bits(&mut raw, 125, 122, 4);
+        set_bits(&mut raw, 73, 62, 0xfff);
+        let csd = MmcCsd::new(raw);
+        assert_eq!(csd.spec_version(), 4);
+        assert_eq!(csd.capacity(), None);
+    }
+
+    #[test]
+    fn test_ext_csd() {
+        let mut data = [0; EXT_CSD_LEN];
+        data[168] = 32;
+        data[179] = 0x48;
+        data[192] = 8;
+        data[212..216].copy_from_slice(&0x00e9_0000u32.to_le_bytes());
+        data[226] = 32;
+
+        let ext_csd = ExtCsd::new(data);
+        assert_eq!(ext_csd.revision(), 8);
+        assert_eq!(ext_csd.partition_config(), 0x48);
+        assert_eq!(ext_csd.rpmb_size(), 4 * 1024 * 1024);
+        assert_eq!(ext_csd.boot_size(), 4 * 1024 * 1024);
+        assert_eq!(ext_csd.sector_count(), 0x00e9_0000);
+    }
+}
@@ -0,0 +1,390 @@
+//! SD MMC Pass Thru protocol.
+//!
+//! [`SdMmcPassThru`] is installed on SD and eMMC host controller handles,
+//! and can send any command to the card in a slot of the controller. For
+//! example, to get the size of the RPMB partition of an eMMC device:
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::sd_mmc::pass_thru::SdMmcPassThru;
+//! # fn test() -> uefi::Result {
+//!
+//! let handle = boot::get_handle_for_protocol::<SdMmcPassThru>()?;
+//! let mut sd_mmc = boot::open_protocol_exclusive::<SdMmcPassThru>(handle)?;
+//! let slots: Vec<u8> = sd_mmc.slots().collect();
+//! for slot in slots {
+//!     let ext_csd = sd_mmc.send_ext_csd(slot)?;
+//!     log::info!("slot {slot}: RPMB size: {}", ext_csd.rpmb_size());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Access to the RPMB partition itself is done by selecting it in the
+//! PARTITION_CONFIG field of the EXT_CSD register with a SWITCH command, and
+//! then sending the authenticated data frames with the SET_BLOCK_COUNT,
+//! WRITE_MULTIPLE_BLOCK, and READ_MULTIPLE_BLOCK commands.
+
+use super::CardStatus;
+use crate::duration;
+use crate::proto::device_path::DevicePath;
+use crate::proto::unsafe_protocol;
+use crate::{Result, Status, StatusExt};
+use core::ptr;
+use core::time::Duration;
+use uefi_raw::protocol::sd_mmc::{
+    SdMmcCommandBlock, SdMmcPassThruCommandPacket, SdMmcPassThruProtocol,
+};
+
+#[cfg(feature = "alloc")]
+use {
+    super::{ExtCsd, EXT_CSD_LEN},
+    crate::boot,
+    crate::mem::AlignedBuffer,
+    crate::proto::device_path::FfiDevicePath,
+    alloc::boxed::Box,
+    core::ptr::NonNull,
+};
+
+pub use uefi_raw::protocol::sd_mmc::{SdMmcCommandType, SdMmcResponseType, SdMmcStatusBlock};
+
+/// Slot number that starts slot enumeration.
+const SLOT_START: u8 = 0xff;
+
+/// Timeout of the commands sent by the typed helpers.
+const HELPER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Command indices of the commands sent by the typed helpers.
+mod command {
+    /// SEND_EXT_CSD of eMMC devices. SD cards use the same index for
+    /// SEND_IF_COND.
+    #[cfg(feature = "alloc")]
+    pub const SEND_EXT_CSD: u16 = 8;
+    pub const SEND_CSD: u16 = 9;
+    pub const SEND_CID: u16 = 10;
+    pub const SEND_STATUS: u16 = 13;
+}
+
+/// An SD or eMMC command, submitted with [`SdMmcPassThru::execute_command`].
+#[derive(Debug)]
+pub struct SdMmcCommand<'buf> {
+    command_block: SdMmcCommandBlock,
+    read_buffer: Option<&'buf mut [u8]>,
+    write_buffer: Option<&'buf [u8]>,
+    timeout: Duration,
+}
+
+impl<'buf> SdMmcCommand<'buf> {
+    /// Create a command with index `index`, such as 17 for
+    /// READ_SINGLE_BLOCK, that expects a response of `response_type`.
+    #[must_use]
+    pub const fn new(
+        index: u16,
+        command_type: SdMmcCommandType,
+        response_type: SdMmcResponseType,
+    ) -> Self {
+        Self {
+            command_block: SdMmcCommandBlock {
+                command_index: index,
+                command_argument: 0,
+                command_type,
+                response_type,
+            },
+            read_buffer: None,
+            write_buffer: None,
+            timeout: Duration::ZERO,
+        }
+    }
+
+    /// Set the argument of the command.
+    #[must_use]
+    pub const fn with_argument(mut self, argument: u32) -> Self {
+        self.command_block.command_argument = argument;
+        self
+    }
+
+    /// Set the buffer that data is read into. It must be aligned to
+    /// [`SdMmcPassThru::io_align`].
+    #[must_use]
+    pub fn with_read_buffer(mut self, buffer: &'buf mut [u8]) -> Self {
+        self.read_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the buffer that data is written from. It must be aligned to
+    /// [`SdMmcPassThru::io_align`].
+    #[must_use]
+    pub const fn with_write_buffer(mut self, buffer: &'buf [u8]) -> Self {
+        self.write_buffer = Some(buffer);
+        self
+    }
+
+    /// Set the timeout of the command. The default of [`Duration::ZERO`]
+    /// waits indefinitely.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Result of a completed [`SdMmcCommand`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SdMmcResponse {
+    /// Raw response of the card.
+    pub status_block: SdMmcStatusBlock,
+
+    /// Number of bytes read into the read buffer.
+    pub in_transfer_length: usize,
+
+    /// Number of bytes written from the write buffer.
+    pub out_transfer_length: usize,
+}
+
+impl SdMmcResponse {
+    /// Get the card status of an R1, R1b, or R5 response.
+    #[must_use]
+    pub const fn card_status(&self) -> CardStatus {
+        CardStatus(self.status_block.resp0)
+    }
+
+    /// Get the 128-bit register of an R2 response, such as the CID or CSD
+    /// register. The CRC in bits 0-7 is not part of the response, and is
+    /// zero.
+    #[must_use]
+    pub const fn register(&self) -> u128 {
+        let block = &self.status_block;
+        ((block.resp0 as u128)
+            | (block.resp1 as u128) << 32
+            | (block.resp2 as u128) << 64
+            | (block.resp3 as u128) << 96)
+            << 8
+    }
+
+    /// Get the operation conditions register (OCR) of an R3 response.
+    #[must_use]
+    pub const fn ocr(&self) -> u32 {
+        self.status_block.resp0
+    }
+
+    /// Get the relative card address (RCA) of an R6 response.
+    #[must_use]
+    pub const fn rca(&self) -> u16 {
+        (self.status_block.resp0 >> 16) as u16
+    }
+}
+
+/// SD MMC Pass Thru protocol.
+///
+/// Corresponds to the `EFI_SD_MMC_PASS_THRU_PROTOCOL` in the C API.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(SdMmcPassThruProtocol::GUID)]
+pub struct SdMmcPassThru(SdMmcPassThruProtocol);
+
+impl SdMmcPassThru {
+    /// Get the alignment required for data buffers. Values of 0 and 1 mean
+    /// that buffers don't need to be aligned.
+    #[must_use]
+    pub const fn io_align(&self) -> usize {
+        self.0.io_align
+    }
+
+    /// Allocate a zeroed buffer of `len` bytes aligned to
+    /// [`io_align`](Self::io_align).
+    #[cfg(feature = "alloc")]
+    pub fn alloc_io_buffer(&self, len: usize) -> Result<AlignedBuffer> {
+        AlignedBuffer::from_size_align(len, self.io_align())
+            .map_err(|_| Status::BAD_BUFFER_SIZE.into())
+    }
+
+    /// Get an iterator over the slots of the controller that have a card
+    /// inserted.
+    #[must_use]
+    pub const fn slots(&self) -> SdMmcSlotIter<'_> {
+        SdMmcSlotIter {
+            pass_thru: self,
+            slot: SLOT_START,
+        }
+    }
+
+    /// Get the device path node of `slot`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::NOT_FOUND`]: the slot does not exist.
+    #[cfg(feature = "alloc")]
+    pub fn device_path(&self, slot: u8) -> Result<Box<DevicePath>> {
+        let mut device_path = ptr::null_mut();
+        unsafe { (self.0.build_device_path)(&self.0, slot, &mut device_path) }.to_result()?;
+        let device_path = NonNull::new(device_path).ok_or(Status::NOT_FOUND)?;
+        let boxed =
+            unsafe { DevicePath::from_ffi_ptr(device_path.as_ptr().cast::<FfiDevicePath>()) }
+                .to_boxed();
+        let _ = unsafe { boot::free_pool(device_path.cast()) };
+        Ok(boxed)
+    }
+
+    /// Get the slot with the device path node `device_path`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: `device_path` is not an SD or eMMC device
+    ///   node.
+    /// * [`Status::NOT_FOUND`]: the slot does not exist.
+    pub fn slot(&self, device_path: &DevicePath) -> Result<u8> {
+        let mut slot = 0;
+        unsafe { (self.0.get_slot_number)(&self.0, device_path.as_ffi_ptr().cast(), &mut slot) }
+            .to_result_with_val(|| slot)
+    }
+
+    /// Reset the card in `slot`.
+    pub fn reset_device(&mut self, slot: u8) -> Result {
+        unsafe { (self.0.reset_device)(&mut self.0, slot) }.to_result()
+    }
+
+    /// Send `command` to the card in `slot`, and wait for it to complete.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::INVALID_PARAMETER`]: a buffer is not aligned to
+    ///   [`io_align`](Self::io_align), or the command is invalid.
+    /// * [`Status::BAD_BUFFER_SIZE`]: a buffer is too large for the
+    ///   controller.
+    /// * [`Status::NO_MEDIA`]: there is no card in the slot.
+    /// * [`Status::DEVICE_ERROR`]: the command failed.
+    /// * [`Status::TIMEOUT`]: the command did not complete in time.
+    pub fn execute_command(
+        &mut self,
+        slot: u8,
+        command: SdMmcCommand<'_>,
+    ) -> Result<SdMmcResponse> {
+        let align = self.io_align().max(1);
+        let (in_buffer, in_len) = match command.read_buffer {
+            Some(buffer) => raw_buffer(buffer.as_mut_ptr(), buffer.len(), align)?,
+            None => (ptr::null_mut(), 0),
+        };
+        // The driver only reads from the output buffer.
+        let (out_buffer, out_len) = match command.write_buffer {
+            Some(buffer) => raw_buffer(buffer.as_ptr().cast_mut(), buffer.len(), align)?,
+            None => (ptr::null_mut(), 0),
+        };
+
+        let mut command_block = command.command_block;
+        let mut status_block = SdMmcStatusBlock::default();
+        let mut packet = SdMmcPassThruCommandPacket {
+            sd_mmc_cmd_blk: &mut command_block,
+            sd_mmc_status_blk: &mut status_block,
+            timeout: duration::as_timer_units(command.timeout),
+            in_data_buffer: in_buffer.cast(),
+            out_data_buffer: out_buffer.cast(),
+            in_transfer_length: in_len,
+            out_transfer_length: out_len,
+            transaction_status: Status::SUCCESS,
+        };
+        unsafe { (self.0.pass_thru)(&mut self.0, slot, &mut packet, ptr::null_mut()) }
+            .to_result()?;
+        packet
+            .transaction_status
+            .to_result_with_val(|| SdMmcResponse {
+                status_block,
+                in_transfer_length: packet.in_transfer_length as usize,
+                out_transfer_length: packet.out_transfer_length as usize,
+            })
+    }
+
+    /// Send a command addressed to the card with relative address `rca`.
+    fn addressed_command(
+        &mut self,
+        slot: u8,
+        index: u16,
+        response_type: SdMmcResponseType,
+        rca: u16,
+    ) -> Result<SdMmcResponse> {
+        let command = SdMmcCommand::new(index, SdMmcCommandType::AC, response_type)
+            .with_argument(u32::from(rca) << 16)
+            .with_timeout(HELPER_TIMEOUT);
+        self.execute_command(slot, command)
+    }
+
+    /// Get the status of the card in `slot` with relative address `rca`.
+    pub fn send_status(&mut self, slot: u8, rca: u16) -> Result<CardStatus> {
+        self.addressed_command(slot, command::SEND_STATUS, SdMmcResponseType::R1, rca)
+            .map(|response| response.card_status())
+    }
+
+    /// Get the raw CID register of the card in `slot` with relative address
+    /// `rca`. It can be parsed with [`SdCid`] or [`MmcCid`].
+    ///
+    /// The card must be in the stand-by state, so it must be deselected
+    /// first if it is in the transfer state.
+    ///
+    /// [`SdCid`]: super::SdCid
+    /// [`MmcCid`]: super::MmcCid
+    pub fn send_cid(&mut self, slot: u8, rca: u16) -> Result<u128> {
+        self.addressed_command(slot, command::SEND_CID, SdMmcResponseType::R2, rca)
+            .map(|response| response.register())
+    }
+
+    /// Get the raw CSD register of the card in `slot` with relative address
+    /// `rca`. It can be parsed with [`SdCsd`] or [`MmcCsd`].
+    ///
+    /// The card must be in the stand-by state, so it must be deselected
+    /// first if it is in the transfer state.
+    ///
+    /// [`SdCsd`]: super::SdCsd
+    /// [`MmcCsd`]: super::MmcCsd
+    pub fn send_csd(&mut self, slot: u8, rca: u16) -> Result<u128> {
+        self.addressed_command(slot, command::SEND_CSD, SdMmcResponseType::R2, rca)
+            .map(|response| response.register())
+    }
+
+    /// Get the EXT_CSD register of the eMMC device in `slot`. The device
+    /// must be in the transfer state, which is the case after the firmware
+    /// initialized it.
+    #[cfg(feature = "alloc")]
+    pub fn send_ext_csd(&mut self, slot: u8) -> Result<ExtCsd> {
+        let mut buffer = self.alloc_io_buffer(EXT_CSD_LEN)?;
+        let command = SdMmcCommand::new(
+            command::SEND_EXT_CSD,
+            SdMmcCommandType::ADTC,
+            SdMmcResponseType::R1,
+        )
+        .with_read_buffer(buffer.as_mut_slice())
+        .with_timeout(HELPER_TIMEOUT);
+        self.execute_command(slot, command)?;
+
+        let mut data = [0; EXT_CSD_LEN];
+        data.copy_from_slice(buffer.as_slice());
+        Ok(ExtCsd::new(data))
+    }
+}
+
+/// Check the alignment of a buffer for a command packet, and get its length.
+fn raw_buffer(ptr: *mut u8, len: usize, align: usize) -> Result<(*mut u8, u32)> {
+    if ptr as usize % align != 0 {
+        return Err(Status::INVALID_PARAMETER.into());
+    }
+    let len = u32::try_from(len).map_err(|_| Status::BAD_BUFFER_SIZE)?;
+    Ok((ptr, len))
+}
+
+/// Iterator over the slots of an [`SdMmcPassThru`] controller, returned by
+/// [`SdMmcPassThru::slots`].
+#[derive(Debug)]
+pub struct SdMmcSlotIter<'a> {
+    pass_thru: &'a SdMmcPassThru,
+    slot: u8,
+}
+
+impl Iterator for SdMmcSlotIter<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let proto = &self.pass_thru.0;
+        unsafe { (proto.get_next_slot)(proto, &mut self.slot) }
+            .to_result()
+            .ok()?;
+        Some(self.slot)
+    }
+}
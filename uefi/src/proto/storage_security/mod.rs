@@ -0,0 +1,194 @@
+//! Storage Security Command protocol.
+//!
+//! The [`StorageSecurityCommand`] protocol sends SECURITY PROTOCOL OUT and
+//! receives SECURITY PROTOCOL IN data (TRUSTED SEND and TRUSTED RECEIVE for
+//! ATA) to and from a storage device. It is used to manage self-encrypting
+//! drives, for example to unlock a TCG Opal drive before booting. The
+//! [`opal`] module contains parsers for the discovery data returned by such
+//! drives.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::media::block::BlockIO;
+//! use uefi::proto::storage_security::opal::{Level0Discovery, LockingFeatures};
+//! use uefi::proto::storage_security::StorageSecurityCommand;
+//! # fn find_locked() -> uefi::Result {
+//!
+//! for handle in boot::find_handles::<StorageSecurityCommand>()? {
+//!     let media_id = boot::open_protocol_exclusive::<BlockIO>(handle)?
+//!         .media()
+//!         .media_id();
+//!     let mut ssc = boot::open_protocol_exclusive::<StorageSecurityCommand>(handle)?;
+//!     let mut buffer = [0; 2048];
+//!     let discovery = ssc.level0_discovery(media_id, &mut buffer)?;
+//!     if let Some(locking) = discovery.locking() {
+//!         if locking.contains(LockingFeatures::LOCKED) {
+//!             log::info!("Drive is locked");
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod opal;
+
+use crate::duration;
+use crate::proto::unsafe_protocol;
+use crate::{Result, Status, StatusExt};
+use core::time::Duration;
+use opal::{Level0Discovery, SecurityProtocolList, LEVEL0_DISCOVERY_COM_ID};
+use uefi_raw::protocol::media::StorageSecurityCommandProtocol;
+
+/// Security protocol that returns information about the supported security
+/// protocols.
+pub const SECURITY_PROTOCOL_INFORMATION: u8 = 0x00;
+
+/// Security protocol of TCG storage management, used for discovery and
+/// sessions.
+pub const SECURITY_PROTOCOL_TCG_1: u8 = 0x01;
+
+/// Security protocol of TCG storage management, used for resetting the
+/// session manager.
+pub const SECURITY_PROTOCOL_TCG_2: u8 = 0x02;
+
+/// The Storage Security Command protocol.
+#[derive(Debug)]
+#[repr(transparent)]
+#[unsafe_protocol(StorageSecurityCommandProtocol::GUID)]
+pub struct StorageSecurityCommand(StorageSecurityCommandProtocol);
+
+impl StorageSecurityCommand {
+    /// Receive data of the security protocol `protocol_id` from the device,
+    /// and get the number of bytes received.
+    ///
+    /// The meaning of `protocol_specific_data` depends on the protocol, for
+    /// TCG protocols it is a ComID. It is passed to the device in big-endian
+    /// byte order, as required by the UEFI specification. A `timeout` of
+    /// [`Duration::ZERO`] waits indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: the device does not support security
+    ///   protocol commands, or not with this protocol.
+    /// * [`Status::DEVICE_ERROR`]: the command failed.
+    /// * [`Status::NO_MEDIA`]: there is no medium in the device.
+    /// * [`Status::MEDIA_CHANGED`]: `media_id` is not for the current medium.
+    /// * [`Status::WARN_BUFFER_TOO_SMALL`]: `buffer` was too small, and
+    ///   contains the truncated data.
+    /// * [`Status::INVALID_PARAMETER`]: `buffer` is empty.
+    /// * [`Status::TIMEOUT`]: the timeout elapsed before the command
+    ///   completed.
+    pub fn receive_data(
+        &mut self,
+        media_id: u32,
+        timeout: Duration,
+        protocol_id: u8,
+        protocol_specific_data: u16,
+        buffer: &mut [u8],
+    ) -> Result<usize> {
+        let mut transfer_size = 0;
+        unsafe {
+            (self.0.receive_data)(
+                &mut self.0,
+                media_id,
+                duration::as_timer_units(timeout),
+                protocol_id,
+                protocol_specific_data.to_be(),
+                buffer.len(),
+                buffer.as_mut_ptr().cast(),
+                &mut transfer_size,
+            )
+        }
+        .to_result_with_val(|| transfer_size)
+    }
+
+    /// Send `buffer` as data of the security protocol `protocol_id` to the
+    /// device. See [`receive_data`] for the other parameters.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::UNSUPPORTED`]: the device does not support security
+    ///   protocol commands, or not with this protocol.
+    /// * [`Status::DEVICE_ERROR`]: the command failed.
+    /// * [`Status::NO_MEDIA`]: there is no medium in the device.
+    /// * [`Status::MEDIA_CHANGED`]: `media_id` is not for the current medium.
+    /// * [`Status::INVALID_PARAMETER`]: `buffer` is empty.
+    /// * [`Status::TIMEOUT`]: the timeout elapsed before the command
+    ///   completed.
+    ///
+    /// [`receive_data`]: Self::receive_data
+    pub fn send_data(
+        &mut self,
+        media_id: u32,
+        timeout: Duration,
+        protocol_id: u8,
+        protocol_specific_data: u16,
+        buffer: &[u8],
+    ) -> Result {
+        unsafe {
+            (self.0.send_data)(
+                &mut self.0,
+                media_id,
+                duration::as_timer_units(timeout),
+                protocol_id,
+                protocol_specific_data.to_be(),
+                buffer.len(),
+                buffer.as_ptr().cast(),
+            )
+        }
+        .to_result()
+    }
+
+    /// Get the security protocols supported by the device, using `buffer`
+    /// to receive the list.
+    ///
+    /// # Errors
+    ///
+    /// See [`receive_data`]. [`Status::DEVICE_ERROR`] is also returned if
+    /// the received list is malformed.
+    ///
+    /// [`receive_data`]: Self::receive_data
+    pub fn supported_protocols<'buf>(
+        &mut self,
+        media_id: u32,
+        buffer: &'buf mut [u8],
+    ) -> Result<SecurityProtocolList<'buf>> {
+        let len = self.receive_data(
+            media_id,
+            Duration::ZERO,
+            SECURITY_PROTOCOL_INFORMATION,
+            0,
+            buffer,
+        )?;
+        SecurityProtocolList::parse(&buffer[..len]).ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+
+    /// Get the TCG Level 0 discovery data of the device, using `buffer` to
+    /// receive the data. This describes the TCG features of the device,
+    /// such as whether it supports Opal and is locked. A buffer of 2048
+    /// bytes is enough for most devices.
+    ///
+    /// # Errors
+    ///
+    /// See [`receive_data`]. [`Status::DEVICE_ERROR`] is also returned if
+    /// the received data is malformed.
+    ///
+    /// [`receive_data`]: Self::receive_data
+    pub fn level0_discovery<'buf>(
+        &mut self,
+        media_id: u32,
+        buffer: &'buf mut [u8],
+    ) -> Result<Level0Discovery<'buf>> {
+        let len = self.receive_data(
+            media_id,
+            Duration::ZERO,
+            SECURITY_PROTOCOL_TCG_1,
+            LEVEL0_DISCOVERY_COM_ID,
+            buffer,
+        )?;
+        Level0Discovery::parse(&buffer[..len]).ok_or_else(|| Status::DEVICE_ERROR.into())
+    }
+}
@@ -0,0 +1,316 @@
+//! Parsers for TCG storage discovery data.
+//!
+//! Self-encrypting drives that implement a TCG Security Subsystem Class
+//! (SSC), such as Opal, describe their features in the Level 0 discovery
+//! data, which is returned by [`StorageSecurityCommand::level0_discovery`].
+//!
+//! [`StorageSecurityCommand::level0_discovery`]: super::StorageSecurityCommand::level0_discovery
+
+use bitflags::bitflags;
+
+/// ComID used to receive the Level 0 discovery data.
+pub const LEVEL0_DISCOVERY_COM_ID: u16 = 0x0001;
+
+/// Size of the Level 0 discovery header.
+pub const LEVEL0_HEADER_LEN: usize = 48;
+
+/// Size of the header of a feature descriptor.
+const FEATURE_HEADER_LEN: usize = 4;
+
+const fn read_be_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+/// List of security protocols supported by a device, returned by
+/// [`StorageSecurityCommand::supported_protocols`].
+///
+/// [`StorageSecurityCommand::supported_protocols`]: super::StorageSecurityCommand::supported_protocols
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SecurityProtocolList<'a> {
+    protocols: &'a [u8],
+}
+
+impl<'a> SecurityProtocolList<'a> {
+    /// Parse the data received with the security protocol information
+    /// protocol. Returns `None` if the data is truncated.
+    #[must_use]
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let len = usize::from(read_be_u16(data.get(..8)?, 6));
+        Some(Self {
+            protocols: data.get(8..8 + len)?,
+        })
+    }
+
+    /// Get the IDs of the supported security protocols, in ascending order.
+    #[must_use]
+    pub const fn protocols(&self) -> &'a [u8] {
+        self.protocols
+    }
+
+    /// Check whether the security protocol `protocol_id` is supported.
+    #[must_use]
+    pub fn contains(&self, protocol_id: u8) -> bool {
+        self.protocols.contains(&protocol_id)
+    }
+}
+
+newtype_enum! {
+    /// Code identifying a [`FeatureDescriptor`].
+    pub enum FeatureCode: u16 => {
+        /// Features of the TPer (trusted peripheral).
+        TPER = 0x0001,
+        /// Locking features, see [`LockingFeatures`].
+        LOCKING = 0x0002,
+        /// Alignment requirements of the locking ranges.
+        GEOMETRY = 0x0003,
+        /// Enterprise SSC.
+        ENTERPRISE = 0x0100,
+        /// Opal SSC version 1.
+        OPAL_V1 = 0x0200,
+        /// Single user mode.
+        SINGLE_USER_MODE = 0x0201,
+        /// Additional DataStore tables.
+        DATA_STORE = 0x0202,
+        /// Opal SSC version 2.
+        OPAL_V2 = 0x0203,
+        /// Opalite SSC.
+        OPALITE = 0x0301,
+        /// Pyrite SSC version 1.
+        PYRITE_V1 = 0x0302,
+        /// Pyrite SSC version 2.
+        PYRITE_V2 = 0x0303,
+        /// Ruby SSC.
+        RUBY = 0x0304,
+        /// Block SID authentication.
+        BLOCK_SID = 0x0402,
+    }
+}
+
+impl FeatureCode {
+    /// Check whether this is the feature of a security subsystem class,
+    /// which starts with the base ComID used to communicate with the class.
+    #[must_use]
+    pub const fn is_ssc(self) -> bool {
+        matches!(
+            self,
+            Self::ENTERPRISE
+                | Self::OPAL_V1
+                | Self::OPAL_V2
+                | Self::OPALITE
+                | Self::PYRITE_V1
+                | Self::PYRITE_V2
+                | Self::RUBY
+        )
+    }
+}
+
+bitflags! {
+    /// Flags of the [`LOCKING`] feature.
+    ///
+    /// [`LOCKING`]: FeatureCode::LOCKING
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    #[repr(transparent)]
+    pub struct LockingFeatures: u8 {
+        /// The device supports locking.
+        const LOCKING_SUPPORTED = 1 << 0;
+        /// Locking is enabled for at least one locking range.
+        const LOCKING_ENABLED = 1 << 1;
+        /// At least one locking range is locked.
+        const LOCKED = 1 << 2;
+        /// The device encrypts the data on the medium.
+        const MEDIA_ENCRYPTION = 1 << 3;
+        /// The shadow MBR is enabled.
+        const MBR_ENABLED = 1 << 4;
+        /// The shadow MBR is done, so the real MBR is visible.
+        const MBR_DONE = 1 << 5;
+    }
+}
+
+/// Level 0 discovery data, returned by
+/// [`StorageSecurityCommand::level0_discovery`].
+///
+/// [`StorageSecurityCommand::level0_discovery`]: super::StorageSecurityCommand::level0_discovery
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Level0Discovery<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Level0Discovery<'a> {
+    /// Parse the discovery data. Returns `None` if the data is shorter than
+    /// the header or the length in the header.
+    #[must_use]
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let header = data.get(..LEVEL0_HEADER_LEN)?;
+        // The length does not include the length field itself.
+        let len = u32::from_be_bytes(header[..4].try_into().unwrap());
+        let len = usize::try_from(len).ok()?.checked_add(4)?;
+        if len < LEVEL0_HEADER_LEN {
+            return None;
+        }
+        Some(Self {
+            data: data.get(..len)?,
+        })
+    }
+
+    /// Revision of the data structure.
+    #[must_use]
+    pub fn revision(&self) -> u32 {
+        u32::from_be_bytes(self.data[4..8].try_into().unwrap())
+    }
+
+    /// Iterate over the feature descriptors. Iteration stops at a truncated
+    /// descriptor.
+    #[must_use]
+    pub fn features(&self) -> FeatureIter<'a> {
+        FeatureIter {
+            data: &self.data[LEVEL0_HEADER_LEN..],
+        }
+    }
+
+    /// Get the descriptor of the feature `code`.
+    #[must_use]
+    pub fn feature(&self, code: FeatureCode) -> Option<FeatureDescriptor<'a>> {
+        self.features().find(|feature| feature.code == code)
+    }
+
+    /// Get the flags of the locking feature.
+    #[must_use]
+    pub fn locking(&self) -> Option<LockingFeatures> {
+        let feature = self.feature(FeatureCode::LOCKING)?;
+        Some(LockingFeatures::from_bits_retain(*feature.data.first()?))
+    }
+
+    /// Get the descriptor of the first security subsystem class supported
+    /// by the device, such as Opal.
+    #[must_use]
+    pub fn ssc(&self) -> Option<FeatureDescriptor<'a>> {
+        self.features().find(|feature| feature.code.is_ssc())
+    }
+}
+
+/// Descriptor of a feature in the [`Level0Discovery`] data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FeatureDescriptor<'a> {
+    /// Code identifying the feature.
+    pub code: FeatureCode,
+
+    /// Version of the descriptor.
+    pub version: u8,
+
+    /// Feature-specific data, following the header of the descriptor.
+    pub data: &'a [u8],
+}
+
+impl FeatureDescriptor<'_> {
+    /// For features of a security subsystem class, get the first ComID to
+    /// use for sessions with the class. See [`FeatureCode::is_ssc`].
+    #[must_use]
+    pub fn base_com_id(&self) -> Option<u16> {
+        if !self.code.is_ssc() {
+            return None;
+        }
+        Some(read_be_u16(self.data.get(..2)?, 0))
+    }
+
+    /// For features of a security subsystem class, get the number of ComIDs
+    /// starting with the [`base_com_id`].
+    ///
+    /// [`base_com_id`]: Self::base_com_id
+    #[must_use]
+    pub fn num_com_ids(&self) -> Option<u16> {
+        if !self.code.is_ssc() {
+            return None;
+        }
+        Some(read_be_u16(self.data.get(..4)?, 2))
+    }
+}
+
+/// Iterator over the feature descriptors of a [`Level0Discovery`].
+#[derive(Clone, Debug)]
+pub struct FeatureIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for FeatureIter<'a> {
+    type Item = FeatureDescriptor<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = self.data.get(..FEATURE_HEADER_LEN)?;
+        let len = FEATURE_HEADER_LEN + usize::from(header[3]);
+        let Some(descriptor) = self.data.get(..len) else {
+            self.data = &[];
+            return None;
+        };
+        self.data = &self.data[len..];
+        Some(FeatureDescriptor {
+            code: FeatureCode(read_be_u16(header, 0)),
+            version: header[2] >> 4,
+            data: &descriptor[FEATURE_HEADER_LEN..],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_protocol_list() {
+        let data = [0, 0, 0, 0, 0, 0, 0, 3, 0x00, 0x01, 0x02, 0xff];
+        let list = SecurityProtocolList::parse(&data).unwrap();
+        assert_eq!(list.protocols(), [0x00, 0x01, 0x02]);
+        assert!(list.contains(0x01));
+        assert!(!list.contains(0xef));
+
+        assert_eq!(SecurityProtocolList::parse(&data[..10]), None);
+    }
+
+    #[test]
+    fn test_level0_discovery() {
+        let mut data = [0; LEVEL0_HEADER_LEN + 16 + 20 + 4];
+        let len = (data.len() - 4) as u32;
+        data[..4].copy_from_slice(&len.to_be_bytes());
+        data[4..8].copy_from_slice(&1u32.to_be_bytes());
+
+        // Locking feature, locked.
+        let locking = &mut data[LEVEL0_HEADER_LEN..][..16];
+        locking[..4].copy_from_slice(&[0x00, 0x02, 0x10, 12]);
+        locking[4] = 0x07;
+
+        // Opal SSC v2 with base ComID 0x07fe.
+        let opal = &mut data[LEVEL0_HEADER_LEN + 16..][..20];
+        opal[..4].copy_from_slice(&[0x02, 0x03, 0x20, 16]);
+        opal[4..8].copy_from_slice(&[0x07, 0xfe, 0x00, 0x01]);
+
+        // Truncated descriptor.
+        data[LEVEL0_HEADER_LEN + 36..].copy_from_slice(&[0x04, 0x02, 0x10, 12]);
+
+        let discovery = Level0Discovery::parse(&data).unwrap();
+        assert_eq!(discovery.revision(), 1);
+        assert_eq!(discovery.features().count(), 2);
+        assert_eq!(
+            discovery.locking(),
+            Some(
+                LockingFeatures::LOCKING_SUPPORTED
+                    | LockingFeatures::LOCKING_ENABLED
+                    | LockingFeatures::LOCKED
+            )
+        );
+
+        let ssc = discovery.ssc().unwrap();
+        assert_eq!(ssc.code, FeatureCode::OPAL_V2);
+        assert_eq!(ssc.version, 2);
+        assert_eq!(ssc.base_com_id(), Some(0x07fe));
+        assert_eq!(ssc.num_com_ids(), Some(1));
+        assert_eq!(
+            discovery
+                .feature(FeatureCode::LOCKING)
+                .unwrap()
+                .base_com_id(),
+            None
+        );
+        assert_eq!(discovery.feature(FeatureCode::BLOCK_SID), None);
+
+        assert_eq!(Level0Discovery::parse(&data[..LEVEL0_HEADER_LEN]), None);
+    }
+}
@@ -70,3 +70,26 @@ impl<Data: Debug> Error<Data> {
 
 #[cfg(feature = "unstable")]
 impl<Data: Debug> core::error::Error for Error<Data> {}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Error for Error {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        use embedded_io::ErrorKind;
+
+        match self.status {
+            Status::NOT_FOUND => ErrorKind::NotFound,
+            Status::ACCESS_DENIED | Status::WRITE_PROTECTED | Status::SECURITY_VIOLATION => {
+                ErrorKind::PermissionDenied
+            }
+            Status::INVALID_PARAMETER | Status::BAD_BUFFER_SIZE => ErrorKind::InvalidInput,
+            Status::VOLUME_CORRUPTED | Status::CRC_ERROR | Status::COMPROMISED_DATA => {
+                ErrorKind::InvalidData
+            }
+            Status::OUT_OF_RESOURCES => ErrorKind::OutOfMemory,
+            Status::UNSUPPORTED => ErrorKind::Unsupported,
+            Status::TIMEOUT => ErrorKind::TimedOut,
+            Status::ABORTED => ErrorKind::Interrupted,
+            _ => ErrorKind::Other,
+        }
+    }
+}
@@ -0,0 +1,525 @@
+//! Reader for the GUID Partition Table (GPT).
+//!
+//! The GPT consists of a header, by default in block 1 of the disk, and an
+//! array of partition entries that the header points to. A backup copy of
+//! both is stored at the end of the disk. [`Gpt::read`] validates the CRC32
+//! checksums of the header and the entries, and falls back to the backup if
+//! the primary copy is damaged.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use uefi::boot;
+//! use uefi::proto::media::block::BlockIO;
+//! use uefi::proto::media::partition::GptPartitionType;
+//! use uefi::storage::gpt::Gpt;
+//! use uefi::storage::gpt::GptError;
+//! # fn find_esp() -> Result<(), GptError> {
+//!
+//! let handle = boot::get_handle_for_protocol::<BlockIO>()?;
+//! let mut block_io = boot::open_protocol_exclusive::<BlockIO>(handle)?;
+//! let gpt = Gpt::read(&mut *block_io)?;
+//! for partition in gpt.partitions() {
+//!     if { partition.partition_type_guid } == GptPartitionType::EFI_SYSTEM_PARTITION {
+//!         let start = partition.starting_lba;
+//!         log::info!("ESP at block {start}: {}", partition.partition_name());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::BlockDevice;
+use crate::proto::media::block::Lba;
+use crate::proto::media::partition::{GptPartitionEntry, GptPartitionType};
+use crate::Guid;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::mem;
+use core::slice::ChunksExact;
+
+/// Address of the block containing the primary GPT header.
+pub const PRIMARY_HEADER_LBA: Lba = 1;
+
+/// Signature at the start of a GPT header.
+pub const SIGNATURE: [u8; 8] = *b"EFI PART";
+
+/// Size of the fields of a GPT header defined by the UEFI specification.
+/// Larger headers are allowed, and the remaining bytes are reserved.
+pub const MIN_HEADER_SIZE: u32 = 92;
+
+/// Size of a [`GptPartitionEntry`]. Larger entries are allowed, and the
+/// remaining bytes are reserved.
+pub const MIN_ENTRY_SIZE: u32 = 128;
+
+/// Error returned when reading a GPT.
+#[derive(Debug)]
+pub enum GptError {
+    /// Reading from the device failed.
+    Io(crate::Error),
+
+    /// The block does not start with the GPT header [`SIGNATURE`].
+    InvalidSignature,
+
+    /// The header is malformed, for example because a size is not valid or
+    /// the partition entries do not fit on the device.
+    InvalidHeader,
+
+    /// The CRC32 checksum of the header is not correct.
+    HeaderCrcMismatch,
+
+    /// The CRC32 checksum of the partition entries is not correct.
+    EntriesCrcMismatch,
+}
+
+impl From<crate::Error> for GptError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl Display for GptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read from the device: {err}"),
+            Self::InvalidSignature => f.write_str("invalid GPT header signature"),
+            Self::InvalidHeader => f.write_str("malformed GPT header"),
+            Self::HeaderCrcMismatch => f.write_str("GPT header CRC32 mismatch"),
+            Self::EntriesCrcMismatch => f.write_str("GPT partition entries CRC32 mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl core::error::Error for GptError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Table of the CRC32 variant used by GPT (IEEE 802.3, reflected).
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Update a CRC32 calculation with `data`. Start with `!0`, and invert the
+/// result to get the checksum.
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &b| {
+        CRC32_TABLE[usize::from(crc as u8 ^ b)] ^ (crc >> 8)
+    })
+}
+
+/// Calculate the CRC32 checksum of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(!0, data)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// GPT header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GptHeader {
+    /// Revision of the header format.
+    pub revision: u32,
+
+    /// Size of the header in bytes.
+    pub header_size: u32,
+
+    /// Address of the block containing this header.
+    pub my_lba: Lba,
+
+    /// Address of the block containing the other copy of the header.
+    pub alternate_lba: Lba,
+
+    /// First block that may be used by a partition.
+    pub first_usable_lba: Lba,
+
+    /// Last block that may be used by a partition.
+    pub last_usable_lba: Lba,
+
+    /// GUID identifying the disk.
+    pub disk_guid: Guid,
+
+    /// Address of the first block of the partition entries.
+    pub partition_entry_lba: Lba,
+
+    /// Number of partition entries, including unused ones.
+    pub number_of_partition_entries: u32,
+
+    /// Size of a partition entry in bytes.
+    pub size_of_partition_entry: u32,
+
+    /// CRC32 checksum of the partition entries.
+    pub partition_entry_array_crc32: u32,
+}
+
+impl GptHeader {
+    /// Parse and validate the header at the start of `block`, which must
+    /// contain the whole block the header was read from.
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::InvalidSignature`] if `block` does not start with the
+    ///   header signature.
+    /// * [`GptError::InvalidHeader`] if the header is malformed.
+    /// * [`GptError::HeaderCrcMismatch`] if the header checksum is not
+    ///   correct.
+    pub fn parse(block: &[u8]) -> Result<Self, GptError> {
+        if block.len() < MIN_HEADER_SIZE as usize {
+            return Err(GptError::InvalidHeader);
+        }
+        if block[..8] != SIGNATURE {
+            return Err(GptError::InvalidSignature);
+        }
+
+        let header_size = read_u32(block, 12);
+        if header_size < MIN_HEADER_SIZE || header_size as usize > block.len() {
+            return Err(GptError::InvalidHeader);
+        }
+
+        // The checksum is calculated with the checksum field set to zero.
+        let header = &block[..header_size as usize];
+        let mut crc = crc32_update(!0, &header[..16]);
+        crc = crc32_update(crc, &[0; 4]);
+        crc = !crc32_update(crc, &header[20..]);
+        if crc != read_u32(block, 16) {
+            return Err(GptError::HeaderCrcMismatch);
+        }
+
+        let header = Self {
+            revision: read_u32(block, 8),
+            header_size,
+            my_lba: read_u64(block, 24),
+            alternate_lba: read_u64(block, 32),
+            first_usable_lba: read_u64(block, 40),
+            last_usable_lba: read_u64(block, 48),
+            disk_guid: Guid::from_bytes(block[56..72].try_into().unwrap()),
+            partition_entry_lba: read_u64(block, 72),
+            number_of_partition_entries: read_u32(block, 80),
+            size_of_partition_entry: read_u32(block, 84),
+            partition_entry_array_crc32: read_u32(block, 88),
+        };
+
+        // Entries are 128 bytes multiplied by a power of two.
+        let entry_size = header.size_of_partition_entry;
+        if entry_size < MIN_ENTRY_SIZE
+            || !entry_size.is_power_of_two()
+            || header.first_usable_lba > header.last_usable_lba
+        {
+            return Err(GptError::InvalidHeader);
+        }
+
+        Ok(header)
+    }
+
+    /// Size of the partition entries in bytes. Returns `None` if the size
+    /// does not fit in a `usize`.
+    #[must_use]
+    pub fn partition_entries_size(&self) -> Option<usize> {
+        usize::try_from(self.number_of_partition_entries)
+            .ok()?
+            .checked_mul(self.size_of_partition_entry as usize)
+    }
+}
+
+/// GUID Partition Table, read by [`Gpt::read`].
+#[derive(Clone, Debug)]
+pub struct Gpt {
+    header: GptHeader,
+    entries: Vec<u8>,
+}
+
+impl Gpt {
+    /// Read and validate the GPT of `device`. If the primary header or
+    /// partition entries are damaged, the backup copy at the end of the
+    /// device is used instead, which can be detected by checking
+    /// [`GptHeader::my_lba`].
+    ///
+    /// # Errors
+    ///
+    /// * [`GptError::Io`] if reading the primary header or partition
+    ///   entries fails.
+    /// * The other [`GptError`] variants if both the primary and the backup
+    ///   copy are invalid. The error is the one of the primary copy.
+    pub fn read<D: BlockDevice + ?Sized>(device: &mut D) -> Result<Self, GptError> {
+        match Self::read_at(device, PRIMARY_HEADER_LBA) {
+            Err(GptError::Io(err)) => Err(GptError::Io(err)),
+            Err(err) => {
+                let backup_lba = device.last_block();
+                Self::read_at(device, backup_lba).map_err(|_| err)
+            }
+            gpt => gpt,
+        }
+    }
+
+    /// Read and validate the header in block `lba`, and the partition
+    /// entries it points to.
+    fn read_at<D: BlockDevice + ?Sized>(device: &mut D, lba: Lba) -> Result<Self, GptError> {
+        let block_size = device.block_size() as usize;
+        let last_block = device.last_block();
+
+        let mut block = vec![0; block_size];
+        device.read_blocks(lba, &mut block)?;
+        let header = GptHeader::parse(&block)?;
+        if header.my_lba != lba {
+            return Err(GptError::InvalidHeader);
+        }
+
+        // Check that the entries are on the device before allocating a
+        // buffer for them.
+        let entries_size = header
+            .partition_entries_size()
+            .ok_or(GptError::InvalidHeader)?;
+        let entries_blocks = entries_size.div_ceil(block_size) as u64;
+        let entries_end = header
+            .partition_entry_lba
+            .checked_add(entries_blocks)
+            .ok_or(GptError::InvalidHeader)?;
+        if header.partition_entry_lba == 0 || entries_end > last_block.saturating_add(1) {
+            return Err(GptError::InvalidHeader);
+        }
+
+        let mut entries = vec![0; entries_blocks as usize * block_size];
+        device.read_blocks(header.partition_entry_lba, &mut entries)?;
+        entries.truncate(entries_size);
+        if crc32(&entries) != header.partition_entry_array_crc32 {
+            return Err(GptError::EntriesCrcMismatch);
+        }
+
+        Ok(Self { header, entries })
+    }
+
+    /// Get the header.
+    #[must_use]
+    pub const fn header(&self) -> &GptHeader {
+        &self.header
+    }
+
+    /// Iterate over all partition entries, including unused ones. The
+    /// index of an entry is its partition number minus one.
+    #[must_use]
+    pub fn entries(&self) -> GptEntryIter<'_> {
+        GptEntryIter {
+            entries: self
+                .entries
+                .chunks_exact(self.header.size_of_partition_entry as usize),
+        }
+    }
+
+    /// Iterate over the partition entries that are in use.
+    pub fn partitions(&self) -> impl Iterator<Item = GptPartitionEntry> + '_ {
+        self.entries()
+            .filter(|entry| { entry.partition_type_guid } != GptPartitionType::UNUSED_ENTRY)
+    }
+}
+
+/// Iterator over the partition entries of a [`Gpt`], returned by
+/// [`Gpt::entries`].
+#[derive(Clone, Debug)]
+pub struct GptEntryIter<'a> {
+    entries: ChunksExact<'a, u8>,
+}
+
+impl Iterator for GptEntryIter<'_> {
+    type Item = GptPartitionEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+        assert!(entry.len() >= mem::size_of::<GptPartitionEntry>());
+        // Safety: the entry is large enough, and all bit patterns are valid.
+        Some(unsafe { entry.as_ptr().cast::<GptPartitionEntry>().read_unaligned() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl ExactSizeIterator for GptEntryIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::media::partition::GptPartitionAttributes;
+    use crate::{guid, Char16, Result};
+
+    const BLOCK_SIZE: usize = 512;
+    const NUM_BLOCKS: usize = 64;
+    const NUM_ENTRIES: u32 = 4;
+
+    struct MemoryDisk(Vec<u8>);
+
+    impl BlockDevice for MemoryDisk {
+        fn block_size(&self) -> u32 {
+            BLOCK_SIZE as u32
+        }
+
+        fn last_block(&self) -> Lba {
+            (self.0.len() / BLOCK_SIZE - 1) as Lba
+        }
+
+        fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+            let start = lba as usize * BLOCK_SIZE;
+            buffer.copy_from_slice(&self.0[start..start + buffer.len()]);
+            Ok(())
+        }
+    }
+
+    fn entry() -> GptPartitionEntry {
+        let mut partition_name = [Char16::try_from('\0').unwrap(); 36];
+        partition_name[0] = Char16::try_from('a').unwrap();
+        GptPartitionEntry {
+            partition_type_guid: GptPartitionType::EFI_SYSTEM_PARTITION,
+            unique_partition_guid: guid!("01234567-89ab-cdef-0123-456789abcdef"),
+            starting_lba: 34,
+            ending_lba: 40,
+            attributes: GptPartitionAttributes::REQUIRED_PARTITION,
+            partition_name,
+        }
+    }
+
+    /// Write a header to block `my_lba`, with the entries in the following
+    /// blocks (primary) or preceding blocks (backup).
+    fn write_copy(disk: &mut [u8], my_lba: u64, alternate_lba: u64, entries: &[u8]) {
+        let entry_lba = if my_lba == 1 {
+            2
+        } else {
+            my_lba - (entries.len() / BLOCK_SIZE) as u64
+        };
+        let entries_start = entry_lba as usize * BLOCK_SIZE;
+        disk[entries_start..entries_start + entries.len()].copy_from_slice(entries);
+
+        let header = &mut disk[my_lba as usize * BLOCK_SIZE..][..BLOCK_SIZE];
+        header[..8].copy_from_slice(&SIGNATURE);
+        header[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+        header[12..16].copy_from_slice(&MIN_HEADER_SIZE.to_le_bytes());
+        header[24..32].copy_from_slice(&my_lba.to_le_bytes());
+        header[32..40].copy_from_slice(&alternate_lba.to_le_bytes());
+        header[40..48].copy_from_slice(&3u64.to_le_bytes());
+        header[48..56].copy_from_slice(&(NUM_BLOCKS as u64 - 3).to_le_bytes());
+        header[72..80].copy_from_slice(&entry_lba.to_le_bytes());
+        header[80..84].copy_from_slice(&NUM_ENTRIES.to_le_bytes());
+        header[84..88].copy_from_slice(&MIN_ENTRY_SIZE.to_le_bytes());
+        header[88..92].copy_from_slice(&crc32(entries).to_le_bytes());
+        let crc = crc32(&header[..MIN_HEADER_SIZE as usize]);
+        header[16..20].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    fn disk() -> MemoryDisk {
+        let mut entries = vec![0; BLOCK_SIZE];
+        let entry = entry();
+        // Safety: the entry is plain old data.
+        let entry_bytes = unsafe {
+            core::slice::from_raw_parts(
+                core::ptr::from_ref(&entry).cast::<u8>(),
+                mem::size_of::<GptPartitionEntry>(),
+            )
+        };
+        entries[128..256].copy_from_slice(entry_bytes);
+
+        let mut disk = vec![0; NUM_BLOCKS * BLOCK_SIZE];
+        let last = NUM_BLOCKS as u64 - 1;
+        write_copy(&mut disk, 1, last, &entries);
+        write_copy(&mut disk, last, 1, &entries);
+        MemoryDisk(disk)
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_read_gpt() {
+        let gpt = Gpt::read(&mut disk()).unwrap();
+        assert_eq!(gpt.header().my_lba, 1);
+        assert_eq!(gpt.header().number_of_partition_entries, NUM_ENTRIES);
+        assert_eq!(gpt.entries().len(), NUM_ENTRIES as usize);
+
+        let partitions: Vec<_> = gpt.partitions().collect();
+        assert_eq!(partitions, [entry()]);
+        assert_eq!(gpt.entries().position(|e| e == entry()), Some(1));
+    }
+
+    #[test]
+    fn test_read_gpt_backup() {
+        // Damage the primary header.
+        let mut disk = disk();
+        disk.0[BLOCK_SIZE + 40] ^= 1;
+        assert!(matches!(
+            GptHeader::parse(&disk.0[BLOCK_SIZE..2 * BLOCK_SIZE]),
+            Err(GptError::HeaderCrcMismatch)
+        ));
+        let gpt = Gpt::read(&mut disk).unwrap();
+        assert_eq!(gpt.header().my_lba, NUM_BLOCKS as u64 - 1);
+        assert_eq!(gpt.partitions().count(), 1);
+
+        // Damage the primary entries.
+        let mut disk = self::disk();
+        disk.0[2 * BLOCK_SIZE + 130] ^= 1;
+        let gpt = Gpt::read(&mut disk).unwrap();
+        assert_eq!(gpt.header().my_lba, NUM_BLOCKS as u64 - 1);
+
+        // Damage both entries.
+        let backup_entries = (NUM_BLOCKS - 2) * BLOCK_SIZE;
+        disk.0[backup_entries + 130] ^= 1;
+        assert!(matches!(
+            Gpt::read(&mut disk),
+            Err(GptError::EntriesCrcMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_read_gpt_invalid() {
+        let mut disk = MemoryDisk(vec![0; NUM_BLOCKS * BLOCK_SIZE]);
+        assert!(matches!(
+            Gpt::read(&mut disk),
+            Err(GptError::InvalidSignature)
+        ));
+
+        // Entries that do not fit on the disk.
+        let mut disk = self::disk();
+        let header = &mut disk.0[BLOCK_SIZE..2 * BLOCK_SIZE];
+        header[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+        header[16..20].fill(0);
+        let crc = crc32(&header[..MIN_HEADER_SIZE as usize]);
+        header[16..20].copy_from_slice(&crc.to_le_bytes());
+        assert!(matches!(
+            GptHeader::parse(header),
+            Ok(GptHeader {
+                number_of_partition_entries: u32::MAX,
+                ..
+            })
+        ));
+        disk.0.truncate(NUM_BLOCKS / 2 * BLOCK_SIZE);
+        assert!(matches!(Gpt::read(&mut disk), Err(GptError::InvalidHeader)));
+    }
+}
@@ -0,0 +1,351 @@
+//! Reader for the legacy Master Boot Record (MBR) partition table.
+//!
+//! The MBR in block 0 of the disk holds up to four primary partitions. One
+//! of them can be an extended partition, which contains a chain of Extended
+//! Boot Records (EBRs) that each describe one logical partition. Disks that
+//! use a GPT have a protective MBR with a single [`GPT_PROTECTIVE`]
+//! partition covering the disk. Hybrid disks have additional partitions in
+//! the protective MBR for legacy operating systems.
+//!
+//! [`GPT_PROTECTIVE`]: MbrOsType::GPT_PROTECTIVE
+
+use crate::proto::media::partition::{MbrOsType, MbrPartitionRecord};
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "alloc")]
+use {super::BlockDevice, crate::proto::media::block::Lba, alloc::vec, alloc::vec::Vec};
+
+/// Size of the boot record. A boot record is stored in a single block,
+/// which may be larger.
+pub const MBR_SIZE: usize = 512;
+
+/// Signature at the end of a boot record.
+pub const SIGNATURE: [u8; 2] = [0x55, 0xaa];
+
+/// Offset of the partition records in a boot record.
+const PARTITION_RECORDS_OFFSET: usize = 446;
+
+/// Error returned when reading an MBR.
+#[derive(Debug)]
+pub enum MbrError {
+    /// Reading from the device failed.
+    Io(crate::Error),
+
+    /// The block does not end with the boot record [`SIGNATURE`].
+    InvalidSignature,
+
+    /// The chain of extended boot records is malformed, for example because
+    /// a record points outside of the extended partition.
+    InvalidExtendedPartition,
+}
+
+impl From<crate::Error> for MbrError {
+    fn from(err: crate::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl Display for MbrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read from the device: {err}"),
+            Self::InvalidSignature => f.write_str("invalid boot record signature"),
+            Self::InvalidExtendedPartition => f.write_str("malformed extended partition"),
+        }
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl core::error::Error for MbrError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Check whether `os_type` is one of the types used for extended partitions
+/// (CHS, LBA, and Linux).
+const fn is_extended(os_type: MbrOsType) -> bool {
+    matches!(os_type.0, 0x05 | 0x0f | 0x85)
+}
+
+/// Master or extended boot record.
+#[derive(Clone, Copy, Debug)]
+pub struct Mbr {
+    /// Signature identifying the disk. Only meaningful in the MBR.
+    pub disk_signature: u32,
+
+    /// Partition records, including unused ones with an [`os_type`] of zero.
+    ///
+    /// [`os_type`]: MbrPartitionRecord::os_type
+    pub records: [MbrPartitionRecord; 4],
+}
+
+impl Mbr {
+    /// Parse the boot record at the start of `block`.
+    ///
+    /// # Errors
+    ///
+    /// * [`MbrError::InvalidSignature`] if `block` is too small, or does not
+    ///   contain the boot record signature.
+    pub fn parse(block: &[u8]) -> Result<Self, MbrError> {
+        if block.len() < MBR_SIZE || block[510..512] != SIGNATURE {
+            return Err(MbrError::InvalidSignature);
+        }
+
+        let records = core::array::from_fn(|i| {
+            let record = &block[PARTITION_RECORDS_OFFSET + i * 16..][..16];
+            // Safety: the record is large enough, and all bit patterns are
+            // valid.
+            unsafe {
+                record
+                    .as_ptr()
+                    .cast::<MbrPartitionRecord>()
+                    .read_unaligned()
+            }
+        });
+        Ok(Self {
+            disk_signature: u32::from_le_bytes(block[440..444].try_into().unwrap()),
+            records,
+        })
+    }
+
+    /// Read and parse the MBR of `device`.
+    ///
+    /// # Errors
+    ///
+    /// * [`MbrError::Io`] if reading from the device fails.
+    /// * [`MbrError::InvalidSignature`] if block 0 is not a boot record.
+    #[cfg(feature = "alloc")]
+    pub fn read<D: BlockDevice + ?Sized>(device: &mut D) -> Result<Self, MbrError> {
+        Self::read_at(device, 0)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_at<D: BlockDevice + ?Sized>(device: &mut D, lba: Lba) -> Result<Self, MbrError> {
+        let mut block = vec![0; device.block_size() as usize];
+        device.read_blocks(lba, &mut block)?;
+        Self::parse(&block)
+    }
+
+    /// Iterate over the partition records that are in use.
+    pub fn partitions(&self) -> impl Iterator<Item = &MbrPartitionRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.os_type != MbrOsType(0))
+    }
+
+    /// Check whether this is the protective MBR of a GPT disk.
+    #[must_use]
+    pub fn is_protective(&self) -> bool {
+        self.partitions()
+            .any(|record| record.os_type == MbrOsType::GPT_PROTECTIVE)
+    }
+
+    /// Check whether this is the protective MBR of a GPT disk that also
+    /// contains other partitions, for legacy operating systems.
+    #[must_use]
+    pub fn is_hybrid(&self) -> bool {
+        self.is_protective()
+            && self
+                .partitions()
+                .any(|record| record.os_type != MbrOsType::GPT_PROTECTIVE)
+    }
+
+    /// Get the extended partition, if any.
+    #[must_use]
+    pub fn extended_partition(&self) -> Option<&MbrPartitionRecord> {
+        self.partitions().find(|record| is_extended(record.os_type))
+    }
+
+    /// Read the logical partitions in the extended partition, by following
+    /// the chain of extended boot records. The [`starting_lba`] of the
+    /// returned records is relative to the start of the disk.
+    ///
+    /// # Errors
+    ///
+    /// * [`MbrError::Io`] if reading from the device fails.
+    /// * [`MbrError::InvalidSignature`] if an extended boot record is not
+    ///   valid.
+    /// * [`MbrError::InvalidExtendedPartition`] if an extended boot record
+    ///   points outside of the extended partition, or back to an earlier one.
+    ///
+    /// [`starting_lba`]: MbrPartitionRecord::starting_lba
+    #[cfg(feature = "alloc")]
+    pub fn read_logical_partitions<D: BlockDevice + ?Sized>(
+        &self,
+        device: &mut D,
+    ) -> Result<Vec<MbrPartitionRecord>, MbrError> {
+        let mut partitions = Vec::new();
+        let Some(extended) = self.extended_partition() else {
+            return Ok(partitions);
+        };
+        let extended_start = u64::from(extended.starting_lba);
+        let extended_size = u64::from(extended.size_in_lba);
+
+        // Offset of the current EBR from the start of the extended partition.
+        let mut offset = 0;
+        loop {
+            let ebr_lba = extended_start + offset;
+            let ebr = Self::read_at(device, ebr_lba)?;
+
+            // The first record describes the logical partition, relative to
+            // the EBR.
+            let mut partition = ebr.records[0];
+            if partition.os_type != MbrOsType(0) {
+                let start = ebr_lba + u64::from(partition.starting_lba);
+                if start + u64::from(partition.size_in_lba) > extended_start + extended_size {
+                    return Err(MbrError::InvalidExtendedPartition);
+                }
+                partition.starting_lba =
+                    u32::try_from(start).map_err(|_| MbrError::InvalidExtendedPartition)?;
+                partitions.push(partition);
+            }
+
+            // The second record points to the next EBR, relative to the
+            // extended partition. EBRs must be in increasing order, which
+            // also rules out loops.
+            let next = ebr.records[1];
+            if !is_extended(next.os_type) {
+                return Ok(partitions);
+            }
+            let next_offset = u64::from(next.starting_lba);
+            if next_offset <= offset || next_offset >= extended_size {
+                return Err(MbrError::InvalidExtendedPartition);
+            }
+            offset = next_offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(os_type: u8, starting_lba: u32, size_in_lba: u32) -> [u8; 16] {
+        let mut record = [0; 16];
+        record[4] = os_type;
+        record[8..12].copy_from_slice(&starting_lba.to_le_bytes());
+        record[12..16].copy_from_slice(&size_in_lba.to_le_bytes());
+        record
+    }
+
+    fn boot_record(records: &[[u8; 16]]) -> [u8; MBR_SIZE] {
+        let mut block = [0; MBR_SIZE];
+        block[440..444].copy_from_slice(&0x1234_5678u32.to_le_bytes());
+        for (i, record) in records.iter().enumerate() {
+            let offset = PARTITION_RECORDS_OFFSET + i * 16;
+            block[offset..offset + 16].copy_from_slice(record);
+        }
+        block[510..].copy_from_slice(&SIGNATURE);
+        block
+    }
+
+    #[test]
+    fn test_parse_mbr() {
+        let block = boot_record(&[record(0x0c, 2048, 4096), [0; 16], record(0x05, 8192, 100)]);
+        let mbr = Mbr::parse(&block).unwrap();
+        assert_eq!(mbr.disk_signature, 0x1234_5678);
+        assert_eq!(mbr.partitions().count(), 2);
+        assert_eq!({ mbr.records[0].starting_lba }, 2048);
+        assert_eq!({ mbr.records[0].size_in_lba }, 4096);
+        assert!(!mbr.is_protective());
+        assert_eq!({ mbr.extended_partition().unwrap().starting_lba }, 8192);
+
+        assert!(matches!(
+            Mbr::parse(&[0; MBR_SIZE]),
+            Err(MbrError::InvalidSignature)
+        ));
+        assert!(matches!(
+            Mbr::parse(&block[..511]),
+            Err(MbrError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_protective_mbr() {
+        let protective = Mbr::parse(&boot_record(&[record(0xee, 1, u32::MAX)])).unwrap();
+        assert!(protective.is_protective());
+        assert!(!protective.is_hybrid());
+
+        let hybrid = Mbr::parse(&boot_record(&[
+            record(0xee, 1, 2047),
+            record(0x0c, 2048, 4096),
+        ]))
+        .unwrap();
+        assert!(hybrid.is_protective());
+        assert!(hybrid.is_hybrid());
+    }
+
+    #[cfg(feature = "alloc")]
+    mod logical {
+        use super::*;
+        use crate::Result;
+
+        const BLOCK_SIZE: usize = 512;
+
+        struct MemoryDisk(Vec<u8>);
+
+        impl BlockDevice for MemoryDisk {
+            fn block_size(&self) -> u32 {
+                BLOCK_SIZE as u32
+            }
+
+            fn last_block(&self) -> Lba {
+                (self.0.len() / BLOCK_SIZE - 1) as Lba
+            }
+
+            fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+                let start = lba as usize * BLOCK_SIZE;
+                buffer.copy_from_slice(&self.0[start..start + buffer.len()]);
+                Ok(())
+            }
+        }
+
+        fn write_block(disk: &mut MemoryDisk, lba: usize, block: &[u8; MBR_SIZE]) {
+            disk.0[lba * BLOCK_SIZE..(lba + 1) * BLOCK_SIZE].copy_from_slice(block);
+        }
+
+        /// Create a disk with an extended partition at block 10, containing
+        /// logical partitions at blocks 11..15 and 21..25.
+        fn disk() -> MemoryDisk {
+            let mut disk = MemoryDisk(vec![0; 64 * BLOCK_SIZE]);
+            write_block(&mut disk, 0, &boot_record(&[record(0x0f, 10, 40)]));
+            write_block(
+                &mut disk,
+                10,
+                &boot_record(&[record(0x83, 1, 4), record(0x05, 10, 10)]),
+            );
+            write_block(&mut disk, 20, &boot_record(&[record(0x83, 1, 4)]));
+            disk
+        }
+
+        #[test]
+        fn test_logical_partitions() {
+            let mut disk = disk();
+            let mbr = Mbr::read(&mut disk).unwrap();
+            let logical = mbr.read_logical_partitions(&mut disk).unwrap();
+            let starts: Vec<u32> = logical.iter().map(|record| record.starting_lba).collect();
+            assert_eq!(starts, [11, 21]);
+            assert!(logical.iter().all(|record| { record.size_in_lba } == 4));
+        }
+
+        #[test]
+        fn test_logical_partitions_loop() {
+            // Point the second EBR back to the first one.
+            let mut disk = disk();
+            write_block(
+                &mut disk,
+                20,
+                &boot_record(&[record(0x83, 1, 4), record(0x05, 0, 10)]),
+            );
+            let mbr = Mbr::read(&mut disk).unwrap();
+            assert!(matches!(
+                mbr.read_logical_partitions(&mut disk),
+                Err(MbrError::InvalidExtendedPartition)
+            ));
+        }
+    }
+}
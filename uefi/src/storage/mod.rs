@@ -0,0 +1,216 @@
+//! Readers for block devices and their partition tables.
+//!
+//! Firmware normally produces a [`PartitionInfo`] protocol for each partition
+//! it finds. The readers in this module parse the partition table directly
+//! instead, which also works for disks that the firmware did not partition,
+//! or for disk images that are not attached as devices at all. They read
+//! from any [`BlockDevice`], which is implemented by the [`BlockIO`] and
+//! [`BlockIO2`] protocols. The [`BlockIoReader`] reads a [`BlockDevice`] at
+//! byte granularity, for parsing other on-disk structures.
+//!
+//! [`BlockIoReader`]: reader::BlockIoReader
+//! [`BlockIO`]: crate::proto::media::block::BlockIO
+//! [`BlockIO2`]: crate::proto::media::block::BlockIO2
+//! [`PartitionInfo`]: crate::proto::media::partition::PartitionInfo
+
+#[cfg(feature = "alloc")]
+pub mod gpt;
+pub mod mbr;
+#[cfg(feature = "alloc")]
+pub mod reader;
+
+use crate::proto::media::block::{BlockIO, BlockIO2, Lba};
+use crate::Result;
+
+#[cfg(feature = "alloc")]
+use {
+    core::fmt::{self, Display, Formatter},
+    gpt::{Gpt, GptError},
+    mbr::{Mbr, MbrError},
+};
+
+/// A device that can be read in logical blocks.
+pub trait BlockDevice {
+    /// Size of a logical block in bytes.
+    fn block_size(&self) -> u32;
+
+    /// Address of the last logical block of the device.
+    fn last_block(&self) -> Lba;
+
+    /// Read `buffer.len()` bytes, starting at block `lba`. The length of
+    /// `buffer` is a multiple of the block size.
+    fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result;
+}
+
+impl<T: BlockDevice + ?Sized> BlockDevice for &mut T {
+    fn block_size(&self) -> u32 {
+        (**self).block_size()
+    }
+
+    fn last_block(&self) -> Lba {
+        (**self).last_block()
+    }
+
+    fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        (**self).read_blocks(lba, buffer)
+    }
+}
+
+/// Partition table of a block device, read by [`PartitionTable::read`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub enum PartitionTable {
+    /// GUID partition table. The protective MBR is included if the disk has
+    /// one, which for hybrid disks also describes some of the partitions.
+    Gpt {
+        /// The partition table.
+        gpt: Gpt,
+
+        /// The protective MBR.
+        protective_mbr: Option<Mbr>,
+    },
+
+    /// Legacy MBR partition table. Use [`Mbr::read_logical_partitions`] to
+    /// read the partitions in an extended partition.
+    Mbr(Mbr),
+}
+
+#[cfg(feature = "alloc")]
+impl PartitionTable {
+    /// Read the partition table of `device`.
+    ///
+    /// A GPT is read if the MBR is protective, or if there is no MBR at all.
+    /// Otherwise the MBR is returned.
+    ///
+    /// # Errors
+    ///
+    /// * [`PartitionTableError::Io`] if reading from the device fails.
+    /// * [`PartitionTableError::Gpt`] if the MBR is protective, but the GPT
+    ///   is not valid.
+    /// * [`PartitionTableError::NotFound`] if there is neither an MBR nor a
+    ///   GPT.
+    pub fn read<D: BlockDevice + ?Sized>(
+        device: &mut D,
+    ) -> core::result::Result<Self, PartitionTableError> {
+        match Mbr::read(device) {
+            Ok(mbr) if !mbr.is_protective() => Ok(Self::Mbr(mbr)),
+            Ok(mbr) => Ok(Self::Gpt {
+                gpt: Gpt::read(device)?,
+                protective_mbr: Some(mbr),
+            }),
+            Err(MbrError::Io(err)) => Err(PartitionTableError::Io(err)),
+            Err(_) => match Gpt::read(device) {
+                Ok(gpt) => Ok(Self::Gpt {
+                    gpt,
+                    protective_mbr: None,
+                }),
+                Err(GptError::Io(err)) => Err(PartitionTableError::Io(err)),
+                Err(_) => Err(PartitionTableError::NotFound),
+            },
+        }
+    }
+}
+
+/// Error returned by [`PartitionTable::read`].
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub enum PartitionTableError {
+    /// Reading from the device failed.
+    Io(crate::Error),
+
+    /// The MBR is protective, but the GPT is not valid.
+    Gpt(GptError),
+
+    /// The device has neither an MBR nor a GPT.
+    NotFound,
+}
+
+#[cfg(feature = "alloc")]
+impl From<GptError> for PartitionTableError {
+    fn from(err: GptError) -> Self {
+        match err {
+            GptError::Io(err) => Self::Io(err),
+            err => Self::Gpt(err),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for PartitionTableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read from the device: {err}"),
+            Self::Gpt(err) => write!(f, "invalid GPT: {err}"),
+            Self::NotFound => f.write_str("no partition table found"),
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "unstable"))]
+impl core::error::Error for PartitionTableError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Gpt(err) => Some(err),
+            Self::NotFound => None,
+        }
+    }
+}
+
+/// Read blocks with `read`, going through a suitably aligned buffer if
+/// `buffer` does not meet the `io_align` requirement of the device.
+fn read_aligned(
+    io_align: u32,
+    buffer: &mut [u8],
+    read: impl FnOnce(&mut [u8]) -> Result,
+) -> Result {
+    #[cfg(feature = "alloc")]
+    if io_align > 1 && buffer.as_ptr().align_offset(io_align as usize) != 0 {
+        let mut aligned =
+            crate::mem::AlignedBuffer::from_size_align(buffer.len(), io_align as usize)
+                .map_err(|_| crate::Status::INVALID_PARAMETER)?;
+        read(aligned.as_mut_slice())?;
+        buffer.copy_from_slice(aligned.as_slice());
+        return Ok(());
+    }
+    #[cfg(not(feature = "alloc"))]
+    let _ = io_align;
+
+    read(buffer)
+}
+
+impl BlockDevice for BlockIO {
+    fn block_size(&self) -> u32 {
+        self.media().block_size()
+    }
+
+    fn last_block(&self) -> Lba {
+        self.media().last_block()
+    }
+
+    fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        let media = self.media();
+        let media_id = media.media_id();
+        read_aligned(media.io_align(), buffer, |buffer| {
+            Self::read_blocks(self, media_id, lba, buffer)
+        })
+    }
+}
+
+impl BlockDevice for BlockIO2 {
+    fn block_size(&self) -> u32 {
+        self.media().block_size()
+    }
+
+    fn last_block(&self) -> Lba {
+        self.media().last_block()
+    }
+
+    fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+        let media = self.media();
+        let media_id = media.media_id();
+        read_aligned(media.io_align(), buffer, |buffer| {
+            Self::read_blocks(self, media_id, lba, buffer)
+        })
+    }
+}
@@ -0,0 +1,347 @@
+//! Byte-granular reader for block devices.
+
+use super::BlockDevice;
+use crate::proto::media::block::Lba;
+use crate::{Result, Status};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Reader that reads any range of bytes from a [`BlockDevice`].
+///
+/// The device can only be read in whole, aligned blocks. The reader reads
+/// partial blocks at the start and end of a request through an internal
+/// buffer of one block, which also serves repeated small reads from the same
+/// block, and reads the whole blocks in between directly into the caller's
+/// buffer.
+///
+/// Besides [`read_at`], the reader has a position that [`read`] advances,
+/// for reading a device as a stream.
+///
+/// # Example
+///
+/// ```no_run
+/// use uefi::boot;
+/// use uefi::proto::media::block::BlockIO;
+/// use uefi::storage::reader::BlockIoReader;
+/// # fn read_superblock() -> uefi::Result {
+///
+/// let handle = boot::get_handle_for_protocol::<BlockIO>()?;
+/// let mut block_io = boot::open_protocol_exclusive::<BlockIO>(handle)?;
+/// let mut reader = BlockIoReader::new(&mut *block_io);
+///
+/// // The superblock of an ext4 filesystem starts at byte 1024.
+/// let mut superblock = [0; 1024];
+/// reader.read_exact_at(1024, &mut superblock)?;
+/// let magic = u16::from_le_bytes([superblock[56], superblock[57]]);
+/// log::info!("ext4: {}", magic == 0xef53);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`read_at`]: Self::read_at
+/// [`read`]: Self::read
+#[derive(Debug)]
+pub struct BlockIoReader<D: BlockDevice> {
+    device: D,
+    block_size: u64,
+    size: u64,
+    block: Vec<u8>,
+    cached_lba: Option<Lba>,
+    position: u64,
+}
+
+impl<D: BlockDevice> BlockIoReader<D> {
+    /// Create a reader for `device`, positioned at the start of the device.
+    ///
+    /// The size of the device is determined when the reader is created. A
+    /// device without a block size, such as a drive without media, is empty.
+    pub fn new(device: D) -> Self {
+        let block_size = u64::from(device.block_size());
+        let size = if block_size == 0 {
+            0
+        } else {
+            device
+                .last_block()
+                .saturating_add(1)
+                .saturating_mul(block_size)
+        };
+        Self {
+            device,
+            block_size,
+            size,
+            block: vec![0; block_size as usize],
+            cached_lba: None,
+            position: 0,
+        }
+    }
+
+    /// Get the device.
+    #[must_use]
+    pub const fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Get the device mutably. Reads made directly through the device are
+    /// not reflected in the internal buffer.
+    pub fn device_mut(&mut self) -> &mut D {
+        &mut self.device
+    }
+
+    /// Get the device back.
+    #[must_use]
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Size of the device in bytes.
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Position of the next [`read`](Self::read), in bytes from the start
+    /// of the device.
+    #[must_use]
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Set the position of the next [`read`](Self::read). The position can
+    /// be past the end of the device, in which case reads return zero
+    /// bytes.
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Read bytes starting at `offset` into `buffer`, and get the number of
+    /// bytes read. Fewer bytes than requested are only read at the end of
+    /// the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`BlockDevice::read_blocks`].
+    pub fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result<usize> {
+        if offset >= self.size {
+            return Ok(0);
+        }
+        let len = usize::try_from(self.size - offset)
+            .map_or(buffer.len(), |remaining| remaining.min(buffer.len()));
+
+        let mut done = 0;
+        while done < len {
+            let position = offset + done as u64;
+            let lba = position / self.block_size;
+            let start = (position % self.block_size) as usize;
+            let remaining = len - done;
+            let block_size = self.block_size as usize;
+
+            let count = if start == 0 && remaining >= block_size {
+                // Read whole blocks directly into the buffer.
+                let count = remaining - remaining % block_size;
+                self.device
+                    .read_blocks(lba, &mut buffer[done..done + count])?;
+                count
+            } else {
+                let count = (block_size - start).min(remaining);
+                let block = self.read_block(lba)?;
+                buffer[done..done + count].copy_from_slice(&block[start..start + count]);
+                count
+            };
+            done += count;
+        }
+        Ok(len)
+    }
+
+    /// Read exactly `buffer.len()` bytes starting at `offset`.
+    ///
+    /// # Errors
+    ///
+    /// * [`Status::END_OF_MEDIA`]: the range extends past the end of the
+    ///   device. Nothing is read in this case.
+    ///
+    /// Returns the errors of [`BlockDevice::read_blocks`] otherwise.
+    pub fn read_exact_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result {
+        let end = offset.checked_add(buffer.len() as u64);
+        if end.map_or(true, |end| end > self.size) {
+            return Err(Status::END_OF_MEDIA.into());
+        }
+        self.read_at(offset, buffer).map(|_| ())
+    }
+
+    /// Read bytes at the current [`position`] into `buffer`, advance the
+    /// position, and get the number of bytes read. Returns zero at the end
+    /// of the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`BlockDevice::read_blocks`].
+    ///
+    /// [`position`]: Self::position
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let count = self.read_at(self.position, buffer)?;
+        self.position += count as u64;
+        Ok(count)
+    }
+
+    /// Get the block `lba` through the internal buffer.
+    fn read_block(&mut self, lba: Lba) -> Result<&[u8]> {
+        if self.cached_lba != Some(lba) {
+            self.cached_lba = None;
+            self.device.read_blocks(lba, &mut self.block)?;
+            self.cached_lba = Some(lba);
+        }
+        Ok(&self.block)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<D: BlockDevice> embedded_io::ErrorType for BlockIoReader<D> {
+    type Error = crate::Error;
+}
+
+#[cfg(feature = "embedded-io")]
+impl<D: BlockDevice> embedded_io::Read for BlockIoReader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        Self::read(self, buf)
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<D: BlockDevice> embedded_io::Seek for BlockIoReader<D> {
+    fn seek(&mut self, pos: embedded_io::SeekFrom) -> Result<u64> {
+        use embedded_io::SeekFrom;
+
+        let (base, offset) = match pos {
+            SeekFrom::Start(position) => (position, 0),
+            SeekFrom::Current(offset) => (self.position, offset),
+            SeekFrom::End(offset) => (self.size, offset),
+        };
+        self.position = base
+            .checked_add_signed(offset)
+            .ok_or(Status::INVALID_PARAMETER)?;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK_SIZE: usize = 16;
+
+    /// Disk whose bytes are their offset, which counts the blocks read.
+    struct PatternDisk {
+        blocks: usize,
+        reads: usize,
+    }
+
+    impl BlockDevice for PatternDisk {
+        fn block_size(&self) -> u32 {
+            BLOCK_SIZE as u32
+        }
+
+        fn last_block(&self) -> Lba {
+            self.blocks as Lba - 1
+        }
+
+        fn read_blocks(&mut self, lba: Lba, buffer: &mut [u8]) -> Result {
+            assert_eq!(buffer.len() % BLOCK_SIZE, 0);
+            let start = lba as usize * BLOCK_SIZE;
+            assert!(start + buffer.len() <= self.blocks * BLOCK_SIZE);
+            for (i, byte) in buffer.iter_mut().enumerate() {
+                *byte = (start + i) as u8;
+            }
+            self.reads += buffer.len() / BLOCK_SIZE;
+            Ok(())
+        }
+    }
+
+    fn reader() -> BlockIoReader<PatternDisk> {
+        BlockIoReader::new(PatternDisk {
+            blocks: 8,
+            reads: 0,
+        })
+    }
+
+    fn pattern(offset: usize, len: usize) -> Vec<u8> {
+        (offset..offset + len).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn test_read_at() {
+        let mut reader = reader();
+        assert_eq!(reader.size(), 8 * BLOCK_SIZE as u64);
+
+        // Partial blocks at both ends, and whole blocks in between.
+        let mut buffer = [0; 40];
+        assert_eq!(reader.read_at(5, &mut buffer).unwrap(), 40);
+        assert_eq!(buffer[..], pattern(5, 40));
+        assert_eq!(reader.device().reads, 3);
+
+        // Small reads within a block are served from the buffer.
+        let mut buffer = [0; 4];
+        reader.read_at(41, &mut buffer).unwrap();
+        reader.read_at(44, &mut buffer).unwrap();
+        assert_eq!(buffer[..], pattern(44, 4));
+        assert_eq!(reader.device().reads, 3);
+
+        // Reads are truncated at the end of the device.
+        let mut buffer = [0; 32];
+        assert_eq!(reader.read_at(120, &mut buffer).unwrap(), 8);
+        assert_eq!(buffer[..8], pattern(120, 8));
+        assert_eq!(reader.read_at(128, &mut buffer).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_read_exact_at() {
+        let mut reader = reader();
+        let mut buffer = [0; 16];
+        reader.read_exact_at(112, &mut buffer).unwrap();
+        assert_eq!(buffer[..], pattern(112, 16));
+        assert_eq!(
+            reader.read_exact_at(113, &mut buffer).unwrap_err().status(),
+            Status::END_OF_MEDIA
+        );
+        assert_eq!(
+            reader
+                .read_exact_at(u64::MAX, &mut buffer)
+                .unwrap_err()
+                .status(),
+            Status::END_OF_MEDIA
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let mut reader = reader();
+        reader.set_position(100);
+        let mut stream = Vec::new();
+        let mut buffer = [0; 7];
+        loop {
+            let count = reader.read(&mut buffer).unwrap();
+            if count == 0 {
+                break;
+            }
+            stream.extend_from_slice(&buffer[..count]);
+        }
+        assert_eq!(stream, pattern(100, 28));
+        assert_eq!(reader.position(), 128);
+    }
+
+    #[cfg(feature = "embedded-io")]
+    #[test]
+    fn test_embedded_io() {
+        use embedded_io::{Read, Seek, SeekFrom};
+
+        let mut reader = reader();
+        assert_eq!(reader.seek(SeekFrom::End(-20)).unwrap(), 108);
+        assert_eq!(reader.seek(SeekFrom::Current(2)).unwrap(), 110);
+        let mut buffer = [0; 8];
+        Read::read_exact(&mut reader, &mut buffer).unwrap();
+        assert_eq!(buffer[..], pattern(110, 8));
+        assert_eq!(
+            reader.seek(SeekFrom::Current(-200)).unwrap_err().status(),
+            Status::INVALID_PARAMETER
+        );
+    }
+}
@@ -48,12 +48,17 @@ impl Package {
 pub enum Feature {
     // `uefi` features.
     Alloc,
+    Async,
     GlobalAllocator,
+    GlobalPageAllocator,
     LogDebugcon,
     Logger,
     Unstable,
     PanicHandler,
     Qemu,
+    Smoltcp,
+    EmbeddedIo,
+    EmbeddedGraphics,
 
     // `uefi-test-runner` features.
     DebugSupport,
@@ -68,12 +73,17 @@ impl Feature {
     fn as_str(&self) -> &'static str {
         match self {
             Self::Alloc => "alloc",
+            Self::Async => "async",
             Self::GlobalAllocator => "global_allocator",
+            Self::GlobalPageAllocator => "global_page_allocator",
             Self::LogDebugcon => "log-debugcon",
             Self::Logger => "logger",
             Self::Unstable => "unstable",
             Self::PanicHandler => "panic_handler",
             Self::Qemu => "qemu",
+            Self::Smoltcp => "smoltcp",
+            Self::EmbeddedIo => "embedded-io",
+            Self::EmbeddedGraphics => "embedded-graphics",
 
             Self::DebugSupport => "uefi-test-runner/debug_support",
             Self::MultiProcessor => "uefi-test-runner/multi_processor",
@@ -89,12 +99,17 @@ impl Feature {
         match package {
             Package::Uefi => vec![
                 Self::Alloc,
+                Self::Async,
                 Self::GlobalAllocator,
+                Self::GlobalPageAllocator,
                 Self::LogDebugcon,
                 Self::Logger,
                 Self::Unstable,
                 Self::PanicHandler,
                 Self::Qemu,
+                Self::Smoltcp,
+                Self::EmbeddedIo,
+                Self::EmbeddedGraphics,
             ],
             Package::UefiTestRunner => {
                 vec![
@@ -473,6 +473,30 @@ pub fn run_qemu(arch: UefiArch, opt: &QemuOpt) -> Result<()> {
     drive_arg.push(test_disk.clone());
     cmd.arg(drive_arg);
 
+    // Attach an empty disk through an NVMe controller, for the NVMe pass thru
+    // test. The serial number lets the test find the controller.
+    let nvme_disk = tmp_dir.join("test_disk.nvme.img");
+    fs_err::write(&nvme_disk, vec![0; 1024 * 1024])?;
+    cmd.arg("-drive");
+    let mut drive_arg = OsString::from("if=none,id=nvme_disk,format=raw,file=");
+    drive_arg.push(nvme_disk);
+    cmd.arg(drive_arg);
+    cmd.args(["-device", "nvme,drive=nvme_disk,serial=uefi-rs-nvme"]);
+
+    // Attach an empty disk through a virtio SCSI controller, for the SCSI
+    // pass thru test. The product ID lets the test find the disk.
+    let scsi_disk = tmp_dir.join("test_disk.scsi.img");
+    fs_err::write(&scsi_disk, vec![0; 1024 * 1024])?;
+    cmd.arg("-drive");
+    let mut drive_arg = OsString::from("if=none,id=scsi_disk,format=raw,file=");
+    drive_arg.push(scsi_disk);
+    cmd.arg(drive_arg);
+    cmd.args(["-device", "virtio-scsi-pci,id=scsi"]);
+    cmd.args([
+        "-device",
+        "scsi-hd,drive=scsi_disk,bus=scsi.0,product=uefi-rs-scsi",
+    ]);
+
     let qemu_monitor_pipe = Pipe::new(tmp_dir, "qemu-monitor")?;
     let serial_pipe = Pipe::new(tmp_dir, "serial")?;
 